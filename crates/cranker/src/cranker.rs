@@ -0,0 +1,308 @@
+//! Scan loop: find stalled verifications and finish them
+
+use crate::metrics::CrankerMetrics;
+use solana_noir_verifier_sdk::{CrankJob, CrankOutcome, PendingVerification, SolanaNoirVerifier};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Which verifications the cranker takes on
+#[derive(Debug, Clone, Copy)]
+pub struct CrankPolicy {
+    /// How long a state must sit in one phase before it counts as stalled
+    pub stuck_after: Duration,
+    /// Smallest tip, in lamports, a verification must have paid
+    pub min_tip: u64,
+}
+
+/// Why a verification was left alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Skip {
+    /// Its owner may still be sending phases
+    Active,
+    /// It paid less than the minimum tip
+    Tip,
+    /// Its setup transaction was not found
+    UnknownSetup,
+}
+
+/// What the cranker remembers about one state account between scans
+struct Tracked {
+    phase: u8,
+    since: Instant,
+    /// `None` until the state's history is read, `Some(None)` if it had no
+    /// recognizable setup
+    job: Option<Option<CrankJob>>,
+    /// Receipt created or proof rejected; nothing left to send
+    finished: bool,
+}
+
+pub struct Cranker {
+    verifier: SolanaNoirVerifier,
+    payer: Keypair,
+    policy: CrankPolicy,
+    metrics: Arc<CrankerMetrics>,
+    tracked: HashMap<Pubkey, Tracked>,
+}
+
+impl Cranker {
+    pub fn new(
+        verifier: SolanaNoirVerifier,
+        payer: Keypair,
+        policy: CrankPolicy,
+        metrics: Arc<CrankerMetrics>,
+    ) -> Self {
+        Self {
+            verifier,
+            payer,
+            policy,
+            metrics,
+            tracked: HashMap::new(),
+        }
+    }
+
+    /// Look for pending verifications once and crank every stalled one
+    pub fn scan(&mut self) -> anyhow::Result<()> {
+        let pending = self.verifier.find_pending_verifications()?;
+        self.metrics.add("scans_total", None, 1);
+        self.metrics
+            .set("pending_verifications", pending.len() as u64);
+
+        // Forget closed states
+        self.tracked
+            .retain(|state, _| pending.iter().any(|p| p.state_account == *state));
+
+        let now = Instant::now();
+        for verification in &pending {
+            match self.prepare(verification, now) {
+                Ok(job) => self.crank(&job),
+                Err(Skip::Active) => {}
+                Err(reason) => {
+                    log::debug!("Skipping {}: {:?}", verification.state_account, reason);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The job to crank for `verification`, unless the policy says to wait
+    /// or skip it
+    fn prepare(
+        &mut self,
+        verification: &PendingVerification,
+        now: Instant,
+    ) -> Result<CrankJob, Skip> {
+        let tracked = self
+            .tracked
+            .entry(verification.state_account)
+            .or_insert(Tracked {
+                phase: verification.phase,
+                since: now,
+                job: None,
+                finished: false,
+            });
+        if tracked.phase != verification.phase {
+            tracked.phase = verification.phase;
+            tracked.since = now;
+        }
+        if tracked.finished || now.duration_since(tracked.since) < self.policy.stuck_after {
+            return Err(Skip::Active);
+        }
+
+        if tracked.job.is_none() {
+            match self
+                .verifier
+                .find_crank_job(verification, &self.payer.pubkey())
+            {
+                Ok(job) => {
+                    let checked = job
+                        .as_ref()
+                        .ok_or(Skip::UnknownSetup)
+                        .and_then(|job| self.policy.check(job));
+                    if let Err(reason) = checked {
+                        self.metrics
+                            .add("skipped_total", Some(("reason", reason.label())), 1);
+                    }
+                    tracked.job = Some(job);
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Failed to read history of {}: {}",
+                        verification.state_account,
+                        e
+                    );
+                    self.metrics.add("errors_total", None, 1);
+                    return Err(Skip::Active);
+                }
+            }
+        }
+
+        match tracked.job {
+            Some(Some(job)) => self.policy.check(&job).map(|()| job),
+            _ => Err(Skip::UnknownSetup),
+        }
+    }
+
+    /// Send transactions for `job` until it finishes or one fails
+    fn crank(&mut self, job: &CrankJob) {
+        log::info!(
+            "Cranking {} (owner {}, tip {} lamports)",
+            job.state_account,
+            job.owner,
+            job.tip_lamports
+        );
+        let mut sent = false;
+        loop {
+            match self.verifier.advance_verification(&self.payer, job) {
+                Ok(CrankOutcome::Advanced {
+                    phase, signature, ..
+                }) => {
+                    sent = true;
+                    log::info!("{}: {} {}", job.state_account, phase, signature);
+                    self.metrics.add("phases_total", Some(("phase", phase)), 1);
+                }
+                Ok(CrankOutcome::ReceiptCreated { receipt, signature }) => {
+                    sent = true;
+                    log::info!("{}: receipt {} {}", job.state_account, receipt, signature);
+                    self.metrics.add("receipts_total", None, 1);
+                }
+                Ok(CrankOutcome::Finished { verified }) => {
+                    // Count only verifications this cranker sent something for
+                    if sent {
+                        let result = if verified { "verified" } else { "rejected" };
+                        log::info!("{}: {}", job.state_account, result);
+                        self.metrics
+                            .add("verifications_total", Some(("result", result)), 1);
+                        self.metrics
+                            .add("tips_lamports_total", None, job.tip_lamports);
+                    }
+                    if let Some(tracked) = self.tracked.get_mut(&job.state_account) {
+                        tracked.finished = true;
+                    }
+                    return;
+                }
+                // Picked up again on the next scan, once the state is idle
+                Err(e) => {
+                    log::warn!("{}: {}", job.state_account, e);
+                    self.metrics.add("errors_total", None, 1);
+                    if let Some(tracked) = self.tracked.get_mut(&job.state_account) {
+                        tracked.since = Instant::now();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl CrankPolicy {
+    fn check(&self, job: &CrankJob) -> Result<(), Skip> {
+        if job.tip_lamports < self.min_tip {
+            return Err(Skip::Tip);
+        }
+        Ok(())
+    }
+}
+
+impl Skip {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Tip => "tip",
+            Self::UnknownSetup => "unknown_setup",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_client::RpcClient;
+    use solana_noir_verifier_sdk::VerifierConfig;
+
+    fn cranker(policy: CrankPolicy) -> Cranker {
+        // Never contacted: the tests pre-load the crank job
+        let rpc = Arc::new(RpcClient::new("http://127.0.0.1:1".to_string()));
+        let verifier = SolanaNoirVerifier::new(rpc, VerifierConfig::new(Pubkey::new_unique()));
+        Cranker::new(verifier, Keypair::new(), policy, Arc::default())
+    }
+
+    fn job(state_account: Pubkey, tip_lamports: u64) -> CrankJob {
+        CrankJob {
+            state_account,
+            proof_account: Pubkey::new_unique(),
+            vk_account: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            tip_lamports,
+        }
+    }
+
+    #[test]
+    fn test_waits_until_stuck() {
+        let mut cranker = cranker(CrankPolicy {
+            stuck_after: Duration::from_secs(30),
+            min_tip: 0,
+        });
+        let state = Pubkey::new_unique();
+        let mut pending = PendingVerification {
+            state_account: state,
+            vk_account: Pubkey::new_unique(),
+            phase: 3,
+        };
+        let start = Instant::now();
+
+        assert_eq!(cranker.prepare(&pending, start), Err(Skip::Active));
+        cranker.tracked.get_mut(&state).unwrap().job = Some(Some(job(state, 0)));
+        assert_eq!(
+            cranker.prepare(&pending, start + Duration::from_secs(10)),
+            Err(Skip::Active)
+        );
+        assert!(cranker
+            .prepare(&pending, start + Duration::from_secs(31))
+            .is_ok());
+
+        // Progress by the owner restarts the clock
+        pending.phase = 5;
+        assert_eq!(
+            cranker.prepare(&pending, start + Duration::from_secs(40)),
+            Err(Skip::Active)
+        );
+        assert!(cranker
+            .prepare(&pending, start + Duration::from_secs(71))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_skips_small_tips_and_unknown_setups() {
+        let mut cranker = cranker(CrankPolicy {
+            stuck_after: Duration::ZERO,
+            min_tip: 10_000,
+        });
+        let now = Instant::now();
+        for (tip, expected) in [
+            (Some(5_000), Err(Skip::Tip)),
+            (None, Err(Skip::UnknownSetup)),
+        ] {
+            let state = Pubkey::new_unique();
+            cranker.tracked.insert(
+                state,
+                Tracked {
+                    phase: 2,
+                    since: now,
+                    job: Some(tip.map(|tip| job(state, tip))),
+                    finished: false,
+                },
+            );
+            let pending = PendingVerification {
+                state_account: state,
+                vk_account: Pubkey::new_unique(),
+                phase: 2,
+            };
+            assert_eq!(cranker.prepare(&pending, now).map(|_| ()), expected);
+        }
+    }
+}
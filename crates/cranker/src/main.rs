@@ -0,0 +1,132 @@
+//! noir-cranker: finishes stalled phased verifications
+//!
+//! Every phase after Phase 1 can be sent by anyone, so a user only has to
+//! upload the proof and run Phase 1 (`SolanaNoirVerifier::submit_verification`).
+//! The cranker polls the verifier program for state accounts that stopped
+//! advancing, sends their remaining phases and the receipt, and exposes
+//! Prometheus metrics.
+//!
+//! ```bash
+//! noir-cranker --program-id <ID> --keypair cranker.json --min-tip 100000
+//! curl localhost:9464/metrics
+//! ```
+
+mod cranker;
+mod metrics;
+
+use anyhow::Context;
+use clap::Parser;
+use cranker::{CrankPolicy, Cranker};
+use metrics::CrankerMetrics;
+use solana_client::rpc_client::RpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_noir_verifier_sdk::{FeeConfig, SolanaNoirVerifier, VerifierConfig};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signer},
+};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "noir-cranker")]
+#[command(about = "Finish stalled Noir proof verifications on Solana")]
+#[command(version)]
+struct Args {
+    /// RPC endpoint
+    #[arg(long, env = "SOLANA_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    rpc_url: String,
+
+    /// Verifier program ID
+    #[arg(long, env = "VERIFIER_PROGRAM_ID")]
+    program_id: Pubkey,
+
+    /// Keypair paying for phase transactions and receipts, and receiving tips
+    #[arg(long, env = "KEYPAIR_PATH")]
+    keypair: PathBuf,
+
+    /// Seconds between scans
+    #[arg(long, default_value_t = 10)]
+    interval: u64,
+
+    /// Seconds a verification must sit in one phase before it is cranked
+    #[arg(long, default_value_t = 60)]
+    stuck_after: u64,
+
+    /// Only crank verifications that tipped at least this many lamports
+    #[arg(long, default_value_t = 0)]
+    min_tip: u64,
+
+    /// Priority fee in micro-lamports per CU
+    #[arg(long)]
+    priority_fee: Option<u64>,
+
+    /// Address of the Prometheus endpoint
+    #[arg(long, default_value = "127.0.0.1:9464")]
+    metrics_addr: SocketAddr,
+
+    /// Scan once and exit
+    #[arg(long)]
+    once: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let payer = read_keypair_file(&args.keypair)
+        .map_err(|e| anyhow::anyhow!("Failed to read keypair from {:?}: {}", args.keypair, e))?;
+    let metrics = Arc::new(CrankerMetrics::default());
+    let rpc = Arc::new(RpcClient::new_with_commitment(
+        args.rpc_url.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let mut fees = FeeConfig::default().with_estimated_cu_limits();
+    if let Some(price) = args.priority_fee {
+        fees = fees.with_priority_fee(price);
+    }
+    let config = VerifierConfig::new(args.program_id)
+        .with_fees(fees)
+        .with_metrics(metrics.clone());
+    let verifier = SolanaNoirVerifier::new(rpc.clone(), config);
+
+    if !args.once {
+        metrics::serve(args.metrics_addr, metrics.clone())?;
+        log::info!("Serving metrics on http://{}/metrics", args.metrics_addr);
+    }
+    log::info!(
+        "Cranking for program {} as {}",
+        args.program_id,
+        payer.pubkey()
+    );
+
+    let cranker_key = payer.pubkey();
+    let mut cranker = Cranker::new(
+        verifier,
+        payer,
+        CrankPolicy {
+            stuck_after: Duration::from_secs(args.stuck_after),
+            min_tip: args.min_tip,
+        },
+        metrics.clone(),
+    );
+    loop {
+        match rpc.get_balance(&cranker_key) {
+            Ok(balance) => metrics.set("balance_lamports", balance),
+            Err(e) => log::warn!("Failed to fetch balance: {}", e),
+        }
+        let scanned = cranker.scan().context("Scan failed");
+        if args.once {
+            return scanned;
+        }
+        if let Err(e) = scanned {
+            log::warn!("{:#}", e);
+            metrics.add("errors_total", None, 1);
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
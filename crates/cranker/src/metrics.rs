@@ -0,0 +1,165 @@
+//! Prometheus metrics
+//!
+//! Counters are kept in memory and rendered in the text exposition format
+//! on `GET /metrics`. The SDK reports transactions, retries and phase CUs
+//! through [`Metrics`]; the cranker adds its own scan and outcome counters.
+
+use solana_noir_verifier_sdk::Metrics;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Prefix of every metric name
+const NAMESPACE: &str = "noir_cranker";
+
+/// Metric families: name, type, help
+const FAMILIES: &[(&str, &str, &str)] = &[
+    ("scans_total", "counter", "Scans for pending verifications"),
+    (
+        "pending_verifications",
+        "gauge",
+        "Verifications past Phase 1 without a receipt, as of the last scan",
+    ),
+    (
+        "phases_total",
+        "counter",
+        "Phase transactions sent, by phase",
+    ),
+    (
+        "compute_units_total",
+        "counter",
+        "Compute units consumed by phase transactions, by phase",
+    ),
+    ("receipts_total", "counter", "Receipts created"),
+    (
+        "verifications_total",
+        "counter",
+        "Verifications driven to a result, by result",
+    ),
+    (
+        "skipped_total",
+        "counter",
+        "Verifications left alone, by reason",
+    ),
+    ("errors_total", "counter", "Failed crank attempts"),
+    (
+        "tips_lamports_total",
+        "counter",
+        "Tips paid by the verifications cranked",
+    ),
+    ("transactions_total", "counter", "Transactions sent"),
+    ("retries_total", "counter", "Retried steps, by operation"),
+    (
+        "balance_lamports",
+        "gauge",
+        "Balance of the cranker keypair",
+    ),
+];
+
+/// One label pair, or none
+type Label = Option<(&'static str, &'static str)>;
+
+/// In-memory metric values
+#[derive(Default)]
+pub struct CrankerMetrics {
+    series: Mutex<BTreeMap<(&'static str, Label), u64>>,
+}
+
+impl CrankerMetrics {
+    /// Add `value` to a counter
+    pub fn add(&self, name: &'static str, label: Label, value: u64) {
+        *self
+            .series
+            .lock()
+            .unwrap()
+            .entry((name, label))
+            .or_default() += value;
+    }
+
+    /// Set a gauge
+    pub fn set(&self, name: &'static str, value: u64) {
+        self.series.lock().unwrap().insert((name, None), value);
+    }
+
+    /// All values in the Prometheus text format
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut out = String::new();
+        for &(family, kind, help) in FAMILIES {
+            let _ = writeln!(out, "# HELP {NAMESPACE}_{family} {help}");
+            let _ = writeln!(out, "# TYPE {NAMESPACE}_{family} {kind}");
+            for ((_, label), value) in series.iter().filter(|((name, _), _)| *name == family) {
+                let _ = match label {
+                    Some((key, val)) => {
+                        writeln!(out, "{NAMESPACE}_{family}{{{key}=\"{val}\"}} {value}")
+                    }
+                    None => writeln!(out, "{NAMESPACE}_{family} {value}"),
+                };
+            }
+        }
+        out
+    }
+}
+
+impl Metrics for CrankerMetrics {
+    fn transaction_sent(&self) {
+        self.add("transactions_total", None, 1);
+    }
+
+    fn retried(&self, operation: &'static str) {
+        self.add("retries_total", Some(("operation", operation)), 1);
+    }
+
+    fn phase_compute_units(&self, phase: &'static str, units: u64) {
+        self.add("compute_units_total", Some(("phase", phase)), units);
+    }
+}
+
+/// Serve `GET /metrics` on `addr` from a background thread
+pub fn serve(addr: SocketAddr, metrics: Arc<CrankerMetrics>) -> anyhow::Result<()> {
+    let server = tiny_http::Server::http(addr)
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", addr, e))?;
+    let content_type =
+        tiny_http::Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = if request.url() == "/metrics" {
+                tiny_http::Response::from_string(metrics.render()).with_header(content_type.clone())
+            } else {
+                tiny_http::Response::from_string("not found").with_status_code(404)
+            };
+            if let Err(e) = request.respond(response) {
+                log::debug!("Failed to answer metrics request: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = CrankerMetrics::default();
+        metrics.add("scans_total", None, 1);
+        metrics.add("scans_total", None, 1);
+        metrics.phase_compute_units("sumcheck", 1_000);
+        metrics.phase_compute_units("sumcheck", 500);
+        metrics.set("pending_verifications", 3);
+        metrics.set("pending_verifications", 2);
+
+        let text = metrics.render();
+        assert!(
+            text.contains("# TYPE noir_cranker_scans_total counter\nnoir_cranker_scans_total 2\n")
+        );
+        assert!(text.contains("noir_cranker_compute_units_total{phase=\"sumcheck\"} 1500\n"));
+        assert!(text.contains("noir_cranker_pending_verifications 2\n"));
+        // Families without values still get their HELP and TYPE
+        assert!(text.contains("# TYPE noir_cranker_receipts_total counter\n"));
+        assert!(!text.contains("noir_cranker_receipts_total 0"));
+    }
+}
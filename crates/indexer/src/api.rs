@@ -0,0 +1,150 @@
+//! HTTP query routes
+//!
+//! | Route | |
+//! |-------|---|
+//! | `GET /circuits` | Verified and rejected counts and average CUs per VK |
+//! | `GET /circuits/<vk>` | The same for one VK |
+//! | `GET /verifications` | Verifications, newest first; filters `vk`, `outcome`, `payer`, `limit` |
+//! | `GET /health` | Liveness |
+//!
+//! Errors are `{"error": ".."}` with a 4xx or 5xx status.
+
+use crate::db::{Db, Query};
+use serde_json::{json, Value};
+
+/// Verifications returned without a `limit`
+const DEFAULT_LIMIT: u32 = 100;
+/// Largest accepted `limit`
+const MAX_LIMIT: u32 = 1000;
+
+pub struct Api {
+    pub db: Db,
+}
+
+impl Api {
+    /// Answer one request
+    pub fn handle(&self, request: tiny_http::Request) {
+        let (status, response) = self.route(request.method().as_str(), request.url());
+        let content_type =
+            tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = tiny_http::Response::from_string(response.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        if let Err(e) = request.respond(response) {
+            log::debug!("Failed to answer request: {}", e);
+        }
+    }
+
+    /// Status code and JSON body for a request
+    fn route(&self, method: &str, url: &str) -> (u16, Value) {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        if method != "GET" {
+            return (405, error("use GET"));
+        }
+        let result = match path {
+            "/health" => return (200, json!({ "ok": true })),
+            "/circuits" => self.db.circuits(None).map(Value::from),
+            "/verifications" => match parse_query(query) {
+                Ok(query) => self.db.verifications(&query).map(Value::from),
+                Err(e) => return (400, error(e)),
+            },
+            path if path.starts_with("/circuits/") => {
+                match self.db.circuits(Some(&path["/circuits/".len()..])) {
+                    Ok(circuits) => match circuits.into_iter().next() {
+                        Some(circuit) => Ok(circuit),
+                        None => return (404, error("no verifications for this VK")),
+                    },
+                    Err(e) => Err(e),
+                }
+            }
+            _ => return (404, error("not found")),
+        };
+        match result {
+            Ok(body) => (200, body),
+            Err(e) => {
+                log::error!("Query failed: {}", e);
+                (500, error("query failed"))
+            }
+        }
+    }
+}
+
+/// Filters of `GET /verifications`
+fn parse_query(query: &str) -> Result<Query, String> {
+    let mut parsed = Query {
+        limit: DEFAULT_LIMIT,
+        ..Default::default()
+    };
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = value.to_string();
+        match key {
+            "vk" => parsed.vk = Some(value),
+            "payer" => parsed.payer = Some(value),
+            "outcome" if value == "verified" || value == "rejected" => parsed.outcome = Some(value),
+            "outcome" => return Err("outcome: expected verified or rejected".into()),
+            "limit" => {
+                parsed.limit = value
+                    .parse::<u32>()
+                    .map_err(|e| format!("limit: {}", e))?
+                    .min(MAX_LIMIT)
+            }
+            _ => return Err(format!("unknown parameter {}", key)),
+        }
+    }
+    Ok(parsed)
+}
+
+fn error(message: impl Into<String>) -> Value {
+    json!({ "error": message.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::IndexedTransaction;
+    use crate::decode::Event;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_routes() {
+        let mut db = Db::in_memory().unwrap();
+        let (state, vk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let tx = |signature: &str, event| IndexedTransaction {
+            signature: signature.to_string(),
+            slot: 5,
+            block_time: None,
+            fee_payer: Pubkey::new_unique(),
+            compute_units: 100,
+            events: vec![event],
+            pi_hash: None,
+        };
+        db.apply(&tx(
+            "a",
+            Event::Phase {
+                state,
+                vk: Some(vk),
+            },
+        ))
+        .unwrap();
+        db.apply(&tx("b", Event::Rejected { state, code: 500 }))
+            .unwrap();
+        let api = Api { db };
+
+        let (status, body) = api.route("GET", "/circuits");
+        assert_eq!((status, body[0]["rejected"].as_u64()), (200, Some(1)));
+        assert_eq!(api.route("GET", &format!("/circuits/{}", vk)).0, 200);
+        assert_eq!(api.route("GET", "/circuits/unknown").0, 404);
+
+        let (status, body) = api.route("GET", &format!("/verifications?vk={}&limit=5", vk));
+        assert_eq!((status, body.as_array().map(Vec::len)), (200, Some(1)));
+        let (_, body) = api.route("GET", "/verifications?outcome=verified");
+        assert_eq!(body.as_array().map(Vec::len), Some(0));
+
+        assert_eq!(api.route("GET", "/verifications?outcome=maybe").0, 400);
+        assert_eq!(api.route("GET", "/verifications?limit=x").0, 400);
+        assert_eq!(api.route("GET", "/verifications?order=asc").0, 400);
+        assert_eq!(api.route("POST", "/circuits").0, 405);
+        assert_eq!(api.route("GET", "/health").0, 200);
+    }
+}
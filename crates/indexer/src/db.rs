@@ -0,0 +1,346 @@
+//! SQLite storage
+//!
+//! `states` accumulates what is known about each verification while its
+//! phases run; a row in `verifications` is written once the outcome is
+//! known. Every transaction is applied together with the cursor, so a
+//! restart resumes after the last transaction stored.
+
+use crate::decode::Event;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursor (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    signature TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS states (
+    state TEXT PRIMARY KEY,
+    vk TEXT,
+    compute_units INTEGER NOT NULL DEFAULT 0,
+    transactions INTEGER NOT NULL DEFAULT 0,
+    first_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS verifications (
+    state TEXT PRIMARY KEY,
+    vk TEXT,
+    outcome TEXT NOT NULL CHECK (outcome IN ('verified', 'rejected')),
+    error_code INTEGER,
+    receipt TEXT,
+    pi_hash TEXT,
+    payer TEXT NOT NULL,
+    compute_units INTEGER NOT NULL,
+    transactions INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    signature TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS verifications_vk ON verifications (vk, slot);
+";
+
+/// A verifier transaction, as stored
+#[derive(Debug, Clone)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub fee_payer: Pubkey,
+    pub compute_units: u64,
+    pub events: Vec<Event>,
+    /// Public inputs hash of the receipt created, if it could be read
+    pub pi_hash: Option<[u8; 32]>,
+}
+
+/// Filters of [`Db::verifications`]
+#[derive(Debug, Default)]
+pub struct Query {
+    pub vk: Option<String>,
+    pub outcome: Option<String>,
+    pub payer: Option<String>,
+    pub limit: u32,
+}
+
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    /// Open or create the database at `path`
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        // Lets the API read while the indexer writes
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Signature of the last transaction stored
+    pub fn cursor(&self) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row("SELECT signature FROM cursor WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()
+    }
+
+    /// Store one transaction and move the cursor past it
+    ///
+    /// The transaction's compute units are counted for the state of its
+    /// first event.
+    pub fn apply(&mut self, tx: &IndexedTransaction) -> rusqlite::Result<()> {
+        let db = self.conn.transaction()?;
+        if let Some(first) = tx.events.first() {
+            let state = state_of(first).to_string();
+            db.execute(
+                "INSERT INTO states (state, first_slot) VALUES (?1, ?2)
+                 ON CONFLICT (state) DO NOTHING",
+                params![state, tx.slot],
+            )?;
+            db.execute(
+                "UPDATE states SET compute_units = compute_units + ?2,
+                    transactions = transactions + 1 WHERE state = ?1",
+                params![state, tx.compute_units],
+            )?;
+        }
+
+        for event in &tx.events {
+            let state = state_of(event).to_string();
+            match *event {
+                Event::Phase { vk: Some(vk), .. } => {
+                    db.execute(
+                        "UPDATE states SET vk = ?2 WHERE state = ?1",
+                        params![state, vk.to_string()],
+                    )?;
+                }
+                Event::Phase { vk: None, .. } => {}
+                Event::Verified {
+                    vk, receipt, payer, ..
+                } => {
+                    db.execute(
+                        "INSERT OR REPLACE INTO verifications (state, vk, outcome, receipt,
+                            pi_hash, payer, compute_units, transactions, slot, block_time,
+                            signature)
+                         SELECT ?1, ?2, 'verified', ?3, ?4, ?5, compute_units, transactions,
+                            ?6, ?7, ?8
+                         FROM states WHERE state = ?1",
+                        params![
+                            state,
+                            vk.to_string(),
+                            receipt.to_string(),
+                            tx.pi_hash.map(hex::encode),
+                            payer.to_string(),
+                            tx.slot,
+                            tx.block_time,
+                            tx.signature,
+                        ],
+                    )?;
+                }
+                Event::Rejected { code, .. } => {
+                    db.execute(
+                        "INSERT OR REPLACE INTO verifications (state, vk, outcome, error_code,
+                            payer, compute_units, transactions, slot, block_time, signature)
+                         SELECT ?1, vk, 'rejected', ?2, ?3, compute_units, transactions,
+                            ?4, ?5, ?6
+                         FROM states WHERE state = ?1",
+                        params![
+                            state,
+                            code,
+                            tx.fee_payer.to_string(),
+                            tx.slot,
+                            tx.block_time,
+                            tx.signature,
+                        ],
+                    )?;
+                }
+            }
+        }
+
+        db.execute(
+            "INSERT OR REPLACE INTO cursor (id, signature) VALUES (0, ?1)",
+            params![tx.signature],
+        )?;
+        db.commit()
+    }
+
+    /// Verification counts and cost per VK, most recently used first
+    pub fn circuits(&self, vk: Option<&str>) -> rusqlite::Result<Vec<Value>> {
+        let mut statement = self.conn.prepare(
+            "SELECT vk,
+                SUM(outcome = 'verified'),
+                SUM(outcome = 'rejected'),
+                AVG(CASE WHEN outcome = 'verified' THEN compute_units END),
+                MAX(slot)
+             FROM verifications
+             WHERE vk IS NOT NULL AND (?1 IS NULL OR vk = ?1)
+             GROUP BY vk ORDER BY MAX(slot) DESC",
+        )?;
+        let rows = statement.query_map(params![vk], |row| {
+            Ok(json!({
+                "vk": row.get::<_, String>(0)?,
+                "verified": row.get::<_, u64>(1)?,
+                "rejected": row.get::<_, u64>(2)?,
+                "average_compute_units": row.get::<_, Option<f64>>(3)?.map(|cus| cus.round() as u64),
+                "last_slot": row.get::<_, u64>(4)?,
+            }))
+        })?;
+        rows.collect()
+    }
+
+    /// Verifications matching `query`, newest first
+    pub fn verifications(&self, query: &Query) -> rusqlite::Result<Vec<Value>> {
+        let mut statement = self.conn.prepare(
+            "SELECT state, vk, outcome, error_code, receipt, pi_hash, payer, compute_units,
+                transactions, slot, block_time, signature
+             FROM verifications
+             WHERE (?1 IS NULL OR vk = ?1) AND (?2 IS NULL OR outcome = ?2)
+                AND (?3 IS NULL OR payer = ?3)
+             ORDER BY slot DESC LIMIT ?4",
+        )?;
+        let rows = statement.query_map(
+            params![query.vk, query.outcome, query.payer, query.limit],
+            |row| {
+                Ok(json!({
+                    "state_account": row.get::<_, String>(0)?,
+                    "vk": row.get::<_, Option<String>>(1)?,
+                    "outcome": row.get::<_, String>(2)?,
+                    "error_code": row.get::<_, Option<u32>>(3)?,
+                    "receipt": row.get::<_, Option<String>>(4)?,
+                    "pi_hash": row.get::<_, Option<String>>(5)?,
+                    "payer": row.get::<_, String>(6)?,
+                    "compute_units": row.get::<_, u64>(7)?,
+                    "transactions": row.get::<_, u64>(8)?,
+                    "slot": row.get::<_, u64>(9)?,
+                    "block_time": row.get::<_, Option<i64>>(10)?,
+                    "signature": row.get::<_, String>(11)?,
+                }))
+            },
+        )?;
+        rows.collect()
+    }
+}
+
+fn state_of(event: &Event) -> Pubkey {
+    match *event {
+        Event::Phase { state, .. }
+        | Event::Verified { state, .. }
+        | Event::Rejected { state, .. } => state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(
+        signature: &str,
+        slot: u64,
+        compute_units: u64,
+        events: Vec<Event>,
+    ) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot,
+            block_time: Some(1_700_000_000),
+            fee_payer: Pubkey::new_unique(),
+            compute_units,
+            events,
+            pi_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_verified_and_rejected() {
+        let mut db = Db::in_memory().unwrap();
+        assert_eq!(db.cursor().unwrap(), None);
+
+        let (vk, verified, rejected) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (receipt, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let phase1 = |state| Event::Phase {
+            state,
+            vk: Some(vk),
+        };
+
+        db.apply(&tx("a", 10, 1_000, vec![phase1(verified)]))
+            .unwrap();
+        db.apply(&tx("b", 11, 2_000, vec![phase1(rejected)]))
+            .unwrap();
+        db.apply(&tx(
+            "c",
+            12,
+            500,
+            vec![Event::Rejected {
+                state: rejected,
+                code: 504,
+            }],
+        ))
+        .unwrap();
+        let mut last = tx(
+            "d",
+            13,
+            300,
+            vec![
+                Event::Phase {
+                    state: verified,
+                    vk: Some(vk),
+                },
+                Event::Verified {
+                    state: verified,
+                    vk,
+                    receipt,
+                    payer,
+                },
+            ],
+        );
+        last.pi_hash = Some([7; 32]);
+        db.apply(&last).unwrap();
+        assert_eq!(db.cursor().unwrap().as_deref(), Some("d"));
+
+        let rows = db.verifications(&Query {
+            limit: 10,
+            ..Default::default()
+        });
+        let rows = rows.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["outcome"], "verified");
+        assert_eq!(rows[0]["compute_units"], 1_300);
+        assert_eq!(rows[0]["transactions"], 2);
+        assert_eq!(rows[0]["payer"], payer.to_string());
+        assert_eq!(rows[0]["pi_hash"], "07".repeat(32));
+        assert_eq!(rows[1]["outcome"], "rejected");
+        assert_eq!(rows[1]["error_code"], 504);
+        assert_eq!(rows[1]["vk"], vk.to_string());
+        assert_eq!(rows[1]["compute_units"], 2_500);
+
+        let circuits = db.circuits(Some(&vk.to_string())).unwrap();
+        assert_eq!(circuits.len(), 1);
+        assert_eq!(circuits[0]["verified"], 1);
+        assert_eq!(circuits[0]["rejected"], 1);
+        assert_eq!(circuits[0]["average_compute_units"], 1_300);
+        assert_eq!(circuits[0]["last_slot"], 13);
+
+        let rejected_only = db
+            .verifications(&Query {
+                outcome: Some("rejected".into()),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(rejected_only.len(), 1);
+    }
+}
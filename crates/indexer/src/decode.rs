@@ -0,0 +1,232 @@
+//! What a verifier transaction means for the index
+//!
+//! The program emits no structured events, so transactions are read from
+//! their instructions. Phase instructions name the state account first;
+//! Phase 1, Phase 3c and the final check also carry the VK. A receipt is
+//! created only for a verified proof. A rejected proof fails its phase
+//! transaction with one of the proof-rejection error codes, rolling back the
+//! state, so rejections are read from the transaction error.
+
+use solana_noir_verifier_sdk::*;
+use solana_sdk::{
+    instruction::InstructionError, message::compiled_instruction::CompiledInstruction,
+    pubkey::Pubkey, transaction::TransactionError,
+};
+
+/// One fact from a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A phase transaction ran for `state`
+    Phase {
+        state: Pubkey,
+        /// Set by instructions that carry the VK
+        vk: Option<Pubkey>,
+    },
+    /// A receipt was created for the verified proof in `state`
+    Verified {
+        state: Pubkey,
+        vk: Pubkey,
+        receipt: Pubkey,
+        payer: Pubkey,
+    },
+    /// The program rejected the proof in `state`
+    Rejected { state: Pubkey, code: u32 },
+}
+
+/// Events of one transaction
+///
+/// `keys` are all account keys of the message, loaded ones included.
+/// Failed transactions yield only a rejection, if they are one.
+pub fn decode(
+    keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+    err: Option<&TransactionError>,
+    program_id: &Pubkey,
+) -> Vec<Event> {
+    let account = |ix: &CompiledInstruction, i: usize| {
+        ix.accounts
+            .get(i)
+            .and_then(|&index| keys.get(index as usize))
+            .copied()
+    };
+    let verifier_ix = |ix: &&CompiledInstruction| {
+        keys.get(ix.program_id_index as usize) == Some(program_id) && !ix.data.is_empty()
+    };
+
+    if let Some(err) = err {
+        let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+            return Vec::new();
+        };
+        let rejection = ProgramErrorCode::from_code(*code).is_some_and(|c| c.is_proof_rejection());
+        let state = instructions
+            .get(*index as usize)
+            .filter(verifier_ix)
+            .filter(|ix| is_phase(ix.data[0]))
+            .and_then(|ix| account(ix, 0));
+        return match state {
+            Some(state) if rejection => vec![Event::Rejected { state, code: *code }],
+            _ => Vec::new(),
+        };
+    }
+
+    instructions
+        .iter()
+        .filter(verifier_ix)
+        .filter_map(|ix| {
+            let tag = ix.data[0];
+            if tag == IX_CREATE_RECEIPT {
+                return Some(Event::Verified {
+                    state: account(ix, 0)?,
+                    vk: account(ix, 2)?,
+                    receipt: account(ix, 3)?,
+                    payer: account(ix, 4)?,
+                });
+            }
+            if !is_phase(tag) {
+                return None;
+            }
+            let vk = match tag {
+                IX_PHASE1_FULL | IX_PHASE3C_AND_PAIRING => account(ix, 2),
+                IX_FINAL_CHECK => account(ix, 1),
+                _ => None,
+            };
+            Some(Event::Phase {
+                state: account(ix, 0)?,
+                vk,
+            })
+        })
+        .collect()
+}
+
+/// Whether `tag` is a phase instruction, whose first account is the state
+fn is_phase(tag: u8) -> bool {
+    matches!(
+        tag,
+        IX_PHASE1_FULL
+            | IX_PHASE2_ROUNDS
+            | IX_PHASE2C_RELATION_COEFFICIENTS
+            | IX_PHASE2D_RELATIONS
+            | IX_PHASE2D_RELATIONS_PART1
+            | IX_PHASE2D_RELATIONS_PART2
+            | IX_PHASE3A_WEIGHTS
+            | IX_PHASE3B1_FOLDING
+            | IX_PHASE3B2_GEMINI
+            | IX_PHASE3C_AND_PAIRING
+            | IX_PHASE2D_AND_3A
+            | IX_PHASE3B_COMBINED
+            | IX_FINAL_CHECK
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::message::Message;
+
+    struct Accounts {
+        program: Pubkey,
+        payer: Pubkey,
+        state: Pubkey,
+        proof: Pubkey,
+        vk: Pubkey,
+    }
+
+    fn accounts() -> Accounts {
+        Accounts {
+            program: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            state: Pubkey::new_unique(),
+            proof: Pubkey::new_unique(),
+            vk: Pubkey::new_unique(),
+        }
+    }
+
+    fn events(
+        a: &Accounts,
+        ixs: &[solana_sdk::instruction::Instruction],
+        err: Option<TransactionError>,
+    ) -> Vec<Event> {
+        let message = Message::new(ixs, Some(&a.payer));
+        decode(
+            &message.account_keys,
+            &message.instructions,
+            err.as_ref(),
+            &a.program,
+        )
+    }
+
+    #[test]
+    fn test_decode_phases_and_receipt() {
+        let a = accounts();
+        assert_eq!(
+            events(
+                &a,
+                &[phase1_full(&a.program, &a.state, &a.proof, &a.vk)],
+                None
+            ),
+            vec![Event::Phase {
+                state: a.state,
+                vk: Some(a.vk)
+            }]
+        );
+        assert_eq!(
+            events(
+                &a,
+                &[phase2_rounds(&a.program, &a.state, &a.proof, 0, 6)],
+                None
+            ),
+            vec![Event::Phase {
+                state: a.state,
+                vk: None
+            }]
+        );
+
+        let receipt = Pubkey::new_unique();
+        let ix = create_receipt(&a.program, &a.state, &a.proof, &a.vk, &receipt, &a.payer);
+        assert_eq!(
+            events(&a, &[ix], None),
+            vec![Event::Verified {
+                state: a.state,
+                vk: a.vk,
+                receipt,
+                payer: a.payer
+            }]
+        );
+
+        // Other programs' instructions and non-phase instructions are ignored
+        let other = accounts();
+        assert!(events(
+            &a,
+            &[
+                phase1_full(&other.program, &a.state, &a.proof, &a.vk),
+                init_buffer(&a.program, &a.proof, 1),
+            ],
+            None
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_decode_rejection() {
+        let a = accounts();
+        let ixs = [phase3c_and_pairing(&a.program, &a.state, &a.proof, &a.vk)];
+        let failed = |code| {
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(code),
+            ))
+        };
+
+        assert_eq!(
+            events(&a, &ixs, failed(504)),
+            vec![Event::Rejected {
+                state: a.state,
+                code: 504
+            }]
+        );
+        // A phase sent twice fails too, but proves nothing about the proof
+        let invalid_phase = ProgramErrorCode::InvalidPhase.code();
+        assert!(events(&a, &ixs, failed(invalid_phase)).is_empty());
+        assert!(events(&a, &ixs, Some(TransactionError::BlockhashNotFound)).is_empty());
+    }
+}
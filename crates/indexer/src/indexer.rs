@@ -0,0 +1,125 @@
+//! Polling the verifier program's transaction history
+
+use crate::db::{Db, IndexedTransaction};
+use crate::decode::{decode, Event};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_noir_verifier_sdk::RECEIPT_PI_HASH_OFFSET;
+use solana_rpc_client_api::{
+    config::{RpcTransactionConfig, UiTransactionEncoding},
+    response::UiLoadedAddresses,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::TransactionError};
+use std::str::FromStr;
+
+/// Largest page of `getSignaturesForAddress`
+const PAGE_SIZE: usize = 1000;
+
+pub struct Indexer {
+    pub rpc: RpcClient,
+    pub program_id: Pubkey,
+    pub db: Db,
+    /// Most recent transactions read when the database is empty
+    pub backfill_limit: usize,
+}
+
+impl Indexer {
+    /// Store every verifier transaction since the cursor, oldest first
+    ///
+    /// Returns how many transactions were stored.
+    pub fn poll(&mut self) -> anyhow::Result<usize> {
+        let cursor = self.db.cursor()?;
+        let until = cursor.as_deref().map(Signature::from_str).transpose()?;
+
+        // Newest first, paging back to the cursor
+        let mut pending = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.rpc.get_signatures_for_address_with_config(
+                &self.program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(PAGE_SIZE),
+                    commitment: None,
+                },
+            )?;
+            let full = page.len() == PAGE_SIZE;
+            before = match page.last() {
+                Some(last) => Some(Signature::from_str(&last.signature)?),
+                None => None,
+            };
+            pending.extend(page);
+            if cursor.is_none() && pending.len() >= self.backfill_limit {
+                pending.truncate(self.backfill_limit);
+                break;
+            }
+            if !full {
+                break;
+            }
+        }
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: None,
+            max_supported_transaction_version: Some(0),
+        };
+        let count = pending.len();
+        for status in pending.into_iter().rev() {
+            let signature = Signature::from_str(&status.signature)?;
+            let tx = self.rpc.get_transaction_with_config(&signature, config)?;
+            let meta = tx.transaction.meta;
+            let Some(decoded) = tx.transaction.transaction.decode() else {
+                log::warn!("Skipping {}: cannot decode transaction", signature);
+                continue;
+            };
+
+            let mut keys = decoded.message.static_account_keys().to_vec();
+            let mut compute_units = 0;
+            let mut err = None;
+            if let Some(meta) = meta {
+                let loaded: Option<UiLoadedAddresses> = meta.loaded_addresses.into();
+                if let Some(loaded) = loaded {
+                    for key in loaded.writable.iter().chain(&loaded.readonly) {
+                        keys.push(Pubkey::from_str(key)?);
+                    }
+                }
+                compute_units = Option::from(meta.compute_units_consumed).unwrap_or(0);
+                err = meta.err.map(TransactionError::from);
+            }
+            let events = decode(
+                &keys,
+                decoded.message.instructions(),
+                err.as_ref(),
+                &self.program_id,
+            );
+
+            let pi_hash = events
+                .iter()
+                .find_map(|event| match event {
+                    Event::Verified { receipt, .. } => Some(*receipt),
+                    _ => None,
+                })
+                .and_then(|receipt| self.pi_hash(&receipt));
+            self.db.apply(&IndexedTransaction {
+                signature: status.signature,
+                slot: status.slot,
+                block_time: status.block_time,
+                fee_payer: keys[0],
+                compute_units,
+                events,
+                pi_hash,
+            })?;
+        }
+        Ok(count)
+    }
+
+    /// Public inputs hash of a receipt, unless it was closed since
+    fn pi_hash(&self, receipt: &Pubkey) -> Option<[u8; 32]> {
+        let account = self.rpc.get_account(receipt).ok()?;
+        account
+            .data
+            .get(RECEIPT_PI_HASH_OFFSET..RECEIPT_PI_HASH_OFFSET + 32)?
+            .try_into()
+            .ok()
+    }
+}
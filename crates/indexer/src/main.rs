@@ -0,0 +1,95 @@
+//! noir-indexer: index Noir proof verifications on Solana into SQLite
+//!
+//! Polls the verifier program's transactions, records each verification's
+//! VK, public inputs hash, payer, outcome and compute units, and serves
+//! them over a small HTTP API.
+//!
+//! ```bash
+//! noir-indexer --program-id <ID> --db verifications.db
+//! curl localhost:8090/circuits
+//! curl 'localhost:8090/verifications?vk=<VK>&outcome=rejected'
+//! ```
+
+mod api;
+mod db;
+mod decode;
+mod indexer;
+
+use clap::Parser;
+use solana_client::rpc_client::RpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "noir-indexer")]
+#[command(about = "Index Noir proof verifications on Solana into SQLite")]
+#[command(version)]
+struct Args {
+    /// RPC endpoint
+    #[arg(long, env = "SOLANA_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    rpc_url: String,
+
+    /// Verifier program ID
+    #[arg(long, env = "VERIFIER_PROGRAM_ID")]
+    program_id: Pubkey,
+
+    /// SQLite database, created if missing
+    #[arg(long, default_value = "verifications.db")]
+    db: PathBuf,
+
+    /// Address to serve the query API on
+    #[arg(long, default_value = "127.0.0.1:8090")]
+    listen: SocketAddr,
+
+    /// Seconds between polls
+    #[arg(long, default_value_t = 10)]
+    interval: u64,
+
+    /// Most recent program transactions read into an empty database
+    #[arg(long, default_value_t = 10_000)]
+    backfill_limit: usize,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    // Finalized, so rolled-back forks never reach the database
+    let rpc = RpcClient::new_with_commitment(args.rpc_url.clone(), CommitmentConfig::finalized());
+    let mut indexer = indexer::Indexer {
+        rpc,
+        program_id: args.program_id,
+        db: db::Db::open(&args.db)?,
+        backfill_limit: args.backfill_limit,
+    };
+
+    let server = tiny_http::Server::http(args.listen)
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", args.listen, e))?;
+    let api = api::Api {
+        db: db::Db::open(&args.db)?,
+    };
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            api.handle(request);
+        }
+    });
+    log::info!(
+        "Indexing {} into {:?}, serving on http://{}",
+        args.program_id,
+        args.db,
+        args.listen
+    );
+
+    loop {
+        match indexer.poll() {
+            Ok(0) => {}
+            Ok(count) => log::info!("Indexed {} transactions", count),
+            Err(e) => log::warn!("Poll failed: {}", e),
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
@@ -0,0 +1,315 @@
+//! Pairing claim accumulation for recursive proofs
+//!
+//! A verified UltraHonk proof reduces to a pairing claim `(P0, P1)` such that
+//! `e(P0, [1]₂) · e(P1, [x]₂) == 1`. Proofs produced by recursive circuits also
+//! carry an *accumulated* claim from the proofs they verified in-circuit: the
+//! 16-limb pairing point object at the start of the proof.
+//!
+//! Instead of running one pairing per claim, claims are folded together with
+//! a random linear combination:
+//!
+//! ```text
+//! sep  = keccak256(acc.lhs || acc.rhs || p0 || p1) mod r
+//! acc' = (acc.lhs + sep·p0, acc.rhs + sep·p1)
+//! ```
+//!
+//! The separator is derived from both claims, so an adversary cannot choose
+//! one claim to cancel the other. A single pairing check on the final
+//! accumulator then covers every absorbed claim.
+//!
+//! ```ignore
+//! let mut acc = Accumulator::from_pairing_point_object(&proof.pairing_point_object())?;
+//! acc.absorb(&p0, &p1)?;
+//! assert!(acc.finalize()?);
+//! ```
+
+use crate::errors::VerifyError;
+use crate::ops;
+use crate::types::{Fr, G1};
+use crate::verifier::{default_srs_g2, g2_generator};
+
+/// Running pairing claim built from one or more `(P0, P1)` pairs
+#[derive(Debug, Clone, Default)]
+pub struct Accumulator {
+    /// `None` until the first claim is absorbed
+    claim: Option<(G1, G1)>,
+}
+
+impl Accumulator {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self { claim: None }
+    }
+
+    /// Start from the accumulated claim carried in a proof's pairing point object
+    pub fn from_pairing_point_object(ppo: &[Fr]) -> Result<Self, VerifyError> {
+        let (lhs, rhs) = convert_pairing_points_to_g1(ppo)?;
+        Ok(Self {
+            claim: Some((lhs, rhs)),
+        })
+    }
+
+    /// Whether no claim has been absorbed yet
+    pub fn is_empty(&self) -> bool {
+        self.claim.is_none()
+    }
+
+    /// Current accumulated `(P0, P1)`, if any
+    pub fn claim(&self) -> Option<(G1, G1)> {
+        self.claim
+    }
+
+    /// Fold a pairing claim `(p0, p1)` into the accumulator
+    ///
+    /// The first claim is taken as-is; later claims are combined with a
+    /// recursion separator (see module docs). Costs two G1 mul + two G1 add.
+    pub fn absorb(&mut self, p0: &G1, p1: &G1) -> Result<(), VerifyError> {
+        let (acc_lhs, acc_rhs) = match self.claim {
+            None => {
+                self.claim = Some((*p0, *p1));
+                return Ok(());
+            }
+            Some(claim) => claim,
+        };
+
+        let separator = generate_recursion_separator(&acc_lhs, &acc_rhs, p0, p1);
+
+        let lhs = ops::g1_add(&acc_lhs, &ops::g1_mul(p0, &separator)?)?;
+        let rhs = ops::g1_add(&acc_rhs, &ops::g1_mul(p1, &separator)?)?;
+
+        #[cfg(feature = "debug")]
+        {
+            crate::dbg_fr!("recursion separator", &separator);
+            crate::dbg_g1!("accumulated P0", &lhs);
+            crate::dbg_g1!("accumulated P1", &rhs);
+        }
+
+        self.claim = Some((lhs, rhs));
+        Ok(())
+    }
+
+    /// Run the single pairing check `e(P0, [1]₂) · e(P1, [x]₂) == 1`
+    ///
+    /// Returns `Ok(false)` if the check fails and an error if nothing was
+    /// absorbed (an empty accumulator proves nothing).
+    pub fn finalize(self) -> Result<bool, VerifyError> {
+        let (p0, p1) = self.claim.ok_or(VerifyError::VerificationFailed)?;
+        Ok(ops::pairing_check(&[
+            (p0, g2_generator()),
+            (p1, default_srs_g2()),
+        ])?)
+    }
+}
+
+/// Convert pairing point object (16 Fr limbs) to two G1 points
+///
+/// The pairing points are serialized as 68-bit limbs (4 limbs per 256-bit coordinate)
+/// - lhs.x = limbs[0] | limbs[1] << 68 | limbs[2] << 136 | limbs[3] << 204
+/// - lhs.y = limbs[4..7]
+/// - rhs.x = limbs[8..11]
+/// - rhs.y = limbs[12..15]
+pub fn convert_pairing_points_to_g1(ppo: &[Fr]) -> Result<(G1, G1), VerifyError> {
+    if ppo.len() != 16 {
+        return Err(VerifyError::PublicInput(alloc::format!(
+            "Expected 16 pairing point limbs, got {}",
+            ppo.len()
+        )));
+    }
+
+    // Helper to combine 4 68-bit limbs into a 256-bit value
+    // Fr values are big-endian 32-byte arrays, but limbs are small values (fit in ~68 bits)
+    fn combine_limbs(limbs: &[Fr]) -> [u8; 32] {
+        // Each limb is 68 bits. We combine them:
+        // val = limbs[0] | (limbs[1] << 68) | (limbs[2] << 136) | (limbs[3] << 204)
+
+        // Since Fr is big-endian, convert to little-endian for easier bit manipulation
+        let limb0 = fr_to_le(&limbs[0]);
+        let limb1 = fr_to_le(&limbs[1]);
+        let limb2 = fr_to_le(&limbs[2]);
+        let limb3 = fr_to_le(&limbs[3]);
+
+        // Combine using bit shifts (working in little-endian)
+        let mut combined = limb0;
+        combined = add_256_le(&combined, &shift_left_256_le(&limb1, 68));
+        combined = add_256_le(&combined, &shift_left_256_le(&limb2, 136));
+        combined = add_256_le(&combined, &shift_left_256_le(&limb3, 204));
+
+        // Convert back to big-endian for the result
+        le_to_be(&combined)
+    }
+
+    // Convert Fr (big-endian) to little-endian
+    fn fr_to_le(fr: &Fr) -> [u8; 32] {
+        let mut le = [0u8; 32];
+        for i in 0..32 {
+            le[i] = fr[31 - i];
+        }
+        le
+    }
+
+    // Convert little-endian to big-endian
+    fn le_to_be(le: &[u8; 32]) -> [u8; 32] {
+        let mut be = [0u8; 32];
+        for i in 0..32 {
+            be[i] = le[31 - i];
+        }
+        be
+    }
+
+    // Shift left in little-endian representation
+    #[allow(clippy::needless_range_loop)]
+    fn shift_left_256_le(val: &[u8; 32], bits: usize) -> [u8; 32] {
+        let mut result = [0u8; 32];
+        let byte_shift = bits / 8;
+        let bit_shift = bits % 8;
+
+        if byte_shift >= 32 {
+            return result;
+        }
+
+        for i in byte_shift..32 {
+            let src_idx = i - byte_shift;
+            result[i] = val[src_idx] << bit_shift;
+            if bit_shift > 0 && src_idx > 0 {
+                result[i] |= val[src_idx - 1] >> (8 - bit_shift);
+            }
+        }
+
+        result
+    }
+
+    // Add two 256-bit values in little-endian
+    fn add_256_le(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let mut result = [0u8; 32];
+        let mut carry: u16 = 0;
+
+        for i in 0..32 {
+            let sum = a[i] as u16 + b[i] as u16 + carry;
+            result[i] = sum as u8;
+            carry = sum >> 8;
+        }
+
+        result
+    }
+
+    // Extract coordinates
+    let lhs_x = combine_limbs(&ppo[0..4]);
+    let lhs_y = combine_limbs(&ppo[4..8]);
+    let rhs_x = combine_limbs(&ppo[8..12]);
+    let rhs_y = combine_limbs(&ppo[12..16]);
+
+    // Create G1 points (64 bytes each: x || y)
+    let mut lhs = [0u8; 64];
+    lhs[0..32].copy_from_slice(&lhs_x);
+    lhs[32..64].copy_from_slice(&lhs_y);
+
+    let mut rhs = [0u8; 64];
+    rhs[0..32].copy_from_slice(&rhs_x);
+    rhs[32..64].copy_from_slice(&rhs_y);
+
+    #[cfg(feature = "debug")]
+    {
+        crate::dbg_g1!("lhs from pairingPointObject", &lhs);
+        crate::dbg_g1!("rhs from pairingPointObject", &rhs);
+    }
+
+    Ok((lhs, rhs))
+}
+
+/// Generate recursion separator by hashing pairing points
+///
+/// Hashes: proofLhs, proofRhs, accLhs, accRhs -> keccak256 -> Fr (mod r)
+///
+/// Naming follows the Solidity verifier, where "proof" is the claim carried in
+/// the pairing point object and "acc" is the claim just computed by Shplemini.
+pub fn generate_recursion_separator(
+    proof_lhs: &G1,
+    proof_rhs: &G1,
+    acc_lhs: &G1,
+    acc_rhs: &G1,
+) -> Fr {
+    // proofLhs.x, proofLhs.y, proofRhs.x, proofRhs.y, accLhs.x, accLhs.y, accRhs.x, accRhs.y
+    let mut buf = [0u8; 256];
+    buf[0..64].copy_from_slice(proof_lhs);
+    buf[64..128].copy_from_slice(proof_rhs);
+    buf[128..192].copy_from_slice(acc_lhs);
+    buf[192..256].copy_from_slice(acc_rhs);
+
+    let hash = crate::transcript::keccak256(&buf);
+
+    #[cfg(feature = "debug")]
+    crate::dbg_fr!("  raw hash", &hash);
+
+    // Like Solidity's FrLib.fromBytes32: uint256(value) % MODULUS
+    crate::field::fr_reduce(&hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::g1_generator;
+    use crate::types::SCALAR_ONE;
+
+    /// The SRS secret is unknown, so the identity pair is the only claim we
+    /// can construct that satisfies the pairing equation
+    fn identity_claim() -> (G1, G1) {
+        ([0u8; 64], [0u8; 64])
+    }
+
+    #[test]
+    fn test_first_absorb_is_verbatim() {
+        let g = g1_generator();
+        let mut acc = Accumulator::new();
+        assert!(acc.is_empty());
+        acc.absorb(&g, &g).unwrap();
+        assert_eq!(acc.claim(), Some((g, g)));
+    }
+
+    #[test]
+    fn test_absorb_combines_with_separator() {
+        let g = g1_generator();
+        let two_g = ops::g1_add(&g, &g).unwrap();
+
+        let mut acc = Accumulator::new();
+        acc.absorb(&g, &two_g).unwrap();
+        acc.absorb(&two_g, &g).unwrap();
+
+        let sep = generate_recursion_separator(&g, &two_g, &two_g, &g);
+        assert_ne!(sep, SCALAR_ONE);
+        let expected_lhs = ops::g1_add(&g, &ops::g1_mul(&two_g, &sep).unwrap()).unwrap();
+        let expected_rhs = ops::g1_add(&two_g, &ops::g1_mul(&g, &sep).unwrap()).unwrap();
+        assert_eq!(acc.claim(), Some((expected_lhs, expected_rhs)));
+    }
+
+    #[test]
+    fn test_finalize() {
+        assert!(Accumulator::new().finalize().is_err());
+
+        let (p0, p1) = identity_claim();
+        let mut acc = Accumulator::new();
+        acc.absorb(&p0, &p1).unwrap();
+        acc.absorb(&p0, &p1).unwrap();
+        assert!(acc.finalize().unwrap());
+
+        // A single bad claim poisons the accumulator
+        let mut acc = Accumulator::new();
+        acc.absorb(&p0, &p1).unwrap();
+        acc.absorb(&g1_generator(), &p1).unwrap();
+        assert!(!acc.finalize().unwrap());
+    }
+
+    #[test]
+    fn test_pairing_point_object_limbs() {
+        // Generator (1, 2) split into 68-bit limbs: only the low limb is set
+        let mut ppo = [[0u8; 32]; 16];
+        ppo[0][31] = 1;
+        ppo[4][31] = 2;
+        ppo[8][31] = 1;
+        ppo[12][31] = 2;
+
+        let acc = Accumulator::from_pairing_point_object(&ppo).unwrap();
+        assert_eq!(acc.claim(), Some((g1_generator(), g1_generator())));
+
+        assert!(Accumulator::from_pairing_point_object(&ppo[..15]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Host-side BN254 backend using arkworks
+//!
+//! Enabled with the `arkworks` feature on non-Solana targets. `ops` routes
+//! `g1_add`, `g1_mul`, `g1_msm` and `pairing_check` here instead of the alt_bn128
+//! syscall shims, so an off-chain `verify()` runs on an independent, audited
+//! curve implementation. The SDK and CLI use this to pre-verify a proof
+//! locally before paying for the on-chain transactions.
+//!
+//! Encodings are the same as the syscalls (big-endian, G2 as `x1 || x0 || y1 || y0`)
+//! and so are the acceptance rules: coordinates must be canonical and on the
+//! curve, G2 points must be in the prime-order subgroup, `(0, 0)` is the
+//! identity, and scalars are taken as full 256-bit integers.
+
+use crate::errors::Bn254Error;
+use crate::types::{Scalar, G1, G2};
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G1Projective, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInt, PrimeField, Zero};
+
+/// Parse a 32-byte big-endian integer into 4 little-endian u64 limbs
+fn be_to_bigint(bytes: &[u8]) -> BigInt<4> {
+    let mut limbs = [0u64; 4];
+    for (i, chunk) in bytes.chunks_exact(8).enumerate() {
+        limbs[3 - i] = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    BigInt::new(limbs)
+}
+
+/// Parse a canonical (< p) big-endian base field element
+fn fq_from_be(bytes: &[u8]) -> Option<Fq> {
+    Fq::from_bigint(be_to_bigint(bytes))
+}
+
+fn fq_to_be(value: &Fq, out: &mut [u8]) {
+    let limbs = value.into_bigint().0;
+    for (i, limb) in limbs.iter().rev().enumerate() {
+        out[i * 8..i * 8 + 8].copy_from_slice(&limb.to_be_bytes());
+    }
+}
+
+fn g1_from_bytes(point: &G1) -> Result<G1Affine, Bn254Error> {
+    if point.iter().all(|&b| b == 0) {
+        return Ok(G1Affine::zero());
+    }
+    let x = fq_from_be(&point[0..32]).ok_or(Bn254Error::InvalidG1)?;
+    let y = fq_from_be(&point[32..64]).ok_or(Bn254Error::InvalidG1)?;
+    let p = G1Affine::new_unchecked(x, y);
+    // G1 has cofactor 1, so on-curve implies in the subgroup
+    if !p.is_on_curve() {
+        return Err(Bn254Error::InvalidG1);
+    }
+    Ok(p)
+}
+
+fn g1_to_bytes(point: &G1Affine) -> G1 {
+    let mut out = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        fq_to_be(&x, &mut out[0..32]);
+        fq_to_be(&y, &mut out[32..64]);
+    }
+    out
+}
+
+fn g2_from_bytes(point: &G2) -> Result<G2Affine, Bn254Error> {
+    if point.iter().all(|&b| b == 0) {
+        return Ok(G2Affine::zero());
+    }
+    let fq =
+        |range: core::ops::Range<usize>| fq_from_be(&point[range]).ok_or(Bn254Error::InvalidG2);
+    let x = Fq2::new(fq(32..64)?, fq(0..32)?);
+    let y = Fq2::new(fq(96..128)?, fq(64..96)?);
+    let p = G2Affine::new_unchecked(x, y);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Bn254Error::InvalidG2);
+    }
+    Ok(p)
+}
+
+/// G1 addition
+pub fn g1_add(a: &G1, b: &G1) -> Result<G1, Bn254Error> {
+    let sum = G1Projective::from(g1_from_bytes(a)?) + g1_from_bytes(b)?;
+    Ok(g1_to_bytes(&sum.into_affine()))
+}
+
+/// G1 scalar multiplication
+pub fn g1_mul(point: &G1, scalar: &Scalar) -> Result<G1, Bn254Error> {
+    let product = g1_from_bytes(point)?.mul_bigint(be_to_bigint(scalar));
+    Ok(g1_to_bytes(&product.into_affine()))
+}
+
+/// Multi-scalar multiplication (lengths are checked by the caller)
+pub fn g1_msm(points: &[G1], scalars: &[Scalar]) -> Result<G1, Bn254Error> {
+    let mut acc = G1Projective::zero();
+    for (point, scalar) in points.iter().zip(scalars) {
+        acc += g1_from_bytes(point)?.mul_bigint(be_to_bigint(scalar));
+    }
+    Ok(g1_to_bytes(&acc.into_affine()))
+}
+
+/// Multi-pairing check: true if ∏ e(a_i, b_i) == 1
+pub fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, Bn254Error> {
+    let mut g1s = alloc::vec::Vec::with_capacity(pairs.len());
+    let mut g2s = alloc::vec::Vec::with_capacity(pairs.len());
+    for (g1, g2) in pairs {
+        g1s.push(g1_from_bytes(g1)?);
+        g2s.push(g2_from_bytes(g2)?);
+    }
+    Ok(Bn254::multi_pairing(g1s, g2s).is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::G1_GENERATOR;
+    use solana_bn254::prelude::{
+        alt_bn128_g1_addition_be, alt_bn128_g1_multiplication_be, alt_bn128_pairing_be,
+    };
+
+    fn syscall_add(a: &G1, b: &G1) -> G1 {
+        let mut input = [0u8; 128];
+        input[..64].copy_from_slice(a);
+        input[64..].copy_from_slice(b);
+        alt_bn128_g1_addition_be(&input)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn syscall_mul(p: &G1, s: &Scalar) -> G1 {
+        let mut input = [0u8; 96];
+        input[..64].copy_from_slice(p);
+        input[64..].copy_from_slice(s);
+        alt_bn128_g1_multiplication_be(&input)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_g1_ops_match_syscalls() {
+        let g = G1_GENERATOR;
+        let two_g = syscall_add(&g, &g);
+        assert_eq!(g1_add(&g, &g).unwrap(), two_g);
+        assert_eq!(g1_add(&g, &[0u8; 64]).unwrap(), g);
+
+        let mut scalar = [0u8; 32];
+        scalar[0] = 0x1f;
+        scalar[17] = 0xab;
+        scalar[31] = 0x42;
+        assert_eq!(
+            g1_mul(&two_g, &scalar).unwrap(),
+            syscall_mul(&two_g, &scalar)
+        );
+
+        // Scalars >= r are accepted and behave like the syscall
+        let max = [0xffu8; 32];
+        assert_eq!(g1_mul(&g, &max).unwrap(), syscall_mul(&g, &max));
+
+        assert_eq!(g1_mul(&g, &[0u8; 32]).unwrap(), [0u8; 64]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_points() {
+        let mut off_curve = G1_GENERATOR;
+        off_curve[63] = 3;
+        assert!(g1_add(&off_curve, &G1_GENERATOR).is_err());
+
+        // x = p is non-canonical
+        let mut non_canonical = [0u8; 64];
+        non_canonical[..32].copy_from_slice(&hex_literal::hex!(
+            "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47"
+        ));
+        assert!(g1_mul(&non_canonical, &[1u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_pairing_matches_syscall() {
+        let g1 = G1_GENERATOR;
+        let neg_g1 = crate::ops::g1_neg(&g1).unwrap();
+        let g2 = g2_to_bytes_for_test();
+
+        // e(G, H) * e(-G, H) == 1
+        let pairs = [(g1, g2), (neg_g1, g2)];
+        assert!(pairing_check(&pairs).unwrap());
+
+        let mut input = alloc::vec::Vec::new();
+        for (a, b) in &pairs {
+            input.extend_from_slice(a);
+            input.extend_from_slice(b);
+        }
+        assert_eq!(alt_bn128_pairing_be(&input).unwrap()[31], 1);
+
+        assert!(!pairing_check(&[(g1, g2), (g1, g2)]).unwrap());
+    }
+
+    fn g2_to_bytes_for_test() -> G2 {
+        let (x, y) = G2Affine::generator().xy().unwrap();
+        let mut out = [0u8; 128];
+        fq_to_be(&x.c1, &mut out[0..32]);
+        fq_to_be(&x.c0, &mut out[32..64]);
+        fq_to_be(&y.c1, &mut out[64..96]);
+        fq_to_be(&y.c0, &mut out[96..128]);
+        out
+    }
+}
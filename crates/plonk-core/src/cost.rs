@@ -0,0 +1,315 @@
+//! Compute-unit cost model for phased on-chain verification
+//!
+//! [`cu_estimate`] predicts the CUs each phase of the verifier program will
+//! consume for a given circuit shape. The constants are calibrated against
+//! the localnet measurements of bb 0.87 ZK proofs in the README (log_n 12-18,
+//! after the Montgomery and batch-inversion optimizations): a full
+//! verification of `simple_square` (log_n = 12) takes ~5.4M CUs and every
+//! extra `log_n` adds ~260K.
+//!
+//! Estimates are meant for picking a phase layout and sizing compute budget
+//! requests, not as exact predictions. Use [`PhasePlan::compute_unit_limit`]
+//! to add headroom before requesting a budget.
+
+/// Solana's per-transaction compute unit cap
+pub const MAX_TX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Largest circuit (`log2` of its size) the phased verifier accepts
+///
+/// Proofs are padded to 28 rounds, but Phase 3a grows with every round and
+/// the model is extrapolated beyond the measured log_n 18. At 24 each phase,
+/// split as [`PhasePlan`] prescribes, still fits a transaction with the
+/// headroom [`PhasePlan::compute_unit_limit`] adds.
+pub const MAX_LOG_N: u8 = 24;
+
+/// Largest `log_n` the constants were measured at; beyond it the planner
+/// stops combining phases, so an underestimate costs an extra transaction
+/// instead of a failed one
+const CALIBRATED_LOG_N: u8 = 18;
+
+/// Fixed cost of any phase transaction: account loading, state/proof parsing
+const TX_OVERHEAD: u32 = 50_000;
+
+/// Phase 1 (all challenges in one transaction)
+const PHASE1_FULL: u32 = 280_000;
+/// Public input hashing + public_input_delta, per public input
+const PHASE1_PER_PUBLIC_INPUT: u32 = 2_000;
+/// Libra commitments and evaluations absorbed only by ZK transcripts
+const PHASE1_ZK_EXTRA: u32 = 10_000;
+/// Sub-phased Phase 1: 1a, 1b, 1c, 1d, 1e1, 1e2 (delta costs added separately)
+const PHASE1_SUB: [u32; 6] = [45_000, 60_000, 70_000, 70_000, 30_000, 40_000];
+
+/// One sumcheck round (batched barycentric evaluation + pow update)
+const SUMCHECK_ROUND: u32 = 200_000;
+
+/// Arithmetic, permutation, range and elliptic relations (Phase 2d1)
+const RELATIONS_PART1: u32 = 90_000;
+/// Lookup, memory and Poseidon relations + final check (Phase 2d2)
+const RELATIONS_PART2: u32 = 160_000;
+
+/// Phase 3a weights (fixed part)
+const WEIGHTS_BASE: u32 = 610_000;
+/// Phase 3a r^(2^i) powers and fold weights, per round
+const WEIGHTS_PER_ROUND: u32 = 20_000;
+
+/// Phase 3b1 folding (fixed part)
+const FOLDING_BASE: u32 = 100_000;
+/// Phase 3b1 per fold round (batched inversion)
+const FOLDING_PER_ROUND: u32 = 30_000;
+
+/// Phase 3b2 Gemini scalars
+const GEMINI: u32 = 340_000;
+/// Phase 3b2 Libra scalars (ZK only)
+const GEMINI_ZK_EXTRA: u32 = 40_000;
+
+/// Phase 3c MSM over the fixed VK and proof commitments
+const MSM_BASE: u32 = 615_000;
+/// Phase 3c Gemini fold commitment, per round
+const MSM_PER_ROUND: u32 = 10_000;
+/// Phase 3c Libra and masking commitments (ZK only)
+const MSM_ZK_EXTRA: u32 = 30_000;
+
+/// Phase 4: two-pair pairing syscall
+const PAIRING: u32 = 55_000;
+
+/// Estimated compute units per phase of a phased verification
+///
+/// Fields hold the program's own work only; the fixed per-transaction
+/// overhead is added by [`PhasePlan::compute_unit_limit`] and the layout
+/// checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhasePlan {
+    /// Circuit size the plan was computed for
+    pub log_n: u8,
+    /// Phase 1 in one transaction (`Phase1Full`)
+    pub phase1_full: u32,
+    /// Sub-phased Phase 1: 1a, 1b, 1c, 1d, 1e1, 1e2
+    pub phase1_sub: [u32; 6],
+    /// A single sumcheck round
+    pub sumcheck_round: u32,
+    /// First and second relation halves (Phase 2d1 / 2d2)
+    pub relations: [u32; 2],
+    /// Phase 3a weights
+    pub weights: u32,
+    /// Phase 3b1 folding
+    pub folding: u32,
+    /// Phase 3b2 Gemini + Libra
+    pub gemini: u32,
+    /// Phase 3c MSM
+    pub msm: u32,
+    /// Phase 4 pairing check
+    pub pairing: u32,
+}
+
+/// Estimate per-phase compute units for a circuit
+pub fn cu_estimate(log_n: u8, is_zk: bool, num_public_inputs: usize) -> PhasePlan {
+    let rounds = log_n as u32;
+    let pi = (num_public_inputs as u32).saturating_mul(PHASE1_PER_PUBLIC_INPUT);
+    let zk = |extra: u32| if is_zk { extra } else { 0 };
+
+    let mut phase1_sub = PHASE1_SUB;
+    phase1_sub[3] += zk(PHASE1_ZK_EXTRA);
+    // Public inputs are split evenly across the two delta phases
+    phase1_sub[4] = phase1_sub[4].saturating_add(pi / 2);
+    phase1_sub[5] = phase1_sub[5].saturating_add(pi - pi / 2);
+
+    PhasePlan {
+        log_n,
+        phase1_full: PHASE1_FULL.saturating_add(pi) + zk(PHASE1_ZK_EXTRA),
+        phase1_sub,
+        sumcheck_round: SUMCHECK_ROUND,
+        relations: [RELATIONS_PART1, RELATIONS_PART2],
+        weights: WEIGHTS_BASE + WEIGHTS_PER_ROUND * rounds,
+        folding: FOLDING_BASE + FOLDING_PER_ROUND * rounds,
+        gemini: GEMINI + zk(GEMINI_ZK_EXTRA),
+        msm: MSM_BASE + MSM_PER_ROUND * rounds + zk(MSM_ZK_EXTRA),
+        pairing: PAIRING,
+    }
+}
+
+impl PhasePlan {
+    /// Compute budget to request for a phase estimated at `estimate` CUs
+    ///
+    /// Adds the per-transaction overhead and 20% headroom, capped at
+    /// [`MAX_TX_COMPUTE_UNITS`].
+    pub fn compute_unit_limit(estimate: u32) -> u32 {
+        let with_overhead = estimate.saturating_add(TX_OVERHEAD);
+        with_overhead
+            .saturating_add(with_overhead / 5)
+            .min(MAX_TX_COMPUTE_UNITS)
+    }
+
+    /// Whether all challenges fit in a single `Phase1Full` transaction
+    pub fn use_phase1_full(&self) -> bool {
+        fits(self.phase1_full)
+    }
+
+    /// Largest number of sumcheck rounds that fits in one transaction
+    pub fn rounds_per_tx(&self) -> u8 {
+        let rounds = (MAX_TX_COMPUTE_UNITS - TX_OVERHEAD) / self.sumcheck_round.max(1);
+        rounds.clamp(1, u8::MAX as u32) as u8
+    }
+
+    /// Whether both relation halves fit in one `Phase2dRelations` transaction
+    pub fn use_combined_relations(&self) -> bool {
+        fits(self.relations[0] + self.relations[1])
+    }
+
+    /// Whether a `Phase2Rounds` batch of `rounds` rounds fits one transaction
+    pub fn fits_rounds(&self, rounds: usize) -> bool {
+        rounds <= self.rounds_per_tx() as usize
+    }
+
+    /// Whether the relations and Phase 3a should share a `Phase2dAnd3a`
+    /// transaction
+    ///
+    /// Phase 3a grows with `log_n`, so circuits past the measured sizes
+    /// send them separately.
+    pub fn use_relations_and_weights(&self) -> bool {
+        self.log_n <= CALIBRATED_LOG_N && Self::fits_tx(self.relations_and_weights())
+    }
+
+    /// Whether folding and Gemini should share a `Phase3bCombined` transaction
+    pub fn use_combined_folding(&self) -> bool {
+        self.log_n <= CALIBRATED_LOG_N && Self::fits_tx(self.folding + self.gemini)
+    }
+
+    /// Both relation halves plus Phase 3a, as `Phase2dAnd3a` runs them
+    pub fn relations_and_weights(&self) -> u32 {
+        self.relations[0] + self.relations[1] + self.weights
+    }
+
+    /// Whether a phase estimated at `estimate` CUs fits under the
+    /// transaction cap at all (no headroom)
+    pub fn fits_tx(estimate: u32) -> bool {
+        fits(estimate)
+    }
+
+    /// The first phase that cannot fit a transaction with headroom even when
+    /// sent on its own, if any
+    pub fn oversized_phase(&self) -> Option<&'static str> {
+        let phase1 = if self.use_phase1_full() {
+            self.phase1_full
+        } else {
+            self.phase1_sub.iter().copied().max().unwrap_or(0)
+        };
+        let relations = if self.use_combined_relations() {
+            self.relations[0] + self.relations[1]
+        } else {
+            self.relations[0].max(self.relations[1])
+        };
+        [
+            ("Phase 1", phase1),
+            ("Phase 2 round", self.sumcheck_round),
+            ("Phase 2d", relations),
+            ("Phase 3a", self.weights),
+            ("Phase 3b1", self.folding),
+            ("Phase 3b2", self.gemini),
+            ("Phase 3c", self.msm),
+            ("Phase 4", self.pairing),
+        ]
+        .into_iter()
+        .find(|&(_, estimate)| !fits_with_headroom(estimate))
+        .map(|(phase, _)| phase)
+    }
+
+    /// Whether the circuit is within [`MAX_LOG_N`] and every phase fits
+    pub fn is_supported(&self) -> bool {
+        self.log_n <= MAX_LOG_N && self.oversized_phase().is_none()
+    }
+
+    /// Total estimated CUs along the cheapest layout this plan allows
+    pub fn total(&self) -> u64 {
+        let phase1 = if self.use_phase1_full() {
+            self.phase1_full as u64
+        } else {
+            self.phase1_sub.iter().map(|&c| c as u64).sum()
+        };
+        phase1
+            + self.sumcheck_round as u64 * self.log_n as u64
+            + self.relations.iter().map(|&c| c as u64).sum::<u64>()
+            + self.weights as u64
+            + self.folding as u64
+            + self.gemini as u64
+            + self.msm as u64
+            + self.pairing as u64
+    }
+}
+
+fn fits(estimate: u32) -> bool {
+    estimate.saturating_add(TX_OVERHEAD) <= MAX_TX_COMPUTE_UNITS
+}
+
+/// Like [`fits`], with the headroom of [`PhasePlan::compute_unit_limit`]
+fn fits_with_headroom(estimate: u32) -> bool {
+    let with_overhead = estimate.saturating_add(TX_OVERHEAD);
+    with_overhead.saturating_add(with_overhead / 5) <= MAX_TX_COMPUTE_UNITS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cu_estimate_matches_measurements() {
+        // simple_square: ~5.44M measured, ~260K per extra log_n
+        let small = cu_estimate(12, true, 1).total();
+        assert!((5_200_000..5_700_000).contains(&small), "{small}");
+        let large = cu_estimate(18, true, 32).total();
+        assert!((6_900_000..7_500_000).contains(&large), "{large}");
+
+        assert!(cu_estimate(12, false, 1).total() < small);
+    }
+
+    #[test]
+    fn test_phase_layout() {
+        let plan = cu_estimate(12, true, 1);
+        assert!(plan.use_phase1_full());
+        assert!(plan.use_combined_relations());
+        assert_eq!(plan.rounds_per_tx(), 6);
+
+        // Enough public inputs push challenge generation into sub-phases
+        let plan = cu_estimate(12, true, 600);
+        assert!(!plan.use_phase1_full());
+        assert!(plan.phase1_sub.iter().all(|&c| c < plan.phase1_full));
+
+        assert_eq!(
+            PhasePlan::compute_unit_limit(1_300_000),
+            MAX_TX_COMPUTE_UNITS
+        );
+        assert_eq!(PhasePlan::compute_unit_limit(200_000), 300_000);
+    }
+
+    #[test]
+    fn test_max_log_n_boundary() {
+        for log_n in [12, 18, MAX_LOG_N] {
+            let plan = cu_estimate(log_n, true, 32);
+            assert!(plan.is_supported(), "log_n {log_n}");
+            assert!(plan.fits_rounds(plan.rounds_per_tx() as usize));
+            assert!(!plan.fits_rounds(plan.rounds_per_tx() as usize + 1));
+        }
+        assert!(!cu_estimate(MAX_LOG_N + 1, true, 32).is_supported());
+
+        // Past the cap Phase 3a alone no longer leaves headroom
+        assert_eq!(cu_estimate(26, true, 1).oversized_phase(), Some("Phase 3a"));
+        assert_eq!(cu_estimate(MAX_LOG_N, true, 1).oversized_phase(), None);
+    }
+
+    #[test]
+    fn test_large_circuits_split_combined_phases() {
+        let small = cu_estimate(18, true, 32);
+        assert!(small.use_relations_and_weights());
+        assert!(small.use_combined_folding());
+
+        // Past the measured sizes the 2d+3a and 3b1+3b2 pairs are split
+        for log_n in [CALIBRATED_LOG_N + 1, MAX_LOG_N] {
+            let large = cu_estimate(log_n, true, 32);
+            assert!(!large.use_relations_and_weights());
+            assert!(!large.use_combined_folding());
+            assert!(large.use_combined_relations());
+        }
+        assert!(!PhasePlan::fits_tx(
+            cu_estimate(MAX_LOG_N + 2, true, 1).relations_and_weights()
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 //! Error types for the UltraHonk verifier
 
 extern crate alloc;
+use crate::proof::ProofVariant;
 use alloc::string::String;
 use thiserror::Error;
 
@@ -22,6 +23,12 @@ pub enum VerifyError {
     #[error("Transcript error: {0}")]
     Transcript(String),
 
+    #[error("Invalid G1 point in proof: {commitment}[{index}]")]
+    InvalidPoint {
+        commitment: &'static str,
+        index: usize,
+    },
+
     #[error("Verification failed")]
     VerificationFailed,
 }
@@ -46,6 +53,18 @@ pub enum KeyError {
 
     #[error("Point not on curve")]
     PointNotOnCurve,
+
+    #[error("Invalid number of public inputs")]
+    InvalidPublicInputCount,
+
+    #[error("Invalid number of commitments")]
+    InvalidCommitmentCount,
+
+    #[error("Invalid SRS G2 point")]
+    InvalidSrsG2,
+
+    #[error("Invalid compiled VK layout")]
+    InvalidCompiledLayout,
 }
 
 /// Proof parsing errors
@@ -59,6 +78,9 @@ pub enum ProofError {
 
     #[error("Invalid scalar")]
     InvalidScalar,
+
+    #[error("Unsupported proof variant: {0} (only UltraKeccak and UltraKeccakZK are verified)")]
+    UnsupportedVariant(ProofVariant),
 }
 
 /// BN254 operation errors
@@ -51,6 +51,11 @@ use alloc::vec::Vec;
 
 /// Field element in Montgomery form (4 x u64 limbs, little-endian)
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrLimbs(pub [u64; 4]);
 
 impl FrLimbs {
@@ -218,6 +223,7 @@ pub fn batch_inv_limbs(inputs: &[FrLimbs]) -> Option<Vec<FrLimbs>> {
 /// Montgomery multiplication: computes a * b * R^-1 mod r
 /// If inputs are in Montgomery form (a' = a*R, b' = b*R), output is (a*b)*R (also in Montgomery form)
 #[inline]
+#[allow(clippy::needless_range_loop)]
 fn mont_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
     // CIOS (Coarsely Integrated Operand Scanning) Montgomery multiplication
     let mut t = [0u64; 5]; // 5 limbs to handle overflow
@@ -385,6 +391,7 @@ pub fn fr_neg(a: &Fr) -> Fr {
 /// But if inputs are already Fr (not Montgomery), we can be smarter:
 /// - mont_mul(a, b) = a * b * R⁻¹
 /// - mont_mul(result, R²) = a * b * R⁻¹ * R² * R⁻¹ = a * b
+///
 /// Total: 2 mont_mul (current approach)
 pub fn fr_mul(a: &Fr, b: &Fr) -> Fr {
     let a_limbs = fr_to_limbs(a);
@@ -883,6 +890,7 @@ fn sub_4x4_with_borrow(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
 
 /// Compute a^exp mod r using square-and-multiply
 #[allow(dead_code)]
+#[allow(clippy::needless_range_loop)]
 fn pow_mod(base: &[u64; 4], exp: &[u64; 4]) -> [u64; 4] {
     let mut result = [0u64; 4];
     result[0] = 1; // result = 1
@@ -1129,10 +1137,10 @@ mod tests {
     #[test]
     fn test_batch_inv_limbs_consistency() {
         // Test that batch_inv_limbs matches batch_inv
-        let values: Vec<Fr> = (1..=5).map(|i| fr_from_u64(i)).collect();
+        let values: Vec<Fr> = (1..=5).map(fr_from_u64).collect();
         let expected = batch_inv(&values).unwrap();
 
-        let limbs_values: Vec<FrLimbs> = values.iter().map(|v| FrLimbs::from_bytes(v)).collect();
+        let limbs_values: Vec<FrLimbs> = values.iter().map(FrLimbs::from_bytes).collect();
         let result: Vec<Fr> = batch_inv_limbs(&limbs_values)
             .unwrap()
             .iter()
@@ -14,9 +14,24 @@
 //! - [32..64]: log2(domain_size) as 32-byte big-endian field
 //! - [64..96]: num_public_inputs as 32-byte big-endian field
 //! - [96..1888]: 28 G1 commitments (64 bytes each)
+//!
+//! ### Compiled format (on-chain cache, 16 + 64·n bytes):
+//! - [0]: log2_circuit_size as u8
+//! - [1]: log2_domain_size as u8
+//! - [2]: number of commitments (n)
+//! - [3]: format version (`COMPILED_VK_VERSION`)
+//! - [4..8]: num_public_inputs as u32 little-endian
+//! - [8..12]: pub_inputs_offset as u32 little-endian
+//! - [12..16]: reserved, always zero
+//! - [16..]: n G1 commitments (64 bytes each, syscall byte order)
+//!
+//! The compiled format is produced once from an already-validated key, so it
+//! can be read back zero-copy via [`CompiledVk`] without re-running the checks.
 
 use crate::errors::KeyError;
-use crate::types::G1;
+use crate::ops;
+use crate::proof::CONST_PROOF_SIZE_LOG_N;
+use crate::types::{Fr, G1, G2};
 
 extern crate alloc;
 use alloc::vec::Vec;
@@ -33,6 +48,15 @@ pub const VK_NUM_COMMITMENTS_NEW: usize = 27;
 /// Number of commitments in old format
 pub const VK_NUM_COMMITMENTS_OLD: usize = 28;
 
+/// Header size of the compiled VK format
+pub const COMPILED_VK_HEADER_SIZE: usize = 16;
+
+/// Version byte written into compiled VKs
+pub const COMPILED_VK_VERSION: u8 = 1;
+
+/// Compiled VK size for the new format (27 commitments): 1744 bytes
+pub const COMPILED_VK_SIZE: usize = COMPILED_VK_HEADER_SIZE + VK_NUM_COMMITMENTS_NEW * 64;
+
 /// Parsed verification key for UltraHonk
 ///
 /// Note: commitments are stored on the heap (Vec) to avoid BPF stack overflow.
@@ -164,6 +188,229 @@ impl VerificationKey {
     pub fn domain_size(&self) -> u32 {
         1 << self.log2_domain_size
     }
+
+    /// Check the key for internal consistency
+    ///
+    /// `from_bytes` only parses the layout. This additionally checks that the
+    /// header fields are in the range the verifier supports and that every
+    /// commitment is a valid curve point (the identity is allowed: unused
+    /// selectors commit to the zero polynomial).
+    pub fn validate(&self) -> Result<(), KeyError> {
+        // Proofs are padded to CONST_PROOF_SIZE_LOG_N rounds; larger circuits
+        // cannot be expressed, and a zero-round circuit is meaningless
+        if self.log2_circuit_size == 0 || self.log2_circuit_size as usize > CONST_PROOF_SIZE_LOG_N {
+            return Err(KeyError::InvalidCircuitSize);
+        }
+        if self.log2_domain_size < self.log2_circuit_size || self.log2_domain_size > 30 {
+            return Err(KeyError::InvalidDomainSize);
+        }
+        if self.num_public_inputs >= self.circuit_size() {
+            return Err(KeyError::InvalidPublicInputCount);
+        }
+
+        if self.commitments.len() != self.num_commitments
+            || (self.num_commitments != VK_NUM_COMMITMENTS_NEW
+                && self.num_commitments != VK_NUM_COMMITMENTS_OLD)
+        {
+            return Err(KeyError::InvalidCommitmentCount);
+        }
+
+        for commitment in &self.commitments {
+            if !ops::g1_is_on_curve(commitment) {
+                return Err(KeyError::PointNotOnCurve);
+            }
+        }
+        Ok(())
+    }
+
+    /// Canonical 32-byte hash identifying this key (and so its circuit)
+    ///
+    /// Matches bb's `vk->hash_with_origin_tagging` for the Keccak flavor: the
+    /// header fields (log2 circuit size, log2 domain size, public input count)
+    /// are each encoded as a 32-byte big-endian word, followed by every
+    /// commitment as 64 bytes (x || y). The Keccak256 digest is reduced mod r.
+    /// This encoding is stable and shared by the program, SDK, and CLI.
+    pub fn hash(&self) -> Fr {
+        let mut buf = Vec::with_capacity(3 * 32 + self.commitments.len() * 64);
+
+        for field in [
+            self.log2_circuit_size,
+            self.log2_domain_size,
+            self.num_public_inputs,
+        ] {
+            let mut word = [0u8; 32];
+            word[28..32].copy_from_slice(&field.to_be_bytes());
+            buf.extend_from_slice(&word);
+        }
+
+        for commitment in &self.commitments {
+            buf.extend_from_slice(commitment);
+        }
+
+        let digest = crate::transcript::keccak256(&buf);
+        crate::transcript::reduce_hash_to_fr_public(&digest)
+    }
+
+    /// Hash identifying this key together with the x·G2 SRS point it is
+    /// checked against
+    ///
+    /// Equal to [`Self::hash`] for the default Ignition point. Any other SRS
+    /// point accepts different proofs for the same commitments, so it is
+    /// folded in as `keccak256(hash || srs_g2)` reduced mod r.
+    pub fn hash_with_srs(&self, srs_g2: &G2) -> Fr {
+        let hash = self.hash();
+        if *srs_g2 == crate::verifier::default_srs_g2() {
+            return hash;
+        }
+
+        let mut buf = [0u8; 32 + 128];
+        buf[..32].copy_from_slice(&hash);
+        buf[32..].copy_from_slice(srs_g2);
+        let digest = crate::transcript::keccak256(&buf);
+        crate::transcript::reduce_hash_to_fr_public(&digest)
+    }
+
+    /// Size of this key in the compiled format
+    pub fn compiled_size(&self) -> usize {
+        COMPILED_VK_HEADER_SIZE + self.num_commitments * 64
+    }
+
+    /// Serialize into the compiled format, writing into `out`
+    ///
+    /// `out` must be at least `compiled_size()` bytes; any trailing bytes are zeroed.
+    pub fn write_compiled(&self, out: &mut [u8]) -> Result<(), KeyError> {
+        let size = self.compiled_size();
+        if out.len() < size {
+            return Err(KeyError::InvalidSize {
+                expected: size,
+                actual: out.len(),
+            });
+        }
+
+        out.fill(0);
+        out[0] = self.log2_circuit_size as u8;
+        out[1] = self.log2_domain_size as u8;
+        out[2] = self.num_commitments as u8;
+        out[3] = COMPILED_VK_VERSION;
+        out[4..8].copy_from_slice(&self.num_public_inputs.to_le_bytes());
+        out[8..12].copy_from_slice(&self.pub_inputs_offset.to_le_bytes());
+
+        let mut offset = COMPILED_VK_HEADER_SIZE;
+        for commitment in &self.commitments[..self.num_commitments] {
+            out[offset..offset + 64].copy_from_slice(commitment);
+            offset += 64;
+        }
+        Ok(())
+    }
+
+    /// Serialize into a freshly allocated compiled VK
+    pub fn to_compiled_bytes(&self) -> Vec<u8> {
+        let mut out = alloc::vec![0u8; self.compiled_size()];
+        // Cannot fail: the buffer is sized exactly
+        let _ = self.write_compiled(&mut out);
+        out
+    }
+}
+
+/// Zero-copy view over a compiled verification key
+///
+/// Only the layout is checked here (version byte, commitment count, length);
+/// the key material itself was validated when it was compiled.
+#[derive(Debug, Clone, Copy)]
+pub struct CompiledVk<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> CompiledVk<'a> {
+    /// Wrap compiled VK bytes (trailing bytes beyond the commitments are ignored)
+    pub fn from_bytes(raw: &'a [u8]) -> Result<Self, KeyError> {
+        if raw.len() < COMPILED_VK_HEADER_SIZE {
+            return Err(KeyError::InvalidSize {
+                expected: COMPILED_VK_SIZE,
+                actual: raw.len(),
+            });
+        }
+        if raw[3] != COMPILED_VK_VERSION {
+            return Err(KeyError::InvalidCompiledLayout);
+        }
+        if raw[0] > 30 {
+            return Err(KeyError::InvalidCircuitSize);
+        }
+        if raw[1] > 30 {
+            return Err(KeyError::InvalidDomainSize);
+        }
+
+        let num_commitments = raw[2] as usize;
+        if num_commitments != VK_NUM_COMMITMENTS_NEW && num_commitments != VK_NUM_COMMITMENTS_OLD {
+            return Err(KeyError::InvalidCompiledLayout);
+        }
+
+        let size = COMPILED_VK_HEADER_SIZE + num_commitments * 64;
+        if raw.len() < size {
+            return Err(KeyError::InvalidSize {
+                expected: size,
+                actual: raw.len(),
+            });
+        }
+
+        Ok(Self { raw: &raw[..size] })
+    }
+
+    /// Log2 of circuit size
+    pub fn log2_circuit_size(&self) -> u32 {
+        self.raw[0] as u32
+    }
+
+    /// Log2 of domain size
+    pub fn log2_domain_size(&self) -> u32 {
+        self.raw[1] as u32
+    }
+
+    /// Number of public inputs (including pairing point limbs)
+    pub fn num_public_inputs(&self) -> u32 {
+        u32::from_le_bytes([self.raw[4], self.raw[5], self.raw[6], self.raw[7]])
+    }
+
+    /// Public inputs offset
+    pub fn pub_inputs_offset(&self) -> u32 {
+        u32::from_le_bytes([self.raw[8], self.raw[9], self.raw[10], self.raw[11]])
+    }
+
+    /// Number of G1 commitments
+    pub fn num_commitments(&self) -> usize {
+        self.raw[2] as usize
+    }
+
+    /// Get circuit size (2^log2_circuit_size)
+    pub fn circuit_size(&self) -> u32 {
+        1 << self.log2_circuit_size()
+    }
+
+    /// Borrow commitment `i` directly from the underlying bytes
+    pub fn commitment(&self, i: usize) -> &'a G1 {
+        let start = COMPILED_VK_HEADER_SIZE + i * 64;
+        self.raw[start..start + 64]
+            .try_into()
+            .expect("commitment slice is 64 bytes")
+    }
+
+    /// Materialize a `VerificationKey` (plain copy, no re-validation)
+    pub fn to_key(&self) -> VerificationKey {
+        let num_commitments = self.num_commitments();
+        let mut commitments = Vec::with_capacity(num_commitments);
+        for i in 0..num_commitments {
+            commitments.push(*self.commitment(i));
+        }
+
+        VerificationKey {
+            log2_circuit_size: self.log2_circuit_size(),
+            log2_domain_size: self.log2_domain_size(),
+            num_public_inputs: self.num_public_inputs(),
+            pub_inputs_offset: self.pub_inputs_offset(),
+            commitments,
+            num_commitments,
+        }
+    }
 }
 
 /// Read a u32 from a 32-byte big-endian field (value in last 4 bytes)
@@ -249,4 +496,123 @@ mod tests {
         assert_eq!(vk.circuit_size(), 64);
         assert_eq!(vk.num_commitments, VK_NUM_COMMITMENTS_NEW);
     }
+
+    /// New-format VK bytes with every commitment set to the G1 generator
+    fn valid_vk_bytes() -> [u8; VK_SIZE_NEW] {
+        let mut bytes = [0u8; VK_SIZE_NEW];
+        bytes[7] = 64;
+        bytes[15] = 6;
+        bytes[23] = 17;
+        bytes[31] = 1;
+        for commitment in bytes[32..].chunks_exact_mut(64) {
+            commitment[31] = 1;
+            commitment[63] = 2;
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_vk_validate() {
+        let vk = VerificationKey::from_bytes(&valid_vk_bytes()).unwrap();
+        assert!(vk.validate().is_ok());
+
+        // Off-curve commitment
+        let mut bytes = valid_vk_bytes();
+        bytes[32 + 5 * 64 + 63] = 3;
+        let vk = VerificationKey::from_bytes(&bytes).unwrap();
+        assert!(matches!(vk.validate(), Err(KeyError::PointNotOnCurve)));
+
+        // More public inputs than the circuit has rows
+        let mut vk = VerificationKey::from_bytes(&valid_vk_bytes()).unwrap();
+        vk.num_public_inputs = 64;
+        assert!(matches!(
+            vk.validate(),
+            Err(KeyError::InvalidPublicInputCount)
+        ));
+
+        // Circuit larger than the padded proof supports
+        let mut vk = VerificationKey::from_bytes(&valid_vk_bytes()).unwrap();
+        vk.log2_circuit_size = 29;
+        vk.log2_domain_size = 29;
+        assert!(matches!(vk.validate(), Err(KeyError::InvalidCircuitSize)));
+    }
+
+    #[test]
+    fn test_vk_hash_is_stable_and_binding() {
+        let vk = VerificationKey::from_bytes(&valid_vk_bytes()).unwrap();
+        let hash = vk.hash();
+        assert_ne!(hash, [0u8; 32]);
+        assert_eq!(hash, vk.clone().hash());
+
+        // Must be a canonical Fr
+        assert!(hash < crate::types::FR_MODULUS);
+
+        let mut other = vk.clone();
+        other.commitments[26][0] ^= 1;
+        assert_ne!(other.hash(), hash);
+
+        let mut other = vk;
+        other.num_public_inputs += 1;
+        assert_ne!(other.hash(), hash);
+    }
+
+    #[test]
+    fn test_vk_hash_with_srs() {
+        let vk = VerificationKey::from_bytes(&valid_vk_bytes()).unwrap();
+        let default_srs = crate::verifier::default_srs_g2();
+        assert_eq!(vk.hash_with_srs(&default_srs), vk.hash());
+
+        let mut custom_srs = default_srs;
+        custom_srs[127] ^= 1;
+        let custom = vk.hash_with_srs(&custom_srs);
+        assert_ne!(custom, vk.hash());
+        assert!(custom < crate::types::FR_MODULUS);
+    }
+
+    #[test]
+    fn test_compiled_vk_roundtrip() {
+        let mut bytes = [0u8; VK_SIZE_NEW];
+        bytes[7] = 64;
+        bytes[15] = 6;
+        bytes[23] = 17;
+        bytes[31] = 1;
+        for (i, b) in bytes[32..].iter_mut().enumerate() {
+            *b = i as u8;
+        }
+
+        let vk = VerificationKey::from_bytes(&bytes).unwrap();
+        let compiled = vk.to_compiled_bytes();
+        assert_eq!(compiled.len(), COMPILED_VK_SIZE);
+
+        let view = CompiledVk::from_bytes(&compiled).unwrap();
+        assert_eq!(view.log2_circuit_size(), 6);
+        assert_eq!(view.num_public_inputs(), 17);
+        assert_eq!(view.pub_inputs_offset(), 1);
+        assert_eq!(view.circuit_size(), 64);
+        assert_eq!(view.commitment(26), &vk.commitments[26]);
+
+        let restored = view.to_key();
+        assert_eq!(restored.log2_domain_size, vk.log2_domain_size);
+        assert_eq!(restored.commitments, vk.commitments);
+    }
+
+    #[test]
+    fn test_compiled_vk_rejects_bad_layout() {
+        let mut compiled = [0u8; COMPILED_VK_SIZE];
+        compiled[0] = 6;
+        compiled[1] = 6;
+        compiled[2] = VK_NUM_COMMITMENTS_NEW as u8;
+        compiled[3] = COMPILED_VK_VERSION;
+        assert!(CompiledVk::from_bytes(&compiled).is_ok());
+
+        // Truncated
+        assert!(matches!(
+            CompiledVk::from_bytes(&compiled[..COMPILED_VK_SIZE - 1]),
+            Err(KeyError::InvalidSize { .. })
+        ));
+
+        // Wrong version (e.g. raw bb VK bytes)
+        compiled[3] = 0;
+        assert!(CompiledVk::from_bytes(&compiled).is_err());
+    }
 }
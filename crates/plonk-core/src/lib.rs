@@ -26,39 +26,59 @@
 
 extern crate alloc;
 
+pub mod accumulator;
+#[cfg(all(feature = "arkworks", not(target_os = "solana")))]
+pub mod arkworks;
 pub mod constants;
+pub mod cost;
 pub mod debug;
 pub mod errors;
 pub mod field;
 pub mod key;
 pub mod ops;
 pub mod proof;
+pub mod receipt;
 pub mod relations;
+pub mod scratch;
 pub mod shplemini;
 pub mod sumcheck;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
 pub mod transcript;
 pub mod types;
 pub mod verifier;
 
 // Re-export main types
+pub use accumulator::Accumulator;
+pub use cost::{cu_estimate, PhasePlan, MAX_LOG_N, MAX_TX_COMPUTE_UNITS};
 pub use errors::VerifyError;
 pub use field::{batch_inv_limbs, FrLimbs};
+pub use key::{CompiledVk, VerificationKey};
+pub use proof::ProofVariant;
+pub use transcript::ChallengeSet;
 pub use types::{Fr, Scalar, G1, G2};
 pub use verifier::{
     // Split delta computation
     compute_delta_part1,
+    compute_delta_part1_streamed,
     compute_delta_part2,
+    default_srs_g2,
     // Incremental challenge generation for multi-TX verification
     generate_challenges_phase1a,
+    generate_challenges_phase1a_streamed,
     generate_challenges_phase1b,
     generate_challenges_phase1c,
     generate_challenges_phase1d,
+    validate_srs_g2,
     verify,
+    verify_auto,
     verify_inner,
     verify_step1_challenges,
     verify_step2_sumcheck,
     verify_step3_pairing_points,
     verify_step4_pairing_check,
+    verify_step4_pairing_check_with_srs,
+    verify_variant,
     Challenges,
     DeltaPartialResult,
     Phase1aResult,
@@ -69,15 +89,19 @@ pub use verifier::{
 };
 
 // Re-export incremental sumcheck types and functions
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+pub use sumcheck::{diagnose_sumcheck_relations, SumcheckDiagnostics};
 pub use sumcheck::{
-    sumcheck_rounds_init, verify_sumcheck_relations, verify_sumcheck_rounds_partial,
-    SumcheckRoundsState,
+    sumcheck_rounds_init, verify_sumcheck_relations, verify_sumcheck_relations_part1,
+    verify_sumcheck_relations_part2, verify_sumcheck_rounds_partial, RelationBatching,
+    SumcheckRoundError, SumcheckRoundsState,
 };
 
 // Re-export incremental shplemini (MSM) types and functions
 pub use shplemini::{
-    shplemini_phase3a, shplemini_phase3b1, shplemini_phase3b2, shplemini_phase3c,
-    ShpleminiPhase3aResult, ShpleminiPhase3b1Result, ShpleminiPhase3bResult,
+    shplemini_msm_p0, shplemini_msm_scalars, shplemini_phase3a, shplemini_phase3b1,
+    shplemini_phase3b2, shplemini_phase3c, shplemini_phase3c_cached, ShpleminiPhase3aResult,
+    ShpleminiPhase3b1Result, ShpleminiPhase3bResult, MSM_MAX_TERMS,
 };
 
 /// VK size for new format (bb v0.84.0+)
@@ -85,6 +109,11 @@ pub use shplemini::{
 /// = 32 + 1728 = 1760 bytes
 pub const VK_SIZE: usize = key::VK_SIZE_NEW;
 
+/// Compiled (pre-parsed) VK size for the new format
+/// = 16-byte header + 27 G1 commitments
+/// = 16 + 1728 = 1744 bytes
+pub const COMPILED_VK_SIZE: usize = key::COMPILED_VK_SIZE;
+
 /// VK size for old format (legacy)
 /// = 96-byte header + 28 G1 commitments (64 bytes each)
 /// = 96 + 1792 = 1888 bytes
@@ -2,43 +2,27 @@
 //!
 //! All curve arithmetic is performed via `solana-bn254` syscalls,
 //! which are available in both on-chain programs and `solana-program-test`.
+//! Off-chain builds with the `arkworks` feature use [`crate::arkworks`] for
+//! addition, multiplication, MSM and pairings instead.
 
 use crate::errors::Bn254Error;
-use crate::types::{Scalar, G1, G2};
-use solana_bn254::prelude::{
-    alt_bn128_g1_addition_be, alt_bn128_g1_multiplication_be, alt_bn128_pairing_be,
-};
+use crate::types::{Scalar, G1, G1_IDENTITY, G2};
 
 extern crate alloc;
-use alloc::format;
-use alloc::vec::Vec;
 
-/// Performs G1 addition using the alt_bn128_g1_addition_be syscall.
-pub fn g1_add(a: &G1, b: &G1) -> Result<G1, Bn254Error> {
-    let mut input = [0u8; 128];
-    input[..64].copy_from_slice(a);
-    input[64..].copy_from_slice(b);
-
-    let result = alt_bn128_g1_addition_be(&input)
-        .map_err(|e| Bn254Error::SyscallError(format!("G1 addition failed: {:?}", e)))?;
+#[cfg(all(feature = "arkworks", not(target_os = "solana")))]
+use crate::arkworks as backend;
+#[cfg(not(all(feature = "arkworks", not(target_os = "solana"))))]
+use syscall as backend;
 
-    let mut out = [0u8; 64];
-    out.copy_from_slice(&result);
-    Ok(out)
+/// Performs G1 addition.
+pub fn g1_add(a: &G1, b: &G1) -> Result<G1, Bn254Error> {
+    backend::g1_add(a, b)
 }
 
-/// Performs G1 scalar multiplication using the alt_bn128_g1_multiplication_be syscall.
+/// Performs G1 scalar multiplication.
 pub fn g1_mul(point: &G1, scalar: &Scalar) -> Result<G1, Bn254Error> {
-    let mut input = [0u8; 96];
-    input[..64].copy_from_slice(point);
-    input[64..].copy_from_slice(scalar);
-
-    let result = alt_bn128_g1_multiplication_be(&input)
-        .map_err(|e| Bn254Error::SyscallError(format!("G1 multiplication failed: {:?}", e)))?;
-
-    let mut out = [0u8; 64];
-    out.copy_from_slice(&result);
-    Ok(out)
+    backend::g1_mul(point, scalar)
 }
 
 /// Performs G1 subtraction (a - b = a + (-b))
@@ -81,28 +65,218 @@ pub fn g1_neg(point: &G1) -> Result<G1, Bn254Error> {
     Ok(result)
 }
 
-/// Performs a multi-pairing check using the alt_bn128_pairing_be syscall.
-/// Returns true if ∏ e(a_i, b_i) == 1 (identity in GT)
-pub fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, Bn254Error> {
-    if pairs.is_empty() {
-        return Ok(true);
+// ============================================================================
+// Point validation (pure Rust, independent of syscall input checks)
+// ============================================================================
+
+/// BN254 base field modulus q (little-endian u64 limbs)
+const FQ_MODULUS_LIMBS: [u64; 4] = [
+    0x3c208c16d87cfd47,
+    0x97816a916871ca8d,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+];
+
+/// 2^512 mod q (converts into Montgomery form)
+const FQ_R2: [u64; 4] = [
+    0xf32cfc5b538afa89,
+    0xb5e71911d44501fb,
+    0x47ab1eff0a417ff6,
+    0x06d89f71cab8351f,
+];
+
+/// -q^{-1} mod 2^64
+const FQ_INV: u64 = 0x87d20782e4866389;
+
+/// Curve constant b = 3 in Montgomery form
+const FQ_B_MONT: [u64; 4] = [
+    0x7a17caa950ad28d7,
+    0x1f6ac17ae15521b9,
+    0x334bea4e696bd284,
+    0x2a1f6744ce179d8e,
+];
+
+/// Returns true if `point` is the identity or a valid affine point on
+/// y² = x³ + 3 with both coordinates canonical (< q).
+///
+/// BN254 G1 has cofactor 1, so on-curve also implies prime-order subgroup
+/// membership; no separate subgroup check is needed.
+pub fn g1_is_on_curve(point: &G1) -> bool {
+    if *point == G1_IDENTITY {
+        return true;
+    }
+
+    let mut x_bytes = [0u8; 32];
+    let mut y_bytes = [0u8; 32];
+    x_bytes.copy_from_slice(&point[..32]);
+    y_bytes.copy_from_slice(&point[32..]);
+    let x = crate::field::fr_to_limbs(&x_bytes);
+    let y = crate::field::fr_to_limbs(&y_bytes);
+
+    if !fq_lt_modulus(&x) || !fq_lt_modulus(&y) {
+        return false;
+    }
+
+    let x = fq_mont_mul(&x, &FQ_R2);
+    let y = fq_mont_mul(&y, &FQ_R2);
+
+    let lhs = fq_mont_mul(&y, &y);
+    let x3 = fq_mont_mul(&fq_mont_mul(&x, &x), &x);
+    let rhs = fq_add(&x3, &FQ_B_MONT);
+
+    lhs == rhs
+}
+
+/// Twist constant b' = 3 / (9 + u) as (c0, c1), big-endian
+const FQ2_TWIST_B: ([u8; 32], [u8; 32]) = (
+    hex_literal::hex!("2b149d40ceb8aaae81be18991be06ac3b5b4c5e559dbefa33267e6dc24a138e5"),
+    hex_literal::hex!("009713b03af0fed4cd2cafadeed8fdf4a74fa084e52d1852e4a2bd0685c315d2"),
+);
+
+/// Returns true if `point` is the identity or a valid affine point on the
+/// twist y² = x³ + 3/(9+u) with all four coordinates canonical (< q).
+///
+/// Unlike G1, the twist has a large cofactor, so this does NOT imply
+/// membership in the prime-order subgroup. That check is too expensive to
+/// run on-chain; the `arkworks` backend performs it at pairing time.
+pub fn g2_is_on_curve(point: &G2) -> bool {
+    if point.iter().all(|&b| b == 0) {
+        return true;
+    }
+
+    // Encoding is x1 || x0 || y1 || y0
+    let mut coords = [[0u64; 4]; 4];
+    for (i, coord) in coords.iter_mut().enumerate() {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&point[i * 32..(i + 1) * 32]);
+        *coord = crate::field::fr_to_limbs(&bytes);
+        if !fq_lt_modulus(coord) {
+            return false;
+        }
+        *coord = fq_mont_mul(coord, &FQ_R2);
+    }
+    let x = (coords[1], coords[0]);
+    let y = (coords[3], coords[2]);
+
+    let b = (
+        fq_mont_mul(&crate::field::fr_to_limbs(&FQ2_TWIST_B.0), &FQ_R2),
+        fq_mont_mul(&crate::field::fr_to_limbs(&FQ2_TWIST_B.1), &FQ_R2),
+    );
+
+    let lhs = fq2_mul(&y, &y);
+    let x3 = fq2_mul(&fq2_mul(&x, &x), &x);
+    let rhs = (fq_add(&x3.0, &b.0), fq_add(&x3.1, &b.1));
+
+    lhs == rhs
+}
+
+type Fq2Limbs = ([u64; 4], [u64; 4]);
+
+/// (a0 + a1·u)(b0 + b1·u) with u² = -1, Montgomery form
+fn fq2_mul(a: &Fq2Limbs, b: &Fq2Limbs) -> Fq2Limbs {
+    let a0b0 = fq_mont_mul(&a.0, &b.0);
+    let a1b1 = fq_mont_mul(&a.1, &b.1);
+    let a0b1 = fq_mont_mul(&a.0, &b.1);
+    let a1b0 = fq_mont_mul(&a.1, &b.0);
+    (fq_sub(&a0b0, &a1b1), fq_add(&a0b1, &a1b0))
+}
+
+/// a - b mod q for canonical inputs
+fn fq_sub(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(b[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        out[i] = d2;
+        borrow = (b1 | b2) as u64;
     }
+    if borrow != 0 {
+        let mut carry = 0u64;
+        for i in 0..4 {
+            let sum = (out[i] as u128) + (FQ_MODULUS_LIMBS[i] as u128) + (carry as u128);
+            out[i] = sum as u64;
+            carry = (sum >> 64) as u64;
+        }
+    }
+    out
+}
 
-    let mut input = Vec::with_capacity(pairs.len() * 192);
-    for (g1, g2) in pairs {
-        input.extend_from_slice(g1);
-        input.extend_from_slice(g2);
+fn fq_lt_modulus(a: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != FQ_MODULUS_LIMBS[i] {
+            return a[i] < FQ_MODULUS_LIMBS[i];
+        }
     }
+    false
+}
 
-    let result = alt_bn128_pairing_be(&input)
-        .map_err(|e| Bn254Error::SyscallError(format!("Pairing check failed: {:?}", e)))?;
+/// Subtract q from `a` (caller guarantees a >= q or a overflowed 2^256)
+fn fq_sub_modulus(a: &[u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(FQ_MODULUS_LIMBS[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        out[i] = d2;
+        borrow = (b1 | b2) as u64;
+    }
+    out
+}
 
-    // The syscall returns 32 bytes, with 0x01 in the last byte if the pairing check passes
-    if result.len() != 32 {
-        return Err(Bn254Error::PairingFailed);
+fn fq_add(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    let mut carry = 0u64;
+    for i in 0..4 {
+        let sum = (a[i] as u128) + (b[i] as u128) + (carry as u128);
+        out[i] = sum as u64;
+        carry = (sum >> 64) as u64;
     }
+    if carry != 0 || !fq_lt_modulus(&out) {
+        out = fq_sub_modulus(&out);
+    }
+    out
+}
 
-    Ok(result[31] == 1)
+/// CIOS Montgomery multiplication modulo q
+#[allow(clippy::needless_range_loop)]
+fn fq_mont_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut t = [0u64; 5];
+
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let prod = (t[j] as u128) + (a[j] as u128) * (b[i] as u128) + carry;
+            t[j] = prod as u64;
+            carry = prod >> 64;
+        }
+        t[4] = t[4].wrapping_add(carry as u64);
+
+        let m = t[0].wrapping_mul(FQ_INV);
+        let prod = (t[0] as u128) + (m as u128) * (FQ_MODULUS_LIMBS[0] as u128);
+        let mut carry = prod >> 64;
+        for j in 1..4 {
+            let prod = (t[j] as u128) + (m as u128) * (FQ_MODULUS_LIMBS[j] as u128) + carry;
+            t[j - 1] = prod as u64;
+            carry = prod >> 64;
+        }
+        let sum = (t[4] as u128) + carry;
+        t[3] = sum as u64;
+        t[4] = (sum >> 64) as u64;
+    }
+
+    let result = [t[0], t[1], t[2], t[3]];
+    if t[4] != 0 || !fq_lt_modulus(&result) {
+        fq_sub_modulus(&result)
+    } else {
+        result
+    }
+}
+
+/// Performs a multi-pairing check.
+/// Returns true if ∏ e(a_i, b_i) == 1 (identity in GT)
+pub fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, Bn254Error> {
+    backend::pairing_check(pairs)
 }
 
 /// G1 scalar multiplication alias
@@ -121,24 +295,128 @@ pub fn g1_msm(points: &[G1], scalars: &[Scalar]) -> Result<G1, Bn254Error> {
     if points.len() != scalars.len() {
         return Err(Bn254Error::InvalidG1);
     }
+    backend::g1_msm(points, scalars)
+}
+
+/// alt_bn128 syscall implementations (the only backend on-chain)
+#[cfg(not(all(feature = "arkworks", not(target_os = "solana"))))]
+mod syscall {
+    use super::*;
+    use crate::types::{SCALAR_ONE, SCALAR_ZERO};
+    use alloc::{format, vec::Vec};
+    use solana_bn254::prelude::{
+        alt_bn128_g1_addition_be, alt_bn128_g1_multiplication_be, alt_bn128_pairing_be,
+    };
+
+    /// Performs G1 addition using the alt_bn128_g1_addition_be syscall.
+    pub(super) fn g1_add(a: &G1, b: &G1) -> Result<G1, Bn254Error> {
+        let mut input = [0u8; 128];
+        input[..64].copy_from_slice(a);
+        input[64..].copy_from_slice(b);
 
-    if points.is_empty() {
-        return Ok([0u8; 64]); // Identity
+        let result = alt_bn128_g1_addition_be(&input)
+            .map_err(|e| Bn254Error::SyscallError(format!("G1 addition failed: {:?}", e)))?;
+
+        let mut out = [0u8; 64];
+        out.copy_from_slice(&result);
+        Ok(out)
     }
 
-    let mut acc = g1_mul(&points[0], &scalars[0])?;
-    for i in 1..points.len() {
-        let term = g1_mul(&points[i], &scalars[i])?;
-        acc = g1_add(&acc, &term)?;
+    /// Performs G1 scalar multiplication using the alt_bn128_g1_multiplication_be syscall.
+    pub(super) fn g1_mul(point: &G1, scalar: &Scalar) -> Result<G1, Bn254Error> {
+        let mut input = [0u8; 96];
+        input[..64].copy_from_slice(point);
+        input[64..].copy_from_slice(scalar);
+
+        let result = alt_bn128_g1_multiplication_be(&input)
+            .map_err(|e| Bn254Error::SyscallError(format!("G1 multiplication failed: {:?}", e)))?;
+
+        let mut out = [0u8; 64];
+        out.copy_from_slice(&result);
+        Ok(out)
     }
 
-    Ok(acc)
+    /// Performs a multi-pairing check using the alt_bn128_pairing_be syscall.
+    /// Returns true if ∏ e(a_i, b_i) == 1 (identity in GT)
+    pub(super) fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, Bn254Error> {
+        if pairs.is_empty() {
+            return Ok(true);
+        }
+
+        let mut input = Vec::with_capacity(pairs.len() * 192);
+        for (g1, g2) in pairs {
+            input.extend_from_slice(g1);
+            input.extend_from_slice(g2);
+        }
+
+        let result = alt_bn128_pairing_be(&input)
+            .map_err(|e| Bn254Error::SyscallError(format!("Pairing check failed: {:?}", e)))?;
+
+        // The syscall returns 32 bytes, with 0x01 in the last byte if the pairing check passes
+        if result.len() != 32 {
+            return Err(Bn254Error::PairingFailed);
+        }
+
+        Ok(result[31] == 1)
+    }
+
+    /// Syscall MSM.
+    ///
+    /// The alt_bn128 syscalls only accept a single multiplication or addition per
+    /// call, so the cost is driven by how many of them we issue. Terms with a zero
+    /// scalar or an identity point are dropped, unit scalars skip the
+    /// multiplication, and the accumulator is seeded with the first surviving term
+    /// instead of being added to the identity. The syscall input buffers are reused
+    /// across terms rather than rebuilt for every call.
+    pub(super) fn g1_msm(points: &[G1], scalars: &[Scalar]) -> Result<G1, Bn254Error> {
+        // [acc || term] for additions, [point || scalar] for multiplications
+        let mut add_input = [0u8; 128];
+        let mut mul_input = [0u8; 96];
+        let mut has_acc = false;
+
+        for (point, scalar) in points.iter().zip(scalars.iter()) {
+            if *scalar == SCALAR_ZERO || *point == G1_IDENTITY {
+                continue;
+            }
+
+            let term_dst = if has_acc {
+                &mut add_input[64..]
+            } else {
+                &mut add_input[..64]
+            };
+
+            if *scalar == SCALAR_ONE {
+                term_dst.copy_from_slice(point);
+            } else {
+                mul_input[..64].copy_from_slice(point);
+                mul_input[64..].copy_from_slice(scalar);
+                let term = alt_bn128_g1_multiplication_be(&mul_input).map_err(|e| {
+                    Bn254Error::SyscallError(format!("G1 multiplication failed: {:?}", e))
+                })?;
+                term_dst.copy_from_slice(&term);
+            }
+
+            if has_acc {
+                let sum = alt_bn128_g1_addition_be(&add_input).map_err(|e| {
+                    Bn254Error::SyscallError(format!("G1 addition failed: {:?}", e))
+                })?;
+                add_input[..64].copy_from_slice(&sum);
+            }
+            has_acc = true;
+        }
+
+        let mut out = G1_IDENTITY;
+        if has_acc {
+            out.copy_from_slice(&add_input[..64]);
+        }
+        Ok(out)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::G1_IDENTITY;
+    use crate::types::{G1_GENERATOR, SCALAR_ONE, SCALAR_ZERO};
 
     #[test]
     fn test_g1_neg_identity() {
@@ -146,6 +424,85 @@ mod tests {
         assert_eq!(neg, G1_IDENTITY);
     }
 
+    fn scalar(v: u64) -> Scalar {
+        let mut s = SCALAR_ZERO;
+        s[24..].copy_from_slice(&v.to_be_bytes());
+        s
+    }
+
+    #[test]
+    fn test_g1_is_on_curve() {
+        assert!(g1_is_on_curve(&G1_IDENTITY));
+        assert!(g1_is_on_curve(&G1_GENERATOR));
+        assert!(g1_is_on_curve(&g1_neg(&G1_GENERATOR).unwrap()));
+
+        let p = g1_mul(&G1_GENERATOR, &scalar(123456789)).unwrap();
+        assert!(g1_is_on_curve(&p));
+
+        // (1, 3) is not on the curve
+        let mut bad = G1_GENERATOR;
+        bad[63] = 3;
+        assert!(!g1_is_on_curve(&bad));
+
+        // Non-canonical coordinate: y + q
+        let mut non_canonical = p;
+        let y = crate::field::fr_to_limbs(p[32..].try_into().unwrap());
+        let mut carry = 0u128;
+        let mut y_plus_q = [0u64; 4];
+        for i in 0..4 {
+            let s = (y[i] as u128) + (FQ_MODULUS_LIMBS[i] as u128) + carry;
+            y_plus_q[i] = s as u64;
+            carry = s >> 64;
+        }
+        assert_eq!(carry, 0);
+        non_canonical[32..].copy_from_slice(&crate::field::limbs_to_fr(&y_plus_q));
+        assert!(!g1_is_on_curve(&non_canonical));
+    }
+
+    #[test]
+    fn test_g2_is_on_curve() {
+        let g2 = crate::verifier::g2_generator();
+        assert!(g2_is_on_curve(&[0u8; 128]));
+        assert!(g2_is_on_curve(&g2));
+        assert!(g2_is_on_curve(&crate::verifier::default_srs_g2()));
+
+        // Swapping the Fq2 components of x moves the point off the twist
+        let mut swapped = g2;
+        swapped[..32].copy_from_slice(&g2[32..64]);
+        swapped[32..64].copy_from_slice(&g2[..32]);
+        assert!(!g2_is_on_curve(&swapped));
+
+        let mut bad = g2;
+        bad[95] ^= 1;
+        assert!(!g2_is_on_curve(&bad));
+    }
+
+    #[test]
+    fn test_g1_msm_matches_naive() {
+        let g = G1_GENERATOR;
+        let g2 = g1_mul(&g, &scalar(2)).unwrap();
+        let g7 = g1_mul(&g, &scalar(7)).unwrap();
+        let points = [g, g2, G1_IDENTITY, g7, g];
+        let scalars = [scalar(3), SCALAR_ONE, scalar(5), SCALAR_ZERO, scalar(11)];
+
+        // 3*G + 1*(2G) + 5*O + 0*(7G) + 11*G = 16G
+        let expected = g1_mul(&g, &scalar(16)).unwrap();
+        assert_eq!(g1_msm(&points, &scalars).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_g1_msm_all_terms_skipped() {
+        let points = [G1_GENERATOR, G1_IDENTITY];
+        let scalars = [SCALAR_ZERO, SCALAR_ONE];
+        assert_eq!(g1_msm(&points, &scalars).unwrap(), G1_IDENTITY);
+        assert_eq!(g1_msm(&[], &[]).unwrap(), G1_IDENTITY);
+    }
+
+    #[test]
+    fn test_g1_msm_length_mismatch() {
+        assert!(g1_msm(&[G1_GENERATOR], &[]).is_err());
+    }
+
     // Note: Tests that use syscalls will only work in solana-program-test environment
     // For unit tests, we test the pure logic parts
 }
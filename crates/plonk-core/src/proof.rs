@@ -29,8 +29,9 @@
 //!
 //! Total ZK proof size: 16224 bytes
 
-use crate::errors::ProofError;
-use crate::types::{Fr, G1};
+use crate::errors::{ProofError, VerifyError};
+use crate::ops;
+use crate::types::{Fr, G1, G1_IDENTITY};
 
 extern crate alloc;
 use alloc::vec::Vec;
@@ -103,6 +104,22 @@ pub fn g1_from_limbed(limbed: &[u8; G1_LIMBED_SIZE]) -> G1 {
     result
 }
 
+/// Like [`g1_from_limbed`], but rejects limbs with bits set above their
+/// 136-bit (low) / 120-bit (high) width instead of silently dropping them
+pub fn g1_from_limbed_checked(limbed: &[u8; G1_LIMBED_SIZE]) -> Option<G1> {
+    for coord in limbed.chunks_exact(64) {
+        // Low limb: only the last 17 bytes may be non-zero
+        if coord[..15].iter().any(|&b| b != 0) {
+            return None;
+        }
+        // High limb: only the last 15 bytes may be non-zero
+        if coord[32..49].iter().any(|&b| b != 0) {
+            return None;
+        }
+    }
+    Some(g1_from_limbed(limbed))
+}
+
 /// Reconstruct a 256-bit coordinate from limbed representation
 /// Input: 64 bytes = x_0 (32 bytes) || x_1 (32 bytes)
 /// Output: 32 bytes = x_0 | (x_1 << 136)
@@ -132,6 +149,90 @@ fn reconstruct_coordinate(limbs: &[u8]) -> [u8; 32] {
     result
 }
 
+/// Proof serialization formats recognised by [`Proof::detect_format`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofFormat {
+    /// bb 0.87+ Keccak proofs: fixed size, arrays padded to CONST_PROOF_SIZE_LOG_N
+    Bb087,
+}
+
+/// Barretenberg proof flavors, as far as this verifier knows them
+///
+/// Only the Keccak/KZG flavors are verified. The others are listed so that
+/// callers naming them get [`ProofError::UnsupportedVariant`] instead of a
+/// size mismatch or a failed pairing; supporting one means giving it a
+/// layout here and a transcript/opening path in the verifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProofVariant {
+    /// `UltraKeccakFlavor`: Keccak transcript, Shplemini/KZG opening
+    UltraKeccak,
+    /// `UltraKeccakZKFlavor`: adds Libra masking and the Gemini masking
+    /// polynomial (bb's default for `--oracle_hash keccak`)
+    UltraKeccakZk,
+    /// ZK UltraHonk whose opening tail is an IPA proof over Grumpkin
+    /// rather than a KZG quotient
+    UltraZkIpa,
+    /// `UltraStarknetZKFlavor`: the ZK layout with a Starknet Poseidon
+    /// transcript. Byte-for-byte the same size as [`Self::UltraKeccakZk`],
+    /// so it cannot be detected and must be named by the caller.
+    UltraStarknetZk,
+}
+
+impl ProofVariant {
+    /// The variant `verify(.., is_zk)` checks
+    pub fn from_is_zk(is_zk: bool) -> Self {
+        if is_zk {
+            Self::UltraKeccakZk
+        } else {
+            Self::UltraKeccak
+        }
+    }
+
+    /// Detect the variant from the proof length
+    ///
+    /// Only supported variants are returned: a Starknet proof is reported as
+    /// [`Self::UltraKeccakZk`] and then fails verification.
+    pub fn detect(bytes: &[u8]) -> Result<Self, ProofError> {
+        Proof::detect_format(bytes).map(|(is_zk, _format)| Self::from_is_zk(is_zk))
+    }
+
+    /// Whether this verifier can check proofs of this variant
+    pub fn is_supported(self) -> bool {
+        matches!(self, Self::UltraKeccak | Self::UltraKeccakZk)
+    }
+
+    /// Whether the flavor masks the witness (Libra, Gemini masking)
+    pub fn is_zk(self) -> bool {
+        !matches!(self, Self::UltraKeccak)
+    }
+
+    /// `Ok(is_zk)` for a supported variant, the error otherwise
+    pub fn require_supported(self) -> Result<bool, ProofError> {
+        if self.is_supported() {
+            Ok(self.is_zk())
+        } else {
+            Err(ProofError::UnsupportedVariant(self))
+        }
+    }
+
+    /// Name as bb's flavor classes spell it
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UltraKeccak => "UltraKeccak",
+            Self::UltraKeccakZk => "UltraKeccakZK",
+            Self::UltraZkIpa => "UltraZK+IPA",
+            Self::UltraStarknetZk => "UltraStarknetZK",
+        }
+    }
+}
+
+impl core::fmt::Display for ProofVariant {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// Parsed UltraHonk proof with semantic structure (bb 0.87 format)
 ///
 /// Uses zero-copy design: references account data directly instead of copying
@@ -160,6 +261,37 @@ impl<'a> Proof<'a> {
         }
     }
 
+    /// Detect whether `bytes` is a ZK proof, and in which format
+    ///
+    /// bb 0.87 proofs are fixed-size, so ZK and non-ZK proofs are told apart
+    /// by length alone; `log_n` comes from the VK and is not needed here.
+    pub fn detect_format(bytes: &[u8]) -> Result<(bool, ProofFormat), ProofError> {
+        match bytes.len() {
+            EXPECTED_ZK_PROOF_SIZE => Ok((true, ProofFormat::Bb087)),
+            EXPECTED_NON_ZK_PROOF_SIZE => Ok((false, ProofFormat::Bb087)),
+            actual => Err(ProofError::InvalidSize {
+                expected: EXPECTED_ZK_PROOF_SIZE,
+                actual,
+            }),
+        }
+    }
+
+    /// Parse a proof of a named variant, rejecting ones the verifier cannot
+    /// check before looking at the bytes
+    pub fn from_bytes_variant(
+        bytes: &'a [u8],
+        log_n: usize,
+        variant: ProofVariant,
+    ) -> Result<Self, ProofError> {
+        let is_zk = variant.require_supported()?;
+        Self::from_bytes(bytes, log_n, is_zk)
+    }
+
+    /// The variant this proof is parsed as
+    pub fn variant(&self) -> ProofVariant {
+        ProofVariant::from_is_zk(self.is_zk)
+    }
+
     /// Calculate expected proof size in Fr elements
     pub fn expected_size(log_n: usize, is_zk: bool) -> usize {
         // For bb 0.87, the proof size is fixed regardless of log_n
@@ -218,7 +350,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Offset where sumcheck univariates start
-    fn sumcheck_univariates_offset(&self) -> usize {
+    pub(crate) fn sumcheck_univariates_offset(&self) -> usize {
         if self.is_zk {
             self.libra_sum_offset() + FR_SIZE
         } else {
@@ -294,9 +426,74 @@ impl<'a> Proof<'a> {
         self.shplonk_q_offset() + G1_LIMBED_SIZE
     }
 
+    // ========== Point validation ==========
+
+    /// Every G1 commitment slot in the proof as
+    /// `(name, index, byte offset, identity allowed)`
+    pub(crate) fn g1_slots(&self) -> Vec<(&'static str, usize, usize, bool)> {
+        let mut slots = Vec::with_capacity(NUM_WITNESS_COMMS + 4 + CONST_PROOF_SIZE_LOG_N + 1);
+
+        // Witness columns may legitimately commit to a zero polynomial
+        // (e.g. lookup columns of a circuit without lookups)
+        for i in 0..NUM_WITNESS_COMMS {
+            let offset = self.witness_comms_offset() + i * G1_LIMBED_SIZE;
+            slots.push(("witness_commitment", i, offset, true));
+        }
+
+        // ZK masking commitments are randomized and never the identity
+        if self.is_zk {
+            slots.push(("libra_commitment", 0, self.libra_comm0_offset(), false));
+            slots.push(("libra_commitment", 1, self.libra_comm1_offset(), false));
+            slots.push(("libra_commitment", 2, self.libra_comm2_offset(), false));
+            slots.push((
+                "gemini_masking_poly",
+                0,
+                self.gemini_masking_poly_offset(),
+                false,
+            ));
+        }
+
+        // Fold commitments of dummy rounds (i >= log_n - 1) are padding
+        for i in 0..(CONST_PROOF_SIZE_LOG_N - 1) {
+            let offset = self.gemini_fold_comms_offset() + i * G1_LIMBED_SIZE;
+            slots.push(("gemini_fold_commitment", i, offset, true));
+        }
+
+        slots.push(("shplonk_q", 0, self.shplonk_q_offset(), false));
+        slots.push(("kzg_quotient", 0, self.kzg_quotient_offset(), false));
+        slots
+    }
+
+    /// Validate every G1 commitment in the proof
+    ///
+    /// For each limbed point this checks that the limbs are canonical
+    /// (x_0/y_0 < 2^136, x_1/y_1 < 2^120), so the bytes absorbed by the
+    /// transcript map to exactly one point, and that the reconstructed point
+    /// is on the curve. The identity is only accepted where an honest prover
+    /// can produce it.
+    pub fn validate_points(&self) -> Result<(), VerifyError> {
+        for (commitment, index, offset, allow_identity) in self.g1_slots() {
+            let limbed: &[u8; G1_LIMBED_SIZE] = self.raw_data[offset..offset + G1_LIMBED_SIZE]
+                .try_into()
+                .map_err(|_| VerifyError::InvalidPoint { commitment, index })?;
+
+            let point = g1_from_limbed_checked(limbed)
+                .ok_or(VerifyError::InvalidPoint { commitment, index })?;
+
+            if point == G1_IDENTITY && !allow_identity {
+                return Err(VerifyError::InvalidPoint { commitment, index });
+            }
+            if !ops::g1_is_on_curve(&point) {
+                return Err(VerifyError::InvalidPoint { commitment, index });
+            }
+        }
+        Ok(())
+    }
+
     // ========== Accessor methods ==========
 
     /// Get pairing point object (16 Fr elements)
+    #[allow(clippy::needless_range_loop)]
     pub fn pairing_point_object(&self) -> [Fr; NUM_PAIRING_POINT_FRS] {
         let mut result = [[0u8; FR_SIZE]; NUM_PAIRING_POINT_FRS];
         let offset = self.pairing_point_offset();
@@ -322,6 +519,7 @@ impl<'a> Proof<'a> {
 
     /// Get witness commitment in raw limbed format (4 Fr elements)
     /// Used for transcript where Solidity uses the limbed format
+    #[allow(clippy::needless_range_loop)]
     pub fn witness_commitment_limbed(&self, index: usize) -> [Fr; 4] {
         assert!(
             index < NUM_WITNESS_COMMS,
@@ -391,6 +589,7 @@ impl<'a> Proof<'a> {
     /// Get libraCommitments[0] in raw limbed format (ZK only)
     /// Returns [x_0, x_1, y_0, y_1] as 4 Fr elements
     /// Used for transcript where Solidity uses the limbed format
+    #[allow(clippy::needless_range_loop)]
     pub fn libra_commitment_0_limbed(&self) -> [Fr; 4] {
         assert!(
             self.is_zk,
@@ -481,6 +680,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Get libraCommitments[1] in limbed format [x_0, x_1, y_0, y_1] (ZK only)
+    #[allow(clippy::needless_range_loop)]
     pub fn libra_commitment_1_limbed(&self) -> [Fr; 4] {
         assert!(
             self.is_zk,
@@ -508,6 +708,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Get libraCommitments[2] in limbed format [x_0, x_1, y_0, y_1] (ZK only)
+    #[allow(clippy::needless_range_loop)]
     pub fn libra_commitment_2_limbed(&self) -> [Fr; 4] {
         assert!(
             self.is_zk,
@@ -535,6 +736,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Get geminiMaskingPoly in limbed format [x_0, x_1, y_0, y_1] (ZK only)
+    #[allow(clippy::needless_range_loop)]
     pub fn gemini_masking_poly_limbed(&self) -> [Fr; 4] {
         assert!(
             self.is_zk,
@@ -574,6 +776,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Get gemini fold commitment in limbed format [x_0, x_1, y_0, y_1]
+    #[allow(clippy::needless_range_loop)]
     pub fn gemini_fold_commitment_limbed(&self, index: usize) -> [Fr; 4] {
         assert!(
             index < CONST_PROOF_SIZE_LOG_N - 1,
@@ -616,6 +819,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Get libraPolyEvals (ZK only, 4 Fr elements)
+    #[allow(clippy::needless_range_loop)]
     pub fn libra_poly_evals(&self) -> [Fr; 4] {
         assert!(self.is_zk, "libra_poly_evals only available for ZK proofs");
         let offset = self.libra_poly_evals_offset();
@@ -636,6 +840,7 @@ impl<'a> Proof<'a> {
     }
 
     /// Get shplonkQ in limbed format [x_0, x_1, y_0, y_1]
+    #[allow(clippy::needless_range_loop)]
     pub fn shplonk_q_limbed(&self) -> [Fr; 4] {
         let offset = self.shplonk_q_offset();
         let mut result = [[0u8; FR_SIZE]; 4];
@@ -690,6 +895,112 @@ mod tests {
         assert_eq!(g1[63], 2, "y should be 2");
     }
 
+    /// Proof bytes with every commitment slot set to the G1 generator
+    #[test]
+    fn test_detect_format() {
+        let zk = vec![0u8; EXPECTED_ZK_PROOF_SIZE];
+        let non_zk = vec![0u8; EXPECTED_NON_ZK_PROOF_SIZE];
+        assert_eq!(
+            Proof::detect_format(&zk).unwrap(),
+            (true, ProofFormat::Bb087)
+        );
+        assert_eq!(
+            Proof::detect_format(&non_zk).unwrap(),
+            (false, ProofFormat::Bb087)
+        );
+        assert!(matches!(
+            Proof::detect_format(&zk[..100]),
+            Err(ProofError::InvalidSize { actual: 100, .. })
+        ));
+    }
+
+    fn proof_with_generator_points(is_zk: bool) -> Vec<u8> {
+        let mut bytes = vec![0u8; Proof::expected_size_bytes(is_zk)];
+        let slots = Proof::from_bytes(&bytes, 6, is_zk).unwrap().g1_slots();
+        for (_, _, offset, _) in slots {
+            bytes[offset + 31] = 1; // x_0 = 1
+            bytes[offset + 95] = 2; // y_0 = 2
+        }
+        bytes
+    }
+
+    fn slot_offset(bytes: &[u8], is_zk: bool, name: &str, index: usize) -> usize {
+        let proof = Proof::from_bytes(bytes, 6, is_zk).unwrap();
+        proof
+            .g1_slots()
+            .into_iter()
+            .find(|(n, i, _, _)| *n == name && *i == index)
+            .map(|(_, _, offset, _)| offset)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_validate_points_accepts_valid() {
+        for is_zk in [true, false] {
+            let bytes = proof_with_generator_points(is_zk);
+            let proof = Proof::from_bytes(&bytes, 6, is_zk).unwrap();
+            assert!(proof.validate_points().is_ok());
+            assert_eq!(proof.shplonk_q(), crate::types::G1_GENERATOR);
+        }
+    }
+
+    #[test]
+    fn test_validate_points_rejects_off_curve() {
+        let mut bytes = proof_with_generator_points(true);
+        let offset = slot_offset(&bytes, true, "witness_commitment", 3);
+        bytes[offset + 95] = 3; // (1, 3) is not on the curve
+
+        let proof = Proof::from_bytes(&bytes, 6, true).unwrap();
+        assert!(matches!(
+            proof.validate_points(),
+            Err(VerifyError::InvalidPoint {
+                commitment: "witness_commitment",
+                index: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_points_rejects_non_canonical_limbs() {
+        let mut bytes = proof_with_generator_points(true);
+        let offset = slot_offset(&bytes, true, "gemini_fold_commitment", 5);
+        // Garbage in the unused high bytes of x_0 would be dropped by
+        // g1_from_limbed but still change the transcript
+        bytes[offset] = 0xff;
+
+        let proof = Proof::from_bytes(&bytes, 6, true).unwrap();
+        assert!(matches!(
+            proof.validate_points(),
+            Err(VerifyError::InvalidPoint {
+                commitment: "gemini_fold_commitment",
+                index: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_points_identity_rules() {
+        // Identity is fine for a witness commitment
+        let mut bytes = proof_with_generator_points(true);
+        let offset = slot_offset(&bytes, true, "witness_commitment", 0);
+        bytes[offset..offset + G1_LIMBED_SIZE].fill(0);
+        let proof = Proof::from_bytes(&bytes, 6, true).unwrap();
+        assert!(proof.validate_points().is_ok());
+
+        // ...but not for the KZG quotient
+        let mut bytes = proof_with_generator_points(true);
+        let offset = slot_offset(&bytes, true, "kzg_quotient", 0);
+        bytes[offset..offset + G1_LIMBED_SIZE].fill(0);
+        let proof = Proof::from_bytes(&bytes, 6, true).unwrap();
+        assert!(matches!(
+            proof.validate_points(),
+            Err(VerifyError::InvalidPoint {
+                commitment: "kzg_quotient",
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_proof_parse_zk() {
         // Create a minimal ZK proof of correct size
@@ -708,6 +1019,32 @@ mod tests {
         assert_eq!(proof.log_n, 12);
     }
 
+    #[test]
+    fn test_proof_variants() {
+        let zk = vec![0u8; EXPECTED_ZK_PROOF_SIZE];
+        let non_zk = vec![0u8; EXPECTED_NON_ZK_PROOF_SIZE];
+        assert_eq!(
+            ProofVariant::detect(&zk).unwrap(),
+            ProofVariant::UltraKeccakZk
+        );
+        assert_eq!(
+            ProofVariant::detect(&non_zk).unwrap(),
+            ProofVariant::UltraKeccak
+        );
+        assert!(ProofVariant::detect(&zk[1..]).is_err());
+
+        let proof = Proof::from_bytes_variant(&zk, 12, ProofVariant::UltraKeccakZk).unwrap();
+        assert!(proof.is_zk);
+        assert_eq!(proof.variant(), ProofVariant::UltraKeccakZk);
+
+        for variant in [ProofVariant::UltraZkIpa, ProofVariant::UltraStarknetZk] {
+            assert!(variant.is_zk() && !variant.is_supported());
+            let err = Proof::from_bytes_variant(&zk, 12, variant).unwrap_err();
+            assert!(matches!(err, ProofError::UnsupportedVariant(v) if v == variant));
+            assert!(err.to_string().contains(variant.name()));
+        }
+    }
+
     #[test]
     fn test_proof_wrong_size() {
         let proof_bytes = vec![0u8; 1000];
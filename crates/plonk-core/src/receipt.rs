@@ -0,0 +1,81 @@
+//! Public input hashing for verification receipts
+//!
+//! Receipt PDAs are derived from `["receipt", vk_account, keccak(public_inputs)]`.
+//! The seeds and hash come from `solana-noir-receipt-seeds`, which the
+//! verifier program, the CPI crate and the SDK share; this module adds an
+//! incremental hasher for public inputs spread across accounts.
+
+use alloc::vec::Vec;
+
+pub use solana_noir_receipt_seeds::{pi_hash as public_inputs_hash, RECEIPT_SEED};
+
+/// Incremental public input hasher
+///
+/// Collects byte slices in order and hashes their concatenation on
+/// [`PiHasher::finalize`], so public inputs assembled from several accounts
+/// never need to be copied into one buffer. Slices are borrowed until then.
+///
+/// ```
+/// use plonk_solana_core::receipt::{public_inputs_hash, PiHasher};
+///
+/// let (a, b) = ([1u8; 32], [2u8; 64]);
+/// let mut hasher = PiHasher::new();
+/// hasher.update(&a).update(&b);
+/// assert_eq!(hasher.finalize(), public_inputs_hash(&[a.as_slice(), &b].concat()));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PiHasher<'a> {
+    parts: Vec<&'a [u8]>,
+}
+
+impl<'a> PiHasher<'a> {
+    /// Create an empty hasher
+    pub fn new() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    /// Append raw public input bytes
+    pub fn update(&mut self, data: &'a [u8]) -> &mut Self {
+        self.parts.push(data);
+        self
+    }
+
+    /// Number of bytes absorbed so far
+    pub fn len(&self) -> usize {
+        self.parts.iter().map(|p| p.len()).sum()
+    }
+
+    /// True if nothing has been absorbed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keccak256 of everything absorbed, equal to [`public_inputs_hash`] of
+    /// the concatenation
+    pub fn finalize(&self) -> [u8; 32] {
+        solana_noir_receipt_seeds::pi_hashv(&self.parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pi_hasher_matches_one_shot() {
+        let inputs: Vec<u8> = (0..96u8).collect();
+
+        let mut hasher = PiHasher::new();
+        hasher
+            .update(&inputs[..10])
+            .update(&[])
+            .update(&inputs[10..64])
+            .update(&inputs[64..]);
+        assert_eq!(hasher.len(), 96);
+        assert_eq!(hasher.finalize(), public_inputs_hash(&inputs));
+
+        // Empty input hashes like an empty slice, not like a zero field
+        assert_eq!(PiHasher::new().finalize(), public_inputs_hash(&[]));
+        assert_ne!(PiHasher::new().finalize(), public_inputs_hash(&[0u8; 32]));
+    }
+}
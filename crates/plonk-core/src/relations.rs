@@ -270,7 +270,7 @@ fn pow5_l(x: &FrLimbs) -> FrLimbs {
 /// Accumulate arithmetic subrelations (FrLimbs version)
 /// Correct port of Fr version
 #[inline(never)]
-fn accumulate_arithmetic_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
+fn accumulate_arithmetic_l(evals: &[FrLimbs], out: &mut [FrLimbs]) {
     let q_arith = wire_l(evals, Wire::QArith);
     let q_m = wire_l(evals, Wire::Qm);
     let w_l = wire_l(evals, Wire::Wl);
@@ -299,15 +299,14 @@ fn accumulate_arithmetic_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs)
     acc = acc.add(&q_4.mul(w_4));
     acc = acc.add(q_c);
 
-    // (acc + (q_arith - 1) * w_4_shift) * q_arith * d
+    // (acc + (q_arith - 1) * w_4_shift) * q_arith
     let q_minus_1 = q_arith.sub(&FrLimbs::ONE);
     let term = q_minus_1.mul(w_4_shift);
     acc = acc.add(&term);
     acc = acc.mul(q_arith);
-    acc = acc.mul(d);
     out[0] = acc;
 
-    // Subrelation 1: (w_l + w_4 - w_l_shift + q_m) * (q_arith - 2) * (q_arith - 1) * q_arith * d
+    // Subrelation 1: (w_l + w_4 - w_l_shift + q_m) * (q_arith - 2) * (q_arith - 1) * q_arith
     let mut acc1 = w_l.add(w_4);
     acc1 = acc1.sub(w_l_shift);
     acc1 = acc1.add(q_m);
@@ -316,18 +315,12 @@ fn accumulate_arithmetic_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs)
     acc1 = acc1.mul(&q_minus_2);
     acc1 = acc1.mul(&q_minus_1);
     acc1 = acc1.mul(q_arith);
-    acc1 = acc1.mul(d);
     out[1] = acc1;
 }
 
 /// Accumulate permutation subrelations (FrLimbs version)
 #[inline(never)]
-fn accumulate_permutation_l(
-    evals: &[FrLimbs],
-    rp: &RelationParametersLimbs,
-    out: &mut [FrLimbs],
-    d: &FrLimbs,
-) {
+fn accumulate_permutation_l(evals: &[FrLimbs], rp: &RelationParametersLimbs, out: &mut [FrLimbs]) {
     let w_l = wire_l(evals, Wire::Wl);
     let w_r = wire_l(evals, Wire::Wr);
     let w_o = wire_l(evals, Wire::Wo);
@@ -364,21 +357,16 @@ fn accumulate_permutation_l(
     let lhs = z_perm.add(lag_first).mul(&numerator);
     let delta_term = lag_last.mul(&rp.public_inputs_delta);
     let rhs = z_perm_shift.add(&delta_term).mul(&denominator);
-    out[2] = lhs.sub(&rhs).mul(d);
+    out[2] = lhs.sub(&rhs);
 
     // Subrelation 3: lag_last * z_perm_shift
-    out[3] = lag_last.mul(z_perm_shift).mul(d);
+    out[3] = lag_last.mul(z_perm_shift);
 }
 
 /// Accumulate lookup subrelations (FrLimbs version)
 /// Correct port of Fr version
 #[inline(never)]
-fn accumulate_lookup_l(
-    evals: &[FrLimbs],
-    rp: &RelationParametersLimbs,
-    out: &mut [FrLimbs],
-    d: &FrLimbs,
-) {
+fn accumulate_lookup_l(evals: &[FrLimbs], rp: &RelationParametersLimbs, out: &mut [FrLimbs]) {
     let w_l = wire_l(evals, Wire::Wl);
     let w_r = wire_l(evals, Wire::Wr);
     let w_o = wire_l(evals, Wire::Wo);
@@ -423,9 +411,9 @@ fn accumulate_lookup_l(
         .add(q_lookup)
         .sub(&lookup_read_tags.mul(q_lookup));
 
-    // Subrelation 4: (read_term * write_term * inv - inv_exists) * d
+    // Subrelation 4: read_term * write_term * inv - inv_exists
     let product = read_term.mul(&write_term).mul(lookup_inv);
-    out[4] = product.sub(&inv_exists).mul(d);
+    out[4] = product.sub(&inv_exists);
 
     // Subrelation 5: q_lookup * (write_term * inv) - read_counts * (read_term * inv)
     let read_inverse = lookup_inv.mul(&write_term);
@@ -439,7 +427,7 @@ fn accumulate_lookup_l(
 
 /// Accumulate range/delta subrelations (FrLimbs version)
 #[inline(never)]
-fn accumulate_range_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
+fn accumulate_range_l(evals: &[FrLimbs], out: &mut [FrLimbs]) {
     let w_l = wire_l(evals, Wire::Wl);
     let w_r = wire_l(evals, Wire::Wr);
     let w_o = wire_l(evals, Wire::Wo);
@@ -472,16 +460,16 @@ fn accumulate_range_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
         .mul(&delta_4.sub(&L_TWO))
         .mul(&delta_4.sub(&L_THREE));
 
-    out[6] = check1.mul(q_range).mul(d);
-    out[7] = check2.mul(q_range).mul(d);
-    out[8] = check3.mul(q_range).mul(d);
-    out[9] = check4.mul(q_range).mul(d);
+    out[6] = check1.mul(q_range);
+    out[7] = check2.mul(q_range);
+    out[8] = check3.mul(q_range);
+    out[9] = check4.mul(q_range);
 }
 
 /// Accumulate elliptic subrelations (FrLimbs version)
 /// Correct port of Fr version with proper wire mappings
 #[inline(never)]
-fn accumulate_elliptic_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
+fn accumulate_elliptic_l(evals: &[FrLimbs], out: &mut [FrLimbs]) {
     // Wire mappings from Fr version (DIFFERENT from typical x1=w_l etc!)
     let x1 = wire_l(evals, Wire::Wr); // w_r
     let y1 = wire_l(evals, Wire::Wo); // w_o
@@ -527,8 +515,8 @@ fn accumulate_elliptic_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
     let y_double_id = x1_sq_3.mul(&x1.sub(x3)).sub(&y1_2.mul(&y1.add(y3)));
 
     // Combine with selectors
-    let add_factor = FrLimbs::ONE.sub(q_double).mul(q_elliptic).mul(d);
-    let double_factor = q_double.mul(q_elliptic).mul(d);
+    let add_factor = FrLimbs::ONE.sub(q_double).mul(q_elliptic);
+    let double_factor = q_double.mul(q_elliptic);
 
     out[10] = x_add_id
         .mul(&add_factor)
@@ -541,12 +529,7 @@ fn accumulate_elliptic_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
 /// Accumulate auxiliary (memory) subrelations (FrLimbs version)
 /// Full port of the Fr version - matches exactly
 #[inline(never)]
-fn accumulate_aux_l(
-    evals: &[FrLimbs],
-    rp: &RelationParametersLimbs,
-    out: &mut [FrLimbs],
-    d: &FrLimbs,
-) {
+fn accumulate_aux_l(evals: &[FrLimbs], rp: &RelationParametersLimbs, out: &mut [FrLimbs]) {
     let w_l = wire_l(evals, Wire::Wl);
     let w_r = wire_l(evals, Wire::Wr);
     let w_o = wire_l(evals, Wire::Wo);
@@ -643,7 +626,7 @@ fn accumulate_aux_l(
     let adjacent_values_match = neg_index_plus_one.mul(&record_delta);
 
     // ROM selector: q_l * q_r * q_aux * domainSep
-    let rom_selector = q_l.mul(q_r).mul(&q_aux.mul(d));
+    let rom_selector = q_l.mul(q_r).mul(q_aux);
 
     // Subrel[13]: adjacent_values_match * rom_selector
     out[13] = adjacent_values_match.mul(&rom_selector);
@@ -678,7 +661,7 @@ fn accumulate_aux_l(
         next_gate_access_type.mul(&next_gate_access_type.sub(&FrLimbs::ONE));
 
     // RAM selector: q_arith * q_aux * domainSep (NOT q_o!)
-    let ram_selector = q_arith.mul(&q_aux.mul(d));
+    let ram_selector = q_arith.mul(q_aux);
 
     // Subrel[15]: ram_adjacent * ram_selector
     out[15] = ram_adjacent.mul(&ram_selector);
@@ -710,12 +693,12 @@ fn accumulate_aux_l(
     let auxiliary_identity = memory_identity
         .add(&non_native_field_identity)
         .add(&limb_accumulator_identity);
-    out[12] = auxiliary_identity.mul(&q_aux.mul(d));
+    out[12] = auxiliary_identity.mul(q_aux);
 }
 
 /// Accumulate Poseidon external subrelations (FrLimbs version)
 #[inline(never)]
-fn accumulate_poseidon_external_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
+fn accumulate_poseidon_external_l(evals: &[FrLimbs], out: &mut [FrLimbs]) {
     let w_l = wire_l(evals, Wire::Wl);
     let w_r = wire_l(evals, Wire::Wr);
     let w_o = wire_l(evals, Wire::Wo);
@@ -757,15 +740,15 @@ fn accumulate_poseidon_external_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &Fr
     let v3 = t2.add(&v4);
 
     // External subrelations (indices 18-21 in bb 0.87)
-    out[18] = v1.sub(w_l_shift).mul(q_pos_ext).mul(d);
-    out[19] = v2.sub(w_r_shift).mul(q_pos_ext).mul(d);
-    out[20] = v3.sub(w_o_shift).mul(q_pos_ext).mul(d);
-    out[21] = v4.sub(w_4_shift).mul(q_pos_ext).mul(d);
+    out[18] = v1.sub(w_l_shift).mul(q_pos_ext);
+    out[19] = v2.sub(w_r_shift).mul(q_pos_ext);
+    out[20] = v3.sub(w_o_shift).mul(q_pos_ext);
+    out[21] = v4.sub(w_4_shift).mul(q_pos_ext);
 }
 
 /// Accumulate Poseidon internal subrelations (FrLimbs version)
 #[inline(never)]
-fn accumulate_poseidon_internal_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &FrLimbs) {
+fn accumulate_poseidon_internal_l(evals: &[FrLimbs], out: &mut [FrLimbs]) {
     let q_pos_int = wire_l(evals, Wire::QPoseidon2Internal);
     let w_l = wire_l(evals, Wire::Wl);
     let w_r = wire_l(evals, Wire::Wr);
@@ -792,25 +775,152 @@ fn accumulate_poseidon_internal_l(evals: &[FrLimbs], out: &mut [FrLimbs], d: &Fr
     let v4 = u4.mul(&L_POSEIDON_DIAG_3).add(&u_sum);
 
     // Internal subrelations (indices 22-25 in bb 0.87)
-    out[22] = q_pos_int.mul(&v1.sub(w_l_shift)).mul(d);
-    out[23] = q_pos_int.mul(&v2.sub(w_r_shift)).mul(d);
-    out[24] = q_pos_int.mul(&v3.sub(w_o_shift)).mul(d);
-    out[25] = q_pos_int.mul(&v4.sub(w_4_shift)).mul(d);
+    out[22] = q_pos_int.mul(&v1.sub(w_l_shift));
+    out[23] = q_pos_int.mul(&v2.sub(w_r_shift));
+    out[24] = q_pos_int.mul(&v3.sub(w_o_shift));
+    out[25] = q_pos_int.mul(&v4.sub(w_4_shift));
 }
 
-/// Batch all subrelations with alpha challenges (FrLimbs version)
-fn batch_subrelations_l(evals: &[FrLimbs], alphas: &[FrLimbs]) -> FrLimbs {
+/// The one subrelation not scaled by the pow factor (lookup inverses)
+const UNSCALED_SUBRELATION: usize = 5;
+
+/// Batch all subrelations with alpha challenges and the pow scaling factor
+/// `d` (FrLimbs version)
+///
+/// The FrLimbs accumulators leave `d` out. Every subrelation except
+/// [`UNSCALED_SUBRELATION`] carries it, so it is applied once to their
+/// batched sum rather than once per subrelation.
+fn batch_subrelations_l(evals: &[FrLimbs], alphas: &[FrLimbs], d: &FrLimbs) -> FrLimbs {
     let mut acc = evals[0];
     for (i, alpha) in alphas.iter().enumerate() {
         if i >= evals.len() - 1 {
             break;
         }
+        if i + 1 == UNSCALED_SUBRELATION {
+            continue;
+        }
         let term = evals[i + 1].mul(alpha);
         acc = acc.add(&term);
     }
+    acc = acc.mul(d);
+    if let Some(alpha) = alphas.get(UNSCALED_SUBRELATION - 1) {
+        acc = acc.add(&evals[UNSCALED_SUBRELATION].mul(alpha));
+    }
     acc
 }
 
+/// One batching coefficient per subrelation, the alphas with the pow factor
+/// folded in (see [`relation_coefficients_l`])
+pub type RelationCoefficients = [FrLimbs; NUM_SUBRELATIONS];
+
+/// Size of [`RelationCoefficients`] as raw Montgomery bytes
+pub const RELATION_COEFFICIENTS_SIZE: usize = NUM_SUBRELATIONS * 32;
+
+/// Fold the alphas and the pow scaling factor `d` into one coefficient per
+/// subrelation
+///
+/// `c[0] = d` and `c[i] = alpha[i-1]·d`, except for
+/// [`UNSCALED_SUBRELATION`], whose coefficient is its alpha alone. Batching
+/// with them gives the same sum as [`batch_subrelations_l`]. They depend
+/// only on challenges, so a client can compute them off chain and pass them
+/// to the relations instructions, which then skip converting the alphas and
+/// `d` once their [`relation_coefficients_commitment`] checks out.
+pub fn relation_coefficients_l(alphas: &[FrLimbs], d: &FrLimbs) -> RelationCoefficients {
+    let mut coeffs = [FrLimbs::ZERO; NUM_SUBRELATIONS];
+    coeffs[0] = *d;
+    for (i, alpha) in alphas.iter().take(NUMBER_OF_ALPHAS).enumerate() {
+        coeffs[i + 1] = if i + 1 == UNSCALED_SUBRELATION {
+            *alpha
+        } else {
+            alpha.mul(d)
+        };
+    }
+    coeffs
+}
+
+/// [`relation_coefficients_l`] from the alphas and pow factor as the
+/// verification state stores them
+pub fn relation_coefficients(alphas: &[Fr], pow_partial: &Fr) -> RelationCoefficients {
+    let alphas_l: alloc::vec::Vec<FrLimbs> = alphas.iter().map(FrLimbs::from_bytes).collect();
+    relation_coefficients_l(&alphas_l, &FrLimbs::from_bytes(pow_partial))
+}
+
+/// Raw Montgomery bytes of `coeffs`, as the relations instructions take them
+pub fn relation_coefficients_to_bytes(
+    coeffs: &RelationCoefficients,
+) -> [u8; RELATION_COEFFICIENTS_SIZE] {
+    let mut bytes = [0u8; RELATION_COEFFICIENTS_SIZE];
+    for (chunk, coeff) in bytes.chunks_exact_mut(32).zip(coeffs) {
+        chunk.copy_from_slice(&coeff.to_raw_bytes());
+    }
+    bytes
+}
+
+/// Inverse of [`relation_coefficients_to_bytes`]; `None` unless `bytes` is
+/// [`RELATION_COEFFICIENTS_SIZE`] long
+///
+/// No conversion out of Montgomery form, so the bytes must come from a
+/// trusted source or match a recorded [`relation_coefficients_commitment`].
+pub fn relation_coefficients_from_bytes(bytes: &[u8]) -> Option<RelationCoefficients> {
+    if bytes.len() != RELATION_COEFFICIENTS_SIZE {
+        return None;
+    }
+    let mut coeffs = [FrLimbs::ZERO; NUM_SUBRELATIONS];
+    for (coeff, chunk) in coeffs.iter_mut().zip(bytes.chunks_exact(32)) {
+        *coeff = FrLimbs::from_raw_bytes(chunk.try_into().ok()?);
+    }
+    Some(coeffs)
+}
+
+/// Keccak256 of [`relation_coefficients_to_bytes`]
+pub fn relation_coefficients_commitment(coeffs: &RelationCoefficients) -> [u8; 32] {
+    crate::transcript::keccak256(&relation_coefficients_to_bytes(coeffs))
+}
+
+/// Batch all subrelations with precomputed [`RelationCoefficients`]
+fn batch_with_coefficients_l(evals: &[FrLimbs], coeffs: &RelationCoefficients) -> FrLimbs {
+    evals
+        .iter()
+        .zip(coeffs)
+        .fold(FrLimbs::ZERO, |acc, (eval, coeff)| {
+            acc.add(&eval.mul(coeff))
+        })
+}
+
+/// Which subrelations an accumulator evaluates
+#[derive(Clone, Copy)]
+enum Subrelations {
+    All,
+    /// Arithmetic, permutation, range and elliptic
+    Part1,
+    /// Lookup, memory and Poseidon
+    Part2,
+}
+
+/// Evaluate `which` subrelations, unbatched and without the pow factor;
+/// the others are left zero
+fn evaluate_subrelations_l(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    which: Subrelations,
+) -> [FrLimbs; NUM_SUBRELATIONS] {
+    let mut out = [FrLimbs::ZERO; NUM_SUBRELATIONS];
+
+    if !matches!(which, Subrelations::Part2) {
+        accumulate_arithmetic_l(evals, &mut out);
+        accumulate_permutation_l(evals, rp, &mut out);
+        accumulate_range_l(evals, &mut out);
+        accumulate_elliptic_l(evals, &mut out);
+    }
+    if !matches!(which, Subrelations::Part1) {
+        accumulate_lookup_l(evals, rp, &mut out);
+        accumulate_aux_l(evals, rp, &mut out);
+        accumulate_poseidon_external_l(evals, &mut out);
+        accumulate_poseidon_internal_l(evals, &mut out);
+    }
+    out
+}
+
 /// Accumulate all relation evaluations (FrLimbs version - main entry point)
 ///
 /// This is the optimized FrLimbs version that avoids byte conversions.
@@ -822,18 +932,170 @@ pub fn accumulate_relation_evaluations_l(
     alphas: &[FrLimbs],
     pow_partial: &FrLimbs,
 ) -> FrLimbs {
-    let mut out = [FrLimbs::ZERO; NUM_SUBRELATIONS];
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::All);
+    batch_subrelations_l(&out, alphas, pow_partial)
+}
+
+/// First half of [`accumulate_relation_evaluations_l`]
+///
+/// Arithmetic, permutation, range and elliptic subrelations. Batching with
+/// alphas is linear, so `part1 + part2` equals the full accumulation and the
+/// two halves can run in separate transactions.
+#[inline(never)]
+pub fn accumulate_relation_evaluations_l_part1(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    alphas: &[FrLimbs],
+    pow_partial: &FrLimbs,
+) -> FrLimbs {
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::Part1);
+    batch_subrelations_l(&out, alphas, pow_partial)
+}
+
+/// Second half of [`accumulate_relation_evaluations_l`]
+///
+/// Lookup, memory and Poseidon subrelations, the more expensive group.
+#[inline(never)]
+pub fn accumulate_relation_evaluations_l_part2(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    alphas: &[FrLimbs],
+    pow_partial: &FrLimbs,
+) -> FrLimbs {
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::Part2);
+    batch_subrelations_l(&out, alphas, pow_partial)
+}
 
-    accumulate_arithmetic_l(evals, &mut out, pow_partial);
-    accumulate_permutation_l(evals, rp, &mut out, pow_partial);
-    accumulate_lookup_l(evals, rp, &mut out, pow_partial);
-    accumulate_range_l(evals, &mut out, pow_partial);
-    accumulate_elliptic_l(evals, &mut out, pow_partial);
-    accumulate_aux_l(evals, rp, &mut out, pow_partial);
-    accumulate_poseidon_external_l(evals, &mut out, pow_partial);
-    accumulate_poseidon_internal_l(evals, &mut out, pow_partial);
+/// [`accumulate_relation_evaluations_l`] batched with precomputed
+/// [`RelationCoefficients`]
+#[inline(never)]
+pub fn accumulate_relation_evaluations_l_with_coefficients(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    coeffs: &RelationCoefficients,
+) -> FrLimbs {
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::All);
+    batch_with_coefficients_l(&out, coeffs)
+}
+
+/// [`accumulate_relation_evaluations_l_part1`] batched with precomputed
+/// [`RelationCoefficients`]
+#[inline(never)]
+pub fn accumulate_relation_evaluations_l_part1_with_coefficients(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    coeffs: &RelationCoefficients,
+) -> FrLimbs {
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::Part1);
+    batch_with_coefficients_l(&out, coeffs)
+}
+
+/// [`accumulate_relation_evaluations_l_part2`] batched with precomputed
+/// [`RelationCoefficients`]
+#[inline(never)]
+pub fn accumulate_relation_evaluations_l_part2_with_coefficients(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    coeffs: &RelationCoefficients,
+) -> FrLimbs {
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::Part2);
+    batch_with_coefficients_l(&out, coeffs)
+}
 
-    batch_subrelations_l(&out, alphas)
+/// A relation and the subrelations it contributes to the batched sum
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RelationGroup {
+    Arithmetic,
+    Permutation,
+    Lookup,
+    Range,
+    Elliptic,
+    Memory,
+    PoseidonExternal,
+    PoseidonInternal,
+}
+
+impl RelationGroup {
+    /// Every group, in subrelation order
+    pub const ALL: [Self; 8] = [
+        Self::Arithmetic,
+        Self::Permutation,
+        Self::Lookup,
+        Self::Range,
+        Self::Elliptic,
+        Self::Memory,
+        Self::PoseidonExternal,
+        Self::PoseidonInternal,
+    ];
+
+    /// Indices of this group's subrelations (bb 0.87)
+    pub const fn subrelations(self) -> core::ops::Range<usize> {
+        match self {
+            Self::Arithmetic => 0..2,
+            Self::Permutation => 2..4,
+            Self::Lookup => 4..6,
+            Self::Range => 6..10,
+            Self::Elliptic => 10..12,
+            Self::Memory => 12..18,
+            Self::PoseidonExternal => 18..22,
+            Self::PoseidonInternal => 22..26,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Arithmetic => "arithmetic",
+            Self::Permutation => "permutation",
+            Self::Lookup => "lookup",
+            Self::Range => "range",
+            Self::Elliptic => "elliptic",
+            Self::Memory => "memory",
+            Self::PoseidonExternal => "poseidon external",
+            Self::PoseidonInternal => "poseidon internal",
+        }
+    }
+}
+
+impl core::fmt::Display for RelationGroup {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Each [`RelationGroup`]'s share of [`accumulate_relation_evaluations_l`],
+/// in [`RelationGroup::ALL`] order
+///
+/// Batches the same subrelations with the same alphas and pow factor, one
+/// group at a time, so the shares sum to the full accumulation. Only built
+/// on chain with the `diagnostics` feature.
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+#[inline(never)]
+pub fn relation_group_contributions_l(
+    evals: &[FrLimbs],
+    rp: &RelationParametersLimbs,
+    alphas: &[FrLimbs],
+    pow_partial: &FrLimbs,
+) -> [FrLimbs; RelationGroup::ALL.len()] {
+    let out = evaluate_subrelations_l(evals, rp, Subrelations::All);
+
+    RelationGroup::ALL.map(|group| {
+        let mut acc = FrLimbs::ZERO;
+        for i in group.subrelations() {
+            let mut term = out[i];
+            if i > 0 {
+                // Subrelations past the last alpha are not batched
+                let Some(alpha) = alphas.get(i - 1) else {
+                    break;
+                };
+                term = term.mul(alpha);
+            }
+            if i != UNSCALED_SUBRELATION {
+                term = term.mul(pow_partial);
+            }
+            acc = acc.add(&term);
+        }
+        acc
+    })
 }
 
 /// Accumulate arithmetic subrelations (indices 0-1)
@@ -1057,7 +1319,7 @@ fn accumulate_lookup(evals: &[Fr], rp: &RelationParameters, out: &mut [Fr], d: &
         &fr_mul(&lookup_read_tags, &q_lookup),
     );
 
-    // Subrelation 4: (read_term * write_term * inv - inv_exists) * d
+    // Subrelation 4: read_term * write_term * inv - inv_exists
     let product = fr_mul(&fr_mul(&read_term, &write_term), &lookup_inv);
     out[4] = fr_mul(&fr_sub(&product, &inv_exists), d);
 
@@ -1694,18 +1956,24 @@ mod tests {
 
             // Run FrLimbs version and capture intermediate results
             let mut out_l = [FrLimbs::ZERO; NUM_SUBRELATIONS];
-            accumulate_arithmetic_l(&evals_l, &mut out_l, &pow_partial_l);
-            accumulate_permutation_l(&evals_l, &rp_l, &mut out_l, &pow_partial_l);
-            accumulate_lookup_l(&evals_l, &rp_l, &mut out_l, &pow_partial_l);
-            accumulate_range_l(&evals_l, &mut out_l, &pow_partial_l);
-            accumulate_elliptic_l(&evals_l, &mut out_l, &pow_partial_l);
-            accumulate_aux_l(&evals_l, &rp_l, &mut out_l, &pow_partial_l);
-            accumulate_poseidon_external_l(&evals_l, &mut out_l, &pow_partial_l);
-            accumulate_poseidon_internal_l(&evals_l, &mut out_l, &pow_partial_l);
+            accumulate_arithmetic_l(&evals_l, &mut out_l);
+            accumulate_permutation_l(&evals_l, &rp_l, &mut out_l);
+            accumulate_lookup_l(&evals_l, &rp_l, &mut out_l);
+            accumulate_range_l(&evals_l, &mut out_l);
+            accumulate_elliptic_l(&evals_l, &mut out_l);
+            accumulate_aux_l(&evals_l, &rp_l, &mut out_l);
+            accumulate_poseidon_external_l(&evals_l, &mut out_l);
+            accumulate_poseidon_internal_l(&evals_l, &mut out_l);
 
             // Compare each subrelation
+            // The FrLimbs accumulators leave the pow factor to the batching
             for i in 0..NUM_SUBRELATIONS {
-                let out_l_fr = out_l[i].to_bytes();
+                let scaled = if i == UNSCALED_SUBRELATION {
+                    out_l[i]
+                } else {
+                    out_l[i].mul(&pow_partial_l)
+                };
+                let out_l_fr = scaled.to_bytes();
                 if out_fr[i] != out_l_fr {
                     println!("MISMATCH subrel[{}]:", i);
                     println!("  Fr:     {:02x?}", &out_fr[i]);
@@ -1719,6 +1987,132 @@ mod tests {
         assert_eq!(result_fr, result_l_fr, "Fr and FrLimbs results must match");
     }
 
+    #[test]
+    fn test_relation_halves_sum_to_full() {
+        let evals: Vec<FrLimbs> = (0..40)
+            .map(|i| FrLimbs::from_bytes(&fr_from_u64((i + 3) as u64 * 31)))
+            .collect();
+        let rp = RelationParametersLimbs::from_fr(&RelationParameters {
+            eta: fr_from_u64(5),
+            eta_two: fr_from_u64(25),
+            eta_three: fr_from_u64(125),
+            beta: fr_from_u64(77),
+            gamma: fr_from_u64(88),
+            public_inputs_delta: fr_from_u64(99),
+        });
+        let alphas: Vec<FrLimbs> = (0..NUMBER_OF_ALPHAS)
+            .map(|i| FrLimbs::from_bytes(&fr_from_u64((i + 2) as u64 * 13)))
+            .collect();
+        let pow_partial = FrLimbs::from_bytes(&fr_from_u64(4242));
+
+        let full = accumulate_relation_evaluations_l(&evals, &rp, &alphas, &pow_partial);
+        let part1 = accumulate_relation_evaluations_l_part1(&evals, &rp, &alphas, &pow_partial);
+        let part2 = accumulate_relation_evaluations_l_part2(&evals, &rp, &alphas, &pow_partial);
+
+        assert_eq!(part1.add(&part2).to_bytes(), full.to_bytes());
+        assert_ne!(part1.to_bytes(), full.to_bytes());
+    }
+
+    #[test]
+    fn test_coefficients_match_factored_batching() {
+        let evals: Vec<FrLimbs> = (0..40)
+            .map(|i| FrLimbs::from_bytes(&fr_from_u64((i + 7) as u64 * 23)))
+            .collect();
+        let rp = RelationParametersLimbs::from_fr(&RelationParameters {
+            eta: fr_from_u64(6),
+            eta_two: fr_from_u64(36),
+            eta_three: fr_from_u64(216),
+            beta: fr_from_u64(51),
+            gamma: fr_from_u64(53),
+            public_inputs_delta: fr_from_u64(59),
+        });
+        let alphas_fr: Vec<Fr> = (0..NUMBER_OF_ALPHAS)
+            .map(|i| fr_from_u64((i + 3) as u64 * 17))
+            .collect();
+        let pow_fr = fr_from_u64(31337);
+        let alphas: Vec<FrLimbs> = alphas_fr.iter().map(FrLimbs::from_bytes).collect();
+        let pow_partial = FrLimbs::from_bytes(&pow_fr);
+
+        let coeffs = relation_coefficients(&alphas_fr, &pow_fr);
+        assert_eq!(coeffs, relation_coefficients_l(&alphas, &pow_partial));
+        assert_eq!(coeffs[0], pow_partial);
+        assert_eq!(coeffs[1], alphas[0].mul(&pow_partial));
+        assert_eq!(
+            coeffs[UNSCALED_SUBRELATION],
+            alphas[UNSCALED_SUBRELATION - 1]
+        );
+
+        let pairs = [
+            (
+                accumulate_relation_evaluations_l(&evals, &rp, &alphas, &pow_partial),
+                accumulate_relation_evaluations_l_with_coefficients(&evals, &rp, &coeffs),
+            ),
+            (
+                accumulate_relation_evaluations_l_part1(&evals, &rp, &alphas, &pow_partial),
+                accumulate_relation_evaluations_l_part1_with_coefficients(&evals, &rp, &coeffs),
+            ),
+            (
+                accumulate_relation_evaluations_l_part2(&evals, &rp, &alphas, &pow_partial),
+                accumulate_relation_evaluations_l_part2_with_coefficients(&evals, &rp, &coeffs),
+            ),
+        ];
+        for (factored, precomputed) in pairs {
+            assert_eq!(factored.to_bytes(), precomputed.to_bytes());
+        }
+
+        // The raw bytes round-trip and the commitment covers every coefficient
+        let bytes = relation_coefficients_to_bytes(&coeffs);
+        assert_eq!(relation_coefficients_from_bytes(&bytes), Some(coeffs));
+        assert_eq!(relation_coefficients_from_bytes(&bytes[1..]), None);
+        let mut tampered = coeffs;
+        tampered[UNSCALED_SUBRELATION] = tampered[UNSCALED_SUBRELATION].mul(&pow_partial);
+        assert_ne!(
+            relation_coefficients_commitment(&tampered),
+            relation_coefficients_commitment(&coeffs)
+        );
+    }
+
+    #[test]
+    fn test_group_contributions_sum_to_full() {
+        let evals: Vec<FrLimbs> = (0..40)
+            .map(|i| FrLimbs::from_bytes(&fr_from_u64((i + 5) as u64 * 19)))
+            .collect();
+        let rp = RelationParametersLimbs::from_fr(&RelationParameters {
+            eta: fr_from_u64(3),
+            eta_two: fr_from_u64(9),
+            eta_three: fr_from_u64(27),
+            beta: fr_from_u64(41),
+            gamma: fr_from_u64(43),
+            public_inputs_delta: fr_from_u64(47),
+        });
+        let alphas: Vec<FrLimbs> = (0..NUMBER_OF_ALPHAS)
+            .map(|i| FrLimbs::from_bytes(&fr_from_u64((i + 1) as u64 * 11)))
+            .collect();
+        let pow_partial = FrLimbs::from_bytes(&fr_from_u64(777));
+
+        let full = accumulate_relation_evaluations_l(&evals, &rp, &alphas, &pow_partial);
+        let groups = relation_group_contributions_l(&evals, &rp, &alphas, &pow_partial);
+        let sum = groups.iter().fold(FrLimbs::ZERO, |acc, g| acc.add(g));
+        assert_eq!(sum.to_bytes(), full.to_bytes());
+
+        // The groups tile the subrelations in order
+        let mut next = 0;
+        for group in RelationGroup::ALL {
+            assert_eq!(group.subrelations().start, next, "{group}");
+            next = group.subrelations().end;
+        }
+        assert_eq!(next, NUM_SUBRELATIONS);
+
+        // Zeroing the Poseidon internal selector only moves that group
+        let mut evals = evals;
+        evals[Wire::QPoseidon2Internal as usize] = FrLimbs::ZERO;
+        let changed = relation_group_contributions_l(&evals, &rp, &alphas, &pow_partial);
+        for (i, group) in RelationGroup::ALL.iter().enumerate() {
+            let moved = changed[i].to_bytes() != groups[i].to_bytes();
+            assert_eq!(moved, *group == RelationGroup::PoseidonInternal, "{group}");
+        }
+    }
+
     #[test]
     #[ignore] // Run with: cargo test -p plonk-solana-core generate_frlimbs_constants -- --ignored --nocapture
     fn generate_frlimbs_constants() {
@@ -0,0 +1,51 @@
+//! Scratch buffers for per-round tables
+//!
+//! The default SBF allocator is a 32 KB bump allocator that never frees, so
+//! every temporary `Vec` in a phase counts against the heap until the
+//! instruction ends. With the `fixed-buffers` feature, tables of at most
+//! `CONST_PROOF_SIZE_LOG_N` elements (Gemini evaluations, sumcheck
+//! challenges, fold scalars) are kept inline on the stack instead.
+//!
+//! One inline table of `FrLimbs` is about 900 bytes, so a phase keeps at
+//! most two of them live to stay well inside a 4 KB stack frame. Larger
+//! buffers, such as the P0 MSM terms and batch-inversion inputs, stay on the
+//! heap.
+
+#[cfg(feature = "fixed-buffers")]
+use crate::proof::CONST_PROOF_SIZE_LOG_N;
+
+/// A table with one entry per sumcheck round
+#[cfg(feature = "fixed-buffers")]
+pub type RoundBuf<T> = smallvec::SmallVec<[T; CONST_PROOF_SIZE_LOG_N]>;
+
+/// A table with one entry per sumcheck round
+#[cfg(not(feature = "fixed-buffers"))]
+pub type RoundBuf<T> = alloc::vec::Vec<T>;
+
+/// Stack available to one SBF call frame
+pub const SBF_STACK_FRAME_SIZE: usize = 4096;
+
+/// Inline round tables a single phase function may hold at once
+pub const MAX_LIVE_ROUND_BUFS: usize = 2;
+
+// Two inline tables must leave at least half the frame for everything else
+const _: () = assert!(
+    MAX_LIVE_ROUND_BUFS * core::mem::size_of::<RoundBuf<crate::field::FrLimbs>>()
+        <= SBF_STACK_FRAME_SIZE / 2
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FrLimbs;
+    use crate::proof::CONST_PROOF_SIZE_LOG_N;
+
+    #[test]
+    fn test_round_buf_holds_a_full_round_table() {
+        let buf: RoundBuf<FrLimbs> =
+            core::iter::repeat_n(FrLimbs::ONE, CONST_PROOF_SIZE_LOG_N).collect();
+        assert_eq!(buf.len(), CONST_PROOF_SIZE_LOG_N);
+        #[cfg(feature = "fixed-buffers")]
+        assert!(!buf.spilled());
+    }
+}
@@ -17,6 +17,7 @@ use crate::field::{batch_inv, batch_inv_limbs, fr_add, fr_inv, fr_mul, fr_neg, f
 use crate::key::VerificationKey;
 use crate::ops;
 use crate::proof::{Proof, CONST_PROOF_SIZE_LOG_N};
+use crate::scratch::RoundBuf;
 use crate::types::{Fr, G1, SCALAR_ONE, SCALAR_ZERO};
 use crate::verifier::Challenges;
 
@@ -34,6 +35,11 @@ const USE_FR_LIMBS: bool = true;
 /// Intermediate state after Phase 3a (weights + scalar accumulation)
 /// Uses FrLimbs (Montgomery form) to avoid conversion overhead between phases
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShpleminiPhase3aResult {
     pub r_pows: Vec<FrLimbs>,
     pub pos0: FrLimbs,
@@ -46,6 +52,11 @@ pub struct ShpleminiPhase3aResult {
 /// Intermediate state after Phase 3b (folding)
 /// Uses FrLimbs (Montgomery form) to avoid conversion overhead between phases
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShpleminiPhase3bResult {
     pub const_acc: FrLimbs,
     pub gemini_scalars: Vec<FrLimbs>,
@@ -123,7 +134,7 @@ pub fn shplemini_phase3a(
     let z_plus_r0 = shplonk_z_l.add(&r_pows_l[0]);
 
     // Batch invert all 3 denominators at once
-    let denoms = vec![z_minus_r0, z_plus_r0, gemini_r_l];
+    let denoms = [z_minus_r0, z_plus_r0, gemini_r_l];
     let invs = batch_inv_limbs(&denoms).ok_or("shplonk batch inversion failed")?;
     let pos0_l = invs[0];
     let neg0_l = invs[1];
@@ -220,6 +231,11 @@ pub fn shplemini_phase3a(
 /// Intermediate state after Phase 3b1 (folding only)
 /// Uses FrLimbs (Montgomery form) to avoid conversion overhead between phases
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShpleminiPhase3b1Result {
     pub fold_pos: Vec<FrLimbs>,
     pub const_acc: FrLimbs,
@@ -248,8 +264,8 @@ pub fn shplemini_phase3b1(
     // Only convert values from proof and challenges (still in Fr format)
     let shplonk_nu_l = FrLimbs::from_bytes(&challenges.shplonk_nu);
     let gemini_a_evals = proof.gemini_a_evaluations();
-    let gemini_a_l: Vec<FrLimbs> = gemini_a_evals.iter().map(FrLimbs::from_bytes).collect();
-    let sumcheck_u_l: Vec<FrLimbs> = challenges
+    let gemini_a_l: RoundBuf<FrLimbs> = gemini_a_evals.iter().map(FrLimbs::from_bytes).collect();
+    let sumcheck_u_l: RoundBuf<FrLimbs> = challenges
         .sumcheck_challenges
         .iter()
         .take(log_n)
@@ -350,7 +366,7 @@ pub fn shplemini_phase3b2(
     let shplonk_nu_l = FrLimbs::from_bytes(&challenges.shplonk_nu);
     let gemini_r_l = FrLimbs::from_bytes(&challenges.gemini_r);
     let gemini_a_evals = proof.gemini_a_evaluations();
-    let gemini_a_l: Vec<FrLimbs> = gemini_a_evals.iter().map(FrLimbs::from_bytes).collect();
+    let gemini_a_l: RoundBuf<FrLimbs> = gemini_a_evals.iter().map(FrLimbs::from_bytes).collect();
 
     // BATCH INVERSION OPTIMIZATION with FrLimbs:
     // Note: Using Vec here because SmallFrArray<64> would be 2KB, causing stack overflow
@@ -426,7 +442,7 @@ pub fn shplemini_phase3b2(
         v_pow_l = v_pow_l.mul(&nu_sq_l);
 
         let libra_evals = proof.libra_poly_evals();
-        let libra_evals_l: Vec<FrLimbs> = libra_evals.iter().map(FrLimbs::from_bytes).collect();
+        let libra_evals_l = libra_evals.map(|eval| FrLimbs::from_bytes(&eval));
         let denominators_l = [denom0_l, denom1_l, denom0_l, denom0_l];
         let mut batching_scalars_l = [FrLimbs::ZERO; 4];
 
@@ -466,6 +482,18 @@ pub fn shplemini_phase3c(
     vk: &VerificationKey,
     challenges: &Challenges,
     phase3b: &ShpleminiPhase3bResult,
+) -> Result<(G1, G1), &'static str> {
+    let scalars = shplemini_msm_scalars(vk, challenges, phase3b, proof.is_zk);
+    shplemini_phase3c_cached(proof, vk, &scalars)
+}
+
+/// Phase 3c over MSM scalars from [`shplemini_msm_scalars`], e.g. ones an
+/// earlier transaction computed and stored: only the MSM itself runs
+#[inline(never)]
+pub fn shplemini_phase3c_cached(
+    proof: &Proof,
+    vk: &VerificationKey,
+    scalars: &[Fr],
 ) -> Result<(G1, G1), &'static str> {
     #[cfg(feature = "solana")]
     {
@@ -473,30 +501,7 @@ pub fn shplemini_phase3c(
         solana_program::log::sol_log_compute_units();
     }
 
-    // Convert FrLimbs to Fr for MSM (syscall needs big-endian bytes)
-    // This is the only place we convert - all prior phases stay in FrLimbs
-    let const_acc_fr = phase3b.const_acc.to_bytes();
-    let unshifted_fr = phase3b.unshifted.to_bytes();
-    let shifted_fr = phase3b.shifted.to_bytes();
-    let r_pows_fr: Vec<Fr> = phase3b.r_pows.iter().map(|l| l.to_bytes()).collect();
-    let gemini_scalars_fr: Vec<Fr> = phase3b
-        .gemini_scalars
-        .iter()
-        .map(|l| l.to_bytes())
-        .collect();
-    let libra_scalars_fr: Vec<Fr> = phase3b.libra_scalars.iter().map(|l| l.to_bytes()).collect();
-
-    let p0 = compute_p0_full(
-        proof,
-        vk,
-        challenges,
-        &const_acc_fr,
-        &unshifted_fr,
-        &shifted_fr,
-        &r_pows_fr,
-        &gemini_scalars_fr,
-        &libra_scalars_fr,
-    )?;
+    let p0 = shplemini_msm_p0(proof, vk, scalars)?;
 
     let kzg_quotient = proof.kzg_quotient();
     let p1 = ops::g1_neg(&kzg_quotient).map_err(|_| "G1 negate failed")?;
@@ -529,6 +534,7 @@ pub const LIBRA_EVALUATIONS: usize = 4;
 ///
 /// Returns (P0, P1) where the pairing check is: e(P0, G2) == e(P1, x·G2)
 #[inline(never)]
+#[allow(clippy::needless_range_loop)]
 pub fn compute_shplemini_pairing_points(
     proof: &Proof,
     vk: &VerificationKey,
@@ -879,7 +885,6 @@ pub fn compute_shplemini_pairing_points(
         &const_acc,
         &unshifted,
         &shifted,
-        &r_pows,
         &gemini_scalars,
         &libra_scalars,
     )?;
@@ -898,10 +903,16 @@ pub fn compute_shplemini_pairing_points(
     Ok((p0, p1))
 }
 
+/// Upper bound on the number of P0 MSM terms: shplonk_q, masking poly,
+/// 28 VK commitments (old format), 8 wires, 27 Gemini folds, 3 libra,
+/// generator and KZG quotient.
+pub const MSM_MAX_TERMS: usize = 1 + 1 + 28 + 8 + (CONST_PROOF_SIZE_LOG_N - 1) + 3 + 1 + 1;
+
 /// Compute P0 for Shplemini verification
 ///
 /// This builds the complete P0 point using all commitments from VK and proof
 /// implementing the full MSM as in Solidity's batchMul
+#[allow(clippy::too_many_arguments)]
 fn compute_p0_full(
     proof: &Proof,
     vk: &VerificationKey,
@@ -909,12 +920,152 @@ fn compute_p0_full(
     const_acc: &Fr,
     unshifted_scalar: &Fr,
     shifted_scalar: &Fr,
-    _r_pows: &[Fr],
     gemini_scalars: &[Fr],
     libra_scalars: &[Fr],
 ) -> Result<G1, &'static str> {
-    let _log_n = vk.log2_circuit_size as usize;
+    let scalars = msm_scalars(
+        vk,
+        challenges,
+        proof.is_zk,
+        const_acc,
+        unshifted_scalar,
+        shifted_scalar,
+        gemini_scalars,
+        libra_scalars,
+    );
+    shplemini_msm_p0(proof, vk, &scalars)
+}
+
+/// Evaluate P0 from its MSM scalars, in the order [`shplemini_msm_scalars`]
+/// returns them
+///
+/// Collecting every term first lets `ops::g1_msm` drop the zero-scalar terms
+/// (dummy Gemini rounds) and skip the multiplication for unit scalars.
+pub fn shplemini_msm_p0(
+    proof: &Proof,
+    vk: &VerificationKey,
+    scalars: &[Fr],
+) -> Result<G1, &'static str> {
+    let points = msm_points(proof, vk);
+    if points.len() != scalars.len() {
+        return Err("MSM scalar count does not match the proof and VK");
+    }
+
+    #[cfg(feature = "solana")]
+    {
+        solana_program::msg!("MSM: {} terms collected", points.len());
+        solana_program::log::sol_log_compute_units();
+    }
+
+    let p0 = ops::g1_msm(&points, scalars).map_err(|_| "G1 MSM failed")?;
+
+    #[cfg(feature = "debug")]
+    {
+        crate::dbg_g1!("P0 after full MSM", &p0);
+    }
+
+    Ok(p0)
+}
+
+/// Commitments of the P0 MSM
+///
+/// Solidity order:
+/// [0] shplonk_q
+/// [1] geminiMaskingPoly (ZK only)
+/// [2..38] VK commitments (28) + proof wire commitments (8)
+/// [38..38+log_n-1] gemini fold comms
+/// [...+3] libra commitments (ZK only)
+/// [...] G1_generator
+/// [...] kzg_quotient
+fn msm_points(proof: &Proof, vk: &VerificationKey) -> Vec<G1> {
+    let mut points: Vec<G1> = Vec::with_capacity(MSM_MAX_TERMS);
+    points.push(proof.shplonk_q());
+
+    #[cfg(feature = "debug")]
+    {
+        crate::trace!("===== MSM COMMITMENTS =====");
+        crate::dbg_g1!("shplonk_q (commitment[0])", &points[0]);
+
+        // Print first VK commitment
+        crate::dbg_g1!("vk.commitments[0] (qm)", &vk.commitments[0]);
+        crate::dbg_g1!("vk.commitments[1] (qc)", &vk.commitments[1]);
+        crate::dbg_g1!("vk.commitments[27] (lagrangeLast)", &vk.commitments[27]);
+
+        // Print first wire commitment
+        crate::dbg_g1!("witness_commitment(0) (w1)", &proof.witness_commitment(0));
+    }
 
+    if proof.is_zk {
+        points.push(proof.gemini_masking_poly());
+    }
+    points.extend_from_slice(&vk.commitments[..vk.num_commitments]);
+
+    // Our proof order: w1(0), w2(1), w3(2), lookupReadCounts(3), lookupReadTags(4), w4(5), lookupInverses(6), zPerm(7)
+    // Solidity order: [w1, w2, w3, w4, zPerm, lookupInverses, lookupReadCounts, lookupReadTags]
+    for our_idx in WIRE_MAPPING {
+        points.push(proof.witness_commitment(our_idx));
+    }
+    for i in 0..(CONST_PROOF_SIZE_LOG_N - 1) {
+        points.push(proof.gemini_fold_commitment(i));
+    }
+    if proof.is_zk {
+        points.push(proof.libra_commitment_0());
+        points.push(proof.libra_commitment_1());
+        points.push(proof.libra_commitment_2());
+    }
+    points.push(ops::g1_generator());
+    points.push(proof.kzg_quotient());
+    points
+}
+
+/// Our wire commitment index for each of Solidity's wire slots 30-37
+const WIRE_MAPPING: [usize; 8] = [0, 1, 2, 5, 7, 6, 3, 4];
+
+/// P0 MSM scalars from Phase 3b's output, one per commitment in Solidity's
+/// batchMul order
+///
+/// Phase 3c evaluates the same scalars; computing them separately lets a
+/// caller cache them and run the MSM alone with [`shplemini_msm_p0`].
+pub fn shplemini_msm_scalars(
+    vk: &VerificationKey,
+    challenges: &Challenges,
+    phase3b: &ShpleminiPhase3bResult,
+    is_zk: bool,
+) -> Vec<Fr> {
+    let gemini_scalars: RoundBuf<Fr> = phase3b
+        .gemini_scalars
+        .iter()
+        .map(|l| l.to_bytes())
+        .collect();
+    let libra_scalars: Vec<Fr> = phase3b.libra_scalars.iter().map(|l| l.to_bytes()).collect();
+    msm_scalars(
+        vk,
+        challenges,
+        is_zk,
+        &phase3b.const_acc.to_bytes(),
+        &phase3b.unshifted.to_bytes(),
+        &phase3b.shifted.to_bytes(),
+        &gemini_scalars,
+        &libra_scalars,
+    )
+}
+
+/// Scalars matching `msm_points`
+///
+/// Kept out of line so its rho power table does not share a stack frame
+/// with the caller's scratch buffers.
+#[inline(never)]
+#[allow(clippy::needless_range_loop, clippy::too_many_arguments)]
+fn msm_scalars(
+    vk: &VerificationKey,
+    challenges: &Challenges,
+    is_zk: bool,
+    const_acc: &Fr,
+    unshifted_scalar: &Fr,
+    shifted_scalar: &Fr,
+    gemini_scalars: &[Fr],
+    libra_scalars: &[Fr],
+) -> Vec<Fr> {
     // OPTIMIZATION: Precompute all rho powers to avoid O(n²) loop
     // We need rho^1 through rho^42 (for shifted contributions rho^37-41 plus some buffer)
     const MAX_RHO_POWERS: usize = 45;
@@ -925,44 +1076,27 @@ fn compute_p0_full(
         rho_pows[i] = fr_mul(&rho_pows[i - 1], &challenges.rho);
     }
 
-    // We compute P0 as the MSM of all commitments with their scalars
-    // Solidity order:
+    // Scalars follow `msm_points`:
     // [0] shplonk_q (scalar=1)
     // [1] geminiMaskingPoly (scalar=-unshifted)
-    // [2..38] VK commitments (28) + proof wire commitments (8) with scalars -unshifted*rho^i / -shifted*rho^i
+    // [2..38] VK + wire commitments with scalars -unshifted*rho^i / -shifted*rho^i
     // [38..38+log_n-1] gemini fold comms
     // [...+3] libra commitments
     // [...] G1_generator (scalar=const_acc)
     // [...] kzg_quotient (scalar=z)
+    let mut scalars: Vec<Fr> = Vec::with_capacity(MSM_MAX_TERMS);
 
     // Start with shplonk_q (scalar = 1)
-    let mut p0 = proof.shplonk_q();
-
-    #[cfg(feature = "debug")]
-    {
-        crate::trace!("===== MSM COMMITMENTS =====");
-        crate::dbg_g1!("shplonk_q (commitment[0])", &p0);
-
-        // Print first VK commitment
-        crate::dbg_g1!("vk.commitments[0] (qm)", &vk.commitments[0]);
-        crate::dbg_g1!("vk.commitments[1] (qc)", &vk.commitments[1]);
-        crate::dbg_g1!("vk.commitments[27] (lagrangeLast)", &vk.commitments[27]);
-
-        // Print first wire commitment
-        crate::dbg_g1!("witness_commitment(0) (w1)", &proof.witness_commitment(0));
-    }
+    scalars.push(SCALAR_ONE);
 
     // Add geminiMaskingPoly * (-unshifted)
-    if proof.is_zk {
+    if is_zk {
         let neg_unshifted = fr_neg(unshifted_scalar);
         #[cfg(feature = "debug")]
         {
             crate::dbg_fr!("scalar[1] (masking, -unshifted)", &neg_unshifted);
         }
-        let masking_comm = proof.gemini_masking_poly();
-        let scaled =
-            ops::g1_scalar_mul(&masking_comm, &neg_unshifted).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled).map_err(|_| "G1 add failed")?;
+        scalars.push(neg_unshifted);
     }
 
     // Build scalars for VK and proof commitments
@@ -977,9 +1111,7 @@ fn compute_p0_full(
     let num_vk_commitments = vk.num_commitments;
     for i in 0..num_vk_commitments {
         let scalar = fr_mul(&neg_unshifted, &rho_pows[i + 1]);
-        let commitment = vk.commitments[i];
-        let scaled = ops::g1_scalar_mul(&commitment, &scalar).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled).map_err(|_| "G1 add failed")?;
+        scalars.push(scalar);
 
         #[cfg(feature = "debug")]
         if i < 3 || i == num_vk_commitments - 1 {
@@ -989,15 +1121,8 @@ fn compute_p0_full(
     // Track rho index for wire commitments
     let mut rho_idx = num_vk_commitments + 1;
 
-    #[cfg(feature = "solana")]
-    {
-        solana_program::msg!("MSM: after VK comms");
-        solana_program::log::sol_log_compute_units();
-    }
-
     #[cfg(feature = "debug")]
     {
-        crate::dbg_g1!("P0 after VK commitments", &p0);
         crate::dbg_fr!(
             &format!(
                 "rho_pows[0] after VK (should be rho^{})",
@@ -1012,18 +1137,12 @@ fn compute_p0_full(
     // Solidity order: w1(30), w2(31), w3(32), w4(33), zPerm(34), lookupInverses(35), lookupReadCounts(36), lookupReadTags(37)
     // Our proof order: w1(0), w2(1), w3(2), lookupReadCounts(3), lookupReadTags(4), w4(5), lookupInverses(6), zPerm(7)
 
-    // Map our proof indices to Solidity order
-    // Solidity idx 30-37: [w1, w2, w3, w4, zPerm, lookupInverses, lookupReadCounts, lookupReadTags]
-    // Our idx 0-7: [w1, w2, w3, lookupReadCounts, lookupReadTags, w4, lookupInverses, zPerm]
-    // Mapping: [0, 1, 2, 5, 7, 6, 3, 4]
-    let wire_mapping = [0usize, 1, 2, 5, 7, 6, 3, 4];
+    // Map our proof indices to Solidity order (see `WIRE_MAPPING`)
 
     // Indices 30-34 (w1, w2, w3, w4, zPerm) are shifted commitments
     // They get both unshifted and shifted scalar contributions
     // SHIFTED_COMMITMENTS_START = 30
-    for (sol_idx, &our_idx) in wire_mapping.iter().enumerate() {
-        let commitment = proof.witness_commitment(our_idx);
-
+    for (sol_idx, &_our_idx) in WIRE_MAPPING.iter().enumerate() {
         // Solidity scalars[30..38] start with unshifted scalar contribution
         // After VK loop (27 iterations), rho_idx = 28
         // Wire scalars use rho^28, rho^29, ..., rho^35
@@ -1032,7 +1151,7 @@ fn compute_p0_full(
         #[cfg(feature = "debug")]
         {
             crate::dbg_fr!(
-                &format!("Wire[{}] (sol_idx={}) unshifted_scalar", our_idx, sol_idx),
+                &format!("Wire[{}] (sol_idx={}) unshifted_scalar", _our_idx, sol_idx),
                 &scalar
             );
         }
@@ -1054,7 +1173,7 @@ fn compute_p0_full(
                 crate::dbg_fr!(
                     &format!(
                         "Wire[{}] shifted_contrib (rho^{})",
-                        our_idx, shifted_rho_idx
+                        _our_idx, shifted_rho_idx
                     ),
                     &shifted_contrib
                 );
@@ -1065,7 +1184,7 @@ fn compute_p0_full(
             #[cfg(feature = "debug")]
             {
                 crate::dbg_fr!(
-                    &format!("Wire[{}] FINAL scalar (sol_idx={})", our_idx, sol_idx),
+                    &format!("Wire[{}] FINAL scalar (sol_idx={})", _our_idx, sol_idx),
                     &scalar
                 );
             }
@@ -1076,28 +1195,16 @@ fn compute_p0_full(
             crate::dbg_fr!(
                 &format!(
                     "Wire[{}] FINAL scalar (sol_idx={}, no shift)",
-                    our_idx, sol_idx
+                    _our_idx, sol_idx
                 ),
                 &scalar
             );
         }
 
-        let scaled = ops::g1_scalar_mul(&commitment, &scalar).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled).map_err(|_| "G1 add failed")?;
+        scalars.push(scalar);
         rho_idx += 1;
     }
 
-    #[cfg(feature = "debug")]
-    {
-        crate::dbg_g1!("P0 after wire commitments", &p0);
-    }
-
-    #[cfg(feature = "solana")]
-    {
-        solana_program::msg!("MSM: after wire comms");
-        solana_program::log::sol_log_compute_units();
-    }
-
     // Add gemini fold commitments with their scalars
     // Solidity: for all CONST_PROOF_SIZE_LOG_N - 1 = 27 commitments
     // scalars are zero for dummy rounds (i >= log_n - 1)
@@ -1110,19 +1217,11 @@ fn compute_p0_full(
         if i < 3 || i == 26 {
             crate::dbg_fr!(&format!("gemini_scalars[{}]", i), &gemini_scalars[i]);
         }
-        let commitment = proof.gemini_fold_commitment(i);
-        let scaled =
-            ops::g1_scalar_mul(&commitment, &gemini_scalars[i]).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled).map_err(|_| "G1 add failed")?;
-    }
-
-    #[cfg(feature = "debug")]
-    {
-        crate::dbg_g1!("P0 after gemini fold", &p0);
+        scalars.push(gemini_scalars[i]);
     }
 
     // Add libra commitments with their scalars (ZK only)
-    if proof.is_zk {
+    if is_zk {
         #[cfg(feature = "debug")]
         {
             crate::trace!("===== LIBRA SCALARS =====");
@@ -1132,46 +1231,19 @@ fn compute_p0_full(
         }
 
         // libraCommitments[0], [1], [2]
-        let libra_comm_0 = proof.libra_commitment_0();
-        let libra_comm_1 = proof.libra_commitment_1();
-        let libra_comm_2 = proof.libra_commitment_2();
-
-        let scaled0 =
-            ops::g1_scalar_mul(&libra_comm_0, &libra_scalars[0]).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled0).map_err(|_| "G1 add failed")?;
-
         // libra_scalars[1] = batchingScalars[1] + batchingScalars[2] (combined)
-        let scaled1 =
-            ops::g1_scalar_mul(&libra_comm_1, &libra_scalars[1]).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled1).map_err(|_| "G1 add failed")?;
-
-        let scaled2 =
-            ops::g1_scalar_mul(&libra_comm_2, &libra_scalars[2]).map_err(|_| "G1 mul failed")?;
-        p0 = ops::g1_add(&p0, &scaled2).map_err(|_| "G1 add failed")?;
-
-        #[cfg(feature = "debug")]
-        {
-            crate::dbg_g1!("P0 after libra comms", &p0);
-        }
+        scalars.push(libra_scalars[0]);
+        scalars.push(libra_scalars[1]);
+        scalars.push(libra_scalars[2]);
     }
 
-    // Add const_acc * G1_generator
-    let g_scaled =
-        ops::g1_scalar_mul(&ops::g1_generator(), const_acc).map_err(|_| "G1 scalar mul failed")?;
-    p0 = ops::g1_add(&p0, &g_scaled).map_err(|_| "G1 add failed")?;
-
-    // Add z * kzg_quotient
-    let kzg_quotient = proof.kzg_quotient();
-    let kzg_scaled = ops::g1_scalar_mul(&kzg_quotient, &challenges.shplonk_z)
-        .map_err(|_| "G1 scalar mul failed")?;
-    p0 = ops::g1_add(&p0, &kzg_scaled).map_err(|_| "G1 add failed")?;
+    // const_acc * G1_generator
+    scalars.push(*const_acc);
 
-    #[cfg(feature = "debug")]
-    {
-        crate::dbg_g1!("P0 after full MSM", &p0);
-    }
+    // z * kzg_quotient
+    scalars.push(challenges.shplonk_z);
 
-    Ok(p0)
+    scalars
 }
 
 #[cfg(test)]
@@ -1188,4 +1260,61 @@ mod tests {
         assert_eq!(LIBRA_COMMITMENTS, 3);
         assert_eq!(LIBRA_EVALUATIONS, 4);
     }
+
+    #[test]
+    fn test_msm_scalars_line_up_with_points() {
+        use crate::field::fr_from_u64;
+        use crate::verifier::RelationParameters;
+
+        let mut vk_bytes = vec![0u8; crate::VK_SIZE];
+        vk_bytes[7] = 64;
+        vk_bytes[15] = 6;
+        vk_bytes[23] = 1;
+        vk_bytes[31] = 1;
+        let vk = VerificationKey::from_bytes(&vk_bytes).unwrap();
+        let challenges = Challenges {
+            relation_params: RelationParameters {
+                eta: SCALAR_ZERO,
+                eta_two: SCALAR_ZERO,
+                eta_three: SCALAR_ZERO,
+                beta: SCALAR_ZERO,
+                gamma: SCALAR_ZERO,
+                public_input_delta: SCALAR_ZERO,
+            },
+            alpha: SCALAR_ZERO,
+            alphas: vec![],
+            libra_challenge: None,
+            gate_challenges: vec![],
+            sumcheck_challenges: vec![],
+            rho: fr_from_u64(3),
+            gemini_r: fr_from_u64(5),
+            shplonk_nu: fr_from_u64(7),
+            shplonk_z: fr_from_u64(11),
+        };
+        let phase3b = ShpleminiPhase3bResult {
+            const_acc: FrLimbs::from_bytes(&fr_from_u64(13)),
+            gemini_scalars: vec![FrLimbs::ONE; CONST_PROOF_SIZE_LOG_N - 1],
+            libra_scalars: vec![FrLimbs::ONE; LIBRA_COMMITMENTS],
+            r_pows: vec![],
+            unshifted: FrLimbs::ONE,
+            shifted: FrLimbs::ONE,
+        };
+
+        for is_zk in [true, false] {
+            let proof_bytes = vec![0u8; Proof::expected_size(6, is_zk) * 32];
+            let proof = Proof::from_bytes(&proof_bytes, 6, is_zk).unwrap();
+            let scalars = shplemini_msm_scalars(&vk, &challenges, &phase3b, is_zk);
+            assert_eq!(scalars.len(), msm_points(&proof, &vk).len());
+            assert!(scalars.len() <= MSM_MAX_TERMS);
+            assert_eq!(scalars[0], SCALAR_ONE);
+            assert_eq!(scalars[scalars.len() - 2], fr_from_u64(13));
+            assert_eq!(scalars[scalars.len() - 1], challenges.shplonk_z);
+
+            assert_eq!(
+                shplemini_phase3c_cached(&proof, &vk, &scalars).unwrap(),
+                shplemini_phase3c(&proof, &vk, &challenges, &phase3b).unwrap()
+            );
+            assert!(shplemini_msm_p0(&proof, &vk, &scalars[1..]).is_err());
+        }
+    }
 }
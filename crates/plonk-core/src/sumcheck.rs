@@ -17,10 +17,18 @@ use alloc::vec::Vec;
 
 use crate::field::{batch_inv, batch_inv_limbs, fr_add, fr_inv, fr_mul, fr_sub, FrLimbs};
 use crate::proof::Proof;
+use crate::relations::RelationCoefficients;
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+use crate::relations::RelationGroup;
 use crate::types::{Fr, SCALAR_ONE, SCALAR_ZERO};
 
 /// Relation parameters for sumcheck evaluation
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RelationParameters {
     pub eta: Fr,
     pub eta_two: Fr,
@@ -32,6 +40,11 @@ pub struct RelationParameters {
 
 /// Challenges for sumcheck verification
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SumcheckChallenges {
     pub gate_challenges: Vec<Fr>,
     pub sumcheck_u_challenges: Vec<Fr>,
@@ -355,7 +368,9 @@ fn next_target_batch(univariate: &[Fr], chi: &Fr, is_zk: bool) -> Result<Fr, &'s
 /// - Denominators (9 muls): ~22K CUs
 /// - Batch inversion (26 muls + 1 GCD): ~87K CUs
 /// - Accumulate + result (10 muls): ~28K CUs
+///
 /// Total: ~205-215K CUs per round
+#[allow(clippy::needless_range_loop)]
 fn next_target_batch_limbs(univariate: &[Fr], chi: &Fr, is_zk: bool) -> Result<Fr, &'static str> {
     let n = if is_zk { 9 } else { 8 };
 
@@ -471,6 +486,7 @@ fn next_target_l(
 }
 
 /// Original version using Fr (bytes) throughout
+#[allow(clippy::needless_range_loop)]
 fn next_target_batch_bytes(univariate: &[Fr], chi: &Fr, is_zk: bool) -> Result<Fr, &'static str> {
     let n = if is_zk { 9 } else { 8 };
 
@@ -508,6 +524,7 @@ fn next_target_batch_bytes(univariate: &[Fr], chi: &Fr, is_zk: bool) -> Result<F
 }
 
 /// Original version with individual inversions (9 fr_inv calls per round)
+#[allow(clippy::needless_range_loop)]
 fn next_target_individual(univariate: &[Fr], chi: &Fr, is_zk: bool) -> Result<Fr, &'static str> {
     let n = if is_zk { 9 } else { 8 };
 
@@ -660,6 +677,11 @@ fn verify_sumcheck_rounds(
 
 /// Intermediate state for partial sumcheck round verification
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SumcheckRoundsState {
     pub target: Fr,
     pub pow_partial: Fr,
@@ -689,8 +711,21 @@ pub fn sumcheck_rounds_init(proof: &Proof, libra_challenge: Option<&Fr>) -> Sumc
     }
 }
 
+/// Failure in `verify_sumcheck_rounds_partial`, tagged with the failing round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SumcheckRoundError {
+    pub round: usize,
+    pub reason: &'static str,
+}
+
+impl core::fmt::Display for SumcheckRoundError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "round {}: {}", self.round, self.reason)
+    }
+}
+
 /// Verify a range of sumcheck rounds [start_round, end_round)
-/// Returns updated state or error
+/// Returns updated state or the round that failed
 #[inline(never)]
 pub fn verify_sumcheck_rounds_partial(
     proof: &Proof,
@@ -698,7 +733,7 @@ pub fn verify_sumcheck_rounds_partial(
     state: &SumcheckRoundsState,
     start_round: usize,
     end_round: usize,
-) -> Result<SumcheckRoundsState, &'static str> {
+) -> Result<SumcheckRoundsState, SumcheckRoundError> {
     let mut target = state.target;
     let mut pow_partial = state.pow_partial;
 
@@ -718,15 +753,20 @@ pub fn verify_sumcheck_rounds_partial(
 
         // Check round sum: u[0] + u[1] == target
         if !check_round_sum(&univariate, &target) {
-            return Err("sumcheck round sum check failed");
+            return Err(SumcheckRoundError {
+                round,
+                reason: "sumcheck round sum check failed",
+            });
         }
 
         // Get challenge for this round
         let chi = &challenges.sumcheck_u_challenges[round];
 
         // Compute next target using barycentric interpolation (~210K CUs per round)
-        target = next_target(&univariate, chi, proof.is_zk)
-            .map_err(|_| "barycentric interpolation failed")?;
+        target = next_target(&univariate, chi, proof.is_zk).map_err(|_| SumcheckRoundError {
+            round,
+            reason: "barycentric interpolation failed",
+        })?;
 
         // Update pow_partial (~10K CUs)
         let gate_challenge = &challenges.gate_challenges[round];
@@ -746,13 +786,27 @@ pub fn verify_sumcheck_rounds_partial(
     })
 }
 
+/// How the relations are batched into one sum
+#[derive(Clone, Copy, Debug)]
+pub enum RelationBatching<'a> {
+    /// The alpha challenges, with the pow factor from the rounds state
+    Alphas(&'a [Fr]),
+    /// Coefficients from
+    /// [`relation_coefficients`](crate::relations::relation_coefficients)
+    /// for the same alphas and pow factor
+    ///
+    /// The caller must have checked them, e.g. against a commitment it
+    /// recorded when it computed them.
+    Coefficients(&'a RelationCoefficients),
+}
+
 /// Verify relations and final check (after all rounds completed)
 /// Uses verifier::RelationParameters for compatibility with phased verification
 #[inline(never)]
 pub fn verify_sumcheck_relations(
     proof: &Proof,
     relation_params: &crate::verifier::RelationParameters,
-    alphas: &[Fr],
+    batching: RelationBatching<'_>,
     sumcheck_u_challenges: &[Fr],
     state: &SumcheckRoundsState,
     libra_challenge: Option<&Fr>,
@@ -766,18 +820,14 @@ pub fn verify_sumcheck_relations(
         solana_program::log::sol_log_compute_units();
     }
 
-    // Convert to local RelationParameters
-    let local_params = RelationParameters {
-        eta: relation_params.eta,
-        eta_two: relation_params.eta_two,
-        eta_three: relation_params.eta_three,
-        beta: relation_params.beta,
-        gamma: relation_params.gamma,
-        public_inputs_delta: relation_params.public_input_delta,
-    };
-
     // Accumulate relation evaluations
-    let mut grand = accumulate_relations(proof, &local_params, alphas, pow_partial)?;
+    let grand = accumulate_relations_with(
+        proof,
+        &local_relation_params(relation_params),
+        batching,
+        pow_partial,
+        ACCUMULATE_FULL,
+    )?;
 
     #[cfg(feature = "solana")]
     {
@@ -785,24 +835,84 @@ pub fn verify_sumcheck_relations(
         solana_program::log::sol_log_compute_units();
     }
 
-    // ZK adjustment (for ZK proofs)
-    // Solidity: grandHonkRelationSum = grandHonkRelationSum * (1 - evaluation) + libraEvaluation * libraChallenge
-    // where evaluation = product(sumCheckUChallenges[2..log_n])
-    if proof.is_zk {
-        if let Some(libra_chal) = libra_challenge {
-            let libra_eval = proof.libra_evaluation();
-            // Compute evaluation = product(sumcheck_challenges[2..log_n])
-            let mut evaluation = SCALAR_ONE;
-            for i in 2..proof.log_n {
-                evaluation = fr_mul(&evaluation, &sumcheck_u_challenges[i]);
-            }
+    final_relations_check(proof, grand, target, sumcheck_u_challenges, libra_challenge)
+}
 
-            // grand = grand * (1 - evaluation) + libraEvaluation * libraChallenge
-            let one_minus_eval = fr_sub(&SCALAR_ONE, &evaluation);
-            let libra_term = fr_mul(&libra_eval, libra_chal);
-            let grand_scaled = fr_mul(&grand, &one_minus_eval);
-            grand = fr_add(&grand_scaled, &libra_term);
-        }
+/// First half of [`verify_sumcheck_relations`] (arithmetic, permutation,
+/// range and elliptic subrelations)
+///
+/// Returns the batched partial sum, which the caller stores and passes to
+/// [`verify_sumcheck_relations_part2`] in a later transaction.
+#[inline(never)]
+pub fn verify_sumcheck_relations_part1(
+    proof: &Proof,
+    relation_params: &crate::verifier::RelationParameters,
+    batching: RelationBatching<'_>,
+    state: &SumcheckRoundsState,
+) -> Result<Fr, &'static str> {
+    accumulate_relations_with(
+        proof,
+        &local_relation_params(relation_params),
+        batching,
+        &state.pow_partial,
+        ACCUMULATE_PART1,
+    )
+}
+
+/// Second half of [`verify_sumcheck_relations`] (lookup, memory and
+/// Poseidon subrelations) plus the final check
+///
+/// `partial` is the value returned by [`verify_sumcheck_relations_part1`].
+#[inline(never)]
+pub fn verify_sumcheck_relations_part2(
+    proof: &Proof,
+    relation_params: &crate::verifier::RelationParameters,
+    batching: RelationBatching<'_>,
+    sumcheck_u_challenges: &[Fr],
+    state: &SumcheckRoundsState,
+    libra_challenge: Option<&Fr>,
+    partial: &Fr,
+) -> Result<(), &'static str> {
+    let rest = accumulate_relations_with(
+        proof,
+        &local_relation_params(relation_params),
+        batching,
+        &state.pow_partial,
+        ACCUMULATE_PART2,
+    )?;
+
+    final_relations_check(
+        proof,
+        fr_add(partial, &rest),
+        &state.target,
+        sumcheck_u_challenges,
+        libra_challenge,
+    )
+}
+
+fn local_relation_params(rp: &crate::verifier::RelationParameters) -> RelationParameters {
+    RelationParameters {
+        eta: rp.eta,
+        eta_two: rp.eta_two,
+        eta_three: rp.eta_three,
+        beta: rp.beta,
+        gamma: rp.gamma,
+        public_inputs_delta: rp.public_input_delta,
+    }
+}
+
+/// Apply the ZK adjustment to the accumulated relations and compare with
+/// the final round target
+fn final_relations_check(
+    proof: &Proof,
+    mut grand: Fr,
+    target: &Fr,
+    sumcheck_u_challenges: &[Fr],
+    libra_challenge: Option<&Fr>,
+) -> Result<(), &'static str> {
+    if let Some((scale, libra_term)) = zk_adjustment(proof, sumcheck_u_challenges, libra_challenge)
+    {
+        grand = fr_add(&fr_mul(&grand, &scale), &libra_term);
     }
 
     // Check that grand == target
@@ -813,6 +923,142 @@ pub fn verify_sumcheck_relations(
     }
 }
 
+/// ZK adjustment of the grand relation sum, as `(scale, libra_term)`
+///
+/// The adjusted sum is `grand * scale + libra_term`. `None` for non-ZK
+/// proofs, which compare the sum unchanged.
+#[allow(clippy::needless_range_loop)]
+fn zk_adjustment(
+    proof: &Proof,
+    sumcheck_u_challenges: &[Fr],
+    libra_challenge: Option<&Fr>,
+) -> Option<(Fr, Fr)> {
+    // Solidity: grandHonkRelationSum = grandHonkRelationSum * (1 - evaluation) + libraEvaluation * libraChallenge
+    // where evaluation = product(sumCheckUChallenges[2..log_n])
+    if !proof.is_zk {
+        return None;
+    }
+    let libra_chal = libra_challenge?;
+    let libra_eval = proof.libra_evaluation();
+    let mut evaluation = SCALAR_ONE;
+    for i in 2..proof.log_n {
+        evaluation = fr_mul(&evaluation, &sumcheck_u_challenges[i]);
+    }
+    Some((
+        fr_sub(&SCALAR_ONE, &evaluation),
+        fr_mul(&libra_eval, libra_chal),
+    ))
+}
+
+/// Breakdown of the final relations check by [`RelationGroup`]
+///
+/// Produced by [`diagnose_sumcheck_relations`] to explain a failed check.
+/// Always built off chain; on chain only with the `diagnostics` feature.
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SumcheckDiagnostics {
+    /// Final round target the relations must reach
+    pub expected: Fr,
+    /// ZK-adjusted grand relation sum the proof's evaluations produce
+    pub actual: Fr,
+    /// Each group's ZK-scaled share of `actual`
+    pub groups: [(RelationGroup, Fr); RelationGroup::ALL.len()],
+    /// Libra masking term in `actual`, zero for non-ZK proofs
+    pub libra_term: Fr,
+}
+
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+impl SumcheckDiagnostics {
+    pub fn passed(&self) -> bool {
+        self.actual == self.expected
+    }
+
+    /// `actual - expected`
+    pub fn gap(&self) -> Fr {
+        fr_sub(&self.actual, &self.expected)
+    }
+
+    /// The group whose share alone accounts for the gap
+    ///
+    /// This is the usual shape of a circuit/verifier mismatch: the verifier
+    /// accumulates a relation the prover's circuit never constrained, and
+    /// dropping that group's share makes the check pass. `None` when the
+    /// check passed or no single group explains the gap, e.g. because an
+    /// evaluation was altered; `groups` still shows where the sum comes from.
+    pub fn diverging_group(&self) -> Option<RelationGroup> {
+        if self.passed() {
+            return None;
+        }
+        let gap = self.gap();
+        self.groups
+            .iter()
+            .find(|(_, share)| *share == gap)
+            .map(|(group, _)| *group)
+    }
+}
+
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+impl core::fmt::Display for SumcheckDiagnostics {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use crate::debug::fr_to_hex;
+
+        if self.passed() {
+            return write!(f, "relations check passed");
+        }
+        write!(
+            f,
+            "relations check failed: expected {}, got {}",
+            fr_to_hex(&self.expected),
+            fr_to_hex(&self.actual)
+        )?;
+        match self.diverging_group() {
+            Some(group) => write!(f, " ({group} relation diverged)"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Recompute the final relations check one [`RelationGroup`] at a time
+///
+/// Takes the same inputs as [`verify_sumcheck_relations`] and repeats its
+/// accumulation, so it is meant for the failure path: call it after that
+/// check fails to find out which relation broke it.
+#[cfg(any(feature = "diagnostics", not(target_os = "solana")))]
+#[inline(never)]
+pub fn diagnose_sumcheck_relations(
+    proof: &Proof,
+    relation_params: &crate::verifier::RelationParameters,
+    alphas: &[Fr],
+    sumcheck_u_challenges: &[Fr],
+    state: &SumcheckRoundsState,
+    libra_challenge: Option<&Fr>,
+) -> Result<SumcheckDiagnostics, &'static str> {
+    let shares = with_relation_inputs(
+        proof,
+        &local_relation_params(relation_params),
+        alphas,
+        &state.pow_partial,
+        crate::relations::relation_group_contributions_l,
+    )?;
+    let (scale, libra_term) = zk_adjustment(proof, sumcheck_u_challenges, libra_challenge)
+        .unwrap_or((SCALAR_ONE, SCALAR_ZERO));
+
+    let mut actual = libra_term;
+    let mut groups = [(RelationGroup::Arithmetic, SCALAR_ZERO); RelationGroup::ALL.len()];
+    for ((slot, group), share) in groups.iter_mut().zip(RelationGroup::ALL).zip(shares) {
+        let share = fr_mul(&share.to_bytes(), &scale);
+        actual = fr_add(&actual, &share);
+        *slot = (group, share);
+    }
+
+    Ok(SumcheckDiagnostics {
+        expected: state.target,
+        actual,
+        groups,
+        libra_term,
+    })
+}
+
 /// Verify the complete sumcheck protocol including relation evaluation
 ///
 /// This performs:
@@ -943,6 +1189,93 @@ fn accumulate_relations(
     alphas: &[Fr],
     pow_partial: &Fr,
 ) -> Result<Fr, &'static str> {
+    accumulate_relations_with(
+        proof,
+        relation_params,
+        RelationBatching::Alphas(alphas),
+        pow_partial,
+        ACCUMULATE_FULL,
+    )
+}
+
+/// Signature shared by the full and split FrLimbs accumulators
+type AccumulateFn =
+    fn(&[FrLimbs], &crate::relations::RelationParametersLimbs, &[FrLimbs], &FrLimbs) -> FrLimbs;
+
+/// Signature of the same accumulators batching with [`RelationCoefficients`]
+type AccumulateWithCoefficientsFn =
+    fn(&[FrLimbs], &crate::relations::RelationParametersLimbs, &RelationCoefficients) -> FrLimbs;
+
+/// The two batching forms of one FrLimbs accumulator
+#[derive(Clone, Copy)]
+struct Accumulator {
+    alphas: AccumulateFn,
+    coefficients: AccumulateWithCoefficientsFn,
+}
+
+const ACCUMULATE_FULL: Accumulator = Accumulator {
+    alphas: crate::relations::accumulate_relation_evaluations_l,
+    coefficients: crate::relations::accumulate_relation_evaluations_l_with_coefficients,
+};
+
+const ACCUMULATE_PART1: Accumulator = Accumulator {
+    alphas: crate::relations::accumulate_relation_evaluations_l_part1,
+    coefficients: crate::relations::accumulate_relation_evaluations_l_part1_with_coefficients,
+};
+
+const ACCUMULATE_PART2: Accumulator = Accumulator {
+    alphas: crate::relations::accumulate_relation_evaluations_l_part2,
+    coefficients: crate::relations::accumulate_relation_evaluations_l_part2_with_coefficients,
+};
+
+/// Run `accumulate` over the proof's sumcheck evaluations, converting to and
+/// from FrLimbs at the boundary
+fn accumulate_relations_with(
+    proof: &Proof,
+    relation_params: &RelationParameters,
+    batching: RelationBatching<'_>,
+    pow_partial: &Fr,
+    accumulate: Accumulator,
+) -> Result<Fr, &'static str> {
+    let grand_l = match batching {
+        RelationBatching::Alphas(alphas) => with_relation_inputs(
+            proof,
+            relation_params,
+            alphas,
+            pow_partial,
+            accumulate.alphas,
+        )?,
+        RelationBatching::Coefficients(coeffs) => {
+            let (evals_l, rp_l) = relation_inputs_l(proof, relation_params)?;
+            (accumulate.coefficients)(&evals_l, &rp_l, coeffs)
+        }
+    };
+    // Convert result back to Fr at the boundary
+    Ok(grand_l.to_bytes())
+}
+
+/// Convert the proof's sumcheck evaluations and the relation inputs to
+/// FrLimbs once and pass them to `f`
+fn with_relation_inputs<T>(
+    proof: &Proof,
+    relation_params: &RelationParameters,
+    alphas: &[Fr],
+    pow_partial: &Fr,
+    f: fn(&[FrLimbs], &crate::relations::RelationParametersLimbs, &[FrLimbs], &FrLimbs) -> T,
+) -> Result<T, &'static str> {
+    let (evals_l, rp_l) = relation_inputs_l(proof, relation_params)?;
+    let alphas_l: Vec<FrLimbs> = alphas.iter().map(FrLimbs::from_bytes).collect();
+    let pow_partial_l = FrLimbs::from_bytes(pow_partial);
+
+    // Accumulate using FrLimbs (faster - no per-operation byte conversions)
+    Ok(f(&evals_l, &rp_l, &alphas_l, &pow_partial_l))
+}
+
+/// The proof's sumcheck evaluations and the relation parameters as FrLimbs
+fn relation_inputs_l(
+    proof: &Proof,
+    relation_params: &RelationParameters,
+) -> Result<(Vec<FrLimbs>, crate::relations::RelationParametersLimbs), &'static str> {
     // Get sumcheck evaluations (40 or 41 Fr values)
     let evals = proof.sumcheck_evaluations();
 
@@ -952,8 +1285,6 @@ fn accumulate_relations(
 
     // Convert all inputs to FrLimbs once at the boundary
     let evals_l: Vec<FrLimbs> = evals.iter().map(FrLimbs::from_bytes).collect();
-    let alphas_l: Vec<FrLimbs> = alphas.iter().map(FrLimbs::from_bytes).collect();
-    let pow_partial_l = FrLimbs::from_bytes(pow_partial);
 
     // Convert relation parameters to FrLimbs
     let rp_fr = crate::relations::RelationParameters {
@@ -966,16 +1297,7 @@ fn accumulate_relations(
     };
     let rp_l = crate::relations::RelationParametersLimbs::from_fr(&rp_fr);
 
-    // Accumulate using FrLimbs (faster - no per-operation byte conversions)
-    let grand_l = crate::relations::accumulate_relation_evaluations_l(
-        &evals_l,
-        &rp_l,
-        &alphas_l,
-        &pow_partial_l,
-    );
-
-    // Convert result back to Fr at the boundary
-    Ok(grand_l.to_bytes())
+    Ok((evals_l, rp_l))
 }
 
 #[cfg(test)]
@@ -1005,6 +1327,42 @@ mod tests {
         assert!(!check_round_sum(&[a, b], &wrong_target));
     }
 
+    #[test]
+    fn test_diverging_group() {
+        let mut groups = [(RelationGroup::Arithmetic, SCALAR_ZERO); RelationGroup::ALL.len()];
+        for (i, (slot, group)) in groups.iter_mut().zip(RelationGroup::ALL).enumerate() {
+            *slot = (group, fr_from_u64(10 + i as u64));
+        }
+        // Shares sum to 10 + 11 + ... + 17 = 108, plus a libra term of 2
+        let passing = SumcheckDiagnostics {
+            expected: fr_from_u64(110),
+            actual: fr_from_u64(110),
+            groups,
+            libra_term: fr_from_u64(2),
+        };
+        assert!(passing.passed());
+        assert_eq!(passing.diverging_group(), None);
+        assert_eq!(passing.to_string(), "relations check passed");
+
+        // Without the lookup share (12) the check would pass
+        let extra_lookup = SumcheckDiagnostics {
+            expected: fr_from_u64(98),
+            ..passing.clone()
+        };
+        assert_eq!(extra_lookup.gap(), fr_from_u64(12));
+        assert_eq!(extra_lookup.diverging_group(), Some(RelationGroup::Lookup));
+        assert!(extra_lookup
+            .to_string()
+            .ends_with("(lookup relation diverged)"));
+
+        let unexplained = SumcheckDiagnostics {
+            expected: fr_from_u64(109),
+            ..passing
+        };
+        assert!(!unexplained.passed());
+        assert_eq!(unexplained.diverging_group(), None);
+    }
+
     #[test]
     fn test_update_pow() {
         let pow = SCALAR_ONE;
@@ -1024,6 +1382,7 @@ mod tests {
     /// Generate FrLimbs constants for hardcoding
     /// Run with: cargo test generate_fr_limbs_constants -- --nocapture
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn generate_fr_limbs_constants() {
         println!("\n=== FrLimbs Constants for Hardcoding ===\n");
 
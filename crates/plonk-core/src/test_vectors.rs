@@ -0,0 +1,323 @@
+//! Proof mutators and challenge dumps for negative tests and fuzzers
+//!
+//! Enable with `--features test-vectors`. Nothing here is used by the
+//! verifier itself; it exists so downstream crates can derive invalid proofs
+//! from a valid one without hardcoding byte offsets of the bb 0.87 layout,
+//! and can pin the Fiat-Shamir challenges of a proof in a golden file.
+//!
+//! ```ignore
+//! use plonk_solana_core::test_vectors::{mutate, Mutation};
+//!
+//! let bad = mutate(&proof, Mutation::CorruptCommitment(0))?;
+//! assert!(verify(&vk, &bad, &public_inputs, true).is_err());
+//! ```
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use thiserror::Error;
+
+use crate::debug::fr_to_hex;
+use crate::errors::{ProofError, VerifyError};
+use crate::key::VerificationKey;
+use crate::ops;
+use crate::proof::{
+    Proof, BATCHED_RELATION_PARTIAL_LENGTH, CONST_PROOF_SIZE_LOG_N, FR_SIZE, G1_LIMBED_SIZE,
+    ZK_BATCHED_RELATION_PARTIAL_LENGTH,
+};
+use crate::types::{Fr, G1, G1_GENERATOR};
+use crate::verifier::{verify_step1_challenges, Challenges};
+
+/// Errors from applying a [`Mutation`]
+#[derive(Debug, Error)]
+pub enum MutationError {
+    #[error(transparent)]
+    Proof(#[from] ProofError),
+
+    #[error("Byte offset {offset} is outside the {len}-byte proof")]
+    OffsetOutOfRange { offset: usize, len: usize },
+
+    #[error("Commitment index {index} out of range ({count} commitments)")]
+    CommitmentOutOfRange { index: usize, count: usize },
+
+    #[error("Sumcheck round {round} out of range ({CONST_PROOF_SIZE_LOG_N} rounds)")]
+    RoundOutOfRange { round: usize },
+}
+
+/// One targeted change to a serialized proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// Invert every bit of the byte at this offset
+    FlipByte(usize),
+    /// Replace the i-th G1 commitment (in [`commitment_slots`] order) with a
+    /// different valid curve point, so the proof still passes point
+    /// validation and fails later in the protocol
+    CorruptCommitment(usize),
+    /// Zero every coefficient of the sumcheck univariate of this round.
+    /// Rounds at or past the circuit's `log_n` are padding and already zero.
+    ZeroSumcheckUnivariate(usize),
+}
+
+impl Mutation {
+    /// Apply the mutation in place
+    ///
+    /// The ZK/non-ZK layout is detected from the proof length.
+    pub fn apply(&self, proof: &mut [u8]) -> Result<(), MutationError> {
+        let (is_zk, _format) = Proof::detect_format(proof)?;
+        match *self {
+            Mutation::FlipByte(offset) => {
+                let len = proof.len();
+                let byte = proof
+                    .get_mut(offset)
+                    .ok_or(MutationError::OffsetOutOfRange { offset, len })?;
+                *byte ^= 0xFF;
+            }
+            Mutation::CorruptCommitment(index) => {
+                let slots = commitment_slots(proof)?;
+                let count = slots.len();
+                let (_, _, offset) = *slots
+                    .get(index)
+                    .ok_or(MutationError::CommitmentOutOfRange { index, count })?;
+                let slot = &mut proof[offset..offset + G1_LIMBED_SIZE];
+                let generator = g1_to_limbed(&G1_GENERATOR);
+                let replacement = if slot == generator.as_slice() {
+                    let neg = ops::g1_neg(&G1_GENERATOR).expect("generator is on the curve");
+                    g1_to_limbed(&neg)
+                } else {
+                    generator
+                };
+                slot.copy_from_slice(&replacement);
+            }
+            Mutation::ZeroSumcheckUnivariate(round) => {
+                if round >= CONST_PROOF_SIZE_LOG_N {
+                    return Err(MutationError::RoundOutOfRange { round });
+                }
+                let univariate_len = if is_zk {
+                    ZK_BATCHED_RELATION_PARTIAL_LENGTH
+                } else {
+                    BATCHED_RELATION_PARTIAL_LENGTH
+                };
+                let parsed = Proof::from_bytes(proof, 0, is_zk)?;
+                let offset =
+                    parsed.sumcheck_univariates_offset() + round * univariate_len * FR_SIZE;
+                proof[offset..offset + univariate_len * FR_SIZE].fill(0);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy `proof` and apply `mutation` to the copy
+pub fn mutate(proof: &[u8], mutation: Mutation) -> Result<Vec<u8>, MutationError> {
+    let mut out = proof.to_vec();
+    mutation.apply(&mut out)?;
+    Ok(out)
+}
+
+/// Every G1 commitment in the proof as `(name, index, byte offset)`
+///
+/// Position in this list is the index [`Mutation::CorruptCommitment`] takes.
+pub fn commitment_slots(proof: &[u8]) -> Result<Vec<(&'static str, usize, usize)>, ProofError> {
+    let (is_zk, _format) = Proof::detect_format(proof)?;
+    let parsed = Proof::from_bytes(proof, 0, is_zk)?;
+    Ok(parsed
+        .g1_slots()
+        .into_iter()
+        .map(|(name, index, offset, _allow_identity)| (name, index, offset))
+        .collect())
+}
+
+/// Split an affine point into the proof's limbed encoding
+/// (x_0 || x_1 || y_0 || y_1, low 136 / high 120 bits per coordinate)
+fn g1_to_limbed(point: &G1) -> [u8; G1_LIMBED_SIZE] {
+    let mut limbed = [0u8; G1_LIMBED_SIZE];
+    for (coord, out) in point.chunks(32).zip(limbed.chunks_mut(64)) {
+        out[15..32].copy_from_slice(&coord[15..32]);
+        out[49..64].copy_from_slice(&coord[0..15]);
+    }
+    limbed
+}
+
+/// Run the transcript for a proof and return its challenges
+///
+/// Unlike [`verify`](crate::verify) this does not check the public input
+/// count, so it also works on proofs a fuzzer has made inconsistent.
+pub fn challenges_for(
+    vk_bytes: &[u8],
+    proof_bytes: &[u8],
+    public_inputs: &[Fr],
+    is_zk: bool,
+) -> Result<Challenges, VerifyError> {
+    let vk = VerificationKey::from_bytes(vk_bytes)?;
+    let proof = Proof::from_bytes(proof_bytes, vk.log2_circuit_size as usize, is_zk)?;
+    verify_step1_challenges(&vk, &proof, public_inputs)
+}
+
+/// Render challenges as `name = 0x..` lines, one per field element
+///
+/// The order and naming are stable, so the output can be committed as a
+/// golden file and diffed against other verifiers' transcripts.
+pub fn dump_challenges(challenges: &Challenges) -> String {
+    let mut out = String::new();
+    let mut line = |name: &str, fr: &Fr| {
+        let _ = writeln!(out, "{} = {}", name, fr_to_hex(fr));
+    };
+
+    let rp = &challenges.relation_params;
+    line("eta", &rp.eta);
+    line("eta_two", &rp.eta_two);
+    line("eta_three", &rp.eta_three);
+    line("beta", &rp.beta);
+    line("gamma", &rp.gamma);
+    line("public_input_delta", &rp.public_input_delta);
+    line("alpha", &challenges.alpha);
+    for (i, alpha) in challenges.alphas.iter().enumerate() {
+        line(&format!("alphas[{}]", i), alpha);
+    }
+    if let Some(libra) = &challenges.libra_challenge {
+        line("libra_challenge", libra);
+    }
+    for (i, gate) in challenges.gate_challenges.iter().enumerate() {
+        line(&format!("gate_challenges[{}]", i), gate);
+    }
+    for (i, u) in challenges.sumcheck_challenges.iter().enumerate() {
+        line(&format!("sumcheck_challenges[{}]", i), u);
+    }
+    line("rho", &challenges.rho);
+    line("gemini_r", &challenges.gemini_r);
+    line("shplonk_nu", &challenges.shplonk_nu);
+    line("shplonk_z", &challenges.shplonk_z);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::{g1_from_limbed, EXPECTED_NON_ZK_PROOF_SIZE, EXPECTED_ZK_PROOF_SIZE};
+
+    #[test]
+    fn test_limbed_roundtrip() {
+        let neg = ops::g1_neg(&G1_GENERATOR).unwrap();
+        for point in [G1_GENERATOR, neg] {
+            assert_eq!(g1_from_limbed(&g1_to_limbed(&point)), point);
+        }
+    }
+
+    #[test]
+    fn test_flip_byte() {
+        let proof = vec![0u8; EXPECTED_ZK_PROOF_SIZE];
+        let out = mutate(&proof, Mutation::FlipByte(7)).unwrap();
+        assert_eq!(out[7], 0xFF);
+        assert_eq!(out.iter().filter(|b| **b != 0).count(), 1);
+
+        assert!(matches!(
+            mutate(&proof, Mutation::FlipByte(EXPECTED_ZK_PROOF_SIZE)),
+            Err(MutationError::OffsetOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_commitment_writes_valid_point() {
+        let proof = vec![0u8; EXPECTED_ZK_PROOF_SIZE];
+        let slots = commitment_slots(&proof).unwrap();
+        let (_, _, offset) = slots[3];
+
+        let once = mutate(&proof, Mutation::CorruptCommitment(3)).unwrap();
+        let limbed: [u8; G1_LIMBED_SIZE] =
+            once[offset..offset + G1_LIMBED_SIZE].try_into().unwrap();
+        assert_eq!(g1_from_limbed(&limbed), G1_GENERATOR);
+
+        // Corrupting twice still changes the slot
+        let twice = mutate(&once, Mutation::CorruptCommitment(3)).unwrap();
+        assert_ne!(
+            once[offset..offset + G1_LIMBED_SIZE],
+            twice[offset..offset + G1_LIMBED_SIZE]
+        );
+
+        assert!(matches!(
+            mutate(&proof, Mutation::CorruptCommitment(slots.len())),
+            Err(MutationError::CommitmentOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_zero_sumcheck_univariate() {
+        let proof = vec![0xAAu8; EXPECTED_NON_ZK_PROOF_SIZE];
+        let out = mutate(&proof, Mutation::ZeroSumcheckUnivariate(2)).unwrap();
+
+        let parsed = Proof::from_bytes(&out, 0, false).unwrap();
+        assert!(parsed
+            .sumcheck_univariates_for_round(2)
+            .iter()
+            .all(|c| *c == [0u8; 32]));
+        assert!(parsed.sumcheck_univariate(1, 0) != [0u8; 32]);
+        assert!(parsed.sumcheck_univariate(3, 0) != [0u8; 32]);
+
+        assert!(matches!(
+            mutate(
+                &proof,
+                Mutation::ZeroSumcheckUnivariate(CONST_PROOF_SIZE_LOG_N)
+            ),
+            Err(MutationError::RoundOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_wrong_size() {
+        assert!(matches!(
+            mutate(&[0u8; 10], Mutation::FlipByte(0)),
+            Err(MutationError::Proof(ProofError::InvalidSize { .. }))
+        ));
+    }
+
+    fn load_test_artifacts() -> Option<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+        let base = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../test-circuits/simple_square/target/keccak");
+        Some((
+            std::fs::read(base.join("vk")).ok()?,
+            std::fs::read(base.join("proof")).ok()?,
+            std::fs::read(base.join("public_inputs")).ok()?,
+        ))
+    }
+
+    #[test]
+    fn test_mutations_reject_real_proof() {
+        let Some((vk, proof, pi_bytes)) = load_test_artifacts() else {
+            println!("⚠️  Test artifacts not found. Skipping test.");
+            return;
+        };
+        let public_inputs: Vec<Fr> = pi_bytes.chunks(32).map(|c| c.try_into().unwrap()).collect();
+        assert!(crate::verify(&vk, &proof, &public_inputs, true).is_ok());
+
+        let num_commitments = commitment_slots(&proof).unwrap().len();
+        let mutations = [
+            Mutation::FlipByte(0),
+            Mutation::FlipByte(proof.len() - 1),
+            Mutation::CorruptCommitment(0),
+            Mutation::CorruptCommitment(num_commitments - 1),
+            Mutation::ZeroSumcheckUnivariate(0),
+        ];
+        for mutation in mutations {
+            let bad = mutate(&proof, mutation).unwrap();
+            assert!(
+                crate::verify(&vk, &bad, &public_inputs, true).is_err(),
+                "{:?} should be rejected",
+                mutation
+            );
+        }
+
+        // The dump is deterministic and changes with the transcript
+        let golden = dump_challenges(&challenges_for(&vk, &proof, &public_inputs, true).unwrap());
+        let again = dump_challenges(&challenges_for(&vk, &proof, &public_inputs, true).unwrap());
+        assert_eq!(golden, again);
+        assert!(golden.starts_with("eta = 0x"));
+
+        let bad = mutate(&proof, Mutation::CorruptCommitment(0)).unwrap();
+        let shifted = dump_challenges(&challenges_for(&vk, &bad, &public_inputs, true).unwrap());
+        assert_ne!(golden, shifted);
+    }
+}
@@ -5,13 +5,32 @@
 //!
 //! On Solana, uses the sol_keccak256 syscall (~100 CUs).
 //! Off-chain, uses pure Rust sha3 implementation.
-
+//!
+//! # Recomputing challenges
+//!
+//! Tools outside the verifier (indexers, debuggers, other clients) can
+//! recompute a proof's challenges with [`ChallengeSet::generate`], either in
+//! one go or from the phased results ([`generate_challenges_phase1a`] through
+//! [`generate_challenges_phase1d`]) via [`ChallengeSet::from_phases`]. Both
+//! give the same set, and [`ChallengeSet::to_json`] renders it for comparison
+//! with the Solidity verifier or `bb`'s transcript.
+
+use crate::errors::VerifyError;
 use crate::field::limbs_to_fr;
+use crate::key::VerificationKey;
+use crate::proof::Proof;
 use crate::types::{Fr, G1};
 
 extern crate alloc;
+use alloc::string::String;
 use alloc::vec::Vec;
 
+pub use crate::verifier::{
+    generate_challenges, generate_challenges_phase1a, generate_challenges_phase1a_streamed,
+    generate_challenges_phase1b, generate_challenges_phase1c, generate_challenges_phase1d,
+    Challenges, Phase1aResult, Phase1bResult, Phase1cResult, Phase1dResult,
+};
+
 /// Transcript for Fiat-Shamir challenge generation
 /// Uses a buffer to accumulate data, then hashes it all at once
 pub struct Transcript {
@@ -71,24 +90,7 @@ impl Transcript {
     /// Hash the current buffer contents
     #[inline(always)]
     fn hash_buffer(&self) -> [u8; 32] {
-        // Use syscall on Solana (target_os=solana, target_arch=bpf or sbpf)
-        #[cfg(any(target_os = "solana", target_arch = "bpf", target_arch = "sbpf"))]
-        {
-            let hash = solana_keccak_hasher::hash(&self.buffer);
-            hash.to_bytes()
-        }
-
-        // Use pure Rust sha3 off-chain
-        #[cfg(not(any(target_os = "solana", target_arch = "bpf", target_arch = "sbpf")))]
-        {
-            use sha3::{Digest, Keccak256};
-            let mut hasher = Keccak256::new();
-            hasher.update(&self.buffer);
-            let result = hasher.finalize();
-            let mut hash_bytes = [0u8; 32];
-            hash_bytes.copy_from_slice(&result);
-            hash_bytes
-        }
+        keccak256(&self.buffer)
     }
 
     /// Internal: Generate a raw challenge and update transcript state.
@@ -143,12 +145,75 @@ impl Transcript {
     }
 }
 
+/// Public inputs serialized as consecutive 32-byte big-endian elements,
+/// read in place
+///
+/// A trailing partial element is ignored; callers check the length.
+pub fn iter_public_inputs(bytes: &[u8]) -> impl ExactSizeIterator<Item = &Fr> + Clone {
+    bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().expect("chunks_exact yields 32 bytes"))
+}
+
+/// Append serialized public inputs to the transcript without collecting
+/// them into a `Vec<Fr>` first
+///
+/// Absorbs exactly what appending each element with
+/// [`Transcript::append_scalar`] would, so `bytes` can be the public input
+/// region of a proof buffer account however many inputs it holds.
+pub fn append_public_inputs(transcript: &mut Transcript, bytes: &[u8]) {
+    for pi in iter_public_inputs(bytes) {
+        transcript.append_scalar(pi);
+    }
+}
+
 impl Default for Transcript {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Keccak256 of `data`: syscall on Solana, pure Rust sha3 elsewhere
+#[inline(always)]
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    // Use syscall on Solana (target_os=solana, target_arch=bpf or sbpf)
+    #[cfg(any(target_os = "solana", target_arch = "bpf", target_arch = "sbpf"))]
+    {
+        solana_keccak_hasher::hash(data).to_bytes()
+    }
+
+    // Use pure Rust sha3 off-chain
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf", target_arch = "sbpf")))]
+    {
+        use sha3::{Digest, Keccak256};
+        let mut hasher = Keccak256::new();
+        hasher.update(data);
+        let result = hasher.finalize();
+        let mut hash_bytes = [0u8; 32];
+        hash_bytes.copy_from_slice(&result);
+        hash_bytes
+    }
+}
+
+/// Keccak256 of the concatenation of `parts`, without copying them together
+#[inline(always)]
+pub fn keccak256v(parts: &[&[u8]]) -> [u8; 32] {
+    #[cfg(any(target_os = "solana", target_arch = "bpf", target_arch = "sbpf"))]
+    {
+        solana_keccak_hasher::hashv(parts).to_bytes()
+    }
+
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf", target_arch = "sbpf")))]
+    {
+        use sha3::{Digest, Keccak256};
+        let mut hasher = Keccak256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+}
+
 /// Reduce a 32-byte hash to Fr by interpreting as big-endian modular reduction
 /// Public version for use by other modules
 pub fn reduce_hash_to_fr_public(hash: &[u8; 32]) -> Fr {
@@ -248,6 +313,154 @@ pub fn split_challenge(challenge: &Fr) -> (Fr, Fr) {
     (lo, hi)
 }
 
+/// Every Fiat-Shamir challenge of a proof, plus the public input delta
+///
+/// The fields and their order are stable: this is the form to store or
+/// compare, while [`Challenges`] and the phase results are shaped around the
+/// verifier's own needs. `sumcheck_challenges` always has
+/// `CONST_PROOF_SIZE_LOG_N` entries, including the padding rounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChallengeSet {
+    pub eta: Fr,
+    pub eta_two: Fr,
+    pub eta_three: Fr,
+    pub beta: Fr,
+    pub gamma: Fr,
+    pub public_input_delta: Fr,
+    pub alphas: Vec<Fr>,
+    pub gate_challenges: Vec<Fr>,
+    /// ZK proofs only
+    pub libra_challenge: Option<Fr>,
+    pub sumcheck_challenges: Vec<Fr>,
+    pub rho: Fr,
+    pub gemini_r: Fr,
+    pub shplonk_nu: Fr,
+    pub shplonk_z: Fr,
+}
+
+impl ChallengeSet {
+    /// Run the whole transcript for a proof
+    ///
+    /// The proof's points are not validated, see [`generate_challenges`].
+    pub fn generate(
+        vk: &VerificationKey,
+        proof: &Proof,
+        public_inputs: &[Fr],
+    ) -> Result<Self, VerifyError> {
+        generate_challenges(vk, proof, public_inputs).map(Self::from)
+    }
+
+    /// Assemble the set from phased challenge generation
+    ///
+    /// The phases do not compute the public input delta; pass the result of
+    /// [`compute_delta_part2`](crate::compute_delta_part2).
+    pub fn from_phases(
+        phase1a: &Phase1aResult,
+        phase1b: &Phase1bResult,
+        phase1c: &Phase1cResult,
+        phase1d: &Phase1dResult,
+        public_input_delta: Fr,
+    ) -> Self {
+        let mut sumcheck_challenges = phase1c.sumcheck_challenges.clone();
+        sumcheck_challenges.extend_from_slice(&phase1d.sumcheck_challenges);
+        Self {
+            eta: phase1a.eta,
+            eta_two: phase1a.eta_two,
+            eta_three: phase1a.eta_three,
+            beta: phase1a.beta,
+            gamma: phase1a.gamma,
+            public_input_delta,
+            alphas: phase1b.alphas.clone(),
+            gate_challenges: phase1b.gate_challenges.clone(),
+            libra_challenge: phase1b.libra_challenge,
+            sumcheck_challenges,
+            rho: phase1d.rho,
+            gemini_r: phase1d.gemini_r,
+            shplonk_nu: phase1d.shplonk_nu,
+            shplonk_z: phase1d.shplonk_z,
+        }
+    }
+
+    /// Render as a JSON object with `0x`-prefixed big-endian hex values
+    ///
+    /// Keys follow the field order; `libra_challenge` is `null` for
+    /// non-ZK proofs.
+    pub fn to_json(&self) -> String {
+        use crate::debug::fr_to_hex;
+        use core::fmt::Write;
+
+        fn array(values: &[Fr]) -> String {
+            let mut out = String::from("[");
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                let _ = write!(out, "\"{}\"", fr_to_hex(v));
+            }
+            out.push(']');
+            out
+        }
+        let scalar = |v: &Fr| alloc::format!("\"{}\"", fr_to_hex(v));
+
+        let fields: [(&str, String); 14] = [
+            ("eta", scalar(&self.eta)),
+            ("eta_two", scalar(&self.eta_two)),
+            ("eta_three", scalar(&self.eta_three)),
+            ("beta", scalar(&self.beta)),
+            ("gamma", scalar(&self.gamma)),
+            ("public_input_delta", scalar(&self.public_input_delta)),
+            ("alphas", array(&self.alphas)),
+            ("gate_challenges", array(&self.gate_challenges)),
+            (
+                "libra_challenge",
+                self.libra_challenge
+                    .as_ref()
+                    .map_or_else(|| String::from("null"), scalar),
+            ),
+            ("sumcheck_challenges", array(&self.sumcheck_challenges)),
+            ("rho", scalar(&self.rho)),
+            ("gemini_r", scalar(&self.gemini_r)),
+            ("shplonk_nu", scalar(&self.shplonk_nu)),
+            ("shplonk_z", scalar(&self.shplonk_z)),
+        ];
+
+        let mut out = String::from("{\n");
+        for (i, (key, value)) in fields.iter().enumerate() {
+            let comma = if i + 1 < fields.len() { "," } else { "" };
+            let _ = writeln!(out, "  \"{}\": {}{}", key, value, comma);
+        }
+        out.push('}');
+        out
+    }
+}
+
+impl From<Challenges> for ChallengeSet {
+    fn from(c: Challenges) -> Self {
+        let rp = c.relation_params;
+        Self {
+            eta: rp.eta,
+            eta_two: rp.eta_two,
+            eta_three: rp.eta_three,
+            beta: rp.beta,
+            gamma: rp.gamma,
+            public_input_delta: rp.public_input_delta,
+            alphas: c.alphas,
+            gate_challenges: c.gate_challenges,
+            libra_challenge: c.libra_challenge,
+            sumcheck_challenges: c.sumcheck_challenges,
+            rho: c.rho,
+            gemini_r: c.gemini_r,
+            shplonk_nu: c.shplonk_nu,
+            shplonk_z: c.shplonk_z,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,4 +533,122 @@ mod tests {
         // Just verify we can generate a challenge without crashing
         let _ = t.challenge_split();
     }
+
+    /// A 2^6 circuit with one user input and a ZK proof whose commitments
+    /// are all the generator, so it passes point validation
+    fn synthetic_inputs() -> (Vec<u8>, Vec<u8>, Vec<Fr>) {
+        use crate::key::VK_SIZE_NEW;
+        use crate::proof::EXPECTED_ZK_PROOF_SIZE;
+
+        let mut vk = alloc::vec![0u8; VK_SIZE_NEW];
+        vk[7] = 64;
+        vk[15] = 6;
+        vk[23] = 17;
+        vk[31] = 1;
+
+        let mut proof: Vec<u8> = (0..EXPECTED_ZK_PROOF_SIZE)
+            .map(|i| if i % 32 == 31 { i as u8 } else { 0 })
+            .collect();
+        let slots = Proof::from_bytes(&proof, 6, true).unwrap().g1_slots();
+        for (_, _, offset, _) in slots {
+            proof[offset..offset + 128].fill(0);
+            proof[offset + 31] = 1;
+            proof[offset + 95] = 2;
+        }
+
+        let mut pi = [0u8; 32];
+        pi[31] = 9;
+        (vk, proof, alloc::vec![pi])
+    }
+
+    #[test]
+    fn test_challenge_set_phases_match_full_transcript() {
+        use crate::{compute_delta_part1, compute_delta_part2};
+
+        let (vk_bytes, proof_bytes, public_inputs) = synthetic_inputs();
+        let vk = VerificationKey::from_bytes(&vk_bytes).unwrap();
+        let proof = Proof::from_bytes(&proof_bytes, 6, true).unwrap();
+
+        let full = ChallengeSet::generate(&vk, &proof, &public_inputs).unwrap();
+
+        let a = generate_challenges_phase1a(&vk, &proof, &public_inputs).unwrap();
+        let b = generate_challenges_phase1b(&proof, &a.transcript_state).unwrap();
+        let c = generate_challenges_phase1c(&proof, &b.transcript_state).unwrap();
+        let d = generate_challenges_phase1d(&proof, &c.transcript_state, true).unwrap();
+        let partial =
+            compute_delta_part1(&public_inputs, &proof, &a.beta, &a.gamma, vk.circuit_size());
+        let delta = compute_delta_part2(&proof, &a.beta, &partial);
+
+        let phased = ChallengeSet::from_phases(&a, &b, &c, &d, delta);
+        assert_eq!(phased, full);
+        assert_eq!(full.gate_challenges.len(), 6);
+        assert_eq!(
+            full.sumcheck_challenges.len(),
+            crate::proof::CONST_PROOF_SIZE_LOG_N
+        );
+        assert!(full.libra_challenge.is_some());
+    }
+
+    #[test]
+    fn test_challenge_set_json() {
+        let (vk_bytes, proof_bytes, public_inputs) = synthetic_inputs();
+        let vk = VerificationKey::from_bytes(&vk_bytes).unwrap();
+        let proof = Proof::from_bytes(&proof_bytes, 6, true).unwrap();
+        let mut set = ChallengeSet::generate(&vk, &proof, &public_inputs).unwrap();
+
+        let json = set.to_json();
+        assert!(json.starts_with("{\n  \"eta\": \"0x"));
+        assert!(json.ends_with("\"\n}"));
+        assert!(json.contains("\"libra_challenge\": \"0x"));
+        assert_eq!(json.matches("0x").count(), 11 + set.alphas.len() + 6 + 28);
+
+        set.libra_challenge = None;
+        assert!(set.to_json().contains("\"libra_challenge\": null,"));
+    }
+
+    #[test]
+    fn test_append_public_inputs_matches_append_scalar() {
+        let inputs: Vec<Fr> = (1u8..=5).map(|i| [i; 32]).collect();
+        let bytes = inputs.as_flattened();
+
+        let mut streamed = Transcript::new();
+        append_public_inputs(&mut streamed, bytes);
+        let mut scalars = Transcript::new();
+        for pi in &inputs {
+            scalars.append_scalar(pi);
+        }
+        assert_eq!(streamed.challenge(), scalars.challenge());
+        assert_eq!(iter_public_inputs(bytes).len(), 5);
+    }
+
+    #[test]
+    fn test_streamed_phase1_matches_vec() {
+        use crate::{
+            compute_delta_part1, compute_delta_part1_streamed, generate_challenges_phase1a,
+        };
+
+        let (vk_bytes, proof_bytes, _) = synthetic_inputs();
+        let vk = VerificationKey::from_bytes(&vk_bytes).unwrap();
+        let proof = Proof::from_bytes(&proof_bytes, 6, true).unwrap();
+        let inputs: Vec<Fr> = (0u8..40).map(|i| [i; 32]).collect();
+        let bytes = inputs.as_flattened();
+
+        let a = generate_challenges_phase1a(&vk, &proof, &inputs).unwrap();
+        let b = generate_challenges_phase1a_streamed(&vk, &proof, bytes).unwrap();
+        assert_eq!(a.transcript_state, b.transcript_state);
+        assert_eq!((a.beta, a.gamma), (b.beta, b.gamma));
+
+        let pa = compute_delta_part1(&inputs, &proof, &a.beta, &a.gamma, 64);
+        let pb = compute_delta_part1_streamed(bytes, &proof, &a.beta, &a.gamma, 64);
+        assert_eq!(
+            (pa.numerator, pa.denominator),
+            (pb.numerator, pb.denominator)
+        );
+        assert_eq!(pb.items_processed, 48);
+
+        assert!(matches!(
+            generate_challenges_phase1a_streamed(&vk, &proof, &bytes[..33]),
+            Err(VerifyError::PublicInput(_))
+        ));
+    }
 }
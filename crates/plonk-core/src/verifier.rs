@@ -8,19 +8,24 @@
 //! 3. ShpleminiVerifier: Verify batched polynomial commitment opening
 //! 4. Final pairing check via Solana BN254 syscalls
 
-use crate::errors::VerifyError;
+use crate::errors::{KeyError, VerifyError};
 use crate::field::{fr_add, fr_from_u64, fr_mul, fr_sub};
 use crate::key::VerificationKey;
 use crate::ops;
-use crate::proof::Proof;
-use crate::transcript::Transcript;
-use crate::types::{Fr, G1, SCALAR_ONE};
+use crate::proof::{Proof, ProofVariant};
+use crate::transcript::{append_public_inputs, iter_public_inputs, Transcript};
+use crate::types::{Fr, G1, G2, SCALAR_ONE};
 
 extern crate alloc;
 use alloc::vec::Vec;
 
 /// Relation parameters derived from the transcript
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RelationParameters {
     pub eta: Fr,
     pub eta_two: Fr,
@@ -32,6 +37,11 @@ pub struct RelationParameters {
 
 /// Challenges for the verification protocol
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Challenges {
     pub relation_params: RelationParameters,
     pub alpha: Fr,
@@ -64,6 +74,7 @@ pub fn verify(
 ) -> Result<(), VerifyError> {
     // Parse verification key first to get log_n
     let vk = VerificationKey::from_bytes(vk_bytes)?;
+    vk.validate()?;
 
     // Get log_circuit_size from VK
     let log_n = vk.log2_circuit_size as usize;
@@ -90,6 +101,36 @@ pub fn verify(
     verify_inner(&vk, &proof, public_inputs)
 }
 
+/// Verify an UltraHonk proof, detecting ZK vs non-ZK from the proof bytes
+///
+/// Same as [`verify`], with `is_zk` taken from [`ProofVariant::detect`].
+pub fn verify_auto(
+    vk_bytes: &[u8],
+    proof_bytes: &[u8],
+    public_inputs: &[Fr],
+) -> Result<(), VerifyError> {
+    verify_variant(
+        vk_bytes,
+        proof_bytes,
+        public_inputs,
+        ProofVariant::detect(proof_bytes)?,
+    )
+}
+
+/// Verify a proof of a named bb flavor
+///
+/// Fails with [`ProofError::UnsupportedVariant`](crate::errors::ProofError::UnsupportedVariant)
+/// for flavors the verifier does not implement, before parsing anything.
+pub fn verify_variant(
+    vk_bytes: &[u8],
+    proof_bytes: &[u8],
+    public_inputs: &[Fr],
+    variant: ProofVariant,
+) -> Result<(), VerifyError> {
+    let is_zk = variant.require_supported()?;
+    verify(vk_bytes, proof_bytes, public_inputs, is_zk)
+}
+
 /// Internal verification with parsed structures
 #[inline(never)]
 pub fn verify_inner(
@@ -97,6 +138,9 @@ pub fn verify_inner(
     proof: &Proof,
     public_inputs: &[Fr],
 ) -> Result<(), VerifyError> {
+    // Reject malformed commitments before they reach the transcript or syscalls
+    proof.validate_points()?;
+
     // Step 1: Generate challenges via Fiat-Shamir transcript
     let challenges = generate_challenges(vk, proof, public_inputs)?;
 
@@ -123,7 +167,7 @@ pub fn verify_inner(
 
     // Step 4: Final pairing check: e(P0, G2_gen) * e(P1, G2_x) == 1
     // where G2_x is the x·G2 from the trusted setup
-    let pairing_result = ops::pairing_check(&[(p0, g2_generator()), (p1, vk_g2())])?;
+    let pairing_result = ops::pairing_check(&[(p0, g2_generator()), (p1, default_srs_g2())])?;
 
     if pairing_result {
         crate::trace!("VERIFICATION PASSED!");
@@ -141,6 +185,7 @@ pub fn verify_step1_challenges(
     proof: &Proof,
     public_inputs: &[Fr],
 ) -> Result<Challenges, VerifyError> {
+    proof.validate_points()?;
     generate_challenges(vk, proof, public_inputs)
 }
 
@@ -165,20 +210,52 @@ pub fn verify_step3_pairing_points(
 }
 
 /// Step 4: Final pairing check (for phased verification)
+///
+/// Uses the default SRS point, see [`default_srs_g2`].
 #[inline(never)]
 pub fn verify_step4_pairing_check(p0: &G1, p1: &G1) -> Result<bool, VerifyError> {
+    verify_step4_pairing_check_with_srs(p0, p1, &default_srs_g2())
+}
+
+/// Step 4 against a caller-supplied x·G2 SRS point
+///
+/// `srs_g2` must come from the same setup the VK commitments were generated
+/// with; check it once with [`validate_srs_g2`] when it is first accepted.
+#[inline(never)]
+pub fn verify_step4_pairing_check_with_srs(
+    p0: &G1,
+    p1: &G1,
+    srs_g2: &G2,
+) -> Result<bool, VerifyError> {
     Ok(ops::pairing_check(&[
         (*p0, g2_generator()),
-        (*p1, vk_g2()),
+        (*p1, *srs_g2),
     ])?)
 }
 
+/// Check that a custom x·G2 SRS point is usable
+///
+/// Rejects the identity (which would make the second pairing term vanish),
+/// non-canonical coordinates and points off the twist. Subgroup membership
+/// is not checked here; see [`ops::g2_is_on_curve`].
+pub fn validate_srs_g2(srs_g2: &G2) -> Result<(), KeyError> {
+    if srs_g2.iter().all(|&b| b == 0) || !ops::g2_is_on_curve(srs_g2) {
+        return Err(KeyError::InvalidSrsG2);
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Incremental Challenge Generation (for multi-TX verification)
 // ============================================================================
 
 /// Result from Phase 1a: eta, beta, gamma challenges
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Phase1aResult {
     pub eta: Fr,
     pub eta_two: Fr,
@@ -191,6 +268,11 @@ pub struct Phase1aResult {
 
 /// Result from Phase 1b: alphas and gate challenges  
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Phase1bResult {
     pub alphas: Vec<Fr>,
     pub gate_challenges: Vec<Fr>,
@@ -201,6 +283,11 @@ pub struct Phase1bResult {
 
 /// Result from Phase 1c: first half of sumcheck challenges (rounds 0-13)
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Phase1cResult {
     pub sumcheck_challenges: Vec<Fr>,
     /// Transcript state to continue from
@@ -209,6 +296,11 @@ pub struct Phase1cResult {
 
 /// Result from Phase 1d: remaining sumcheck + final challenges
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Phase1dResult {
     pub sumcheck_challenges: Vec<Fr>, // rounds 14-27
     pub rho: Fr,
@@ -219,12 +311,36 @@ pub struct Phase1dResult {
 
 /// Phase 1a: Generate eta, beta, gamma challenges
 /// Returns the challenges and transcript state to continue from
-#[inline(never)]
 pub fn generate_challenges_phase1a(
     vk: &VerificationKey,
     proof: &Proof,
     public_inputs: &[Fr],
 ) -> Result<Phase1aResult, VerifyError> {
+    generate_challenges_phase1a_streamed(vk, proof, public_inputs.as_flattened())
+}
+
+/// Phase 1a reading the public inputs in place from their serialized bytes
+///
+/// `public_inputs` is consecutive 32-byte big-endian elements, e.g. the
+/// public input region of a proof buffer account. Nothing is copied to the
+/// heap, so the input count is bounded by the account, not the BPF heap.
+#[inline(never)]
+pub fn generate_challenges_phase1a_streamed(
+    vk: &VerificationKey,
+    proof: &Proof,
+    public_inputs: &[u8],
+) -> Result<Phase1aResult, VerifyError> {
+    if !public_inputs.len().is_multiple_of(32) {
+        return Err(VerifyError::PublicInput(alloc::format!(
+            "Public input bytes ({}) are not a multiple of 32",
+            public_inputs.len()
+        )));
+    }
+
+    // Phase 1a is the entry point of phased verification; later phases
+    // trust the proof buffer from here on
+    proof.validate_points()?;
+
     let mut transcript = Transcript::new();
 
     // Circuit metadata
@@ -237,9 +353,7 @@ pub fn generate_challenges_phase1a(
     transcript.append_u64(pub_inputs_offset);
 
     // Public inputs
-    for pi in public_inputs.iter() {
-        transcript.append_scalar(pi);
-    }
+    append_public_inputs(&mut transcript, public_inputs);
 
     // Pairing point object (16 Fr values)
     let ppo = proof.pairing_point_object();
@@ -600,6 +714,11 @@ pub fn generate_challenges_phase1d(
 
 /// Result from partial delta computation
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeltaPartialResult {
     pub numerator: Fr,
     pub denominator: Fr,
@@ -610,13 +729,32 @@ pub struct DeltaPartialResult {
 
 /// Compute public_input_delta - Phase 1: First 9 items
 /// Returns partial accumulators to continue in next TX
-#[inline(never)]
 pub fn compute_delta_part1(
     public_inputs: &[Fr],
     proof: &Proof,
     beta: &Fr,
     gamma: &Fr,
     circuit_size: u32,
+) -> DeltaPartialResult {
+    compute_delta_part1_streamed(
+        public_inputs.as_flattened(),
+        proof,
+        beta,
+        gamma,
+        circuit_size,
+    )
+}
+
+/// [`compute_delta_part1`] reading the public inputs in place from their
+/// serialized bytes (see [`generate_challenges_phase1a_streamed`])
+#[inline(never)]
+#[allow(clippy::needless_range_loop)]
+pub fn compute_delta_part1_streamed(
+    public_inputs: &[u8],
+    proof: &Proof,
+    beta: &Fr,
+    gamma: &Fr,
+    circuit_size: u32,
 ) -> DeltaPartialResult {
     use crate::field::{fr_add, fr_from_u64, fr_mul, fr_sub};
     use crate::types::SCALAR_ONE;
@@ -634,6 +772,8 @@ pub fn compute_delta_part1(
     let mut denominator_acc = fr_sub(gamma, &fr_mul(beta, &offset_plus_one));
 
     // Process public inputs (usually 1)
+    let public_inputs = iter_public_inputs(public_inputs);
+    let num_public_inputs = public_inputs.len();
     for pi in public_inputs {
         numerator = fr_mul(&numerator, &fr_add(&numerator_acc, pi));
         denominator = fr_mul(&denominator, &fr_add(&denominator_acc, pi));
@@ -655,12 +795,13 @@ pub fn compute_delta_part1(
         denominator,
         numerator_acc,
         denominator_acc,
-        items_processed: public_inputs.len() + 8,
+        items_processed: num_public_inputs + 8,
     }
 }
 
 /// Compute public_input_delta - Phase 2: Remaining 8 items + final division
 #[inline(never)]
+#[allow(clippy::needless_range_loop)]
 pub fn compute_delta_part2(proof: &Proof, beta: &Fr, partial: &DeltaPartialResult) -> Fr {
     use crate::field::{fr_add, fr_div, fr_mul, fr_sub};
     use crate::types::SCALAR_ONE;
@@ -685,9 +826,12 @@ pub fn compute_delta_part2(proof: &Proof, beta: &Fr, partial: &DeltaPartialResul
 
 /// Generate all challenges from the transcript
 ///
-/// Based on bb's UltraHonk transcript manifest (ultra_transcript.test.cpp)
+/// Based on bb's UltraHonk transcript manifest (ultra_transcript.test.cpp).
+/// Unlike [`verify_step1_challenges`] this does not validate the proof's
+/// points first, so it also runs on proofs that verification would reject.
+/// See [`crate::transcript`] for the stable [`ChallengeSet`](crate::transcript::ChallengeSet) form.
 #[inline(never)]
-fn generate_challenges(
+pub fn generate_challenges(
     vk: &VerificationKey,
     proof: &Proof,
     public_inputs: &[Fr],
@@ -719,8 +863,11 @@ fn generate_challenges(
     );
 
     // Add user public inputs (actual user inputs, not pairing points)
-    for (_i, pi) in public_inputs.iter().enumerate() {
-        crate::dbg_fr!(&alloc::format!("public_input[{}]", _i), pi);
+    #[cfg(feature = "debug")]
+    for (i, pi) in public_inputs.iter().enumerate() {
+        crate::dbg_fr!(&alloc::format!("public_input[{}]", i), pi);
+    }
+    for pi in public_inputs {
         transcript.append_scalar(pi);
     }
 
@@ -1049,42 +1196,6 @@ fn generate_challenges(
     })
 }
 
-/// Compute the VK hash for the transcript
-/// This matches bb's `vk->hash_with_origin_tagging` behavior
-/// For Keccak (U256Codec): each field/commitment is raw uint256_t
-#[allow(dead_code)]
-fn compute_vk_hash(vk: &VerificationKey) -> Fr {
-    use sha3::{Digest, Keccak256};
-
-    let mut hasher = Keccak256::new();
-
-    // Add VK header fields as uint256_t (32 bytes each, big-endian padded)
-    let mut buf = [0u8; 32];
-    buf[28..32].copy_from_slice(&vk.log2_circuit_size.to_be_bytes());
-    hasher.update(buf);
-
-    buf = [0u8; 32];
-    buf[28..32].copy_from_slice(&vk.log2_domain_size.to_be_bytes());
-    hasher.update(buf);
-
-    buf = [0u8; 32];
-    buf[28..32].copy_from_slice(&vk.num_public_inputs.to_be_bytes());
-    hasher.update(buf);
-
-    // Add all VK commitments as 64 bytes each (x || y)
-    for commitment in &vk.commitments {
-        hasher.update(commitment);
-    }
-
-    // Finalize and reduce to Fr
-    let hash = hasher.finalize();
-    let mut result = [0u8; 32];
-    result.copy_from_slice(&hash);
-
-    // Reduce mod r if needed
-    crate::transcript::reduce_hash_to_fr_public(&result)
-}
-
 /// Compute the public input contribution to the permutation argument
 /// Including the pairing point object (16 Fr values)
 fn compute_public_input_delta_with_ppo(
@@ -1217,9 +1328,11 @@ fn compute_pairing_points(
         .map_err(|_| VerifyError::VerificationFailed)
 }
 
-/// Get the x·G2 point from the trusted setup
-/// This is hardcoded because bb VK format doesn't contain G2 points
-fn vk_g2() -> crate::types::G2 {
+/// Default x·G2 point from the Aztec Ignition trusted setup
+///
+/// The bb VK format doesn't contain G2 points, so this is used whenever no
+/// other SRS point is supplied.
+pub fn default_srs_g2() -> G2 {
     // This is the x·G2 point from the trusted setup (SRS)
     // Used for the second pairing: e(P1, x·G2)
     let mut g2 = [0u8; 128];
@@ -1239,7 +1352,7 @@ fn vk_g2() -> crate::types::G2 {
 }
 
 /// BN254 G2 generator point
-fn g2_generator() -> crate::types::G2 {
+pub(crate) fn g2_generator() -> crate::types::G2 {
     // BN254 G2 generator coordinates (big-endian)
     // x = (x0, x1) where x = x0 + x1*i
     // y = (y0, y1) where y = y0 + y1*i
@@ -1263,169 +1376,11 @@ fn g2_generator() -> crate::types::G2 {
     g2
 }
 
-/// Convert pairing point object (16 Fr limbs) to two G1 points
-///
-/// The pairing points are serialized as 68-bit limbs (4 limbs per 256-bit coordinate)
-/// - lhs.x = limbs[0] | limbs[1] << 68 | limbs[2] << 136 | limbs[3] << 204
-/// - lhs.y = limbs[4..7]
-/// - rhs.x = limbs[8..11]
-/// - rhs.y = limbs[12..15]
-#[allow(dead_code)]
-fn convert_pairing_points_to_g1(ppo: &[Fr]) -> Result<(G1, G1), VerifyError> {
-    if ppo.len() != 16 {
-        return Err(VerifyError::PublicInput(alloc::format!(
-            "Expected 16 pairing point limbs, got {}",
-            ppo.len()
-        )));
-    }
-
-    // Helper to combine 4 68-bit limbs into a 256-bit value
-    // Fr values are big-endian 32-byte arrays, but limbs are small values (fit in ~68 bits)
-    fn combine_limbs(limbs: &[Fr]) -> [u8; 32] {
-        // Each limb is 68 bits. We combine them:
-        // val = limbs[0] | (limbs[1] << 68) | (limbs[2] << 136) | (limbs[3] << 204)
-
-        // Since Fr is big-endian, convert to little-endian for easier bit manipulation
-        let limb0 = fr_to_le(&limbs[0]);
-        let limb1 = fr_to_le(&limbs[1]);
-        let limb2 = fr_to_le(&limbs[2]);
-        let limb3 = fr_to_le(&limbs[3]);
-
-        // Combine using bit shifts (working in little-endian)
-        let mut combined = limb0;
-        combined = add_256_le(&combined, &shift_left_256_le(&limb1, 68));
-        combined = add_256_le(&combined, &shift_left_256_le(&limb2, 136));
-        combined = add_256_le(&combined, &shift_left_256_le(&limb3, 204));
-
-        // Convert back to big-endian for the result
-        le_to_be(&combined)
-    }
-
-    // Convert Fr (big-endian) to little-endian
-    fn fr_to_le(fr: &Fr) -> [u8; 32] {
-        let mut le = [0u8; 32];
-        for i in 0..32 {
-            le[i] = fr[31 - i];
-        }
-        le
-    }
-
-    // Convert little-endian to big-endian
-    fn le_to_be(le: &[u8; 32]) -> [u8; 32] {
-        let mut be = [0u8; 32];
-        for i in 0..32 {
-            be[i] = le[31 - i];
-        }
-        be
-    }
-
-    // Shift left in little-endian representation
-    fn shift_left_256_le(val: &[u8; 32], bits: usize) -> [u8; 32] {
-        let mut result = [0u8; 32];
-        let byte_shift = bits / 8;
-        let bit_shift = bits % 8;
-
-        if byte_shift >= 32 {
-            return result;
-        }
-
-        for i in byte_shift..32 {
-            let src_idx = i - byte_shift;
-            result[i] = val[src_idx] << bit_shift;
-            if bit_shift > 0 && src_idx > 0 {
-                result[i] |= val[src_idx - 1] >> (8 - bit_shift);
-            }
-        }
-
-        result
-    }
-
-    // Add two 256-bit values in little-endian
-    fn add_256_le(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
-        let mut result = [0u8; 32];
-        let mut carry: u16 = 0;
-
-        for i in 0..32 {
-            let sum = a[i] as u16 + b[i] as u16 + carry;
-            result[i] = sum as u8;
-            carry = sum >> 8;
-        }
-
-        result
-    }
-
-    // Extract coordinates
-    let lhs_x = combine_limbs(&ppo[0..4]);
-    let lhs_y = combine_limbs(&ppo[4..8]);
-    let rhs_x = combine_limbs(&ppo[8..12]);
-    let rhs_y = combine_limbs(&ppo[12..16]);
-
-    // Create G1 points (64 bytes each: x || y)
-    let mut lhs = [0u8; 64];
-    lhs[0..32].copy_from_slice(&lhs_x);
-    lhs[32..64].copy_from_slice(&lhs_y);
-
-    let mut rhs = [0u8; 64];
-    rhs[0..32].copy_from_slice(&rhs_x);
-    rhs[32..64].copy_from_slice(&rhs_y);
-
-    #[cfg(feature = "debug")]
-    {
-        crate::dbg_g1!("lhs from pairingPointObject", &lhs);
-        crate::dbg_g1!("rhs from pairingPointObject", &rhs);
-    }
-
-    Ok((lhs, rhs))
-}
-
-/// Generate recursion separator by hashing pairing points
-///
-/// Hashes: proofLhs, proofRhs, accLhs, accRhs -> keccak256 -> Fr (mod r)
-#[allow(dead_code)]
-fn generate_recursion_separator(
-    proof_lhs: &G1,
-    proof_rhs: &G1,
-    acc_lhs: &G1,
-    acc_rhs: &G1,
-) -> Result<Fr, VerifyError> {
-    use sha3::{Digest, Keccak256};
-
-    // Hash: proofLhs.x, proofLhs.y, proofRhs.x, proofRhs.y, accLhs.x, accLhs.y, accRhs.x, accRhs.y
-    let mut hasher = Keccak256::new();
-
-    hasher.update(&proof_lhs[0..32]); // proofLhs.x
-    hasher.update(&proof_lhs[32..64]); // proofLhs.y
-    hasher.update(&proof_rhs[0..32]); // proofRhs.x
-    hasher.update(&proof_rhs[32..64]); // proofRhs.y
-    hasher.update(&acc_lhs[0..32]); // accLhs.x
-    hasher.update(&acc_lhs[32..64]); // accLhs.y
-    hasher.update(&acc_rhs[0..32]); // accRhs.x
-    hasher.update(&acc_rhs[32..64]); // accRhs.y
-
-    let hash = hasher.finalize();
-
-    #[cfg(feature = "debug")]
-    {
-        let mut raw_hash = [0u8; 32];
-        raw_hash.copy_from_slice(&hash);
-        crate::dbg_fr!("  raw hash", &raw_hash);
-    }
-
-    // Convert to Fr with modular reduction (like Solidity's FrLib.fromBytes32)
-    // FrLib.fromBytes32: return Fr.wrap(uint256(value) % MODULUS)
-    let mut result = [0u8; 32];
-    result.copy_from_slice(&hash);
-
-    // Apply modular reduction
-    result = crate::field::fr_reduce(&result);
-
-    Ok(result)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::key::{VK_SIZE_NEW, VK_SIZE_OLD};
+    use crate::errors::ProofError;
+    use crate::key::VK_SIZE_NEW;
     use crate::proof::Proof as ProofStruct;
     use crate::types::SCALAR_ZERO;
 
@@ -1444,15 +1399,6 @@ mod tests {
         vk
     }
 
-    /// Create a test VK in the OLD format (1888 bytes, legacy)
-    fn create_test_vk_old() -> Vec<u8> {
-        let mut vk = vec![0u8; VK_SIZE_OLD];
-        vk[31] = 6; // log2_circuit_size = 6
-        vk[63] = 17; // log2_domain_size = 17
-        vk[95] = 1; // num_public_inputs = 1
-        vk
-    }
-
     fn create_test_proof(log_n: usize, is_zk: bool) -> Vec<u8> {
         let expected_fr = ProofStruct::expected_size(log_n, is_zk);
         vec![0u8; expected_fr * 32]
@@ -1481,6 +1427,21 @@ mod tests {
         let _ = result;
     }
 
+    #[test]
+    fn test_verify_unsupported_variant() {
+        let vk = create_test_vk();
+        let proof = create_test_proof(6, true);
+        let public_inputs: [[u8; 32]; 1] = [[0u8; 32]];
+
+        let result = verify_variant(&vk, &proof, &public_inputs, ProofVariant::UltraStarknetZk);
+        assert!(matches!(
+            result,
+            Err(VerifyError::Proof(ProofError::UnsupportedVariant(
+                ProofVariant::UltraStarknetZk
+            )))
+        ));
+    }
+
     #[test]
     fn test_public_input_delta_with_ppo() {
         let beta = fr_from_u64(2);
@@ -1815,6 +1776,90 @@ mod tests {
         }
     }
 
+    /// Precomputed relation coefficients batch a real proof's evaluations
+    /// like the per-subrelation `d` scaling of the Fr accumulators
+    ///
+    /// Compares one subrelation at a time, so the unscaled lookup inverses
+    /// subrelation (5) is checked on its own.
+    #[test]
+    fn test_relation_coefficients_match_real_proof() {
+        use crate::field::{fr_sub, FrLimbs};
+        use crate::relations::{
+            accumulate_relation_evaluations, accumulate_relation_evaluations_l,
+            accumulate_relation_evaluations_l_with_coefficients, relation_coefficients,
+            RelationParameters as RelParams, RelationParametersLimbs, NUM_SUBRELATIONS,
+        };
+        use crate::sumcheck::{sumcheck_rounds_init, verify_sumcheck_rounds_partial};
+
+        let Some((vk_bytes, proof_bytes, pi_bytes)) = load_test_artifacts() else {
+            println!("⚠️  Test artifacts not found. Skipping test.");
+            return;
+        };
+
+        let vk = VerificationKey::from_bytes(&vk_bytes).unwrap();
+        let log_n = vk.log2_circuit_size as usize;
+        let proof = ProofStruct::from_bytes(&proof_bytes, log_n, true).unwrap();
+        let public_inputs: Vec<Fr> = pi_bytes
+            .chunks_exact(32)
+            .map(|c| c.try_into().unwrap())
+            .collect();
+        let challenges = generate_challenges(&vk, &proof, &public_inputs).unwrap();
+
+        let sumcheck_challenges = crate::sumcheck::SumcheckChallenges {
+            gate_challenges: challenges.gate_challenges.clone(),
+            sumcheck_u_challenges: challenges.sumcheck_challenges.clone(),
+            alphas: challenges.alphas.clone(),
+        };
+        let init = sumcheck_rounds_init(&proof, challenges.libra_challenge.as_ref());
+        let rounds =
+            verify_sumcheck_rounds_partial(&proof, &sumcheck_challenges, &init, 0, log_n).unwrap();
+        let d = rounds.pow_partial;
+
+        let rp = &challenges.relation_params;
+        let rp_fr = RelParams {
+            eta: rp.eta,
+            eta_two: rp.eta_two,
+            eta_three: rp.eta_three,
+            beta: rp.beta,
+            gamma: rp.gamma,
+            public_inputs_delta: rp.public_input_delta,
+        };
+        let rp_l = RelationParametersLimbs::from_fr(&rp_fr);
+        let evals = proof.sumcheck_evaluations();
+        let evals_l: Vec<FrLimbs> = evals.iter().map(FrLimbs::from_bytes).collect();
+        let alphas = &challenges.alphas;
+        let alphas_l: Vec<FrLimbs> = alphas.iter().map(FrLimbs::from_bytes).collect();
+        let coeffs = relation_coefficients(alphas, &d);
+
+        let reference = accumulate_relation_evaluations(&evals, &rp_fr, alphas, &d);
+        let factored =
+            accumulate_relation_evaluations_l(&evals_l, &rp_l, &alphas_l, &FrLimbs::from_bytes(&d));
+        let precomputed =
+            accumulate_relation_evaluations_l_with_coefficients(&evals_l, &rp_l, &coeffs);
+        assert_eq!(factored.to_bytes(), reference);
+        assert_eq!(precomputed.to_bytes(), reference);
+
+        // Subrelation i alone: keep only alpha[i-1] in the reference and
+        // subtract subrelation 0, which no alpha scales
+        let no_alphas = vec![SCALAR_ZERO; alphas.len()];
+        let first = accumulate_relation_evaluations(&evals, &rp_fr, &no_alphas, &d);
+        for i in 0..NUM_SUBRELATIONS {
+            let expected = if i == 0 {
+                first
+            } else {
+                let mut only = no_alphas.clone();
+                only[i - 1] = alphas[i - 1];
+                let with = accumulate_relation_evaluations(&evals, &rp_fr, &only, &d);
+                fr_sub(&with, &first)
+            };
+            let mut single = [FrLimbs::ZERO; NUM_SUBRELATIONS];
+            single[i] = coeffs[i];
+            let actual =
+                accumulate_relation_evaluations_l_with_coefficients(&evals_l, &rp_l, &single);
+            assert_eq!(actual.to_bytes(), expected, "subrelation {i}");
+        }
+    }
+
     /// Test 5: Verify proof structure matches theory documentation
     ///
     /// Cross-reference with docs/theory.md Section 12 (Data Formats)
@@ -1883,7 +1928,7 @@ mod tests {
 
         // Test that our parser accepts different log_n values
         // In bb 0.87, proof size is FIXED based on CONST_PROOF_SIZE_LOG_N=28
-        let test_cases = vec![
+        let test_cases = [
             (6, true),   // Small circuit
             (10, true),  // Medium circuit
             (12, true),  // Our test circuit
@@ -1944,6 +1989,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_step4_with_custom_srs() {
+        let g = crate::types::G1_GENERATOR;
+        let neg_g = ops::g1_neg(&g).unwrap();
+
+        // With [x]₂ = [1]₂, e(G, [1]₂) · e(-G, [1]₂) == 1
+        let unit_srs = g2_generator();
+        assert!(verify_step4_pairing_check_with_srs(&g, &neg_g, &unit_srs).unwrap());
+        assert!(!verify_step4_pairing_check(&g, &neg_g).unwrap());
+        assert_eq!(
+            verify_step4_pairing_check(&g, &neg_g).unwrap(),
+            verify_step4_pairing_check_with_srs(&g, &neg_g, &default_srs_g2()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validate_srs_g2() {
+        assert!(validate_srs_g2(&default_srs_g2()).is_ok());
+        assert!(validate_srs_g2(&g2_generator()).is_ok());
+        assert!(matches!(
+            validate_srs_g2(&[0u8; 128]),
+            Err(KeyError::InvalidSrsG2)
+        ));
+
+        let mut off_curve = default_srs_g2();
+        off_curve[127] ^= 1;
+        assert!(validate_srs_g2(&off_curve).is_err());
+
+        // Coordinates >= q are rejected even if congruent to a valid point
+        let mut non_canonical = default_srs_g2();
+        non_canonical[..32].copy_from_slice(&[0xff; 32]);
+        assert!(validate_srs_g2(&non_canonical).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_challenges_borsh_roundtrip() {
+        let fr = |v: u8| {
+            let mut x = SCALAR_ZERO;
+            x[31] = v;
+            x
+        };
+        let challenges = Challenges {
+            relation_params: RelationParameters {
+                eta: fr(1),
+                eta_two: fr(2),
+                eta_three: fr(3),
+                beta: fr(4),
+                gamma: fr(5),
+                public_input_delta: fr(6),
+            },
+            alpha: fr(7),
+            alphas: (0..25).map(fr).collect(),
+            libra_challenge: Some(fr(8)),
+            gate_challenges: (0..28).map(fr).collect(),
+            sumcheck_challenges: (28..56).map(fr).collect(),
+            rho: fr(9),
+            gemini_r: fr(10),
+            shplonk_nu: fr(11),
+            shplonk_z: fr(12),
+        };
+
+        let bytes = borsh::to_vec(&challenges).unwrap();
+        let decoded: Challenges = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.alphas, challenges.alphas);
+        assert_eq!(decoded.libra_challenge, challenges.libra_challenge);
+        assert_eq!(decoded.relation_params.public_input_delta, fr(6));
+        assert_eq!(borsh::to_vec(&decoded).unwrap(), bytes);
+    }
+
     /// Test 7: Verify VK hash matches expected value
     ///
     /// Note: In bb 0.87, VK hash is NOT used in the transcript initialization.
@@ -1956,13 +2071,13 @@ mod tests {
         };
 
         let vk = crate::key::VerificationKey::from_bytes(&vk_bytes).unwrap();
-        let vk_hash = compute_vk_hash(&vk);
+        let vk_hash = vk.hash();
 
         // Verify VK hash is non-zero and deterministic
         assert_ne!(vk_hash, [0u8; 32], "VK hash should not be zero");
 
         // Compute again to verify determinism
-        let vk_hash2 = compute_vk_hash(&vk);
+        let vk_hash2 = vk.hash();
         assert_eq!(vk_hash, vk_hash2, "VK hash should be deterministic");
 
         // Log the hash for debugging
@@ -0,0 +1,164 @@
+//! Receipt PDA seeds for the Noir UltraHonk verifier
+//!
+//! A verification receipt lives at
+//! `find_program_address(["receipt", vk_account, keccak(public_inputs)], verifier)`.
+//! The verifier program creates receipts there, and the CPI crate and SDK
+//! look them up; all three derive the address through this crate so the
+//! scheme can only change in one place. The [`VerificationResult`] the
+//! verifier returns to its CPI callers is encoded here for the same reason.
+//!
+//! ```
+//! use solana_noir_receipt_seeds::{derive_receipt_pda, pi_hash, pi_hashv};
+//! use solana_pubkey::Pubkey;
+//!
+//! let (vk, verifier) = (Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32]));
+//! let public_inputs = [[3u8; 32], [4u8; 32]];
+//!
+//! let hash = pi_hash(public_inputs.as_flattened());
+//! assert_eq!(hash, pi_hashv(&[&public_inputs[0], &public_inputs[1]]));
+//! let (receipt, _bump) = derive_receipt_pda(&vk, &hash, &verifier);
+//! # let _ = receipt;
+//! ```
+
+#![no_std]
+
+use solana_pubkey::{Pubkey, PubkeyError};
+
+/// PDA seed prefix for verification receipts
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+/// Keccak256 of the raw public input bytes (32 bytes per input, big-endian)
+pub fn pi_hash(public_inputs: &[u8]) -> [u8; 32] {
+    solana_keccak_hasher::hash(public_inputs).to_bytes()
+}
+
+/// [`pi_hash`] of the concatenation of `parts`, without copying them
+pub fn pi_hashv(parts: &[&[u8]]) -> [u8; 32] {
+    solana_keccak_hasher::hashv(parts).to_bytes()
+}
+
+/// Keccak256 of the raw proof bytes, as receipts record them for
+/// re-attestation by digest
+pub fn proof_digest(proof: &[u8]) -> [u8; 32] {
+    solana_keccak_hasher::hash(proof).to_bytes()
+}
+
+/// Seeds of the receipt for `vk_account` and a public input hash, without
+/// the bump
+pub fn receipt_seeds<'a>(vk_account: &'a Pubkey, pi_hash: &'a [u8; 32]) -> [&'a [u8]; 3] {
+    [RECEIPT_SEED, vk_account.as_ref(), pi_hash]
+}
+
+/// Receipt address and bump for `vk_account` and a public input hash
+pub fn derive_receipt_pda(
+    vk_account: &Pubkey,
+    pi_hash: &[u8; 32],
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&receipt_seeds(vk_account, pi_hash), program_id)
+}
+
+/// Receipt address for a known bump, e.g. the one stored in the receipt
+///
+/// Cheaper than [`derive_receipt_pda`] on chain, since it hashes once
+/// instead of searching for the bump.
+pub fn create_receipt_address(
+    vk_account: &Pubkey,
+    pi_hash: &[u8; 32],
+    bump: u8,
+    program_id: &Pubkey,
+) -> Result<Pubkey, PubkeyError> {
+    let [prefix, vk, hash] = receipt_seeds(vk_account, pi_hash);
+    Pubkey::create_program_address(&[prefix, vk, hash, &[bump]], program_id)
+}
+
+/// Outcome the verifier reports through `sol_set_return_data`
+///
+/// `Verify`, `CreateReceipt` and `ReverifyByDigest` set it on success, so a
+/// program invoking them can read what was verified instead of relying on
+/// the CPI not failing. A failed verification aborts the instruction, and
+/// with it the caller's transaction, so a result read after a CPI always
+/// has `verified` set; the flag is kept so the format can report other
+/// outcomes later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerificationResult {
+    pub verified: bool,
+    /// Slot the proof was verified at (the receipt's `verified_slot` for
+    /// receipt instructions)
+    pub slot: u64,
+    /// Canonical hash of the VK the proof was verified against
+    /// (`VerificationKey::hash_with_srs`), as stored in the receipt
+    pub vk_hash: [u8; 32],
+    /// Keccak256 of the public inputs, see [`pi_hash`]
+    pub pi_hash: [u8; 32],
+}
+
+impl VerificationResult {
+    /// Encoded size: verified(1) + slot(8) + vk_hash(32) + pi_hash(32)
+    pub const SIZE: usize = 1 + 8 + 32 + 32;
+
+    /// Encode as return data
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut data = [0u8; Self::SIZE];
+        data[0] = self.verified as u8;
+        data[1..9].copy_from_slice(&self.slot.to_le_bytes());
+        data[9..41].copy_from_slice(&self.vk_hash);
+        data[41..].copy_from_slice(&self.pi_hash);
+        data
+    }
+
+    /// Decode return data; `None` unless it is exactly [`Self::SIZE`] bytes
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let data: &[u8; Self::SIZE] = data.try_into().ok()?;
+        Some(Self {
+            verified: data[0] != 0,
+            slot: u64::from_le_bytes(data[1..9].try_into().ok()?),
+            vk_hash: data[9..41].try_into().ok()?,
+            pi_hash: data[41..].try_into().ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_matches_derive() {
+        let vk = Pubkey::new_from_array([5; 32]);
+        let program = Pubkey::new_from_array([9; 32]);
+        let hash = pi_hash(&[7; 64]);
+
+        let (pda, bump) = derive_receipt_pda(&vk, &hash, &program);
+        assert_eq!(create_receipt_address(&vk, &hash, bump, &program), Ok(pda));
+        assert_ne!(
+            create_receipt_address(&vk, &pi_hash(&[8; 64]), bump, &program),
+            Ok(pda)
+        );
+    }
+
+    #[test]
+    fn test_pi_hashv_matches_one_shot() {
+        let inputs = [1u8; 96];
+        assert_eq!(
+            pi_hashv(&[&inputs[..10], &[], &inputs[10..]]),
+            pi_hash(&inputs)
+        );
+        assert_eq!(pi_hashv(&[]), pi_hash(&[]));
+    }
+
+    #[test]
+    fn test_verification_result_roundtrip() {
+        let result = VerificationResult {
+            verified: true,
+            slot: 0x0102_0304_0506_0708,
+            vk_hash: [3; 32],
+            pi_hash: pi_hash(&[7; 64]),
+        };
+        let data = result.to_bytes();
+        assert_eq!(data[0], 1);
+        assert_eq!(data[1], 0x08);
+        assert_eq!(VerificationResult::from_bytes(&data), Some(result));
+        assert_eq!(VerificationResult::from_bytes(&data[1..]), None);
+    }
+}
@@ -0,0 +1,495 @@
+//! Public inputs from Noir artifacts
+//!
+//! `bb prove` writes the public inputs in the verifier's layout already, but
+//! `nargo` takes and reports values as TOML (`Prover.toml`,
+//! `Verifier.toml`) and noir_js takes them as a JSON input map. Both key
+//! values by parameter name, in no particular order, so the circuit's ABI
+//! (the `abi` of the program artifact `nargo compile` writes to
+//! `target/<circuit>.json`) decides which of them are public and in what
+//! order they are laid out.
+//!
+//! ```
+//! use solana_noir_verifier_sdk::NoirAbi;
+//!
+//! let abi = NoirAbi::from_json(r#"{"abi": {"parameters": [
+//!     {"name": "x", "type": {"kind": "field"}, "visibility": "private"},
+//!     {"name": "y", "type": {"kind": "field"}, "visibility": "public"}
+//! ], "return_type": null}}"#)?;
+//! let inputs = abi.public_inputs_from_toml("x = \"3\"\ny = \"9\"\n")?;
+//! assert_eq!(inputs.to_bytes()[31], 9);
+//! # Ok::<(), solana_noir_verifier_sdk::VerifierError>(())
+//! ```
+
+use crate::{
+    error::{Result, VerifierError},
+    public_inputs::{parse_field_value, PublicInputs},
+};
+use plonk_solana_core::{field::fr_from_u64, types::SCALAR_ZERO, Fr};
+use std::path::Path;
+
+/// Key of the return value in an input map
+const RETURN_KEY: &str = "return";
+
+/// Public part of a Noir program's ABI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoirAbi {
+    /// Public parameters, in declaration order
+    parameters: Vec<(String, AbiType)>,
+    /// Return type, which is always public
+    return_type: Option<AbiType>,
+}
+
+/// An ABI type, as far as laying out its value needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AbiType {
+    Field,
+    Boolean,
+    Integer {
+        signed: bool,
+        width: u32,
+    },
+    Array {
+        length: usize,
+        element: Box<AbiType>,
+    },
+    String {
+        length: usize,
+    },
+    Struct {
+        fields: Vec<(String, AbiType)>,
+    },
+    Tuple {
+        fields: Vec<AbiType>,
+    },
+}
+
+/// An input value, whichever format it was read from
+enum Value {
+    Scalar(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+impl NoirAbi {
+    /// Read the ABI of a program artifact (`target/<circuit>.json`)
+    pub fn read_artifact(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| artifact_error(format!("cannot read {}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Parse a program artifact, or just its `abi` object
+    pub fn from_json(json: &str) -> Result<Self> {
+        let artifact: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| artifact_error(format!("not JSON: {}", e)))?;
+        let abi = artifact.get("abi").unwrap_or(&artifact);
+        let parameters = abi
+            .get("parameters")
+            .and_then(serde_json::Value::as_array)
+            .ok_or_else(|| artifact_error("no `abi.parameters`".to_string()))?;
+
+        let mut public = Vec::new();
+        for parameter in parameters {
+            let name = json_str(parameter, "name")?;
+            if json_str(parameter, "visibility")? == "public" {
+                let typ = parameter
+                    .get("type")
+                    .ok_or_else(|| artifact_error(format!("parameter {} has no type", name)))?;
+                public.push((name.to_string(), AbiType::from_json(typ)?));
+            }
+        }
+        // The return value is public unless the artifact says otherwise
+        let return_type = match abi.get("return_type") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(ret) => match ret.get("visibility").and_then(serde_json::Value::as_str) {
+                Some(visibility) if visibility != "public" => None,
+                _ => Some(AbiType::from_json(ret.get("abi_type").unwrap_or(ret))?),
+            },
+        };
+        Ok(Self {
+            parameters: public,
+            return_type,
+        })
+    }
+
+    /// Number of field elements the public inputs take
+    pub fn num_public_inputs(&self) -> usize {
+        let params: usize = self.parameters.iter().map(|(_, t)| t.field_count()).sum();
+        params + self.return_type.as_ref().map_or(0, AbiType::field_count)
+    }
+
+    /// Lay out the public values of a `nargo` TOML input map
+    ///
+    /// The map must also hold the `return` value if the circuit returns one.
+    pub fn public_inputs_from_toml(&self, toml: &str) -> Result<PublicInputs> {
+        let table: toml::Table = toml
+            .parse()
+            .map_err(|e| VerifierError::InvalidPublicInput(format!("invalid TOML: {}", e)))?;
+        let values = table
+            .into_iter()
+            .map(|(key, value)| Ok((key, Value::from_toml(value)?)))
+            .collect::<Result<_>>()?;
+        self.layout(values)
+    }
+
+    /// Lay out the public values of a JSON input map, as noir_js takes it
+    pub fn public_inputs_from_json(&self, json: &str) -> Result<PublicInputs> {
+        let map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)
+            .map_err(|e| VerifierError::InvalidPublicInput(format!("not a JSON object: {}", e)))?;
+        let values = map
+            .into_iter()
+            .map(|(key, value)| Ok((key, Value::from_json(value)?)))
+            .collect::<Result<_>>()?;
+        self.layout(values)
+    }
+
+    fn layout(&self, mut values: Vec<(String, Value)>) -> Result<PublicInputs> {
+        let mut fields = Vec::with_capacity(self.num_public_inputs());
+        let returned = self.return_type.as_ref().map(|t| (RETURN_KEY, t));
+        let public = self.parameters.iter().map(|(name, t)| (name.as_str(), t));
+        for (name, typ) in public.chain(returned) {
+            let value = take(&mut values, name).ok_or_else(|| {
+                VerifierError::InvalidPublicInput(format!("no value for public input {}", name))
+            })?;
+            typ.flatten(&value, name, &mut fields)?;
+        }
+        Ok(PublicInputs::from_fields(fields))
+    }
+}
+
+impl AbiType {
+    fn from_json(typ: &serde_json::Value) -> Result<Self> {
+        let length = || {
+            typ.get("length")
+                .and_then(serde_json::Value::as_u64)
+                .map(|n| n as usize)
+                .ok_or_else(|| artifact_error(format!("type without length: {}", typ)))
+        };
+        let fields = || {
+            typ.get("fields")
+                .and_then(serde_json::Value::as_array)
+                .ok_or_else(|| artifact_error(format!("type without fields: {}", typ)))
+        };
+        Ok(match json_str(typ, "kind")? {
+            "field" => Self::Field,
+            "boolean" => Self::Boolean,
+            "integer" => Self::Integer {
+                signed: json_str(typ, "sign")? == "signed",
+                width: typ
+                    .get("width")
+                    .and_then(serde_json::Value::as_u64)
+                    .filter(|w| (1..=128).contains(w))
+                    .ok_or_else(|| artifact_error(format!("bad integer width: {}", typ)))?
+                    as u32,
+            },
+            "array" => Self::Array {
+                length: length()?,
+                element: Box::new(Self::from_json(&typ["type"])?),
+            },
+            "string" => Self::String { length: length()? },
+            "struct" => Self::Struct {
+                fields: fields()?
+                    .iter()
+                    .map(|f| {
+                        Ok((
+                            json_str(f, "name")?.to_string(),
+                            Self::from_json(&f["type"])?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?,
+            },
+            "tuple" => Self::Tuple {
+                fields: fields()?
+                    .iter()
+                    .map(Self::from_json)
+                    .collect::<Result<_>>()?,
+            },
+            kind => return Err(artifact_error(format!("unknown ABI type {:?}", kind))),
+        })
+    }
+
+    fn field_count(&self) -> usize {
+        match self {
+            Self::Field | Self::Boolean | Self::Integer { .. } => 1,
+            Self::Array { length, element } => length * element.field_count(),
+            Self::String { length } => *length,
+            Self::Struct { fields } => fields.iter().map(|(_, t)| t.field_count()).sum(),
+            Self::Tuple { fields } => fields.iter().map(Self::field_count).sum(),
+        }
+    }
+
+    /// Append the field elements of `value` to `out`; `path` names the
+    /// value in errors
+    fn flatten(&self, value: &Value, path: &str, out: &mut Vec<Fr>) -> Result<()> {
+        let mismatch =
+            || VerifierError::InvalidPublicInput(format!("{} does not match its ABI type", path));
+        let scalar = |text: &str| {
+            parse_field_value(text).map_err(|e| match e {
+                VerifierError::InvalidPublicInput(reason) => {
+                    VerifierError::InvalidPublicInput(format!("{}: {}", path, reason))
+                }
+                other => other,
+            })
+        };
+        match (self, value) {
+            (Self::Field, Value::Scalar(text)) => out.push(scalar(text)?),
+            (Self::Boolean, Value::Bool(b)) => out.push(fr_from_u64(*b as u64)),
+            (Self::Boolean, Value::Scalar(text)) => {
+                let field = scalar(text)?;
+                if field != SCALAR_ZERO && field != fr_from_u64(1) {
+                    return Err(mismatch());
+                }
+                out.push(field);
+            }
+            (Self::Integer { signed, width }, Value::Scalar(text)) => {
+                out.push(integer(text, *signed, *width).map_err(|reason| {
+                    VerifierError::InvalidPublicInput(format!("{}: {}", path, reason))
+                })?)
+            }
+            (Self::Array { length, element }, Value::List(items)) if items.len() == *length => {
+                for (i, item) in items.iter().enumerate() {
+                    element.flatten(item, &format!("{}[{}]", path, i), out)?;
+                }
+            }
+            (Self::String { length }, Value::Scalar(text)) if text.len() == *length => {
+                out.extend(text.bytes().map(|b| fr_from_u64(b as u64)));
+            }
+            (Self::Struct { fields }, Value::Map(entries)) => {
+                for (name, typ) in fields {
+                    let (_, item) =
+                        entries.iter().find(|(key, _)| key == name).ok_or_else(|| {
+                            VerifierError::InvalidPublicInput(format!(
+                                "no value for {}.{}",
+                                path, name
+                            ))
+                        })?;
+                    typ.flatten(item, &format!("{}.{}", path, name), out)?;
+                }
+            }
+            (Self::Tuple { fields }, Value::List(items)) if items.len() == fields.len() => {
+                for (i, (typ, item)) in fields.iter().zip(items).enumerate() {
+                    typ.flatten(item, &format!("{}.{}", path, i), out)?;
+                }
+            }
+            _ => return Err(mismatch()),
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    fn from_toml(value: toml::Value) -> Result<Self> {
+        Ok(match value {
+            toml::Value::String(s) => Self::Scalar(s),
+            toml::Value::Integer(i) => Self::Scalar(i.to_string()),
+            toml::Value::Boolean(b) => Self::Bool(b),
+            toml::Value::Array(items) => Self::List(
+                items
+                    .into_iter()
+                    .map(Self::from_toml)
+                    .collect::<Result<_>>()?,
+            ),
+            toml::Value::Table(table) => Self::Map(
+                table
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, Self::from_toml(v)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            other => {
+                return Err(VerifierError::InvalidPublicInput(format!(
+                    "unsupported TOML value {}",
+                    other
+                )))
+            }
+        })
+    }
+
+    fn from_json(value: serde_json::Value) -> Result<Self> {
+        Ok(match value {
+            serde_json::Value::String(s) => Self::Scalar(s),
+            serde_json::Value::Number(n) if n.is_u64() || n.is_i64() => Self::Scalar(n.to_string()),
+            serde_json::Value::Bool(b) => Self::Bool(b),
+            serde_json::Value::Array(items) => Self::List(
+                items
+                    .into_iter()
+                    .map(Self::from_json)
+                    .collect::<Result<_>>()?,
+            ),
+            serde_json::Value::Object(map) => Self::Map(
+                map.into_iter()
+                    .map(|(k, v)| Ok((k, Self::from_json(v)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            other => {
+                return Err(VerifierError::InvalidPublicInput(format!(
+                    "unsupported JSON value {}",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+/// A `width`-bit integer; negative signed values are laid out in two's
+/// complement, as Noir does
+fn integer(text: &str, signed: bool, width: u32) -> std::result::Result<Fr, String> {
+    let text = text.trim().trim_matches('"');
+    let (negative, magnitude) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let field = parse_field_value(magnitude).map_err(|e| match e {
+        VerifierError::InvalidPublicInput(reason) => reason,
+        other => other.to_string(),
+    })?;
+    let out_of_range = || {
+        format!(
+            "{} does not fit in {}{}",
+            text,
+            if signed { "i" } else { "u" },
+            width
+        )
+    };
+    if field[..16] != [0; 16] {
+        return Err(out_of_range());
+    }
+    let magnitude = u128::from_be_bytes(field[16..].try_into().unwrap());
+    let limit = |bits: u32| 1u128.checked_shl(bits).map_or(u128::MAX, |l| l - 1);
+    let value = match (signed, negative) {
+        (false, true) if magnitude != 0 => return Err(out_of_range()),
+        (false, _) if magnitude > limit(width) => return Err(out_of_range()),
+        (true, false) if magnitude > limit(width - 1) => return Err(out_of_range()),
+        (true, true) if magnitude > limit(width - 1) + 1 => return Err(out_of_range()),
+        (true, true) => magnitude.wrapping_neg() & limit(width),
+        _ => magnitude,
+    };
+    let mut fr = SCALAR_ZERO;
+    fr[16..].copy_from_slice(&value.to_be_bytes());
+    Ok(fr)
+}
+
+/// Remove and return the value for `key`
+fn take(values: &mut Vec<(String, Value)>, key: &str) -> Option<Value> {
+    let index = values.iter().position(|(k, _)| k == key)?;
+    Some(values.swap_remove(index).1)
+}
+
+fn json_str<'a>(value: &'a serde_json::Value, key: &str) -> Result<&'a str> {
+    value
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| artifact_error(format!("missing `{}` in {}", key, value)))
+}
+
+fn artifact_error(message: String) -> VerifierError {
+    VerifierError::NoirArtifact(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTIFACT: &str = r#"{
+        "noir_version": "1.0.0-beta.8",
+        "abi": {
+            "parameters": [
+                {"name": "secret", "type": {"kind": "field"}, "visibility": "private"},
+                {"name": "root", "type": {"kind": "field"}, "visibility": "public"},
+                {"name": "point", "type": {"kind": "struct", "path": "Point", "fields": [
+                    {"name": "x", "type": {"kind": "integer", "sign": "unsigned", "width": 32}},
+                    {"name": "y", "type": {"kind": "integer", "sign": "signed", "width": 8}}
+                ]}, "visibility": "public"},
+                {"name": "path", "type": {"kind": "array", "length": 2,
+                    "type": {"kind": "boolean"}}, "visibility": "public"},
+                {"name": "tag", "type": {"kind": "string", "length": 2}, "visibility": "public"}
+            ],
+            "return_type": {"abi_type": {"kind": "field"}, "visibility": "public"},
+            "error_types": {}
+        }
+    }"#;
+
+    fn expected() -> Vec<Fr> {
+        [5, 7, 0xff, 1, 0, b'o' as u64, b'k' as u64, 42]
+            .map(fr_from_u64)
+            .to_vec()
+    }
+
+    #[test]
+    fn test_toml_and_json_agree() {
+        let abi = NoirAbi::from_json(ARTIFACT).unwrap();
+        assert_eq!(abi.num_public_inputs(), 8);
+
+        // Keys sorted, as nargo writes them, unlike the ABI order
+        let toml = r#"
+            path = [true, false]
+            return = "0x2a"
+            root = "5"
+            secret = "1"
+            tag = "ok"
+
+            [point]
+            x = 7
+            y = "-1"
+        "#;
+        let inputs = abi.public_inputs_from_toml(toml).unwrap();
+        assert_eq!(inputs.fields(), expected());
+
+        let json = r#"{"root": 5, "secret": "1", "point": {"y": -1, "x": "7"},
+            "path": [1, "0"], "tag": "ok", "return": "42"}"#;
+        assert_eq!(abi.public_inputs_from_json(json).unwrap(), inputs);
+    }
+
+    #[test]
+    fn test_errors_name_the_input() {
+        let abi = NoirAbi::from_json(ARTIFACT).unwrap();
+        let base = "root = 5\npath = [true, false]\ntag = \"ok\"\nreturn = 42\n";
+        let error = |extra: &str| match abi.public_inputs_from_toml(&format!("{}{}", base, extra)) {
+            Err(VerifierError::InvalidPublicInput(reason)) => reason,
+            other => panic!("expected an invalid input, got {:?}", other),
+        };
+        assert!(error("").contains("no value for public input point"));
+        assert!(error("[point]\nx = 7\n").contains("point.y"));
+        assert!(error("[point]\nx = 4294967296\ny = 0\n").contains("point.x"));
+        assert!(error("[point]\nx = 1\ny = -129\n").contains("does not fit in i8"));
+
+        let r = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let json = format!(r#"{{"root": "{}"}}"#, r);
+        match abi.public_inputs_from_json(&json) {
+            Err(VerifierError::InvalidPublicInput(reason)) => {
+                assert!(reason.starts_with("root:"), "{}", reason);
+                assert!(reason.contains("field modulus"), "{}", reason);
+            }
+            other => panic!("expected an invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_integer_layout() {
+        assert_eq!(integer("-1", true, 8), Ok(fr_from_u64(0xff)));
+        assert_eq!(integer("-128", true, 8), Ok(fr_from_u64(0x80)));
+        assert_eq!(integer("127", true, 8), Ok(fr_from_u64(127)));
+        assert!(integer("128", true, 8).is_err());
+        assert!(integer("-1", false, 8).is_err());
+        assert_eq!(integer("0xffff", false, 16), Ok(fr_from_u64(0xffff)));
+
+        let max = integer(&u128::MAX.to_string(), false, 128).unwrap();
+        assert_eq!(max[..16], [0; 16]);
+        assert_eq!(max[16..], [0xff; 16]);
+    }
+
+    #[test]
+    fn test_bare_abi_without_return() {
+        let abi = NoirAbi::from_json(
+            r#"{"parameters": [{"name": "y", "type": {"kind": "field"}, "visibility": "public"}],
+                "return_type": null}"#,
+        )
+        .unwrap();
+        assert_eq!(abi.num_public_inputs(), 1);
+        assert!(matches!(
+            NoirAbi::from_json("{}"),
+            Err(VerifierError::NoirArtifact(_))
+        ));
+    }
+}
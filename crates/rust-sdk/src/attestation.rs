@@ -0,0 +1,314 @@
+//! Signed receipt attestations for consumers off Solana
+//!
+//! An EVM contract or a backend without a Solana RPC cannot read a receipt
+//! account itself. An oracle it trusts reads the receipt instead and signs
+//! what it saw with an ed25519 key: the cluster, the VK and its hash, the
+//! public inputs hash and the slot the proof was verified in. The consumer
+//! checks the signature against the oracle's key and the fields against the
+//! circuit it expects.
+//!
+//! # Format
+//!
+//! [`ReceiptAttestation::to_bytes`] is the signed message followed by the
+//! oracle's public key and the signature, [`ATTESTATION_SIZE`] bytes in all.
+//! Integers are little-endian, as in the receipt account.
+//!
+//! | Offset | Size | Field |
+//! |--------|------|-------|
+//! | 0 | 16 | [`ATTESTATION_DOMAIN`] |
+//! | 16 | 32 | Genesis hash of the cluster |
+//! | 48 | 32 | Verifier program ID |
+//! | 80 | 32 | Receipt PDA |
+//! | 112 | 32 | VK account |
+//! | 144 | 32 | Keccak256 of the VK as stored in its account |
+//! | 176 | 32 | Keccak256 of the public inputs |
+//! | 208 | 8 | Slot the proof was verified in |
+//! | 216 | 8 | Unix timestamp the proof was verified at |
+//! | 224 | 8 | Slot of the snapshot the oracle read |
+//! | 232 | 32 | Oracle public key |
+//! | 264 | 64 | Ed25519 signature of bytes 0..232 |
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    rpc::RpcOperation,
+    types::*,
+};
+use plonk_solana_core::transcript::keccak256;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+
+/// Prefix of every attestation message, naming the format and its version
+pub const ATTESTATION_DOMAIN: &[u8; 16] = b"noir-solana-att1";
+
+/// Size of the signed part of an attestation
+pub const ATTESTATION_MESSAGE_SIZE: usize = 232;
+
+/// Size of a serialized attestation
+pub const ATTESTATION_SIZE: usize = ATTESTATION_MESSAGE_SIZE + 32 + 64;
+
+/// An oracle's signed statement that a receipt existed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptAttestation {
+    /// Genesis hash of the cluster the receipt was read from
+    pub cluster: Hash,
+    pub program_id: Pubkey,
+    pub receipt_pda: Pubkey,
+    pub vk_account: Pubkey,
+    /// Keccak256 of the VK bytes in its account
+    ///
+    /// Equals the hash of the `bb` VK file unless the VK was compiled on
+    /// chain, in which case it hashes the compiled layout.
+    pub vk_hash: [u8; 32],
+    pub public_inputs_hash: [u8; 32],
+    pub verified_slot: u64,
+    pub verified_timestamp: i64,
+    /// Slot of the snapshot the receipt and VK were read at
+    pub snapshot_slot: u64,
+    pub oracle: Pubkey,
+    pub signature: Signature,
+}
+
+impl ReceiptAttestation {
+    /// The bytes the oracle signs
+    pub fn message(&self) -> [u8; ATTESTATION_MESSAGE_SIZE] {
+        let mut message = [0u8; ATTESTATION_MESSAGE_SIZE];
+        message[0..16].copy_from_slice(ATTESTATION_DOMAIN);
+        message[16..48].copy_from_slice(self.cluster.as_ref());
+        message[48..80].copy_from_slice(self.program_id.as_ref());
+        message[80..112].copy_from_slice(self.receipt_pda.as_ref());
+        message[112..144].copy_from_slice(self.vk_account.as_ref());
+        message[144..176].copy_from_slice(&self.vk_hash);
+        message[176..208].copy_from_slice(&self.public_inputs_hash);
+        message[208..216].copy_from_slice(&self.verified_slot.to_le_bytes());
+        message[216..224].copy_from_slice(&self.verified_timestamp.to_le_bytes());
+        message[224..232].copy_from_slice(&self.snapshot_slot.to_le_bytes());
+        message
+    }
+
+    /// Message, oracle key and signature
+    pub fn to_bytes(&self) -> [u8; ATTESTATION_SIZE] {
+        let mut bytes = [0u8; ATTESTATION_SIZE];
+        bytes[..ATTESTATION_MESSAGE_SIZE].copy_from_slice(&self.message());
+        bytes[ATTESTATION_MESSAGE_SIZE..ATTESTATION_MESSAGE_SIZE + 32]
+            .copy_from_slice(self.oracle.as_ref());
+        bytes[ATTESTATION_MESSAGE_SIZE + 32..].copy_from_slice(self.signature.as_ref());
+        bytes
+    }
+
+    /// Parse an attestation; `None` if the size or domain is wrong
+    ///
+    /// The signature is not checked; see [`verify`](Self::verify).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != ATTESTATION_SIZE || &bytes[0..16] != ATTESTATION_DOMAIN {
+            return None;
+        }
+        let array =
+            |range: std::ops::Range<usize>| -> [u8; 32] { bytes[range].try_into().unwrap() };
+        let word = |at: usize| -> [u8; 8] { bytes[at..at + 8].try_into().unwrap() };
+        Some(Self {
+            cluster: Hash::new_from_array(array(16..48)),
+            program_id: Pubkey::new_from_array(array(48..80)),
+            receipt_pda: Pubkey::new_from_array(array(80..112)),
+            vk_account: Pubkey::new_from_array(array(112..144)),
+            vk_hash: array(144..176),
+            public_inputs_hash: array(176..208),
+            verified_slot: u64::from_le_bytes(word(208)),
+            verified_timestamp: i64::from_le_bytes(word(216)),
+            snapshot_slot: u64::from_le_bytes(word(224)),
+            oracle: Pubkey::new_from_array(array(232..264)),
+            signature: Signature::try_from(&bytes[264..]).ok()?,
+        })
+    }
+
+    /// Whether `oracle` signed this attestation
+    pub fn verify(&self) -> bool {
+        self.signature.verify(self.oracle.as_ref(), &self.message())
+    }
+
+    /// Whether the receipt is the PDA for the VK and public inputs hash
+    fn has_receipt_address(&self, bump: u8) -> bool {
+        solana_noir_receipt_seeds::create_receipt_address(
+            &self.vk_account,
+            &self.public_inputs_hash,
+            bump,
+            &self.program_id,
+        )
+        .is_ok_and(|address| address == self.receipt_pda)
+    }
+}
+
+impl SolanaNoirVerifier {
+    /// Read a receipt at finalized commitment and sign an attestation of it
+    ///
+    /// The receipt and its VK account are read in one snapshot. Both must
+    /// be owned by the verifier program, and the receipt must sit at the PDA
+    /// its own VK account, public inputs hash and bump derive.
+    pub fn attest_receipt(
+        &self,
+        oracle: &dyn Signer,
+        receipt_pda: &Pubkey,
+    ) -> Result<ReceiptAttestation> {
+        let cluster = self.rpc_call(RpcOperation::Fetch, |rpc| rpc.get_genesis_hash())?;
+        let read = |keys: &[Pubkey]| {
+            self.rpc_call(RpcOperation::Fetch, |rpc| {
+                rpc.get_multiple_accounts_with_commitment(keys, CommitmentConfig::finalized())
+            })
+        };
+        let owned = |account: &Option<Account>| {
+            account
+                .as_ref()
+                .is_some_and(|account| account.owner == self.config.program_id)
+        };
+
+        // The receipt names its VK account; both are then read together
+        let first = read(&[*receipt_pda])?.value.into_iter().next().flatten();
+        let vk_account = first
+            .as_ref()
+            .and_then(|receipt| {
+                receipt
+                    .data
+                    .get(RECEIPT_VK_ACCOUNT_OFFSET..RECEIPT_VK_ACCOUNT_OFFSET + 32)
+            })
+            .map(|key| Pubkey::new_from_array(key.try_into().unwrap()))
+            .ok_or(VerifierError::ReceiptNotFound)?;
+        let snapshot = read(&[*receipt_pda, vk_account])?;
+        let [receipt, vk]: [Option<Account>; 2] = snapshot
+            .value
+            .try_into()
+            .map_err(|_| VerifierError::Attestation("RPC returned the wrong accounts".into()))?;
+
+        if !owned(&receipt) {
+            return Err(VerifierError::Attestation(format!(
+                "{} is not a receipt of the verifier program",
+                receipt_pda
+            )));
+        }
+        let receipt = receipt.unwrap();
+        let info =
+            ReceiptInfo::from_account_data(*receipt_pda, &receipt.data).ok_or_else(|| {
+                VerifierError::Attestation(format!("{} is not a receipt account", receipt_pda))
+            })?;
+        if !owned(&vk) {
+            return Err(VerifierError::Attestation(format!(
+                "VK account {} of the receipt is gone",
+                vk_account
+            )));
+        }
+        let vk_data = vk.unwrap().data;
+        let vk_len = vk_data
+            .get(1..VK_HEADER_SIZE)
+            .map_or(0, |len| u16::from_le_bytes([len[0], len[1]]) as usize);
+        let vk_bytes = vk_data
+            .get(VK_HEADER_SIZE..VK_HEADER_SIZE + vk_len)
+            .filter(|bytes| !bytes.is_empty())
+            .ok_or_else(|| {
+                VerifierError::Attestation(format!("{} is not a VK account", vk_account))
+            })?;
+
+        let mut attestation = ReceiptAttestation {
+            cluster,
+            program_id: self.config.program_id,
+            receipt_pda: *receipt_pda,
+            vk_account,
+            vk_hash: keccak256(vk_bytes),
+            public_inputs_hash: receipt.data[RECEIPT_PI_HASH_OFFSET..RECEIPT_PI_HASH_OFFSET + 32]
+                .try_into()
+                .unwrap(),
+            verified_slot: info.verified_slot,
+            verified_timestamp: info.verified_timestamp,
+            snapshot_slot: snapshot.context.slot,
+            oracle: oracle.pubkey(),
+            signature: Signature::default(),
+        };
+        if !attestation.has_receipt_address(receipt.data[RECEIPT_BUMP_OFFSET]) {
+            return Err(VerifierError::Attestation(format!(
+                "{} is not the receipt PDA of its VK and public inputs",
+                receipt_pda
+            )));
+        }
+        attestation.signature = oracle
+            .try_sign_message(&attestation.message())
+            .map_err(|e| VerifierError::Attestation(e.to_string()))?;
+        Ok(attestation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+
+    fn attestation(oracle: &Keypair) -> ReceiptAttestation {
+        let program_id = Pubkey::new_unique();
+        let vk_account = Pubkey::new_unique();
+        let public_inputs_hash = [7; 32];
+        let (receipt_pda, _) = solana_noir_receipt_seeds::derive_receipt_pda(
+            &vk_account,
+            &public_inputs_hash,
+            &program_id,
+        );
+        let mut attestation = ReceiptAttestation {
+            cluster: Hash::new_from_array([1; 32]),
+            program_id,
+            receipt_pda,
+            vk_account,
+            vk_hash: [2; 32],
+            public_inputs_hash,
+            verified_slot: 100,
+            verified_timestamp: 1_700_000_000,
+            snapshot_slot: 164,
+            oracle: oracle.pubkey(),
+            signature: Signature::default(),
+        };
+        attestation.signature = oracle.sign_message(&attestation.message());
+        attestation
+    }
+
+    #[test]
+    fn test_round_trip_and_signature() {
+        let oracle = Keypair::new();
+        let attestation = attestation(&oracle);
+        assert!(attestation.verify());
+
+        let bytes = attestation.to_bytes();
+        assert_eq!(&bytes[..16], ATTESTATION_DOMAIN);
+        assert_eq!(&bytes[208..216], &100u64.to_le_bytes());
+        let parsed = ReceiptAttestation::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, attestation);
+
+        // Any change to a signed field breaks the signature
+        let mut tampered = bytes;
+        tampered[200] ^= 1;
+        assert!(!ReceiptAttestation::from_bytes(&tampered).unwrap().verify());
+        // So does claiming another oracle signed it
+        let mut forged = attestation.clone();
+        forged.oracle = Keypair::new().pubkey();
+        assert!(!forged.verify());
+
+        assert!(ReceiptAttestation::from_bytes(&bytes[1..]).is_none());
+        let mut wrong_domain = bytes;
+        wrong_domain[0] = b'x';
+        assert!(ReceiptAttestation::from_bytes(&wrong_domain).is_none());
+    }
+
+    #[test]
+    fn test_receipt_address() {
+        let attestation = attestation(&Keypair::new());
+        let (_, bump) = solana_noir_receipt_seeds::derive_receipt_pda(
+            &attestation.vk_account,
+            &attestation.public_inputs_hash,
+            &attestation.program_id,
+        );
+        assert!(attestation.has_receipt_address(bump));
+
+        let mut other_inputs = attestation.clone();
+        other_inputs.public_inputs_hash = [8; 32];
+        assert!(!other_inputs.has_receipt_address(bump));
+    }
+}
@@ -0,0 +1,106 @@
+//! Verifying many proofs of one circuit
+//!
+//! A single verification spends most of its time waiting for confirmations,
+//! so running several side by side multiplies throughput. While one proof
+//! is in sumcheck the next one's chunks are already uploading. Jobs share
+//! the client's lookup table and recent blockhash, and
+//! [`VerifierConfig::batch_concurrency`] bounds how many run at once, and
+//! with it the transactions in flight.
+
+use crate::{client::SolanaNoirVerifier, error::Result, types::*};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// One proof for [`SolanaNoirVerifier::verify_many`]
+#[derive(Clone, Debug)]
+pub struct ProofJob {
+    pub proof: Vec<u8>,
+    /// Concatenated 32-byte public inputs
+    pub public_inputs: Vec<u8>,
+}
+
+impl SolanaNoirVerifier {
+    /// Verify many proofs against `vk_account`, several at a time
+    ///
+    /// Each job runs like [`Self::verify_phased`], up to
+    /// [`VerifierConfig::batch_concurrency`] at once; results are in job
+    /// order. A failed job does not stop the others. Each finished job is
+    /// reported to [`ProgressObserver::job_finished`](crate::ProgressObserver::job_finished).
+    pub fn verify_many(
+        &self,
+        payer: &(dyn Signer + Sync),
+        vk_account: &Pubkey,
+        jobs: &[ProofJob],
+    ) -> Vec<Result<VerifiedReceipt>> {
+        run_bounded(jobs, self.config.batch_concurrency, |index, job| {
+            let result =
+                self.verify_phased(payer, None, &job.proof, &job.public_inputs, vk_account);
+            self.notify(|o| o.job_finished(index, result.is_ok()));
+            result
+        })
+    }
+}
+
+/// Map `items` and their indices through `f` on up to `workers` threads,
+/// keeping their order
+fn run_bounded<T: Sync, R: Send>(
+    items: &[T],
+    workers: usize,
+    f: impl Fn(usize, &T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let workers = workers.clamp(1, items.len().max(1));
+
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        done.push((index, f(index, item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("batch worker panicked"))
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_run_bounded() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<u64> = (0..10).collect();
+
+        let results = run_bounded(&items, 3, |index, &i| {
+            assert_eq!(index as u64, i);
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            // Later items finish first
+            thread::sleep(Duration::from_millis(20 - 2 * i));
+            running.fetch_sub(1, Ordering::SeqCst);
+            i * i
+        });
+
+        assert_eq!(results, items.iter().map(|i| i * i).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(run_bounded(&[] as &[u64], 3, |_, &i| i).is_empty());
+    }
+}
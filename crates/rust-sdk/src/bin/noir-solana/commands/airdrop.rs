@@ -0,0 +1,81 @@
+//! Airdrop command - fund a keypair from the devnet/localnet faucet
+
+use crate::config::Config;
+use crate::output::print_json;
+use crate::CommonArgs;
+use anyhow::{bail, Result};
+use clap::Args;
+use console::style;
+use serde_json::json;
+use solana_noir_verifier_sdk::SolanaNoirVerifier;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Args)]
+pub struct AirdropArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Amount in SOL
+    #[arg(default_value_t = 1.0)]
+    amount: f64,
+
+    /// Account to fund (default: the configured keypair)
+    #[arg(long)]
+    to: Option<Pubkey>,
+}
+
+pub fn run(config: &Config, args: AirdropArgs) -> Result<()> {
+    if config.rpc_url.contains("mainnet") {
+        bail!("Mainnet has no faucet; airdrops only work on devnet, testnet and localnet");
+    }
+    if args.amount.is_nan() || args.amount <= 0.0 {
+        bail!("Airdrop amount must be positive");
+    }
+    let recipient = match args.to {
+        Some(pubkey) => pubkey,
+        None => config.load_keypair()?.pubkey(),
+    };
+    let lamports = (args.amount * LAMPORTS_PER_SOL) as u64;
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Requesting {} SOL for {} on {}...",
+            style("→").cyan().bold(),
+            args.amount,
+            recipient,
+            config.rpc_url
+        );
+    }
+
+    // The faucet needs no program; the verifier is only used for its RPC handling
+    let client = config.rpc_client();
+    let verifier = SolanaNoirVerifier::new(
+        client.clone(),
+        config.verifier_config(config.program_id.unwrap_or_default()),
+    );
+    let signature = verifier.request_airdrop(&recipient, lamports)?;
+    let balance = client.get_balance(&recipient)?;
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({
+                "recipient": recipient.to_string(),
+                "lamports": lamports,
+                "balance": balance,
+                "signature": signature.to_string(),
+            }),
+        );
+    } else if !config.quiet {
+        println!(
+            "{} Airdropped {} SOL",
+            style("✓").green().bold(),
+            args.amount
+        );
+        println!("  Balance: {:.6} SOL", balance as f64 / LAMPORTS_PER_SOL);
+        println!("  Signature: {}", signature);
+    }
+    Ok(())
+}
@@ -0,0 +1,237 @@
+//! Batch verify command - verify every proof listed in a manifest
+
+use crate::config::Config;
+use crate::output::{print_json, Reported};
+use crate::{CommonArgs, FeeArgs};
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Deserialize;
+use serde_json::json;
+use solana_noir_verifier_sdk::{
+    ProgressObserver, ProofJob, SolanaNoirVerifier, DEFAULT_BATCH_CONCURRENCY,
+};
+use solana_sdk::signature::Signer;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Args)]
+pub struct BatchVerifyArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    /// Manifest listing the proofs (.toml or .json)
+    #[arg(long)]
+    manifest: PathBuf,
+
+    /// VK account public key (overrides the manifest's `vk_account`)
+    #[arg(long)]
+    vk_account: Option<String>,
+
+    /// Proofs verified at once
+    #[arg(long, default_value_t = DEFAULT_BATCH_CONCURRENCY)]
+    concurrency: usize,
+}
+
+/// Batch manifest; proof paths are relative to the manifest's directory
+///
+/// ```toml
+/// vk_account = "..."
+///
+/// [[proofs]]
+/// proof = "a/proof"
+/// public_inputs = "a/public_inputs"
+/// ```
+#[derive(Deserialize)]
+struct Manifest {
+    vk_account: Option<String>,
+    proofs: Vec<ManifestEntry>,
+}
+
+#[derive(Deserialize)]
+struct ManifestEntry {
+    proof: PathBuf,
+    public_inputs: PathBuf,
+}
+
+impl Manifest {
+    fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest: {:?}", path))?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).context("Invalid JSON manifest")
+        } else {
+            toml::from_str(&content).context("Invalid TOML manifest")
+        }
+    }
+}
+
+pub fn run(config: &Config, args: BatchVerifyArgs) -> Result<()> {
+    let manifest = Manifest::load(&args.manifest)?;
+    let base = args.manifest.parent().unwrap_or(Path::new("."));
+    let vk_account = args
+        .vk_account
+        .or(manifest.vk_account)
+        .context("No VK account. Use --vk-account or set vk_account in the manifest")?;
+    let vk_account = config.resolve_vk(&vk_account)?;
+
+    let jobs = manifest
+        .proofs
+        .iter()
+        .map(|entry| {
+            let read = |path: &Path| {
+                fs::read(base.join(path)).with_context(|| format!("Failed to read {:?}", path))
+            };
+            Ok(ProofJob {
+                proof: read(&entry.proof)?,
+                public_inputs: crate::read_public_inputs(&base.join(&entry.public_inputs), None)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Verifying {} proofs on {} ({} at a time)...",
+            style("→").cyan().bold(),
+            jobs.len(),
+            config.rpc_url,
+            args.concurrency
+        );
+    }
+
+    let program_id = config.require_program_id()?;
+    let keypair = config.load_keypair()?;
+    let client = config.rpc_client();
+    let balance_before = client.get_balance(&keypair.pubkey())?;
+
+    let pb = if !config.quiet && !config.json_output {
+        let pb = ProgressBar::new(jobs.len() as u64);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} proofs {msg}")
+                .unwrap()
+                .progress_chars("█▓░"),
+        );
+        Some(pb)
+    } else {
+        None
+    };
+
+    let mut verifier_config = config
+        .verifier_config(program_id)
+        .with_batch_concurrency(args.concurrency);
+    if let Some(pb) = &pb {
+        verifier_config = verifier_config.with_progress_observer(Arc::new(BatchProgress {
+            bar: pb.clone(),
+            failed: AtomicUsize::new(0),
+        }));
+    }
+    let verifier = SolanaNoirVerifier::new(client.clone(), verifier_config);
+
+    let started = Instant::now();
+    let results = verifier.verify_many(&keypair, &vk_account, &jobs);
+    let elapsed = started.elapsed();
+    if let Some(pb) = &pb {
+        pb.finish_and_clear();
+    }
+
+    let balance_after = client.get_balance(&keypair.pubkey())?;
+    let spent = balance_before.saturating_sub(balance_after);
+    let succeeded = results.iter().filter(|r| r.is_ok()).count();
+    let failed = results.len() - succeeded;
+    let total_cus: u64 = results.iter().flatten().map(|r| r.total_cus).sum();
+
+    if config.json_output {
+        let entries: Vec<_> = results
+            .iter()
+            .zip(&manifest.proofs)
+            .map(|(result, entry)| match result {
+                Ok(receipt) => json!({
+                    "proof": entry.proof,
+                    "verified": true,
+                    "receipt": receipt.receipt.receipt_pda.to_string(),
+                    "total_cus": receipt.total_cus,
+                }),
+                Err(e) => json!({
+                    "proof": entry.proof,
+                    "verified": false,
+                    "error": e.to_string(),
+                }),
+            })
+            .collect();
+        print_json(
+            failed == 0,
+            json!({
+                "succeeded": succeeded,
+                "failed": failed,
+                "total_cus": total_cus,
+                "lamports_spent": spent,
+                "elapsed_secs": elapsed.as_secs_f64(),
+                "results": entries,
+            }),
+        );
+    } else if !config.quiet {
+        for (result, entry) in results.iter().zip(&manifest.proofs) {
+            match result {
+                Ok(receipt) => println!(
+                    "  {} {} → {}",
+                    style("✓").green().bold(),
+                    entry.proof.display(),
+                    receipt.receipt.receipt_pda
+                ),
+                Err(e) => println!(
+                    "  {} {}: {}",
+                    style("✗").red().bold(),
+                    entry.proof.display(),
+                    e
+                ),
+            }
+        }
+        println!();
+        println!(
+            "  Verified: {}/{} in {:.1}s",
+            succeeded,
+            results.len(),
+            elapsed.as_secs_f64()
+        );
+        println!("  Total CUs: {}", total_cus);
+        println!(
+            "  Total cost: {:.6} SOL (receipt rent included)",
+            spent as f64 / LAMPORTS_PER_SOL
+        );
+    }
+
+    if failed > 0 {
+        return Err(Reported::verification_failed(format!(
+            "{} of {} proofs failed",
+            failed,
+            results.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Advances the bar as proofs finish and counts failures
+struct BatchProgress {
+    bar: ProgressBar,
+    failed: AtomicUsize,
+}
+
+impl ProgressObserver for BatchProgress {
+    fn job_finished(&self, _index: usize, succeeded: bool) {
+        if !succeeded {
+            let failed = self.failed.fetch_add(1, Ordering::Relaxed) + 1;
+            self.bar.set_message(format!("({} failed)", failed));
+        }
+        self.bar.inc(1);
+    }
+}
@@ -0,0 +1,219 @@
+//! Bench command - per-phase CU, latency and size report of a full verification
+
+use crate::config::Config;
+use crate::output::tagged;
+use crate::{CommonArgs, FeeArgs, PublicInputsArgs};
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use solana_noir_verifier_sdk::{ProgressObserver, SolanaNoirVerifier};
+use solana_rpc_client_api::config::{RpcTransactionConfig, UiTransactionEncoding};
+use solana_sdk::signature::Signature;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    /// Path to the proof file
+    #[arg(long)]
+    proof: PathBuf,
+
+    #[command(flatten)]
+    public_inputs: PublicInputsArgs,
+
+    /// VK account public key or profile alias
+    #[arg(long)]
+    vk_account: String,
+
+    /// Also write the report to this file
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+/// One row of the report: account setup, a phase, or receipt and close
+struct Stage {
+    name: String,
+    signatures: Vec<Signature>,
+    compute_units: u64,
+    /// Whether the phase transaction's CUs came in, so the next send
+    /// belongs to receipt and close
+    phase_done: bool,
+    started: Instant,
+    elapsed: Duration,
+}
+
+impl Stage {
+    fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            signatures: Vec::new(),
+            compute_units: 0,
+            phase_done: false,
+            started: Instant::now(),
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// Splits the verification's events into stages as they arrive
+struct Recorder(Mutex<Vec<Stage>>);
+
+impl Recorder {
+    fn next_stage(stages: &mut Vec<Stage>, name: &str) {
+        if let Some(last) = stages.last_mut() {
+            last.elapsed = last.started.elapsed();
+        }
+        stages.push(Stage::new(name));
+    }
+}
+
+impl ProgressObserver for Recorder {
+    fn phase_started(&self, phase: &str) {
+        Self::next_stage(&mut self.0.lock().unwrap(), phase);
+    }
+
+    fn transaction_sent(&self, signature: &Signature) {
+        let mut stages = self.0.lock().unwrap();
+        if stages.last().is_some_and(|stage| stage.phase_done) {
+            Self::next_stage(&mut stages, "Receipt and close");
+        }
+        stages.last_mut().unwrap().signatures.push(*signature);
+    }
+
+    fn compute_units_consumed(&self, _signature: &Signature, units: u64) {
+        let mut stages = self.0.lock().unwrap();
+        let stage = stages.last_mut().unwrap();
+        stage.compute_units += units;
+        stage.phase_done = true;
+    }
+}
+
+pub fn run(config: &Config, args: BenchArgs) -> Result<()> {
+    let proof_bytes = fs::read(&args.proof)
+        .with_context(|| format!("Failed to read proof file: {:?}", args.proof))?;
+    let pi_bytes = args.public_inputs.read()?;
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Benchmarking a verification on {}...",
+            style("→").cyan().bold(),
+            config.rpc_url
+        );
+    }
+
+    let program_id = config.require_program_id()?;
+    let keypair = config.load_keypair()?;
+    let client = config.rpc_client();
+    let recorder = Arc::new(Recorder(Mutex::new(vec![Stage::new("Setup and upload")])));
+    let verifier = SolanaNoirVerifier::new(
+        client.clone(),
+        config
+            .verifier_config(program_id)
+            .with_progress_observer(recorder.clone()),
+    );
+
+    let started = Instant::now();
+    let receipt = verifier.verify_phased(&keypair, None, &proof_bytes, &pi_bytes, &vk_account)?;
+    let total_elapsed = started.elapsed();
+
+    let mut stages = std::mem::take(&mut *recorder.0.lock().unwrap());
+    if let Some(last) = stages.last_mut() {
+        last.elapsed = last.started.elapsed();
+    }
+    let rows: Vec<(String, usize, u64, f64, usize)> = stages
+        .iter()
+        .map(|stage| {
+            let bytes = stage
+                .signatures
+                .iter()
+                .map(|sig| transaction_size(&client, sig))
+                .sum();
+            (
+                stage.name.clone(),
+                stage.signatures.len(),
+                stage.compute_units,
+                stage.elapsed.as_secs_f64() * 1000.0,
+                bytes,
+            )
+        })
+        .collect();
+
+    let report = if config.json_output {
+        let stages: Vec<_> = rows
+            .iter()
+            .map(|(name, txs, cus, ms, bytes)| {
+                json!({
+                    "stage": name,
+                    "transactions": txs,
+                    "compute_units": cus,
+                    "millis": ms.round() as u64,
+                    "bytes": bytes,
+                })
+            })
+            .collect();
+        let report = json!({
+            "log_n": verifier
+                .get_verification_state(&receipt.state_account)
+                .map_or(0, |s| s.status.log_n),
+            "total_compute_units": receipt.total_cus,
+            "total_millis": total_elapsed.as_millis() as u64,
+            "transactions": receipt.signatures.len(),
+            "stages": stages,
+        });
+        tagged(true, report).to_string()
+    } else {
+        let mut table = String::from(
+            "| Stage | Transactions | CUs | Time (ms) | Bytes |\n|---|---:|---:|---:|---:|\n",
+        );
+        for (name, txs, cus, ms, bytes) in &rows {
+            table.push_str(&format!(
+                "| {} | {} | {} | {:.0} | {} |\n",
+                name, txs, cus, ms, bytes
+            ));
+        }
+        table.push_str(&format!(
+            "| **Total** | {} | {} | {:.0} | {} |\n",
+            receipt.signatures.len(),
+            receipt.total_cus,
+            total_elapsed.as_secs_f64() * 1000.0,
+            rows.iter().map(|row| row.4).sum::<usize>()
+        ));
+        table
+    };
+
+    if !config.quiet || config.json_output {
+        println!("{}", report.trim_end());
+    }
+    if let Some(path) = &args.report {
+        fs::write(path, &report).with_context(|| format!("Failed to write {:?}", path))?;
+    }
+    Ok(())
+}
+
+/// Serialized size of a confirmed transaction (0 if unavailable)
+fn transaction_size(client: &RpcClient, signature: &Signature) -> usize {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: None,
+        max_supported_transaction_version: Some(0),
+    };
+    client
+        .get_transaction_with_config(signature, config)
+        .ok()
+        .and_then(|tx| tx.transaction.transaction.decode())
+        .map_or(0, |tx| {
+            // Compact-u16 signature count, then the signatures and message
+            1 + tx.signatures.len() * 64 + tx.message.serialize().len()
+        })
+}
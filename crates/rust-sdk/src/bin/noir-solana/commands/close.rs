@@ -1,33 +1,61 @@
 //! Close command - close accounts and reclaim rent
 
 use crate::config::Config;
-use crate::CommonArgs;
+use crate::output::print_json;
+use crate::{CommonArgs, FeeArgs};
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
-use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerifierConfig};
-use solana_sdk::pubkey::Pubkey;
+use serde_json::json;
+use solana_noir_verifier_sdk::{SolanaNoirVerifier, SweepResult};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
 use std::str::FromStr;
 
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
 #[derive(Args)]
 pub struct CloseArgs {
     #[command(flatten)]
     pub common: CommonArgs,
 
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
     /// State account public key
-    #[arg(long)]
-    state_account: String,
+    #[arg(long, required_unless_present = "sweep")]
+    state_account: Option<String>,
 
     /// Proof account public key
-    #[arg(long)]
-    proof_account: String,
+    #[arg(long, required_unless_present = "sweep")]
+    proof_account: Option<String>,
+
+    /// Find and close every buffer and state account the keypair paid for
+    #[arg(long, conflicts_with_all = ["state_account", "proof_account"])]
+    sweep: bool,
+
+    /// With --sweep, also close the keypair's receipts (integrators then
+    /// treat those proofs as unverified)
+    #[arg(long, requires = "sweep")]
+    include_receipts: bool,
+
+    /// With --sweep, how many recent transactions of the keypair to scan
+    /// for buffers and state accounts
+    #[arg(long, default_value_t = 1000, requires = "sweep")]
+    history: usize,
+
+    /// With --sweep, list what would be closed without sending anything
+    #[arg(long, requires = "sweep")]
+    dry_run: bool,
 }
 
 pub fn run(config: &Config, args: CloseArgs) -> Result<()> {
-    let state_account =
-        Pubkey::from_str(&args.state_account).context("Invalid state account public key")?;
-    let proof_account =
-        Pubkey::from_str(&args.proof_account).context("Invalid proof account public key")?;
+    if args.sweep {
+        return run_sweep(config, &args);
+    }
+    let state_account = Pubkey::from_str(args.state_account.as_deref().unwrap_or_default())
+        .context("Invalid state account public key")?;
+    let proof_account = Pubkey::from_str(args.proof_account.as_deref().unwrap_or_default())
+        .context("Invalid proof account public key")?;
 
     if !config.quiet && !config.json_output {
         println!(
@@ -41,26 +69,123 @@ pub fn run(config: &Config, args: CloseArgs) -> Result<()> {
     let keypair = config.load_keypair()?;
     let client = config.rpc_client();
 
-    let verifier = SolanaNoirVerifier::new(client, VerifierConfig::new(program_id));
+    let verifier = SolanaNoirVerifier::new(client, config.verifier_config(program_id));
 
     // Close accounts
     let (rent_reclaimed, signature) =
         verifier.close_accounts(&keypair, &state_account, &proof_account)?;
 
     if config.json_output {
-        println!(
-            r#"{{"closed": true, "rent_reclaimed_lamports": {}, "signature": "{}"}}"#,
-            rent_reclaimed, signature
+        print_json(
+            true,
+            json!({
+                "closed": true,
+                "rent_reclaimed_lamports": rent_reclaimed,
+                "signature": signature.to_string(),
+            }),
         );
     } else if !config.quiet {
         println!("{} Accounts closed!", style("✓").green().bold());
         println!(
             "  Rent reclaimed: {} lamports ({:.6} SOL)",
             rent_reclaimed,
-            rent_reclaimed as f64 / 1_000_000_000.0
+            rent_reclaimed as f64 / LAMPORTS_PER_SOL
         );
         println!("  Signature: {}", signature);
     }
 
     Ok(())
 }
+
+/// Close every verifier account the keypair paid for
+fn run_sweep(config: &Config, args: &CloseArgs) -> Result<()> {
+    let program_id = config.require_program_id()?;
+    let keypair = config.load_keypair()?;
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id));
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Scanning the last {} transactions of {} for verifier accounts...",
+            style("→").cyan().bold(),
+            args.history,
+            keypair.pubkey()
+        );
+    }
+    let owned = verifier.find_owned_accounts(&keypair.pubkey(), args.history)?;
+    let receipt_lamports: u64 = owned.receipts.iter().map(|r| r.lamports).sum();
+    let closable_lamports = owned.verifications.iter().map(|v| v.lamports).sum::<u64>()
+        + if args.include_receipts {
+            receipt_lamports
+        } else {
+            0
+        };
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "  Finished verifications: {} ({:.6} SOL)",
+            owned.verifications.len(),
+            owned.verifications.iter().map(|v| v.lamports).sum::<u64>() as f64 / LAMPORTS_PER_SOL
+        );
+        println!(
+            "  Receipts: {} ({:.6} SOL){}",
+            owned.receipts.len(),
+            receipt_lamports as f64 / LAMPORTS_PER_SOL,
+            if args.include_receipts {
+                ""
+            } else {
+                ", kept (use --include-receipts)"
+            }
+        );
+        for state in &owned.in_progress {
+            println!("  {} In progress, skipped: {}", style("→").dim(), state);
+        }
+    }
+
+    let result = if args.dry_run {
+        SweepResult::default()
+    } else {
+        verifier.sweep(&keypair, &owned, args.include_receipts)?
+    };
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({
+                "dry_run": args.dry_run,
+                "verifications": owned.verifications.len(),
+                "receipts": owned.receipts.len(),
+                "in_progress": owned.in_progress.len(),
+                "closable_lamports": closable_lamports,
+                "closed_accounts": result.closed_accounts,
+                "recovered_lamports": result.recovered_lamports,
+                "signatures": result
+                    .signatures
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>(),
+            }),
+        );
+    } else if !config.quiet {
+        println!();
+        if args.dry_run {
+            println!(
+                "{} Would recover {:.6} SOL",
+                style("→").cyan().bold(),
+                closable_lamports as f64 / LAMPORTS_PER_SOL
+            );
+        } else {
+            println!(
+                "{} Closed {} accounts in {} transactions",
+                style("✓").green().bold(),
+                result.closed_accounts,
+                result.signatures.len()
+            );
+            println!(
+                "  Rent reclaimed: {} lamports ({:.6} SOL)",
+                result.recovered_lamports,
+                result.recovered_lamports as f64 / LAMPORTS_PER_SOL
+            );
+        }
+    }
+    Ok(())
+}
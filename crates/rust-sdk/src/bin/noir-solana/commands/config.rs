@@ -0,0 +1,135 @@
+//! Config commands - manage named profiles in ~/.config/noir-solana/config.toml
+
+use crate::config::{config_file_path, ConfigFile, DefaultConfig};
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use console::style;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Set a profile value (network, keypair, program-id, priority-fee,
+    /// vk.<alias>); an empty value removes it
+    Set(SetArgs),
+    /// Make a profile the default
+    Use(UseArgs),
+    /// Print the configuration file
+    Show,
+}
+
+#[derive(Args)]
+pub struct SetArgs {
+    /// Setting to change
+    key: String,
+
+    /// New value
+    value: String,
+
+    /// Profile to change (default: the default profile)
+    #[arg(long, env = "NOIR_SOLANA_PROFILE")]
+    profile: Option<String>,
+}
+
+#[derive(Args)]
+pub struct UseArgs {
+    /// Profile name
+    profile: String,
+}
+
+pub fn run(cmd: ConfigCommands) -> Result<()> {
+    match cmd {
+        ConfigCommands::Set(args) => run_set(args),
+        ConfigCommands::Use(args) => run_use(args),
+        ConfigCommands::Show => run_show(),
+    }
+}
+
+fn load_or_default() -> Result<ConfigFile> {
+    if config_file_path()?.exists() {
+        ConfigFile::load().context("Failed to parse the config file")
+    } else {
+        Ok(ConfigFile::default())
+    }
+}
+
+fn run_set(args: SetArgs) -> Result<()> {
+    let mut file = load_or_default()?;
+    let name = args
+        .profile
+        .or_else(|| file.default.as_ref().and_then(|d| d.profile.clone()))
+        .context("No profile selected. Use --profile or `noir-solana config use <profile>`")?;
+    let profile = file.profiles.entry(name.clone()).or_default();
+    let value = Some(args.value.clone()).filter(|v| !v.is_empty());
+
+    match args.key.as_str() {
+        "network" => profile.network = value,
+        "keypair" => profile.keypair = value,
+        "program-id" | "program_id" => {
+            if let Some(id) = &value {
+                Pubkey::from_str(id).context("Invalid program ID")?;
+            }
+            profile.program_id = value;
+        }
+        "priority-fee" | "priority_fee" => {
+            profile.priority_fee = value
+                .map(|fee| fee.parse().context("Priority fee must be micro-lamports per CU"))
+                .transpose()?;
+        }
+        key => match key.strip_prefix("vk.") {
+            Some(alias) if !alias.is_empty() => match value {
+                Some(address) => {
+                    Pubkey::from_str(&address).context("Invalid VK account public key")?;
+                    profile.vks.insert(alias.to_string(), address);
+                }
+                None => {
+                    profile.vks.remove(alias);
+                }
+            },
+            _ => anyhow::bail!(
+                "Unknown setting '{}'. Expected network, keypair, program-id, priority-fee or vk.<alias>",
+                key
+            ),
+        },
+    }
+
+    let path = file.save()?;
+    println!(
+        "{} Set {} = {:?} in profile '{}' ({})",
+        style("✓").green().bold(),
+        args.key,
+        args.value,
+        name,
+        path.display()
+    );
+    Ok(())
+}
+
+fn run_use(args: UseArgs) -> Result<()> {
+    let mut file = load_or_default()?;
+    if !file.profiles.contains_key(&args.profile) {
+        anyhow::bail!(
+            "Profile '{}' not found. Create it with `noir-solana config set <key> <value> --profile {}`",
+            args.profile,
+            args.profile
+        );
+    }
+    file.default
+        .get_or_insert_with(DefaultConfig::default)
+        .profile = Some(args.profile.clone());
+    file.save()?;
+    println!(
+        "{} Default profile is now '{}'",
+        style("✓").green().bold(),
+        args.profile
+    );
+    Ok(())
+}
+
+fn run_show() -> Result<()> {
+    let path = config_file_path()?;
+    let file = load_or_default()?;
+    println!("{} {}", style("→").cyan().bold(), path.display());
+    print!("{}", toml::to_string_pretty(&file)?);
+    Ok(())
+}
@@ -1,13 +1,28 @@
-//! Deploy command - deploy verifier program to the network
+//! Deploy command - deploy or upgrade the verifier program
 
 use crate::config::Config;
+use crate::output::print_json;
 use crate::CommonArgs;
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
-use std::path::PathBuf;
+use serde_json::json;
+use solana_sdk::{
+    hash::hash,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signer},
+};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+const BPF_LOADER_UPGRADEABLE: Pubkey =
+    Pubkey::from_str_const("BPFLoaderUpgradeab1e11111111111111111111111");
+
+/// Bytes before the ELF in a ProgramData account: state tag, slot and
+/// optional upgrade authority
+const PROGRAMDATA_METADATA_SIZE: usize = 45;
+
 #[derive(Args)]
 pub struct DeployArgs {
     #[command(flatten)]
@@ -23,17 +38,18 @@ pub struct DeployArgs {
     /// Use existing keypair for program ID (for upgrades)
     #[arg(long)]
     program_keypair: Option<PathBuf>,
+
+    /// Upgrade the deployed program (--program-id, or the pubkey of
+    /// --program-keypair) through a buffer account
+    #[arg(long)]
+    upgrade: bool,
+
+    /// Keypair holding the program's upgrade authority (default: --keypair)
+    #[arg(long, requires = "upgrade")]
+    upgrade_authority: Option<PathBuf>,
 }
 
 pub fn run(config: &Config, args: DeployArgs) -> Result<()> {
-    if !config.quiet {
-        println!(
-            "{} Deploying verifier program to {}...",
-            style("→").cyan().bold(),
-            config.rpc_url
-        );
-    }
-
     // Check if program file exists
     if !args.program.exists() {
         anyhow::bail!(
@@ -49,47 +65,47 @@ pub fn run(config: &Config, args: DeployArgs) -> Result<()> {
         .as_ref()
         .context("Keypair required for deployment")?;
 
-    // Build solana deploy command
-    let mut cmd = Command::new("solana");
-    cmd.arg("program")
-        .arg("deploy")
-        .arg(&args.program)
-        .arg("--url")
-        .arg(&config.rpc_url)
-        .arg("--keypair")
-        .arg(keypair_path);
-
-    if let Some(program_keypair) = &args.program_keypair {
-        cmd.arg("--program-id").arg(program_keypair);
+    if args.upgrade {
+        return upgrade(config, &args, keypair_path);
     }
 
-    // Run deployment
-    let output = cmd.output().context("Failed to run solana CLI")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Deployment failed: {}", stderr);
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Deploying verifier program to {}...",
+            style("→").cyan().bold(),
+            config.rpc_url
+        );
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Build solana deploy command
+    let mut cmd = solana(config, keypair_path, "deploy");
+    cmd.arg(&args.program);
+    if let Some(program_keypair) = &args.program_keypair {
+        cmd.arg("--program-id").arg(program_keypair);
+    }
+    let stdout = run_solana(cmd, "Deployment failed")?;
 
     // Parse program ID from output
     // Format: "Program Id: <pubkey>"
-    let program_id = stdout
-        .lines()
-        .find(|line| line.contains("Program Id:"))
-        .and_then(|line| line.split(':').nth(1))
-        .map(|s| s.trim())
-        .context("Could not parse program ID from output")?;
+    let program_id =
+        parse_field(&stdout, "Program Id:").context("Could not parse program ID from output")?;
+    let program_hash = check_program(config, &program_id.parse()?, &args.program)?;
 
     if config.json_output {
-        println!(r#"{{"program_id": "{}"}}"#, program_id);
+        print_json(
+            true,
+            json!({
+                "program_id": program_id,
+                "program_hash": program_hash,
+            }),
+        );
     } else if !config.quiet {
         println!(
             "{} Program deployed successfully!",
             style("✓").green().bold()
         );
-        println!("  Program ID: {}", style(program_id).cyan());
+        println!("  Program ID: {}", style(&program_id).cyan());
+        println!("  Program hash: {}", program_hash);
         println!();
         println!("Add to your config:");
         println!("  export VERIFIER_PROGRAM_ID={}", program_id);
@@ -97,3 +113,178 @@ pub fn run(config: &Config, args: DeployArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Write the program to a buffer, hand the buffer to the upgrade
+/// authority, upgrade and check the deployed bytes
+fn upgrade(config: &Config, args: &DeployArgs, keypair_path: &Path) -> Result<()> {
+    let program_id = match (config.program_id, &args.program_keypair) {
+        (Some(id), _) => id,
+        (None, Some(path)) => read_keypair_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read program keypair {:?}: {}", path, e))?
+            .pubkey(),
+        (None, None) => anyhow::bail!("--upgrade needs --program-id or --program-keypair"),
+    };
+    let authority_path = args.upgrade_authority.as_deref().unwrap_or(keypair_path);
+    let authority = read_keypair_file(authority_path)
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read upgrade authority {:?}: {}",
+                authority_path,
+                e
+            )
+        })?
+        .pubkey();
+    let progress = !config.quiet && !config.json_output;
+
+    if progress {
+        println!(
+            "{} Upgrading {} on {}...",
+            style("→").cyan().bold(),
+            program_id,
+            config.rpc_url
+        );
+        println!("  Writing {} to a buffer", args.program.display());
+    }
+    let mut cmd = solana(config, keypair_path, "write-buffer");
+    cmd.arg(&args.program);
+    let stdout = run_solana(cmd, "Writing the program buffer failed")?;
+    let buffer = parse_field(&stdout, "Buffer:").context("Could not parse buffer from output")?;
+
+    // From here a failure leaves the buffer's rent behind
+    let upgraded = upgrade_from_buffer(
+        config,
+        keypair_path,
+        &buffer,
+        &program_id,
+        authority_path,
+        &authority,
+        authority != config.load_keypair()?.pubkey(),
+    );
+    if let Err(e) = upgraded {
+        anyhow::bail!(
+            "{}\nBuffer {} is left open; reclaim its rent with \
+             `solana program close {} --authority {}`",
+            e,
+            buffer,
+            buffer,
+            authority_path.display()
+        );
+    }
+
+    let program_hash = check_program(config, &program_id, &args.program)?;
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({
+                "program_id": program_id.to_string(),
+                "buffer": buffer,
+                "upgrade_authority": authority.to_string(),
+                "program_hash": program_hash,
+                "upgraded": true,
+            }),
+        );
+    } else if !config.quiet {
+        println!(
+            "{} Program upgraded successfully!",
+            style("✓").green().bold()
+        );
+        println!("  Program ID: {}", style(program_id).cyan());
+        println!("  Program hash: {} (matches the local build)", program_hash);
+    }
+    Ok(())
+}
+
+/// Hand `buffer` to the upgrade authority when it isn't the payer, then
+/// upgrade `program_id` from it
+fn upgrade_from_buffer(
+    config: &Config,
+    keypair_path: &Path,
+    buffer: &str,
+    program_id: &Pubkey,
+    authority_path: &Path,
+    authority: &Pubkey,
+    transfer_buffer: bool,
+) -> Result<()> {
+    let progress = !config.quiet && !config.json_output;
+    if transfer_buffer {
+        if progress {
+            println!("  Setting buffer {} authority to {}", buffer, authority);
+        }
+        let mut cmd = solana(config, keypair_path, "set-buffer-authority");
+        cmd.arg(buffer)
+            .arg("--new-buffer-authority")
+            .arg(authority.to_string());
+        run_solana(cmd, "Setting the buffer authority failed")?;
+    }
+
+    if progress {
+        println!("  Upgrading from buffer {}", buffer);
+    }
+    let mut cmd = solana(config, keypair_path, "upgrade");
+    cmd.arg(buffer)
+        .arg(program_id.to_string())
+        .arg("--upgrade-authority")
+        .arg(authority_path);
+    run_solana(cmd, "Upgrade failed")?;
+    Ok(())
+}
+
+/// Compare the deployed program with the local build, returning its hash
+///
+/// ProgramData accounts can be larger than the ELF (after `extend` or a
+/// smaller upgrade); the rest must be zero.
+fn check_program(config: &Config, program_id: &Pubkey, local: &Path) -> Result<String> {
+    let elf = fs::read(local).with_context(|| format!("Failed to read {:?}", local))?;
+    let (programdata, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE);
+    let data = config
+        .rpc_client()
+        .get_account_data(&programdata)
+        .with_context(|| format!("Failed to fetch program data of {}", program_id))?;
+
+    let deployed = data.get(PROGRAMDATA_METADATA_SIZE..).unwrap_or_default();
+    let matches = deployed.len() >= elf.len()
+        && deployed[..elf.len()] == elf[..]
+        && deployed[elf.len()..].iter().all(|&b| b == 0);
+    let local_hash = hash(&elf);
+    if !matches {
+        anyhow::bail!(
+            "Program {} on chain does not match {:?} (local hash {})",
+            program_id,
+            local,
+            local_hash
+        );
+    }
+    Ok(local_hash.to_string())
+}
+
+/// `solana program <subcommand>` with the cluster and fee payer set
+fn solana(config: &Config, keypair_path: &Path, subcommand: &str) -> Command {
+    let mut cmd = Command::new("solana");
+    cmd.arg("program")
+        .arg(subcommand)
+        .arg("--url")
+        .arg(&config.rpc_url)
+        .arg("--keypair")
+        .arg(keypair_path);
+    cmd
+}
+
+/// Run a solana CLI command and return its stdout
+fn run_solana(mut cmd: Command, failed: &str) -> Result<String> {
+    let output = cmd.output().context("Failed to run solana CLI")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{}: {}", failed, stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Value after `label` on the first line containing it
+fn parse_field(stdout: &str, label: &str) -> Option<String> {
+    stdout
+        .lines()
+        .find_map(|line| line.split_once(label))
+        .map(|(_, value)| value.trim().to_string())
+}
@@ -0,0 +1,140 @@
+//! Estimate command - expected cost of verifying a proof
+
+use crate::config::Config;
+use crate::output::print_json;
+use crate::{CommonArgs, FeeArgs};
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use plonk_solana_core::key::VerificationKey;
+use plonk_solana_core::proof::{Proof, CONST_PROOF_SIZE_LOG_N};
+use serde_json::json;
+use solana_noir_verifier_sdk::{phase_compute_units, SolanaNoirVerifier, PROOF_SIZE};
+use std::fs;
+use std::path::PathBuf;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Args)]
+pub struct EstimateArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    /// Path to the proof file (default: a ZK proof of the standard size)
+    #[arg(long)]
+    proof: Option<PathBuf>,
+
+    /// Path to the VK file, read for the circuit size
+    #[arg(long, conflicts_with = "log_n")]
+    vk: Option<PathBuf>,
+
+    /// Circuit size as log2, instead of --vk (default: the largest supported)
+    #[arg(long)]
+    log_n: Option<u8>,
+
+    /// Path to the public inputs file
+    #[arg(long, conflicts_with = "num_public_inputs")]
+    public_inputs: Option<PathBuf>,
+
+    /// Number of public inputs, instead of --public-inputs
+    #[arg(long, default_value_t = 1)]
+    num_public_inputs: usize,
+}
+
+pub fn run(config: &Config, args: EstimateArgs) -> Result<()> {
+    let (proof_len, is_zk) = match &args.proof {
+        Some(path) => {
+            let proof =
+                fs::read(path).with_context(|| format!("Failed to read proof file: {:?}", path))?;
+            let (is_zk, _format) = Proof::detect_format(&proof)
+                .map_err(|e| anyhow::anyhow!("Unrecognized proof format: {:?}", e))?;
+            (proof.len(), is_zk)
+        }
+        None => (PROOF_SIZE, true),
+    };
+    let log_n = match (&args.vk, args.log_n) {
+        (Some(path), _) => {
+            let vk =
+                fs::read(path).with_context(|| format!("Failed to read VK file: {:?}", path))?;
+            VerificationKey::from_bytes(&vk)
+                .map_err(|e| anyhow::anyhow!("Invalid VK: {:?}", e))?
+                .log2_circuit_size as u8
+        }
+        (None, Some(log_n)) => log_n,
+        (None, None) => CONST_PROOF_SIZE_LOG_N as u8,
+    };
+    let num_pi = match &args.public_inputs {
+        Some(path) => crate::read_public_inputs(path, None)?.len() / 32,
+        None => args.num_public_inputs,
+    };
+
+    // Rent comes from the cluster; no transaction is sent
+    let verifier = SolanaNoirVerifier::new(
+        config.rpc_client(),
+        config.verifier_config(config.program_id.unwrap_or_default()),
+    );
+    let report = verifier.estimate_for_circuit(log_n, proof_len, num_pi, is_zk)?;
+    let phases = phase_compute_units(log_n, is_zk, num_pi);
+    let sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL;
+
+    if config.json_output {
+        let phases: Vec<_> = phases
+            .iter()
+            .map(|(phase, cus)| json!({ "phase": phase, "compute_units": cus }))
+            .collect();
+        print_json(
+            true,
+            json!({
+                "log_n": report.log_n,
+                "num_transactions": report.num_transactions,
+                "total_compute_units": report.total_compute_units,
+                "phases": phases,
+                "signature_fees": report.signature_fees,
+                "priority_fees": report.priority_fees,
+                "buffer_rent": report.buffer_rent,
+                "state_rent": report.state_rent,
+                "receipt_rent": report.receipt_rent,
+                "total_lamports": report.total_lamports(),
+                "net_lamports": report.net_lamports(),
+            }),
+        );
+    } else if !config.quiet {
+        println!(
+            "{} Estimated cost for log_n = {} ({} public inputs, {})",
+            style("→").cyan().bold(),
+            log_n,
+            num_pi,
+            if is_zk { "ZK" } else { "non-ZK" }
+        );
+        println!();
+        println!("  Phases:");
+        for (phase, cus) in &phases {
+            println!("    {:<40} {:>10} CUs", phase, cus);
+        }
+        println!("    {:<40} {:>10} CUs", "Total", report.total_compute_units);
+        println!();
+        println!("  Transactions: {}", report.num_transactions);
+        println!("  Signature fees: {:.6} SOL", sol(report.signature_fees));
+        println!("  Priority fees: {:.6} SOL", sol(report.priority_fees));
+        println!(
+            "  Rent deposits: {:.6} SOL (buffer), {:.6} SOL (state), {:.6} SOL (receipt)",
+            sol(report.buffer_rent),
+            sol(report.state_rent),
+            sol(report.receipt_rent)
+        );
+        println!();
+        println!(
+            "  Needed up front: {}",
+            style(format!("{:.6} SOL", sol(report.total_lamports()))).cyan()
+        );
+        println!(
+            "  Spent after buffer and state rent returns: {:.6} SOL",
+            sol(report.net_lamports())
+        );
+    }
+
+    Ok(())
+}
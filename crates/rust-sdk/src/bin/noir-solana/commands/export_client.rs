@@ -0,0 +1,1207 @@
+//! Export-client command - generate a typed client from the program IDL
+//!
+//! The client is generated from the Anchor interface program's IDL, which
+//! is itself generated from the Rust instruction and account definitions,
+//! so instruction layouts, PDAs, events and error codes can't drift from
+//! the program. IDL features without a mapping here (composite accounts,
+//! enums with fields, generics) are rejected rather than guessed at.
+
+use crate::config::Config;
+use crate::output::print_json;
+use crate::CommonArgs;
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use console::style;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+/// IDL of `programs/ultrahonk-verifier-anchor`
+const VERIFIER_IDL: &str = include_str!(
+    "../../../../../../programs/ultrahonk-verifier-anchor/idl/ultrahonk_verifier_anchor.json"
+);
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// TypeScript on @solana/web3.js
+    Ts,
+}
+
+#[derive(Args)]
+pub struct ExportClientArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Client language
+    #[arg(long, value_enum)]
+    lang: Lang,
+
+    /// IDL to generate from (default: the bundled verifier interface IDL)
+    #[arg(long)]
+    idl: Option<PathBuf>,
+
+    /// Output file (default: ./<program name>.ts)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(config: &Config, args: ExportClientArgs) -> Result<()> {
+    let source = match &args.idl {
+        Some(path) => {
+            fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?
+        }
+        None => VERIFIER_IDL.to_string(),
+    };
+    let idl: Idl = serde_json::from_str(&source).context("Invalid IDL")?;
+
+    let (code, extension) = match args.lang {
+        Lang::Ts => (TsClient::new(&idl)?.generate()?, "ts"),
+    };
+    let out = args
+        .out
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{}", idl.metadata.name, extension)));
+    fs::write(&out, code).with_context(|| format!("Failed to write {:?}", out))?;
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({
+                "path": out,
+                "program": idl.metadata.name,
+                "program_id": idl.address,
+                "instructions": idl.instructions.len(),
+            }),
+        );
+    } else if !config.quiet {
+        println!(
+            "{} Wrote {} client for {} ({} instructions) to {}",
+            style("✓").green().bold(),
+            match args.lang {
+                Lang::Ts => "TypeScript",
+            },
+            style(&idl.metadata.name).bold(),
+            idl.instructions.len(),
+            out.display()
+        );
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// IDL (Anchor spec 0.1.0)
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct Idl {
+    address: String,
+    metadata: Metadata,
+    #[serde(default)]
+    instructions: Vec<Instruction>,
+    #[serde(default)]
+    accounts: Vec<Discriminated>,
+    #[serde(default)]
+    events: Vec<Discriminated>,
+    #[serde(default)]
+    errors: Vec<ErrorCode>,
+    #[serde(default)]
+    types: Vec<TypeDef>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct Instruction {
+    name: String,
+    #[serde(default)]
+    docs: Vec<String>,
+    discriminator: Vec<u8>,
+    accounts: Vec<AccountItem>,
+    args: Vec<Field>,
+    returns: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct AccountItem {
+    name: String,
+    #[serde(default)]
+    docs: Vec<String>,
+    #[serde(default)]
+    writable: bool,
+    #[serde(default)]
+    signer: bool,
+    #[serde(default)]
+    optional: bool,
+    address: Option<String>,
+    pda: Option<Pda>,
+    /// Set on composite (nested) account groups
+    accounts: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Pda {
+    seeds: Vec<Seed>,
+    program: Option<Seed>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Seed {
+    Const { value: Vec<u8> },
+    Arg { path: String },
+    Account { path: String },
+}
+
+/// An IDL account or event: a type name and its data prefix
+#[derive(Deserialize)]
+struct Discriminated {
+    name: String,
+    discriminator: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct ErrorCode {
+    code: u32,
+    name: String,
+    msg: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TypeDef {
+    name: String,
+    #[serde(default)]
+    docs: Vec<String>,
+    #[serde(rename = "type")]
+    ty: TypeDefKind,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum TypeDefKind {
+    Struct {
+        #[serde(default)]
+        fields: Vec<Field>,
+    },
+    Enum {
+        variants: Vec<Variant>,
+    },
+}
+
+#[derive(Deserialize)]
+struct Variant {
+    name: String,
+    fields: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Field {
+    name: String,
+    #[serde(default)]
+    docs: Vec<String>,
+    #[serde(rename = "type")]
+    ty: Value,
+}
+
+/// A field type, parsed from the IDL's JSON form
+enum Ty {
+    Primitive(String),
+    Array(Box<Ty>, usize),
+    Vec(Box<Ty>),
+    Option(Box<Ty>),
+    Defined(String),
+}
+
+impl Ty {
+    fn parse(value: &Value) -> Result<Self> {
+        if let Some(name) = value.as_str() {
+            return Ok(Ty::Primitive(name.to_string()));
+        }
+        if let Some([inner, len]) = value
+            .get("array")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            let len = len
+                .as_u64()
+                .with_context(|| format!("Unsupported array length: {}", len))?;
+            return Ok(Ty::Array(Box::new(Ty::parse(inner)?), len as usize));
+        }
+        if let Some(inner) = value.get("vec") {
+            return Ok(Ty::Vec(Box::new(Ty::parse(inner)?)));
+        }
+        if let Some(inner) = value.get("option") {
+            return Ok(Ty::Option(Box::new(Ty::parse(inner)?)));
+        }
+        if let Some(defined) = value.get("defined") {
+            let name = defined.get("name").unwrap_or(defined);
+            if let Some(name) = name.as_str() {
+                return Ok(Ty::Defined(name.to_string()));
+            }
+        }
+        bail!("Unsupported IDL type: {}", value)
+    }
+
+    fn is_u8(&self) -> bool {
+        matches!(self, Ty::Primitive(name) if name == "u8")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TypeScript
+// ---------------------------------------------------------------------------
+
+/// Borsh reader and writer shared by the generated functions
+const TS_WRITER: &str = r#"
+class Writer {
+  private readonly data: number[] = [];
+
+  uint(value: number | bigint, size: number): void {
+    let v = BigInt(value);
+    for (let i = 0; i < size; i++) {
+      this.data.push(Number(v & BigInt(0xff)));
+      v >>= BigInt(8);
+    }
+  }
+
+  int(value: number | bigint, size: number): void {
+    this.uint(BigInt.asUintN(size * 8, BigInt(value)), size);
+  }
+
+  float(value: number, size: 4 | 8): void {
+    const view = new DataView(new ArrayBuffer(size));
+    if (size === 4) view.setFloat32(0, value, true);
+    else view.setFloat64(0, value, true);
+    this.raw(new Uint8Array(view.buffer));
+  }
+
+  bool(value: boolean): void {
+    this.data.push(value ? 1 : 0);
+  }
+
+  raw(bytes: Uint8Array | number[]): void {
+    for (const b of bytes) this.data.push(b);
+  }
+
+  fixed(bytes: Uint8Array | number[], length: number): void {
+    checkLength(bytes.length, length);
+    this.raw(bytes);
+  }
+
+  bytes(bytes: Uint8Array | number[]): void {
+    this.uint(bytes.length, 4);
+    this.raw(bytes);
+  }
+
+  string(value: string): void {
+    this.bytes(Buffer.from(value, 'utf8'));
+  }
+
+  finish(): Buffer {
+    return Buffer.from(this.data);
+  }
+}
+
+function checkLength(actual: number, expected: number): void {
+  if (actual !== expected) {
+    throw new Error(`Expected ${expected} items, got ${actual}`);
+  }
+}
+"#;
+
+const TS_READER: &str = r#"
+class Reader {
+  private offset = 0;
+
+  constructor(private readonly data: Uint8Array) {}
+
+  raw(length: number): Uint8Array {
+    if (this.offset + length > this.data.length) {
+      throw new Error('Data too short');
+    }
+    const bytes = this.data.slice(this.offset, this.offset + length);
+    this.offset += length;
+    return bytes;
+  }
+
+  uint(size: number): bigint {
+    const bytes = this.raw(size);
+    let v = BigInt(0);
+    for (let i = size - 1; i >= 0; i--) {
+      v = (v << BigInt(8)) | BigInt(bytes[i]);
+    }
+    return v;
+  }
+
+  int(size: number): bigint {
+    return BigInt.asIntN(size * 8, this.uint(size));
+  }
+
+  float(size: 4 | 8): number {
+    const bytes = this.raw(size);
+    const view = new DataView(bytes.buffer, bytes.byteOffset, size);
+    return size === 4 ? view.getFloat32(0, true) : view.getFloat64(0, true);
+  }
+
+  bool(): boolean {
+    return this.uint(1) !== BigInt(0);
+  }
+
+  publicKey(): PublicKey {
+    return new PublicKey(this.raw(32));
+  }
+
+  bytes(): Uint8Array {
+    return this.raw(Number(this.uint(4)));
+  }
+
+  string(): string {
+    return Buffer.from(this.bytes()).toString('utf8');
+  }
+}
+"#;
+
+const TS_STARTS_WITH: &str = r#"
+function startsWith(data: Uint8Array, prefix: Uint8Array): boolean {
+  return data.length >= prefix.length && prefix.every((b, i) => data[i] === b);
+}
+"#;
+
+const TS_EVENTS: &str = r#"
+const PROGRAM_DATA = 'Program data: ';
+
+/** Decode the program's events from a transaction's log messages */
+export function parseEvents(logs: string[]): ProgramEvent[] {
+  return logs
+    .filter((line) => line.startsWith(PROGRAM_DATA))
+    .map((line) => decodeEvent(Buffer.from(line.slice(PROGRAM_DATA.length), 'base64')))
+    .filter((event): event is ProgramEvent => event !== null);
+}
+"#;
+
+const TS_ERRORS: &str = r#"
+/** Look up a `Custom(code)` error raised by the program */
+export function errorFromCode(code: number): ProgramError | undefined {
+  return ERRORS.find((e) => e.code === code);
+}
+"#;
+
+/// A PDA helper: its function name and the account it derives
+struct PdaHelper<'a> {
+    function: String,
+    account: &'a str,
+    pda: &'a Pda,
+    instruction: &'a Instruction,
+}
+
+struct TsClient<'a> {
+    idl: &'a Idl,
+    types: BTreeMap<&'a str, &'a TypeDef>,
+    /// Types instruction arguments encode, directly or through fields
+    encoded: BTreeSet<&'a str>,
+    /// Fixed account addresses, by the constant they're exported as
+    addresses: BTreeMap<String, String>,
+    /// PDA helpers, keyed by `(instruction, account)`
+    pdas: BTreeMap<(&'a str, &'a str), PdaHelper<'a>>,
+}
+
+impl<'a> TsClient<'a> {
+    fn new(idl: &'a Idl) -> Result<Self> {
+        let types = idl.types.iter().map(|t| (t.name.as_str(), t)).collect();
+        let mut client = TsClient {
+            idl,
+            types,
+            encoded: BTreeSet::new(),
+            addresses: BTreeMap::new(),
+            pdas: BTreeMap::new(),
+        };
+
+        for ix in &idl.instructions {
+            for arg in &ix.args {
+                client.mark_encoded(&Ty::parse(&arg.ty)?)?;
+            }
+            for account in &ix.accounts {
+                if account.accounts.is_some() {
+                    bail!(
+                        "Composite accounts are not supported ({}.{})",
+                        ix.name,
+                        account.name
+                    );
+                }
+                if let Some(address) = &account.address {
+                    client.address_const(&account.name, address);
+                }
+                if let Some(pda) = &account.pda {
+                    client.add_pda(ix, account, pda)?;
+                }
+            }
+        }
+        Ok(client)
+    }
+
+    fn mark_encoded(&mut self, ty: &Ty) -> Result<()> {
+        match ty {
+            Ty::Primitive(_) => Ok(()),
+            Ty::Array(inner, _) | Ty::Vec(inner) | Ty::Option(inner) => self.mark_encoded(inner),
+            Ty::Defined(name) => {
+                let def = self.type_def(name)?;
+                if self.encoded.insert(def.name.as_str()) {
+                    if let TypeDefKind::Struct { fields } = &def.ty {
+                        for field in fields {
+                            self.mark_encoded(&Ty::parse(&field.ty)?)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn type_def(&self, name: &str) -> Result<&'a TypeDef> {
+        self.types
+            .get(name)
+            .copied()
+            .with_context(|| format!("Type {} is not defined in the IDL", name))
+    }
+
+    /// Name of the exported constant holding `address`
+    ///
+    /// Accounts sharing a name and address share the constant; a name
+    /// reused for another address gets an indexed one.
+    fn address_const(&mut self, account: &str, address: &str) -> String {
+        let base = format!("{}_ADDRESS", screaming(account));
+        let mut name = base.clone();
+        let mut index = 2;
+        while let Some(existing) = self.addresses.get(&name) {
+            if existing == address {
+                return name;
+            }
+            name = format!("{}_{}", base, index);
+            index += 1;
+        }
+        self.addresses.insert(name.clone(), address.to_string());
+        name
+    }
+
+    fn add_pda(
+        &mut self,
+        ix: &'a Instruction,
+        account: &'a AccountItem,
+        pda: &'a Pda,
+    ) -> Result<()> {
+        for seed in pda.seeds.iter().chain(&pda.program) {
+            match seed {
+                Seed::Const { .. } => {}
+                Seed::Arg { path } => {
+                    let arg = ix.args.iter().find(|a| &a.name == path);
+                    match arg.map(|a| Ty::parse(&a.ty)).transpose()? {
+                        Some(Ty::Primitive(p))
+                            if p == "pubkey" || p == "string" || p == "bytes" => {}
+                        Some(Ty::Array(inner, _)) | Some(Ty::Vec(inner)) if inner.is_u8() => {}
+                        _ => bail!(
+                            "Unsupported PDA seed {}.{}: {}",
+                            ix.name,
+                            account.name,
+                            path
+                        ),
+                    }
+                }
+                Seed::Account { path } => {
+                    let seed_account = ix.accounts.iter().find(|a| &a.name == path);
+                    if !seed_account.is_some_and(|a| !a.optional && a.pda.is_none()) {
+                        bail!(
+                            "Unsupported PDA seed {}.{}: {}",
+                            ix.name,
+                            account.name,
+                            path
+                        );
+                    }
+                }
+            }
+        }
+
+        let same_pda = |helper: &PdaHelper| {
+            helper.pda.seeds == pda.seeds
+                && helper.pda.program == pda.program
+                && seed_params(helper.instruction, helper.pda) == seed_params(ix, pda)
+        };
+        let mut function = format!("find{}Address", pascal(&account.name));
+        if let Some(existing) = self.pdas.values().find(|h| h.function == function) {
+            if same_pda(existing) {
+                let function = existing.function.clone();
+                let helper = PdaHelper {
+                    function,
+                    account: existing.account,
+                    pda: existing.pda,
+                    instruction: existing.instruction,
+                };
+                self.pdas.insert((&ix.name, &account.name), helper);
+                return Ok(());
+            }
+            function = format!("find{}{}Address", pascal(&ix.name), pascal(&account.name));
+        }
+        self.pdas.insert(
+            (&ix.name, &account.name),
+            PdaHelper {
+                function,
+                account: &account.name,
+                pda,
+                instruction: ix,
+            },
+        );
+        Ok(())
+    }
+
+    fn generate(&self) -> Result<String> {
+        let idl = self.idl;
+        let mut out = String::new();
+        let has_decoders = !idl.types.is_empty() || !idl.accounts.is_empty();
+
+        writeln!(
+            out,
+            "// {} client, generated by `noir-solana export-client --lang ts` from",
+            idl.metadata.name
+        )?;
+        writeln!(
+            out,
+            "// the program's IDL (version {}). Do not edit; regenerate instead.",
+            idl.metadata.version
+        )?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "import {{ PublicKey, TransactionInstruction }} from '@solana/web3.js';"
+        )?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "export const PROGRAM_ID = new PublicKey('{}');",
+            idl.address
+        )?;
+        for (name, address) in &self.addresses {
+            writeln!(out, "export const {} = new PublicKey('{}');", name, address)?;
+        }
+
+        if !idl.instructions.is_empty() {
+            out.push_str(TS_WRITER);
+        }
+        if has_decoders {
+            out.push_str(TS_READER);
+        }
+        if !idl.events.is_empty() || !idl.accounts.is_empty() {
+            out.push_str(TS_STARTS_WITH);
+        }
+
+        for def in &idl.types {
+            self.type_def_ts(&mut out, def)?;
+        }
+        for account in &idl.accounts {
+            self.account_decoder(&mut out, account)?;
+        }
+
+        let mut emitted = BTreeSet::new();
+        for helper in self.pdas.values() {
+            if emitted.insert(&helper.function) {
+                self.pda_helper(&mut out, helper)?;
+            }
+        }
+        for ix in &idl.instructions {
+            self.instruction(&mut out, ix)?;
+        }
+
+        if !idl.events.is_empty() {
+            self.events(&mut out)?;
+        }
+        if !idl.errors.is_empty() {
+            self.errors(&mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn type_def_ts(&self, out: &mut String, def: &TypeDef) -> Result<()> {
+        let name = &def.name;
+        writeln!(out)?;
+        match &def.ty {
+            TypeDefKind::Struct { fields } => {
+                doc(out, "", &def.docs)?;
+                writeln!(out, "export interface {} {{", name)?;
+                for field in fields {
+                    doc(out, "  ", &field.docs)?;
+                    let ty = self.ts_type(&Ty::parse(&field.ty)?)?;
+                    writeln!(out, "  {}: {};", camel(&field.name), ty)?;
+                }
+                writeln!(out, "}}")?;
+
+                writeln!(out)?;
+                writeln!(out, "function read{}(r: Reader): {} {{", name, name)?;
+                writeln!(out, "  return {{")?;
+                for field in fields {
+                    let expr = self.read_expr(&Ty::parse(&field.ty)?)?;
+                    writeln!(out, "    {}: {},", camel(&field.name), expr)?;
+                }
+                writeln!(out, "  }};")?;
+                writeln!(out, "}}")?;
+
+                if self.encoded.contains(name.as_str()) {
+                    writeln!(out)?;
+                    writeln!(
+                        out,
+                        "function write{}(w: Writer, value: {}): void {{",
+                        name, name
+                    )?;
+                    for field in fields {
+                        let value = format!("value.{}", camel(&field.name));
+                        self.write_stmt(out, "  ", &Ty::parse(&field.ty)?, &value)?;
+                    }
+                    writeln!(out, "}}")?;
+                }
+
+                writeln!(out)?;
+                writeln!(out, "/** Decode the Borsh encoding of `{}` */", name)?;
+                writeln!(
+                    out,
+                    "export function decode{}(data: Uint8Array): {} {{",
+                    name, name
+                )?;
+                writeln!(out, "  return read{}(new Reader(data));", name)?;
+                writeln!(out, "}}")?;
+            }
+            TypeDefKind::Enum { variants } => {
+                if variants.iter().any(|v| v.fields.is_some()) {
+                    bail!(
+                        "Enum {} has variants with fields, which are not supported",
+                        name
+                    );
+                }
+                doc(out, "", &def.docs)?;
+                writeln!(out, "export enum {} {{", name)?;
+                for (i, variant) in variants.iter().enumerate() {
+                    writeln!(out, "  {} = {},", variant.name, i)?;
+                }
+                writeln!(out, "}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn account_decoder(&self, out: &mut String, account: &Discriminated) -> Result<()> {
+        let name = &account.name;
+        let disc = format!("{}_ACCOUNT_DISCRIMINATOR", screaming(name));
+        writeln!(out)?;
+        writeln!(
+            out,
+            "export const {} = Buffer.from({:?});",
+            disc, account.discriminator
+        )?;
+        writeln!(out)?;
+        writeln!(out, "/** Decode a `{}` account's data */", name)?;
+        writeln!(
+            out,
+            "export function decode{}Account(data: Uint8Array): {} {{",
+            name, name
+        )?;
+        writeln!(out, "  if (!startsWith(data, {})) {{", disc)?;
+        writeln!(out, "    throw new Error('Not a {} account');", name)?;
+        writeln!(out, "  }}")?;
+        writeln!(
+            out,
+            "  return read{}(new Reader(data.subarray({}.length)));",
+            self.type_def(name)?.name,
+            disc
+        )?;
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+
+    fn pda_helper(&self, out: &mut String, helper: &PdaHelper) -> Result<()> {
+        let (ix, pda) = (helper.instruction, helper.pda);
+        let mut params = Vec::new();
+        let mut seeds = Vec::new();
+        for seed in &pda.seeds {
+            seeds.push(match seed {
+                Seed::Const { value } => const_seed(value),
+                Seed::Account { path } => {
+                    params.push(format!("{}: PublicKey", camel(path)));
+                    format!("{}.toBuffer()", camel(path))
+                }
+                Seed::Arg { path } => {
+                    let arg = ix.args.iter().find(|a| &a.name == path).unwrap();
+                    let ty = Ty::parse(&arg.ty)?;
+                    params.push(format!("{}: {}", camel(path), self.ts_type(&ty)?));
+                    match ty {
+                        Ty::Primitive(p) if p == "pubkey" => format!("{}.toBuffer()", camel(path)),
+                        Ty::Primitive(p) if p == "string" => {
+                            format!("Buffer.from({}, 'utf8')", camel(path))
+                        }
+                        _ => format!("Buffer.from({})", camel(path)),
+                    }
+                }
+            });
+        }
+        let program = match &pda.program {
+            None => {
+                params.push("programId: PublicKey = PROGRAM_ID".to_string());
+                "programId".to_string()
+            }
+            Some(Seed::Const { value }) => {
+                let address = Pubkey::try_from(value.as_slice())
+                    .map_err(|_| anyhow::anyhow!("Invalid PDA program in {}", ix.name))?;
+                self.address_ref(&address.to_string())
+            }
+            Some(Seed::Account { path }) => {
+                params.push(format!("{}: PublicKey", camel(path)));
+                camel(path)
+            }
+            Some(Seed::Arg { path }) => {
+                params.push(format!("{}: PublicKey", camel(path)));
+                camel(path)
+            }
+        };
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/** Derive the `{}` account of `{}` */",
+            helper.account, ix.name
+        )?;
+        writeln!(
+            out,
+            "export function {}({}): [PublicKey, number] {{",
+            helper.function,
+            params.join(", ")
+        )?;
+        writeln!(out, "  return PublicKey.findProgramAddressSync(")?;
+        writeln!(out, "    [{}],", seeds.join(", "))?;
+        writeln!(out, "    {},", program)?;
+        writeln!(out, "  );")?;
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+
+    /// Expression for a fixed address: its constant, or inline if unnamed
+    fn address_ref(&self, address: &str) -> String {
+        if address == self.idl.address {
+            return "PROGRAM_ID".to_string();
+        }
+        match self.addresses.iter().find(|(_, a)| *a == address) {
+            Some((name, _)) => name.clone(),
+            None => format!("new PublicKey('{}')", address),
+        }
+    }
+
+    fn instruction(&self, out: &mut String, ix: &Instruction) -> Result<()> {
+        let pascal_name = pascal(&ix.name);
+        let disc = format!("{}_DISCRIMINATOR", screaming(&ix.name));
+        let accounts_type = format!("{}Accounts", pascal_name);
+        let args_type = format!("{}Args", pascal_name);
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "export const {} = Buffer.from({:?});",
+            disc, ix.discriminator
+        )?;
+
+        writeln!(out)?;
+        writeln!(out, "export interface {} {{", accounts_type)?;
+        for account in ix.accounts.iter().filter(|a| a.address.is_none()) {
+            let mut docs = account.docs.clone();
+            let derived = self.pdas.get(&(ix.name.as_str(), account.name.as_str()));
+            if let Some(helper) = derived {
+                docs.push(format!("Derived with `{}` when omitted", helper.function));
+            }
+            doc(out, "  ", &docs)?;
+            let optional = account.optional || derived.is_some();
+            writeln!(
+                out,
+                "  {}{}: PublicKey;",
+                camel(&account.name),
+                if optional { "?" } else { "" }
+            )?;
+        }
+        writeln!(out, "}}")?;
+
+        if !ix.args.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "export interface {} {{", args_type)?;
+            for arg in &ix.args {
+                doc(out, "  ", &arg.docs)?;
+                let ty = self.ts_type(&Ty::parse(&arg.ty)?)?;
+                writeln!(out, "  {}: {};", camel(&arg.name), ty)?;
+            }
+            writeln!(out, "}}")?;
+        }
+
+        let mut docs = ix.docs.clone();
+        if let Some(returns) = &ix.returns {
+            let returns = self.ts_type(&Ty::parse(returns)?)?;
+            docs.push(String::new());
+            docs.push(format!("Return data: `{}`", returns));
+        }
+        writeln!(out)?;
+        doc(out, "", &docs)?;
+        let args_param = if ix.args.is_empty() {
+            String::new()
+        } else {
+            format!("args: {}, ", args_type)
+        };
+        // `create_receipt` builds `createReceiptInstruction`, not `createCreate...`
+        let builder = match ix.name.strip_prefix("create_") {
+            Some(rest) => pascal(rest),
+            None => pascal_name.clone(),
+        };
+        writeln!(out, "export function create{}Instruction(", builder)?;
+        writeln!(
+            out,
+            "  accounts: {}, {}programId: PublicKey = PROGRAM_ID,",
+            accounts_type, args_param
+        )?;
+        writeln!(out, "): TransactionInstruction {{")?;
+        writeln!(out, "  const w = new Writer();")?;
+        writeln!(out, "  w.raw({});", disc)?;
+        for arg in &ix.args {
+            let value = format!("args.{}", camel(&arg.name));
+            self.write_stmt(out, "  ", &Ty::parse(&arg.ty)?, &value)?;
+        }
+        writeln!(out, "  return new TransactionInstruction({{")?;
+        writeln!(out, "    programId,")?;
+        writeln!(out, "    keys: [")?;
+        for account in &ix.accounts {
+            let field = format!("accounts.{}", camel(&account.name));
+            let (pubkey, present) = if let Some(address) = &account.address {
+                (self.address_ref(address), None)
+            } else if let Some(helper) = self.pdas.get(&(ix.name.as_str(), account.name.as_str())) {
+                let pubkey = format!("{} ?? {}", field, self.pda_call(ix, helper)?);
+                (pubkey, None)
+            } else if account.optional {
+                // Anchor reads the program ID in an optional slot as "none"
+                let present = format!("{} !== undefined", field);
+                (format!("{} ?? programId", field), Some(present))
+            } else {
+                (field, None)
+            };
+            let flag = |set: bool| match (&present, set) {
+                (_, false) => "false".to_string(),
+                (None, true) => "true".to_string(),
+                (Some(present), true) => present.clone(),
+            };
+            writeln!(
+                out,
+                "      {{ pubkey: {}, isSigner: {}, isWritable: {} }},",
+                pubkey,
+                flag(account.signer),
+                flag(account.writable)
+            )?;
+        }
+        writeln!(out, "    ],")?;
+        writeln!(out, "    data: w.finish(),")?;
+        writeln!(out, "  }});")?;
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+
+    /// Call deriving a PDA account from an instruction's accounts and args
+    fn pda_call(&self, ix: &Instruction, helper: &PdaHelper) -> Result<String> {
+        let mut params = Vec::new();
+        for seed in helper.pda.seeds.iter().chain(&helper.pda.program) {
+            match seed {
+                Seed::Const { .. } => {}
+                Seed::Arg { path } => params.push(format!("args.{}", camel(path))),
+                Seed::Account { path } => {
+                    let account = ix.accounts.iter().find(|a| &a.name == path).unwrap();
+                    params.push(match &account.address {
+                        Some(address) => self.address_ref(address),
+                        None => format!("accounts.{}", camel(path)),
+                    });
+                }
+            }
+        }
+        if helper.pda.program.is_none() {
+            params.push("programId".to_string());
+        }
+        Ok(format!("{}({})[0]", helper.function, params.join(", ")))
+    }
+
+    fn events(&self, out: &mut String) -> Result<()> {
+        let events = &self.idl.events;
+        writeln!(out)?;
+        writeln!(out, "export const EVENT_DISCRIMINATORS = {{")?;
+        for event in events {
+            writeln!(
+                out,
+                "  {}: Buffer.from({:?}),",
+                event.name, event.discriminator
+            )?;
+        }
+        writeln!(out, "}};")?;
+
+        writeln!(out)?;
+        writeln!(out, "export type ProgramEvent =")?;
+        for (i, event) in events.iter().enumerate() {
+            self.type_def(&event.name)?;
+            writeln!(
+                out,
+                "  | {{ name: '{}'; data: {} }}{}",
+                event.name,
+                event.name,
+                if i + 1 == events.len() { ";" } else { "" }
+            )?;
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/** Decode an event from its `Program data:` log payload */"
+        )?;
+        writeln!(
+            out,
+            "export function decodeEvent(data: Uint8Array): ProgramEvent | null {{"
+        )?;
+        for event in events {
+            writeln!(
+                out,
+                "  if (startsWith(data, EVENT_DISCRIMINATORS.{})) {{",
+                event.name
+            )?;
+            writeln!(
+                out,
+                "    const r = new Reader(data.subarray(EVENT_DISCRIMINATORS.{}.length));",
+                event.name
+            )?;
+            writeln!(
+                out,
+                "    return {{ name: '{}', data: read{}(r) }};",
+                event.name, event.name
+            )?;
+            writeln!(out, "  }}")?;
+        }
+        writeln!(out, "  return null;")?;
+        writeln!(out, "}}")?;
+        out.push_str(TS_EVENTS);
+        Ok(())
+    }
+
+    fn errors(&self, out: &mut String) -> Result<()> {
+        writeln!(out)?;
+        writeln!(out, "export interface ProgramError {{")?;
+        writeln!(out, "  code: number;")?;
+        writeln!(out, "  name: string;")?;
+        writeln!(out, "  msg?: string;")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(out, "export const ERRORS: ProgramError[] = [")?;
+        for error in &self.idl.errors {
+            let msg = match &error.msg {
+                Some(msg) => format!(", msg: {}", serde_json::to_string(msg)?),
+                None => String::new(),
+            };
+            writeln!(
+                out,
+                "  {{ code: {}, name: '{}'{} }},",
+                error.code, error.name, msg
+            )?;
+        }
+        writeln!(out, "];")?;
+        out.push_str(TS_ERRORS);
+        Ok(())
+    }
+
+    fn ts_type(&self, ty: &Ty) -> Result<String> {
+        Ok(match ty {
+            Ty::Primitive(p) => match p.as_str() {
+                "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "f32" | "f64" => "number",
+                "u64" | "i64" | "u128" | "i128" => "bigint",
+                "bool" => "boolean",
+                "pubkey" => "PublicKey",
+                "string" => "string",
+                "bytes" => "Uint8Array",
+                _ => bail!("Unsupported IDL type: {}", p),
+            }
+            .to_string(),
+            Ty::Array(inner, _) | Ty::Vec(inner) if inner.is_u8() => "Uint8Array".to_string(),
+            Ty::Array(inner, _) | Ty::Vec(inner) => format!("{}[]", self.ts_type(inner)?),
+            Ty::Option(inner) => format!("{} | null", self.ts_type(inner)?),
+            Ty::Defined(name) => self.type_def(name)?.name.clone(),
+        })
+    }
+
+    fn read_expr(&self, ty: &Ty) -> Result<String> {
+        Ok(match ty {
+            Ty::Primitive(p) => match p.as_str() {
+                "u8" | "u16" | "u32" => format!("Number(r.uint({}))", int_size(p)),
+                "i8" | "i16" | "i32" => format!("Number(r.int({}))", int_size(p)),
+                "u64" | "u128" => format!("r.uint({})", int_size(p)),
+                "i64" | "i128" => format!("r.int({})", int_size(p)),
+                "f32" => "r.float(4)".to_string(),
+                "f64" => "r.float(8)".to_string(),
+                "bool" => "r.bool()".to_string(),
+                "pubkey" => "r.publicKey()".to_string(),
+                "string" => "r.string()".to_string(),
+                "bytes" => "r.bytes()".to_string(),
+                _ => bail!("Unsupported IDL type: {}", p),
+            },
+            Ty::Array(inner, len) if inner.is_u8() => format!("r.raw({})", len),
+            Ty::Array(inner, len) => {
+                format!(
+                    "Array.from({{ length: {} }}, () => {})",
+                    len,
+                    self.read_expr(inner)?
+                )
+            }
+            Ty::Vec(inner) if inner.is_u8() => "r.bytes()".to_string(),
+            Ty::Vec(inner) => format!(
+                "Array.from({{ length: Number(r.uint(4)) }}, () => {})",
+                self.read_expr(inner)?
+            ),
+            Ty::Option(inner) => format!("r.bool() ? {} : null", self.read_expr(inner)?),
+            Ty::Defined(name) => match &self.type_def(name)?.ty {
+                TypeDefKind::Struct { .. } => format!("read{}(r)", name),
+                TypeDefKind::Enum { .. } => format!("Number(r.uint(1)) as {}", name),
+            },
+        })
+    }
+
+    fn write_stmt(&self, out: &mut String, indent: &str, ty: &Ty, value: &str) -> Result<()> {
+        match ty {
+            Ty::Primitive(p) => {
+                let stmt = match p.as_str() {
+                    "u8" | "u16" | "u32" | "u64" | "u128" => {
+                        format!("w.uint({}, {});", value, int_size(p))
+                    }
+                    "i8" | "i16" | "i32" | "i64" | "i128" => {
+                        format!("w.int({}, {});", value, int_size(p))
+                    }
+                    "f32" => format!("w.float({}, 4);", value),
+                    "f64" => format!("w.float({}, 8);", value),
+                    "bool" => format!("w.bool({});", value),
+                    "pubkey" => format!("w.raw({}.toBytes());", value),
+                    "string" => format!("w.string({});", value),
+                    "bytes" => format!("w.bytes({});", value),
+                    _ => bail!("Unsupported IDL type: {}", p),
+                };
+                writeln!(out, "{}{}", indent, stmt)?;
+            }
+            Ty::Array(inner, len) if inner.is_u8() => {
+                writeln!(out, "{}w.fixed({}, {});", indent, value, len)?;
+            }
+            Ty::Vec(inner) if inner.is_u8() => {
+                writeln!(out, "{}w.bytes({});", indent, value)?;
+            }
+            Ty::Array(inner, _) | Ty::Vec(inner) => {
+                match ty {
+                    Ty::Array(_, len) => {
+                        writeln!(out, "{}checkLength({}.length, {});", indent, value, len)?
+                    }
+                    _ => writeln!(out, "{}w.uint({}.length, 4);", indent, value)?,
+                }
+                writeln!(out, "{}for (const item of {}) {{", indent, value)?;
+                self.write_stmt(out, &format!("{}  ", indent), inner, "item")?;
+                writeln!(out, "{}}}", indent)?;
+            }
+            Ty::Option(inner) => {
+                writeln!(out, "{}w.bool({} !== null);", indent, value)?;
+                writeln!(out, "{}if ({} !== null) {{", indent, value)?;
+                self.write_stmt(out, &format!("{}  ", indent), inner, value)?;
+                writeln!(out, "{}}}", indent)?;
+            }
+            Ty::Defined(name) => match &self.type_def(name)?.ty {
+                TypeDefKind::Struct { .. } => {
+                    writeln!(out, "{}write{}(w, {});", indent, name, value)?
+                }
+                TypeDefKind::Enum { .. } => writeln!(out, "{}w.uint({}, 1);", indent, value)?,
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Parameters a PDA helper takes, as `(kind, path)`
+fn seed_params<'p>(ix: &'p Instruction, pda: &'p Pda) -> Vec<(&'static str, &'p Value)> {
+    pda.seeds
+        .iter()
+        .filter_map(|seed| match seed {
+            Seed::Arg { path } => ix
+                .args
+                .iter()
+                .find(|a| &a.name == path)
+                .map(|a| ("arg", &a.ty)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Seed expression for constant bytes, as a string when they're printable
+fn const_seed(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(s)
+            if s.chars()
+                .all(|c| c.is_ascii_graphic() && c != '\'' && c != '\\') =>
+        {
+            format!("Buffer.from('{}')", s)
+        }
+        _ => format!("Buffer.from({:?})", value),
+    }
+}
+
+fn int_size(primitive: &str) -> usize {
+    primitive[1..].parse::<usize>().unwrap_or(8) / 8
+}
+
+/// Write `docs` as a JSDoc comment
+fn doc(out: &mut String, indent: &str, docs: &[String]) -> Result<()> {
+    match docs {
+        [] => {}
+        [line] => writeln!(out, "{}/** {} */", indent, line)?,
+        lines => {
+            writeln!(out, "{}/**", indent)?;
+            for line in lines {
+                if line.is_empty() {
+                    writeln!(out, "{} *", indent)?;
+                } else {
+                    writeln!(out, "{} * {}", indent, line)?;
+                }
+            }
+            writeln!(out, "{} */", indent)?;
+        }
+    }
+    Ok(())
+}
+
+fn pascal(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn camel(snake: &str) -> String {
+    let pascal = pascal(snake);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn screaming(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 && !out.ends_with('_') {
+            out.push('_');
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
@@ -0,0 +1,282 @@
+//! Inspect command - decode on-chain accounts and local proof/VK files
+
+use crate::config::Config;
+use crate::output::print_json;
+use crate::CommonArgs;
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use plonk_solana_core::proof::Proof;
+use plonk_solana_core::{CompiledVk, VerificationKey, G2};
+use solana_noir_verifier_sdk::{
+    VerificationStateView, BUFFER_HEADER_SIZE, RECEIPT_SIZE, SRS_G2_SIZE, STATE_SIZE,
+    VK_HEADER_SIZE, VK_SIZE,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Args)]
+pub struct InspectArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Account address (proof buffer, state, receipt or VK) or path to a
+    /// local proof or VK file
+    target: String,
+}
+
+pub fn run(config: &Config, args: InspectArgs) -> Result<()> {
+    let (kind, fields) = if Path::new(&args.target).is_file() {
+        let bytes = fs::read(&args.target)
+            .with_context(|| format!("Failed to read file: {:?}", args.target))?;
+        decode_file(&bytes)?
+    } else {
+        let address = Pubkey::from_str(&args.target)
+            .context("Not a file or an account address (base58 public key)")?;
+        let account = config
+            .rpc_client()
+            .get_account(&address)
+            .with_context(|| format!("Failed to fetch account {}", address))?;
+        let mut decoded = decode_account(&account.data)?;
+        decoded.1.insert(0, ("owner", account.owner.to_string()));
+        decoded
+            .1
+            .insert(1, ("lamports", account.lamports.to_string()));
+        decoded
+    };
+
+    if config.json_output {
+        let mut body = serde_json::Map::new();
+        body.insert("kind".to_string(), kind.into());
+        for (key, value) in fields {
+            body.insert(key.to_string(), value.into());
+        }
+        print_json(true, body.into());
+    } else if !config.quiet {
+        println!("{} {}", style("→").cyan().bold(), style(kind).bold());
+        for (key, value) in &fields {
+            println!("  {:<22} {}", key, value);
+        }
+    }
+    Ok(())
+}
+
+type Decoded = (&'static str, Vec<(&'static str, String)>);
+
+/// Decode a local proof or VK file, told apart by size
+fn decode_file(bytes: &[u8]) -> Result<Decoded> {
+    if let Ok((is_zk, format)) = Proof::detect_format(bytes) {
+        return Ok((
+            "proof",
+            vec![
+                ("size", format!("{} bytes", bytes.len())),
+                ("is_zk", is_zk.to_string()),
+                ("format", format!("{:?}", format)),
+            ],
+        ));
+    }
+    let vk = VerificationKey::from_bytes(bytes)
+        .map_err(|e| anyhow::anyhow!("Neither a proof nor a VK: {:?}", e))?;
+    let srs_g2 = plonk_solana_core::default_srs_g2();
+    Ok(("verification key", vk_fields(bytes.len(), &vk, &srs_g2)))
+}
+
+/// Decode a verifier account, told apart by size
+fn decode_account(data: &[u8]) -> Result<Decoded> {
+    match data.len() {
+        STATE_SIZE => Ok(("verification state", state_fields(data))),
+        RECEIPT_SIZE => Ok(("receipt", receipt_fields(data))),
+        len if len >= VK_HEADER_SIZE + VK_SIZE && data[0] <= 3 && is_vk_account(data) => {
+            Ok(("VK account", vk_account_fields(data)?))
+        }
+        len if len >= BUFFER_HEADER_SIZE => Ok(("proof buffer", buffer_fields(data))),
+        len => anyhow::bail!("Unrecognized account ({} bytes)", len),
+    }
+}
+
+fn is_vk_account(data: &[u8]) -> bool {
+    let vk_bytes = &data[VK_HEADER_SIZE..VK_HEADER_SIZE + VK_SIZE];
+    CompiledVk::from_bytes(vk_bytes).is_ok() || VerificationKey::from_bytes(vk_bytes).is_ok()
+}
+
+fn buffer_fields(data: &[u8]) -> Vec<(&'static str, String)> {
+    let status = match data[0] {
+        0 => "empty",
+        1 => "uploading",
+        2 => "ready",
+        3 => "locked",
+        _ => "unknown",
+    };
+    let proof_len = u16::from_le_bytes([data[1], data[2]]) as usize;
+    let num_pi = u16::from_le_bytes([data[3], data[4]]) as usize;
+    let bitmap = u32::from_le_bytes(data[5..9].try_into().unwrap());
+    let num_chunks = proof_len.div_ceil(solana_noir_verifier_sdk::DEFAULT_CHUNK_SIZE);
+    let missing: Vec<String> = (0..num_chunks)
+        .filter(|i| bitmap & (1 << i) == 0)
+        .map(|i| i.to_string())
+        .collect();
+
+    let mut fields = vec![
+        ("status", status.to_string()),
+        ("proof_length", proof_len.to_string()),
+        (
+            "chunks",
+            format!("{}/{}", num_chunks - missing.len(), num_chunks),
+        ),
+    ];
+    if data[0] != 2 && !missing.is_empty() {
+        fields.push(("missing_chunks", missing.join(", ")));
+    }
+    let pi_end = BUFFER_HEADER_SIZE + num_pi * 32;
+    let values: Vec<String> = data
+        .get(BUFFER_HEADER_SIZE..pi_end)
+        .unwrap_or_default()
+        .chunks(32)
+        .map(|pi| format!("0x{}", hex(pi)))
+        .collect();
+    fields.push(("public_inputs", num_pi.to_string()));
+    fields.push(("public_input_values", values.join(", ")));
+    fields
+}
+
+fn state_fields(data: &[u8]) -> Vec<(&'static str, String)> {
+    let phase = match data[0] {
+        0 => "uninitialized",
+        1 => "challenges in progress",
+        2 => "challenges generated",
+        3 => "sumcheck in progress",
+        4 => "sumcheck verified",
+        5 => "MSM in progress",
+        6 => "MSM computed",
+        7 => "complete",
+        255 => "failed",
+        _ => "unknown",
+    };
+    let view = VerificationStateView::from_account_data(data)
+        .expect("decode_account only passes STATE_SIZE accounts");
+    let state = &view.status;
+    let mut fields = vec![
+        ("phase", format!("{} ({})", phase, state.raw_phase)),
+        ("challenge_sub_phase", state.challenge_sub_phase.to_string()),
+        ("sumcheck_sub_phase", state.sumcheck_sub_phase.to_string()),
+        ("log_n", state.log_n.to_string()),
+        ("is_zk", view.is_zk.to_string()),
+        ("public_inputs", view.num_public_inputs.to_string()),
+        ("vk_account", view.vk_account.to_string()),
+        ("vk_hash", format!("0x{}", hex(&view.vk_hash))),
+        ("proof_buffer", view.proof_buffer.to_string()),
+        (
+            "sumcheck_rounds",
+            format!("{}/{}", state.sumcheck_rounds_completed, state.log_n),
+        ),
+        ("sumcheck_passed", view.sumcheck_passed.to_string()),
+        ("shplemini_sub_phase", state.shplemini_sub_phase.to_string()),
+        (
+            "msm_scalars_hash",
+            view.msm_scalars_hash
+                .map_or_else(|| "(none)".to_string(), |hash| format!("0x{}", hex(&hash))),
+        ),
+        (
+            "relation_coefficients_hash",
+            view.relation_coefficients_hash
+                .map_or_else(|| "(none)".to_string(), |hash| format!("0x{}", hex(&hash))),
+        ),
+        ("verified", state.verified.to_string()),
+    ];
+    match &view.challenges {
+        Some(set) => {
+            let challenges = [
+                ("eta", Some(&set.eta)),
+                ("beta", Some(&set.beta)),
+                ("gamma", Some(&set.gamma)),
+                ("public_input_delta", Some(&set.public_input_delta)),
+                ("alpha[0]", set.alphas.first()),
+                ("gate_challenge[0]", set.gate_challenges.first()),
+                ("libra_challenge", set.libra_challenge.as_ref()),
+                ("rho", Some(&set.rho)),
+                ("gemini_r", Some(&set.gemini_r)),
+                ("shplonk_nu", Some(&set.shplonk_nu)),
+                ("shplonk_z", Some(&set.shplonk_z)),
+            ];
+            for (name, value) in challenges {
+                let shown =
+                    value.map_or_else(|| "(not set)".to_string(), |v| format!("0x{}", hex(v)));
+                fields.push((name, shown));
+            }
+        }
+        None => fields.push(("challenges", "(not generated yet)".to_string())),
+    }
+    fields
+}
+
+fn receipt_fields(data: &[u8]) -> Vec<(&'static str, String)> {
+    let pubkey = |range: std::ops::Range<usize>| {
+        Pubkey::new_from_array(data[range].try_into().unwrap()).to_string()
+    };
+    vec![
+        (
+            "verified_slot",
+            u64::from_le_bytes(data[0..8].try_into().unwrap()).to_string(),
+        ),
+        (
+            "verified_timestamp",
+            i64::from_le_bytes(data[8..16].try_into().unwrap()).to_string(),
+        ),
+        ("vk_account", pubkey(16..48)),
+        ("public_inputs_hash", format!("0x{}", hex(&data[48..80]))),
+        ("bump", data[80].to_string()),
+        (
+            "verification_count",
+            u32::from_le_bytes(data[84..88].try_into().unwrap()).to_string(),
+        ),
+        ("rent_payer", pubkey(88..120)),
+        ("tag", format!("0x{}", hex(&data[120..152]))),
+        ("proof_digest", format!("0x{}", hex(&data[152..184]))),
+        ("vk_hash", format!("0x{}", hex(&data[184..216]))),
+        ("rent_beneficiary", pubkey(216..248)),
+    ]
+}
+
+fn vk_account_fields(data: &[u8]) -> Result<Vec<(&'static str, String)>> {
+    let status = match data[0] {
+        0 => "empty",
+        1 => "uploading",
+        2 => "ready",
+        _ => "compiled",
+    };
+    let vk_bytes = &data[VK_HEADER_SIZE..VK_HEADER_SIZE + VK_SIZE];
+    let vk = if data[0] == 3 {
+        CompiledVk::from_bytes(vk_bytes).map(|vk| vk.to_key())
+    } else {
+        VerificationKey::from_bytes(vk_bytes)
+    }
+    .map_err(|e| anyhow::anyhow!("Invalid VK: {:?}", e))?;
+
+    // An unset SRS slot means the default point, as on chain
+    let srs_offset = VK_HEADER_SIZE + VK_SIZE;
+    let srs_g2: G2 = match data.get(srs_offset..srs_offset + SRS_G2_SIZE) {
+        Some(slot) if slot.iter().any(|&b| b != 0) => slot.try_into().unwrap(),
+        _ => plonk_solana_core::default_srs_g2(),
+    };
+
+    let mut fields = vec![("status", status.to_string())];
+    fields.extend(vk_fields(VK_SIZE, &vk, &srs_g2));
+    Ok(fields)
+}
+
+fn vk_fields(size: usize, vk: &VerificationKey, srs_g2: &G2) -> Vec<(&'static str, String)> {
+    vec![
+        ("size", format!("{} bytes", size)),
+        ("log_n", vk.log2_circuit_size.to_string()),
+        ("circuit_size", (1u64 << vk.log2_circuit_size).to_string()),
+        ("public_inputs", vk.num_public_inputs.to_string()),
+        ("commitments", vk.num_commitments.to_string()),
+        ("vk_hash", format!("0x{}", hex(&vk.hash_with_srs(srs_g2)))),
+    ]
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
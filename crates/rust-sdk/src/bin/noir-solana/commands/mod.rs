@@ -1,8 +1,20 @@
 //! CLI commands
 
+pub mod airdrop;
+pub mod batch_verify;
+pub mod bench;
 pub mod close;
+pub mod config;
 pub mod deploy;
+pub mod estimate;
+pub mod export_client;
+pub mod inspect;
+pub mod prove;
 pub mod receipt;
+pub mod registry;
+pub mod scaffold;
+pub mod simulate;
 pub mod status;
 pub mod upload_vk;
 pub mod verify;
+pub mod watch;
@@ -0,0 +1,144 @@
+//! Prove command - generate a proof from a Noir circuit and verify it on-chain
+
+use super::verify::verify_proof;
+use crate::config::Config;
+use crate::output::print_json;
+use crate::{CommonArgs, FeeArgs};
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use serde_json::json;
+use solana_noir_verifier_sdk::{prove, ProveOptions, SolanaNoirVerifier, VerifyOptions};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ProveArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    /// Noir package directory (containing Nargo.toml)
+    #[arg(long, default_value = ".")]
+    circuit: PathBuf,
+
+    /// Prover inputs name, read from <name>.toml in the package
+    #[arg(long, default_value = "Prover")]
+    witness: String,
+
+    /// VK account public key; the generated VK is uploaded when omitted
+    #[arg(long)]
+    vk_account: Option<String>,
+
+    /// nargo binary
+    #[arg(long, default_value = "nargo")]
+    nargo: PathBuf,
+
+    /// bb binary (default: bb on the PATH, else ~/.bb/bb)
+    #[arg(long)]
+    bb: Option<PathBuf>,
+
+    /// Only generate the proof, without verifying it on-chain
+    #[arg(long)]
+    no_verify: bool,
+
+    /// Skip preflight simulation (faster but less safe)
+    #[arg(long)]
+    skip_preflight: bool,
+
+    /// Don't close accounts after verification (keep state for debugging)
+    #[arg(long)]
+    no_close: bool,
+
+    /// Start even if the keypair's balance looks too low for the verification
+    #[arg(long)]
+    skip_balance_check: bool,
+}
+
+pub fn run(config: &Config, args: ProveArgs) -> Result<()> {
+    let vk_account = args
+        .vk_account
+        .as_deref()
+        .map(|vk| config.resolve_vk(vk))
+        .transpose()?;
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Proving {} with {}.toml...",
+            style("→").cyan().bold(),
+            args.circuit.display(),
+            args.witness
+        );
+    }
+
+    // nargo execute + bb prove --oracle_hash keccak --zk
+    let mut options = ProveOptions::new(&args.circuit)
+        .with_witness(&args.witness)
+        .with_nargo(&args.nargo);
+    if let Some(bb) = &args.bb {
+        options = options.with_bb(bb);
+    }
+    let output = prove(&options).context("Proof generation failed")?;
+
+    if !config.quiet && !config.json_output {
+        println!("{} Proof generated", style("✓").green().bold());
+        println!("  Artifacts: {}", output.output_dir.display());
+        println!();
+    }
+
+    if args.no_verify {
+        if config.json_output {
+            print_json(
+                true,
+                json!({
+                    "proof": output.output_dir.join("proof"),
+                    "vk": output.output_dir.join("vk"),
+                    "public_inputs": output.output_dir.join("public_inputs"),
+                }),
+            );
+        }
+        return Ok(());
+    }
+
+    let vk_account = match vk_account {
+        Some(vk_account) => vk_account,
+        None => {
+            if !config.quiet && !config.json_output {
+                println!(
+                    "{} Uploading VK ({} bytes)...",
+                    style("→").cyan().bold(),
+                    output.vk.len()
+                );
+            }
+            let verifier = SolanaNoirVerifier::new(
+                config.rpc_client(),
+                config.verifier_config(config.require_program_id()?),
+            );
+            let uploaded = verifier.upload_vk(&config.load_keypair()?, &output.vk)?;
+            if !config.quiet && !config.json_output {
+                println!(
+                    "  VK Account: {}",
+                    style(uploaded.vk_account.to_string()).cyan()
+                );
+                println!();
+            }
+            uploaded.vk_account
+        }
+    };
+
+    let options = VerifyOptions {
+        skip_preflight: args.skip_preflight,
+        auto_close: !args.no_close,
+        ..VerifyOptions::default()
+    };
+    verify_proof(
+        config,
+        &output.proof,
+        &output.public_inputs,
+        &vk_account,
+        options,
+        None,
+        !args.skip_balance_check,
+    )
+}
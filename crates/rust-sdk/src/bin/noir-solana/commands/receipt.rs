@@ -1,12 +1,17 @@
-//! Receipt commands - create and check verification receipts
+//! Receipt commands - create, check and attest verification receipts
 
+use super::inspect::hex;
 use crate::config::Config;
-use crate::CommonArgs;
+use crate::output::{print_json, Reported};
+use crate::{CommonArgs, PublicInputsArgs};
 use anyhow::{Context, Result};
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 use console::style;
-use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerifierConfig};
-use solana_sdk::pubkey::Pubkey;
+use serde_json::json;
+use solana_noir_verifier_sdk::{
+    DuplicateReceiptPolicy, ReceiptAttestation, ReceiptOptions, SolanaNoirVerifier,
+};
+use solana_sdk::{pubkey::Pubkey, signature::read_keypair_file};
 use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -17,6 +22,10 @@ pub enum ReceiptCommands {
     Create(CreateReceiptArgs),
     /// Check if a receipt exists
     Check(CheckReceiptArgs),
+    /// Sign an attestation of a receipt for consumers off Solana
+    Attest(AttestReceiptArgs),
+    /// Check the signature and fields of an attestation
+    VerifyAttestation(VerifyAttestationArgs),
 }
 
 impl ReceiptCommands {
@@ -24,6 +33,8 @@ impl ReceiptCommands {
         match self {
             ReceiptCommands::Create(args) => &args.common,
             ReceiptCommands::Check(args) => &args.common,
+            ReceiptCommands::Attest(args) => &args.common,
+            ReceiptCommands::VerifyAttestation(args) => &args.common,
         }
     }
 }
@@ -41,13 +52,45 @@ pub struct CreateReceiptArgs {
     #[arg(long)]
     proof_account: String,
 
-    /// VK account public key
+    /// VK account public key or profile alias
     #[arg(long)]
     vk_account: String,
 
-    /// Path to the public inputs file
+    #[command(flatten)]
+    public_inputs: PublicInputsArgs,
+
+    /// Application-defined 32-byte tag to store in the receipt, as hex
+    #[arg(long, value_parser = parse_tag)]
+    tag: Option<[u8; 32]>,
+
+    /// What to do if the receipt already exists
+    #[arg(long, value_enum, default_value_t = OnDuplicate::Error)]
+    on_duplicate: OnDuplicate,
+
+    /// Account to refund the rent to when the receipt is closed (default:
+    /// the payer)
     #[arg(long)]
-    public_inputs: PathBuf,
+    rent_beneficiary: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OnDuplicate {
+    /// Fail
+    Error,
+    /// Move the receipt's slot and timestamp to this verification
+    Refresh,
+    /// Increment the receipt's verification count
+    Count,
+}
+
+impl From<OnDuplicate> for DuplicateReceiptPolicy {
+    fn from(value: OnDuplicate) -> Self {
+        match value {
+            OnDuplicate::Error => DuplicateReceiptPolicy::Error,
+            OnDuplicate::Refresh => DuplicateReceiptPolicy::Refresh,
+            OnDuplicate::Count => DuplicateReceiptPolicy::Count,
+        }
+    }
 }
 
 #[derive(Args)]
@@ -55,19 +98,67 @@ pub struct CheckReceiptArgs {
     #[command(flatten)]
     pub common: CommonArgs,
 
-    /// VK account public key
+    /// VK account public key or profile alias
     #[arg(long)]
     vk_account: String,
 
-    /// Path to the public inputs file
+    #[command(flatten)]
+    public_inputs: PublicInputsArgs,
+
+    /// Proof file to compare with the digest the receipt recorded; exits
+    /// with the verification-failed code if it is not the receipt's proof
     #[arg(long)]
-    public_inputs: PathBuf,
+    proof: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct AttestReceiptArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// VK account public key or profile alias
+    #[arg(long)]
+    vk_account: String,
+
+    #[command(flatten)]
+    public_inputs: PublicInputsArgs,
+
+    /// Keypair signing the attestation (defaults to --keypair)
+    #[arg(long)]
+    oracle_keypair: Option<PathBuf>,
+
+    /// Write the binary attestation here instead of printing it as hex
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct VerifyAttestationArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Binary attestation file, as written by `receipt attest --out`
+    attestation: PathBuf,
+
+    /// Oracle public key the attestation must be signed by
+    #[arg(long)]
+    oracle: Option<Pubkey>,
+
+    /// VK account the attestation must be for, or profile alias
+    #[arg(long)]
+    vk_account: Option<String>,
+
+    /// Public inputs file the attestation must be for
+    #[arg(long)]
+    public_inputs: Option<PathBuf>,
 }
 
 pub fn run(config: &Config, command: ReceiptCommands) -> Result<()> {
     match command {
         ReceiptCommands::Create(args) => create_receipt(config, args),
         ReceiptCommands::Check(args) => check_receipt(config, args),
+        ReceiptCommands::Attest(args) => attest_receipt(config, args),
+        ReceiptCommands::VerifyAttestation(args) => verify_attestation(config, args),
     }
 }
 
@@ -76,9 +167,8 @@ fn create_receipt(config: &Config, args: CreateReceiptArgs) -> Result<()> {
         Pubkey::from_str(&args.state_account).context("Invalid state account public key")?;
     let proof_account =
         Pubkey::from_str(&args.proof_account).context("Invalid proof account public key")?;
-    let vk_account = Pubkey::from_str(&args.vk_account).context("Invalid VK account public key")?;
-    let pi_bytes = fs::read(&args.public_inputs)
-        .with_context(|| format!("Failed to read public inputs: {:?}", args.public_inputs))?;
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+    let pi_bytes = args.public_inputs.read()?;
 
     if !config.quiet && !config.json_output {
         println!(
@@ -92,22 +182,32 @@ fn create_receipt(config: &Config, args: CreateReceiptArgs) -> Result<()> {
     let keypair = config.load_keypair()?;
     let client = config.rpc_client();
 
-    let verifier = SolanaNoirVerifier::new(client, VerifierConfig::new(program_id));
+    let verifier = SolanaNoirVerifier::new(client, config.verifier_config(program_id));
 
     // Derive receipt PDA
     let (receipt_pda, _bump) = verifier.derive_receipt_pda(&vk_account, &pi_bytes);
 
     // Create receipt
-    let receipt_pubkey = verifier.create_receipt(
+    let mut options = ReceiptOptions::default().with_on_duplicate(args.on_duplicate.into());
+    if let Some(tag) = args.tag {
+        options = options.with_tag(tag);
+    }
+    if let Some(beneficiary) = &args.rent_beneficiary {
+        options = options.with_rent_beneficiary(
+            Pubkey::from_str(beneficiary).context("Invalid rent beneficiary public key")?,
+        );
+    }
+    let receipt_pubkey = verifier.create_receipt_with_options(
         &keypair,
         &state_account,
         &proof_account,
         &vk_account,
         &pi_bytes,
+        &options,
     )?;
 
     if config.json_output {
-        println!(r#"{{"receipt_pda": "{}"}}"#, receipt_pubkey);
+        print_json(true, json!({ "receipt_pda": receipt_pubkey.to_string() }));
     } else if !config.quiet {
         println!("{} Receipt created!", style("✓").green().bold());
         println!("  Receipt PDA: {}", style(receipt_pda.to_string()).cyan());
@@ -117,9 +217,14 @@ fn create_receipt(config: &Config, args: CreateReceiptArgs) -> Result<()> {
 }
 
 fn check_receipt(config: &Config, args: CheckReceiptArgs) -> Result<()> {
-    let vk_account = Pubkey::from_str(&args.vk_account).context("Invalid VK account public key")?;
-    let pi_bytes = fs::read(&args.public_inputs)
-        .with_context(|| format!("Failed to read public inputs: {:?}", args.public_inputs))?;
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+    let pi_bytes = args.public_inputs.read()?;
+    let proof_digest = match &args.proof {
+        Some(path) => Some(solana_noir_receipt_seeds::proof_digest(
+            &fs::read(path).with_context(|| format!("Failed to read proof {:?}", path))?,
+        )),
+        None => None,
+    };
 
     if !config.quiet && !config.json_output {
         println!(
@@ -132,27 +237,58 @@ fn check_receipt(config: &Config, args: CheckReceiptArgs) -> Result<()> {
     let program_id = config.require_program_id()?;
     let client = config.rpc_client();
 
-    let verifier = SolanaNoirVerifier::new(client, VerifierConfig::new(program_id));
+    let verifier = SolanaNoirVerifier::new(client, config.verifier_config(program_id));
 
     // Check receipt
     let receipt = verifier.get_receipt(&vk_account, &pi_bytes)?;
 
     match receipt {
         Some(receipt) => {
+            let proof_matches = proof_digest.map(|digest| digest == receipt.proof_digest);
             if config.json_output {
-                println!(
-                    r#"{{"exists": true, "verified_slot": {}, "verified_timestamp": {}}}"#,
-                    receipt.verified_slot, receipt.verified_timestamp
-                );
+                let mut value = json!({
+                    "exists": true,
+                    "receipt_pda": receipt.receipt_pda.to_string(),
+                    "verified_slot": receipt.verified_slot,
+                    "verified_timestamp": receipt.verified_timestamp,
+                    "rent_payer": receipt.rent_payer.to_string(),
+                    "rent_beneficiary": receipt.rent_beneficiary.to_string(),
+                    "tag": format!("0x{}", hex(&receipt.tag)),
+                    "verification_count": receipt.verification_count,
+                    "proof_digest": format!("0x{}", hex(&receipt.proof_digest)),
+                });
+                if let Some(matches) = proof_matches {
+                    value["proof_matches"] = json!(matches);
+                }
+                print_json(proof_matches != Some(false), value);
             } else if !config.quiet {
                 println!("{} Receipt found!", style("✓").green().bold());
                 println!("  Verified Slot: {}", receipt.verified_slot);
                 println!("  Verified At: {}", receipt.verified_timestamp);
+                if receipt.tag != [0; 32] {
+                    println!("  Tag: 0x{}", hex(&receipt.tag));
+                }
+                if receipt.verification_count > 1 {
+                    println!("  Verifications: {}", receipt.verification_count);
+                }
+                if receipt.rent_beneficiary != receipt.rent_payer {
+                    println!("  Rent Beneficiary: {}", receipt.rent_beneficiary);
+                }
+                match proof_matches {
+                    Some(true) => println!("  Proof: {}", style("matches receipt ✓").green()),
+                    Some(false) => println!("  Proof: {}", style("differs from receipt ✗").red()),
+                    None => {}
+                }
+            }
+            if proof_matches == Some(false) {
+                return Err(Reported::verification_failed(
+                    "proof digest differs from the receipt",
+                ));
             }
         }
         None => {
             if config.json_output {
-                println!(r#"{{"exists": false}}"#);
+                print_json(true, json!({ "exists": false }));
             } else if !config.quiet {
                 println!(
                     "{} No receipt found for this proof",
@@ -164,3 +300,135 @@ fn check_receipt(config: &Config, args: CheckReceiptArgs) -> Result<()> {
 
     Ok(())
 }
+
+fn attest_receipt(config: &Config, args: AttestReceiptArgs) -> Result<()> {
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+    let pi_bytes = args.public_inputs.read()?;
+    let oracle = match &args.oracle_keypair {
+        Some(path) => read_keypair_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read oracle keypair {:?}: {}", path, e))?,
+        None => config.load_keypair()?,
+    };
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Attesting verification receipt...",
+            style("→").cyan().bold()
+        );
+    }
+
+    let program_id = config.require_program_id()?;
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id));
+    let (receipt_pda, _) = verifier.derive_receipt_pda(&vk_account, &pi_bytes);
+    let attestation = verifier.attest_receipt(&oracle, &receipt_pda)?;
+    let bytes = attestation.to_bytes();
+    if let Some(path) = &args.out {
+        fs::write(path, bytes).with_context(|| format!("Failed to write {:?}", path))?;
+    }
+
+    if config.json_output {
+        let mut value = attestation_json(&attestation);
+        if args.out.is_none() {
+            value["attestation"] = hex(&bytes).into();
+        }
+        print_json(true, value);
+    } else if !config.quiet {
+        println!("{} Receipt attested!", style("✓").green().bold());
+        println!("  Receipt PDA: {}", style(receipt_pda.to_string()).cyan());
+        println!("  Oracle: {}", attestation.oracle);
+        println!("  Snapshot Slot: {}", attestation.snapshot_slot);
+        match &args.out {
+            Some(path) => println!("  Written to {:?}", path),
+            None => println!("  Attestation: {}", hex(&bytes)),
+        }
+    }
+
+    Ok(())
+}
+
+fn verify_attestation(config: &Config, args: VerifyAttestationArgs) -> Result<()> {
+    let bytes = fs::read(&args.attestation)
+        .with_context(|| format!("Failed to read attestation: {:?}", args.attestation))?;
+    let attestation = ReceiptAttestation::from_bytes(&bytes)
+        .with_context(|| format!("{:?} is not a receipt attestation", args.attestation))?;
+
+    let mut problems = Vec::new();
+    if !attestation.verify() {
+        problems.push("signature does not match".to_string());
+    }
+    if let Some(oracle) = args.oracle {
+        if attestation.oracle != oracle {
+            problems.push(format!("signed by {}, not {}", attestation.oracle, oracle));
+        }
+    }
+    if let Some(vk) = &args.vk_account {
+        let vk_account = config.resolve_vk(vk)?;
+        if attestation.vk_account != vk_account {
+            problems.push(format!(
+                "for VK {}, not {}",
+                attestation.vk_account, vk_account
+            ));
+        }
+    }
+    if let Some(path) = &args.public_inputs {
+        let pi_bytes = crate::read_public_inputs(path, None)?;
+        let pi_hash = plonk_solana_core::receipt::public_inputs_hash(&pi_bytes);
+        if attestation.public_inputs_hash != pi_hash {
+            problems.push("for other public inputs".to_string());
+        }
+    }
+    if problems.is_empty() && args.oracle.is_none() && !config.quiet && !config.json_output {
+        println!(
+            "{} No --oracle given; the signature only proves {} signed it",
+            style("!").yellow().bold(),
+            attestation.oracle
+        );
+    }
+
+    if !problems.is_empty() {
+        return Err(Reported::verification_failed(format!(
+            "Attestation rejected: {}",
+            problems.join("; ")
+        )));
+    }
+    if config.json_output {
+        print_json(true, attestation_json(&attestation));
+    } else if !config.quiet {
+        println!("{} Attestation valid", style("✓").green().bold());
+        println!("  Receipt PDA: {}", attestation.receipt_pda);
+        println!("  VK Account: {}", attestation.vk_account);
+        println!("  Verified Slot: {}", attestation.verified_slot);
+        println!("  Oracle: {}", attestation.oracle);
+    }
+    Ok(())
+}
+
+/// Fields of an attestation for `--output json`
+fn attestation_json(attestation: &ReceiptAttestation) -> serde_json::Value {
+    json!({
+        "cluster": attestation.cluster.to_string(),
+        "program_id": attestation.program_id.to_string(),
+        "receipt_pda": attestation.receipt_pda.to_string(),
+        "vk_account": attestation.vk_account.to_string(),
+        "vk_hash": hex(&attestation.vk_hash),
+        "public_inputs_hash": hex(&attestation.public_inputs_hash),
+        "verified_slot": attestation.verified_slot,
+        "verified_timestamp": attestation.verified_timestamp,
+        "snapshot_slot": attestation.snapshot_slot,
+        "oracle": attestation.oracle.to_string(),
+        "signature": attestation.signature.to_string(),
+    })
+}
+
+/// `--tag`: exactly 32 bytes as hex, with or without `0x`
+fn parse_tag(text: &str) -> Result<[u8; 32], String> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    if digits.len() != 64 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("expected 32 bytes as 64 hex digits".to_string());
+    }
+    let mut tag = [0u8; 32];
+    for (byte, pair) in tag.iter_mut().zip(digits.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap();
+    }
+    Ok(tag)
+}
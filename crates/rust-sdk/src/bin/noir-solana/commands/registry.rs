@@ -0,0 +1,449 @@
+//! Registry commands - publish and look up VKs by `name@version`
+//!
+//! Entries live in a local file by default, or in the `vk-registry`
+//! program when `--registry-program` is given.
+
+use super::inspect::hex;
+use crate::config::Config;
+use crate::output::print_json;
+use crate::{CommonArgs, FeeArgs};
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use console::style;
+use serde_json::json;
+use solana_noir_verifier_sdk::{
+    OnChainRegistryEntry, RegistryEntry, SolanaNoirVerifier, VkRegistry,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum RegistryCommands {
+    /// Upload a VK and register it as name@version
+    Publish(PublishArgs),
+    /// List registered VKs
+    List(ListArgs),
+    /// Show a registered VK and whether its account exists
+    Show(KeyArgs),
+    /// Print the VK account registered as name@version
+    Resolve(KeyArgs),
+    /// Deprecate name@version in the registry program
+    Deprecate(DeprecateArgs),
+    /// Hand a name in the registry program to a new authority
+    Transfer(TransferArgs),
+}
+
+impl RegistryCommands {
+    pub fn common(&self) -> &CommonArgs {
+        match self {
+            RegistryCommands::Publish(args) => &args.common,
+            RegistryCommands::List(args) => &args.common,
+            RegistryCommands::Show(args) | RegistryCommands::Resolve(args) => &args.common,
+            RegistryCommands::Deprecate(args) => &args.common,
+            RegistryCommands::Transfer(args) => &args.common,
+        }
+    }
+
+    /// Compute budget options of the commands that send transactions
+    pub fn fees(&self) -> Option<&FeeArgs> {
+        match self {
+            RegistryCommands::Publish(args) => Some(&args.fees),
+            RegistryCommands::Deprecate(args) => Some(&args.fees),
+            RegistryCommands::Transfer(args) => Some(&args.fees),
+            _ => None,
+        }
+    }
+}
+
+/// Where the registry and its VK cache live
+#[derive(Args)]
+pub struct RegistryFileArgs {
+    /// Registry file
+    #[arg(long, env = "NOIR_SOLANA_REGISTRY", default_value = "vk-registry.txt")]
+    registry: PathBuf,
+
+    /// Directory caching published VK bytes
+    #[arg(long, default_value = ".vk-cache")]
+    cache_dir: PathBuf,
+
+    /// Use the on-chain registry program instead of the registry file
+    #[arg(long, env = "NOIR_SOLANA_REGISTRY_PROGRAM")]
+    registry_program: Option<Pubkey>,
+}
+
+impl RegistryFileArgs {
+    fn open(&self) -> Result<VkRegistry> {
+        Ok(VkRegistry::open(&self.registry)?.with_cache_dir(&self.cache_dir))
+    }
+}
+
+#[derive(Args)]
+pub struct PublishArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    #[command(flatten)]
+    file: RegistryFileArgs,
+
+    /// Registry key, e.g. my_circuit@1.2.0
+    key: String,
+
+    /// Path to the verification key file
+    #[arg(long)]
+    vk: PathBuf,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    file: RegistryFileArgs,
+}
+
+#[derive(Args)]
+pub struct KeyArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    file: RegistryFileArgs,
+
+    /// Registry key, e.g. my_circuit@1.2.0
+    key: String,
+}
+
+#[derive(Args)]
+pub struct DeprecateArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    /// Registry program
+    #[arg(long, env = "NOIR_SOLANA_REGISTRY_PROGRAM")]
+    registry_program: Pubkey,
+
+    /// Registry key, e.g. my_circuit@1.2.0
+    key: String,
+}
+
+#[derive(Args)]
+pub struct TransferArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
+    /// Registry program
+    #[arg(long, env = "NOIR_SOLANA_REGISTRY_PROGRAM")]
+    registry_program: Pubkey,
+
+    /// Circuit name, e.g. my_circuit
+    name: String,
+
+    /// New authority of the name and all its versions
+    new_authority: Pubkey,
+}
+
+pub fn run(config: &Config, command: RegistryCommands) -> Result<()> {
+    match command {
+        RegistryCommands::Publish(args) => publish(config, args),
+        RegistryCommands::List(args) => list(config, args),
+        RegistryCommands::Show(args) => show(config, args),
+        RegistryCommands::Resolve(args) => resolve(config, args),
+        RegistryCommands::Deprecate(args) => deprecate(config, args),
+        RegistryCommands::Transfer(args) => transfer(config, args),
+    }
+}
+
+/// Client for the registry program
+///
+/// Deprecating, transferring and listing never touch the verifier, so its
+/// program ID is only required by the commands that upload or check VKs.
+fn registry_client(
+    config: &Config,
+    registry_program: Pubkey,
+    needs_verifier: bool,
+) -> Result<SolanaNoirVerifier> {
+    let program_id = if needs_verifier {
+        config.require_program_id()?
+    } else {
+        config.program_id.unwrap_or_default()
+    };
+    Ok(SolanaNoirVerifier::new(
+        config.rpc_client(),
+        config
+            .verifier_config(program_id)
+            .with_registry_program(registry_program),
+    ))
+}
+
+fn publish(config: &Config, args: PublishArgs) -> Result<()> {
+    let vk_bytes =
+        fs::read(&args.vk).with_context(|| format!("Failed to read VK file: {:?}", args.vk))?;
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Publishing {} ({} bytes) to {}...",
+            style("→").cyan().bold(),
+            args.key,
+            vk_bytes.len(),
+            config.rpc_url
+        );
+    }
+
+    if let Some(registry_program) = args.file.registry_program {
+        let keypair = config.load_keypair()?;
+        let verifier = registry_client(config, registry_program, true)?;
+        let entry = verifier.publish_vk_on_chain(&keypair, &args.key, &vk_bytes)?;
+        if config.json_output {
+            print_json(true, on_chain_json(&entry));
+        } else if !config.quiet {
+            println!("{} Published {}", style("✓").green().bold(), args.key);
+            println!("  VK Account: {}", style(entry.vk_account).cyan());
+            println!("  VK Hash:    {}", hex(&entry.vk_hash));
+            println!("  Entry:      {}", entry.address);
+            println!("  Authority:  {}", entry.registered_by);
+        }
+        return Ok(());
+    }
+    let mut registry = args.file.open()?;
+
+    let program_id = config.require_program_id()?;
+    let keypair = config.load_keypair()?;
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id));
+    let entry = verifier.publish_vk(&keypair, &mut registry, &args.key, &vk_bytes)?;
+
+    if config.json_output {
+        print_json(true, entry_json(&args.key, &entry));
+    } else if !config.quiet {
+        println!("{} Published {}", style("✓").green().bold(), args.key);
+        println!("  VK Account: {}", style(entry.vk_account).cyan());
+        println!("  VK Hash:    {}", hex(&entry.vk_hash));
+        println!("  Registry:   {}", args.file.registry.display());
+        println!();
+        println!("Use it for verification:");
+        println!(
+            "  noir-solana verify --vk-account $(noir-solana registry resolve {})",
+            args.key
+        );
+    }
+    Ok(())
+}
+
+fn list(config: &Config, args: ListArgs) -> Result<()> {
+    if let Some(registry_program) = args.file.registry_program {
+        let entries = registry_client(config, registry_program, false)?.list_registry_entries()?;
+        if config.json_output {
+            let entries: Vec<_> = entries.iter().map(on_chain_json).collect();
+            print_json(true, json!({ "entries": entries }));
+        } else if entries.is_empty() {
+            if !config.quiet {
+                println!("No VKs in registry program {}", registry_program);
+            }
+        } else {
+            for entry in &entries {
+                let status = match entry.deprecated_slot {
+                    Some(_) => style("deprecated").red().to_string(),
+                    None => String::new(),
+                };
+                println!(
+                    "{:<24} {} {}",
+                    style(entry.key()).bold(),
+                    entry.vk_account,
+                    status
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let registry = args.file.open()?;
+
+    if config.json_output {
+        let entries: Vec<_> = registry
+            .entries()
+            .map(|(key, entry)| entry_json(key, entry))
+            .collect();
+        print_json(true, json!({ "entries": entries }));
+    } else if registry.entries().next().is_none() {
+        if !config.quiet {
+            println!("No VKs in {}", args.file.registry.display());
+        }
+    } else {
+        for (key, entry) in registry.entries() {
+            println!("{:<24} {}", style(key).bold(), entry.vk_account);
+        }
+    }
+    Ok(())
+}
+
+fn show(config: &Config, args: KeyArgs) -> Result<()> {
+    if let Some(registry_program) = args.file.registry_program {
+        return show_on_chain(config, registry_program, &args.key);
+    }
+
+    let registry = args.file.open()?;
+    let entry = *registry.resolve(&args.key)?;
+    let cached = registry.vk_bytes(&args.key).ok();
+
+    let program_id = config.require_program_id()?;
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id));
+    let on_chain = verifier.resolve_vk(&registry, &args.key);
+
+    if config.json_output {
+        let mut body = entry_json(&args.key, &entry);
+        body["cached"] = cached.is_some().into();
+        body["on_chain"] = on_chain.is_ok().into();
+        print_json(true, body);
+        return Ok(());
+    }
+
+    println!("{}", style(&args.key).bold());
+    println!("  VK Account: {}", entry.vk_account);
+    println!("  VK Hash:    {}", hex(&entry.vk_hash));
+    match &cached {
+        Some(vk) => println!("  Cached VK:  {} bytes", vk.len()),
+        None => println!("  Cached VK:  not in {}", args.file.cache_dir.display()),
+    }
+    match on_chain {
+        Ok(_) => println!("  On chain:   {}", style("yes").green()),
+        Err(e) => println!("  On chain:   {} ({})", style("no").red(), e),
+    }
+    Ok(())
+}
+
+fn resolve(config: &Config, args: KeyArgs) -> Result<()> {
+    if let Some(registry_program) = args.file.registry_program {
+        let verifier = registry_client(config, registry_program, true)?;
+        let vk_account = verifier.resolve_vk_on_chain(&args.key)?;
+        if config.json_output {
+            let entry = verifier
+                .fetch_registry_entry(&args.key)?
+                .context("Registry entry disappeared")?;
+            print_json(true, on_chain_json(&entry));
+        } else {
+            println!("{}", vk_account);
+        }
+        return Ok(());
+    }
+
+    let registry = args.file.open()?;
+    let program_id = config.require_program_id()?;
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id));
+    let vk_account = verifier.resolve_vk(&registry, &args.key)?;
+    let entry = registry.resolve(&args.key)?;
+
+    // Plain output, so the command can be used inside $(...)
+    if config.json_output {
+        print_json(true, entry_json(&args.key, entry));
+    } else {
+        println!("{}", vk_account);
+    }
+    Ok(())
+}
+
+fn show_on_chain(config: &Config, registry_program: Pubkey, key: &str) -> Result<()> {
+    let verifier = registry_client(config, registry_program, true)?;
+    let entry = verifier
+        .fetch_registry_entry(key)?
+        .with_context(|| format!("{} is not registered", key))?;
+    let resolved = verifier.resolve_vk_on_chain(key);
+
+    if config.json_output {
+        let mut body = on_chain_json(&entry);
+        body["on_chain"] = resolved.is_ok().into();
+        print_json(true, body);
+        return Ok(());
+    }
+
+    println!("{}", style(key).bold());
+    println!("  VK Account: {}", entry.vk_account);
+    println!("  VK Hash:    {}", hex(&entry.vk_hash));
+    println!("  Entry:      {}", entry.address);
+    println!(
+        "  Registered: slot {} by {}",
+        entry.registered_slot, entry.registered_by
+    );
+    if let Some(slot) = entry.deprecated_slot {
+        println!("  Deprecated: slot {}", style(slot).red());
+    }
+    match resolved {
+        Ok(_) => println!("  Resolves:   {}", style("yes").green()),
+        Err(e) => println!("  Resolves:   {} ({})", style("no").red(), e),
+    }
+    Ok(())
+}
+
+fn deprecate(config: &Config, args: DeprecateArgs) -> Result<()> {
+    let keypair = config.load_keypair()?;
+    let verifier = registry_client(config, args.registry_program, false)?;
+    let signature = verifier.deprecate_vk(&keypair, &args.key)?;
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({ "key": args.key, "signature": signature.to_string() }),
+        );
+    } else if !config.quiet {
+        println!("{} Deprecated {}", style("✓").green().bold(), args.key);
+        println!("  Signature: {}", signature);
+    }
+    Ok(())
+}
+
+fn transfer(config: &Config, args: TransferArgs) -> Result<()> {
+    let keypair = config.load_keypair()?;
+    let verifier = registry_client(config, args.registry_program, false)?;
+    let signature =
+        verifier.transfer_registry_authority(&keypair, &args.name, &args.new_authority)?;
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({
+                "name": args.name,
+                "new_authority": args.new_authority.to_string(),
+                "signature": signature.to_string(),
+            }),
+        );
+    } else if !config.quiet {
+        println!(
+            "{} Transferred {} to {}",
+            style("✓").green().bold(),
+            args.name,
+            args.new_authority
+        );
+        println!("  Signature: {}", signature);
+    }
+    Ok(())
+}
+
+fn on_chain_json(entry: &OnChainRegistryEntry) -> serde_json::Value {
+    json!({
+        "key": entry.key(),
+        "vk_account": entry.vk_account.to_string(),
+        "vk_hash": hex(&entry.vk_hash),
+        "entry": entry.address.to_string(),
+        "registered_by": entry.registered_by.to_string(),
+        "registered_slot": entry.registered_slot,
+        "registered_timestamp": entry.registered_timestamp,
+        "deprecated_slot": entry.deprecated_slot,
+    })
+}
+
+fn entry_json(key: &str, entry: &RegistryEntry) -> serde_json::Value {
+    json!({
+        "key": key,
+        "vk_account": entry.vk_account.to_string(),
+        "vk_hash": hex(&entry.vk_hash),
+    })
+}
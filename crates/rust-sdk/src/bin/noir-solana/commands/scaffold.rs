@@ -0,0 +1,184 @@
+//! Scaffold command - generate an integrator program gated on verifier receipts
+
+use crate::config::Config;
+use crate::output::print_json;
+use crate::CommonArgs;
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use console::style;
+use serde_json::json;
+use solana_noir_verifier_sdk::SolanaNoirVerifier;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair, Signer},
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const NATIVE_CARGO: &str = include_str!("../templates/native/Cargo.toml.tmpl");
+const NATIVE_LIB: &str = include_str!("../templates/native/lib.rs.tmpl");
+const ANCHOR_CARGO: &str = include_str!("../templates/anchor/Cargo.toml.tmpl");
+const ANCHOR_LIB: &str = include_str!("../templates/anchor/lib.rs.tmpl");
+const README: &str = include_str!("../templates/README.md.tmpl");
+
+/// Public inputs the generated Anchor test derives a receipt address for
+const TEST_PUBLIC_INPUTS: [u8; 32] = [7; 32];
+
+const CPI_GIT: &str = "https://github.com/nicolaslara/solana-noir-verifier";
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Framework {
+    Native,
+    Anchor,
+}
+
+#[derive(Args)]
+pub struct ScaffoldArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Crate name of the generated program
+    name: String,
+
+    /// VK account public key or profile alias the program accepts receipts for
+    #[arg(long)]
+    vk_account: String,
+
+    /// Program framework
+    #[arg(long, value_enum, default_value = "native")]
+    framework: Framework,
+
+    /// Output directory (default: ./<name>)
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Program ID for `declare_id!` (default: a new keypair written to
+    /// target/deploy)
+    #[arg(long)]
+    declare_id: Option<String>,
+
+    /// Depend on a local checkout of solana-noir-verifier-cpi instead of git
+    #[arg(long)]
+    cpi_path: Option<PathBuf>,
+}
+
+pub fn run(config: &Config, args: ScaffoldArgs) -> Result<()> {
+    let verifier_program = config.require_program_id()?;
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+    let lib_name = args.name.replace('-', "_");
+    if lib_name.is_empty()
+        || lib_name.starts_with(|c: char| c.is_ascii_digit())
+        || !lib_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        anyhow::bail!("Invalid crate name: {:?}", args.name);
+    }
+
+    let out = args.out.unwrap_or_else(|| PathBuf::from(&args.name));
+    if out.exists() && fs::read_dir(&out)?.next().is_some() {
+        anyhow::bail!("Output directory {:?} is not empty", out);
+    }
+
+    let (program_id, keypair) = match &args.declare_id {
+        Some(id) => (Pubkey::from_str(id).context("Invalid --declare-id")?, None),
+        None => {
+            let keypair = Keypair::new();
+            (keypair.pubkey(), Some(keypair))
+        }
+    };
+    let keypair_path = format!("target/deploy/{}-keypair.json", lib_name);
+
+    let cpi_dependency = match &args.cpi_path {
+        Some(path) => {
+            let path = fs::canonicalize(path)
+                .with_context(|| format!("CPI crate not found: {:?}", path))?;
+            format!("{{ path = {:?} }}", path)
+        }
+        None => format!("{{ git = {:?} }}", CPI_GIT),
+    };
+    // A throwaway client: deriving the PDA needs no RPC
+    let verifier = SolanaNoirVerifier::new(
+        config.rpc_client(),
+        config.verifier_config(verifier_program),
+    );
+    let expected_receipt = verifier
+        .derive_receipt_pda(&vk_account, &TEST_PUBLIC_INPUTS)
+        .0;
+
+    let (build_command, files) = match args.framework {
+        Framework::Native => (
+            "cargo build-sbf",
+            [("Cargo.toml", NATIVE_CARGO), ("src/lib.rs", NATIVE_LIB)],
+        ),
+        Framework::Anchor => (
+            "anchor build   # from an Anchor workspace, with this crate under programs/",
+            [("Cargo.toml", ANCHOR_CARGO), ("src/lib.rs", ANCHOR_LIB)],
+        ),
+    };
+    let keypair_note = if keypair.is_some() {
+        format!(", whose keypair is in `{}`", keypair_path)
+    } else {
+        String::new()
+    };
+    let render = |template: &str| {
+        template
+            .replace("{{crate_name}}", &args.name)
+            .replace("{{lib_name}}", &lib_name)
+            .replace("{{program_id}}", &program_id.to_string())
+            .replace("{{verifier_program}}", &verifier_program.to_string())
+            .replace("{{vk_account}}", &vk_account.to_string())
+            .replace("{{cpi_dependency}}", &cpi_dependency)
+            .replace("{{expected_receipt}}", &expected_receipt.to_string())
+            .replace("{{build_command}}", build_command)
+            .replace("{{keypair_note}}", &keypair_note)
+    };
+
+    for (path, template) in files.into_iter().chain([("README.md", README)]) {
+        write_file(&out.join(path), &render(template))?;
+    }
+    if let Some(keypair) = &keypair {
+        let path = out.join(&keypair_path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        write_keypair_file(keypair, &path)
+            .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", path, e))?;
+    }
+    write_file(&out.join(".gitignore"), "/target\n")?;
+
+    if config.json_output {
+        print_json(
+            true,
+            json!({
+                "path": out,
+                "program_id": program_id.to_string(),
+                "verifier_program": verifier_program.to_string(),
+                "vk_account": vk_account.to_string(),
+            }),
+        );
+    } else if !config.quiet {
+        println!(
+            "{} Created {} program {} in {}",
+            style("✓").green().bold(),
+            match args.framework {
+                Framework::Native => "native",
+                Framework::Anchor => "Anchor",
+            },
+            style(&args.name).bold(),
+            out.display()
+        );
+        println!("  Program ID: {}", program_id);
+        println!("  Verifier:   {}", verifier_program);
+        println!("  VK account: {}", vk_account);
+        println!();
+        println!("  cd {} && cargo test", out.display());
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write {:?}", path))
+}
@@ -0,0 +1,114 @@
+//! Simulate command - dry-run a verification without paying fees
+
+use crate::config::Config;
+use crate::output::{print_json, Reported};
+use crate::{CommonArgs, PublicInputsArgs};
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use serde_json::json;
+use solana_noir_verifier_sdk::SolanaNoirVerifier;
+use solana_sdk::signature::Signer;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Path to the proof file
+    #[arg(long)]
+    proof: PathBuf,
+
+    #[command(flatten)]
+    public_inputs: PublicInputsArgs,
+
+    /// VK account public key or profile alias
+    #[arg(long)]
+    vk_account: String,
+
+    /// Print program logs of simulated transactions
+    #[arg(long)]
+    logs: bool,
+}
+
+pub fn run(config: &Config, args: SimulateArgs) -> Result<()> {
+    let proof_bytes = fs::read(&args.proof)
+        .with_context(|| format!("Failed to read proof file: {:?}", args.proof))?;
+    let pi_bytes = args.public_inputs.read()?;
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+
+    if !config.quiet && !config.json_output {
+        println!(
+            "{} Simulating verification on {}...",
+            style("→").cyan().bold(),
+            config.rpc_url
+        );
+    }
+
+    let program_id = config.require_program_id()?;
+    let payer = config.load_keypair()?.pubkey();
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id));
+    let report = verifier.simulate(&payer, &proof_bytes, &pi_bytes, &vk_account)?;
+
+    if config.json_output {
+        let steps: Vec<_> = report
+            .steps
+            .iter()
+            .map(|step| {
+                json!({
+                    "name": step.name,
+                    "on_chain": step.on_chain,
+                    "compute_units": step.compute_units,
+                    "error": step.error,
+                    "logs": step.logs,
+                })
+            })
+            .collect();
+        print_json(
+            report.passed(),
+            json!({ "passed": report.passed(), "steps": steps }),
+        );
+    } else if !config.quiet {
+        println!();
+        for step in &report.steps {
+            let mark = if step.error.is_some() {
+                style("✗").red().bold()
+            } else {
+                style("✓").green().bold()
+            };
+            let source = if step.on_chain {
+                "simulated"
+            } else {
+                "estimated"
+            };
+            println!(
+                "  {} {:<40} {:>10} CUs ({})",
+                mark, step.name, step.compute_units, source
+            );
+            if let Some(error) = &step.error {
+                println!("      {}", style(error).red());
+            }
+            if args.logs || step.error.is_some() {
+                for line in &step.logs {
+                    println!("      {}", style(line).dim());
+                }
+            }
+        }
+        println!();
+        match report.first_failure() {
+            None => println!("{} Verification would succeed", style("✓").green().bold()),
+            Some(step) => println!(
+                "{} Verification would fail at {}",
+                style("✗").red().bold(),
+                step.name
+            ),
+        }
+    }
+
+    if !report.passed() {
+        return Err(Reported::verification_failed("simulation failed"));
+    }
+    Ok(())
+}
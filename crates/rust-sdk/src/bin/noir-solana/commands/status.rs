@@ -1,13 +1,19 @@
 //! Status command - check verification state
 
 use crate::config::Config;
+use crate::output::{print_json, Reported};
 use crate::CommonArgs;
 use anyhow::{Context, Result};
 use clap::Args;
-use console::style;
-use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerificationPhase, VerifierConfig};
+use console::{style, Term};
+use serde_json::json;
+use solana_noir_verifier_sdk::{
+    SolanaNoirVerifier, StageProgress, VerificationPhase, VerificationState, VerificationStateView,
+};
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 #[derive(Args)]
 pub struct StatusArgs {
@@ -17,6 +23,14 @@ pub struct StatusArgs {
     /// State account public key
     #[arg(long)]
     state_account: String,
+
+    /// Keep polling and redraw the progress until verification completes or fails
+    #[arg(long)]
+    follow: bool,
+
+    /// Poll interval for --follow, in milliseconds
+    #[arg(long, default_value_t = 1000, requires = "follow")]
+    interval_ms: u64,
 }
 
 pub fn run(config: &Config, args: StatusArgs) -> Result<()> {
@@ -34,23 +48,74 @@ pub fn run(config: &Config, args: StatusArgs) -> Result<()> {
     let program_id = config.require_program_id()?;
     let client = config.rpc_client();
 
-    let verifier = SolanaNoirVerifier::new(client, VerifierConfig::new(program_id));
+    let verifier = SolanaNoirVerifier::new(client, config.verifier_config(program_id));
 
     // Get verification state
-    let state = verifier.get_verification_state(&state_account)?;
+    let mut view = verifier.get_verification_state(&state_account)?;
 
+    if args.follow {
+        // With --output json only the final state is printed
+        let live = !config.quiet && !config.json_output;
+        let term = Term::stdout();
+        let mut drawn: Option<Vec<String>> = None;
+        loop {
+            if live {
+                let lines = progress_lines(&view.status);
+                if drawn.as_ref() != Some(&lines) {
+                    // Redraw in place on a terminal, append otherwise
+                    match drawn.as_ref().filter(|_| term.is_term()) {
+                        Some(previous) => term.clear_last_lines(previous.len())?,
+                        None => println!(),
+                    }
+                    for line in &lines {
+                        term.write_line(line)?;
+                    }
+                    drawn = Some(lines);
+                }
+            }
+            if view.status.is_finished() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(args.interval_ms));
+            view = verifier.get_verification_state(&state_account)?;
+        }
+    }
+
+    let state = &view.status;
     let is_complete = state.phase == VerificationPhase::Verified;
     let is_failed = state.phase == VerificationPhase::Failed;
 
     if config.json_output {
-        println!(
-            r#"{{"phase": {:?}, "complete": {}, "failed": {}, "verified": {}}}"#,
-            state.phase, is_complete, is_failed, state.verified
+        print_json(
+            !(args.follow && is_failed),
+            json!({
+                "phase": format!("{:?}", state.phase),
+                "complete": is_complete,
+                "failed": is_failed,
+                "verified": state.verified,
+                "vk_account": view.vk_account.to_string(),
+                "proof_buffer": view.proof_buffer.to_string(),
+                "stages": state.progress().iter().map(|stage| json!({
+                    "name": stage.name,
+                    "done": stage.done,
+                    "total": stage.total,
+                })).collect::<Vec<_>>(),
+                "challenges": challenges_json(&view),
+            }),
         );
     } else if !config.quiet {
         println!();
         println!("  State Account: {}", state_account);
         println!("  Current Phase: {:?}", state.phase);
+        if state.raw_phase != 0 {
+            println!("  VK Account:    {}", view.vk_account);
+            println!("  Proof Buffer:  {}", view.proof_buffer);
+        }
+        if !args.follow {
+            for line in progress_lines(state) {
+                println!("{}", line);
+            }
+        }
 
         if is_complete {
             println!("  Status: {}", style("Complete ✓").green());
@@ -61,5 +126,52 @@ pub fn run(config: &Config, args: StatusArgs) -> Result<()> {
         }
     }
 
+    if args.follow && is_failed {
+        return Err(Reported::verification_failed(
+            "verification failed on chain",
+        ));
+    }
     Ok(())
 }
+
+/// One line per stage: done, running (the first stage not done) or pending
+fn progress_lines(state: &VerificationState) -> Vec<String> {
+    let stages = state.progress();
+    let current = stages.iter().position(|stage| !stage.is_complete());
+    let failed = state.phase == VerificationPhase::Failed;
+    stages
+        .iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            let mark = if stage.is_complete() {
+                style("✓").green()
+            } else if Some(i) == current && failed {
+                style("✗").red()
+            } else if Some(i) == current {
+                style("…").yellow()
+            } else {
+                style("·").dim()
+            };
+            format!("  {} {:<24} {}", mark, stage.name, count(stage))
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// The stored challenges as `0x` hex, `null` until Phase 1 has sealed them
+fn challenges_json(view: &VerificationStateView) -> serde_json::Value {
+    view.challenges
+        .as_ref()
+        .and_then(|set| serde_json::from_str(&set.to_json()).ok())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// `done/total`, left out for single-step stages and unknown sizes
+fn count(stage: &StageProgress) -> String {
+    if stage.total <= 1 {
+        String::new()
+    } else {
+        format!("{}/{}", stage.done, stage.total)
+    }
+}
@@ -1,12 +1,14 @@
 //! Upload VK command - upload a verification key to the chain
 
 use crate::config::Config;
-use crate::CommonArgs;
+use crate::output::print_json;
+use crate::{CommonArgs, FeeArgs};
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
-use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerifierConfig};
+use serde_json::json;
+use solana_noir_verifier_sdk::SolanaNoirVerifier;
 use std::fs;
 use std::path::PathBuf;
 
@@ -15,6 +17,9 @@ pub struct UploadVkArgs {
     #[command(flatten)]
     pub common: CommonArgs,
 
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
     /// Path to the verification key file
     #[arg(long)]
     vk: PathBuf,
@@ -25,7 +30,7 @@ pub fn run(config: &Config, args: UploadVkArgs) -> Result<()> {
     let vk_bytes =
         fs::read(&args.vk).with_context(|| format!("Failed to read VK file: {:?}", args.vk))?;
 
-    if !config.quiet {
+    if !config.quiet && !config.json_output {
         println!(
             "{} Uploading VK ({} bytes) to {}...",
             style("→").cyan().bold(),
@@ -39,7 +44,7 @@ pub fn run(config: &Config, args: UploadVkArgs) -> Result<()> {
     let keypair = config.load_keypair()?;
     let client = config.rpc_client();
 
-    let verifier = SolanaNoirVerifier::new(client, VerifierConfig::new(program_id));
+    let verifier = SolanaNoirVerifier::new(client, config.verifier_config(program_id));
 
     // Progress bar
     let pb = if !config.quiet && !config.json_output {
@@ -64,15 +69,17 @@ pub fn run(config: &Config, args: UploadVkArgs) -> Result<()> {
     }
 
     if config.json_output {
-        println!(
-            r#"{{"vk_account": "{}", "chunks": {}, "signatures": {:?}}}"#,
-            result.vk_account,
-            result.num_chunks,
-            result
-                .signatures
-                .iter()
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>()
+        print_json(
+            true,
+            json!({
+                "vk_account": result.vk_account.to_string(),
+                "chunks": result.num_chunks,
+                "signatures": result
+                    .signatures
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>(),
+            }),
         );
     } else if !config.quiet {
         println!("{} VK uploaded successfully!", style("✓").green().bold());
@@ -1,33 +1,53 @@
 //! Verify command - verify a proof on-chain
 
 use crate::config::Config;
-use crate::CommonArgs;
+use crate::output::{print_json, Reported};
+use crate::{CommonArgs, FeeArgs, PublicInputsArgs};
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
-use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerifierConfig, VerifyOptions};
-use solana_sdk::pubkey::Pubkey;
+use plonk_solana_core::proof::Proof;
+use serde_json::json;
+use solana_noir_verifier_sdk::{
+    verify_locally, CleanupPolicy, ProgressObserver, SolanaNoirVerifier, VerificationSession,
+    VerifierError, VerifyOptions,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer};
 use std::fs;
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
 
 #[derive(Args)]
 pub struct VerifyArgs {
     #[command(flatten)]
     pub common: CommonArgs,
 
+    #[command(flatten)]
+    pub fees: FeeArgs,
+
     /// Path to the proof file
     #[arg(long)]
     proof: PathBuf,
 
-    /// Path to the public inputs file
-    #[arg(long)]
-    public_inputs: PathBuf,
+    #[command(flatten)]
+    public_inputs: PublicInputsArgs,
+
+    /// VK account public key or profile alias
+    #[arg(long, required_unless_present = "vk")]
+    vk_account: Option<String>,
 
-    /// VK account public key
+    /// Verify off-chain with the host backend instead of on-chain
     #[arg(long)]
-    vk_account: String,
+    local: bool,
+
+    /// Path to the VK file for --local (no RPC needed; otherwise the VK is
+    /// read from --vk-account)
+    #[arg(long, requires = "local")]
+    vk: Option<PathBuf>,
 
     /// Skip preflight simulation (faster but less safe)
     #[arg(long)]
@@ -36,28 +56,221 @@ pub struct VerifyArgs {
     /// Don't close accounts after verification (keep state for debugging)
     #[arg(long)]
     no_close: bool,
+
+    /// Start even if the keypair's balance looks too low for the verification
+    #[arg(long)]
+    skip_balance_check: bool,
+
+    /// Checkpoint file for --resume, removed once the accounts are closed
+    #[arg(long, default_value = "verify.session", conflicts_with = "local")]
+    session: PathBuf,
+
+    /// Continue the verification checkpointed in this session file instead
+    /// of starting over
+    #[arg(long, conflicts_with_all = ["local", "session"])]
+    resume: Option<PathBuf>,
 }
 
 pub fn run(config: &Config, args: VerifyArgs) -> Result<()> {
     // Load proof and public inputs
     let proof_bytes = fs::read(&args.proof)
         .with_context(|| format!("Failed to read proof file: {:?}", args.proof))?;
-    let pi_bytes = fs::read(&args.public_inputs).with_context(|| {
-        format!(
-            "Failed to read public inputs file: {:?}",
-            args.public_inputs
-        )
-    })?;
+    let pi_bytes = args.public_inputs.read()?;
+
+    if args.local {
+        return verify_local(config, &args, &proof_bytes, &pi_bytes);
+    }
+    let vk_account = config.resolve_vk(args.vk_account.as_deref().unwrap_or_default())?;
+    if let Some(path) = &args.resume {
+        return resume_proof(config, &args, path, &proof_bytes, &pi_bytes, &vk_account);
+    }
+
+    let options = VerifyOptions {
+        skip_preflight: args.skip_preflight,
+        auto_close: !args.no_close,
+        ..VerifyOptions::default()
+    };
+    verify_proof(
+        config,
+        &proof_bytes,
+        &pi_bytes,
+        &vk_account,
+        options,
+        Some(&args.session),
+        !args.skip_balance_check,
+    )
+}
+
+/// Finish a verification from its session file
+///
+/// Chunks and phases already on chain are skipped, so the proof buffer and
+/// state account of the interrupted run are reused rather than paid for
+/// again. The run ends like `verify_phased`, with a receipt.
+fn resume_proof(
+    config: &Config,
+    args: &VerifyArgs,
+    session_path: &Path,
+    proof_bytes: &[u8],
+    pi_bytes: &[u8],
+    vk_account: &Pubkey,
+) -> Result<()> {
+    let program_id = config.require_program_id()?;
+    let keypair = config.load_keypair()?;
 
-    let vk_account = Pubkey::from_str(&args.vk_account).context("Invalid VK account public key")?;
+    let pb = spinner(config);
+    let mut verifier_config = config
+        .verifier_config(program_id)
+        .with_session_file(session_path);
+    if args.no_close {
+        verifier_config = verifier_config.with_cleanup(CleanupPolicy::Keep);
+    }
+    if let Some(pb) = &pb {
+        verifier_config =
+            verifier_config.with_progress_observer(Arc::new(SpinnerProgress(pb.clone())));
+    }
+    let verifier = SolanaNoirVerifier::new(config.rpc_client(), verifier_config);
+
+    let session = VerificationSession::load(&verifier, session_path)
+        .with_context(|| format!("Failed to load session: {:?}", session_path))?;
+    if let Some(pb) = &pb {
+        pb.println(format!(
+            "{} Resuming verification on {}",
+            style("→").cyan().bold(),
+            config.rpc_url
+        ));
+        pb.println(format!("  Proof Account: {}", session.proof_account()));
+        pb.println(format!("  State Account: {}", session.state_account()));
+        pb.println(format!(
+            "  Missing chunks: {}, last phase: {:?}",
+            session.missing_chunks().len(),
+            session.phase()
+        ));
+    }
+
+    let result = session.resume(&keypair, None, proof_bytes, pi_bytes, vk_account);
+    if let Some(pb) = &pb {
+        pb.finish_and_clear();
+    }
 
-    if !config.quiet {
+    match result {
+        Ok(receipt) => {
+            if config.json_output {
+                print_json(
+                    true,
+                    json!({
+                        "verified": true,
+                        "resumed": true,
+                        "receipt_pda": receipt.receipt.receipt_pda.to_string(),
+                        "total_cus": receipt.total_cus,
+                        "num_transactions": receipt.signatures.len(),
+                        "state_account": receipt.state_account.to_string(),
+                        "proof_account": receipt.proof_account.to_string(),
+                        "recovered_lamports": receipt.recovered_lamports,
+                    }),
+                );
+            } else if !config.quiet {
+                println!("{} Proof verified successfully!", style("✓").green().bold());
+                println!();
+                println!("  Transactions: {}", receipt.signatures.len());
+                println!("  Total CUs: {}", receipt.total_cus);
+                println!("  Receipt PDA: {}", receipt.receipt.receipt_pda);
+                if let Some(lamports) = receipt.recovered_lamports {
+                    println!();
+                    println!(
+                        "  {} Accounts closed, {} lamports reclaimed",
+                        style("→").dim(),
+                        lamports
+                    );
+                }
+            }
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Verify with plonk-core's host backend and report pass/fail and timing
+fn verify_local(
+    config: &Config,
+    args: &VerifyArgs,
+    proof_bytes: &[u8],
+    pi_bytes: &[u8],
+) -> Result<()> {
+    if !config.quiet && !config.json_output {
+        println!("{} Verifying proof locally...", style("→").cyan().bold());
+    }
+
+    let started = Instant::now();
+    let result = match (&args.vk, &args.vk_account) {
+        (Some(path), _) => {
+            let vk_bytes =
+                fs::read(path).with_context(|| format!("Failed to read VK file: {:?}", path))?;
+            verify_locally(&vk_bytes, proof_bytes, pi_bytes)
+        }
+        (None, Some(vk_account)) => {
+            let vk_account = config.resolve_vk(vk_account)?;
+            let program_id = config.program_id.unwrap_or_default();
+            SolanaNoirVerifier::new(config.rpc_client(), config.verifier_config(program_id))
+                .verify_locally_with_account(&vk_account, proof_bytes, pi_bytes)
+        }
+        (None, None) => unreachable!("clap requires --vk or --vk-account"),
+    };
+    let elapsed = started.elapsed();
+
+    if config.json_output {
+        if result.is_ok() {
+            print_json(
+                true,
+                json!({
+                    "verified": true,
+                    "local": true,
+                    "millis": elapsed.as_millis() as u64,
+                }),
+            );
+        }
+    } else if !config.quiet {
+        match &result {
+            Ok(()) => println!(
+                "{} Proof verified locally in {:.2}s",
+                style("✓").green().bold(),
+                elapsed.as_secs_f64()
+            ),
+            Err(_) => println!(
+                "{} Proof rejected locally after {:.2}s",
+                style("✗").red().bold(),
+                elapsed.as_secs_f64()
+            ),
+        }
+    }
+    result.map_err(Into::into)
+}
+
+/// Verify proof bytes on-chain and report the result
+///
+/// Shared with `prove`, which verifies the artifacts it just generated.
+/// With `check_balance`, a payer who can't cover the estimated cost is
+/// turned away before the first transaction.
+pub fn verify_proof(
+    config: &Config,
+    proof_bytes: &[u8],
+    pi_bytes: &[u8],
+    vk_account: &Pubkey,
+    options: VerifyOptions,
+    session: Option<&Path>,
+    check_balance: bool,
+) -> Result<()> {
+    if !config.quiet && !config.json_output {
         println!(
             "{} Verifying proof on {}...",
             style("→").cyan().bold(),
             config.rpc_url
         );
-        println!("  Proof: {} bytes", proof_bytes.len());
+        let format = match Proof::detect_format(proof_bytes) {
+            Ok((true, _)) => "ZK",
+            Ok((false, _)) => "non-ZK",
+            Err(_) => "unrecognized",
+        };
+        println!("  Proof: {} bytes ({})", proof_bytes.len(), format);
         println!("  Public inputs: {} bytes", pi_bytes.len());
         println!("  VK Account: {}", vk_account);
         println!();
@@ -68,37 +281,30 @@ pub fn run(config: &Config, args: VerifyArgs) -> Result<()> {
     let keypair = config.load_keypair()?;
     let client = config.rpc_client();
 
-    let verifier = SolanaNoirVerifier::new(client, VerifierConfig::new(program_id));
-
-    // Progress bar for phases
-    let pb = if !config.quiet && !config.json_output {
-        let pb = ProgressBar::new(9); // 9 transactions
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} TXs - {msg}")
-                .unwrap()
-                .progress_chars("█▓░"),
-        );
-        pb.set_message("Starting verification...");
-        Some(pb)
-    } else {
-        None
-    };
+    let pb = spinner(config);
+    let mut verifier_config = config.verifier_config(program_id);
+    if let Some(path) = session {
+        verifier_config = verifier_config.with_session_file(path);
+    }
+    if let Some(pb) = &pb {
+        verifier_config =
+            verifier_config.with_progress_observer(Arc::new(SpinnerProgress(pb.clone())));
+    }
+    let verifier = SolanaNoirVerifier::new(client, verifier_config);
 
-    // Verification options
-    let options = VerifyOptions {
-        skip_preflight: args.skip_preflight,
-        auto_close: !args.no_close,
-    };
+    if check_balance {
+        if let Err(e) = verifier.check_balance(&keypair.pubkey(), vk_account, proof_bytes, pi_bytes)
+        {
+            if let Some(pb) = &pb {
+                pb.finish_and_clear();
+            }
+            return Err(insufficient_funds(config, &keypair.pubkey(), e));
+        }
+    }
 
     // Run verification
-    let result = verifier.verify(
-        &keypair,
-        &proof_bytes,
-        &pi_bytes,
-        &vk_account,
-        Some(options),
-    );
+    let auto_close = options.auto_close;
+    let result = verifier.verify(&keypair, proof_bytes, pi_bytes, vk_account, Some(options));
 
     if let Some(pb) = &pb {
         pb.finish_and_clear();
@@ -107,13 +313,16 @@ pub fn run(config: &Config, args: VerifyArgs) -> Result<()> {
     match result {
         Ok(result) => {
             if config.json_output {
-                println!(
-                    r#"{{"verified": {}, "total_cus": {}, "num_transactions": {}, "state_account": "{}", "proof_account": "{}"}}"#,
+                print_json(
                     result.verified,
-                    result.total_cus,
-                    result.num_transactions,
-                    result.state_account,
-                    result.proof_account
+                    json!({
+                        "verified": result.verified,
+                        "total_cus": result.total_cus,
+                        "num_transactions": result.num_transactions,
+                        "state_account": result.state_account.to_string(),
+                        "proof_account": result.proof_account.to_string(),
+                        "recovered_lamports": result.recovered_lamports,
+                    }),
                 );
             } else if !config.quiet {
                 if result.verified {
@@ -127,18 +336,78 @@ pub fn run(config: &Config, args: VerifyArgs) -> Result<()> {
                 println!("  State Account: {}", result.state_account);
                 println!("  Proof Account: {}", result.proof_account);
 
-                if !args.no_close {
+                if auto_close {
                     println!();
                     println!("  {} Accounts closed, rent reclaimed", style("→").dim());
                 }
             }
+            if !result.verified {
+                return Err(Reported::verification_failed("Proof verification failed"));
+            }
             Ok(())
         }
         Err(e) => {
-            if config.json_output {
-                println!(r#"{{"verified": false, "error": "{}"}}"#, e);
+            if let Some(path) = session.filter(|path| path.exists() && !config.json_output) {
+                eprintln!(
+                    "{} Progress saved; rerun with --resume {} to continue",
+                    style("→").cyan().bold(),
+                    path.display()
+                );
             }
             Err(e.into())
         }
     }
 }
+
+/// Say how much SOL is missing when the balance check fails
+fn insufficient_funds(config: &Config, payer: &Pubkey, error: VerifierError) -> anyhow::Error {
+    let VerifierError::InsufficientFunds { needed, available } = error else {
+        return error.into();
+    };
+    let sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL;
+    let mut message = format!(
+        "Need {:.6} more SOL: the verification needs about {:.6} SOL up front and {} has {:.6}",
+        sol(needed - available),
+        sol(needed),
+        payer,
+        sol(available)
+    );
+    if !config.rpc_url.contains("mainnet") {
+        message.push_str(" (run `noir-solana airdrop` to fund it)");
+    }
+    anyhow::Error::new(error).context(message)
+}
+
+/// Spinner driven by the verifier's progress events
+fn spinner(config: &Config) -> Option<ProgressBar> {
+    if config.quiet || config.json_output {
+        return None;
+    }
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {pos} TXs - {msg}")
+            .unwrap(),
+    );
+    pb.enable_steady_tick(Duration::from_millis(100));
+    pb.set_message("Starting verification...");
+    Some(pb)
+}
+
+/// Advances the spinner as transactions confirm
+struct SpinnerProgress(ProgressBar);
+
+impl ProgressObserver for SpinnerProgress {
+    fn phase_started(&self, phase: &str) {
+        self.0.set_message(phase.to_string());
+    }
+
+    fn transaction_confirmed(&self, _signature: &Signature) {
+        self.0.inc(1);
+    }
+
+    fn chunks_uploaded(&self, uploaded: usize, total: usize) {
+        self.0
+            .set_message(format!("Uploading proof: {}/{} chunks", uploaded, total));
+    }
+}
@@ -0,0 +1,113 @@
+//! Watch command - stream new receipts of a VK as JSON lines
+
+use super::inspect::hex;
+use crate::config::Config;
+use crate::output::print_json;
+use crate::CommonArgs;
+use anyhow::Result;
+use clap::Args;
+use console::style;
+use serde_json::json;
+use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerifierError, WatchedReceipt};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+
+/// Wait before resubscribing after the websocket drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Args)]
+pub struct WatchArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// VK account public key or profile alias
+    #[arg(long)]
+    vk_account: String,
+
+    /// Websocket URL (default: derived from the RPC URL)
+    #[arg(long, env = "SOLANA_WS_URL")]
+    ws_url: Option<String>,
+
+    /// Print the receipts that already exist before watching
+    #[arg(long)]
+    existing: bool,
+
+    /// Stop after this many receipts
+    #[arg(long)]
+    count: Option<usize>,
+}
+
+pub fn run(config: &Config, args: WatchArgs) -> Result<()> {
+    let vk_account = config.resolve_vk(&args.vk_account)?;
+    let program_id = config.require_program_id()?;
+    let ws_url = args.ws_url.unwrap_or_else(|| config.websocket_url());
+    let verifier = SolanaNoirVerifier::new(
+        config.rpc_client(),
+        config
+            .verifier_config(program_id)
+            .with_websocket_url(&ws_url),
+    );
+
+    // Receipts are keyed by slot too: a closed receipt can be created again
+    let mut seen = HashSet::new();
+    let mut remaining = args.count.unwrap_or(usize::MAX);
+    let mut emit = |receipt: WatchedReceipt| {
+        if seen.insert((receipt.receipt.receipt_pda, receipt.receipt.verified_slot)) {
+            print_receipt(&vk_account, &receipt);
+            remaining -= 1;
+        }
+        remaining > 0
+    };
+
+    if args.existing {
+        for receipt in verifier.find_receipts(&vk_account)? {
+            if !emit(receipt) {
+                return Ok(());
+            }
+        }
+    }
+
+    if !config.quiet {
+        eprintln!(
+            "{} Watching receipts of {} on {}",
+            style("→").cyan().bold(),
+            vk_account,
+            ws_url
+        );
+    }
+    loop {
+        match verifier.watch_receipts(&vk_account, &mut emit) {
+            Ok(()) => return Ok(()),
+            Err(VerifierError::Websocket(e)) => {
+                if !config.quiet {
+                    eprintln!(
+                        "{} {}; reconnecting in {}s",
+                        style("✗").yellow().bold(),
+                        e,
+                        RECONNECT_DELAY.as_secs()
+                    );
+                }
+                thread::sleep(RECONNECT_DELAY);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// One JSON object per line, whatever `--output` says
+fn print_receipt(vk_account: &Pubkey, watched: &WatchedReceipt) {
+    let receipt = &watched.receipt;
+    print_json(
+        true,
+        json!({
+            "receipt": receipt.receipt_pda.to_string(),
+            "vk_account": vk_account.to_string(),
+            "public_inputs_hash": format!("0x{}", hex(&watched.public_inputs_hash)),
+            "verified_slot": receipt.verified_slot,
+            "verified_timestamp": receipt.verified_timestamp,
+            "rent_payer": receipt.rent_payer.to_string(),
+        }),
+    );
+}
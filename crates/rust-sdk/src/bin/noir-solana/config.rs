@@ -1,22 +1,32 @@
 //! Configuration handling for noir-solana CLI
 //!
-//! Priority: CLI flags > environment variables > config file > defaults
+//! Priority: CLI flags > environment variables > profile > config file >
+//! defaults. The profile is `--profile`, or `default.profile` in the file.
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_commitment_config::CommitmentConfig;
+use solana_noir_verifier_sdk::{FeeConfig, VerifierConfig};
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{read_keypair_file, Keypair},
 };
-use std::{collections::HashMap, fs, path::PathBuf, str::FromStr, sync::Arc};
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr, sync::Arc};
 
 /// Resolved configuration for CLI commands
 pub struct Config {
     pub rpc_url: String,
     pub keypair_path: Option<PathBuf>,
     pub program_id: Option<Pubkey>,
+    /// Priority fee from `--priority-fee` or the profile
+    pub priority_fee: Option<PriorityFee>,
+    /// Phase transaction CU limit from `--cu-limit`
+    pub cu_limit: Option<CuLimit>,
+    /// Phase transaction heap request from `--heap-frame`
+    pub heap_frame: Option<u32>,
+    /// VK account aliases from the active profile
+    pub vk_aliases: BTreeMap<String, String>,
     pub quiet: bool,
     pub json_output: bool,
 }
@@ -27,14 +37,50 @@ impl Config {
         // Try to load config file
         let file_config = ConfigFile::load().ok();
 
+        // Select the profile; naming one that does not exist is an error
+        let profile_name = common.profile.clone().or_else(|| {
+            file_config
+                .as_ref()
+                .and_then(|c| c.default.as_ref())
+                .and_then(|d| d.profile.clone())
+        });
+        let profile = match &profile_name {
+            Some(name) => Some(
+                file_config
+                    .as_ref()
+                    .and_then(|c| c.profiles.get(name))
+                    .cloned()
+                    .with_context(|| {
+                        format!(
+                            "Profile '{}' not found in {:?}",
+                            name,
+                            config_file_path().unwrap_or_default()
+                        )
+                    })?,
+            ),
+            None => None,
+        };
+
         // Resolve network to RPC URL
-        let rpc_url = resolve_network(&common.network, file_config.as_ref());
+        let network = common
+            .network
+            .clone()
+            .or_else(|| profile.as_ref().and_then(|p| p.network.clone()))
+            .or_else(|| file_config.as_ref().and_then(|c| c.default_network()))
+            .unwrap_or_else(|| "localnet".to_string());
+        let rpc_url = resolve_network(&network, file_config.as_ref());
 
         // Resolve keypair path
         let keypair_path = common
             .keypair
             .as_ref()
             .map(PathBuf::from)
+            .or_else(|| {
+                profile
+                    .as_ref()
+                    .and_then(|p| p.keypair.as_deref())
+                    .map(expand_tilde)
+            })
             .or_else(|| file_config.as_ref().and_then(|c| c.default_keypair()))
             .or_else(default_keypair_path);
 
@@ -43,16 +89,29 @@ impl Config {
             .program_id
             .as_ref()
             .and_then(|s| Pubkey::from_str(s).ok())
+            .or_else(|| {
+                profile
+                    .as_ref()
+                    .and_then(|p| p.program_id.as_ref())
+                    .and_then(|s| Pubkey::from_str(s).ok())
+            })
             .or_else(|| {
                 file_config
                     .as_ref()
-                    .and_then(|c| c.program_id_for_network(&common.network))
+                    .and_then(|c| c.program_id_for_network(&network))
             });
 
+        let (priority_fee, vk_aliases) =
+            profile.map(|p| (p.priority_fee, p.vks)).unwrap_or_default();
+
         Ok(Self {
             rpc_url,
             keypair_path,
             program_id,
+            priority_fee: priority_fee.map(PriorityFee::Fixed),
+            cu_limit: None,
+            heap_frame: None,
+            vk_aliases,
             quiet: common.quiet,
             json_output: common.output == super::OutputFormat::Json,
         })
@@ -66,6 +125,19 @@ impl Config {
         ))
     }
 
+    /// Websocket endpoint matching the RPC URL: `ws`/`wss` on the next
+    /// port for the validator's default 8899, otherwise the same host
+    pub fn websocket_url(&self) -> String {
+        let url = if let Some(rest) = self.rpc_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.rpc_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            self.rpc_url.clone()
+        };
+        url.replace(":8899", ":8900")
+    }
+
     /// Load keypair from configured path
     pub fn load_keypair(&self) -> Result<Keypair> {
         let path = self
@@ -83,30 +155,156 @@ impl Config {
             "No program ID configured. Use --program-id, set VERIFIER_PROGRAM_ID, or configure in ~/.config/noir-solana/config.toml"
         )
     }
+
+    /// Apply `--priority-fee`, `--cu-limit` and `--heap-frame` over the profile
+    pub fn with_fees(mut self, fees: &super::FeeArgs) -> Self {
+        self.priority_fee = fees.priority_fee.or(self.priority_fee);
+        self.cu_limit = fees.cu_limit.or(self.cu_limit);
+        self.heap_frame = fees.heap_frame.or(self.heap_frame);
+        self
+    }
+
+    /// SDK configuration for `program_id` with the priority fee, CU limit and
+    /// heap request
+    pub fn verifier_config(&self, program_id: Pubkey) -> VerifierConfig {
+        let mut config = VerifierConfig::new(program_id);
+        let mut fees = FeeConfig::default();
+        match self.priority_fee {
+            Some(PriorityFee::Fixed(fee)) => fees = fees.with_priority_fee(fee),
+            Some(PriorityFee::Auto) => {
+                fees =
+                    fees.with_dynamic_priority_fee(AUTO_FEE_PERCENTILE, AUTO_FEE_MAX_MICRO_LAMPORTS)
+            }
+            None => {}
+        }
+        match self.cu_limit {
+            Some(CuLimit::Fixed(limit)) => config = config.with_compute_unit_limit(limit),
+            Some(CuLimit::Auto) => fees = fees.with_estimated_cu_limits(),
+            None => {}
+        }
+        if let Some(bytes) = self.heap_frame {
+            fees = fees.with_heap_frame(bytes);
+        }
+        config.with_fees(fees)
+    }
+
+    /// Resolve a VK account given as a public key or a profile alias
+    pub fn resolve_vk(&self, vk: &str) -> Result<Pubkey> {
+        let address = self.vk_aliases.get(vk).map_or(vk, String::as_str);
+        Pubkey::from_str(address).with_context(|| {
+            format!(
+                "Invalid VK account '{}': not a public key or a VK alias of the profile",
+                vk
+            )
+        })
+    }
+}
+
+/// Percentile of recent prioritization fees paid with `--priority-fee auto`
+const AUTO_FEE_PERCENTILE: u8 = 75;
+
+/// Highest price `--priority-fee auto` pays, in micro-lamports per CU
+const AUTO_FEE_MAX_MICRO_LAMPORTS: u64 = 100_000;
+
+/// Most compute units a transaction can request
+const MAX_CU_LIMIT: u32 = 1_400_000;
+
+/// `--priority-fee`: micro-lamports per CU, or `auto`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityFee {
+    Fixed(u64),
+    /// A percentile of recent fees on the accounts each transaction writes
+    Auto,
+}
+
+impl FromStr for PriorityFee {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            _ => s
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|_| "expected micro-lamports per CU or `auto`".to_string()),
+        }
+    }
+}
+
+/// `--cu-limit`: one limit for every phase transaction, or `auto`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CuLimit {
+    Fixed(u32),
+    /// The cost-model estimate of each phase plus headroom
+    Auto,
+}
+
+impl FromStr for CuLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            _ => match s.parse() {
+                Ok(limit) if (1..=MAX_CU_LIMIT).contains(&limit) => Ok(Self::Fixed(limit)),
+                _ => Err(format!(
+                    "expected a compute unit limit up to {} or `auto`",
+                    MAX_CU_LIMIT
+                )),
+            },
+        }
+    }
 }
 
 /// Configuration file structure
-#[derive(Debug, Deserialize)]
-struct ConfigFile {
-    default: Option<DefaultConfig>,
-    networks: Option<HashMap<String, NetworkConfig>>,
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ConfigFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<DefaultConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    networks: Option<BTreeMap<String, NetworkConfig>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
 }
 
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct DefaultConfig {
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DefaultConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
     network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     keypair: Option<String>,
+    /// Profile used when `--profile` is not given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct NetworkConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
     rpc_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     program_id: Option<String>,
 }
 
+/// Named profile: `[profiles.<name>]`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keypair: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+    /// Micro-lamports per CU
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<u64>,
+    /// VK account aliases usable wherever a VK account is expected
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vks: BTreeMap<String, String>,
+}
+
 impl ConfigFile {
-    fn load() -> Result<Self> {
+    pub fn load() -> Result<Self> {
         let path = config_file_path()?;
         if !path.exists() {
             anyhow::bail!("Config file not found");
@@ -116,6 +314,21 @@ impl ConfigFile {
         Ok(config)
     }
 
+    /// Write the file back; comments in it are not preserved
+    pub fn save(&self) -> Result<PathBuf> {
+        let path = config_file_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(path)
+    }
+
+    fn default_network(&self) -> Option<String> {
+        self.default.as_ref().and_then(|d| d.network.clone())
+    }
+
     fn default_keypair(&self) -> Option<PathBuf> {
         self.default
             .as_ref()
@@ -165,7 +378,7 @@ fn resolve_network(network: &str, config: Option<&ConfigFile>) -> String {
 }
 
 /// Get config file path
-fn config_file_path() -> Result<PathBuf> {
+pub fn config_file_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir().context("Could not find config directory")?;
     Ok(config_dir.join("noir-solana").join("config.toml"))
 }
@@ -5,11 +5,17 @@
 
 mod commands;
 mod config;
+mod output;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use clap::{Args, Parser, Subcommand};
-use commands::{close, deploy, receipt, status, upload_vk, verify};
+use commands::{
+    airdrop, batch_verify, bench, close, deploy, estimate, export_client, inspect, prove, receipt,
+    registry, scaffold, simulate, status, upload_vk, verify, watch,
+};
 use console::style;
+use solana_noir_verifier_sdk::{NoirAbi, PublicInputs};
+use std::path::{Path, PathBuf};
 
 /// CLI for verifying Noir UltraHonk proofs on Solana
 #[derive(Parser)]
@@ -24,9 +30,10 @@ struct Cli {
 /// Common options shared across commands
 #[derive(Args, Clone)]
 pub struct CommonArgs {
-    /// Network to connect to (mainnet, devnet, localnet, or custom URL)
-    #[arg(short, long, env = "SOLANA_RPC_URL", default_value = "localnet")]
-    pub network: String,
+    /// Network to connect to (mainnet, devnet, localnet, or custom URL;
+    /// default: the profile's network, else localnet)
+    #[arg(short, long, env = "SOLANA_RPC_URL")]
+    pub network: Option<String>,
 
     /// Path to keypair file
     #[arg(short, long, env = "KEYPAIR_PATH")]
@@ -36,6 +43,10 @@ pub struct CommonArgs {
     #[arg(short, long, env = "VERIFIER_PROGRAM_ID")]
     pub program_id: Option<String>,
 
+    /// Named profile from ~/.config/noir-solana/config.toml
+    #[arg(long, env = "NOIR_SOLANA_PROFILE")]
+    pub profile: Option<String>,
+
     /// Output format (human, json)
     #[arg(long, default_value = "human")]
     pub output: OutputFormat,
@@ -45,6 +56,90 @@ pub struct CommonArgs {
     pub quiet: bool,
 }
 
+/// Compute budget options for commands that send transactions
+#[derive(Args, Clone)]
+pub struct FeeArgs {
+    /// Priority fee in micro-lamports per CU, or `auto` for the 75th
+    /// percentile of recent fees (default: the profile's, else none)
+    #[arg(long, env = "NOIR_SOLANA_PRIORITY_FEE")]
+    pub priority_fee: Option<config::PriorityFee>,
+
+    /// Compute unit limit of phase transactions, or `auto` for per-phase
+    /// estimates (default: 1400000)
+    #[arg(long)]
+    pub cu_limit: Option<config::CuLimit>,
+
+    /// Heap in bytes to request for phase transactions, for a verifier
+    /// built with `large-heap` (default: the runtime's 32 KB)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(32 * 1024..=256 * 1024))]
+    pub heap_frame: Option<u32>,
+}
+
+/// Public inputs file, for commands that take one
+#[derive(Args, Clone)]
+pub struct PublicInputsArgs {
+    /// Public inputs: the binary file `bb prove` writes, or a `.toml` or
+    /// `.json` input map such as nargo's Prover.toml
+    #[arg(long)]
+    pub public_inputs: PathBuf,
+
+    /// Program artifact whose ABI lays out a TOML or JSON input map
+    /// (default: the only `.json` in target/ next to the inputs file)
+    #[arg(long)]
+    pub circuit: Option<PathBuf>,
+}
+
+impl PublicInputsArgs {
+    /// The public inputs in the verifier's layout
+    pub fn read(&self) -> Result<Vec<u8>> {
+        read_public_inputs(&self.public_inputs, self.circuit.as_deref())
+    }
+}
+
+/// Read public inputs in any format the CLI accepts
+pub fn read_public_inputs(path: &Path, circuit: Option<&Path>) -> Result<Vec<u8>> {
+    let extension = path.extension().and_then(|e| e.to_str());
+    if !matches!(extension, Some("toml" | "json")) {
+        let inputs = PublicInputs::read_bb(path)?;
+        return Ok(inputs.to_bytes());
+    }
+
+    let artifact = match circuit {
+        Some(artifact) => artifact.to_path_buf(),
+        None => find_artifact(path).with_context(|| {
+            format!("Pass --circuit target/<circuit>.json to lay out {:?}", path)
+        })?,
+    };
+    let abi = NoirAbi::read_artifact(&artifact)?;
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read public inputs file: {:?}", path))?;
+    let inputs = if extension == Some("toml") {
+        abi.public_inputs_from_toml(&text)
+    } else {
+        abi.public_inputs_from_json(&text)
+    }
+    .with_context(|| format!("Public inputs in {:?} do not fit {:?}", path, artifact))?;
+    Ok(inputs.to_bytes())
+}
+
+/// The only program artifact in `target/` beside an input map
+fn find_artifact(inputs: &Path) -> Result<PathBuf> {
+    let target = inputs
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("target");
+    let artifacts: Vec<PathBuf> = std::fs::read_dir(&target)
+        .with_context(|| format!("No {:?} directory", target))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    match artifacts.as_slice() {
+        [artifact] => Ok(artifact.clone()),
+        [] => bail!("No program artifact in {:?}", target),
+        _ => bail!("Several program artifacts in {:?}", target),
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputFormat {
     Human,
@@ -62,60 +157,192 @@ enum Commands {
     /// Verify a proof on-chain (full workflow)
     Verify(verify::VerifyArgs),
 
+    /// Verify every proof in a manifest, several at a time
+    BatchVerify(batch_verify::BatchVerifyArgs),
+
+    /// Run a full verification and report CUs, time and bytes per phase
+    Bench(bench::BenchArgs),
+
+    /// Prove a Noir circuit with nargo and bb, then verify it on-chain
+    Prove(prove::ProveArgs),
+
+    /// Dry-run a verification against the cluster without paying fees
+    Simulate(simulate::SimulateArgs),
+
+    /// Estimate transactions, compute units and SOL cost of a verification
+    Estimate(estimate::EstimateArgs),
+
+    /// Fund a keypair from the devnet/localnet faucet
+    Airdrop(airdrop::AirdropArgs),
+
     /// Check verification status
     Status(status::StatusArgs),
 
+    /// Decode a verifier account or a local proof/VK file
+    Inspect(inspect::InspectArgs),
+
     /// Manage verification receipts
     #[command(subcommand)]
     Receipt(receipt::ReceiptCommands),
 
+    /// Publish and look up VKs by name@version
+    #[command(subcommand)]
+    Registry(registry::RegistryCommands),
+
+    /// Stream new receipts of a VK as JSON lines
+    Watch(watch::WatchArgs),
+
     /// Close accounts and reclaim rent
     Close(close::CloseArgs),
+
+    /// Generate an integrator program that checks verification receipts
+    Scaffold(scaffold::ScaffoldArgs),
+
+    /// Generate a typed client from the verifier interface's IDL
+    ExportClient(export_client::ExportClientArgs),
+
+    /// Manage configuration profiles
+    #[command(subcommand)]
+    Config(commands::config::ConfigCommands),
+}
+
+impl Commands {
+    /// Options shared by the command; `config` has none
+    fn common(&self) -> Option<&CommonArgs> {
+        match self {
+            Commands::Deploy(args) => Some(&args.common),
+            Commands::UploadVk(args) => Some(&args.common),
+            Commands::Verify(args) => Some(&args.common),
+            Commands::BatchVerify(args) => Some(&args.common),
+            Commands::Bench(args) => Some(&args.common),
+            Commands::Prove(args) => Some(&args.common),
+            Commands::Simulate(args) => Some(&args.common),
+            Commands::Estimate(args) => Some(&args.common),
+            Commands::Airdrop(args) => Some(&args.common),
+            Commands::Status(args) => Some(&args.common),
+            Commands::Inspect(args) => Some(&args.common),
+            Commands::Receipt(cmd) => Some(cmd.common()),
+            Commands::Registry(cmd) => Some(cmd.common()),
+            Commands::Watch(args) => Some(&args.common),
+            Commands::Close(args) => Some(&args.common),
+            Commands::Scaffold(args) => Some(&args.common),
+            Commands::ExportClient(args) => Some(&args.common),
+            Commands::Config(_) => None,
+        }
+    }
 }
 
-fn main() -> Result<()> {
+fn main() {
     // Initialize logging
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp(None)
         .format_target(false)
         .init();
 
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            std::process::exit(if e.use_stderr() {
+                output::EXIT_USAGE
+            } else {
+                output::EXIT_OK
+            });
+        }
+    };
+    let json_output = cli
+        .command
+        .common()
+        .is_some_and(|common| common.output == OutputFormat::Json);
 
-    // Run command
-    let result = match cli.command {
+    // Handle errors nicely, with an exit code scripts can rely on
+    if let Err(e) = run(cli.command) {
+        let code = output::exit_code(&e);
+        if e.downcast_ref::<output::Reported>().is_some() {
+            // The command already printed its result
+        } else if json_output {
+            output::print_error(&e, code);
+        } else {
+            eprintln!("{} {}", style("Error:").red().bold(), e);
+        }
+        std::process::exit(code);
+    }
+}
+
+fn run(command: Commands) -> Result<()> {
+    match command {
         Commands::Deploy(args) => {
             let config = config::Config::load(&args.common)?;
             deploy::run(&config, args)
         }
         Commands::UploadVk(args) => {
-            let config = config::Config::load(&args.common)?;
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
             upload_vk::run(&config, args)
         }
         Commands::Verify(args) => {
-            let config = config::Config::load(&args.common)?;
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
             verify::run(&config, args)
         }
+        Commands::BatchVerify(args) => {
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
+            batch_verify::run(&config, args)
+        }
+        Commands::Bench(args) => {
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
+            bench::run(&config, args)
+        }
+        Commands::Prove(args) => {
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
+            prove::run(&config, args)
+        }
+        Commands::Simulate(args) => {
+            let config = config::Config::load(&args.common)?;
+            simulate::run(&config, args)
+        }
+        Commands::Estimate(args) => {
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
+            estimate::run(&config, args)
+        }
+        Commands::Airdrop(args) => {
+            let config = config::Config::load(&args.common)?;
+            airdrop::run(&config, args)
+        }
         Commands::Status(args) => {
             let config = config::Config::load(&args.common)?;
             status::run(&config, args)
         }
+        Commands::Inspect(args) => {
+            let config = config::Config::load(&args.common)?;
+            inspect::run(&config, args)
+        }
         Commands::Receipt(cmd) => {
             let common = cmd.common();
             let config = config::Config::load(common)?;
             receipt::run(&config, cmd)
         }
-        Commands::Close(args) => {
+        Commands::Registry(cmd) => {
+            let mut config = config::Config::load(cmd.common())?;
+            if let Some(fees) = cmd.fees() {
+                config = config.with_fees(fees);
+            }
+            registry::run(&config, cmd)
+        }
+        Commands::Watch(args) => {
             let config = config::Config::load(&args.common)?;
+            watch::run(&config, args)
+        }
+        Commands::Close(args) => {
+            let config = config::Config::load(&args.common)?.with_fees(&args.fees);
             close::run(&config, args)
         }
-    };
-
-    // Handle errors nicely
-    if let Err(e) = result {
-        eprintln!("{} {}", style("Error:").red().bold(), e);
-        std::process::exit(1);
+        Commands::Scaffold(args) => {
+            let config = config::Config::load(&args.common)?;
+            scaffold::run(&config, args)
+        }
+        Commands::ExportClient(args) => {
+            let config = config::Config::load(&args.common)?;
+            export_client::run(&config, args)
+        }
+        Commands::Config(cmd) => commands::config::run(cmd),
     }
-
-    Ok(())
 }
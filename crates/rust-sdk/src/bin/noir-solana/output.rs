@@ -0,0 +1,95 @@
+//! JSON results and exit codes
+//!
+//! With `--output json` a command prints exactly one JSON object on
+//! stdout: its result, or an error object from `main`. Every object
+//! carries `schema_version` and `ok`; fields are only ever added within a
+//! schema version.
+
+use serde_json::Value;
+use solana_noir_verifier_sdk::VerifierError;
+
+/// Version of the JSON objects the CLI prints
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Success
+pub const EXIT_OK: i32 = 0;
+/// Any error without a more specific code
+pub const EXIT_ERROR: i32 = 1;
+/// The proof was rejected, on chain or locally
+pub const EXIT_VERIFICATION_FAILED: i32 = 2;
+/// RPC, network or confirmation failure; retrying may succeed
+pub const EXIT_TRANSIENT: i32 = 3;
+/// The keypair can't pay for the verification; fund it and retry
+pub const EXIT_INSUFFICIENT_FUNDS: i32 = 4;
+/// Invalid command line
+pub const EXIT_USAGE: i32 = 64;
+
+/// A failure whose result the command already printed
+///
+/// `main` exits with `code` and prints nothing more.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct Reported {
+    pub code: i32,
+    pub message: String,
+}
+
+impl Reported {
+    /// A proof, or some proofs of a batch, failed verification
+    pub fn verification_failed(message: impl Into<String>) -> anyhow::Error {
+        Reported {
+            code: EXIT_VERIFICATION_FAILED,
+            message: message.into(),
+        }
+        .into()
+    }
+}
+
+/// A command result with `schema_version` and `ok` added
+pub fn tagged(ok: bool, mut value: Value) -> Value {
+    if let Value::Object(fields) = &mut value {
+        fields.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+        fields.insert("ok".to_string(), ok.into());
+    }
+    value
+}
+
+/// Print a command result, adding `schema_version` and `ok`
+pub fn print_json(ok: bool, value: Value) {
+    println!("{}", tagged(ok, value));
+}
+
+/// Print the JSON object for an error `main` is about to exit with
+pub fn print_error(error: &anyhow::Error, code: i32) {
+    print_json(
+        false,
+        serde_json::json!({
+            "exit_code": code,
+            "error": error.to_string(),
+        }),
+    );
+}
+
+/// Exit code for an error returned by a command
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    for cause in error.chain() {
+        if let Some(reported) = cause.downcast_ref::<Reported>() {
+            return reported.code;
+        }
+        if let Some(e) = cause.downcast_ref::<VerifierError>() {
+            return match e {
+                VerifierError::VerificationFailed | VerifierError::LocalVerification(_) => {
+                    EXIT_VERIFICATION_FAILED
+                }
+                _ if e.program_error().is_some_and(|c| c.is_proof_rejection()) => {
+                    EXIT_VERIFICATION_FAILED
+                }
+                VerifierError::InsufficientFunds { .. } => EXIT_INSUFFICIENT_FUNDS,
+                VerifierError::Websocket(_) => EXIT_TRANSIENT,
+                _ if e.is_retryable() => EXIT_TRANSIENT,
+                _ => EXIT_ERROR,
+            };
+        }
+    }
+    EXIT_ERROR
+}
@@ -2,33 +2,53 @@
 
 use crate::{
     error::{Result, VerifierError},
-    instructions,
+    fees, instructions,
+    phases::{next_step, PhaseStep},
+    pubsub::{CONFIRMATION_TIMEOUT, POLL_INTERVAL},
+    rpc::{RpcOperation, RpcPool},
+    session::VerificationSession,
+    state::VerificationStateView,
     types::*,
 };
-use sha3::{Digest, Keccak256};
+use plonk_solana_core::proof::Proof;
+use plonk_solana_core::PhasePlan;
 use solana_client::rpc_client::RpcClient;
+use solana_rpc_client_api::response::UiTransactionStatusMeta;
 use solana_sdk::{
+    hash::Hash,
     instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
-    transaction::Transaction,
+    transaction::VersionedTransaction,
 };
 use solana_system_interface::instruction as system_instruction;
 
-// Compute budget program ID
-const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
-    solana_sdk::pubkey::Pubkey::from_str_const("ComputeBudget111111111111111111111111111111");
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Accounts created by [`SolanaNoirVerifier::upload_proof`]
+pub(crate) struct ProofUpload {
+    pub(crate) proof_account: Pubkey,
+    pub(crate) state_account: Pubkey,
+    pub(crate) signatures: Vec<Signature>,
+    num_steps: usize,
+}
 
-/// Build a SetComputeUnitLimit instruction
-fn set_compute_unit_limit(units: u32) -> Instruction {
-    // Instruction code 2 = SetComputeUnitLimit
-    let mut data = vec![2u8];
-    data.extend_from_slice(&units.to_le_bytes());
-    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+/// Instructions from [`SolanaNoirVerifier::setup_instructions`]
+pub(crate) struct AccountSetup {
+    /// One transaction: create both accounts, initialize the proof buffer
+    /// and, when they fit, set the public inputs
+    pub(crate) instructions: Vec<Instruction>,
+    /// `UploadData` chunks for public inputs that did not fit, one per
+    /// transaction; empty when they went in the setup transaction
+    pub(crate) public_input_uploads: Vec<Instruction>,
 }
-use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
+
+/// Most public inputs a verification state records
+const MAX_PUBLIC_INPUTS: usize = u8::MAX as usize;
 
 /// Client for verifying Noir UltraHonk proofs on Solana
 ///
@@ -49,14 +69,27 @@ use std::time::Duration;
 /// let result = verifier.verify(&payer, &proof, &public_inputs, &vk_result.vk_account, None)?;
 /// ```
 pub struct SolanaNoirVerifier {
-    client: Arc<RpcClient>,
-    config: VerifierConfig,
+    /// `client` followed by `config.rpc_endpoints`
+    pub(crate) rpc: RpcPool,
+    pub(crate) config: VerifierConfig,
+    /// Contents of `config.lookup_table`, fetched on first use
+    pub(crate) lookup_table: Mutex<Option<AddressLookupTableAccount>>,
+    /// Last fetched blockhash and when, shared by concurrent senders
+    pub(crate) blockhash: Mutex<Option<(Hash, Instant)>>,
 }
 
 impl SolanaNoirVerifier {
     /// Create a new verifier client
+    ///
+    /// `client` is the primary endpoint; requests fail over to
+    /// [`VerifierConfig::rpc_endpoints`] when it is unhealthy.
     pub fn new(client: Arc<RpcClient>, config: VerifierConfig) -> Self {
-        Self { client, config }
+        Self {
+            rpc: RpcPool::new(client, &config.rpc_endpoints),
+            config,
+            lookup_table: Mutex::new(None),
+            blockhash: Mutex::new(None),
+        }
     }
 
     /// Upload a verification key to the chain
@@ -68,6 +101,30 @@ impl SolanaNoirVerifier {
     /// # Returns
     /// VK account public key and upload details
     pub fn upload_vk(&self, payer: &Keypair, vk: &[u8]) -> Result<VkUploadResult> {
+        self.upload_vk_inner(payer, vk, None)
+    }
+
+    /// Upload a verification key generated against a non-default SRS
+    ///
+    /// `srs_g2` is the x·G2 point of that setup, encoded like the program's
+    /// G2 inputs (`x1 || x0 || y1 || y0`, big-endian). It is stored after the
+    /// VK and used by the final pairing check instead of the Ignition point.
+    pub fn upload_vk_with_srs(
+        &self,
+        payer: &Keypair,
+        vk: &[u8],
+        srs_g2: &[u8; SRS_G2_SIZE],
+    ) -> Result<VkUploadResult> {
+        plonk_solana_core::validate_srs_g2(srs_g2).map_err(|_| VerifierError::InvalidSrsG2)?;
+        self.upload_vk_inner(payer, vk, Some(srs_g2))
+    }
+
+    fn upload_vk_inner(
+        &self,
+        payer: &Keypair,
+        vk: &[u8],
+        srs_g2: Option<&[u8; SRS_G2_SIZE]>,
+    ) -> Result<VkUploadResult> {
         if vk.len() != VK_SIZE {
             return Err(VerifierError::InvalidVkSize {
                 expected: VK_SIZE,
@@ -75,10 +132,16 @@ impl SolanaNoirVerifier {
             });
         }
 
+        // The SRS point is uploaded as if it were the tail of the VK
+        let mut payload = vk.to_vec();
+        if let Some(srs_g2) = srs_g2 {
+            payload.extend_from_slice(srs_g2);
+        }
+
         let vk_account = Keypair::new();
-        let vk_buffer_size = VK_HEADER_SIZE + VK_SIZE;
+        let vk_buffer_size = VK_HEADER_SIZE + payload.len();
         let rent = self
-            .client
+            .client()
             .get_minimum_balance_for_rent_exemption(vk_buffer_size)?;
         let mut signatures = Vec::new();
 
@@ -98,7 +161,10 @@ impl SolanaNoirVerifier {
         signatures.push(setup_sig);
 
         // Upload VK chunks
-        let chunks = self.split_into_chunks(vk);
+        let chunks = split_into_chunks(
+            &payload,
+            self.config.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE),
+        );
         let num_chunks = chunks.len();
 
         for (offset, chunk_data) in chunks {
@@ -112,6 +178,11 @@ impl SolanaNoirVerifier {
             signatures.push(sig);
         }
 
+        // Validate once on-chain and cache the pre-parsed layout for every phase
+        let compile_ix = instructions::compile_vk(&self.config.program_id, &vk_account.pubkey());
+        let sig = self.send_and_confirm(payer, &[], vec![compile_ix], false)?;
+        signatures.push(sig);
+
         Ok(VkUploadResult {
             vk_account: vk_account.pubkey(),
             signatures,
@@ -130,6 +201,7 @@ impl SolanaNoirVerifier {
     ///
     /// # Returns
     /// Verification result
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(vk = %vk_account)))]
     pub fn verify(
         &self,
         payer: &Keypair,
@@ -138,14 +210,34 @@ impl SolanaNoirVerifier {
         vk_account: &Pubkey,
         options: Option<VerifyOptions>,
     ) -> Result<VerificationResult> {
-        if proof.len() != PROOF_SIZE {
-            return Err(VerifierError::InvalidProofSize {
+        self.timed(|| self.verify_inner(payer, proof, public_inputs, vk_account, options))
+    }
+
+    fn verify_inner(
+        &self,
+        payer: &Keypair,
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+        options: Option<VerifyOptions>,
+    ) -> Result<VerificationResult> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
                 expected: PROOF_SIZE,
                 actual: proof.len(),
-            });
+            })?;
+        if !is_zk {
+            return Err(VerifierError::UnsupportedProof(
+                "non-ZK proofs cannot be verified on-chain; generate the proof with ZK enabled"
+                    .to_string(),
+            ));
         }
 
         let options = options.unwrap_or_default();
+        if !options.skip_local_check {
+            self.verify_locally_with_account(vk_account, proof, public_inputs)?;
+        }
+
         let mut signatures = Vec::new();
         let mut total_cus = 0u64;
         let mut num_steps = 0usize;
@@ -154,17 +246,6 @@ impl SolanaNoirVerifier {
 
         let num_pi = public_inputs.len() / 32;
 
-        // Create accounts
-        let proof_account = Keypair::new();
-        let state_account = Keypair::new();
-        let proof_buffer_size = BUFFER_HEADER_SIZE + public_inputs.len() + PROOF_SIZE;
-        let proof_rent = self
-            .client
-            .get_minimum_balance_for_rent_exemption(proof_buffer_size)?;
-        let state_rent = self
-            .client
-            .get_minimum_balance_for_rent_exemption(STATE_SIZE)?;
-
         // Closure for cleanup
         let cleanup = |client: &SolanaNoirVerifier,
                        payer: &Keypair,
@@ -180,145 +261,57 @@ impl SolanaNoirVerifier {
             }
         };
 
-        // Setup: Create accounts + init + set public inputs
-        // TX size limit is 1232 bytes. Calculate what fits.
-        const PI_BUNDLE_THRESHOLD: usize = 800;
-        const PI_SINGLE_TX_MAX: usize = 1100;
-
-        if public_inputs.len() > PI_SINGLE_TX_MAX {
-            return Err(VerifierError::PublicInputsTooLarge {
-                size: public_inputs.len(),
-                max_size: PI_SINGLE_TX_MAX,
-            });
-        }
-
-        if public_inputs.len() <= PI_BUNDLE_THRESHOLD {
-            // Bundle: accounts + init + public inputs in one TX
-            let setup_ix = vec![
-                system_instruction::create_account(
-                    &payer.pubkey(),
-                    &proof_account.pubkey(),
-                    proof_rent,
-                    proof_buffer_size as u64,
-                    &self.config.program_id,
-                ),
-                system_instruction::create_account(
-                    &payer.pubkey(),
-                    &state_account.pubkey(),
-                    state_rent,
-                    STATE_SIZE as u64,
-                    &self.config.program_id,
-                ),
-                instructions::init_buffer(
-                    &self.config.program_id,
-                    &proof_account.pubkey(),
-                    num_pi as u16,
-                ),
-                instructions::set_public_inputs(
-                    &self.config.program_id,
-                    &proof_account.pubkey(),
-                    public_inputs,
-                ),
-            ];
-
-            let sig = self.send_and_confirm(
-                payer,
-                &[&proof_account, &state_account],
-                setup_ix,
-                options.skip_preflight,
-            )?;
-            signatures.push(sig);
-            num_steps += 1;
-        } else {
-            // Split: accounts + init in one TX, PI in another
-            let accounts_ix = vec![
-                system_instruction::create_account(
-                    &payer.pubkey(),
-                    &proof_account.pubkey(),
-                    proof_rent,
-                    proof_buffer_size as u64,
-                    &self.config.program_id,
-                ),
-                system_instruction::create_account(
-                    &payer.pubkey(),
-                    &state_account.pubkey(),
-                    state_rent,
-                    STATE_SIZE as u64,
-                    &self.config.program_id,
-                ),
-                instructions::init_buffer(
-                    &self.config.program_id,
-                    &proof_account.pubkey(),
-                    num_pi as u16,
-                ),
-            ];
-
-            let sig = self.send_and_confirm(
-                payer,
-                &[&proof_account, &state_account],
-                accounts_ix,
-                options.skip_preflight,
-            )?;
-            signatures.push(sig);
-
-            let pi_ix = vec![instructions::set_public_inputs(
-                &self.config.program_id,
-                &proof_account.pubkey(),
-                public_inputs,
-            )];
-
-            let sig = self.send_and_confirm(payer, &[], pi_ix, options.skip_preflight)?;
-            signatures.push(sig);
-            num_steps += 2;
-        }
-
-        // Upload proof chunks
-        let chunks = self.split_into_chunks(proof);
-        for (offset, chunk_data) in chunks {
-            let ix = instructions::upload_chunk(
-                &self.config.program_id,
-                &proof_account.pubkey(),
-                offset as u16,
-                chunk_data,
-            );
-            let sig = self.send_and_confirm(payer, &[], vec![ix], true)?;
-            signatures.push(sig);
-        }
-        num_steps += 1; // Count all uploads as 1 step
+        let upload =
+            self.upload_proof(payer, None, proof, public_inputs, options.skip_preflight)?;
+        let proof_account = upload.proof_account;
+        let state_account = upload.state_account;
+        signatures.extend(upload.signatures);
+        num_steps += upload.num_steps;
 
-        // Phase 1: Challenge generation
+        // Phase 1: Challenge generation (its cost does not depend on log_n)
         let (sig, cus) = self.execute_phase(
+            PhaseStep::Challenges,
             payer,
             instructions::phase1_full(
                 &self.config.program_id,
-                &state_account.pubkey(),
-                &proof_account.pubkey(),
+                &state_account,
+                &proof_account,
                 vk_account,
             ),
             options.skip_preflight,
+            &plonk_solana_core::cu_estimate(0, is_zk, num_pi),
+            None,
         )?;
         signatures.push(sig);
         total_cus += cus;
         num_steps += 1;
 
         // Get log_n from state
-        let log_n = self.get_log_n(&state_account.pubkey())?;
-        let rounds_per_tx = 6u8;
+        let log_n = self.get_log_n(&state_account)?;
+        let plan = plonk_solana_core::cu_estimate(log_n, is_zk, num_pi);
+        let rounds_per_tx = plan.rounds_per_tx();
 
         // Phase 2: Sumcheck rounds
         let mut r = 0u8;
         while r < log_n {
             let end_round = std::cmp::min(r + rounds_per_tx, log_n);
+            let step = PhaseStep::SumcheckRounds {
+                start: r,
+                end: end_round,
+            };
             let (sig, cus) = self.execute_phase(
+                step,
                 payer,
                 instructions::phase2_rounds(
                     &self.config.program_id,
-                    &state_account.pubkey(),
-                    &proof_account.pubkey(),
+                    &state_account,
+                    &proof_account,
                     r,
                     end_round,
                 ),
                 true,
+                &plan,
+                None,
             )?;
             signatures.push(sig);
             total_cus += cus;
@@ -326,60 +319,60 @@ impl SolanaNoirVerifier {
             r += rounds_per_tx;
         }
 
-        // Combined Phase 2d+3a: Relations + Weights
-        let (sig, cus) = self.execute_phase(
-            payer,
-            instructions::phase2d_and_3a(
-                &self.config.program_id,
-                &state_account.pubkey(),
-                &proof_account.pubkey(),
-            ),
-            true,
-        )?;
-        signatures.push(sig);
-        total_cus += cus;
-        num_steps += 1;
-
-        // Combined Phase 3b: Folding + Gemini
-        let (sig, cus) = self.execute_phase(
-            payer,
-            instructions::phase3b_combined(
-                &self.config.program_id,
-                &state_account.pubkey(),
-                &proof_account.pubkey(),
-            ),
-            true,
-        )?;
-        signatures.push(sig);
-        total_cus += cus;
-        num_steps += 1;
+        // Phase 2d+3a and 3b, each pair in one transaction unless the plan
+        // splits it for a large circuit
+        let mut steps = if plan.use_relations_and_weights() {
+            vec![PhaseStep::RelationsAndWeights]
+        } else {
+            vec![PhaseStep::Relations, PhaseStep::Weights]
+        };
+        if plan.use_combined_folding() {
+            steps.push(PhaseStep::FoldingAndGemini);
+        } else {
+            steps.extend([PhaseStep::Folding, PhaseStep::Gemini]);
+        }
+        for step in steps {
+            let instruction = step
+                .instruction(
+                    &self.config.program_id,
+                    &state_account,
+                    &proof_account,
+                    vk_account,
+                )
+                .expect("paired phases always have an instruction");
+            let (sig, cus) = self.execute_phase(step, payer, instruction, true, &plan, None)?;
+            signatures.push(sig);
+            total_cus += cus;
+            num_steps += 1;
+        }
 
         // Phase 3c + 4: MSM + Pairing
         let (sig, cus) = self.execute_phase(
+            PhaseStep::MsmAndPairing,
             payer,
             instructions::phase3c_and_pairing(
                 &self.config.program_id,
-                &state_account.pubkey(),
-                &proof_account.pubkey(),
+                &state_account,
+                &proof_account,
                 vk_account,
             ),
             true,
+            &plan,
+            None,
         )?;
         signatures.push(sig);
         total_cus += cus;
         num_steps += 1;
 
         // Read final state
-        let state = self.get_verification_state(&state_account.pubkey())?;
+        let state = self.get_verification_state(&state_account)?;
 
         // Auto-close accounts to reclaim rent
         if options.auto_close {
-            if let Some((lamports, close_sig)) = cleanup(
-                self,
-                payer,
-                &state_account.pubkey(),
-                &proof_account.pubkey(),
-            ) {
+            if let Some((lamports, close_sig)) =
+                cleanup(self, payer, &state_account, &proof_account)
+            {
+                self.clear_session();
                 recovered_lamports = Some(lamports);
                 accounts_closed = true;
                 signatures.push(close_sig);
@@ -387,9 +380,9 @@ impl SolanaNoirVerifier {
         }
 
         Ok(VerificationResult {
-            verified: state.verified,
-            state_account: state_account.pubkey(),
-            proof_account: proof_account.pubkey(),
+            verified: state.status.verified,
+            state_account,
+            proof_account,
             total_cus,
             num_transactions: signatures.len(),
             num_steps,
@@ -399,76 +392,118 @@ impl SolanaNoirVerifier {
         })
     }
 
-    /// Read verification state from an account
-    pub fn get_verification_state(&self, state_account: &Pubkey) -> Result<VerificationState> {
-        let account_info = self
-            .client
-            .get_account(state_account)
-            .map_err(|_| VerifierError::StateAccountNotFound)?;
-
-        let data = &account_info.data;
-        // Minimum size check - the smallest valid state is around 6376 bytes
-        if data.len() < 4 {
-            return Err(VerifierError::InvalidStateData);
+    /// Verify a proof end to end and create its receipt
+    ///
+    /// Uploads the proof, runs every verification phase, creates the
+    /// receipt and closes the proof and state accounts. Each transaction is
+    /// confirmed before the next one is sent.
+    ///
+    /// The next phase is always chosen from the on-chain state, so a
+    /// transaction that timed out but landed is not replayed. Transient
+    /// failures are retried up to [`VerifierConfig::max_retries`] times per
+    /// transaction; a proof rejected by the program fails immediately with
+    /// [`VerifierError::VerificationFailed`] or the program error.
+    ///
+    /// `authority` owns the verification and pays for it unless a
+    /// `sponsor` is given. A sponsor pays every fee and the account and
+    /// receipt rent, and gets the rent back when the accounts close; the
+    /// authority then only co-signs the setup transaction. Signers must be
+    /// `Sync` because chunks upload from several threads.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(vk = %vk_account)))]
+    pub fn verify_phased(
+        &self,
+        authority: &(dyn Signer + Sync),
+        sponsor: Option<&(dyn Signer + Sync)>,
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+    ) -> Result<VerifiedReceipt> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        if !is_zk {
+            return Err(VerifierError::UnsupportedProof(
+                "non-ZK proofs cannot be verified on-chain; generate the proof with ZK enabled"
+                    .to_string(),
+            ));
         }
 
-        // Parse state: [phase: u8, challenge_sub_phase: u8, sumcheck_sub_phase: u8, log_n: u8, ...]
-        // See phased.rs VerificationState struct for layout
-        let phase_raw = data[0];
-        let phase = match phase_raw {
-            0 => VerificationPhase::NotStarted, // Uninitialized
-            1 => VerificationPhase::NotStarted, // ChallengesInProgress
-            2 => VerificationPhase::ChallengesGenerated,
-            3 => VerificationPhase::NotStarted, // SumcheckInProgress
-            4 => VerificationPhase::SumcheckComplete,
-            5 => VerificationPhase::NotStarted, // MsmInProgress
-            6 => VerificationPhase::MsmComplete,
-            7 => VerificationPhase::Verified, // Complete
-            255 => VerificationPhase::Failed,
-            _ => VerificationPhase::NotStarted,
-        };
-        let log_n = data[3];
+        let payer = sponsor.unwrap_or(authority);
+        let co_signer = Some(authority as &dyn Signer).filter(|a| a.pubkey() != payer.pubkey());
+        self.timed(|| {
+            let upload = self.upload_proof(payer, co_signer, proof, public_inputs, false)?;
+            self.complete_phased(
+                payer,
+                &upload.state_account,
+                &upload.proof_account,
+                &[],
+                vk_account,
+                public_inputs,
+                is_zk,
+                upload.signatures,
+            )
+        })
+    }
 
-        // The verified flag is at the end before final 31-byte padding
-        // Use actual data length, not hardcoded SIZE (handles version differences)
-        let verified = data.len() >= 32 && data[data.len() - 32] == 1;
+    /// Read and decode a verification state account
+    pub fn get_verification_state(&self, state_account: &Pubkey) -> Result<VerificationStateView> {
+        let account_info = self
+            .fetch_account(state_account)
+            .map_err(|_| VerifierError::StateAccountNotFound)?;
 
-        Ok(VerificationState {
-            phase,
-            log_n,
-            verified,
-        })
+        VerificationStateView::from_account_data(&account_info.data)
+            .ok_or(VerifierError::InvalidStateData)
     }
 
     /// Derive the receipt PDA for a given VK and public inputs
     pub fn derive_receipt_pda(&self, vk_account: &Pubkey, public_inputs: &[u8]) -> (Pubkey, u8) {
-        // Hash public inputs using keccak256
-        let pi_hash = Keccak256::digest(public_inputs);
-
-        Pubkey::find_program_address(
-            &[RECEIPT_SEED, vk_account.as_ref(), &pi_hash],
-            &self.config.program_id,
-        )
+        let pi_hash = solana_noir_receipt_seeds::pi_hash(public_inputs);
+        solana_noir_receipt_seeds::derive_receipt_pda(vk_account, &pi_hash, &self.config.program_id)
     }
 
     /// Create a verification receipt after successful verification
     pub fn create_receipt(
         &self,
-        payer: &Keypair,
+        payer: &dyn Signer,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        vk_account: &Pubkey,
+        public_inputs: &[u8],
+    ) -> Result<Pubkey> {
+        self.create_receipt_with_options(
+            payer,
+            state_account,
+            proof_account,
+            vk_account,
+            public_inputs,
+            &ReceiptOptions::default(),
+        )
+    }
+
+    /// [`create_receipt`](Self::create_receipt) with [`ReceiptOptions`],
+    /// e.g. to attach an application-defined tag or to refresh or count an
+    /// existing receipt
+    pub fn create_receipt_with_options(
+        &self,
+        payer: &dyn Signer,
         state_account: &Pubkey,
         proof_account: &Pubkey,
         vk_account: &Pubkey,
         public_inputs: &[u8],
+        options: &ReceiptOptions,
     ) -> Result<Pubkey> {
         let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
 
-        let ix = instructions::create_receipt(
+        let ix = instructions::create_receipt_with_options(
             &self.config.program_id,
             state_account,
             proof_account,
             vk_account,
             &receipt_pda,
             &payer.pubkey(),
+            options,
         );
 
         self.send_and_confirm(payer, &[], vec![ix], false)?;
@@ -476,6 +511,9 @@ impl SolanaNoirVerifier {
     }
 
     /// Get a verification receipt if it exists
+    ///
+    /// The receipt PDA is derived as in the CPI crate, from `vk_account`
+    /// and the Keccak256 of `public_inputs`.
     pub fn get_receipt(
         &self,
         vk_account: &Pubkey,
@@ -483,41 +521,86 @@ impl SolanaNoirVerifier {
     ) -> Result<Option<ReceiptInfo>> {
         let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
 
-        let account_info = match self.client.get_account(&receipt_pda) {
+        let account_info = match self.fetch_account(&receipt_pda) {
             Ok(info) => info,
             Err(_) => return Ok(None),
         };
 
-        if account_info.data.len() < RECEIPT_SIZE {
-            return Ok(None);
-        }
-
         if account_info.owner != self.config.program_id {
             return Ok(None);
         }
 
-        // Read verified_slot (offset 0, 8 bytes LE)
-        let verified_slot = u64::from_le_bytes(account_info.data[0..8].try_into().unwrap());
-        // Read verified_timestamp (offset 8, 8 bytes LE signed)
-        let verified_timestamp = i64::from_le_bytes(account_info.data[8..16].try_into().unwrap());
-
-        Ok(Some(ReceiptInfo {
+        Ok(ReceiptInfo::from_account_data(
             receipt_pda,
-            verified_slot,
-            verified_timestamp,
-        }))
+            &account_info.data,
+        ))
+    }
+
+    /// Whether a receipt exists for this VK and these public inputs
+    pub fn receipt_exists(&self, vk_account: &Pubkey, public_inputs: &[u8]) -> Result<bool> {
+        Ok(self.get_receipt(vk_account, public_inputs)?.is_some())
+    }
+
+    /// Close a receipt and recover its rent
+    ///
+    /// `payer` must be the account that paid for the receipt; the rent goes
+    /// to the beneficiary the receipt recorded, `payer` unless it was
+    /// created with [`ReceiptOptions::with_rent_beneficiary`]. Afterwards
+    /// the proof no longer counts as verified for CPI checks. Returns the
+    /// lamports recovered.
+    pub fn close_receipt(
+        &self,
+        payer: &dyn Signer,
+        vk_account: &Pubkey,
+        public_inputs: &[u8],
+    ) -> Result<(u64, Signature)> {
+        let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
+        let account = self
+            .fetch_account(&receipt_pda)
+            .map_err(|_| VerifierError::ReceiptNotFound)?;
+        let receipt = ReceiptInfo::from_account_data(receipt_pda, &account.data)
+            .ok_or(VerifierError::ReceiptNotFound)?;
+
+        let ix = instructions::close_receipt_to(
+            &self.config.program_id,
+            &receipt_pda,
+            &payer.pubkey(),
+            &receipt.rent_beneficiary,
+        );
+        let sig = self.send_and_confirm(payer, &[], vec![ix], false)?;
+        Ok((account.lamports, sig))
+    }
+
+    /// Have the program confirm that the receipt for `vk_account` and
+    /// `public_inputs` was created from `proof`
+    ///
+    /// Sends `ReverifyByDigest` with the Keccak256 of `proof`, which costs
+    /// one small transaction instead of a full verification. Fails with
+    /// [`ProgramErrorCode::ProofDigestMismatch`](crate::ProgramErrorCode::ProofDigestMismatch)
+    /// if the receipt recorded a different proof.
+    pub fn reverify_by_digest(
+        &self,
+        payer: &dyn Signer,
+        vk_account: &Pubkey,
+        public_inputs: &[u8],
+        proof: &[u8],
+    ) -> Result<Signature> {
+        let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
+        let digest = solana_noir_receipt_seeds::proof_digest(proof);
+        let ix = instructions::reverify_by_digest(&self.config.program_id, &receipt_pda, &digest);
+        self.send_and_confirm(payer, &[], vec![ix], false)
     }
 
     /// Close proof and state accounts to recover rent
     pub fn close_accounts(
         &self,
-        payer: &Keypair,
+        payer: &dyn Signer,
         state_account: &Pubkey,
         proof_account: &Pubkey,
     ) -> Result<(u64, Signature)> {
         // Get current balances
-        let state_info = self.client.get_account(state_account).ok();
-        let proof_info = self.client.get_account(proof_account).ok();
+        let state_info = self.fetch_account(state_account).ok();
+        let proof_info = self.fetch_account(proof_account).ok();
         let recovered = state_info.map(|a| a.lamports).unwrap_or(0)
             + proof_info.map(|a| a.lamports).unwrap_or(0);
 
@@ -532,95 +615,863 @@ impl SolanaNoirVerifier {
         Ok((recovered, sig))
     }
 
+    /// Unlock a proof buffer so a different proof can be uploaded to it
+    ///
+    /// Phase 1 locks the buffer to `state_account`; this zeroes that state,
+    /// discarding any verification progress on it. A successful verification
+    /// cannot be reset until a receipt records it.
+    pub fn reset_buffer(
+        &self,
+        payer: &dyn Signer,
+        proof_account: &Pubkey,
+        state_account: &Pubkey,
+    ) -> Result<Signature> {
+        let ix = instructions::reset_buffer(&self.config.program_id, proof_account, state_account);
+        self.send_and_confirm(payer, &[], vec![ix], false)
+    }
+
+    /// Request an airdrop and wait for it to confirm
+    ///
+    /// Only devnet, testnet and local validators have a faucet.
+    pub fn request_airdrop(&self, recipient: &Pubkey, lamports: u64) -> Result<Signature> {
+        let sig = self.rpc_call(RpcOperation::Send, |rpc| {
+            rpc.request_airdrop(recipient, lamports)
+        })?;
+        self.wait_for_confirmation(&sig)?;
+        Ok(sig)
+    }
+
     // =========================================================================
     // Private helpers
     // =========================================================================
 
+    /// Run the remaining phases, create the receipt and close the accounts
+    /// the cleanup policy selects
+    ///
+    /// Shared by [`Self::verify_phased`] and
+    /// [`VerificationSession::resume`](crate::VerificationSession::resume).
+    /// `shards` are the shards of `proof_account` when it is a shard
+    /// manifest, and empty otherwise.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(state = %state_account)))]
+    pub(crate) fn complete_phased(
+        &self,
+        payer: &dyn Signer,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        shards: &[Pubkey],
+        vk_account: &Pubkey,
+        public_inputs: &[u8],
+        is_zk: bool,
+        mut signatures: Vec<Signature>,
+    ) -> Result<VerifiedReceipt> {
+        let num_pi = public_inputs.len() / 32;
+        let (phase_signatures, total_cus) = self.run_phases(
+            payer,
+            state_account,
+            proof_account,
+            shards,
+            vk_account,
+            is_zk,
+            num_pi,
+        )?;
+        signatures.extend(phase_signatures);
+
+        let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
+        let receipt_ix = instructions::with_shards(
+            instructions::create_receipt(
+                &self.config.program_id,
+                state_account,
+                proof_account,
+                vk_account,
+                &receipt_pda,
+                &payer.pubkey(),
+            ),
+            shards,
+        );
+        self.with_retries(|| {
+            if self.get_receipt(vk_account, public_inputs)?.is_some() {
+                return Ok(());
+            }
+            signatures.push(self.send_and_confirm(payer, &[], vec![receipt_ix.clone()], false)?);
+            Ok(())
+        })?;
+        let receipt = self.wait_for_receipt(vk_account, public_inputs, CONFIRMATION_TIMEOUT)?;
+
+        let cleanup_ixs = self.cleanup_instructions(
+            &payer.pubkey(),
+            state_account,
+            proof_account,
+            shards,
+            &receipt_pda,
+        );
+        let recovered_lamports = match self.close_for_cleanup(payer, cleanup_ixs) {
+            Ok(closed) => {
+                self.clear_session();
+                closed.map(|(lamports, sig)| {
+                    signatures.push(sig);
+                    lamports
+                })
+            }
+            Err(e) => {
+                log::warn!("Failed to close accounts: {:?}", e);
+                None
+            }
+        };
+
+        Ok(VerifiedReceipt {
+            receipt,
+            state_account: *state_account,
+            proof_account: *proof_account,
+            total_cus,
+            signatures,
+            recovered_lamports,
+        })
+    }
+
+    /// Instructions closing the accounts [`VerifierConfig::cleanup`] selects
+    pub(crate) fn cleanup_instructions(
+        &self,
+        payer: &Pubkey,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        shards: &[Pubkey],
+        receipt_pda: &Pubkey,
+    ) -> Vec<Instruction> {
+        let program_id = &self.config.program_id;
+        let close_buffers = instructions::with_shards(
+            instructions::close_accounts(program_id, state_account, proof_account, payer),
+            shards,
+        );
+        match self.config.cleanup {
+            CleanupPolicy::Keep => Vec::new(),
+            CleanupPolicy::CloseBuffers => vec![close_buffers],
+            CleanupPolicy::CloseAll => vec![
+                close_buffers,
+                instructions::close_receipt(program_id, receipt_pda, payer),
+            ],
+        }
+    }
+
+    /// Send `cleanup_ixs` as one transaction, returning the lamports of the
+    /// accounts it closes
+    fn close_for_cleanup(
+        &self,
+        payer: &dyn Signer,
+        cleanup_ixs: Vec<Instruction>,
+    ) -> Result<Option<(u64, Signature)>> {
+        if cleanup_ixs.is_empty() {
+            return Ok(None);
+        }
+        // Closed accounts are the writable non-signers; the payer signs
+        let recovered = cleanup_ixs
+            .iter()
+            .flat_map(|ix| &ix.accounts)
+            .filter(|meta| meta.is_writable && !meta.is_signer)
+            .filter_map(|meta| self.fetch_account(&meta.pubkey).ok())
+            .map(|account| account.lamports)
+            .sum();
+        let sig = self.send_and_confirm(payer, &[], cleanup_ixs, true)?;
+        Ok(Some((recovered, sig)))
+    }
+
+    /// Create the proof buffer and state accounts and upload the proof
+    ///
+    /// A separate `authority` co-signs the setup transaction; `payer` signs
+    /// and pays for everything else.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(proof_len = proof.len())))]
+    pub(crate) fn upload_proof(
+        &self,
+        payer: &(dyn Signer + Sync),
+        authority: Option<&dyn Signer>,
+        proof: &[u8],
+        public_inputs: &[u8],
+        skip_preflight: bool,
+    ) -> Result<ProofUpload> {
+        let mut signatures = Vec::new();
+        let mut num_steps = 0usize;
+
+        // Create accounts + init + set public inputs
+        let proof_account = Keypair::new();
+        let state_account = Keypair::new();
+        let setup = self.setup_instructions(
+            &payer.pubkey(),
+            authority.map(|a| a.pubkey()).as_ref(),
+            &proof_account.pubkey(),
+            &state_account.pubkey(),
+            public_inputs,
+        )?;
+        let mut setup_signers: Vec<&dyn Signer> = vec![&proof_account, &state_account];
+        setup_signers.extend(authority);
+        signatures.push(self.send_and_confirm(
+            payer,
+            &setup_signers,
+            setup.instructions,
+            skip_preflight,
+        )?);
+        num_steps += 1;
+
+        self.save_session(&VerificationSession::started(
+            self,
+            &proof_account.pubkey(),
+            &state_account.pubkey(),
+            public_inputs,
+        ));
+
+        let chunk_size = self.proof_chunk_size(&payer.pubkey(), &proof_account.pubkey())?;
+        let chunks = split_into_chunks(proof, chunk_size);
+        signatures.extend(self.upload_proof_chunks(
+            payer,
+            &proof_account.pubkey(),
+            setup.public_input_uploads,
+            &chunks,
+        )?);
+        self.update_session(|session| session.set_uploaded());
+        num_steps += 1; // Count all uploads as 1 step
+
+        Ok(ProofUpload {
+            proof_account: proof_account.pubkey(),
+            state_account: state_account.pubkey(),
+            signatures,
+            num_steps,
+        })
+    }
+
+    /// Setup instructions for a new proof buffer and state account
+    ///
+    /// The setup transaction creates both accounts and must be signed by
+    /// them, and by `authority` when one is given. Public inputs go in the
+    /// same transaction when they fit; otherwise they come back as
+    /// `UploadData` chunks, which can be sent alongside the proof chunks.
+    pub(crate) fn setup_instructions(
+        &self,
+        payer: &Pubkey,
+        authority: Option<&Pubkey>,
+        proof_account: &Pubkey,
+        state_account: &Pubkey,
+        public_inputs: &[u8],
+    ) -> Result<AccountSetup> {
+        // TX size limit is 1232 bytes. Calculate what fits.
+        const PI_BUNDLE_THRESHOLD: usize = 800;
+        // Signature, account key and account index of a separate authority
+        const AUTHORITY_OVERHEAD: usize = 64 + 32 + 1;
+
+        if public_inputs.len() > MAX_PUBLIC_INPUTS * 32 {
+            return Err(VerifierError::PublicInputsTooLarge {
+                size: public_inputs.len(),
+                max_size: MAX_PUBLIC_INPUTS * 32,
+            });
+        }
+
+        let num_pi = public_inputs.len() / 32;
+        let proof_buffer_size = BUFFER_HEADER_SIZE + public_inputs.len() + PROOF_SIZE;
+        let proof_rent = self
+            .client()
+            .get_minimum_balance_for_rent_exemption(proof_buffer_size)?;
+        let state_rent = self
+            .client()
+            .get_minimum_balance_for_rent_exemption(STATE_SIZE)?;
+
+        let mut setup_ixs = vec![
+            system_instruction::create_account(
+                payer,
+                proof_account,
+                proof_rent,
+                proof_buffer_size as u64,
+                &self.config.program_id,
+            ),
+            system_instruction::create_account(
+                payer,
+                state_account,
+                state_rent,
+                STATE_SIZE as u64,
+                &self.config.program_id,
+            ),
+            match authority {
+                Some(authority) => instructions::init_buffer_with_authority(
+                    &self.config.program_id,
+                    proof_account,
+                    authority,
+                    num_pi as u16,
+                ),
+                None => {
+                    instructions::init_buffer(&self.config.program_id, proof_account, num_pi as u16)
+                }
+            },
+        ];
+
+        let bundle_threshold = match authority {
+            Some(_) => PI_BUNDLE_THRESHOLD - AUTHORITY_OVERHEAD,
+            None => PI_BUNDLE_THRESHOLD,
+        };
+        if public_inputs.len() <= bundle_threshold {
+            setup_ixs.push(instructions::set_public_inputs(
+                &self.config.program_id,
+                proof_account,
+                public_inputs,
+            ));
+            return Ok(AccountSetup {
+                instructions: setup_ixs,
+                public_input_uploads: Vec::new(),
+            });
+        }
+
+        let public_input_uploads =
+            self.public_input_uploads(payer, proof_account, public_inputs)?;
+        Ok(AccountSetup {
+            instructions: setup_ixs,
+            public_input_uploads,
+        })
+    }
+
+    /// `UploadData` instructions writing `public_inputs` to a proof buffer,
+    /// one per transaction
+    pub(crate) fn public_input_uploads(
+        &self,
+        payer: &Pubkey,
+        proof_account: &Pubkey,
+        public_inputs: &[u8],
+    ) -> Result<Vec<Instruction>> {
+        let chunk_size = self.public_input_chunk_size(payer, proof_account)?;
+        Ok(split_into_chunks(public_inputs, chunk_size)
+            .into_iter()
+            .map(|(offset, bytes)| {
+                instructions::upload_data(
+                    &self.config.program_id,
+                    proof_account,
+                    BufferRegion::PublicInputs,
+                    offset as u16,
+                    bytes,
+                )
+            })
+            .collect())
+    }
+
+    /// Send phase transactions until the state account reports a result
+    ///
+    /// Returns the phase signatures and the compute units they consumed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(state = %state_account)))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn run_phases(
+        &self,
+        payer: &dyn Signer,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        shards: &[Pubkey],
+        vk_account: &Pubkey,
+        is_zk: bool,
+        num_pi: usize,
+    ) -> Result<(Vec<Signature>, u64)> {
+        let mut signatures = Vec::new();
+        let mut total_cus = 0u64;
+
+        #[cfg(feature = "jito")]
+        if let Some(jito) = &self.config.jito {
+            if let Err(e) = self.run_phases_bundled(
+                payer,
+                jito,
+                state_account,
+                proof_account,
+                shards,
+                vk_account,
+                is_zk,
+                num_pi,
+                &mut signatures,
+                &mut total_cus,
+            ) {
+                if !e.is_retryable() {
+                    return Err(e);
+                }
+                log::warn!("Bundles did not land ({}), sending phases one by one", e);
+            }
+        }
+
+        let nonce = if self.config.durable_nonce {
+            let (nonce, sig) = self.create_nonce_account(payer)?;
+            self.update_session(|session| session.set_nonce(Some(nonce)));
+            signatures.push(sig);
+            Some(nonce)
+        } else {
+            None
+        };
+        let result = self.send_phases(
+            payer,
+            state_account,
+            proof_account,
+            shards,
+            vk_account,
+            is_zk,
+            num_pi,
+            nonce.as_ref(),
+            &mut signatures,
+            &mut total_cus,
+        );
+        if let Some(nonce) = nonce {
+            match self.close_nonce_account(payer, &nonce) {
+                Ok(sig) => {
+                    self.update_session(|session| session.set_nonce(None));
+                    signatures.push(sig);
+                }
+                Err(e) => log::warn!("Failed to close nonce account {}: {:?}", nonce, e),
+            }
+        }
+        result.map(|()| (signatures, total_cus))
+    }
+
+    /// Send phase transactions one at a time until the state has a result
+    #[allow(clippy::too_many_arguments)]
+    fn send_phases(
+        &self,
+        payer: &dyn Signer,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        shards: &[Pubkey],
+        vk_account: &Pubkey,
+        is_zk: bool,
+        num_pi: usize,
+        nonce: Option<&Pubkey>,
+        signatures: &mut Vec<Signature>,
+        total_cus: &mut u64,
+    ) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            let state = self
+                .fetch_account(state_account)
+                .map_err(|_| VerifierError::StateAccountNotFound)?
+                .data;
+            // log_n reads as 0 until Phase 1 has run, which Phase 1 does not need
+            let plan = plonk_solana_core::cu_estimate(state[3], is_zk, num_pi);
+            let step = next_step(&state, &plan)?;
+
+            let Some(ix) = step
+                .instruction(
+                    &self.config.program_id,
+                    state_account,
+                    proof_account,
+                    vk_account,
+                )
+                .map(|ix| instructions::with_shards(ix, shards))
+            else {
+                return match step {
+                    PhaseStep::Done => Ok(()),
+                    _ => Err(VerifierError::VerificationFailed),
+                };
+            };
+
+            log::debug!("Phase step {:?}", step);
+            match self.execute_phase(step, payer, ix, false, &plan, nonce) {
+                Ok((sig, cus)) => {
+                    signatures.push(sig);
+                    *total_cus += cus;
+                    attempt = 0;
+                }
+                // Re-read the state before retrying: the step may have landed
+                Err(e) if attempt < self.config.max_retries && e.is_retryable() => {
+                    attempt += 1;
+                    self.record(|m| m.retried("phase"));
+                    log::warn!(
+                        "{:?} failed ({}/{}): {}",
+                        step,
+                        attempt,
+                        self.config.max_retries,
+                        e
+                    );
+                    thread::sleep(Duration::from_millis(500 * attempt as u64));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Run `f`, retrying transient failures up to `config.max_retries` times
+    ///
+    /// Proof rejections are returned straight away; retrying cannot change
+    /// them.
+    pub(crate) fn with_retries<T>(&self, mut f: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            match f() {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.config.max_retries && e.is_retryable() => {
+                    attempt += 1;
+                    self.record(|m| m.retried("transaction"));
+                    log::warn!(
+                        "Retrying after error ({}/{}): {}",
+                        attempt,
+                        self.config.max_retries,
+                        e
+                    );
+                    thread::sleep(Duration::from_millis(500 * attempt as u64));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Upload proof chunks, up to `config.upload_concurrency` at a time
+    ///
+    /// Chunks write disjoint ranges and set their own bit in the buffer's
+    /// chunk bitmap, so they can land in any order. `public_input_uploads`
+    /// join the same queue ahead of them.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(chunks = chunks.len())))]
+    pub(crate) fn upload_proof_chunks(
+        &self,
+        payer: &(dyn Signer + Sync),
+        proof_account: &Pubkey,
+        public_input_uploads: Vec<Instruction>,
+        chunks: &[(usize, &[u8])],
+    ) -> Result<Vec<Signature>> {
+        let mut uploads = public_input_uploads;
+        uploads.extend(chunks.iter().map(|&(offset, chunk_data)| {
+            instructions::upload_chunk(
+                &self.config.program_id,
+                proof_account,
+                offset as u16,
+                chunk_data,
+            )
+        }));
+        self.send_uploads(payer, &uploads)
+    }
+
+    /// Send independent upload instructions, one per transaction, up to
+    /// `config.upload_concurrency` at a time
+    ///
+    /// Each worker pulls the next unsent upload until none are left; an
+    /// upload that still fails after its retries stops the other workers
+    /// and fails the whole upload. Signatures are returned in upload order.
+    pub(crate) fn send_uploads(
+        &self,
+        payer: &(dyn Signer + Sync),
+        uploads: &[Instruction],
+    ) -> Result<Vec<Signature>> {
+        let next = AtomicUsize::new(0);
+        let uploaded = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let workers = self
+            .config
+            .upload_concurrency
+            .clamp(1, uploads.len().max(1));
+
+        let mut results: Vec<(usize, Result<Signature>)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        while !failed.load(Ordering::Relaxed) {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(ix) = uploads.get(index) else {
+                                break;
+                            };
+                            let result = self.with_retries(|| {
+                                self.send_and_confirm(payer, &[], vec![ix.clone()], true)
+                            });
+                            match result {
+                                Ok(_) => {
+                                    let done = uploaded.fetch_add(1, Ordering::Relaxed) + 1;
+                                    self.notify(|o| o.chunks_uploaded(done, uploads.len()));
+                                }
+                                Err(_) => failed.store(true, Ordering::Relaxed),
+                            }
+                            done.push((index, result));
+                        }
+                        done
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("upload worker panicked"))
+                .collect()
+        });
+
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
     fn get_log_n(&self, state_account: &Pubkey) -> Result<u8> {
         let state = self.get_verification_state(state_account)?;
-        Ok(state.log_n)
+        Ok(state.status.log_n)
     }
 
-    fn execute_phase(
+    /// Send one phase transaction, returning its signature and consumed CUs
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(step = %step)))]
+    pub(crate) fn execute_phase(
         &self,
-        payer: &Keypair,
+        step: PhaseStep,
+        payer: &dyn Signer,
         instruction: solana_sdk::instruction::Instruction,
         skip_preflight: bool,
+        plan: &PhasePlan,
+        nonce: Option<&Pubkey>,
     ) -> Result<(Signature, u64)> {
-        let cu_ix = set_compute_unit_limit(self.config.compute_unit_limit);
-        let instructions = vec![cu_ix, instruction];
+        self.notify(|o| o.phase_started(&step.to_string()));
+        let cu_limit = self.phase_cu_limit(plan, &instruction);
+        let sig = self.send_with_budget(
+            payer,
+            &[],
+            vec![instruction],
+            skip_preflight,
+            Some(cu_limit),
+            nonce,
+        )?;
 
-        let sig = self.send_and_confirm(payer, &[], instructions, skip_preflight)?;
+        let cus = self.transaction_cus(&sig);
+        self.notify(|o| o.compute_units_consumed(&sig, cus));
+        self.record(|m| m.phase_compute_units(step.label(), cus));
+        Ok((sig, cus))
+    }
 
+    /// Compute units a confirmed transaction consumed (0 if unavailable)
+    pub(crate) fn transaction_cus(&self, sig: &Signature) -> u64 {
+        self.transaction_meta(sig)
+            .and_then(|m| m.compute_units_consumed.into())
+            .unwrap_or(0)
+    }
+
+    /// Status metadata (logs, CUs) of a confirmed transaction
+    fn transaction_meta(&self, sig: &Signature) -> Option<UiTransactionStatusMeta> {
         // Get CUs from transaction - use default encoding config
         let config = solana_rpc_client_api::config::RpcTransactionConfig {
             encoding: Some(solana_rpc_client_api::config::UiTransactionEncoding::Json),
             commitment: Some(solana_commitment_config::CommitmentConfig::confirmed()),
             max_supported_transaction_version: Some(0),
         };
-        let tx_details = self.client.get_transaction_with_config(&sig, config);
-
-        let cus = tx_details
+        self.client()
+            .get_transaction_with_config(sig, config)
             .ok()
             .and_then(|t| t.transaction.meta)
-            .and_then(|m| m.compute_units_consumed.into())
-            .unwrap_or(0);
+    }
 
-        Ok((sig, cus))
+    /// Compute unit limit to request for a phase instruction
+    pub(crate) fn phase_cu_limit(&self, plan: &PhasePlan, instruction: &Instruction) -> u32 {
+        match self.config.fees.cu_limit_strategy {
+            CuLimitStrategy::Fixed => self.config.compute_unit_limit,
+            CuLimitStrategy::Estimated => fees::phase_cu_estimate(plan, instruction)
+                .map(PhasePlan::compute_unit_limit)
+                .unwrap_or(self.config.compute_unit_limit),
+        }
     }
 
-    fn send_and_confirm(
+    pub(crate) fn send_and_confirm(
         &self,
-        payer: &Keypair,
-        additional_signers: &[&Keypair],
+        payer: &dyn Signer,
+        additional_signers: &[&dyn Signer],
         instructions: Vec<solana_sdk::instruction::Instruction>,
         skip_preflight: bool,
     ) -> Result<Signature> {
-        let recent_blockhash = self.client.get_latest_blockhash()?;
+        self.send_with_budget(
+            payer,
+            additional_signers,
+            instructions,
+            skip_preflight,
+            None,
+            None,
+        )
+    }
 
-        let mut signers: Vec<&Keypair> = vec![payer];
-        signers.extend(additional_signers);
+    /// Compute budget instructions to prepend to `instructions`
+    fn budget_instructions(
+        &self,
+        instructions: &[Instruction],
+        cu_limit: Option<u32>,
+    ) -> Result<Vec<Instruction>> {
+        let mut budget = Vec::new();
+        if let Some(units) = cu_limit {
+            budget.push(fees::set_compute_unit_limit(units));
+            if let Some(bytes) = self.config.fees.heap_frame_bytes {
+                budget.push(fees::request_heap_frame(bytes));
+            }
+        }
+
+        let price = match self.config.fees.priority_fee_strategy {
+            PriorityFeeStrategy::None => None,
+            PriorityFeeStrategy::Fixed(price) => Some(price),
+            PriorityFeeStrategy::Dynamic {
+                percentile,
+                max_micro_lamports,
+            } => {
+                let mut writable: Vec<Pubkey> = instructions
+                    .iter()
+                    .flat_map(|ix| &ix.accounts)
+                    .filter(|meta| meta.is_writable)
+                    .map(|meta| meta.pubkey)
+                    .collect();
+                writable.sort();
+                writable.dedup();
+                let recent = self.client().get_recent_prioritization_fees(&writable)?;
+                let fee = fees::fee_percentile(
+                    recent.iter().map(|f| f.prioritization_fee).collect(),
+                    percentile,
+                );
+                Some(fee.min(max_micro_lamports))
+            }
+        };
+        if let Some(price) = price.filter(|&p| p > 0) {
+            budget.push(fees::set_compute_unit_price(price));
+        }
+
+        Ok(budget)
+    }
 
-        let tx = Transaction::new_signed_with_payer(
+    /// Sign and send with compute budget instructions from `config.fees`
+    ///
+    /// With a `nonce` account the transaction is signed against its stored
+    /// blockhash instead of a recent one.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub(crate) fn send_with_budget(
+        &self,
+        payer: &dyn Signer,
+        additional_signers: &[&dyn Signer],
+        instructions: Vec<solana_sdk::instruction::Instruction>,
+        skip_preflight: bool,
+        cu_limit: Option<u32>,
+        nonce: Option<&Pubkey>,
+    ) -> Result<Signature> {
+        let recent_blockhash = match nonce {
+            Some(nonce) => self.nonce_blockhash(nonce)?,
+            None => self.latest_blockhash()?,
+        };
+        let tx = self.build_transaction(
+            payer,
+            additional_signers,
             &instructions,
-            Some(&payer.pubkey()),
-            &signers,
+            cu_limit,
             recent_blockhash,
-        );
+            nonce,
+        )?;
 
         let config = solana_client::rpc_config::RpcSendTransactionConfig {
             skip_preflight,
             ..Default::default()
         };
 
-        let sig = self.client.send_transaction_with_config(&tx, config)?;
+        let sig = self
+            .rpc_call(RpcOperation::Send, |rpc| {
+                rpc.send_transaction_with_config(&tx, config)
+            })
+            .map_err(VerifierError::from_client_error)?;
+        self.notify(|o| o.transaction_sent(&sig));
+        self.record(|m| m.transaction_sent());
+        self.wait_for_confirmation(&sig)?;
+        self.notify(|o| o.transaction_confirmed(&sig));
+        Ok(sig)
+    }
 
-        // Poll for confirmation - matches test_phased.rs approach
-        // 30 attempts × 200ms = 6 second timeout per TX
-        for _ in 0..30 {
-            thread::sleep(Duration::from_millis(200));
-            match self.client.get_signature_status(&sig)? {
-                Some(result) => {
-                    if let Err(e) = result {
-                        return Err(VerifierError::TransactionFailed(e.to_string()));
-                    }
-                    return Ok(sig);
-                }
-                None => continue,
+    /// Build and sign a transaction, prepending budget instructions
+    ///
+    /// Budget instructions are dropped when they would push the transaction
+    /// over the size limit; full 1020-byte chunk uploads have no room left
+    /// for them. With `config.lookup_table` set the message is compiled as
+    /// v0 against that table. A `nonce` account is advanced by the first
+    /// instruction, as the runtime requires, and `recent_blockhash` must be
+    /// its stored value.
+    pub(crate) fn build_transaction(
+        &self,
+        payer: &dyn Signer,
+        additional_signers: &[&dyn Signer],
+        instructions: &[Instruction],
+        cu_limit: Option<u32>,
+        recent_blockhash: Hash,
+        nonce: Option<&Pubkey>,
+    ) -> Result<VersionedTransaction> {
+        let message = self.compile_message(
+            &payer.pubkey(),
+            instructions,
+            cu_limit,
+            recent_blockhash,
+            nonce,
+        )?;
+
+        let mut signers: Vec<&dyn Signer> = vec![payer];
+        signers.extend(additional_signers);
+        VersionedTransaction::try_new(message, &signers)
+            .map_err(|e| VerifierError::TransactionFailed(e.to_string()))
+    }
+
+    /// Compile the message [`Self::build_transaction`] signs
+    pub(crate) fn compile_message(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        cu_limit: Option<u32>,
+        recent_blockhash: Hash,
+        nonce: Option<&Pubkey>,
+    ) -> Result<VersionedMessage> {
+        let advance: Vec<Instruction> = nonce
+            .map(|nonce| system_instruction::advance_nonce_account(nonce, payer))
+            .into_iter()
+            .collect();
+        let mut budgeted = advance.clone();
+        budgeted.extend(self.budget_instructions(instructions, cu_limit)?);
+        budgeted.extend_from_slice(instructions);
+        let mut unbudgeted = advance;
+        unbudgeted.extend_from_slice(instructions);
+
+        let lookup_tables: Vec<AddressLookupTableAccount> =
+            self.lookup_table_account()?.into_iter().collect();
+        let compile = |ixs: &[Instruction]| -> Result<VersionedMessage> {
+            if lookup_tables.is_empty() {
+                return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+                    ixs,
+                    Some(payer),
+                    &recent_blockhash,
+                )));
             }
-        }
+            v0::Message::try_compile(payer, ixs, &lookup_tables, recent_blockhash)
+                .map(VersionedMessage::V0)
+                .map_err(|e| VerifierError::TransactionFailed(e.to_string()))
+        };
 
-        Err(VerifierError::ConfirmationTimeout)
+        let message = compile(&budgeted)?;
+        let tx_size =
+            1 + 64 * message.header().num_required_signatures as usize + message.serialize().len();
+        if tx_size <= self.config.max_transaction_size {
+            return Ok(message);
+        }
+        log::debug!(
+            "Skipping compute budget instructions ({} byte transaction)",
+            tx_size
+        );
+        compile(&unbudgeted)
     }
 
-    fn split_into_chunks<'a>(&self, data: &'a [u8]) -> Vec<(usize, &'a [u8])> {
-        let mut chunks = Vec::new();
-        let mut offset = 0;
-        while offset < data.len() {
-            let chunk_size = std::cmp::min(self.config.chunk_size, data.len() - offset);
-            chunks.push((offset, &data[offset..offset + chunk_size]));
-            offset += chunk_size;
+    /// Poll until `sig` is confirmed or fails
+    pub(crate) fn wait_for_confirmation(&self, sig: &Signature) -> Result<()> {
+        // A notification means the status below is already available
+        let notified = match &self.config.websocket_url {
+            Some(url) => self.await_signature(url, sig)?,
+            None => false,
+        };
+
+        // Poll for confirmation - matches test_phased.rs approach
+        let deadline = Instant::now() + CONFIRMATION_TIMEOUT;
+        loop {
+            if !notified {
+                thread::sleep(POLL_INTERVAL);
+            }
+            match self.rpc_call(RpcOperation::Confirm, |rpc| rpc.get_signature_status(sig))? {
+                Some(Ok(())) => return Ok(()),
+                Some(Err(e)) => {
+                    // Landed but failed: fetch its logs to explain why
+                    let meta = self.transaction_meta(sig);
+                    let logs: Vec<String> = meta
+                        .as_ref()
+                        .and_then(|m| m.log_messages.clone().into())
+                        .unwrap_or_default();
+                    let cus = meta.and_then(|m| m.compute_units_consumed.into());
+                    return Err(VerifierError::from_transaction_failure(&e, &logs, cus));
+                }
+                None if Instant::now() < deadline => continue,
+                None => return Err(VerifierError::ConfirmationTimeout),
+            }
         }
-        chunks
     }
 }
+
+/// Split `data` into `(offset, chunk)` pieces of at most `chunk_size` bytes
+pub(crate) fn split_into_chunks(data: &[u8], chunk_size: usize) -> Vec<(usize, &[u8])> {
+    data.chunks(chunk_size.max(1))
+        .enumerate()
+        .map(|(i, chunk)| (i * chunk_size.max(1), chunk))
+        .collect()
+}
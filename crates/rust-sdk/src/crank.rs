@@ -0,0 +1,390 @@
+//! Third-party cranking of phased verifications
+//!
+//! Phase instructions take no signer, so once a proof is uploaded and
+//! Phase 1 has bound the VK to the state account, anyone can send the
+//! remaining phases and the receipt. [`SolanaNoirVerifier::submit_verification`]
+//! stops there, optionally tipping a cranker in the Phase 1 transaction, and
+//! a cranker service finishes the job:
+//!
+//! ```ignore
+//! for pending in verifier.find_pending_verifications()? {
+//!     let Some(job) = verifier.find_crank_job(&pending, &cranker.pubkey())? else { continue };
+//!     while let CrankOutcome::Advanced { .. } | CrankOutcome::ReceiptCreated { .. } =
+//!         verifier.advance_verification(&cranker, &job)?
+//!     {}
+//! }
+//! ```
+//!
+//! The state account does not record its proof buffer, so the cranker reads
+//! it from the setup transaction in the state's history.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    instructions,
+    phases::{next_step, PhaseStep},
+    rpc::RpcOperation,
+    sweep::setup_accounts,
+    types::*,
+};
+use plonk_solana_core::proof::Proof;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    config::{UiAccountEncoding, UiTransactionEncoding},
+    filter::RpcFilterType,
+};
+use solana_sdk::{
+    message::compiled_instruction::CompiledInstruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use solana_system_interface::{instruction as system_instruction, program as system_program};
+
+/// Transactions of a state account searched for its setup; a verification
+/// sends a few dozen at most
+const HISTORY_LIMIT: usize = 1000;
+
+// Program `Phase` values handled by a cranker
+const PHASE_CHALLENGES_GENERATED: u8 = 2;
+const PHASE_MSM_COMPUTED: u8 = 6;
+const PHASE_COMPLETE: u8 = 7;
+
+/// Offset of `vk_account` in the state account
+const STATE_VK_ACCOUNT_OFFSET: usize = 8;
+
+impl SolanaNoirVerifier {
+    /// Upload a proof and run Phase 1, leaving the rest to a cranker
+    ///
+    /// With a `tip` of `(cranker, lamports)` the Phase 1 transaction also
+    /// transfers `lamports` to the cranker, where
+    /// [`Self::find_crank_job`] finds it. The receipt appears once a cranker
+    /// has finished; wait for it with
+    /// [`wait_for_receipt`](Self::wait_for_receipt). The cranker pays the
+    /// receipt rent, and the state and proof buffer stay open for `payer`
+    /// to close later.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(vk = %vk_account)))]
+    pub fn submit_verification(
+        &self,
+        payer: &(dyn Signer + Sync),
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+        tip: Option<(&Pubkey, u64)>,
+    ) -> Result<SubmittedVerification> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        if !is_zk {
+            return Err(VerifierError::UnsupportedProof(
+                "non-ZK proofs cannot be verified on-chain; generate the proof with ZK enabled"
+                    .to_string(),
+            ));
+        }
+
+        let upload = self.upload_proof(payer, None, proof, public_inputs, false)?;
+        let mut signatures = upload.signatures;
+
+        let step = PhaseStep::Challenges;
+        let phase1 = step
+            .instruction(
+                &self.config.program_id,
+                &upload.state_account,
+                &upload.proof_account,
+                vk_account,
+            )
+            .expect("Phase 1 has an instruction");
+        // log_n is only known after Phase 1, which does not depend on it
+        let plan = plonk_solana_core::cu_estimate(0, is_zk, public_inputs.len() / 32);
+        let cu_limit = self.phase_cu_limit(&plan, &phase1);
+        let mut ixs = vec![phase1];
+        if let Some((cranker, lamports)) = tip.filter(|&(_, lamports)| lamports > 0) {
+            ixs.push(system_instruction::transfer(
+                &payer.pubkey(),
+                cranker,
+                lamports,
+            ));
+        }
+
+        self.notify(|o| o.phase_started(&step.to_string()));
+        let sig = self.send_with_budget(payer, &[], ixs, false, Some(cu_limit), None)?;
+        signatures.push(sig);
+
+        Ok(SubmittedVerification {
+            state_account: upload.state_account,
+            proof_account: upload.proof_account,
+            signatures,
+        })
+    }
+
+    /// State accounts past Phase 1 that may still need transactions
+    ///
+    /// Includes verified states, whose receipt may be missing; rejected
+    /// proofs and states still before Phase 1 are left out.
+    pub fn find_pending_verifications(&self) -> Result<Vec<PendingVerification>> {
+        let program_id = self.config.program_id;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(STATE_SIZE as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.rpc_call(RpcOperation::Fetch, |rpc| {
+            rpc.get_program_ui_accounts_with_config(&program_id, config.clone())
+        })?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(state_account, account)| {
+                pending_verification(state_account, &account.data.decode()?)
+            })
+            .collect())
+    }
+
+    /// Proof buffer, owner and tips of a pending verification
+    ///
+    /// Reads the state account's transaction history. Returns `None` when
+    /// no setup transaction is found, e.g. for states created outside the
+    /// SDK.
+    pub fn find_crank_job(
+        &self,
+        pending: &PendingVerification,
+        cranker: &Pubkey,
+    ) -> Result<Option<CrankJob>> {
+        let statuses = self.rpc_call(RpcOperation::Fetch, |rpc| {
+            rpc.get_signatures_for_address_with_config(
+                &pending.state_account,
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(HISTORY_LIMIT),
+                    ..Default::default()
+                },
+            )
+        })?;
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: None,
+            max_supported_transaction_version: Some(0),
+        };
+        let mut setup = None;
+        let mut tip_lamports = 0u64;
+        for status in statuses.iter().filter(|status| status.err.is_none()) {
+            let Ok(signature) = status.signature.parse::<Signature>() else {
+                continue;
+            };
+            let Some(tx) = self
+                .rpc_call(RpcOperation::Fetch, |rpc| {
+                    rpc.get_transaction_with_config(&signature, config)
+                })?
+                .transaction
+                .transaction
+                .decode()
+            else {
+                continue;
+            };
+            let keys = tx.message.static_account_keys();
+            let instructions = tx.message.instructions();
+            tip_lamports += transferred_to(keys, instructions, cranker);
+            if let Some((state, proof)) =
+                setup_accounts(keys, instructions, &self.config.program_id)
+            {
+                if state == pending.state_account {
+                    setup = Some((proof, keys[0]));
+                }
+            }
+        }
+
+        Ok(setup.map(|(proof_account, owner)| CrankJob {
+            state_account: pending.state_account,
+            proof_account,
+            vk_account: pending.vk_account,
+            owner,
+            tip_lamports,
+        }))
+    }
+
+    /// Send the next transaction of `job`, paid by `payer`
+    ///
+    /// Sends one phase, or once the proof verified, the receipt. Call
+    /// repeatedly until it returns [`CrankOutcome::Finished`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(state = %job.state_account)))]
+    pub fn advance_verification(&self, payer: &dyn Signer, job: &CrankJob) -> Result<CrankOutcome> {
+        let Ok(state) = self.fetch_account(&job.state_account) else {
+            return Ok(CrankOutcome::Finished { verified: false });
+        };
+        let state = state.data;
+        if state.len() < STATE_SIZE {
+            return Err(VerifierError::InvalidStateData);
+        }
+        let plan = plonk_solana_core::cu_estimate(state[3], state[4] == 1, state[5] as usize);
+        let step = next_step(&state, &plan)?;
+
+        match step {
+            // Without Phase 1 the VK is not bound yet; only its owner knows it
+            PhaseStep::Challenges => Err(VerifierError::InvalidStateData),
+            PhaseStep::Failed => Ok(CrankOutcome::Finished { verified: false }),
+            PhaseStep::Done => self.create_missing_receipt(payer, job),
+            _ => {
+                let ix = step
+                    .instruction(
+                        &self.config.program_id,
+                        &job.state_account,
+                        &job.proof_account,
+                        &job.vk_account,
+                    )
+                    .expect("phase steps have an instruction");
+                let (signature, compute_units) =
+                    self.execute_phase(step, payer, ix, false, &plan, None)?;
+                Ok(CrankOutcome::Advanced {
+                    phase: step.label(),
+                    signature,
+                    compute_units,
+                })
+            }
+        }
+    }
+
+    /// Create the receipt of a verified job, unless it exists
+    fn create_missing_receipt(&self, payer: &dyn Signer, job: &CrankJob) -> Result<CrankOutcome> {
+        let finished = CrankOutcome::Finished { verified: true };
+        let Ok(buffer) = self.fetch_account(&job.proof_account) else {
+            return Ok(finished);
+        };
+        let Some(public_inputs) = buffer_public_inputs(&buffer.data) else {
+            return Err(VerifierError::InvalidStateData);
+        };
+        if self.receipt_exists(&job.vk_account, public_inputs)? {
+            return Ok(finished);
+        }
+
+        let (receipt, _) = self.derive_receipt_pda(&job.vk_account, public_inputs);
+        let ix = instructions::create_receipt(
+            &self.config.program_id,
+            &job.state_account,
+            &job.proof_account,
+            &job.vk_account,
+            &receipt,
+            &payer.pubkey(),
+        );
+        let signature = self.send_and_confirm(payer, &[], vec![ix], false)?;
+        Ok(CrankOutcome::ReceiptCreated { receipt, signature })
+    }
+}
+
+/// The pending verification in a state account's data, if it is one
+fn pending_verification(state_account: Pubkey, data: &[u8]) -> Option<PendingVerification> {
+    if data.len() != STATE_SIZE {
+        return None;
+    }
+    let phase = data[0];
+    let pending = (PHASE_CHALLENGES_GENERATED..=PHASE_MSM_COMPUTED).contains(&phase)
+        || (phase == PHASE_COMPLETE && data[STATE_VERIFIED_OFFSET] == 1);
+    if !pending {
+        return None;
+    }
+    let vk_account = data[STATE_VK_ACCOUNT_OFFSET..STATE_VK_ACCOUNT_OFFSET + 32]
+        .try_into()
+        .map(Pubkey::new_from_array)
+        .ok()?;
+    Some(PendingVerification {
+        state_account,
+        vk_account,
+        phase,
+    })
+}
+
+/// Public inputs stored in a proof buffer
+fn buffer_public_inputs(buffer: &[u8]) -> Option<&[u8]> {
+    let num_pi = u16::from_le_bytes([*buffer.get(3)?, *buffer.get(4)?]) as usize;
+    buffer.get(BUFFER_HEADER_SIZE..BUFFER_HEADER_SIZE + num_pi * 32)
+}
+
+/// Lamports a transaction's system transfers sent to `recipient`
+fn transferred_to(
+    keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+    recipient: &Pubkey,
+) -> u64 {
+    instructions
+        .iter()
+        .filter(|ix| keys.get(ix.program_id_index as usize) == Some(&system_program::ID))
+        // SystemInstruction::Transfer: tag(4) lamports(8)
+        .filter(|ix| ix.data.len() == 12 && ix.data[..4] == [2, 0, 0, 0])
+        .filter(|ix| ix.accounts.get(1).and_then(|&i| keys.get(i as usize)) == Some(recipient))
+        .map(|ix| u64::from_le_bytes(ix.data[4..12].try_into().unwrap()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::message::Message;
+
+    #[test]
+    fn test_pending_verification_phases() {
+        let state = Pubkey::new_unique();
+        let vk = Pubkey::new_unique();
+        let mut data = vec![0u8; STATE_SIZE];
+        data[STATE_VK_ACCOUNT_OFFSET..STATE_VK_ACCOUNT_OFFSET + 32].copy_from_slice(vk.as_ref());
+
+        for phase in [0, 1, 255] {
+            data[0] = phase;
+            assert_eq!(pending_verification(state, &data), None);
+        }
+        for phase in 2..=6 {
+            data[0] = phase;
+            let pending = pending_verification(state, &data).unwrap();
+            assert_eq!((pending.vk_account, pending.phase), (vk, phase));
+        }
+
+        // Complete but rejected, then verified
+        data[0] = PHASE_COMPLETE;
+        assert_eq!(pending_verification(state, &data), None);
+        data[STATE_VERIFIED_OFFSET] = 1;
+        assert!(pending_verification(state, &data).is_some());
+
+        assert_eq!(pending_verification(state, &data[..STATE_SIZE - 1]), None);
+    }
+
+    #[test]
+    fn test_transferred_to_sums_tips() {
+        let (payer, cranker, other) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let message = Message::new(
+            &[
+                system_instruction::transfer(&payer, &cranker, 5_000),
+                system_instruction::transfer(&payer, &other, 7_000),
+                system_instruction::transfer(&payer, &cranker, 1_000),
+                system_instruction::create_account(&payer, &other, 9_000, 0, &cranker),
+            ],
+            Some(&payer),
+        );
+        assert_eq!(
+            transferred_to(&message.account_keys, &message.instructions, &cranker),
+            6_000
+        );
+        assert_eq!(
+            transferred_to(&message.account_keys, &message.instructions, &payer),
+            0
+        );
+    }
+
+    #[test]
+    fn test_buffer_public_inputs() {
+        let mut buffer = vec![0u8; BUFFER_HEADER_SIZE + 64 + 10];
+        buffer[3] = 2;
+        buffer[BUFFER_HEADER_SIZE] = 0xab;
+        let pis = buffer_public_inputs(&buffer).unwrap();
+        assert_eq!((pis.len(), pis[0]), (64, 0xab));
+        assert_eq!(
+            buffer_public_inputs(&buffer[..BUFFER_HEADER_SIZE + 10]),
+            None
+        );
+    }
+}
@@ -1,6 +1,12 @@
 //! Error types for the Solana Noir Verifier SDK
 
+use crate::types::MAX_SHARDS;
 use solana_client::client_error::ClientError;
+use solana_rpc_client_api::{
+    client_error::ErrorKind as ClientErrorKind,
+    request::{RpcError, RpcResponseErrorData},
+};
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
 use thiserror::Error;
 
 /// Errors that can occur during verification
@@ -9,12 +15,27 @@ pub enum VerifierError {
     #[error("Invalid proof size: expected {expected}, got {actual}")]
     InvalidProofSize { expected: usize, actual: usize },
 
+    #[error("Unsupported proof: {0}")]
+    UnsupportedProof(String),
+
     #[error("Invalid VK size: expected {expected}, got {actual}")]
     InvalidVkSize { expected: usize, actual: usize },
 
+    #[error("Invalid SRS G2 point")]
+    InvalidSrsG2,
+
     #[error("Public inputs too large: {size} bytes (max ~{max_size})")]
     PublicInputsTooLarge { size: usize, max_size: usize },
 
+    #[error("Invalid public input: {0}")]
+    InvalidPublicInput(String),
+
+    #[error("Invalid Noir artifact: {0}")]
+    NoirArtifact(String),
+
+    #[error("Invalid shard count: {0} (must be 1 to {max})", max = MAX_SHARDS)]
+    InvalidShardCount(usize),
+
     #[error("State account not found")]
     StateAccountNotFound,
 
@@ -24,17 +45,304 @@ pub enum VerifierError {
     #[error("Receipt not found")]
     ReceiptNotFound,
 
+    #[error("Cannot attest receipt: {0}")]
+    Attestation(String),
+
+    #[error("Session does not match its accounts: {0}")]
+    SessionMismatch(String),
+
+    #[error("Session file error: {0}")]
+    SessionFile(String),
+
+    #[error("VK registry error: {0}")]
+    Registry(String),
+
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
 
+    /// A transaction failed on chain (or in preflight simulation)
+    ///
+    /// `instruction` indexes the transaction's instructions, including any
+    /// compute budget instructions the SDK prepended.
+    #[error("Instruction {instruction} failed on chain: {error}")]
+    OnChain {
+        instruction: u8,
+        error: OnChainVerifyError,
+        /// Compute units consumed before the failure, when the RPC reports them
+        compute_units: Option<u64>,
+    },
+
+    #[cfg(feature = "jito")]
+    #[error("Block engine rejected bundle: {0}")]
+    BundleRejected(String),
+
+    #[cfg(feature = "prover")]
+    #[error("Proof generation failed: {0}")]
+    Prover(String),
+
+    #[error("Insufficient funds: need {needed} lamports, have {available}")]
+    InsufficientFunds { needed: u64, available: u64 },
+
     #[error("Transaction confirmation timeout")]
     ConfirmationTimeout,
 
+    #[error("Websocket error: {0}")]
+    Websocket(String),
+
     #[error("RPC error: {0}")]
     RpcError(#[from] ClientError),
 
     #[error("Verification failed")]
     VerificationFailed,
+
+    #[error("Proof failed local verification: {0}")]
+    LocalVerification(plonk_solana_core::VerifyError),
 }
 
+impl VerifierError {
+    /// Map a failed transaction to a [`VerifierError`]
+    ///
+    /// Custom errors raised by the verifier program are decoded into
+    /// [`OnChainVerifyError::Program`]; anything else is kept as text.
+    pub fn from_transaction_error(err: &TransactionError) -> Self {
+        Self::from_transaction_failure(err, &[], None)
+    }
+
+    /// Map a failed transaction and its logs to a [`VerifierError`]
+    ///
+    /// Instruction errors become [`VerifierError::OnChain`]: verifier error
+    /// codes are decoded, running out of compute units is recognised from
+    /// the error or the logs, and other errors keep the last message the
+    /// program logged. Errors outside any instruction (fees, blockhash) are
+    /// kept as text.
+    pub fn from_transaction_failure(
+        err: &TransactionError,
+        logs: &[String],
+        compute_units: Option<u64>,
+    ) -> Self {
+        let TransactionError::InstructionError(instruction, ix_error) = err else {
+            return VerifierError::TransactionFailed(err.to_string());
+        };
+        let out_of_compute = logs.iter().any(|line| line.contains("exceeded CUs meter"));
+
+        let error = match ix_error {
+            InstructionError::Custom(code) if ProgramErrorCode::from_code(*code).is_some() => {
+                OnChainVerifyError::Program(ProgramErrorCode::from_code(*code).unwrap())
+            }
+            InstructionError::ComputationalBudgetExceeded => {
+                OnChainVerifyError::ComputeBudgetExceeded
+            }
+            _ if out_of_compute => OnChainVerifyError::ComputeBudgetExceeded,
+            other => OnChainVerifyError::Instruction {
+                error: other.clone(),
+                log: failure_log(logs),
+            },
+        };
+        VerifierError::OnChain {
+            instruction: *instruction,
+            error,
+            compute_units,
+        }
+    }
+
+    /// Decode a failed RPC call, keeping simulation logs and CUs
+    ///
+    /// Preflight failures carry the simulated transaction's error, logs and
+    /// compute units; other RPC errors are wrapped unchanged.
+    pub fn from_client_error(err: ClientError) -> Self {
+        if let ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(simulation),
+            ..
+        }) = err.kind()
+        {
+            if let Some(tx_err) = err.get_transaction_error() {
+                if matches!(tx_err, TransactionError::InstructionError(..)) {
+                    return Self::from_transaction_failure(
+                        &tx_err,
+                        simulation.logs.as_deref().unwrap_or_default(),
+                        simulation.units_consumed,
+                    );
+                }
+            }
+        }
+        VerifierError::RpcError(err)
+    }
+
+    /// Whether resending the transaction may succeed
+    ///
+    /// True for timeouts, RPC failures and program errors other than proof
+    /// rejections (e.g. a phase that already ran because an earlier attempt
+    /// landed after its confirmation timed out).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            VerifierError::ConfirmationTimeout | VerifierError::TransactionFailed(_) => true,
+            #[cfg(feature = "jito")]
+            VerifierError::BundleRejected(_) => true,
+            VerifierError::OnChain { error, .. } => error.is_retryable(),
+            VerifierError::RpcError(_) => self
+                .program_error()
+                .is_none_or(|code| !code.is_proof_rejection()),
+            _ => false,
+        }
+    }
+
+    /// The program error code, if this error came from the verifier program
+    pub fn program_error(&self) -> Option<ProgramErrorCode> {
+        match self {
+            VerifierError::OnChain { error, .. } => error.program_error(),
+            VerifierError::RpcError(e) => match e.get_transaction_error()? {
+                TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                    ProgramErrorCode::from_code(code)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Why an instruction failed on chain
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum OnChainVerifyError {
+    /// The verifier program returned one of its error codes
+    #[error("{0}")]
+    Program(ProgramErrorCode),
+
+    /// The transaction ran out of compute units
+    #[error("compute budget exceeded")]
+    ComputeBudgetExceeded,
+
+    /// Any other instruction error, with the program's last log message
+    #[error("{error}{}", log.as_ref().map(|log| format!(" ({log})")).unwrap_or_default())]
+    Instruction {
+        error: InstructionError,
+        log: Option<String>,
+    },
+}
+
+impl OnChainVerifyError {
+    /// The verifier error code, if the program returned one
+    pub fn program_error(&self) -> Option<ProgramErrorCode> {
+        match self {
+            OnChainVerifyError::Program(code) => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Whether resending may succeed
+    ///
+    /// Proof rejections are final, and a transaction that ran out of
+    /// compute units will again with the same budget.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            OnChainVerifyError::Program(code) => !code.is_proof_rejection(),
+            OnChainVerifyError::ComputeBudgetExceeded => false,
+            OnChainVerifyError::Instruction { .. } => true,
+        }
+    }
+}
+
+/// The last `msg!` line in a transaction's logs
+fn failure_log(logs: &[String]) -> Option<String> {
+    logs.iter()
+        .rev()
+        .find_map(|line| line.strip_prefix("Program log: "))
+        .map(str::to_string)
+}
+
+/// Error codes returned by the verifier program as `ProgramError::Custom`
+///
+/// The program's own error type, from `solana-noir-verifier-errors`, so
+/// codes and messages match what the program logs.
+pub use solana_noir_verifier_errors::VerifierError as ProgramErrorCode;
+
 pub type Result<T> = std::result::Result<T, VerifierError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_error_codes_roundtrip() {
+        for code in (0..2000).chain([1255, 1256, u32::MAX]) {
+            if let Some(e) = ProgramErrorCode::from_code(code) {
+                assert_eq!(e.code(), code);
+            }
+        }
+        assert_eq!(
+            ProgramErrorCode::from_code(1004),
+            Some(ProgramErrorCode::SumcheckRoundFailed(4))
+        );
+        assert_eq!(ProgramErrorCode::from_code(1256), None);
+    }
+
+    #[test]
+    fn test_from_transaction_error() {
+        let err = TransactionError::InstructionError(1, InstructionError::Custom(504));
+        let mapped = VerifierError::from_transaction_error(&err);
+        assert!(matches!(
+            mapped,
+            VerifierError::OnChain {
+                instruction: 1,
+                error: OnChainVerifyError::Program(ProgramErrorCode::PairingFailed),
+                compute_units: None,
+            }
+        ));
+        assert_eq!(
+            mapped.program_error(),
+            Some(ProgramErrorCode::PairingFailed)
+        );
+
+        let other = TransactionError::AccountNotFound;
+        assert!(matches!(
+            VerifierError::from_transaction_error(&other),
+            VerifierError::TransactionFailed(_)
+        ));
+    }
+
+    #[test]
+    fn test_from_transaction_failure_reads_logs() {
+        let logs: Vec<String> = [
+            "Program ComputeBudget111111111111111111111111111111 success",
+            "Program log: Proof buffer not ready: status=1",
+            "Program Verifier111 failed: invalid account data for instruction",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        let err = TransactionError::InstructionError(1, InstructionError::InvalidAccountData);
+        let mapped = VerifierError::from_transaction_failure(&err, &logs, Some(4_321));
+        let VerifierError::OnChain {
+            instruction,
+            error,
+            compute_units,
+        } = &mapped
+        else {
+            panic!("expected OnChain, got {mapped:?}");
+        };
+        assert_eq!((*instruction, *compute_units), (1, Some(4_321)));
+        assert_eq!(
+            error,
+            &OnChainVerifyError::Instruction {
+                error: InstructionError::InvalidAccountData,
+                log: Some("Proof buffer not ready: status=1".to_string()),
+            }
+        );
+        assert!(mapped.is_retryable());
+
+        let exhausted = vec![
+            "Program Verifier111 consumed 1400000 of 1400000 compute units".to_string(),
+            "Program Verifier111 failed: exceeded CUs meter at BPF instruction".to_string(),
+        ];
+        let err = TransactionError::InstructionError(2, InstructionError::ProgramFailedToComplete);
+        let mapped = VerifierError::from_transaction_failure(&err, &exhausted, None);
+        assert!(matches!(
+            mapped,
+            VerifierError::OnChain {
+                error: OnChainVerifyError::ComputeBudgetExceeded,
+                ..
+            }
+        ));
+        assert!(!mapped.is_retryable());
+    }
+}
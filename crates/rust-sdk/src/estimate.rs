@@ -0,0 +1,372 @@
+//! Up-front cost estimates for a verification
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    fees,
+    phases::PhaseStep,
+    rpc::RpcOperation,
+    stream::{fit_chunk_size, fit_public_input_chunk_size, upload_carries_fee, whole_slots},
+    types::*,
+};
+use plonk_solana_core::{
+    proof::{Proof, CONST_PROOF_SIZE_LOG_N},
+    PhasePlan,
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// Base fee per signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Runtime CU limit for an instruction when no `SetComputeUnitLimit` is sent
+const DEFAULT_INSTRUCTION_CU_LIMIT: u64 = 200_000;
+
+/// Public input bytes that still fit in the account setup transaction
+/// (mirrors `setup_instructions`)
+const PI_BUNDLE_THRESHOLD: usize = 800;
+
+/// Rent-exempt deposits for the accounts a verification creates
+struct Rents {
+    buffer: u64,
+    state: u64,
+    receipt: u64,
+}
+
+impl SolanaNoirVerifier {
+    /// Estimate what verifying a proof will cost before starting
+    ///
+    /// Follows the transaction layout of
+    /// [`verify_phased`](Self::verify_phased): account setup, chunk uploads,
+    /// phases, receipt and close. The proof does not encode its circuit
+    /// size, so the report is for the largest circuit the proof format
+    /// supports (`log_n = 28`); smaller circuits need fewer sumcheck
+    /// transactions and CUs. Priority fees use the configured
+    /// [`FeeConfig`], querying recent fees for the `Dynamic` strategy.
+    /// Uploads pay a priority fee when their chunks are fitted to the
+    /// transaction size and a whole upload bitmap slot still leaves room
+    /// for it; a fixed [`VerifierConfig::chunk_size`] is assumed to leave
+    /// none.
+    pub fn estimate(
+        &self,
+        proof_len: usize,
+        num_public_inputs: usize,
+        is_zk: bool,
+    ) -> Result<CostReport> {
+        self.estimate_for_circuit(
+            CONST_PROOF_SIZE_LOG_N as u8,
+            proof_len,
+            num_public_inputs,
+            is_zk,
+        )
+    }
+
+    /// [`estimate`](Self::estimate) for a circuit of known size, e.g. the
+    /// `log2_circuit_size` of its VK
+    pub fn estimate_for_circuit(
+        &self,
+        log_n: u8,
+        proof_len: usize,
+        num_public_inputs: usize,
+        is_zk: bool,
+    ) -> Result<CostReport> {
+        let buffer_size = BUFFER_HEADER_SIZE + num_public_inputs * 32 + proof_len;
+        let rents = Rents {
+            buffer: self
+                .client()
+                .get_minimum_balance_for_rent_exemption(buffer_size)?,
+            state: self
+                .client()
+                .get_minimum_balance_for_rent_exemption(STATE_SIZE)?,
+            receipt: self
+                .client()
+                .get_minimum_balance_for_rent_exemption(RECEIPT_SIZE)?,
+        };
+
+        let price = match self.config.fees.priority_fee_strategy {
+            PriorityFeeStrategy::None => 0,
+            PriorityFeeStrategy::Fixed(price) => price,
+            PriorityFeeStrategy::Dynamic {
+                percentile,
+                max_micro_lamports,
+            } => {
+                let recent = self
+                    .client()
+                    .get_recent_prioritization_fees(&[self.config.program_id])?;
+                fees::fee_percentile(
+                    recent.iter().map(|f| f.prioritization_fee).collect(),
+                    percentile,
+                )
+                .min(max_micro_lamports)
+            }
+        };
+
+        let plan = plonk_solana_core::cu_estimate(log_n, is_zk, num_public_inputs);
+        Ok(cost_report(
+            &self.config,
+            &plan,
+            proof_len,
+            num_public_inputs,
+            price,
+            &rents,
+        ))
+    }
+
+    /// Check that `payer` can afford to verify `proof` against `vk_account`
+    ///
+    /// Estimates for the VK's circuit size and returns the report, or
+    /// [`VerifierError::InsufficientFunds`] so a verification fails before
+    /// its first transaction rather than partway through the proof upload.
+    pub fn check_balance(
+        &self,
+        payer: &Pubkey,
+        vk_account: &Pubkey,
+        proof: &[u8],
+        public_inputs: &[u8],
+    ) -> Result<CostReport> {
+        let (vk, _) = self.load_vk(vk_account)?;
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        let report = self.estimate_for_circuit(
+            vk.log2_circuit_size as u8,
+            proof.len(),
+            public_inputs.len() / 32,
+            is_zk,
+        )?;
+
+        let available = self.rpc_call(RpcOperation::Fetch, |rpc| rpc.get_balance(payer))?;
+        let needed = report.total_lamports();
+        if available < needed {
+            return Err(VerifierError::InsufficientFunds { needed, available });
+        }
+        Ok(report)
+    }
+}
+
+/// Estimated program CUs of each phase transaction `verify_phased` sends
+///
+/// Phases are named as in
+/// [`ProgressObserver::phase_started`](crate::ProgressObserver::phase_started).
+pub fn phase_compute_units(log_n: u8, is_zk: bool, num_public_inputs: usize) -> Vec<(String, u32)> {
+    let plan = plonk_solana_core::cu_estimate(log_n, is_zk, num_public_inputs);
+    phase_transactions(&plan)
+        .into_iter()
+        .map(|(step, cus)| (step.to_string(), cus))
+        .collect()
+}
+
+/// Program CUs of each phase transaction `verify_phased` sends
+pub(crate) fn phase_transactions(plan: &PhasePlan) -> Vec<(PhaseStep, u32)> {
+    let rounds_per_tx = plan.rounds_per_tx();
+    let mut txs = vec![(PhaseStep::Challenges, plan.phase1_full)];
+    let mut start = 0u8;
+    while start < plan.log_n {
+        let end = start.saturating_add(rounds_per_tx).min(plan.log_n);
+        txs.push((
+            PhaseStep::SumcheckRounds { start, end },
+            plan.sumcheck_round * (end - start) as u32,
+        ));
+        start = end;
+    }
+    if plan.use_relations_and_weights() {
+        txs.push((PhaseStep::RelationsAndWeights, plan.relations_and_weights()));
+    } else {
+        txs.push((PhaseStep::Relations, plan.relations[0] + plan.relations[1]));
+        txs.push((PhaseStep::Weights, plan.weights));
+    }
+    if plan.use_combined_folding() {
+        txs.push((PhaseStep::FoldingAndGemini, plan.folding + plan.gemini));
+    } else {
+        txs.push((PhaseStep::Folding, plan.folding));
+        txs.push((PhaseStep::Gemini, plan.gemini));
+    }
+    txs.push((PhaseStep::MsmAndPairing, plan.msm + plan.pairing));
+    txs
+}
+
+fn cost_report(
+    config: &VerifierConfig,
+    plan: &PhasePlan,
+    proof_len: usize,
+    num_public_inputs: usize,
+    micro_lamports_per_cu: u64,
+    rents: &Rents,
+) -> CostReport {
+    let phases: Vec<u32> = phase_transactions(plan)
+        .into_iter()
+        .map(|(_, cus)| cus)
+        .collect();
+    // Only the number of accounts matters for the chunk size, not the keys
+    let fitted = config.chunk_size.is_none();
+    let (payer, buffer) = (
+        Pubkey::new_from_array([1; 32]),
+        Pubkey::new_from_array([2; 32]),
+    );
+    let chunk_size = config.chunk_size.map(whole_slots).unwrap_or_else(|| {
+        fit_chunk_size(config, &payer, &buffer, None).unwrap_or(DEFAULT_CHUNK_SIZE)
+    });
+    let num_chunks = proof_len.div_ceil(chunk_size.max(1));
+
+    // Setup signs with the payer and both new accounts; public inputs that
+    // do not fit are uploaded in their own transactions
+    let public_inputs_len = num_public_inputs * 32;
+    let public_input_uploads = if public_inputs_len > PI_BUNDLE_THRESHOLD {
+        let pi_chunk_size = config.chunk_size.unwrap_or_else(|| {
+            fit_public_input_chunk_size(config, &payer, &buffer, None).unwrap_or(DEFAULT_CHUNK_SIZE)
+        });
+        public_inputs_len.div_ceil(pi_chunk_size.max(1))
+    } else {
+        0
+    };
+    let setup_txs = 1 + public_input_uploads;
+    // Receipt, then one close transaction unless the accounts are kept
+    let close_ixs: usize = match config.cleanup {
+        CleanupPolicy::Keep => 0,
+        CleanupPolicy::CloseBuffers => 1,
+        CleanupPolicy::CloseAll => 2,
+    };
+    let finish_txs = 1 + close_ixs.min(1);
+    // Nonce account creation (signed by the new account too) and withdrawal
+    let nonce_txs = if config.durable_nonce { 2 } else { 0 };
+    let num_transactions = setup_txs + num_chunks + phases.len() + finish_txs + nonce_txs;
+    let num_signatures = num_transactions + 2 + nonce_txs / 2;
+
+    // CU limits requested per transaction, which is what priority fees pay for
+    let phase_limits: u64 = phases
+        .iter()
+        .map(|&cus| match config.fees.cu_limit_strategy {
+            CuLimitStrategy::Fixed => config.compute_unit_limit as u64,
+            CuLimitStrategy::Estimated => PhasePlan::compute_unit_limit(cus) as u64,
+        })
+        .sum();
+    // Setup (two create_account, init_buffer, then set_public_inputs or the
+    // public input uploads), receipt,
+    // closes, nonce (create_account, initialize; withdraw) and the
+    // advance_nonce instruction in each phase
+    let nonce_ixs = if config.durable_nonce {
+        3 + phases.len() as u64
+    } else {
+        0
+    };
+    let priced_uploads = if fitted && upload_carries_fee(config, &payer, &buffer, None, chunk_size)
+    {
+        (num_chunks + public_input_uploads) as u64
+    } else {
+        0
+    };
+    let setup_ixs = 3 + public_input_uploads.max(1) as u64;
+    let other_limits = (setup_ixs + 1 + close_ixs as u64 + nonce_ixs + priced_uploads)
+        * DEFAULT_INSTRUCTION_CU_LIMIT;
+    let priority_fees = ((phase_limits + other_limits) * micro_lamports_per_cu).div_ceil(1_000_000);
+
+    CostReport {
+        log_n: plan.log_n,
+        num_transactions,
+        total_compute_units: phases.iter().map(|&cus| cus as u64).sum(),
+        signature_fees: num_signatures as u64 * LAMPORTS_PER_SIGNATURE,
+        priority_fees,
+        buffer_rent: rents.buffer,
+        state_rent: rents.state,
+        receipt_rent: rents.receipt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_cost_report() {
+        let config = VerifierConfig::new(Pubkey::new_unique());
+        let plan = plonk_solana_core::cu_estimate(12, true, 1);
+        let rents = Rents {
+            buffer: 100,
+            state: 200,
+            receipt: 10,
+        };
+
+        let report = cost_report(&config, &plan, PROOF_SIZE, 1, 0, &rents);
+        // Setup + 16 chunks + Phase 1 + 2 sumcheck + 3 closing phases + receipt + close
+        assert_eq!(report.num_transactions, 1 + 16 + 1 + 2 + 3 + 2);
+        assert_eq!(report.signature_fees, (25 + 2) * LAMPORTS_PER_SIGNATURE);
+        assert_eq!(report.total_compute_units, plan.total());
+        assert_eq!(report.priority_fees, 0);
+        assert_eq!(report.net_lamports(), report.signature_fees + 10);
+
+        let priced = cost_report(&config, &plan, PROOF_SIZE, 1, 1_000, &rents);
+        // 6 phases at the fixed 1.4M limit, 6 other instructions at 200K
+        assert_eq!(priced.priority_fees, (6 * 1_400_000 + 6 * 200_000) / 1_000);
+
+        let nonce = cost_report(
+            &config.clone().with_durable_nonce(),
+            &plan,
+            PROOF_SIZE,
+            1,
+            0,
+            &rents,
+        );
+        assert_eq!(nonce.num_transactions, report.num_transactions + 2);
+        assert_eq!(
+            nonce.signature_fees,
+            report.signature_fees + 3 * LAMPORTS_PER_SIGNATURE
+        );
+
+        let keep = cost_report(
+            &config.clone().with_cleanup(CleanupPolicy::Keep),
+            &plan,
+            PROOF_SIZE,
+            1,
+            1_000,
+            &rents,
+        );
+        assert_eq!(keep.num_transactions, report.num_transactions - 1);
+        assert_eq!(keep.priority_fees, priced.priority_fees - 200);
+
+        // Whole-slot uploads leave no room for a configured fee at the
+        // default size, so fitted uploads skip it like fixed ones
+        let fee_config = config
+            .clone()
+            .with_fees(FeeConfig::default().with_priority_fee(1_000));
+        let fitted = cost_report(&fee_config, &plan, PROOF_SIZE, 1, 1_000, &rents);
+        assert_eq!(fitted.num_transactions, report.num_transactions);
+        assert_eq!(fitted.priority_fees, priced.priority_fees);
+        let fixed = cost_report(
+            &fee_config.clone().with_chunk_size(DEFAULT_CHUNK_SIZE),
+            &plan,
+            PROOF_SIZE,
+            1,
+            1_000,
+            &rents,
+        );
+        assert_eq!(fixed.num_transactions, report.num_transactions);
+        assert_eq!(fixed.priority_fees, priced.priority_fees);
+
+        // Larger transactions fit the fee next to their slots and pay it
+        let large = fee_config.with_max_transaction_size(4096);
+        let fitted = cost_report(&large, &plan, PROOF_SIZE, 1, 1_000, &rents);
+        let fixed = cost_report(
+            &large.clone().with_chunk_size(3 * DEFAULT_CHUNK_SIZE),
+            &plan,
+            PROOF_SIZE,
+            1,
+            1_000,
+            &rents,
+        );
+        assert_eq!(fitted.num_transactions, fixed.num_transactions);
+        assert_eq!(
+            fitted.priority_fees,
+            fixed.priority_fees + PROOF_SIZE.div_ceil(3 * DEFAULT_CHUNK_SIZE) as u64 * 200
+        );
+
+        // 32 public inputs fit one upload of their own, 64 take two
+        let wide = cost_report(&config, &plan, PROOF_SIZE, 32, 1_000, &rents);
+        assert_eq!(wide.num_transactions, report.num_transactions + 1);
+        assert_eq!(wide.priority_fees, priced.priority_fees);
+        let wider = cost_report(&config, &plan, PROOF_SIZE, 64, 1_000, &rents);
+        assert_eq!(wider.num_transactions, report.num_transactions + 2);
+        assert_eq!(wider.priority_fees, priced.priority_fees + 200);
+    }
+}
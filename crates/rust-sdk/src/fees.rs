@@ -0,0 +1,118 @@
+//! Compute budget instructions and priority fee selection
+
+use crate::types::*;
+use plonk_solana_core::PhasePlan;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+// Compute budget program ID
+pub(crate) const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey::Pubkey::from_str_const("ComputeBudget111111111111111111111111111111");
+
+/// Build a SetComputeUnitLimit instruction
+pub(crate) fn set_compute_unit_limit(units: u32) -> Instruction {
+    // Instruction code 2 = SetComputeUnitLimit
+    let mut data = vec![2u8];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// Build a RequestHeapFrame instruction
+pub(crate) fn request_heap_frame(bytes: u32) -> Instruction {
+    // Instruction code 1 = RequestHeapFrame
+    let mut data = vec![1u8];
+    data.extend_from_slice(&bytes.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// Build a SetComputeUnitPrice instruction
+pub(crate) fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    // Instruction code 3 = SetComputeUnitPrice
+    let mut data = vec![3u8];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// Estimated program CUs for a phase instruction, from its opcode
+///
+/// Returns `None` for instructions the cost model does not cover.
+pub(crate) fn phase_cu_estimate(plan: &PhasePlan, instruction: &Instruction) -> Option<u32> {
+    let [relations1, relations2] = plan.relations;
+    Some(match *instruction.data.first()? {
+        IX_PHASE1_FULL => plan.phase1_full,
+        IX_PHASE2_ROUNDS => {
+            let (start, end) = (*instruction.data.get(1)?, *instruction.data.get(2)?);
+            plan.sumcheck_round * end.saturating_sub(start) as u32
+        }
+        IX_PHASE2D_RELATIONS => relations1 + relations2,
+        IX_PHASE2D_RELATIONS_PART1 => relations1,
+        IX_PHASE2D_RELATIONS_PART2 => relations2,
+        IX_PHASE3A_WEIGHTS => plan.weights,
+        IX_PHASE3B1_FOLDING => plan.folding,
+        IX_PHASE3B2_GEMINI => plan.gemini,
+        IX_PHASE3C_MSM => plan.msm,
+        IX_PHASE3C_AND_PAIRING => plan.msm + plan.pairing,
+        IX_PHASE2D_AND_3A => relations1 + relations2 + plan.weights,
+        IX_PHASE3B_COMBINED => plan.folding + plan.gemini,
+        IX_FINAL_CHECK => plan.pairing,
+        _ => return None,
+    })
+}
+
+/// The `percentile`-th of recent prioritization fees (0 if there are none)
+pub(crate) fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[rank]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions;
+
+    #[test]
+    fn test_phase_cu_estimate() {
+        let plan = plonk_solana_core::cu_estimate(14, true, 1);
+        let (program, state, proof) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        let rounds = instructions::phase2_rounds(&program, &state, &proof, 6, 10);
+        assert_eq!(
+            phase_cu_estimate(&plan, &rounds),
+            Some(4 * plan.sumcheck_round)
+        );
+        let combined = instructions::phase3b_combined(&program, &state, &proof);
+        assert_eq!(
+            phase_cu_estimate(&plan, &combined),
+            Some(plan.folding + plan.gemini)
+        );
+        let msm = instructions::phase3c_msm(&program, &state, &proof, &proof, None);
+        assert_eq!(phase_cu_estimate(&plan, &msm), Some(plan.msm));
+        let upload = instructions::upload_chunk(&program, &proof, 0, &[0u8; 8]);
+        assert_eq!(phase_cu_estimate(&plan, &upload), None);
+    }
+
+    #[test]
+    fn test_request_heap_frame() {
+        let ix = request_heap_frame(256 * 1024);
+        assert_eq!(ix.program_id, COMPUTE_BUDGET_PROGRAM_ID);
+        assert_eq!(ix.data, [1, 0x00, 0x00, 0x04, 0x00]);
+        assert!(ix.accounts.is_empty());
+    }
+
+    #[test]
+    fn test_fee_percentile() {
+        assert_eq!(fee_percentile(vec![], 75), 0);
+        let fees = vec![50, 0, 10, 1000, 20];
+        assert_eq!(fee_percentile(fees.clone(), 0), 0);
+        assert_eq!(fee_percentile(fees.clone(), 50), 20);
+        assert_eq!(fee_percentile(fees.clone(), 75), 50);
+        assert_eq!(fee_percentile(fees, 100), 1000);
+    }
+}
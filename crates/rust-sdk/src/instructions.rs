@@ -1,6 +1,7 @@
-//! Instruction builders for the UltraHonk verifier program
+//! Instruction builders for the UltraHonk verifier and VK registry programs
 
 use crate::types::*;
+use plonk_solana_core::relations::RELATION_COEFFICIENTS_SIZE;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -35,6 +36,15 @@ pub fn upload_vk_chunk(
     )
 }
 
+/// Create instruction to compile an uploaded VK into the pre-parsed layout
+pub fn compile_vk(program_id: &Pubkey, vk_account: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_COMPILE_VK],
+        vec![AccountMeta::new(*vk_account, false)],
+    )
+}
+
 /// Create instruction to initialize a proof buffer
 pub fn init_buffer(
     program_id: &Pubkey,
@@ -52,6 +62,22 @@ pub fn init_buffer(
     )
 }
 
+/// Create instruction to initialize a proof buffer with a separate authority
+///
+/// `authority` is passed as the instruction's signer account, so the proof
+/// owner signs the setup even when a sponsor pays for it.
+pub fn init_buffer_with_authority(
+    program_id: &Pubkey,
+    proof_account: &Pubkey,
+    authority: &Pubkey,
+    num_public_inputs: u16,
+) -> Instruction {
+    let mut ix = init_buffer(program_id, proof_account, num_public_inputs);
+    ix.accounts
+        .push(AccountMeta::new_readonly(*authority, true));
+    ix
+}
+
 /// Create instruction to upload a proof chunk
 pub fn upload_chunk(
     program_id: &Pubkey,
@@ -71,6 +97,31 @@ pub fn upload_chunk(
     )
 }
 
+/// Create instruction to write bytes to a region of a proof buffer
+///
+/// `offset` is relative to the start of `region`. Proof writes mark the
+/// chunk bitmap like [`upload_chunk`]; public input writes must stay within
+/// the count the buffer was initialized with.
+pub fn upload_data(
+    program_id: &Pubkey,
+    proof_account: &Pubkey,
+    region: BufferRegion,
+    offset: u16,
+    bytes: &[u8],
+) -> Instruction {
+    let mut data = Vec::with_capacity(4 + bytes.len());
+    data.push(IX_UPLOAD_DATA);
+    data.push(region as u8);
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(bytes);
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![AccountMeta::new(*proof_account, false)],
+    )
+}
+
 /// Create instruction to set public inputs
 pub fn set_public_inputs(
     program_id: &Pubkey,
@@ -88,7 +139,93 @@ pub fn set_public_inputs(
     )
 }
 
+/// Create instruction to unlock a proof buffer locked by Phase 1
+///
+/// Also zeroes the verification state the buffer is bound to, so any
+/// progress on it is lost. Fails with `InvalidPhase` while the state holds a
+/// successful verification without a receipt.
+pub fn reset_buffer(
+    program_id: &Pubkey,
+    proof_account: &Pubkey,
+    state_account: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_RESET_BUFFER],
+        vec![
+            AccountMeta::new(*proof_account, false),
+            AccountMeta::new(*state_account, false),
+        ],
+    )
+}
+
+/// Create instruction to turn `manifest` into a shard manifest over `shards`
+///
+/// Shard `i` holds bytes `i * shard_len..` of the public inputs followed by
+/// the proof, so `shards` must have exactly as many accounts as that takes.
+pub fn init_manifest(
+    program_id: &Pubkey,
+    manifest: &Pubkey,
+    shards: &[Pubkey],
+    num_public_inputs: u16,
+    shard_len: u32,
+) -> Instruction {
+    let mut data = [0u8; 7];
+    data[0] = IX_INIT_MANIFEST;
+    data[1..3].copy_from_slice(&num_public_inputs.to_le_bytes());
+    data[3..7].copy_from_slice(&shard_len.to_le_bytes());
+
+    let mut accounts = vec![AccountMeta::new(*manifest, false)];
+    accounts.extend(shards.iter().map(|shard| AccountMeta::new(*shard, false)));
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Create instruction to upload a chunk of a shard
+///
+/// `offset` is relative to the start of the shard.
+pub fn upload_shard_chunk(
+    program_id: &Pubkey,
+    shard: &Pubkey,
+    manifest: &Pubkey,
+    offset: u32,
+    chunk: &[u8],
+) -> Instruction {
+    let mut data = Vec::with_capacity(5 + chunk.len());
+    data.push(IX_UPLOAD_SHARD_CHUNK);
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(chunk);
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*shard, false),
+            AccountMeta::new_readonly(*manifest, false),
+        ],
+    )
+}
+
+/// Append a manifest's shards to an instruction that takes a proof buffer
+///
+/// Use this when the proof account is a shard manifest. `ResetBuffer` and
+/// `CloseAccounts` write the shards, so they get them writable; every other
+/// instruction only reads them.
+pub fn with_shards(mut ix: Instruction, shards: &[Pubkey]) -> Instruction {
+    let writable = matches!(
+        ix.data.first(),
+        Some(&(IX_RESET_BUFFER | IX_CLOSE_ACCOUNTS))
+    );
+    ix.accounts
+        .extend(shards.iter().map(|shard| match writable {
+            true => AccountMeta::new(*shard, false),
+            false => AccountMeta::new_readonly(*shard, false),
+        }));
+    ix
+}
+
 /// Create Phase 1 instruction (challenge generation)
+///
+/// The proof buffer is writable because Phase 1 locks it to the state.
 pub fn phase1_full(
     program_id: &Pubkey,
     state_account: &Pubkey,
@@ -100,7 +237,7 @@ pub fn phase1_full(
         &[IX_PHASE1_FULL],
         vec![
             AccountMeta::new(*state_account, false),
-            AccountMeta::new_readonly(*proof_account, false),
+            AccountMeta::new(*proof_account, false),
             AccountMeta::new_readonly(*vk_account, false),
         ],
     )
@@ -124,6 +261,42 @@ pub fn phase2_rounds(
     )
 }
 
+/// Create Phase 2c instruction, committing to the relation coefficients
+///
+/// Optional. Once it has run, the `_with_coefficients` relations
+/// instructions can pass
+/// [`VerificationStateView::relation_coefficients`](crate::VerificationStateView::relation_coefficients)
+/// instead of having each relations transaction derive them from the alphas.
+pub fn phase2c_relation_coefficients(program_id: &Pubkey, state_account: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_PHASE2C_RELATION_COEFFICIENTS],
+        vec![AccountMeta::new(*state_account, false)],
+    )
+}
+
+/// Relations instruction `tag` carrying the coefficients Phase 2c committed to
+fn relations_with_coefficients(
+    program_id: &Pubkey,
+    tag: u8,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    coefficients: &[u8; RELATION_COEFFICIENTS_SIZE],
+) -> Instruction {
+    let mut data = Vec::with_capacity(1 + RELATION_COEFFICIENTS_SIZE);
+    data.push(tag);
+    data.extend_from_slice(coefficients);
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*proof_account, false),
+        ],
+    )
+}
+
 /// Create Phase 2d relations instruction
 pub fn phase2d_relations(
     program_id: &Pubkey,
@@ -140,6 +313,92 @@ pub fn phase2d_relations(
     )
 }
 
+/// Create Phase 2d1 instruction (first half of the relations)
+///
+/// Use with [`phase2d_relations_part2`] instead of [`phase2d_relations`]
+/// when the single relations transaction runs out of compute units.
+pub fn phase2d_relations_part1(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_PHASE2D_RELATIONS_PART1],
+        vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*proof_account, false),
+        ],
+    )
+}
+
+/// Create Phase 2d2 instruction (second half of the relations + final check)
+pub fn phase2d_relations_part2(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_PHASE2D_RELATIONS_PART2],
+        vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*proof_account, false),
+        ],
+    )
+}
+
+/// [`phase2d_relations`] batching with the coefficients Phase 2c committed
+/// to
+pub fn phase2d_relations_with_coefficients(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    coefficients: &[u8; RELATION_COEFFICIENTS_SIZE],
+) -> Instruction {
+    relations_with_coefficients(
+        program_id,
+        IX_PHASE2D_RELATIONS,
+        state_account,
+        proof_account,
+        coefficients,
+    )
+}
+
+/// [`phase2d_relations_part1`] batching with the coefficients Phase 2c
+/// committed to
+pub fn phase2d_relations_part1_with_coefficients(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    coefficients: &[u8; RELATION_COEFFICIENTS_SIZE],
+) -> Instruction {
+    relations_with_coefficients(
+        program_id,
+        IX_PHASE2D_RELATIONS_PART1,
+        state_account,
+        proof_account,
+        coefficients,
+    )
+}
+
+/// [`phase2d_relations_part2`] batching with the coefficients Phase 2c
+/// committed to
+pub fn phase2d_relations_part2_with_coefficients(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    coefficients: &[u8; RELATION_COEFFICIENTS_SIZE],
+) -> Instruction {
+    relations_with_coefficients(
+        program_id,
+        IX_PHASE2D_RELATIONS_PART2,
+        state_account,
+        proof_account,
+        coefficients,
+    )
+}
+
 /// Create Phase 3a weights instruction
 pub fn phase3a_weights(
     program_id: &Pubkey,
@@ -188,6 +447,73 @@ pub fn phase3b2_gemini(
     )
 }
 
+/// Create Phase 3b2 gemini instruction that also caches the Phase 3c MSM
+/// scalars in `scratch`
+///
+/// `scratch` must be a zeroed account of [`SCRATCH_ACCOUNT_SIZE`] owned by
+/// the verifier, or one this state used before.
+pub fn phase3b2_gemini_to_scratch(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    vk_account: &Pubkey,
+    scratch: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_PHASE3B2_GEMINI, 1],
+        vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*proof_account, false),
+            AccountMeta::new_readonly(*vk_account, false),
+            AccountMeta::new(*scratch, false),
+        ],
+    )
+}
+
+/// Create standalone Phase 3c (MSM) instruction, reading the scalars from
+/// `scratch` if Phase 3b2 cached them there
+pub fn phase3c_msm(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    vk_account: &Pubkey,
+    scratch: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*state_account, false),
+        AccountMeta::new_readonly(*proof_account, false),
+        AccountMeta::new_readonly(*vk_account, false),
+    ];
+    accounts.extend(scratch.map(|scratch| AccountMeta::new_readonly(*scratch, false)));
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_PHASE3C_MSM, scratch.is_some() as u8],
+        accounts,
+    )
+}
+
+/// Create Phase 3c + 4 combined (MSM + Pairing) instruction over the
+/// scalars Phase 3b2 cached in `scratch`
+pub fn phase3c_and_pairing_from_scratch(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    vk_account: &Pubkey,
+    scratch: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_PHASE3C_AND_PAIRING, 1],
+        vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*proof_account, false),
+            AccountMeta::new_readonly(*vk_account, false),
+            AccountMeta::new_readonly(*scratch, false),
+        ],
+    )
+}
+
 /// Create Phase 3c + 4 combined (MSM + Pairing) instruction
 pub fn phase3c_and_pairing(
     program_id: &Pubkey,
@@ -222,6 +548,23 @@ pub fn phase2d_and_3a(
     )
 }
 
+/// [`phase2d_and_3a`] batching the relations with the coefficients Phase 2c
+/// committed to
+pub fn phase2d_and_3a_with_coefficients(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    coefficients: &[u8; RELATION_COEFFICIENTS_SIZE],
+) -> Instruction {
+    relations_with_coefficients(
+        program_id,
+        IX_PHASE2D_AND_3A,
+        state_account,
+        proof_account,
+        coefficients,
+    )
+}
+
 /// Create combined Phase 3b instruction (Folding + Gemini)
 pub fn phase3b_combined(
     program_id: &Pubkey,
@@ -238,6 +581,22 @@ pub fn phase3b_combined(
     )
 }
 
+/// Create final pairing check instruction (after a standalone Phase 3c)
+pub fn final_check(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    vk_account: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_FINAL_CHECK],
+        vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*vk_account, false),
+        ],
+    )
+}
+
 /// Create verification receipt PDA instruction
 pub fn create_receipt(
     program_id: &Pubkey,
@@ -247,11 +606,48 @@ pub fn create_receipt(
     receipt_pda: &Pubkey,
     payer: &Pubkey,
 ) -> Instruction {
+    create_receipt_with_options(
+        program_id,
+        state_account,
+        proof_account,
+        vk_account,
+        receipt_pda,
+        payer,
+        &ReceiptOptions::default(),
+    )
+}
+
+/// [`create_receipt`] with [`ReceiptOptions`]
+///
+/// Data: `[60]` for the defaults, `[60, tag (32)]` for a tag alone,
+/// `[60, tag (32), policy (1)]` with a duplicate policy other than
+/// `Error`, and `[60, tag (32), policy (1), beneficiary (32)]` with a rent
+/// beneficiary. The state is writable so the program can mark it as
+/// recorded.
+pub fn create_receipt_with_options(
+    program_id: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    vk_account: &Pubkey,
+    receipt_pda: &Pubkey,
+    payer: &Pubkey,
+    options: &ReceiptOptions,
+) -> Instruction {
+    let mut data = vec![IX_CREATE_RECEIPT];
+    if *options != ReceiptOptions::default() {
+        data.extend_from_slice(&options.tag);
+    }
+    if options.on_duplicate != DuplicateReceiptPolicy::Error || options.rent_beneficiary.is_some() {
+        data.push(options.on_duplicate as u8);
+    }
+    if let Some(beneficiary) = options.rent_beneficiary {
+        data.extend_from_slice(beneficiary.as_ref());
+    }
     Instruction::new_with_bytes(
         *program_id,
-        &[IX_CREATE_RECEIPT],
+        &data,
         vec![
-            AccountMeta::new_readonly(*state_account, false),
+            AccountMeta::new(*state_account, false),
             AccountMeta::new_readonly(*proof_account, false),
             AccountMeta::new_readonly(*vk_account, false),
             AccountMeta::new(*receipt_pda, false),
@@ -261,7 +657,58 @@ pub fn create_receipt(
     )
 }
 
+/// Create close receipt instruction, returning the receipt's rent
+///
+/// `rent_payer` must be the account that paid for the receipt, and the
+/// receipt must have no other beneficiary.
+pub fn close_receipt(
+    program_id: &Pubkey,
+    receipt_pda: &Pubkey,
+    rent_payer: &Pubkey,
+) -> Instruction {
+    close_receipt_to(program_id, receipt_pda, rent_payer, rent_payer)
+}
+
+/// [`close_receipt`] for a receipt that refunds its rent to
+/// `rent_beneficiary`, as recorded with
+/// [`ReceiptOptions::with_rent_beneficiary`]
+pub fn close_receipt_to(
+    program_id: &Pubkey,
+    receipt_pda: &Pubkey,
+    rent_payer: &Pubkey,
+    rent_beneficiary: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*receipt_pda, false),
+        AccountMeta::new(*rent_payer, true),
+    ];
+    if rent_beneficiary != rent_payer {
+        accounts.push(AccountMeta::new(*rent_beneficiary, false));
+    }
+    Instruction::new_with_bytes(*program_id, &[IX_CLOSE_RECEIPT], accounts)
+}
+
+/// Create an instruction that fails unless `proof_digest` matches the
+/// digest the receipt recorded
+///
+/// Data: `[62, proof_digest (32)]`.
+pub fn reverify_by_digest(
+    program_id: &Pubkey,
+    receipt_pda: &Pubkey,
+    proof_digest: &[u8; 32],
+) -> Instruction {
+    let mut data = vec![IX_REVERIFY_BY_DIGEST];
+    data.extend_from_slice(proof_digest);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![AccountMeta::new_readonly(*receipt_pda, false)],
+    )
+}
+
 /// Create close accounts instruction to recover rent
+///
+/// Append the state's MSM scratch, if it used one, to close it too.
 pub fn close_accounts(
     program_id: &Pubkey,
     state_account: &Pubkey,
@@ -278,3 +725,96 @@ pub fn close_accounts(
         ],
     )
 }
+
+/// Name record PDA of `name` in the VK registry
+pub fn find_registry_name_address(registry_program: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REGISTRY_NAME_SEED, name.as_bytes()], registry_program)
+}
+
+/// Entry PDA of `name@version` in the VK registry
+pub fn find_registry_entry_address(
+    registry_program: &Pubkey,
+    name: &str,
+    version: &str,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REGISTRY_ENTRY_SEED, name.as_bytes(), version.as_bytes()],
+        registry_program,
+    )
+}
+
+/// Create instruction to register `name@version` in the VK registry
+///
+/// Claims `name` for `authority` if nobody registered it yet.
+pub fn registry_register(
+    registry_program: &Pubkey,
+    authority: &Pubkey,
+    name: &str,
+    version: &str,
+    vk_account: &Pubkey,
+    vk_hash: &[u8; 32],
+) -> Instruction {
+    let mut data = Vec::with_capacity(3 + name.len() + version.len() + 64);
+    data.push(IX_REGISTRY_REGISTER);
+    data.push(name.len() as u8);
+    data.extend_from_slice(name.as_bytes());
+    data.push(version.len() as u8);
+    data.extend_from_slice(version.as_bytes());
+    data.extend_from_slice(vk_account.as_ref());
+    data.extend_from_slice(vk_hash);
+
+    Instruction::new_with_bytes(
+        *registry_program,
+        &data,
+        vec![
+            AccountMeta::new(find_registry_name_address(registry_program, name).0, false),
+            AccountMeta::new(
+                find_registry_entry_address(registry_program, name, version).0,
+                false,
+            ),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// Create instruction to deprecate `name@version` in the VK registry
+pub fn registry_deprecate(
+    registry_program: &Pubkey,
+    authority: &Pubkey,
+    name: &str,
+    version: &str,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *registry_program,
+        &[IX_REGISTRY_DEPRECATE],
+        vec![
+            AccountMeta::new_readonly(find_registry_name_address(registry_program, name).0, false),
+            AccountMeta::new(
+                find_registry_entry_address(registry_program, name, version).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Create instruction to hand `name` to `new_authority` in the VK registry
+pub fn registry_transfer_authority(
+    registry_program: &Pubkey,
+    authority: &Pubkey,
+    name: &str,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let mut data = vec![IX_REGISTRY_TRANSFER_AUTHORITY];
+    data.extend_from_slice(new_authority.as_ref());
+
+    Instruction::new_with_bytes(
+        *registry_program,
+        &data,
+        vec![
+            AccountMeta::new(find_registry_name_address(registry_program, name).0, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
@@ -0,0 +1,187 @@
+//! Jito bundle submission for phase transactions
+//!
+//! Sent one at a time, the phases of a verification land in many different
+//! blocks with other transactions in between. With [`JitoConfig`] set, the
+//! remaining phases are sent to a block engine in bundles of up to
+//! [`JITO_MAX_BUNDLE_SIZE`], which land in order within a single slot.
+//!
+//! A bundle with a failing transaction is dropped instead of landing, so a
+//! rejected proof never reaches the state account this way. When bundles
+//! keep failing, [`SolanaNoirVerifier::run_phases`] falls back to single
+//! transactions, which record the failure and report the program error.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    instructions,
+    phases::remaining_steps,
+    types::*,
+};
+use base64::Engine;
+use plonk_solana_core::PhasePlan;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use solana_system_interface::instruction as system_instruction;
+use std::thread;
+use std::time::Duration;
+
+impl SolanaNoirVerifier {
+    /// Send the remaining phases as bundles until none are left
+    ///
+    /// Returns once the state account has a result; the caller reads it.
+    /// Signatures and consumed CUs are appended as bundles land.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(state = %state_account)))]
+    pub(crate) fn run_phases_bundled(
+        &self,
+        payer: &dyn Signer,
+        jito: &JitoConfig,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        shards: &[Pubkey],
+        vk_account: &Pubkey,
+        is_zk: bool,
+        num_pi: usize,
+        signatures: &mut Vec<Signature>,
+        total_cus: &mut u64,
+    ) -> Result<()> {
+        // The state has no log_n before Phase 1; bundling needs it up front
+        // to know how many sumcheck transactions follow
+        let log_n = self.load_vk(vk_account)?.0.log2_circuit_size as u8;
+        let plan = plonk_solana_core::cu_estimate(log_n, is_zk, num_pi);
+        let mut attempt = 0;
+
+        loop {
+            let state = self
+                .fetch_account(state_account)
+                .map_err(|_| VerifierError::StateAccountNotFound)?
+                .data;
+            let steps = remaining_steps(&state, log_n, &plan)?;
+            if steps.is_empty() {
+                return Ok(());
+            }
+
+            let bundle: Vec<Instruction> = steps
+                .iter()
+                .take(JITO_MAX_BUNDLE_SIZE)
+                .filter_map(|step| {
+                    step.instruction(
+                        &self.config.program_id,
+                        state_account,
+                        proof_account,
+                        vk_account,
+                    )
+                })
+                .map(|ix| instructions::with_shards(ix, shards))
+                .collect();
+
+            log::debug!("Bundling {:?}", &steps[..bundle.len()]);
+            for step in &steps[..bundle.len()] {
+                self.notify(|o| o.phase_started(&step.to_string()));
+            }
+            match self.send_bundle(payer, jito, &bundle, &plan) {
+                Ok(sigs) => {
+                    for (sig, step) in sigs.into_iter().zip(&steps) {
+                        let cus = self.transaction_cus(&sig);
+                        self.notify(|o| o.compute_units_consumed(&sig, cus));
+                        self.record(|m| m.phase_compute_units(step.label(), cus));
+                        *total_cus += cus;
+                        signatures.push(sig);
+                    }
+                    attempt = 0;
+                }
+                // Re-read the state before retrying: the bundle may have landed
+                Err(e) if attempt < self.config.max_retries && e.is_retryable() => {
+                    attempt += 1;
+                    self.record(|m| m.retried("bundle"));
+                    log::warn!(
+                        "Bundle failed ({}/{}): {}",
+                        attempt,
+                        self.config.max_retries,
+                        e
+                    );
+                    thread::sleep(Duration::from_millis(500 * attempt as u64));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Sign `instructions` as one transaction each and submit them as a bundle
+    ///
+    /// The last transaction carries the tip. Bundles land atomically, so
+    /// waiting for the last signature confirms all of them.
+    fn send_bundle(
+        &self,
+        payer: &dyn Signer,
+        jito: &JitoConfig,
+        instructions: &[Instruction],
+        plan: &PhasePlan,
+    ) -> Result<Vec<Signature>> {
+        let recent_blockhash = self.latest_blockhash()?;
+        let last = instructions.len().saturating_sub(1);
+
+        let transactions = instructions
+            .iter()
+            .enumerate()
+            .map(|(i, ix)| {
+                let mut ixs = vec![ix.clone()];
+                if i == last {
+                    ixs.push(system_instruction::transfer(
+                        &payer.pubkey(),
+                        &jito.tip_account,
+                        jito.tip_lamports,
+                    ));
+                }
+                let cu_limit = self.phase_cu_limit(plan, ix);
+                self.build_transaction(payer, &[], &ixs, Some(cu_limit), recent_blockhash, None)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let encoded = transactions
+            .iter()
+            .map(|tx| {
+                bincode::serialize(tx)
+                    .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+                    .map_err(|e| VerifierError::TransactionFailed(e.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [encoded, { "encoding": "base64" }],
+        });
+        let response: serde_json::Value = reqwest::blocking::Client::new()
+            .post(&jito.block_engine_url)
+            .json(&request)
+            .send()
+            .and_then(|r| r.json())
+            .map_err(|e| VerifierError::BundleRejected(e.to_string()))?;
+        if let Some(error) = response.get("error") {
+            return Err(VerifierError::BundleRejected(error.to_string()));
+        }
+        log::debug!(
+            "Sent bundle {} ({} transactions)",
+            response["result"].as_str().unwrap_or("?"),
+            transactions.len()
+        );
+
+        let signatures: Vec<Signature> = transactions.iter().map(|tx| tx.signatures[0]).collect();
+        for sig in &signatures {
+            self.notify(|o| o.transaction_sent(sig));
+            self.record(|m| m.transaction_sent());
+        }
+        if let Some(sig) = signatures.last() {
+            self.wait_for_confirmation(sig)?;
+        }
+        for sig in &signatures {
+            self.notify(|o| o.transaction_confirmed(sig));
+        }
+        Ok(signatures)
+    }
+}
@@ -36,12 +36,59 @@
 //! }
 //! ```
 
+#[cfg(feature = "artifacts")]
+mod artifacts;
+mod attestation;
+mod batch;
 mod client;
+mod crank;
 mod error;
+mod estimate;
+mod fees;
+mod hex;
 mod instructions;
+#[cfg(feature = "jito")]
+mod jito;
+mod local;
+mod lookup_table;
+mod metrics;
+mod nonce;
+mod offline;
+mod phases;
+mod progress;
+#[cfg(feature = "prover")]
+mod prover;
+mod public_inputs;
+mod pubsub;
+mod registry;
+mod rpc;
+mod session;
+mod sharded;
+mod simulate;
+mod state;
+mod stream;
+mod sweep;
 mod types;
 
+#[cfg(feature = "artifacts")]
+pub use artifacts::NoirAbi;
+pub use attestation::{
+    ReceiptAttestation, ATTESTATION_DOMAIN, ATTESTATION_MESSAGE_SIZE, ATTESTATION_SIZE,
+};
+pub use batch::ProofJob;
 pub use client::SolanaNoirVerifier;
-pub use error::VerifierError;
+pub use error::{OnChainVerifyError, ProgramErrorCode, VerifierError};
+pub use estimate::phase_compute_units;
 pub use instructions::*;
+pub use local::{verify_locally, vk_hash, vk_hash_with_srs};
+pub use metrics::Metrics;
+pub use offline::{partial_sign, VerifyAccounts};
+pub use progress::ProgressObserver;
+#[cfg(feature = "prover")]
+pub use prover::{prove, OracleHash, ProveOptions, ProverOutput};
+pub use public_inputs::PublicInputs;
+pub use registry::{OnChainRegistryEntry, RegistryEntry, VkRegistry};
+pub use session::VerificationSession;
+pub use sharded::ShardedUpload;
+pub use state::VerificationStateView;
 pub use types::*;
@@ -0,0 +1,238 @@
+//! Off-chain proof verification before submission
+//!
+//! Runs the same `plonk-solana-core` verifier the program uses, on the
+//! arkworks host backend, so a proof that cannot pass on chain is caught
+//! before any account is created or fee paid.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    phases::PhaseStep,
+    types::*,
+};
+use plonk_solana_core::{proof::Proof, CompiledVk, Fr, VerificationKey, VerifyError, G2};
+use solana_sdk::pubkey::Pubkey;
+
+/// Pairing point object limbs counted in `vk.num_public_inputs` (bb 0.87)
+const PAIRING_POINTS_SIZE: u32 = 16;
+
+// Program `VkBufferStatus::Compiled`
+const VK_STATUS_COMPILED: u8 = 3;
+
+/// Verify a proof off-chain against raw VK bytes
+///
+/// `public_inputs` are concatenated 32-byte big-endian field elements, as
+/// passed to [`SolanaNoirVerifier::verify`]. Uses the default SRS; see
+/// [`SolanaNoirVerifier::verify_locally_with_account`] for VKs uploaded with
+/// their own.
+pub fn verify_locally(vk_bytes: &[u8], proof_bytes: &[u8], public_inputs: &[u8]) -> Result<()> {
+    let vk = VerificationKey::from_bytes(vk_bytes)
+        .map_err(|e| VerifierError::LocalVerification(e.into()))?;
+    verify_with_key(
+        &vk,
+        proof_bytes,
+        public_inputs,
+        &plonk_solana_core::default_srs_g2(),
+    )
+}
+
+/// Canonical hash of a VK, as the program records it in receipts
+///
+/// Takes raw bb VK bytes. The hash stays the same when the VK is uploaded
+/// to another account or compiled, so integrator programs can pin a circuit
+/// with it (`is_verified_by_vk_hash` in the CPI crate) instead of pinning
+/// one VK account. This is the hash for the default SRS; use
+/// [`vk_hash_with_srs`] for a VK uploaded with its own x·G2 point.
+pub fn vk_hash(vk_bytes: &[u8]) -> Result<[u8; 32]> {
+    vk_hash_with_srs(vk_bytes, &plonk_solana_core::default_srs_g2())
+}
+
+/// Canonical hash of a VK checked against the x·G2 point `srs_g2`
+///
+/// Receipts of a VK uploaded with a custom SRS record this hash, so they
+/// never match the same VK checked against the default point.
+pub fn vk_hash_with_srs(vk_bytes: &[u8], srs_g2: &G2) -> Result<[u8; 32]> {
+    VerificationKey::from_bytes(vk_bytes)
+        .map(|vk| vk.hash_with_srs(srs_g2))
+        .map_err(|e| VerifierError::LocalVerification(e.into()))
+}
+
+impl SolanaNoirVerifier {
+    /// Verify a proof off-chain against an uploaded VK account
+    ///
+    /// Reads the VK (raw or compiled) and its SRS point the way the program
+    /// does. [`verify`](Self::verify) calls this first unless
+    /// [`VerifyOptions::skip_local_check`] is set.
+    pub fn verify_locally_with_account(
+        &self,
+        vk_account: &Pubkey,
+        proof_bytes: &[u8],
+        public_inputs: &[u8],
+    ) -> Result<()> {
+        let (vk, srs_g2) = self.load_vk(vk_account)?;
+        verify_with_key(&vk, proof_bytes, public_inputs, &srs_g2)
+    }
+
+    /// Parse an uploaded VK account into its key and x·G2 SRS point
+    pub(crate) fn load_vk(&self, vk_account: &Pubkey) -> Result<(VerificationKey, G2)> {
+        let data = self.fetch_account(vk_account)?.data;
+        let vk_bytes = data.get(VK_HEADER_SIZE..VK_HEADER_SIZE + VK_SIZE).ok_or(
+            VerifierError::InvalidVkSize {
+                expected: VK_HEADER_SIZE + VK_SIZE,
+                actual: data.len(),
+            },
+        )?;
+        let vk = if data[0] == VK_STATUS_COMPILED {
+            CompiledVk::from_bytes(vk_bytes).map(|vk| vk.to_key())
+        } else {
+            VerificationKey::from_bytes(vk_bytes)
+        }
+        .map_err(|e| VerifierError::LocalVerification(e.into()))?;
+
+        // An unset SRS slot means the default point, as on chain
+        let srs_offset = VK_HEADER_SIZE + VK_SIZE;
+        let srs_g2: G2 = match data.get(srs_offset..srs_offset + SRS_G2_SIZE) {
+            Some(slot) if slot.iter().any(|&b| b != 0) => slot.try_into().unwrap(),
+            _ => plonk_solana_core::default_srs_g2(),
+        };
+
+        Ok((vk, srs_g2))
+    }
+}
+
+fn verify_with_key(
+    vk: &VerificationKey,
+    proof_bytes: &[u8],
+    public_inputs: &[u8],
+    srs_g2: &G2,
+) -> Result<()> {
+    verify_stages(vk, proof_bytes, public_inputs, srs_g2, |_| {})
+}
+
+/// [`verify_with_key`], calling `reached` with the phase transaction that
+/// completes each check before running it
+///
+/// On failure, the last step passed to `reached` is where the program
+/// would reject the proof.
+pub(crate) fn verify_stages(
+    vk: &VerificationKey,
+    proof_bytes: &[u8],
+    public_inputs: &[u8],
+    srs_g2: &G2,
+    mut reached: impl FnMut(PhaseStep),
+) -> Result<()> {
+    let local = |e: VerifyError| VerifierError::LocalVerification(e);
+
+    if !public_inputs.len().is_multiple_of(32) {
+        return Err(local(VerifyError::PublicInput(format!(
+            "{} bytes is not a whole number of 32-byte field elements",
+            public_inputs.len()
+        ))));
+    }
+    let public_inputs: Vec<Fr> = public_inputs
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+    let expected = vk.num_public_inputs.saturating_sub(PAIRING_POINTS_SIZE) as usize;
+    if public_inputs.len() != expected {
+        return Err(local(VerifyError::PublicInput(format!(
+            "expected {} public inputs, got {}",
+            expected,
+            public_inputs.len()
+        ))));
+    }
+
+    let (is_zk, _format) = Proof::detect_format(proof_bytes).map_err(|e| local(e.into()))?;
+    let proof = Proof::from_bytes(proof_bytes, vk.log2_circuit_size as usize, is_zk)
+        .map_err(|e| local(e.into()))?;
+
+    let plan = plonk_solana_core::cu_estimate(
+        vk.log2_circuit_size.min(u8::MAX as u32) as u8,
+        is_zk,
+        public_inputs.len(),
+    );
+
+    reached(PhaseStep::Challenges);
+    let challenges =
+        plonk_solana_core::verify_step1_challenges(vk, &proof, &public_inputs).map_err(local)?;
+    // The program checks the final sumcheck relation in Phase 2d
+    reached(if plan.use_relations_and_weights() {
+        PhaseStep::RelationsAndWeights
+    } else {
+        PhaseStep::Relations
+    });
+    if !plonk_solana_core::verify_step2_sumcheck(vk, &proof, &challenges).map_err(local)? {
+        return Err(local(VerifyError::VerificationFailed));
+    }
+    reached(if plan.use_combined_folding() {
+        PhaseStep::FoldingAndGemini
+    } else {
+        PhaseStep::Folding
+    });
+    let (p0, p1) =
+        plonk_solana_core::verify_step3_pairing_points(vk, &proof, &challenges).map_err(local)?;
+    reached(PhaseStep::MsmAndPairing);
+    if !plonk_solana_core::verify_step4_pairing_check_with_srs(&p0, &p1, srs_g2).map_err(local)? {
+        return Err(local(VerifyError::VerificationFailed));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_locally_rejects_malformed_input() {
+        let err = verify_locally(&[0u8; 100], &[0u8; PROOF_SIZE], &[]).unwrap_err();
+        assert!(matches!(
+            err,
+            VerifierError::LocalVerification(VerifyError::Key(_))
+        ));
+
+        let vk = VerificationKey {
+            log2_circuit_size: 12,
+            log2_domain_size: 12,
+            num_public_inputs: PAIRING_POINTS_SIZE + 1,
+            pub_inputs_offset: 1,
+            commitments: Vec::new(),
+            num_commitments: 0,
+        };
+        let srs = plonk_solana_core::default_srs_g2();
+        for public_inputs in [&[0u8; 31][..], &[0u8; 64][..]] {
+            assert!(matches!(
+                verify_with_key(&vk, &[0u8; PROOF_SIZE], public_inputs, &srs),
+                Err(VerifierError::LocalVerification(VerifyError::PublicInput(
+                    _
+                )))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vk_hash_survives_compilation() {
+        let mut vk_bytes = vec![0u8; VK_SIZE];
+        vk_bytes[7] = 64; // circuit size
+        vk_bytes[15] = 6; // log2 circuit size
+        vk_bytes[23] = 17; // public inputs
+        for (i, b) in vk_bytes[32..].iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let hash = vk_hash(&vk_bytes).unwrap();
+
+        let compiled = VerificationKey::from_bytes(&vk_bytes)
+            .unwrap()
+            .to_compiled_bytes();
+        let compiled = CompiledVk::from_bytes(&compiled).unwrap();
+        assert_eq!(compiled.to_key().hash(), hash);
+
+        let mut srs = plonk_solana_core::default_srs_g2();
+        assert_eq!(vk_hash_with_srs(&vk_bytes, &srs).unwrap(), hash);
+        srs[127] ^= 1;
+        assert_ne!(vk_hash_with_srs(&vk_bytes, &srs).unwrap(), hash);
+
+        vk_bytes[40] ^= 1;
+        assert_ne!(vk_hash(&vk_bytes).unwrap(), hash);
+        assert!(vk_hash(&vk_bytes[1..]).is_err());
+    }
+}
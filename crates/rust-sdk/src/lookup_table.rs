@@ -0,0 +1,113 @@
+//! Address lookup table management
+//!
+//! A lookup table lets v0 transactions reference an account by a one-byte
+//! index instead of its 32-byte key. Every phase transaction names the
+//! verifier program and usually the VK account, so keeping both in a table
+//! frees room for budget instructions and larger payloads.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    fees::COMPUTE_BUDGET_PROGRAM_ID,
+};
+use solana_address_lookup_table_interface::{instruction as alt_instruction, state};
+use solana_sdk::{
+    message::AddressLookupTableAccount,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use solana_system_interface::program as system_program;
+use std::thread;
+use std::time::Duration;
+
+/// Max addresses per `ExtendLookupTable` instruction that fit in one transaction
+const EXTEND_BATCH: usize = 20;
+
+impl SolanaNoirVerifier {
+    /// Create or top up the lookup table for a circuit
+    ///
+    /// Makes sure the table holds the verifier program, `vk_account`, the
+    /// system and compute budget programs, and any `extra` addresses (e.g.
+    /// a long-lived proof buffer or state account). Uses
+    /// `config.lookup_table` if set, otherwise creates a new table with
+    /// `payer` as authority. Returns the table address; pass it to
+    /// [`VerifierConfig::with_lookup_table`](crate::VerifierConfig::with_lookup_table).
+    ///
+    /// New entries can only be used from the slot after they were added,
+    /// so this waits for that slot before returning.
+    pub fn ensure_alt(
+        &self,
+        payer: &Keypair,
+        vk_account: &Pubkey,
+        extra: &[Pubkey],
+    ) -> Result<Pubkey> {
+        let mut wanted = vec![
+            self.config.program_id,
+            *vk_account,
+            system_program::ID,
+            COMPUTE_BUDGET_PROGRAM_ID,
+        ];
+        wanted.extend_from_slice(extra);
+
+        let table = match self.config.lookup_table {
+            Some(table) => {
+                let existing = self.fetch_lookup_table(&table)?.addresses;
+                wanted.retain(|key| !existing.contains(key));
+                table
+            }
+            None => {
+                let slot = self.client().get_slot()?;
+                let (ix, table) =
+                    alt_instruction::create_lookup_table(payer.pubkey(), payer.pubkey(), slot);
+                self.send_and_confirm(payer, &[], vec![ix], false)?;
+                table
+            }
+        };
+        wanted.dedup();
+
+        if !wanted.is_empty() {
+            for batch in wanted.chunks(EXTEND_BATCH) {
+                let ix = alt_instruction::extend_lookup_table(
+                    table,
+                    payer.pubkey(),
+                    Some(payer.pubkey()),
+                    batch.to_vec(),
+                );
+                self.send_and_confirm(payer, &[], vec![ix], false)?;
+            }
+
+            let extended_at = self.client().get_slot()?;
+            while self.client().get_slot()? <= extended_at {
+                thread::sleep(Duration::from_millis(200));
+            }
+        }
+
+        if self.config.lookup_table == Some(table) {
+            *self.lookup_table.lock().unwrap() = None;
+        }
+        Ok(table)
+    }
+
+    /// The configured lookup table, fetched once and cached
+    pub(crate) fn lookup_table_account(&self) -> Result<Option<AddressLookupTableAccount>> {
+        let Some(table) = self.config.lookup_table else {
+            return Ok(None);
+        };
+        let mut cached = self.lookup_table.lock().unwrap();
+        if cached.is_none() {
+            *cached = Some(self.fetch_lookup_table(&table)?);
+        }
+        Ok(cached.clone())
+    }
+
+    fn fetch_lookup_table(&self, table: &Pubkey) -> Result<AddressLookupTableAccount> {
+        let account = self.fetch_account(table)?;
+        let parsed = state::AddressLookupTable::deserialize(&account.data).map_err(|e| {
+            VerifierError::TransactionFailed(format!("invalid lookup table {table}: {e}"))
+        })?;
+        Ok(AddressLookupTableAccount {
+            key: *table,
+            addresses: parsed.addresses.to_vec(),
+        })
+    }
+}
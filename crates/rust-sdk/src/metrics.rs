@@ -0,0 +1,106 @@
+//! Counters and timings for services embedding the SDK
+//!
+//! [`ProgressObserver`](crate::ProgressObserver) follows one verification;
+//! [`Metrics`] aggregates across all of them. Set with
+//! [`VerifierConfig::with_metrics`](crate::VerifierConfig::with_metrics),
+//! an implementation typically increments Prometheus or OpenTelemetry
+//! instruments, giving throughput, retry and failure rates and the CU cost
+//! of each phase. Labels are static strings so they keep series
+//! cardinality bounded.
+
+use crate::client::SolanaNoirVerifier;
+use crate::error::Result;
+use std::time::{Duration, Instant};
+
+/// Receives counters and timings from the verifier
+///
+/// Every method defaults to doing nothing, and may be called from several
+/// threads at once.
+pub trait Metrics: Send + Sync {
+    /// A transaction was accepted by the RPC node
+    fn transaction_sent(&self) {}
+
+    /// A failed step is being retried
+    ///
+    /// `operation` is `"transaction"`, `"phase"`, `"bundle"`, or
+    /// `"rpc_send"`, `"rpc_confirm"` and `"rpc_fetch"` for requests moved
+    /// to another endpoint.
+    fn retried(&self, _operation: &'static str) {}
+
+    /// Compute units a confirmed phase transaction consumed, e.g. phase
+    /// `"sumcheck"`
+    fn phase_compute_units(&self, _phase: &'static str, _units: u64) {}
+
+    /// A verification finished after `elapsed`, from the first transaction
+    /// to the receipt (or the error)
+    fn verification_finished(&self, _elapsed: Duration, _succeeded: bool) {}
+}
+
+impl SolanaNoirVerifier {
+    /// Pass a measurement to the configured metrics, if any
+    pub(crate) fn record(&self, event: impl FnOnce(&dyn Metrics)) {
+        if let Some(metrics) = &self.config.metrics {
+            event(metrics.as_ref());
+        }
+    }
+
+    /// Run a whole verification, recording its latency and outcome
+    pub(crate) fn timed<T>(&self, verification: impl FnOnce() -> Result<T>) -> Result<T> {
+        let started = Instant::now();
+        let result = verification();
+        self.record(|m| m.verification_finished(started.elapsed(), result.is_ok()));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::VerifierError;
+    use crate::types::VerifierConfig;
+    use solana_client::rpc_client::RpcClient;
+    use solana_sdk::pubkey::Pubkey;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counters {
+        sent: AtomicU64,
+        succeeded: AtomicU64,
+        failed: AtomicU64,
+    }
+
+    impl Metrics for Counters {
+        fn transaction_sent(&self) {
+            self.sent.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn verification_finished(&self, _elapsed: Duration, succeeded: bool) {
+            let counter = if succeeded {
+                &self.succeeded
+            } else {
+                &self.failed
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_timed_records_outcome() {
+        let counters = Arc::new(Counters::default());
+        let verifier = SolanaNoirVerifier::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            VerifierConfig::new(Pubkey::new_unique()).with_metrics(counters.clone()),
+        );
+
+        verifier.record(|m| m.transaction_sent());
+        assert_eq!(verifier.timed(|| Ok(1)).unwrap(), 1);
+        assert!(verifier
+            .timed(|| Err::<(), _>(VerifierError::VerificationFailed))
+            .is_err());
+
+        assert_eq!(counters.sent.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.succeeded.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.failed.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,102 @@
+//! Durable nonce accounts for phase transactions
+//!
+//! A phase transaction signed against a recent blockhash expires after
+//! about 150 slots; when earlier phases confirm slowly, later ones can be
+//! built against a blockhash that is gone by the time they are sent. With
+//! [`VerifierConfig::durable_nonce`](crate::VerifierConfig::durable_nonce)
+//! set, the phased flow creates a nonce account owned by the payer, signs
+//! every phase against its stored value (advancing it as the first
+//! instruction) and withdraws it at the end.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use solana_system_interface::instruction as system_instruction;
+
+/// `solana_nonce::state::State::size()`
+const NONCE_ACCOUNT_SIZE: usize = 80;
+
+/// Offset of the stored blockhash: version tag, state tag, authority
+const NONCE_HASH_OFFSET: usize = 4 + 4 + 32;
+
+// Initialized state tag
+const NONCE_INITIALIZED: u32 = 1;
+
+impl SolanaNoirVerifier {
+    /// Create a nonce account with `payer` as its authority
+    pub(crate) fn create_nonce_account(&self, payer: &dyn Signer) -> Result<(Pubkey, Signature)> {
+        let nonce = Keypair::new();
+        let rent = self
+            .client()
+            .get_minimum_balance_for_rent_exemption(NONCE_ACCOUNT_SIZE)?;
+        let ixs = system_instruction::create_nonce_account(
+            &payer.pubkey(),
+            &nonce.pubkey(),
+            &payer.pubkey(),
+            rent,
+        );
+        let sig =
+            self.with_retries(|| self.send_and_confirm(payer, &[&nonce], ixs.clone(), false))?;
+        log::debug!("Created nonce account {}", nonce.pubkey());
+        Ok((nonce.pubkey(), sig))
+    }
+
+    /// Blockhash currently stored in a nonce account
+    pub(crate) fn nonce_blockhash(&self, nonce: &Pubkey) -> Result<Hash> {
+        parse_nonce_blockhash(&self.fetch_account(nonce)?.data)
+    }
+
+    /// Withdraw a nonce account's balance back to `payer`, closing it
+    pub(crate) fn close_nonce_account(
+        &self,
+        payer: &dyn Signer,
+        nonce: &Pubkey,
+    ) -> Result<Signature> {
+        let lamports = self.fetch_account(nonce)?.lamports;
+        let ix = system_instruction::withdraw_nonce_account(
+            nonce,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            lamports,
+        );
+        self.with_retries(|| self.send_and_confirm(payer, &[], vec![ix.clone()], false))
+    }
+}
+
+fn parse_nonce_blockhash(data: &[u8]) -> Result<Hash> {
+    let initialized = data
+        .get(4..8)
+        .is_some_and(|tag| u32::from_le_bytes(tag.try_into().unwrap()) == NONCE_INITIALIZED);
+    match data.get(NONCE_HASH_OFFSET..NONCE_HASH_OFFSET + 32) {
+        Some(hash) if initialized => Ok(Hash::new_from_array(hash.try_into().unwrap())),
+        _ => Err(VerifierError::TransactionFailed(
+            "nonce account is not initialized".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nonce_blockhash() {
+        let hash = Hash::new_from_array([7u8; 32]);
+        let mut data = vec![0u8; NONCE_ACCOUNT_SIZE];
+        // Versions::Current, State::Initialized, authority, durable nonce, fee
+        data[0..4].copy_from_slice(&1u32.to_le_bytes());
+        data[4..8].copy_from_slice(&NONCE_INITIALIZED.to_le_bytes());
+        data[NONCE_HASH_OFFSET..NONCE_HASH_OFFSET + 32].copy_from_slice(hash.as_ref());
+        assert_eq!(parse_nonce_blockhash(&data).unwrap(), hash);
+
+        data[4..8].copy_from_slice(&0u32.to_le_bytes());
+        assert!(parse_nonce_blockhash(&data).is_err());
+        assert!(parse_nonce_blockhash(&[]).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Building verification transactions without a local keypair
+//!
+//! [`SolanaNoirVerifier::verify_phased`] signs with a `Keypair` as it goes.
+//! Payers held in a multisig, HSM or wallet instead get the whole flow up
+//! front: [`SolanaNoirVerifier::build_phase_transactions`] returns one
+//! transaction per step, signed by whichever signers are at hand, to be
+//! completed with [`partial_sign`] (or by writing the external signature
+//! into place) and sent with [`SolanaNoirVerifier::submit_transactions`].
+//!
+//! Every phase instruction is fixed once the circuit size is known, so the
+//! phases are built from the VK's `log_n` before anything is on chain. All
+//! transactions share one blockhash; rebuild them if signing takes longer
+//! than its ~60 second lifetime.
+
+use crate::{
+    client::{split_into_chunks, SolanaNoirVerifier},
+    error::{Result, VerifierError},
+    instructions,
+    phases::remaining_steps,
+    rpc::RpcOperation,
+    types::*,
+};
+use plonk_solana_core::proof::Proof;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::VersionedTransaction,
+};
+
+/// Accounts a verification built offline uses
+///
+/// The proof buffer and state accounts are new; whoever holds their keys
+/// signs the first setup transaction along with the payer and authority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyAccounts {
+    /// Pays fees and rent, and receives the rent back on close
+    pub payer: Pubkey,
+    /// Proof owner co-signing the setup when `payer` is a sponsor
+    pub authority: Option<Pubkey>,
+    pub proof_account: Pubkey,
+    pub state_account: Pubkey,
+    pub vk_account: Pubkey,
+}
+
+/// Instructions for one transaction, with its phase CU limit if any
+struct PlannedTransaction {
+    instructions: Vec<Instruction>,
+    cu_limit: Option<u32>,
+}
+
+impl SolanaNoirVerifier {
+    /// Every instruction of a verification, in submission order
+    ///
+    /// Setup, chunk uploads, phases, receipt and the close the
+    /// [`CleanupPolicy`] selects, as [`verify_phased`](Self::verify_phased)
+    /// sends them. Only reads from
+    /// the RPC (rent and the VK's circuit size).
+    pub fn build_verify_instructions(
+        &self,
+        accounts: &VerifyAccounts,
+        proof: &[u8],
+        public_inputs: &[u8],
+    ) -> Result<Vec<Instruction>> {
+        Ok(self
+            .plan_transactions(accounts, proof, public_inputs)?
+            .into_iter()
+            .flat_map(|tx| tx.instructions)
+            .collect())
+    }
+
+    /// One transaction per step of a verification, signed by `signers`
+    ///
+    /// Signatures of required signers missing from `signers` are left as
+    /// [`Signature::default`]. Transactions must be submitted in order,
+    /// each after the previous one confirmed.
+    pub fn build_phase_transactions(
+        &self,
+        accounts: &VerifyAccounts,
+        signers: &[&dyn Signer],
+        proof: &[u8],
+        public_inputs: &[u8],
+        recent_blockhash: Hash,
+    ) -> Result<Vec<VersionedTransaction>> {
+        self.plan_transactions(accounts, proof, public_inputs)?
+            .into_iter()
+            .map(|tx| {
+                let message = self.compile_message(
+                    &accounts.payer,
+                    &tx.instructions,
+                    tx.cu_limit,
+                    recent_blockhash,
+                    None,
+                )?;
+                let mut tx = VersionedTransaction {
+                    signatures: vec![
+                        Signature::default();
+                        message.header().num_required_signatures as usize
+                    ],
+                    message,
+                };
+                partial_sign(&mut tx, signers)?;
+                Ok(tx)
+            })
+            .collect()
+    }
+
+    /// Send fully signed transactions in order, confirming each
+    pub fn submit_transactions(
+        &self,
+        transactions: &[VersionedTransaction],
+    ) -> Result<Vec<Signature>> {
+        let config = solana_client::rpc_config::RpcSendTransactionConfig::default();
+        transactions
+            .iter()
+            .map(|tx| {
+                if tx.signatures.contains(&Signature::default()) {
+                    return Err(VerifierError::TransactionFailed(
+                        "transaction is missing signatures".to_string(),
+                    ));
+                }
+                let sig = self
+                    .rpc_call(RpcOperation::Send, |rpc| {
+                        rpc.send_transaction_with_config(tx, config)
+                    })
+                    .map_err(VerifierError::from_client_error)?;
+                self.notify(|o| o.transaction_sent(&sig));
+                self.record(|m| m.transaction_sent());
+                self.wait_for_confirmation(&sig)?;
+                self.notify(|o| o.transaction_confirmed(&sig));
+                Ok(sig)
+            })
+            .collect()
+    }
+
+    fn plan_transactions(
+        &self,
+        accounts: &VerifyAccounts,
+        proof: &[u8],
+        public_inputs: &[u8],
+    ) -> Result<Vec<PlannedTransaction>> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        let program_id = &self.config.program_id;
+        let VerifyAccounts {
+            payer,
+            authority,
+            proof_account,
+            state_account,
+            vk_account,
+        } = accounts;
+        let unbudgeted = |instructions| PlannedTransaction {
+            instructions,
+            cu_limit: None,
+        };
+
+        let setup = self.setup_instructions(
+            payer,
+            authority.as_ref(),
+            proof_account,
+            state_account,
+            public_inputs,
+        )?;
+        let mut txs = vec![unbudgeted(setup.instructions)];
+        txs.extend(
+            setup
+                .public_input_uploads
+                .into_iter()
+                .map(|ix| unbudgeted(vec![ix])),
+        );
+
+        let chunk_size = self.proof_chunk_size(payer, proof_account)?;
+        txs.extend(
+            split_into_chunks(proof, chunk_size)
+                .into_iter()
+                .map(|(offset, chunk)| {
+                    unbudgeted(vec![instructions::upload_chunk(
+                        program_id,
+                        proof_account,
+                        offset as u16,
+                        chunk,
+                    )])
+                }),
+        );
+
+        let log_n = self.load_vk(vk_account)?.0.log2_circuit_size as u8;
+        let plan = plonk_solana_core::cu_estimate(log_n, is_zk, public_inputs.len() / 32);
+        let fresh_state = vec![0u8; STATE_SIZE];
+        for step in remaining_steps(&fresh_state, log_n, &plan)? {
+            if let Some(ix) = step.instruction(program_id, state_account, proof_account, vk_account)
+            {
+                txs.push(PlannedTransaction {
+                    cu_limit: Some(self.phase_cu_limit(&plan, &ix)),
+                    instructions: vec![ix],
+                });
+            }
+        }
+
+        let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
+        txs.push(unbudgeted(vec![instructions::create_receipt(
+            program_id,
+            state_account,
+            proof_account,
+            vk_account,
+            &receipt_pda,
+            payer,
+        )]));
+        let cleanup =
+            self.cleanup_instructions(payer, state_account, proof_account, &[], &receipt_pda);
+        if !cleanup.is_empty() {
+            txs.push(unbudgeted(cleanup));
+        }
+
+        Ok(txs)
+    }
+}
+
+/// Add the signatures of `signers` that `tx` requires
+///
+/// Signers the message does not list are ignored, so the same slice can be
+/// passed for every transaction of a verification.
+pub fn partial_sign(tx: &mut VersionedTransaction, signers: &[&dyn Signer]) -> Result<()> {
+    let num_signers = tx.message.header().num_required_signatures as usize;
+    let message = tx.message.serialize();
+    for signer in signers {
+        let pubkey = signer.pubkey();
+        let Some(index) = tx.message.static_account_keys()[..num_signers]
+            .iter()
+            .position(|key| *key == pubkey)
+        else {
+            continue;
+        };
+        tx.signatures[index] = signer
+            .try_sign_message(&message)
+            .map_err(|e| VerifierError::TransactionFailed(e.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{message::VersionedMessage, signature::Keypair};
+
+    #[test]
+    fn test_partial_sign() {
+        let (payer, proof_account, other) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let ix = solana_system_interface::instruction::create_account(
+            &payer.pubkey(),
+            &proof_account.pubkey(),
+            1,
+            1,
+            &Pubkey::new_unique(),
+        );
+        let message = VersionedMessage::Legacy(solana_sdk::message::Message::new(
+            &[ix],
+            Some(&payer.pubkey()),
+        ));
+        let mut tx = VersionedTransaction {
+            signatures: vec![Signature::default(); 2],
+            message,
+        };
+
+        // An unrelated signer is skipped; the payer's slot stays empty
+        partial_sign(&mut tx, &[&proof_account, &other]).unwrap();
+        assert_eq!(tx.signatures[0], Signature::default());
+        assert_ne!(tx.signatures[1], Signature::default());
+
+        partial_sign(&mut tx, &[&payer]).unwrap();
+        assert!(tx.verify_with_results().iter().all(|&ok| ok));
+    }
+}
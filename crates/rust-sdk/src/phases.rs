@@ -0,0 +1,335 @@
+//! Next-step planning for phased verification
+//!
+//! The orchestrator never tracks progress on its own: before every
+//! transaction it re-reads the state account and asks [`next_step`] what to
+//! send. A transaction that timed out but still landed, or a run that was
+//! interrupted half way, therefore continues from whatever the program
+//! actually recorded instead of replaying a phase it already accepted.
+
+use crate::{
+    error::{Result, VerifierError},
+    instructions,
+    types::*,
+};
+use plonk_solana_core::PhasePlan;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+// Program `Phase` values (programs/ultrahonk-verifier/src/phased.rs)
+const PHASE_UNINITIALIZED: u8 = 0;
+const PHASE_CHALLENGES_IN_PROGRESS: u8 = 1;
+const PHASE_CHALLENGES_GENERATED: u8 = 2;
+const PHASE_SUMCHECK_IN_PROGRESS: u8 = 3;
+const PHASE_SUMCHECK_VERIFIED: u8 = 4;
+const PHASE_MSM_IN_PROGRESS: u8 = 5;
+const PHASE_MSM_COMPUTED: u8 = 6;
+const PHASE_COMPLETE: u8 = 7;
+
+// `SumcheckSubPhase::RelationsPart1Done`
+const SUMCHECK_RELATIONS_PART1_DONE: u8 = 5;
+
+// `ShpleminiSubPhase` values
+const SHPLEMINI_3A_DONE: u8 = 1;
+const SHPLEMINI_3B1_DONE: u8 = 2;
+const SHPLEMINI_3B2_DONE: u8 = 3;
+
+/// The next transaction of a phased verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PhaseStep {
+    /// Phase 1: all challenges
+    Challenges,
+    /// Phase 2: sumcheck rounds `start..end`
+    SumcheckRounds { start: u8, end: u8 },
+    /// Phase 2d + 3a: relations and weights
+    RelationsAndWeights,
+    /// Phase 2d on its own, when 3a does not fit the same transaction
+    Relations,
+    /// Phase 2d2, when 2d1 already ran
+    RelationsPart2,
+    /// Phase 3a on its own
+    Weights,
+    /// Phase 3b1 + 3b2: folding and Gemini
+    FoldingAndGemini,
+    /// Phase 3b1 on its own, when 3b2 does not fit the same transaction
+    Folding,
+    /// Phase 3b2 on its own
+    Gemini,
+    /// Phase 3c + 4: MSM and pairing
+    MsmAndPairing,
+    /// Phase 4 after a standalone Phase 3c
+    FinalCheck,
+    /// The proof verified
+    Done,
+    /// The program rejected the proof
+    Failed,
+}
+
+impl PhaseStep {
+    /// Short name of the step's kind, without round numbers
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Challenges => "challenges",
+            Self::SumcheckRounds { .. } => "sumcheck",
+            Self::RelationsAndWeights => "relations_and_weights",
+            Self::Relations => "relations",
+            Self::RelationsPart2 => "relations_part2",
+            Self::Weights => "weights",
+            Self::FoldingAndGemini => "folding_and_gemini",
+            Self::Folding => "folding",
+            Self::Gemini => "gemini",
+            Self::MsmAndPairing => "msm_and_pairing",
+            Self::FinalCheck => "final_check",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Build the instruction for this step, if it needs one
+    pub(crate) fn instruction(
+        self,
+        program_id: &Pubkey,
+        state_account: &Pubkey,
+        proof_account: &Pubkey,
+        vk_account: &Pubkey,
+    ) -> Option<Instruction> {
+        let (state, proof) = (state_account, proof_account);
+        Some(match self {
+            Self::Challenges => instructions::phase1_full(program_id, state, proof, vk_account),
+            Self::SumcheckRounds { start, end } => {
+                instructions::phase2_rounds(program_id, state, proof, start, end)
+            }
+            Self::RelationsAndWeights => instructions::phase2d_and_3a(program_id, state, proof),
+            Self::Relations => instructions::phase2d_relations(program_id, state, proof),
+            Self::RelationsPart2 => instructions::phase2d_relations_part2(program_id, state, proof),
+            Self::Weights => instructions::phase3a_weights(program_id, state, proof),
+            Self::FoldingAndGemini => instructions::phase3b_combined(program_id, state, proof),
+            Self::Folding => instructions::phase3b1_folding(program_id, state, proof),
+            Self::Gemini => instructions::phase3b2_gemini(program_id, state, proof),
+            Self::MsmAndPairing => {
+                instructions::phase3c_and_pairing(program_id, state, proof, vk_account)
+            }
+            Self::FinalCheck => instructions::final_check(program_id, state, vk_account),
+            Self::Done | Self::Failed => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for PhaseStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Challenges => write!(f, "Phase 1: challenges"),
+            Self::SumcheckRounds { start, end } => {
+                write!(f, "Phase 2: sumcheck rounds {}..{}", start, end)
+            }
+            Self::RelationsAndWeights => write!(f, "Phase 2d+3a: relations and weights"),
+            Self::Relations => write!(f, "Phase 2d: relations"),
+            Self::RelationsPart2 => write!(f, "Phase 2d: relations (part 2)"),
+            Self::Weights => write!(f, "Phase 3a: weights"),
+            Self::FoldingAndGemini => write!(f, "Phase 3b: folding and Gemini"),
+            Self::Folding => write!(f, "Phase 3b1: folding"),
+            Self::Gemini => write!(f, "Phase 3b2: Gemini"),
+            Self::MsmAndPairing => write!(f, "Phase 3c+4: MSM and pairing"),
+            Self::FinalCheck => write!(f, "Phase 4: pairing"),
+            Self::Done => write!(f, "verified"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Decide the next step from raw state account data
+///
+/// `plan` decides how many sumcheck rounds are batched into one Phase 2
+/// transaction and whether paired phases share a transaction; large
+/// circuits send them separately.
+pub(crate) fn next_step(state: &[u8], plan: &PhasePlan) -> Result<PhaseStep> {
+    if state.len() < STATE_SIZE {
+        return Err(VerifierError::InvalidStateData);
+    }
+
+    let log_n = state[3];
+    let rounds_completed = state[STATE_ROUNDS_COMPLETED_OFFSET];
+    let shplemini_sub_phase = state[STATE_SHPLEMINI_SUB_PHASE_OFFSET];
+
+    Ok(match state[0] {
+        PHASE_UNINITIALIZED | PHASE_CHALLENGES_IN_PROGRESS => PhaseStep::Challenges,
+        PHASE_CHALLENGES_GENERATED | PHASE_SUMCHECK_IN_PROGRESS => {
+            if rounds_completed < log_n {
+                PhaseStep::SumcheckRounds {
+                    start: rounds_completed,
+                    end: rounds_completed
+                        .saturating_add(plan.rounds_per_tx())
+                        .min(log_n),
+                }
+            } else if state[2] == SUMCHECK_RELATIONS_PART1_DONE {
+                PhaseStep::RelationsPart2
+            } else if plan.use_relations_and_weights() {
+                PhaseStep::RelationsAndWeights
+            } else {
+                PhaseStep::Relations
+            }
+        }
+        PHASE_SUMCHECK_VERIFIED => PhaseStep::Weights,
+        PHASE_MSM_IN_PROGRESS => match shplemini_sub_phase {
+            SHPLEMINI_3A_DONE if plan.use_combined_folding() => PhaseStep::FoldingAndGemini,
+            SHPLEMINI_3A_DONE => PhaseStep::Folding,
+            SHPLEMINI_3B1_DONE => PhaseStep::Gemini,
+            SHPLEMINI_3B2_DONE => PhaseStep::MsmAndPairing,
+            _ => return Err(VerifierError::InvalidStateData),
+        },
+        PHASE_MSM_COMPUTED => PhaseStep::FinalCheck,
+        PHASE_COMPLETE if state[STATE_VERIFIED_OFFSET] == 1 => PhaseStep::Done,
+        _ => PhaseStep::Failed,
+    })
+}
+
+/// Every step left to finish, assuming each one succeeds
+///
+/// Replays [`next_step`] on a copy of the state, applying the transition the
+/// program records for each step. Before Phase 1 the state has no `log_n`
+/// yet, so the caller passes the one from the VK.
+pub(crate) fn remaining_steps(state: &[u8], log_n: u8, plan: &PhasePlan) -> Result<Vec<PhaseStep>> {
+    let mut state = state.to_vec();
+    if state.len() >= STATE_SIZE && state[3] == 0 {
+        state[3] = log_n;
+    }
+
+    let mut steps = Vec::new();
+    loop {
+        let step = next_step(&state, plan)?;
+        let (phase, sub_phase) = match step {
+            PhaseStep::Done | PhaseStep::Failed => return Ok(steps),
+            PhaseStep::Challenges => (PHASE_CHALLENGES_GENERATED, None),
+            PhaseStep::SumcheckRounds { end, .. } => {
+                state[STATE_ROUNDS_COMPLETED_OFFSET] = end;
+                (PHASE_SUMCHECK_IN_PROGRESS, None)
+            }
+            PhaseStep::RelationsPart2 | PhaseStep::Relations => (PHASE_SUMCHECK_VERIFIED, None),
+            PhaseStep::RelationsAndWeights | PhaseStep::Weights => {
+                (PHASE_MSM_IN_PROGRESS, Some(SHPLEMINI_3A_DONE))
+            }
+            PhaseStep::Folding => (PHASE_MSM_IN_PROGRESS, Some(SHPLEMINI_3B1_DONE)),
+            PhaseStep::FoldingAndGemini | PhaseStep::Gemini => {
+                (PHASE_MSM_IN_PROGRESS, Some(SHPLEMINI_3B2_DONE))
+            }
+            PhaseStep::MsmAndPairing | PhaseStep::FinalCheck => {
+                state[STATE_VERIFIED_OFFSET] = 1;
+                (PHASE_COMPLETE, None)
+            }
+        };
+        state[0] = phase;
+        if let Some(sub_phase) = sub_phase {
+            state[STATE_SHPLEMINI_SUB_PHASE_OFFSET] = sub_phase;
+        }
+        steps.push(step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plonk_solana_core::{cu_estimate, MAX_LOG_N};
+
+    fn state(phase: u8, log_n: u8, rounds: u8) -> Vec<u8> {
+        let mut data = vec![0u8; STATE_SIZE];
+        data[0] = phase;
+        data[3] = log_n;
+        data[STATE_ROUNDS_COMPLETED_OFFSET] = rounds;
+        data
+    }
+
+    #[test]
+    fn test_next_step_walks_every_phase() {
+        let plan = cu_estimate(14, true, 1);
+        assert_eq!(
+            next_step(&state(PHASE_UNINITIALIZED, 0, 0), &plan).unwrap(),
+            PhaseStep::Challenges
+        );
+        assert_eq!(
+            next_step(&state(PHASE_CHALLENGES_GENERATED, 14, 0), &plan).unwrap(),
+            PhaseStep::SumcheckRounds { start: 0, end: 6 }
+        );
+        assert_eq!(
+            next_step(&state(PHASE_SUMCHECK_IN_PROGRESS, 14, 12), &plan).unwrap(),
+            PhaseStep::SumcheckRounds { start: 12, end: 14 }
+        );
+        assert_eq!(
+            next_step(&state(PHASE_SUMCHECK_IN_PROGRESS, 14, 14), &plan).unwrap(),
+            PhaseStep::RelationsAndWeights
+        );
+
+        let mut split = state(PHASE_SUMCHECK_IN_PROGRESS, 14, 14);
+        split[2] = SUMCHECK_RELATIONS_PART1_DONE;
+        assert_eq!(next_step(&split, &plan).unwrap(), PhaseStep::RelationsPart2);
+
+        let mut msm = state(PHASE_MSM_IN_PROGRESS, 14, 14);
+        msm[STATE_SHPLEMINI_SUB_PHASE_OFFSET] = SHPLEMINI_3A_DONE;
+        assert_eq!(next_step(&msm, &plan).unwrap(), PhaseStep::FoldingAndGemini);
+        msm[STATE_SHPLEMINI_SUB_PHASE_OFFSET] = SHPLEMINI_3B2_DONE;
+        assert_eq!(next_step(&msm, &plan).unwrap(), PhaseStep::MsmAndPairing);
+
+        let mut complete = state(PHASE_COMPLETE, 14, 14);
+        assert_eq!(next_step(&complete, &plan).unwrap(), PhaseStep::Failed);
+        complete[STATE_VERIFIED_OFFSET] = 1;
+        assert_eq!(next_step(&complete, &plan).unwrap(), PhaseStep::Done);
+
+        assert_eq!(
+            next_step(&state(255, 14, 3), &plan).unwrap(),
+            PhaseStep::Failed
+        );
+        assert!(next_step(&[0u8; 16], &plan).is_err());
+    }
+
+    #[test]
+    fn test_remaining_steps() {
+        let plan = cu_estimate(14, true, 1);
+        assert_eq!(
+            remaining_steps(&state(PHASE_UNINITIALIZED, 0, 0), 14, &plan).unwrap(),
+            vec![
+                PhaseStep::Challenges,
+                PhaseStep::SumcheckRounds { start: 0, end: 6 },
+                PhaseStep::SumcheckRounds { start: 6, end: 12 },
+                PhaseStep::SumcheckRounds { start: 12, end: 14 },
+                PhaseStep::RelationsAndWeights,
+                PhaseStep::FoldingAndGemini,
+                PhaseStep::MsmAndPairing,
+            ]
+        );
+
+        let mut msm = state(PHASE_MSM_IN_PROGRESS, 14, 14);
+        msm[STATE_SHPLEMINI_SUB_PHASE_OFFSET] = SHPLEMINI_3B1_DONE;
+        assert_eq!(
+            remaining_steps(&msm, 14, &plan).unwrap(),
+            vec![PhaseStep::Gemini, PhaseStep::MsmAndPairing]
+        );
+        assert!(remaining_steps(&state(255, 14, 3), 14, &plan)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_large_circuit_splits_paired_phases() {
+        let plan = cu_estimate(MAX_LOG_N, true, 1);
+        let steps = remaining_steps(&state(PHASE_UNINITIALIZED, 0, 0), MAX_LOG_N, &plan).unwrap();
+        let rounds = steps
+            .iter()
+            .filter_map(|step| match step {
+                PhaseStep::SumcheckRounds { start, end } => Some(end - start),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rounds.iter().map(|&r| r as u32).sum::<u32>(),
+            MAX_LOG_N as u32
+        );
+        assert!(rounds.iter().all(|&r| plan.fits_rounds(r as usize)));
+        assert_eq!(
+            steps[steps.len() - 5..],
+            [
+                PhaseStep::Relations,
+                PhaseStep::Weights,
+                PhaseStep::Folding,
+                PhaseStep::Gemini,
+                PhaseStep::MsmAndPairing,
+            ]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Progress reporting for long verification flows
+//!
+//! A phased verification sends a few dozen transactions over tens of
+//! seconds. An observer set with
+//! [`VerifierConfig::with_progress_observer`](crate::VerifierConfig::with_progress_observer)
+//! is told about each step as it happens, so a CLI can drive a spinner or a
+//! server can stream updates to its clients.
+
+use crate::client::SolanaNoirVerifier;
+use solana_sdk::signature::Signature;
+
+/// Receives progress events from the verifier
+///
+/// Every method defaults to doing nothing. Chunks upload from several
+/// threads, so events may arrive concurrently and out of chunk order.
+pub trait ProgressObserver: Send + Sync {
+    /// A verification phase is about to be sent, e.g.
+    /// `"Phase 2: sumcheck rounds 0..6"`
+    fn phase_started(&self, _phase: &str) {}
+
+    /// A transaction was accepted by the RPC node
+    fn transaction_sent(&self, _signature: &Signature) {}
+
+    /// A transaction confirmed successfully
+    fn transaction_confirmed(&self, _signature: &Signature) {}
+
+    /// Compute units a confirmed phase transaction consumed
+    fn compute_units_consumed(&self, _signature: &Signature, _units: u64) {}
+
+    /// `uploaded` of the `total` proof chunks being sent have landed
+    fn chunks_uploaded(&self, _uploaded: usize, _total: usize) {}
+
+    /// Job `index` of a [`verify_many`](SolanaNoirVerifier::verify_many)
+    /// batch finished
+    fn job_finished(&self, _index: usize, _succeeded: bool) {}
+}
+
+impl SolanaNoirVerifier {
+    /// Pass an event to the configured observer, if any
+    pub(crate) fn notify(&self, event: impl FnOnce(&dyn ProgressObserver)) {
+        if let Some(observer) = &self.config.progress {
+            event(observer.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VerifierConfig;
+    use solana_client::rpc_client::RpcClient;
+    use solana_sdk::pubkey::Pubkey;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl ProgressObserver for Recorder {
+        fn phase_started(&self, phase: &str) {
+            self.0.lock().unwrap().push(phase.to_string());
+        }
+
+        fn chunks_uploaded(&self, uploaded: usize, total: usize) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}/{}", uploaded, total));
+        }
+    }
+
+    #[test]
+    fn test_notify_reaches_observer() {
+        let recorder = Arc::new(Recorder::default());
+        let config =
+            VerifierConfig::new(Pubkey::new_unique()).with_progress_observer(recorder.clone());
+        let verifier = SolanaNoirVerifier::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            config,
+        );
+
+        verifier.notify(|o| o.phase_started("Phase 1: challenges"));
+        verifier.notify(|o| o.transaction_sent(&Signature::default()));
+        verifier.notify(|o| o.chunks_uploaded(3, 16));
+        assert_eq!(*recorder.0.lock().unwrap(), ["Phase 1: challenges", "3/16"]);
+    }
+}
@@ -0,0 +1,237 @@
+//! Proof generation with the Noir toolchain
+//!
+//! Runs `nargo execute` to solve the witness and `bb prove` to produce the
+//! proof, VK and public inputs, the same steps as `test-circuits/build_all.sh`.
+//! Both binaries must be installed (see the README prerequisites); the
+//! versions pinned there produce the bb 0.87 formats the verifier expects.
+
+use crate::error::{Result, VerifierError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Transcript hash `bb prove` uses for Fiat-Shamir challenges
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OracleHash {
+    /// The only transcript the Solana verifier supports
+    #[default]
+    Keccak,
+    Poseidon2,
+    Starknet,
+}
+
+impl OracleHash {
+    /// Value of bb's `--oracle_hash` flag
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keccak => "keccak",
+            Self::Poseidon2 => "poseidon2",
+            Self::Starknet => "starknet",
+        }
+    }
+}
+
+/// How to prove a Noir circuit
+#[derive(Clone, Debug)]
+pub struct ProveOptions {
+    /// Directory containing the circuit's `Nargo.toml`
+    pub circuit_dir: PathBuf,
+    /// Prover inputs file (`<witness>.toml` in `circuit_dir`), also used as
+    /// the witness name; `None` means `Prover`
+    pub witness: Option<String>,
+    pub oracle_hash: OracleHash,
+    /// Generate a ZK proof (`--zk`)
+    pub zk: bool,
+    /// `nargo` binary
+    pub nargo: PathBuf,
+    /// `bb` binary; by default `bb` on the `PATH`, else `~/.bb/bb`
+    pub bb: PathBuf,
+}
+
+impl ProveOptions {
+    /// Keccak ZK proofs for the circuit in `circuit_dir`
+    pub fn new(circuit_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            circuit_dir: circuit_dir.into(),
+            witness: None,
+            oracle_hash: OracleHash::Keccak,
+            zk: true,
+            nargo: PathBuf::from("nargo"),
+            bb: default_bb(),
+        }
+    }
+
+    /// Read inputs from `<name>.toml` instead of `Prover.toml`
+    pub fn with_witness(mut self, name: impl Into<String>) -> Self {
+        self.witness = Some(name.into());
+        self
+    }
+
+    /// Set the transcript hash
+    pub fn with_oracle_hash(mut self, oracle_hash: OracleHash) -> Self {
+        self.oracle_hash = oracle_hash;
+        self
+    }
+
+    /// Set whether the proof is ZK
+    pub fn with_zk(mut self, zk: bool) -> Self {
+        self.zk = zk;
+        self
+    }
+
+    /// Use a specific `nargo` binary
+    pub fn with_nargo(mut self, path: impl Into<PathBuf>) -> Self {
+        self.nargo = path.into();
+        self
+    }
+
+    /// Use a specific `bb` binary
+    pub fn with_bb(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bb = path.into();
+        self
+    }
+}
+
+/// Output of [`prove`], in the formats [`SolanaNoirVerifier::verify`] takes
+///
+/// [`SolanaNoirVerifier::verify`]: crate::SolanaNoirVerifier::verify
+#[derive(Clone, Debug)]
+pub struct ProverOutput {
+    pub proof: Vec<u8>,
+    pub vk: Vec<u8>,
+    /// Concatenated 32-byte big-endian field elements
+    pub public_inputs: Vec<u8>,
+    /// Directory bb wrote the files to (`target/<oracle_hash>`)
+    pub output_dir: PathBuf,
+}
+
+/// Execute a Noir circuit and prove it with bb
+pub fn prove(options: &ProveOptions) -> Result<ProverOutput> {
+    let dir = &options.circuit_dir;
+    let circuit = circuit_name(&fs::read_to_string(dir.join("Nargo.toml")).map_err(io)?)
+        .ok_or_else(|| VerifierError::Prover("Nargo.toml has no package name".to_string()))?;
+    let witness = options.witness.as_deref().unwrap_or("Prover");
+
+    let mut nargo = Command::new(&options.nargo);
+    nargo
+        .current_dir(dir)
+        .args(["execute", witness, "--prover-name", witness]);
+    run(nargo)?;
+
+    let output_dir = dir.join("target").join(options.oracle_hash.as_str());
+    fs::create_dir_all(&output_dir).map_err(io)?;
+    let mut bb = Command::new(&options.bb);
+    bb.current_dir(dir)
+        .args(bb_prove_args(&circuit, witness, options));
+    run(bb)?;
+
+    let read = |name: &str| fs::read(output_dir.join(name)).map_err(io);
+    Ok(ProverOutput {
+        proof: read("proof")?,
+        vk: read("vk")?,
+        public_inputs: read("public_inputs")?,
+        output_dir,
+    })
+}
+
+fn bb_prove_args(circuit: &str, witness: &str, options: &ProveOptions) -> Vec<String> {
+    let mut args = vec![
+        "prove".to_string(),
+        "-b".to_string(),
+        format!("target/{}.json", circuit),
+        "-w".to_string(),
+        format!("target/{}.gz", witness),
+        "-o".to_string(),
+        format!("target/{}", options.oracle_hash.as_str()),
+        "--oracle_hash".to_string(),
+        options.oracle_hash.as_str().to_string(),
+        "--write_vk".to_string(),
+    ];
+    if options.zk {
+        args.push("--zk".to_string());
+    }
+    args
+}
+
+/// `name` from the `[package]` table of a Nargo.toml
+fn circuit_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if let Some((key, value)) = line.split_once('=') {
+            if in_package && key.trim() == "name" {
+                return Some(value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
+
+fn default_bb() -> PathBuf {
+    let on_path = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join("bb").is_file()))
+        .unwrap_or(false);
+    match std::env::var_os("HOME") {
+        Some(home) if !on_path && Path::new(&home).join(".bb/bb").is_file() => {
+            Path::new(&home).join(".bb/bb")
+        }
+        _ => PathBuf::from("bb"),
+    }
+}
+
+fn run(mut command: Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    log::debug!("Running {:?}", command);
+    let output = command
+        .output()
+        .map_err(|e| VerifierError::Prover(format!("failed to run {}: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(VerifierError::Prover(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn io(e: std::io::Error) -> VerifierError {
+    VerifierError::Prover(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_name() {
+        let manifest = "[package]\nname = \"simple_square\"\ntype = \"bin\"\n\n[dependencies]\n";
+        assert_eq!(circuit_name(manifest).as_deref(), Some("simple_square"));
+        assert_eq!(circuit_name("[dependencies]\nname = \"dep\"\n"), None);
+    }
+
+    #[test]
+    fn test_bb_prove_args() {
+        let options = ProveOptions::new("circuits/square").with_zk(false);
+        let args = bb_prove_args("square", "Prover", &options);
+        assert_eq!(
+            args,
+            [
+                "prove",
+                "-b",
+                "target/square.json",
+                "-w",
+                "target/Prover.gz",
+                "-o",
+                "target/keccak",
+                "--oracle_hash",
+                "keccak",
+                "--write_vk"
+            ]
+        );
+        let zk = bb_prove_args("square", "Prover", &ProveOptions::new("."));
+        assert_eq!(zk.last().map(String::as_str), Some("--zk"));
+    }
+}
@@ -0,0 +1,296 @@
+//! Public inputs in the verifier's canonical layout
+//!
+//! The proof buffer, the transcript and the receipt hash all take public
+//! inputs as 32-byte big-endian field elements, concatenated. A value like
+//! `y = 9` is therefore `[0; 31]` followed by `9`, not a single byte.
+//! [`PublicInputs`] builds that layout from the forms values usually come
+//! in, and rejects anything that is not a field element (`>= r`).
+//!
+//! ```
+//! use solana_noir_verifier_sdk::PublicInputs;
+//!
+//! let inputs = PublicInputs::new()
+//!     .with_u64(9)
+//!     .with_hex("0x2a")?
+//!     .with_toml_value("\"-1\"")?;
+//! assert_eq!(inputs.len(), 3);
+//! assert_eq!(inputs.to_bytes()[31], 9);
+//! # Ok::<(), solana_noir_verifier_sdk::VerifierError>(())
+//! ```
+
+use crate::error::{Result, VerifierError};
+use plonk_solana_core::{
+    field::{fr_from_u64, fr_neg},
+    types::{FR_MODULUS, SCALAR_ZERO},
+    Fr,
+};
+
+/// Public inputs as BN254 scalar field elements, in circuit order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicInputs {
+    fields: Vec<Fr>,
+}
+
+impl PublicInputs {
+    /// No public inputs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the canonical layout: 32 bytes per input, big-endian
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.len().is_multiple_of(32) {
+            return Err(invalid(format!(
+                "{} bytes is not a whole number of 32-byte field elements",
+                bytes.len()
+            )));
+        }
+        bytes
+            .chunks_exact(32)
+            .try_fold(Self::new(), |inputs, chunk| inputs.with_bytes(chunk))
+    }
+
+    /// Read a `public_inputs` file as `bb prove` writes it
+    pub fn read_bb(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| {
+            VerifierError::NoirArtifact(format!("cannot read {}: {}", path.display(), e))
+        })?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Field elements already known to be below the modulus
+    #[cfg(feature = "artifacts")]
+    pub(crate) fn from_fields(fields: Vec<Fr>) -> Self {
+        Self { fields }
+    }
+
+    /// Append an integer
+    pub fn with_u64(mut self, value: u64) -> Self {
+        self.fields.push(fr_from_u64(value));
+        self
+    }
+
+    /// Append a big-endian integer of at most 32 bytes
+    ///
+    /// Shorter values are left-padded with zeros.
+    pub fn with_bytes(mut self, bytes: &[u8]) -> Result<Self> {
+        self.fields.push(field_from_be_bytes(bytes)?);
+        Ok(self)
+    }
+
+    /// Append a hex integer, with or without `0x` and of any length up to
+    /// 64 digits
+    pub fn with_hex(mut self, hex: &str) -> Result<Self> {
+        self.fields.push(field_from_hex(hex)?);
+        Ok(self)
+    }
+
+    /// Append a value as written in a Noir `Prover.toml`
+    ///
+    /// Accepts decimal and `0x` hex integers, quoted or not, negative
+    /// values (`-1` is `r - 1`, as Noir reads it) and booleans.
+    pub fn with_toml_value(mut self, value: &str) -> Result<Self> {
+        self.fields.push(parse_field_value(value)?);
+        Ok(self)
+    }
+
+    /// Number of public inputs
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether there are no public inputs
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The field elements, in order
+    pub fn fields(&self) -> &[Fr] {
+        &self.fields
+    }
+
+    /// Canonical layout, as the proof buffer and the SDK's `verify` calls
+    /// expect it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.fields.as_flattened().to_vec()
+    }
+
+    /// Public input hash a receipt for these inputs is stored under
+    pub fn hash(&self) -> [u8; 32] {
+        solana_noir_receipt_seeds::pi_hash(self.fields.as_flattened())
+    }
+}
+
+impl From<PublicInputs> for Vec<u8> {
+    fn from(inputs: PublicInputs) -> Self {
+        inputs.to_bytes()
+    }
+}
+
+/// Parse one `Prover.toml`-style value into a field element
+pub(crate) fn parse_field_value(value: &str) -> Result<Fr> {
+    let text = value.trim();
+    let text = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+        .trim();
+    match text {
+        "true" => return Ok(fr_from_u64(1)),
+        "false" => return Ok(SCALAR_ZERO),
+        _ => {}
+    }
+
+    let (negative, magnitude) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let field = if magnitude.starts_with("0x") || magnitude.starts_with("0X") {
+        field_from_hex(magnitude)?
+    } else {
+        field_from_decimal(magnitude)?
+    };
+    Ok(if negative { fr_neg(&field) } else { field })
+}
+
+fn field_from_be_bytes(bytes: &[u8]) -> Result<Fr> {
+    let significant = &bytes[bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len())..];
+    if significant.len() > 32 {
+        return Err(invalid(format!(
+            "{} bytes do not fit a field element",
+            bytes.len()
+        )));
+    }
+    let mut field = SCALAR_ZERO;
+    field[32 - significant.len()..].copy_from_slice(significant);
+    in_field(field, || format!("0x{}", crate::hex::encode_hex(bytes)))
+}
+
+fn field_from_hex(hex: &str) -> Result<Fr> {
+    let digits = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid(format!("{:?} is not a hex integer", hex)));
+    }
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > 64 {
+        return Err(invalid(format!("{} is larger than the field modulus", hex)));
+    }
+    let padded = format!("{:0>64}", digits);
+    let bytes = crate::hex::decode_hex(&padded).expect("validated hex digits");
+    field_from_be_bytes(&bytes)
+}
+
+fn field_from_decimal(decimal: &str) -> Result<Fr> {
+    if decimal.is_empty() || !decimal.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(format!("{:?} is not an integer", decimal)));
+    }
+    let mut field = SCALAR_ZERO;
+    for digit in decimal.bytes() {
+        // field = field * 10 + digit, big-endian
+        let mut carry = u16::from(digit - b'0');
+        for byte in field.iter_mut().rev() {
+            let value = u16::from(*byte) * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return Err(invalid(format!(
+                "{} is larger than the field modulus",
+                decimal
+            )));
+        }
+    }
+    in_field(field, || decimal.to_string())
+}
+
+fn in_field(field: Fr, shown: impl FnOnce() -> String) -> Result<Fr> {
+    if field >= FR_MODULUS {
+        return Err(invalid(format!(
+            "{} is not less than the field modulus",
+            shown()
+        )));
+    }
+    Ok(field)
+}
+
+fn invalid(message: String) -> VerifierError {
+    VerifierError::InvalidPublicInput(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forms_agree() {
+        let nine = fr_from_u64(9);
+        let inputs = PublicInputs::new()
+            .with_u64(9)
+            .with_bytes(&[9])
+            .unwrap()
+            .with_hex("0x9")
+            .unwrap()
+            .with_hex("09")
+            .unwrap()
+            .with_toml_value("\"9\"")
+            .unwrap()
+            .with_toml_value(" 9 ")
+            .unwrap();
+        assert!(inputs.fields().iter().all(|field| *field == nine));
+
+        let bytes = inputs.to_bytes();
+        assert_eq!(bytes.len(), 6 * 32);
+        assert_eq!(PublicInputs::from_bytes(&bytes).unwrap(), inputs);
+        assert_eq!(inputs.hash(), solana_noir_receipt_seeds::pi_hash(&bytes));
+    }
+
+    #[test]
+    fn test_toml_values() {
+        let big = "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+        let r_minus_one = parse_field_value(big).unwrap();
+        assert_eq!(parse_field_value("-1").unwrap(), r_minus_one);
+        assert_eq!(parse_field_value("\"-0x1\"").unwrap(), r_minus_one);
+        assert_eq!(parse_field_value("-0").unwrap(), SCALAR_ZERO);
+        assert_eq!(parse_field_value("true").unwrap(), fr_from_u64(1));
+        assert_eq!(
+            parse_field_value("18446744073709551616").unwrap(),
+            PublicInputs::new()
+                .with_hex("0x10000000000000000")
+                .unwrap()
+                .fields()[0]
+        );
+    }
+
+    #[test]
+    fn test_rejects_values_outside_the_field() {
+        let r = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        for bad in [
+            r,
+            "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+        ] {
+            assert!(matches!(
+                parse_field_value(bad),
+                Err(VerifierError::InvalidPublicInput(_))
+            ));
+        }
+        assert!(parse_field_value(&"9".repeat(80)).is_err());
+        assert!(PublicInputs::new().with_bytes(&FR_MODULUS).is_err());
+        assert!(PublicInputs::new().with_bytes(&[1; 33]).is_err());
+        assert!(PublicInputs::new().with_hex("0xg1").is_err());
+        assert!(parse_field_value("1.5").is_err());
+        assert!(parse_field_value("").is_err());
+        assert!(PublicInputs::from_bytes(&[0; 33]).is_err());
+
+        // Leading zeros are fine, whatever the width
+        let mut padded = vec![0u8; 8];
+        padded.push(7);
+        assert_eq!(
+            PublicInputs::new().with_bytes(&padded).unwrap().fields()[0],
+            fr_from_u64(7)
+        );
+    }
+}
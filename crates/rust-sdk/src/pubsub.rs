@@ -0,0 +1,312 @@
+//! Websocket notifications for confirmations and receipts
+//!
+//! With [`VerifierConfig::websocket_url`] set, the client waits for
+//! `signatureSubscribe` and `accountSubscribe` notifications instead of
+//! polling `getSignatureStatuses` every 200ms. Each wait opens its own
+//! connection on a small current-thread runtime, so the public API stays
+//! blocking. If the connection cannot be opened the caller falls back to
+//! polling.
+//!
+//! [`SolanaNoirVerifier::watch_receipts`] streams every new receipt of a VK
+//! through `programSubscribe`; it has no polling fallback.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    rpc::RpcOperation,
+    types::*,
+};
+use futures_util::{Stream, StreamExt};
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client_api::{
+    config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
+        UiAccountEncoding,
+    },
+    filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+/// How long to wait for a transaction to confirm
+pub(crate) const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Polling interval when no websocket is configured
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+impl SolanaNoirVerifier {
+    /// Block until a signature notification says `sig` landed
+    ///
+    /// Returns `false` when the websocket could not be used; the caller
+    /// then polls instead. The status itself is read by the caller, since
+    /// notifications only carry a JSON-encoded error.
+    pub(crate) fn await_signature(&self, url: &str, sig: &Signature) -> Result<bool> {
+        let config = RpcSignatureSubscribeConfig {
+            commitment: Some(self.client().commitment()),
+            enable_received_notification: Some(false),
+        };
+        let landed = (|| {
+            let runtime = runtime()?;
+            let pubsub = runtime.block_on(PubsubClient::new(url)).map_err(text)?;
+            let (mut notifications, unsubscribe) = runtime
+                .block_on(pubsub.signature_subscribe(sig, Some(config)))
+                .map_err(text)?;
+            let landed = wait_for(&runtime, &mut notifications, CONFIRMATION_TIMEOUT, || {
+                Ok(self
+                    .rpc_call(RpcOperation::Confirm, |rpc| rpc.get_signature_status(sig))?
+                    .map(|_| ()))
+            });
+            drop(notifications);
+            runtime.block_on(unsubscribe());
+            landed
+        })();
+
+        match landed {
+            Ok(Some(())) => Ok(true),
+            Ok(None) => Err(VerifierError::ConfirmationTimeout),
+            Err(e) => {
+                log::warn!("Signature subscription failed, polling instead: {}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Wait until the receipt for this VK and these public inputs exists
+    ///
+    /// Subscribes to the receipt PDA when a websocket is configured and
+    /// polls `getAccountInfo` otherwise. Returns
+    /// [`VerifierError::ReceiptNotFound`] after `timeout`.
+    pub fn wait_for_receipt(
+        &self,
+        vk_account: &Pubkey,
+        public_inputs: &[u8],
+        timeout: Duration,
+    ) -> Result<ReceiptInfo> {
+        let read = || self.get_receipt(vk_account, public_inputs);
+
+        if let Some(url) = &self.config.websocket_url {
+            let (receipt_pda, _) = self.derive_receipt_pda(vk_account, public_inputs);
+            let config = RpcAccountInfoConfig {
+                commitment: Some(self.client().commitment()),
+                ..RpcAccountInfoConfig::default()
+            };
+            let found = (|| {
+                let runtime = runtime()?;
+                let pubsub = runtime.block_on(PubsubClient::new(url)).map_err(text)?;
+                let (mut notifications, unsubscribe) = runtime
+                    .block_on(pubsub.account_subscribe(&receipt_pda, Some(config)))
+                    .map_err(text)?;
+                let found = wait_for(&runtime, &mut notifications, timeout, read);
+                drop(notifications);
+                runtime.block_on(unsubscribe());
+                found
+            })();
+            match found {
+                Ok(receipt) => return receipt.ok_or(VerifierError::ReceiptNotFound),
+                Err(e) => log::warn!("Receipt subscription failed, polling instead: {}", e),
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(receipt) = read()? {
+                return Ok(receipt);
+            }
+            if Instant::now() >= deadline {
+                return Err(VerifierError::ReceiptNotFound);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl SolanaNoirVerifier {
+    /// Receipts that exist for `vk_account`
+    pub fn find_receipts(&self, vk_account: &Pubkey) -> Result<Vec<WatchedReceipt>> {
+        let program_id = self.config.program_id;
+        let config = receipts_of(vk_account);
+        let accounts = self.rpc_call(RpcOperation::Fetch, |rpc| {
+            rpc.get_program_ui_accounts_with_config(&program_id, config.clone())
+        })?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(pubkey, account)| watched_receipt(pubkey, &account.data.decode()?))
+            .collect())
+    }
+
+    /// Call `on_receipt` for each receipt created for `vk_account` until it
+    /// returns `false`
+    ///
+    /// Needs [`VerifierConfig::websocket_url`]. Receipts created before the
+    /// subscription are not reported; list them with
+    /// [`find_receipts`](Self::find_receipts). Returns an error if the
+    /// connection drops, so the caller can resubscribe.
+    pub fn watch_receipts(
+        &self,
+        vk_account: &Pubkey,
+        mut on_receipt: impl FnMut(WatchedReceipt) -> bool,
+    ) -> Result<()> {
+        let url = self.config.websocket_url.as_ref().ok_or_else(|| {
+            VerifierError::Websocket("watching receipts needs a websocket URL".to_string())
+        })?;
+        let mut config = receipts_of(vk_account);
+        config.account_config.commitment = Some(self.client().commitment());
+
+        let runtime = runtime().map_err(VerifierError::Websocket)?;
+        let pubsub = runtime
+            .block_on(PubsubClient::new(url))
+            .map_err(|e| VerifierError::Websocket(e.to_string()))?;
+        let (mut notifications, unsubscribe) = runtime
+            .block_on(pubsub.program_subscribe(&self.config.program_id, Some(config)))
+            .map_err(|e| VerifierError::Websocket(e.to_string()))?;
+
+        let result = loop {
+            let Some(notification) = runtime.block_on(notifications.next()) else {
+                break Err(VerifierError::Websocket(
+                    "receipt subscription closed".to_string(),
+                ));
+            };
+            let keyed = notification.value;
+            let receipt = Pubkey::from_str(&keyed.pubkey)
+                .ok()
+                .zip(keyed.account.data.decode())
+                .and_then(|(pubkey, data)| watched_receipt(pubkey, &data));
+            if let Some(receipt) = receipt {
+                if !on_receipt(receipt) {
+                    break Ok(());
+                }
+            }
+        };
+        drop(notifications);
+        runtime.block_on(unsubscribe());
+        result
+    }
+}
+
+/// Filters matching the receipts of `vk_account`
+fn receipts_of(vk_account: &Pubkey) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(RECEIPT_SIZE as u64),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                RECEIPT_VK_ACCOUNT_OFFSET,
+                vk_account.to_bytes().to_vec(),
+            )),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn watched_receipt(receipt_pda: Pubkey, data: &[u8]) -> Option<WatchedReceipt> {
+    let receipt = ReceiptInfo::from_account_data(receipt_pda, data)?;
+    Some(WatchedReceipt {
+        receipt,
+        public_inputs_hash: data[RECEIPT_PI_HASH_OFFSET..RECEIPT_PI_HASH_OFFSET + 32]
+            .try_into()
+            .unwrap(),
+    })
+}
+
+/// Call `read` now and after each notification until it returns a value
+/// or `timeout` passes
+///
+/// `read` runs outside the runtime: the blocking `RpcClient` cannot be
+/// called from within one.
+fn wait_for<T, N>(
+    runtime: &Runtime,
+    notifications: &mut (impl Stream<Item = N> + Unpin),
+    timeout: Duration,
+    read: impl Fn() -> Result<Option<T>>,
+) -> std::result::Result<Option<T>, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        // Checked before waiting: the event may precede the subscription
+        if let Some(value) = read().map_err(text)? {
+            return Ok(Some(value));
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let next = async { tokio::time::timeout(remaining, notifications.next()).await };
+        match runtime.block_on(next) {
+            Ok(Some(_)) => continue,
+            Ok(None) | Err(_) => return Ok(None),
+        }
+    }
+}
+
+fn runtime() -> std::result::Result<Runtime, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(text)
+}
+
+fn text(e: impl std::fmt::Display) -> String {
+    e.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_watched_receipt_layout() {
+        let (vk, pi_hash, payer) = (Pubkey::new_unique(), [9u8; 32], Pubkey::new_unique());
+        let mut data = vec![0u8; RECEIPT_SIZE];
+        data[0..8].copy_from_slice(&42u64.to_le_bytes());
+        data[8..16].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        data[RECEIPT_VK_ACCOUNT_OFFSET..RECEIPT_VK_ACCOUNT_OFFSET + 32]
+            .copy_from_slice(vk.as_ref());
+        data[RECEIPT_PI_HASH_OFFSET..RECEIPT_PI_HASH_OFFSET + 32].copy_from_slice(&pi_hash);
+        data[RECEIPT_RENT_PAYER_OFFSET..RECEIPT_TAG_OFFSET].copy_from_slice(payer.as_ref());
+        data[RECEIPT_VERIFICATION_COUNT_OFFSET..RECEIPT_RENT_PAYER_OFFSET]
+            .copy_from_slice(&2u32.to_le_bytes());
+        data[RECEIPT_TAG_OFFSET..RECEIPT_PROOF_DIGEST_OFFSET].copy_from_slice(&[3; 32]);
+        data[RECEIPT_PROOF_DIGEST_OFFSET..RECEIPT_VK_HASH_OFFSET].copy_from_slice(&[4; 32]);
+        data[RECEIPT_VK_HASH_OFFSET..RECEIPT_RENT_BENEFICIARY_OFFSET].copy_from_slice(&[5; 32]);
+        data[RECEIPT_RENT_BENEFICIARY_OFFSET..].copy_from_slice(payer.as_ref());
+
+        let receipt_pda = Pubkey::new_unique();
+        let watched = watched_receipt(receipt_pda, &data).unwrap();
+        assert_eq!(watched.receipt.receipt_pda, receipt_pda);
+        assert_eq!(watched.receipt.verified_slot, 42);
+        assert_eq!(watched.receipt.verified_timestamp, 1_700_000_000);
+        assert_eq!(watched.receipt.rent_payer, payer);
+        assert_eq!(watched.receipt.tag, [3; 32]);
+        assert_eq!(watched.receipt.verification_count, 2);
+        assert_eq!(watched.receipt.proof_digest, [4; 32]);
+        assert_eq!(watched.receipt.vk_hash, [5; 32]);
+        assert_eq!(watched.receipt.rent_beneficiary, payer);
+        assert_eq!(watched.public_inputs_hash, pi_hash);
+        assert!(watched_receipt(receipt_pda, &data[..RECEIPT_SIZE - 1]).is_none());
+    }
+
+    #[test]
+    fn test_wait_for_rereads_on_notification() {
+        let runtime = runtime().unwrap();
+        let reads = Cell::new(0);
+        let read = || {
+            reads.set(reads.get() + 1);
+            Ok((reads.get() == 3).then_some(reads.get()))
+        };
+
+        // Initial read plus one per notification
+        let mut notifications = futures_util::stream::iter([(), ()]);
+        let found = wait_for(&runtime, &mut notifications, Duration::from_secs(1), read);
+        assert_eq!(found, Ok(Some(3)));
+
+        // The stream ends before `read` finds anything
+        reads.set(0);
+        let mut notifications = futures_util::stream::iter([()]);
+        let found = wait_for(&runtime, &mut notifications, Duration::from_secs(1), read);
+        assert_eq!(found, Ok(None));
+        assert_eq!(reads.get(), 2);
+    }
+}
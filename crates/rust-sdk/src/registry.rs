@@ -0,0 +1,573 @@
+//! Named verification keys
+//!
+//! Integrators otherwise pass raw VK account pubkeys around. A
+//! [`VkRegistry`] is a static mapping from `name@version` to the VK
+//! account and the Keccak256 of the VK bytes, kept in a small text file
+//! that can be checked into a repository:
+//!
+//! ```text
+//! # name@version = vk_account vk_hash
+//! square@1.0.0 = 7Yx...Jq 3f2a...c4
+//! ```
+//!
+//! VK bytes are cached in a separate directory, one file per VK hash,
+//! since an uploaded VK is stored in the program's compiled layout and
+//! cannot be read back as the original bytes.
+//!
+//! With [`VerifierConfig::registry_program`] set, the same keys can instead
+//! be published to the `vk-registry` program, where each name is owned by
+//! an authority and every version records who registered it and when
+//! ([`OnChainRegistryEntry`]). Deprecated versions stay readable but no
+//! longer resolve.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    hex::{decode_hex, encode_hex},
+    instructions::{
+        find_registry_entry_address, registry_deprecate, registry_register,
+        registry_transfer_authority,
+    },
+    rpc::RpcOperation,
+    types::*,
+};
+use plonk_solana_core::transcript::keccak256;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, UiAccountEncoding},
+    filter::RpcFilterType,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Where a named VK lives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub vk_account: Pubkey,
+    /// Keccak256 of the VK bytes
+    pub vk_hash: [u8; 32],
+}
+
+/// A version registered with the `vk-registry` program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnChainRegistryEntry {
+    /// Entry PDA
+    pub address: Pubkey,
+    pub name: String,
+    pub version: String,
+    pub vk_account: Pubkey,
+    /// Keccak256 of the VK bytes, as given at registration
+    pub vk_hash: [u8; 32],
+    /// Authority of the name when the version was registered
+    pub registered_by: Pubkey,
+    pub registered_slot: u64,
+    pub registered_timestamp: i64,
+    /// Slot of deprecation, `None` while the version is active
+    pub deprecated_slot: Option<u64>,
+}
+
+impl OnChainRegistryEntry {
+    /// Parse a registry entry account
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Option<Self> {
+        if data.len() != REGISTRY_ENTRY_SIZE {
+            return None;
+        }
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let label = |offset: usize, len: u8| {
+            let len = len as usize;
+            if len == 0 || len > REGISTRY_MAX_LABEL_LEN {
+                return None;
+            }
+            String::from_utf8(data[offset..offset + len].to_vec()).ok()
+        };
+        Some(Self {
+            address,
+            name: label(120, data[184])?,
+            version: label(152, data[185])?,
+            vk_account: Pubkey::try_from(&data[0..32]).ok()?,
+            vk_hash: data[32..64].try_into().ok()?,
+            registered_by: Pubkey::try_from(&data[64..96]).ok()?,
+            registered_slot: u64_at(96),
+            registered_timestamp: u64_at(104) as i64,
+            deprecated_slot: Some(u64_at(112)).filter(|&slot| slot != 0),
+        })
+    }
+
+    /// `name@version`
+    pub fn key(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated_slot.is_some()
+    }
+}
+
+/// `name@version` to VK account mapping, backed by a file
+#[derive(Clone, Debug)]
+pub struct VkRegistry {
+    path: PathBuf,
+    cache_dir: Option<PathBuf>,
+    entries: BTreeMap<String, RegistryEntry>,
+}
+
+impl VkRegistry {
+    /// Read the registry at `path`; a missing file is an empty registry
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => parse_entries(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(registry_error(e)),
+        };
+        Ok(Self {
+            path,
+            cache_dir: None,
+            entries,
+        })
+    }
+
+    /// Cache VK bytes in `dir`, one `<vk_hash>.vk` file each
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Look up a `name@version` key
+    pub fn resolve(&self, key: &str) -> Result<&RegistryEntry> {
+        parse_key(key)?;
+        self.entries
+            .get(key)
+            .ok_or_else(|| VerifierError::Registry(format!("{} is not registered", key)))
+    }
+
+    /// Every registered `name@version` and its entry
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &RegistryEntry)> {
+        self.entries
+            .iter()
+            .map(|(key, entry)| (key.as_str(), entry))
+    }
+
+    /// Record `key` and write the registry file
+    ///
+    /// A key can only be reassigned to the same VK; publish a new version
+    /// for a changed circuit.
+    pub fn register(&mut self, key: &str, entry: RegistryEntry) -> Result<()> {
+        parse_key(key)?;
+        if let Some(existing) = self.entries.get(key) {
+            if existing.vk_hash != entry.vk_hash {
+                return Err(VerifierError::Registry(format!(
+                    "{} is already registered to a different VK",
+                    key
+                )));
+            }
+        }
+        self.entries.insert(key.to_string(), entry);
+        self.save()
+    }
+
+    /// Store VK bytes in the cache, returning their hash
+    pub fn cache_vk(&self, vk: &[u8]) -> Result<[u8; 32]> {
+        let hash = keccak256(vk);
+        if let Some(path) = self.cache_path(&hash) {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(registry_error)?;
+            }
+            fs::write(path, vk).map_err(registry_error)?;
+        }
+        Ok(hash)
+    }
+
+    /// Cached VK bytes for `key`, checked against the registered hash
+    pub fn vk_bytes(&self, key: &str) -> Result<Vec<u8>> {
+        let entry = self.resolve(key)?;
+        let path = self
+            .cache_path(&entry.vk_hash)
+            .ok_or_else(|| VerifierError::Registry("no cache directory set".to_string()))?;
+        let vk = fs::read(&path).map_err(registry_error)?;
+        if keccak256(&vk) != entry.vk_hash {
+            return Err(VerifierError::Registry(format!(
+                "cached VK {} does not match its hash",
+                path.display()
+            )));
+        }
+        Ok(vk)
+    }
+
+    fn cache_path(&self, vk_hash: &[u8; 32]) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.vk", encode_hex(vk_hash))))
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut contents = String::from("# name@version = vk_account vk_hash\n");
+        for (key, entry) in &self.entries {
+            contents.push_str(&format!(
+                "{} = {} {}\n",
+                key,
+                entry.vk_account,
+                encode_hex(&entry.vk_hash)
+            ));
+        }
+        write_file(&self.path, &contents)
+    }
+}
+
+impl SolanaNoirVerifier {
+    /// Upload a VK and register it as `key` (`name@version`)
+    ///
+    /// If `key` already names this exact VK, nothing is uploaded and the
+    /// existing entry is returned.
+    pub fn publish_vk(
+        &self,
+        payer: &Keypair,
+        registry: &mut VkRegistry,
+        key: &str,
+        vk: &[u8],
+    ) -> Result<RegistryEntry> {
+        parse_key(key)?;
+        let vk_hash = registry.cache_vk(vk)?;
+        if let Ok(existing) = registry.resolve(key) {
+            if existing.vk_hash == vk_hash {
+                return Ok(*existing);
+            }
+        }
+        let upload = self.upload_vk(payer, vk)?;
+        let entry = RegistryEntry {
+            vk_account: upload.vk_account,
+            vk_hash,
+        };
+        registry.register(key, entry)?;
+        Ok(entry)
+    }
+
+    /// VK account registered as `key`, checked to exist on chain
+    pub fn resolve_vk(&self, registry: &VkRegistry, key: &str) -> Result<Pubkey> {
+        let entry = registry.resolve(key)?;
+        self.check_vk_account(key, &entry.vk_account)?;
+        Ok(entry.vk_account)
+    }
+
+    fn check_vk_account(&self, key: &str, vk_account: &Pubkey) -> Result<()> {
+        let account = self
+            .fetch_account(vk_account)
+            .map_err(|_| VerifierError::Registry(format!("VK account of {} not found", key)))?;
+        if account.owner != self.config.program_id || account.data.len() < VK_HEADER_SIZE {
+            return Err(VerifierError::Registry(format!(
+                "{} is not a VK account of this program",
+                vk_account
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl SolanaNoirVerifier {
+    /// Upload a VK and register it as `key` with the registry program
+    ///
+    /// `authority` pays for both and becomes the authority of the name if
+    /// this is its first version. If `key` already names this exact VK,
+    /// nothing is sent and the existing entry is returned.
+    pub fn publish_vk_on_chain(
+        &self,
+        authority: &Keypair,
+        key: &str,
+        vk: &[u8],
+    ) -> Result<OnChainRegistryEntry> {
+        parse_on_chain_key(key)?;
+        let vk_hash = keccak256(vk);
+        if let Some(existing) = self.fetch_registry_entry(key)? {
+            if existing.vk_hash == vk_hash {
+                return Ok(existing);
+            }
+            return Err(VerifierError::Registry(format!(
+                "{} is already registered to a different VK",
+                key
+            )));
+        }
+        let upload = self.upload_vk(authority, vk)?;
+        self.register_vk_on_chain(authority, key, &upload.vk_account, &vk_hash)?;
+        self.fetch_registry_entry(key)?
+            .ok_or_else(|| VerifierError::Registry(format!("{} was not registered", key)))
+    }
+
+    /// Register an uploaded VK as `key` with the registry program
+    pub fn register_vk_on_chain(
+        &self,
+        authority: &Keypair,
+        key: &str,
+        vk_account: &Pubkey,
+        vk_hash: &[u8; 32],
+    ) -> Result<Signature> {
+        let (name, version) = parse_on_chain_key(key)?;
+        let ix = registry_register(
+            &self.registry_program()?,
+            &authority.pubkey(),
+            name,
+            version,
+            vk_account,
+            vk_hash,
+        );
+        self.send_and_confirm(authority, &[], vec![ix], false)
+    }
+
+    /// Mark `key` as deprecated; it stays readable but no longer resolves
+    pub fn deprecate_vk(&self, authority: &Keypair, key: &str) -> Result<Signature> {
+        let (name, version) = parse_on_chain_key(key)?;
+        let ix = registry_deprecate(
+            &self.registry_program()?,
+            &authority.pubkey(),
+            name,
+            version,
+        );
+        self.send_and_confirm(authority, &[], vec![ix], false)
+    }
+
+    /// Hand `name` and all its versions to `new_authority`
+    pub fn transfer_registry_authority(
+        &self,
+        authority: &Keypair,
+        name: &str,
+        new_authority: &Pubkey,
+    ) -> Result<Signature> {
+        if !is_valid_label(name) {
+            return Err(VerifierError::Registry(format!("invalid name {:?}", name)));
+        }
+        check_label_len(name)?;
+        let ix = registry_transfer_authority(
+            &self.registry_program()?,
+            &authority.pubkey(),
+            name,
+            new_authority,
+        );
+        self.send_and_confirm(authority, &[], vec![ix], false)
+    }
+
+    /// Registry entry of `key`, or `None` if it was never registered
+    pub fn fetch_registry_entry(&self, key: &str) -> Result<Option<OnChainRegistryEntry>> {
+        let (name, version) = parse_on_chain_key(key)?;
+        let registry_program = self.registry_program()?;
+        let (address, _) = find_registry_entry_address(&registry_program, name, version);
+        let account = self.rpc_call(RpcOperation::Fetch, |rpc| {
+            rpc.get_account_with_commitment(&address, rpc.commitment())
+        })?;
+        let Some(account) = account.value else {
+            return Ok(None);
+        };
+        if account.owner != registry_program {
+            return Err(VerifierError::Registry(format!(
+                "{} is not owned by the registry program",
+                address
+            )));
+        }
+        OnChainRegistryEntry::from_account_data(address, &account.data)
+            .map(Some)
+            .ok_or_else(|| VerifierError::Registry(format!("invalid registry entry {}", address)))
+    }
+
+    /// VK account registered as `key` in the registry program, checked to
+    /// be active and to exist on chain
+    pub fn resolve_vk_on_chain(&self, key: &str) -> Result<Pubkey> {
+        let entry = self
+            .fetch_registry_entry(key)?
+            .ok_or_else(|| VerifierError::Registry(format!("{} is not registered", key)))?;
+        if let Some(slot) = entry.deprecated_slot {
+            return Err(VerifierError::Registry(format!(
+                "{} was deprecated at slot {}",
+                key, slot
+            )));
+        }
+        self.check_vk_account(key, &entry.vk_account)?;
+        Ok(entry.vk_account)
+    }
+
+    /// Every version in the registry program, deprecated ones included
+    pub fn list_registry_entries(&self) -> Result<Vec<OnChainRegistryEntry>> {
+        let registry_program = self.registry_program()?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(REGISTRY_ENTRY_SIZE as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.rpc_call(RpcOperation::Fetch, |rpc| {
+            rpc.get_program_ui_accounts_with_config(&registry_program, config.clone())
+        })?;
+        let mut entries: Vec<_> = accounts
+            .into_iter()
+            .filter_map(|(address, account)| {
+                OnChainRegistryEntry::from_account_data(address, &account.data.decode()?)
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        Ok(entries)
+    }
+
+    fn registry_program(&self) -> Result<Pubkey> {
+        self.config
+            .registry_program
+            .ok_or_else(|| VerifierError::Registry("no registry program configured".to_string()))
+    }
+}
+
+/// Split `name@version`, rejecting keys the file format cannot hold
+fn parse_key(key: &str) -> Result<(&str, &str)> {
+    match key.split_once('@') {
+        Some((name, version)) if is_valid_label(name) && is_valid_label(version) => {
+            Ok((name, version))
+        }
+        _ => Err(VerifierError::Registry(format!(
+            "expected name@version, got {:?}",
+            key
+        ))),
+    }
+}
+
+/// [`parse_key`], also enforcing the registry program's label length
+fn parse_on_chain_key(key: &str) -> Result<(&str, &str)> {
+    let (name, version) = parse_key(key)?;
+    check_label_len(name)?;
+    check_label_len(version)?;
+    Ok((name, version))
+}
+
+fn is_valid_label(part: &str) -> bool {
+    !part.is_empty()
+        && part
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c))
+}
+
+fn check_label_len(label: &str) -> Result<()> {
+    if label.len() > REGISTRY_MAX_LABEL_LEN {
+        return Err(VerifierError::Registry(format!(
+            "{:?} is longer than the registry's {} byte limit",
+            label, REGISTRY_MAX_LABEL_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn parse_entries(contents: &str) -> Result<BTreeMap<String, RegistryEntry>> {
+    let mut entries = BTreeMap::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || VerifierError::Registry(format!("invalid registry line: {}", line));
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        let key = key.trim();
+        parse_key(key)?;
+        let mut fields = value.split_whitespace();
+        let vk_account = fields
+            .next()
+            .and_then(|s| Pubkey::from_str(s).ok())
+            .ok_or_else(invalid)?;
+        let vk_hash = fields
+            .next()
+            .and_then(decode_hex)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(invalid)?;
+        entries.insert(
+            key.to_string(),
+            RegistryEntry {
+                vk_account,
+                vk_hash,
+            },
+        );
+    }
+    Ok(entries)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents).map_err(registry_error)?;
+    fs::rename(&tmp, path).map_err(registry_error)
+}
+
+fn registry_error(e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::Registry(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("square@1.0.0").unwrap(), ("square", "1.0.0"));
+        assert!(parse_key("square").is_err());
+        assert!(parse_key("@1").is_err());
+        assert!(parse_key("sq uare@1").is_err());
+    }
+
+    #[test]
+    fn test_register_and_reopen() {
+        let dir = std::env::temp_dir().join(format!("vk-registry-{}", Pubkey::new_unique()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("registry.txt");
+
+        let mut registry = VkRegistry::open(&path)
+            .unwrap()
+            .with_cache_dir(dir.join("cache"));
+        let vk = vec![5u8; VK_SIZE];
+        let entry = RegistryEntry {
+            vk_account: Pubkey::new_unique(),
+            vk_hash: registry.cache_vk(&vk).unwrap(),
+        };
+        registry.register("square@1", entry).unwrap();
+        // Same VK again is fine, a different one is not
+        registry.register("square@1", entry).unwrap();
+        let other = RegistryEntry {
+            vk_hash: [0u8; 32],
+            ..entry
+        };
+        assert!(registry.register("square@1", other).is_err());
+
+        let reopened = VkRegistry::open(&path)
+            .unwrap()
+            .with_cache_dir(dir.join("cache"));
+        assert_eq!(reopened.resolve("square@1").unwrap(), &entry);
+        assert_eq!(reopened.vk_bytes("square@1").unwrap(), vk);
+        assert!(reopened.resolve("square@2").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_on_chain_entry_from_account_data() {
+        let vk_account = Pubkey::new_unique();
+        let mut data = vec![0u8; REGISTRY_ENTRY_SIZE];
+        data[0..32].copy_from_slice(vk_account.as_ref());
+        data[32..64].copy_from_slice(&[7u8; 32]);
+        data[96..104].copy_from_slice(&10u64.to_le_bytes());
+        data[120..126].copy_from_slice(b"square");
+        data[152..157].copy_from_slice(b"1.0.0");
+        data[184] = 6;
+        data[185] = 5;
+
+        let address = Pubkey::new_unique();
+        let entry = OnChainRegistryEntry::from_account_data(address, &data).unwrap();
+        assert_eq!(entry.key(), "square@1.0.0");
+        assert_eq!(entry.vk_account, vk_account);
+        assert_eq!(entry.vk_hash, [7u8; 32]);
+        assert_eq!(entry.registered_slot, 10);
+        assert!(!entry.is_deprecated());
+
+        data[112..120].copy_from_slice(&20u64.to_le_bytes());
+        let entry = OnChainRegistryEntry::from_account_data(address, &data).unwrap();
+        assert_eq!(entry.deprecated_slot, Some(20));
+
+        assert!(OnChainRegistryEntry::from_account_data(address, &data[1..]).is_none());
+        assert!(parse_on_chain_key(&format!("{}@1", "a".repeat(33))).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! RPC endpoint failover and retries
+//!
+//! A verification sends dozens of requests over several seconds, so one
+//! flaky endpoint is enough to fail it. Requests go to the current endpoint
+//! of an [`RpcPool`]; when it is rate limited, times out or cannot be
+//! reached, the pool moves to the next endpoint that reports healthy and
+//! the request is retried according to its [`RetryPolicy`](crate::RetryPolicy).
+
+use crate::client::SolanaNoirVerifier;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_rpc_client_api::client_error::ErrorKind as ClientErrorKind;
+use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a fetched blockhash is reused, about one slot
+const BLOCKHASH_REUSE: Duration = Duration::from_millis(400);
+
+/// Kind of RPC request, selecting its [`RetryPolicy`](crate::RetryPolicy)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RpcOperation {
+    Send,
+    Confirm,
+    Fetch,
+}
+
+impl RpcOperation {
+    /// Name reported to [`Metrics::retried`](crate::Metrics::retried)
+    fn label(self) -> &'static str {
+        match self {
+            Self::Send => "rpc_send",
+            Self::Confirm => "rpc_confirm",
+            Self::Fetch => "rpc_fetch",
+        }
+    }
+}
+
+/// The primary RPC client and its fallbacks
+pub(crate) struct RpcPool {
+    clients: Vec<Arc<RpcClient>>,
+    current: AtomicUsize,
+}
+
+impl RpcPool {
+    /// `fallbacks` get clients with `primary`'s commitment
+    pub(crate) fn new(primary: Arc<RpcClient>, fallbacks: &[String]) -> Self {
+        let commitment = primary.commitment();
+        let mut clients = vec![primary];
+        clients.extend(
+            fallbacks
+                .iter()
+                .map(|url| Arc::new(RpcClient::new_with_commitment(url.clone(), commitment))),
+        );
+        Self {
+            clients,
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    /// Index and client of the endpoint requests currently go to
+    pub(crate) fn current(&self) -> (usize, Arc<RpcClient>) {
+        let index = self.current.load(Ordering::Relaxed);
+        (index, self.clients[index].clone())
+    }
+
+    /// Move off endpoint `failed` to the next one that reports healthy
+    ///
+    /// If none do, the next endpoint is used anyway. Does nothing when
+    /// another thread already moved off `failed`.
+    pub(crate) fn fail_over(&self, failed: usize) {
+        if self.clients.len() < 2 || self.current.load(Ordering::Relaxed) != failed {
+            return;
+        }
+        let candidates: Vec<usize> = (1..self.clients.len())
+            .map(|offset| (failed + offset) % self.clients.len())
+            .collect();
+        let next = candidates
+            .iter()
+            .copied()
+            .find(|&i| self.clients[i].get_health().is_ok())
+            .unwrap_or(candidates[0]);
+        if self
+            .current
+            .compare_exchange(failed, next, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            log::warn!(
+                "Failing over from {} to {}",
+                self.clients[failed].url(),
+                self.clients[next].url()
+            );
+        }
+    }
+}
+
+impl SolanaNoirVerifier {
+    /// The RPC client requests currently go to
+    pub(crate) fn client(&self) -> Arc<RpcClient> {
+        self.rpc.current().1
+    }
+
+    /// Fetch an account, retrying endpoint failures
+    pub(crate) fn fetch_account(&self, pubkey: &Pubkey) -> Result<Account, ClientError> {
+        self.rpc_call(RpcOperation::Fetch, |rpc| rpc.get_account(pubkey))
+    }
+
+    /// Fetch a recent blockhash, retrying endpoint failures
+    ///
+    /// A blockhash fetched within the last [`BLOCKHASH_REUSE`] is returned
+    /// again, so concurrent uploads and batch jobs share one request. The
+    /// window is shorter than any retry backoff, so a retried transaction
+    /// always gets a fresh blockhash.
+    pub(crate) fn latest_blockhash(&self) -> Result<Hash, ClientError> {
+        let mut cached = self.blockhash.lock().unwrap();
+        if let Some((hash, fetched)) = *cached {
+            if fetched.elapsed() < BLOCKHASH_REUSE {
+                return Ok(hash);
+            }
+        }
+        let hash = self.rpc_call(RpcOperation::Fetch, |rpc| rpc.get_latest_blockhash())?;
+        *cached = Some((hash, Instant::now()));
+        Ok(hash)
+    }
+
+    /// Run `request` against the current endpoint, failing over and
+    /// retrying on endpoint failures per `operation`'s policy
+    pub(crate) fn rpc_call<T>(
+        &self,
+        operation: RpcOperation,
+        request: impl Fn(&RpcClient) -> Result<T, ClientError>,
+    ) -> Result<T, ClientError> {
+        let policy = match operation {
+            RpcOperation::Send => self.config.retry.send,
+            RpcOperation::Confirm => self.config.retry.confirm,
+            RpcOperation::Fetch => self.config.retry.fetch,
+        };
+        let mut attempt = 0;
+        loop {
+            let (index, client) = self.rpc.current();
+            match request(&client) {
+                Err(e) if attempt < policy.max_retries && is_endpoint_failure(&e) => {
+                    attempt += 1;
+                    self.record(|m| m.retried(operation.label()));
+                    log::warn!(
+                        "{:?} request to {} failed ({}/{}): {}",
+                        operation,
+                        client.url(),
+                        attempt,
+                        policy.max_retries,
+                        e
+                    );
+                    self.rpc.fail_over(index);
+                    thread::sleep(policy.backoff(attempt));
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether `err` says more about the endpoint than about the request
+fn is_endpoint_failure(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status()
+                    .is_some_and(|s| s.as_u16() == 429 || s.is_server_error())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RetryPolicy;
+    use solana_rpc_client_api::request::RpcError;
+
+    #[test]
+    fn test_is_endpoint_failure() {
+        let io = ClientError::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert!(is_endpoint_failure(&io));
+        let not_found = ClientError::from(RpcError::ForUser("AccountNotFound".to_string()));
+        assert!(!is_endpoint_failure(&not_found));
+    }
+
+    #[test]
+    fn test_fail_over_moves_once() {
+        let pool = RpcPool::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            &["http://127.0.0.1:2".to_string()],
+        );
+        // Neither endpoint is healthy; the pool still moves on
+        pool.fail_over(0);
+        assert_eq!(pool.current().0, 1);
+        // A stale failure report for endpoint 0 is ignored
+        pool.fail_over(0);
+        assert_eq!(pool.current().0, 1);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(350));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+    }
+}
@@ -0,0 +1,407 @@
+//! Resuming interrupted verifications
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    hex::{decode_hex, encode_hex},
+    types::*,
+};
+use plonk_solana_core::proof::Proof;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// Program `BufferStatus::Ready` and `BufferStatus::Locked`
+const BUFFER_READY: u8 = 2;
+const BUFFER_LOCKED: u8 = 3;
+
+/// A phased verification picked up from its on-chain accounts
+///
+/// Created by [`VerificationSession::recover`] after a client crashed or
+/// lost its connection part way through [`SolanaNoirVerifier::verify_phased`].
+/// The accounts are the source of truth: the proof buffer's chunk bitmap
+/// says which chunks are still missing and the state account says which
+/// phase runs next.
+///
+/// ```ignore
+/// let session = VerificationSession::recover(&verifier, &proof_account, &state_account)?;
+/// println!("{} chunks missing, phase {:?}", session.missing_chunks().len(), session.phase());
+/// let receipt = session.resume(&payer, None, &proof, &public_inputs, &vk_account)?;
+/// ```
+///
+/// A session can also be written to disk with [`VerificationSession::save`]
+/// and read back with [`VerificationSession::load`], which needs no RPC.
+/// With [`VerifierConfig::session_file`] set, `verify_phased` keeps such a
+/// file up to date itself and removes it once the accounts are closed.
+pub struct VerificationSession<'a> {
+    verifier: &'a SolanaNoirVerifier,
+    proof_account: Pubkey,
+    state_account: Pubkey,
+    phase: VerificationPhase,
+    started: bool,
+    public_inputs: Vec<u8>,
+    missing_chunks: Vec<usize>,
+    nonce: Option<Pubkey>,
+}
+
+impl<'a> VerificationSession<'a> {
+    /// Read the proof buffer and state account of an interrupted verification
+    pub fn recover(
+        verifier: &'a SolanaNoirVerifier,
+        buffer: &Pubkey,
+        state: &Pubkey,
+    ) -> Result<Self> {
+        let buffer_data = verifier.fetch_account(buffer)?.data;
+        if buffer_data.len() < BUFFER_HEADER_SIZE {
+            return Err(VerifierError::InvalidStateData);
+        }
+        let num_pi = u16::from_le_bytes([buffer_data[3], buffer_data[4]]) as usize;
+        let pi_end = BUFFER_HEADER_SIZE + num_pi * 32;
+        let public_inputs = buffer_data
+            .get(BUFFER_HEADER_SIZE..pi_end)
+            .ok_or(VerifierError::InvalidStateData)?
+            .to_vec();
+
+        let missing_chunks = if matches!(buffer_data[0], BUFFER_READY | BUFFER_LOCKED) {
+            Vec::new()
+        } else {
+            let bitmap = u32::from_le_bytes(buffer_data[5..9].try_into().unwrap());
+            missing_chunks(bitmap)
+        };
+
+        let status = verifier.get_verification_state(state)?.status;
+
+        Ok(Self {
+            verifier,
+            proof_account: *buffer,
+            state_account: *state,
+            phase: status.phase,
+            started: status.raw_phase != 0,
+            public_inputs,
+            missing_chunks,
+            nonce: None,
+        })
+    }
+
+    /// A session for accounts this client just set up, before any chunk
+    pub(crate) fn started(
+        verifier: &'a SolanaNoirVerifier,
+        proof_account: &Pubkey,
+        state_account: &Pubkey,
+        public_inputs: &[u8],
+    ) -> Self {
+        Self {
+            verifier,
+            proof_account: *proof_account,
+            state_account: *state_account,
+            phase: VerificationPhase::NotStarted,
+            started: false,
+            public_inputs: public_inputs.to_vec(),
+            missing_chunks: missing_chunks(0),
+            nonce: None,
+        }
+    }
+
+    /// Read a session written by [`Self::save`]
+    ///
+    /// Makes no RPC calls, so the chunk list and phase are as of the save.
+    /// Chunks uploaded since are harmlessly re-sent by [`Self::resume`], and
+    /// phases always continue from the state account.
+    pub fn load(verifier: &'a SolanaNoirVerifier, path: impl AsRef<Path>) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(session_file)?;
+        let fields: Vec<(&str, &str)> = contents
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+        let required = |name: &str| {
+            field(name).ok_or_else(|| VerifierError::SessionFile(format!("missing {}", name)))
+        };
+        let pubkey = |value: &str| Pubkey::from_str(value).map_err(session_file);
+
+        let chunk_bitmap: u32 = required("chunk_bitmap")?.parse().map_err(session_file)?;
+        let phase = required("phase")?;
+        Ok(Self {
+            verifier,
+            proof_account: pubkey(required("proof_account")?)?,
+            state_account: pubkey(required("state_account")?)?,
+            phase: parse_phase(phase)
+                .ok_or_else(|| VerifierError::SessionFile(format!("unknown phase {}", phase)))?,
+            started: required("started")?.parse().map_err(session_file)?,
+            public_inputs: decode_hex(required("public_inputs")?)
+                .ok_or_else(|| VerifierError::SessionFile("invalid public inputs".to_string()))?,
+            missing_chunks: missing_chunks(chunk_bitmap),
+            nonce: field("nonce").map(pubkey).transpose()?,
+        })
+    }
+
+    /// Write the session to `path`, replacing any previous save
+    ///
+    /// The file is written next to `path` and renamed into place, so a
+    /// process killed mid-save leaves the previous version intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut fields = vec![
+            ("proof_account", self.proof_account.to_string()),
+            ("state_account", self.state_account.to_string()),
+            (
+                "chunk_bitmap",
+                chunk_bitmap(&self.missing_chunks).to_string(),
+            ),
+            ("phase", format!("{:?}", self.phase)),
+            ("started", self.started.to_string()),
+            ("public_inputs", encode_hex(&self.public_inputs)),
+        ];
+        if let Some(nonce) = &self.nonce {
+            fields.push(("nonce", nonce.to_string()));
+        }
+        let contents: String = fields
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, contents).map_err(session_file)?;
+        fs::rename(&tmp, path).map_err(session_file)
+    }
+
+    /// The proof buffer account
+    pub fn proof_account(&self) -> &Pubkey {
+        &self.proof_account
+    }
+
+    /// The verification state account
+    pub fn state_account(&self) -> &Pubkey {
+        &self.state_account
+    }
+
+    /// Verification phase when the session was recovered
+    pub fn phase(&self) -> VerificationPhase {
+        self.phase
+    }
+
+    /// Indices of proof chunks the buffer has not received
+    pub fn missing_chunks(&self) -> &[usize] {
+        &self.missing_chunks
+    }
+
+    /// Public inputs currently stored in the proof buffer
+    pub fn public_inputs(&self) -> &[u8] {
+        &self.public_inputs
+    }
+
+    /// Durable nonce account a phase run left open, if any
+    pub fn nonce(&self) -> Option<&Pubkey> {
+        self.nonce.as_ref()
+    }
+
+    /// Record the durable nonce account the phases run against
+    pub(crate) fn set_nonce(&mut self, nonce: Option<Pubkey>) {
+        self.nonce = nonce;
+    }
+
+    /// Mark every chunk as uploaded
+    pub(crate) fn set_uploaded(&mut self) {
+        self.missing_chunks.clear();
+    }
+
+    /// Upload what is missing and finish the verification
+    ///
+    /// `proof` and `public_inputs` must be the ones the session was started
+    /// with. Missing chunks are re-sent, public inputs are rewritten if
+    /// Phase 1 has not run yet, and then verification continues like
+    /// [`SolanaNoirVerifier::verify_phased`]: remaining phases, receipt,
+    /// account cleanup. With a `sponsor`, it pays for every transaction
+    /// and receives the rent back, and `authority` signs nothing; use the
+    /// sponsor the session was started with, since it funded the accounts.
+    pub fn resume(
+        &self,
+        authority: &(dyn Signer + Sync),
+        sponsor: Option<&(dyn Signer + Sync)>,
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+    ) -> Result<VerifiedReceipt> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+
+        let verifier = self.verifier;
+        let payer = sponsor.unwrap_or(authority);
+        let mut signatures = Vec::new();
+
+        // The interrupted run's nonce would otherwise keep its rent
+        if let Some(nonce) = &self.nonce {
+            match verifier.close_nonce_account(payer, nonce) {
+                Ok(sig) => signatures.push(sig),
+                Err(e) => log::warn!("Failed to close nonce account {}: {:?}", nonce, e),
+            }
+        }
+
+        let mut public_input_uploads = Vec::new();
+        if public_inputs != self.public_inputs.as_slice() {
+            if self.started || public_inputs.len() != self.public_inputs.len() {
+                return Err(VerifierError::SessionMismatch(
+                    "public inputs differ from the ones in the proof buffer".to_string(),
+                ));
+            }
+            public_input_uploads = verifier.public_input_uploads(
+                &payer.pubkey(),
+                &self.proof_account,
+                public_inputs,
+            )?;
+        }
+
+        if !public_input_uploads.is_empty() || !self.missing_chunks.is_empty() {
+            // The program numbers chunks by `offset / DEFAULT_CHUNK_SIZE`
+            let chunks: Vec<(usize, &[u8])> = self
+                .missing_chunks
+                .iter()
+                .filter_map(|&index| {
+                    let start = index * DEFAULT_CHUNK_SIZE;
+                    let end = (start + DEFAULT_CHUNK_SIZE).min(proof.len());
+                    proof.get(start..end).map(|chunk| (start, chunk))
+                })
+                .collect();
+            signatures.extend(verifier.upload_proof_chunks(
+                payer,
+                &self.proof_account,
+                public_input_uploads,
+                &chunks,
+            )?);
+        }
+
+        verifier.complete_phased(
+            payer,
+            &self.state_account,
+            &self.proof_account,
+            &[],
+            vk_account,
+            public_inputs,
+            is_zk,
+            signatures,
+        )
+    }
+}
+
+impl SolanaNoirVerifier {
+    /// Write `session` to `config.session_file`, if one is set
+    ///
+    /// Checkpoint failures are logged rather than returned; they should not
+    /// fail a verification that is otherwise going fine.
+    pub(crate) fn save_session(&self, session: &VerificationSession) {
+        if let Some(path) = &self.config.session_file {
+            if let Err(e) = session.save(path) {
+                log::warn!("Failed to save session to {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Apply `update` to the session in `config.session_file`, if any
+    pub(crate) fn update_session(&self, update: impl FnOnce(&mut VerificationSession)) {
+        let Some(path) = &self.config.session_file else {
+            return;
+        };
+        match VerificationSession::load(self, path) {
+            Ok(mut session) => {
+                update(&mut session);
+                self.save_session(&session);
+            }
+            Err(e) => log::warn!("Failed to load session from {}: {}", path.display(), e),
+        }
+    }
+
+    /// Remove `config.session_file` once its accounts are closed
+    pub(crate) fn clear_session(&self) {
+        if let Some(path) = &self.config.session_file {
+            if let Err(e) = fs::remove_file(path) {
+                log::warn!("Failed to remove session {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Upload bitmap with every chunk but `missing` set
+fn chunk_bitmap(missing: &[usize]) -> u32 {
+    let num_chunks = PROOF_SIZE.div_ceil(DEFAULT_CHUNK_SIZE);
+    (0..num_chunks)
+        .filter(|i| !missing.contains(i))
+        .fold(0, |bitmap, i| bitmap | (1u32 << i))
+}
+
+/// Inverse of `VerificationPhase`'s `Debug` output
+fn parse_phase(name: &str) -> Option<VerificationPhase> {
+    Some(match name {
+        "NotStarted" => VerificationPhase::NotStarted,
+        "ChallengesGenerated" => VerificationPhase::ChallengesGenerated,
+        "SumcheckComplete" => VerificationPhase::SumcheckComplete,
+        "MsmComplete" => VerificationPhase::MsmComplete,
+        "PairingComplete" => VerificationPhase::PairingComplete,
+        "Verified" => VerificationPhase::Verified,
+        "Failed" => VerificationPhase::Failed,
+        _ => return None,
+    })
+}
+
+fn session_file(e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::SessionFile(e.to_string())
+}
+
+/// Chunk indices whose bit is clear in the buffer's upload bitmap
+fn missing_chunks(bitmap: u32) -> Vec<usize> {
+    let num_chunks = PROOF_SIZE.div_ceil(DEFAULT_CHUNK_SIZE);
+    (0..num_chunks)
+        .filter(|&i| bitmap & (1u32 << i) == 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_client::RpcClient;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_missing_chunks() {
+        let num_chunks = PROOF_SIZE.div_ceil(DEFAULT_CHUNK_SIZE);
+        assert_eq!(missing_chunks(0).len(), num_chunks);
+        assert!(missing_chunks((1u32 << num_chunks) - 1).is_empty());
+        assert_eq!(
+            missing_chunks(((1u32 << num_chunks) - 1) & !0b1001),
+            vec![0, 3]
+        );
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let verifier = SolanaNoirVerifier::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            VerifierConfig::new(Pubkey::new_unique()),
+        );
+        let (proof_account, state_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut session =
+            VerificationSession::started(&verifier, &proof_account, &state_account, &[7u8; 64]);
+        session.missing_chunks = vec![2, 5];
+        session.set_nonce(Some(Pubkey::new_unique()));
+
+        let path = std::env::temp_dir().join(format!("session-{}.txt", proof_account));
+        session.save(&path).unwrap();
+        let loaded = VerificationSession::load(&verifier, &path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.proof_account(), &proof_account);
+        assert_eq!(loaded.state_account(), &state_account);
+        assert_eq!(loaded.missing_chunks(), [2, 5]);
+        assert_eq!(loaded.phase(), VerificationPhase::NotStarted);
+        assert_eq!(loaded.public_inputs(), [7u8; 64]);
+        assert_eq!(loaded.nonce(), session.nonce());
+    }
+}
@@ -0,0 +1,244 @@
+//! Proofs split across several accounts
+//!
+//! A proof buffer keeps the public inputs and the proof in one account, and
+//! its public inputs are written by a single instruction, which caps them
+//! at what fits in one transaction. A shard manifest instead spreads
+//! `[public_inputs][proof]` over up to [`MAX_SHARDS`] shard accounts that
+//! are uploaded chunk by chunk. Uploads to different shards write different
+//! accounts, so they never wait on each other's account locks.
+//! [`SolanaNoirVerifier::verify_sharded`] sets the accounts up, uploads
+//! them in parallel and runs the phases with the shards attached.
+
+use crate::{
+    client::{split_into_chunks, SolanaNoirVerifier},
+    error::{Result, VerifierError},
+    instructions,
+    stream::whole_slots,
+    types::*,
+};
+use plonk_solana_core::proof::Proof;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use solana_system_interface::instruction as system_instruction;
+
+/// Shard accounts created per setup transaction, each of which signs it
+const SHARDS_PER_SETUP_TX: usize = 4;
+
+/// Most public inputs a verification can have (the state counts them in
+/// one byte)
+const MAX_SHARDED_PUBLIC_INPUTS: usize = u8::MAX as usize;
+
+/// Accounts created by [`SolanaNoirVerifier::upload_sharded`]
+#[derive(Debug, Clone)]
+pub struct ShardedUpload {
+    /// Shard manifest, passed wherever a proof buffer is expected
+    pub manifest: Pubkey,
+    /// Shard accounts, in manifest order
+    pub shards: Vec<Pubkey>,
+    /// Verification state account
+    pub state_account: Pubkey,
+    /// Setup and upload signatures
+    pub signatures: Vec<Signature>,
+}
+
+impl SolanaNoirVerifier {
+    /// Verify a proof end to end with its public inputs and proof split
+    /// across `shards` accounts, and create its receipt
+    ///
+    /// Works like [`Self::verify_phased`] with `payer` as the authority,
+    /// except that public inputs are not limited to one transaction and all
+    /// shards upload at the same time. The program copies the shards back
+    /// together in every phase, which takes about 16 KB of heap on top of
+    /// the phase itself: the verifier must be built with `large-heap`, and
+    /// [`FeeConfig::with_heap_frame`] must request the larger heap.
+    ///
+    /// Sharded verifications are not saved as sessions.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(vk = %vk_account)))]
+    pub fn verify_sharded(
+        &self,
+        payer: &(dyn Signer + Sync),
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+        shards: usize,
+    ) -> Result<VerifiedReceipt> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        if !is_zk {
+            return Err(VerifierError::UnsupportedProof(
+                "non-ZK proofs cannot be verified on-chain; generate the proof with ZK enabled"
+                    .to_string(),
+            ));
+        }
+
+        self.timed(|| {
+            let upload = self.upload_sharded(payer, proof, public_inputs, shards)?;
+            self.complete_phased(
+                payer,
+                &upload.state_account,
+                &upload.manifest,
+                &upload.shards,
+                vk_account,
+                public_inputs,
+                is_zk,
+                upload.signatures,
+            )
+        })
+    }
+
+    /// Create a shard manifest, `shards` shard accounts and a state
+    /// account, and upload the public inputs and proof to the shards
+    ///
+    /// Fewer shards are created when the data fits in fewer whole upload
+    /// chunks per shard.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(shards)))]
+    pub fn upload_sharded(
+        &self,
+        payer: &(dyn Signer + Sync),
+        proof: &[u8],
+        public_inputs: &[u8],
+        shards: usize,
+    ) -> Result<ShardedUpload> {
+        let num_pi = public_inputs.len() / 32;
+        if num_pi > MAX_SHARDED_PUBLIC_INPUTS {
+            return Err(VerifierError::PublicInputsTooLarge {
+                size: public_inputs.len(),
+                max_size: MAX_SHARDED_PUBLIC_INPUTS * 32,
+            });
+        }
+        let data = [public_inputs, proof].concat();
+        let shard_len = shard_len(data.len(), shards)?;
+
+        let program_id = &self.config.program_id;
+        let manifest = Keypair::new();
+        let state_account = Keypair::new();
+        let shard_keys: Vec<Keypair> = data.chunks(shard_len).map(|_| Keypair::new()).collect();
+        let shard_pubkeys: Vec<Pubkey> = shard_keys.iter().map(|k| k.pubkey()).collect();
+        let rent = |size: usize| self.client().get_minimum_balance_for_rent_exemption(size);
+        let create = |account: &Pubkey, size: usize| -> Result<Instruction> {
+            Ok(system_instruction::create_account(
+                &payer.pubkey(),
+                account,
+                rent(size)?,
+                size as u64,
+                program_id,
+            ))
+        };
+
+        let mut signatures = Vec::new();
+        let setup = vec![
+            create(&manifest.pubkey(), MANIFEST_ACCOUNT_SIZE)?,
+            create(&state_account.pubkey(), STATE_SIZE)?,
+        ];
+        signatures.push(self.send_and_confirm(
+            payer,
+            &[&manifest, &state_account],
+            setup,
+            false,
+        )?);
+        let shard_sizes: Vec<usize> = data.chunks(shard_len).map(<[u8]>::len).collect();
+        for (batch, sizes) in shard_keys
+            .chunks(SHARDS_PER_SETUP_TX)
+            .zip(shard_sizes.chunks(SHARDS_PER_SETUP_TX))
+        {
+            let setup = batch
+                .iter()
+                .zip(sizes)
+                .map(|(shard, len)| create(&shard.pubkey(), SHARD_HEADER_SIZE + len))
+                .collect::<Result<Vec<_>>>()?;
+            let signers: Vec<&dyn Signer> = batch.iter().map(|k| k as &dyn Signer).collect();
+            signatures.push(self.send_and_confirm(payer, &signers, setup, false)?);
+        }
+        let init = instructions::init_manifest(
+            program_id,
+            &manifest.pubkey(),
+            &shard_pubkeys,
+            num_pi as u16,
+            shard_len as u32,
+        );
+        signatures.push(self.send_and_confirm(payer, &[], vec![init], false)?);
+
+        // Every shard's chunks go into one queue, so the workers spread
+        // across all shards at once
+        let uploads: Vec<Instruction> = data
+            .chunks(shard_len)
+            .zip(&shard_pubkeys)
+            .flat_map(|(bytes, shard)| {
+                split_into_chunks(
+                    bytes,
+                    self.config
+                        .chunk_size
+                        .map_or(DEFAULT_CHUNK_SIZE, whole_slots),
+                )
+                .into_iter()
+                .map(|(offset, chunk)| {
+                    instructions::upload_shard_chunk(
+                        program_id,
+                        shard,
+                        &manifest.pubkey(),
+                        offset as u32,
+                        chunk,
+                    )
+                })
+                .collect::<Vec<_>>()
+            })
+            .collect();
+        signatures.extend(self.send_uploads(payer, &uploads)?);
+
+        Ok(ShardedUpload {
+            manifest: manifest.pubkey(),
+            shards: shard_pubkeys,
+            state_account: state_account.pubkey(),
+            signatures,
+        })
+    }
+}
+
+/// Shard length that splits `len` bytes into at most `shards` shards
+///
+/// Shards are a whole number of upload chunks long, so no upload spans two
+/// shards.
+pub(crate) fn shard_len(len: usize, shards: usize) -> Result<usize> {
+    if !(1..=MAX_SHARDS).contains(&shards) {
+        return Err(VerifierError::InvalidShardCount(shards));
+    }
+    let shard_len = len.div_ceil(shards).next_multiple_of(DEFAULT_CHUNK_SIZE);
+    if shard_len > MAX_SHARD_LEN {
+        return Err(VerifierError::InvalidShardCount(shards));
+    }
+    Ok(shard_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_len() {
+        let len = 32 + PROOF_SIZE;
+        let halves = shard_len(len, 2).unwrap();
+        assert_eq!(halves % DEFAULT_CHUNK_SIZE, 0);
+        assert_eq!(len.div_ceil(halves), 2);
+        assert_eq!(shard_len(len, MAX_SHARDS).unwrap(), 2 * DEFAULT_CHUNK_SIZE);
+
+        // Rounding up to whole chunks can leave fewer shards than asked for
+        assert_eq!(len.div_ceil(shard_len(len, 7).unwrap()), 6);
+
+        assert!(shard_len(len, 1).unwrap() >= len);
+        assert!(matches!(
+            shard_len(len, 0),
+            Err(VerifierError::InvalidShardCount(0))
+        ));
+        assert!(matches!(
+            shard_len(len, MAX_SHARDS + 1),
+            Err(VerifierError::InvalidShardCount(9))
+        ));
+        assert!(shard_len(MAX_SHARDS * MAX_SHARD_LEN + 1, MAX_SHARDS).is_err());
+    }
+}
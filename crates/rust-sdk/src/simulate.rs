@@ -0,0 +1,106 @@
+//! Dry runs of a verification before paying for it
+//!
+//! `simulateTransaction` runs one transaction against current cluster
+//! state, so only the setup transaction can be simulated: chunk uploads and
+//! phases need the accounts it creates. [`SolanaNoirVerifier::simulate`]
+//! simulates the setup on the cluster, which catches a missing program, an
+//! underfunded payer or a wrong VK account, and replays the phases with the
+//! program's own verifier off-chain to find the phase that would reject
+//! the proof.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    estimate::phase_transactions,
+    local::verify_stages,
+    offline::VerifyAccounts,
+    phases::PhaseStep,
+    rpc::RpcOperation,
+    types::*,
+};
+use plonk_solana_core::proof::Proof;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+impl SolanaNoirVerifier {
+    /// Simulate verifying a proof without sending anything
+    ///
+    /// `payer` does not sign; the simulation skips signature checks. The
+    /// proof buffer and state accounts are fresh keys that are discarded.
+    /// Phase steps carry the cost model's CUs, and a proof the verifier
+    /// rejects fails at the phase transaction that completes the failing
+    /// check.
+    pub fn simulate(
+        &self,
+        payer: &Pubkey,
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+    ) -> Result<SimulationReport> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        let (proof_account, state_account) = (Keypair::new(), Keypair::new());
+        let accounts = VerifyAccounts {
+            payer: *payer,
+            authority: None,
+            proof_account: proof_account.pubkey(),
+            state_account: state_account.pubkey(),
+            vk_account: *vk_account,
+        };
+
+        let transactions = self.build_phase_transactions(
+            &accounts,
+            &[&proof_account, &state_account],
+            proof,
+            public_inputs,
+            self.latest_blockhash()?,
+        )?;
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            ..Default::default()
+        };
+        let setup = self
+            .rpc_call(RpcOperation::Fetch, |rpc| {
+                rpc.simulate_transaction_with_config(&transactions[0], config.clone())
+            })?
+            .value;
+        let mut steps = vec![SimulatedStep {
+            name: "setup".to_string(),
+            on_chain: true,
+            compute_units: setup.units_consumed.unwrap_or(0),
+            logs: setup.logs.unwrap_or_default(),
+            error: setup.err.map(|e| e.to_string()),
+        }];
+
+        let (vk, srs_g2) = self.load_vk(vk_account)?;
+        let mut failing = PhaseStep::Challenges;
+        let rejection = verify_stages(&vk, proof, public_inputs, &srs_g2, |step| failing = step)
+            .err()
+            .map(|e| e.to_string());
+
+        let plan = plonk_solana_core::cu_estimate(
+            vk.log2_circuit_size as u8,
+            is_zk,
+            public_inputs.len() / 32,
+        );
+        steps.extend(
+            phase_transactions(&plan)
+                .into_iter()
+                .map(|(step, cus)| SimulatedStep {
+                    name: step.to_string(),
+                    on_chain: false,
+                    compute_units: cus as u64,
+                    logs: Vec::new(),
+                    error: rejection.clone().filter(|_| step == failing),
+                }),
+        );
+        Ok(SimulationReport { steps })
+    }
+}
@@ -0,0 +1,250 @@
+//! Typed view of a verification state account
+//!
+//! The account is the program's `VerificationState` (see
+//! `programs/ultrahonk-verifier/src/phased.rs`), a `repr(C)` struct of bytes
+//! and byte arrays. Borsh encodes such fields as their raw bytes, so deriving
+//! it on a mirror of that struct decodes the account without hand-kept
+//! offsets, and the stored challenges come out as plonk-core's
+//! [`ChallengeSet`].
+
+use crate::types::{VerificationState, STATE_SIZE};
+use borsh::{BorshDeserialize, BorshSerialize};
+use plonk_solana_core::relations::{
+    relation_coefficients, relation_coefficients_to_bytes, NUMBER_OF_ALPHAS,
+    RELATION_COEFFICIENTS_SIZE,
+};
+use plonk_solana_core::ChallengeSet;
+use solana_sdk::pubkey::Pubkey;
+
+type Bytes32 = [u8; 32];
+
+/// Field-for-field mirror of the program's `VerificationState`
+#[derive(BorshSerialize, BorshDeserialize)]
+struct RawVerificationState {
+    phase: u8,
+    challenge_sub_phase: u8,
+    sumcheck_sub_phase: u8,
+    log_n: u8,
+    is_zk: u8,
+    num_public_inputs: u8,
+    receipt_recorded: u8,
+    _reserved: u8,
+    vk_account: Bytes32,
+    _transcript_state: Bytes32,
+    eta: Bytes32,
+    eta_two: Bytes32,
+    eta_three: Bytes32,
+    beta: Bytes32,
+    gamma: Bytes32,
+    public_input_delta: Bytes32,
+    alphas: [Bytes32; NUMBER_OF_ALPHAS],
+    gate_challenges: [Bytes32; 28],
+    sumcheck_challenges: [Bytes32; 28],
+    libra_challenge: Bytes32,
+    rho: Bytes32,
+    gemini_r: Bytes32,
+    shplonk_nu: Bytes32,
+    shplonk_z: Bytes32,
+    _delta_partial: [Bytes32; 4],
+    _sumcheck_target: Bytes32,
+    sumcheck_pow_partial: Bytes32,
+    sumcheck_rounds_completed: u8,
+    _sumcheck_rounds_padding: [u8; 31],
+    sumcheck_passed: u8,
+    _sumcheck_padding: [u8; 31],
+    _shplemini_r_pows: [Bytes32; 28],
+    _shplemini_3a: [Bytes32; 5],
+    _shplemini_fold_pos: [Bytes32; 28],
+    _shplemini_const_acc: Bytes32,
+    _shplemini_gemini_scalars: [Bytes32; 27],
+    _shplemini_libra_scalars: [Bytes32; 3],
+    shplemini_sub_phase: u8,
+    _shplemini_padding: [u8; 31],
+    _p0: [u8; 64],
+    _p1: [u8; 64],
+    verified: u8,
+    _final_padding: [u8; 31],
+    _relations_partial: Bytes32,
+    proof_buffer: Bytes32,
+    challenge_digest: Bytes32,
+    vk_hash: Bytes32,
+    msm_scalars_hash: Bytes32,
+    relation_coefficients_hash: Bytes32,
+}
+
+/// Everything a verification state account records, decoded
+///
+/// Returned by
+/// [`SolanaNoirVerifier::get_verification_state`](crate::SolanaNoirVerifier::get_verification_state).
+/// Intermediate sumcheck and MSM values are left out: they only mean
+/// something to the next phase instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationStateView {
+    /// Phase, sub-phase markers and per-stage progress
+    pub status: VerificationState,
+    pub is_zk: bool,
+    pub num_public_inputs: u8,
+    /// Whether `CreateReceipt` has recorded this verification in a receipt
+    pub receipt_recorded: bool,
+    /// VK account Phase 1 bound the state to
+    pub vk_account: Pubkey,
+    /// Keccak256 of the VK account data Phase 1 read
+    pub vk_hash: [u8; 32],
+    /// Proof buffer Phase 1 locked
+    pub proof_buffer: Pubkey,
+    /// Keccak256 of the stored challenges, zero until Phase 1 completes
+    pub challenge_digest: [u8; 32],
+    pub sumcheck_passed: bool,
+    /// Keccak256 of the MSM scalars Phase 3b2 wrote to a scratch account,
+    /// if it used one
+    pub msm_scalars_hash: Option<[u8; 32]>,
+    /// Commitment Phase 2c recorded to the relation coefficients, if it ran
+    pub relation_coefficients_hash: Option<[u8; 32]>,
+    /// Relation coefficients for the relations instructions, once every
+    /// sumcheck round is verified
+    ///
+    /// Derived from the stored alphas and pow factor the way Phase 2c
+    /// derives them, so they match its commitment.
+    pub relation_coefficients: Option<[u8; RELATION_COEFFICIENTS_SIZE]>,
+    /// The proof's challenges, once Phase 1 has stored all of them
+    ///
+    /// Shaped like [`ChallengeSet::generate`] returns it, so it can be
+    /// compared with challenges computed off-chain.
+    pub challenges: Option<ChallengeSet>,
+}
+
+impl VerificationStateView {
+    /// Decode a state account; `None` unless it is [`STATE_SIZE`] bytes
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.len() != STATE_SIZE {
+            return None;
+        }
+        let raw = RawVerificationState::try_from_slice(data).ok()?;
+        let status = VerificationState::from_account_data(data)?;
+        let sealed = raw.challenge_digest != [0u8; 32];
+        let rounds_done = sealed && raw.sumcheck_rounds_completed >= raw.log_n;
+        let relation_coefficients = rounds_done.then(|| {
+            relation_coefficients_to_bytes(&relation_coefficients(
+                &raw.alphas,
+                &raw.sumcheck_pow_partial,
+            ))
+        });
+        let challenges = sealed.then(|| ChallengeSet {
+            eta: raw.eta,
+            eta_two: raw.eta_two,
+            eta_three: raw.eta_three,
+            beta: raw.beta,
+            gamma: raw.gamma,
+            public_input_delta: raw.public_input_delta,
+            alphas: raw.alphas.to_vec(),
+            gate_challenges: raw.gate_challenges[..(raw.log_n as usize).min(28)].to_vec(),
+            libra_challenge: (raw.is_zk == 1).then_some(raw.libra_challenge),
+            sumcheck_challenges: raw.sumcheck_challenges.to_vec(),
+            rho: raw.rho,
+            gemini_r: raw.gemini_r,
+            shplonk_nu: raw.shplonk_nu,
+            shplonk_z: raw.shplonk_z,
+        });
+        Some(Self {
+            status,
+            is_zk: raw.is_zk == 1,
+            num_public_inputs: raw.num_public_inputs,
+            receipt_recorded: raw.receipt_recorded == 1,
+            vk_account: Pubkey::new_from_array(raw.vk_account),
+            vk_hash: raw.vk_hash,
+            proof_buffer: Pubkey::new_from_array(raw.proof_buffer),
+            challenge_digest: raw.challenge_digest,
+            sumcheck_passed: raw.sumcheck_passed == 1,
+            msm_scalars_hash: (raw.msm_scalars_hash != [0u8; 32]).then_some(raw.msm_scalars_hash),
+            relation_coefficients_hash: (raw.relation_coefficients_hash != [0u8; 32])
+                .then_some(raw.relation_coefficients_hash),
+            relation_coefficients,
+            challenges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        STATE_PROOF_BUFFER_OFFSET, STATE_ROUNDS_COMPLETED_OFFSET, STATE_SHPLEMINI_SUB_PHASE_OFFSET,
+        STATE_VERIFIED_OFFSET,
+    };
+
+    #[test]
+    fn test_layout_matches_program() {
+        let zeros = [0u8; STATE_SIZE];
+        let raw = RawVerificationState::try_from_slice(&zeros).unwrap();
+        assert_eq!(borsh::to_vec(&raw).unwrap().len(), STATE_SIZE);
+
+        let mut data = vec![0u8; STATE_SIZE];
+        data[STATE_ROUNDS_COMPLETED_OFFSET] = 1;
+        data[STATE_SHPLEMINI_SUB_PHASE_OFFSET] = 2;
+        data[STATE_VERIFIED_OFFSET] = 3;
+        data[STATE_PROOF_BUFFER_OFFSET] = 4;
+        data[6504] = 5; // vk_hash
+        data[6536] = 6; // msm_scalars_hash
+        data[6568] = 7; // relation_coefficients_hash
+        let raw = RawVerificationState::try_from_slice(&data).unwrap();
+        assert_eq!(raw.sumcheck_rounds_completed, 1);
+        assert_eq!(raw.shplemini_sub_phase, 2);
+        assert_eq!(raw.verified, 3);
+        assert_eq!(raw.proof_buffer[0], 4);
+        assert_eq!(raw.vk_hash[0], 5);
+        assert_eq!(raw.msm_scalars_hash[0], 6);
+        assert_eq!(raw.relation_coefficients_hash[0], 7);
+    }
+
+    #[test]
+    fn test_view_decodes_challenges() {
+        let mut data = vec![0u8; STATE_SIZE];
+        data[..6].copy_from_slice(&[4, 6, 4, 12, 1, 1]);
+        data[8] = 0xaa; // vk_account
+        data[72] = 0x01; // eta
+        data[264] = 0x02; // alphas[0]
+        data[1064 + 11 * 32] = 0x03; // gate_challenges[11]
+        data[2856] = 0x04; // libra_challenge
+        data[2984] = 0x05; // shplonk_z
+        data[3240] = 1; // sumcheck_passed
+
+        let view = VerificationStateView::from_account_data(&data).unwrap();
+        assert_eq!(view.status.log_n, 12);
+        assert_eq!(view.status.sumcheck_sub_phase, 4);
+        assert!(view.is_zk && view.sumcheck_passed && !view.receipt_recorded);
+        assert_eq!(view.num_public_inputs, 1);
+        assert_eq!(view.vk_account.to_bytes()[0], 0xaa);
+        assert_eq!(view.msm_scalars_hash, None);
+        assert_eq!(view.relation_coefficients_hash, None);
+        // Not sealed yet
+        assert_eq!(view.challenges, None);
+        assert_eq!(view.relation_coefficients, None);
+
+        data[6472] = 0xff; // challenge_digest
+        let view = VerificationStateView::from_account_data(&data).unwrap();
+        let set = view.challenges.unwrap();
+        assert_eq!(set.eta[0], 0x01);
+        assert_eq!(set.alphas.len(), NUMBER_OF_ALPHAS);
+        assert_eq!(set.alphas[0][0], 0x02);
+        assert_eq!(set.gate_challenges.len(), 12);
+        assert_eq!(set.gate_challenges[11][0], 0x03);
+        assert_eq!(set.libra_challenge.unwrap()[0], 0x04);
+        assert_eq!(set.sumcheck_challenges.len(), 28);
+        assert_eq!(set.shplonk_z[0], 0x05);
+        // Only 0 of 12 rounds verified
+        assert_eq!(view.relation_coefficients, None);
+
+        data[STATE_ROUNDS_COMPLETED_OFFSET] = 12;
+        data[STATE_ROUNDS_COMPLETED_OFFSET - 1] = 0x06; // sumcheck_pow_partial
+        let view = VerificationStateView::from_account_data(&data).unwrap();
+        let mut pow_partial = [0u8; 32];
+        pow_partial[31] = 0x06;
+        let expected = relation_coefficients(&set.alphas, &pow_partial);
+        assert_eq!(
+            view.relation_coefficients,
+            Some(relation_coefficients_to_bytes(&expected))
+        );
+
+        assert!(VerificationStateView::from_account_data(&data[1..]).is_none());
+    }
+}
@@ -0,0 +1,625 @@
+//! Streaming a proof through a few large transactions
+//!
+//! [`SolanaNoirVerifier::verify_phased`] sends one transaction per chunk,
+//! sized by [`SolanaNoirVerifier::proof_chunk_size`]. On clusters that accept larger transactions, or with a lookup
+//! table shrinking the account list, several `UploadChunk` instructions fit
+//! in one transaction. [`SolanaNoirVerifier::verify_streamed`] packs the
+//! account setup, the proof and Phase 1 into as few transactions as
+//! [`VerifierConfig::max_transaction_size`] allows, so Phase 1 runs in the
+//! transaction that delivers the last proof bytes.
+
+use crate::{
+    client::SolanaNoirVerifier,
+    error::{Result, VerifierError},
+    fees, instructions,
+    session::VerificationSession,
+    types::*,
+};
+use plonk_solana_core::proof::Proof;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+/// Compute units reserved per `UploadChunk` sharing a transaction with Phase 1
+const UPLOAD_CHUNK_COMPUTE_UNITS: u32 = 10_000;
+
+/// Bytes per slot of the proof buffer's upload bitmap (the program's
+/// `MAX_CHUNK_SIZE`)
+const UPLOAD_SLOT_SIZE: usize = 1020;
+
+impl SolanaNoirVerifier {
+    /// Verify a proof end to end, streaming it through as few transactions
+    /// as the cluster's transaction size allows
+    ///
+    /// Works like [`Self::verify_phased`], except that the proof is not
+    /// uploaded one chunk per transaction: each transaction carries as many
+    /// `UploadChunk` instructions as fit in
+    /// [`VerifierConfig::max_transaction_size`], the first one also creates
+    /// the accounts, and the last one also runs Phase 1. With 64 KB
+    /// transactions that is a single transaction before sumcheck.
+    ///
+    /// With the default 1232-byte limit this sends about as many
+    /// transactions as `verify_phased`, one after the other.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(vk = %vk_account)))]
+    pub fn verify_streamed(
+        &self,
+        authority: &(dyn Signer + Sync),
+        sponsor: Option<&(dyn Signer + Sync)>,
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+    ) -> Result<VerifiedReceipt> {
+        let (is_zk, _format) =
+            Proof::detect_format(proof).map_err(|_| VerifierError::InvalidProofSize {
+                expected: PROOF_SIZE,
+                actual: proof.len(),
+            })?;
+        if !is_zk {
+            return Err(VerifierError::UnsupportedProof(
+                "non-ZK proofs cannot be verified on-chain; generate the proof with ZK enabled"
+                    .to_string(),
+            ));
+        }
+
+        let payer = sponsor.unwrap_or(authority);
+        let co_signer = Some(authority as &dyn Signer).filter(|a| a.pubkey() != payer.pubkey());
+        self.timed(|| {
+            let proof_account = Keypair::new();
+            let state_account = Keypair::new();
+            let signatures = self.stream_proof(
+                payer,
+                co_signer,
+                &proof_account,
+                &state_account,
+                proof,
+                public_inputs,
+                vk_account,
+                is_zk,
+            )?;
+            self.complete_phased(
+                payer,
+                &state_account.pubkey(),
+                &proof_account.pubkey(),
+                &[],
+                vk_account,
+                public_inputs,
+                is_zk,
+                signatures,
+            )
+        })
+    }
+
+    /// Create the accounts, upload the proof and run Phase 1 in packed
+    /// transactions
+    #[allow(clippy::too_many_arguments)]
+    fn stream_proof(
+        &self,
+        payer: &dyn Signer,
+        authority: Option<&dyn Signer>,
+        proof_account: &Keypair,
+        state_account: &Keypair,
+        proof: &[u8],
+        public_inputs: &[u8],
+        vk_account: &Pubkey,
+        is_zk: bool,
+    ) -> Result<Vec<Signature>> {
+        let program_id = &self.config.program_id;
+        let setup = self.setup_instructions(
+            &payer.pubkey(),
+            authority.map(|a| a.pubkey()).as_ref(),
+            &proof_account.pubkey(),
+            &state_account.pubkey(),
+            public_inputs,
+        )?;
+        let setup: Vec<Instruction> = [setup.instructions, setup.public_input_uploads].concat();
+        let phase1 = instructions::phase1_full(
+            program_id,
+            &state_account.pubkey(),
+            &proof_account.pubkey(),
+            vk_account,
+        );
+        let transactions = pack_stream(
+            setup,
+            |offset, chunk| {
+                instructions::upload_chunk(
+                    program_id,
+                    &proof_account.pubkey(),
+                    offset as u16,
+                    chunk,
+                )
+            },
+            proof,
+            self.config.chunk_size.unwrap_or(proof.len()),
+            phase1,
+            self.config.max_transaction_size,
+            |ixs| self.packed_size(&payer.pubkey(), ixs),
+        )?;
+
+        let plan = plonk_solana_core::cu_estimate(0, is_zk, public_inputs.len() / 32);
+        let keypairs = [proof_account as &dyn Signer, state_account];
+        let mut signatures = Vec::with_capacity(transactions.len());
+        for (i, ixs) in transactions.into_iter().enumerate() {
+            // Each account signs the transactions it is a signer in
+            let signers: Vec<&dyn Signer> = keypairs
+                .into_iter()
+                .chain(authority)
+                .filter(|signer| {
+                    ixs.iter()
+                        .flat_map(|ix| &ix.accounts)
+                        .any(|meta| meta.is_signer && meta.pubkey == signer.pubkey())
+                })
+                .collect();
+            let cu_limit = ixs
+                .iter()
+                .any(|ix| ix.data.first() == Some(&IX_PHASE1_FULL))
+                .then(|| {
+                    let uploads = ixs
+                        .iter()
+                        .filter(|ix| ix.data.first() == Some(&IX_UPLOAD_CHUNK))
+                        .count() as u32;
+                    let phase1 = self.phase_cu_limit(&plan, ixs.last().expect("phase 1"));
+                    (phase1 + uploads * UPLOAD_CHUNK_COMPUTE_UNITS).min(DEFAULT_COMPUTE_UNIT_LIMIT)
+                });
+
+            // Re-sending the first transaction would recreate the accounts;
+            // the others write the same bytes or redo Phase 1 on the same
+            // locked buffer
+            let send =
+                || self.send_with_budget(payer, &signers, ixs.clone(), false, cu_limit, None);
+            signatures.push(if i == 0 {
+                send()?
+            } else {
+                self.with_retries(send)?
+            });
+
+            if i == 0 {
+                self.save_session(&VerificationSession::started(
+                    self,
+                    &proof_account.pubkey(),
+                    &state_account.pubkey(),
+                    public_inputs,
+                ));
+            }
+        }
+        self.update_session(|session| session.set_uploaded());
+        Ok(signatures)
+    }
+
+    /// Serialized size of a transaction carrying `instructions`
+    ///
+    /// Sending drops the compute budget instructions from uploads that are
+    /// too large for them, but Phase 1 needs its compute unit limit, so
+    /// room for them is kept in the transaction that runs it.
+    fn packed_size(&self, payer: &Pubkey, instructions: &[Instruction]) -> Result<usize> {
+        let mut ixs = Vec::new();
+        if instructions
+            .iter()
+            .any(|ix| ix.data.first() == Some(&IX_PHASE1_FULL))
+        {
+            ixs.push(fees::set_compute_unit_limit(0));
+            ixs.push(fees::set_compute_unit_price(0));
+            if let Some(bytes) = self.config.fees.heap_frame_bytes {
+                ixs.push(fees::request_heap_frame(bytes));
+            }
+        }
+        ixs.extend_from_slice(instructions);
+
+        let message = match self.lookup_table_account()? {
+            Some(table) => v0::Message::try_compile(payer, &ixs, &[table], Hash::default())
+                .map(VersionedMessage::V0)
+                .map_err(|e| VerifierError::TransactionFailed(e.to_string()))?,
+            None => VersionedMessage::Legacy(Message::new(&ixs, Some(payer))),
+        };
+        Ok(transaction_size(&message))
+    }
+
+    /// Proof bytes each `UploadChunk` transaction carries
+    ///
+    /// [`VerifierConfig::chunk_size`] when set, otherwise the largest chunk
+    /// that fits [`VerifierConfig::max_transaction_size`] alongside the
+    /// priority fee instruction and with the configured lookup table, see
+    /// [`fit_chunk_size`]. Either way the size is cut to whole upload bitmap
+    /// slots, see [`whole_slots`].
+    pub fn proof_chunk_size(&self, payer: &Pubkey, proof_account: &Pubkey) -> Result<usize> {
+        if let Some(size) = self.config.chunk_size {
+            return Ok(whole_slots(size));
+        }
+        let table = self.lookup_table_account()?;
+        fit_chunk_size(&self.config, payer, proof_account, table.as_ref())
+    }
+
+    /// Public input bytes each `UploadData` transaction carries, chosen like
+    /// [`proof_chunk_size`](Self::proof_chunk_size)
+    pub(crate) fn public_input_chunk_size(
+        &self,
+        payer: &Pubkey,
+        proof_account: &Pubkey,
+    ) -> Result<usize> {
+        if let Some(size) = self.config.chunk_size {
+            return Ok(size);
+        }
+        let table = self.lookup_table_account()?;
+        fit_public_input_chunk_size(&self.config, payer, proof_account, table.as_ref())
+    }
+}
+
+/// Largest proof chunk one `UploadChunk` transaction from `payer` can carry
+///
+/// Counts the `SetComputeUnitPrice` instruction the fee strategy adds and,
+/// with `table`, compiles a v0 message against it. The chunk is then cut to
+/// whole upload bitmap slots by [`whole_slots`]. When not even one slot
+/// fits next to the fee instruction, slot-sized chunks go without it, as
+/// they do with a fixed 1020-byte [`VerifierConfig::chunk_size`].
+pub(crate) fn fit_chunk_size(
+    config: &VerifierConfig,
+    payer: &Pubkey,
+    proof_account: &Pubkey,
+    table: Option<&AddressLookupTableAccount>,
+) -> Result<usize> {
+    let fit = |config: &VerifierConfig| {
+        fit_upload(config, payer, table, |chunk| {
+            instructions::upload_chunk(&config.program_id, proof_account, 0, chunk)
+        })
+    };
+    let mut low = fit(config)?;
+    if low < UPLOAD_SLOT_SIZE && config.fees.sets_compute_unit_price() {
+        let unpriced = VerifierConfig {
+            fees: FeeConfig::default(),
+            ..config.clone()
+        };
+        low = fit(&unpriced)?;
+    }
+    if low < UPLOAD_SLOT_SIZE {
+        return Err(VerifierError::TransactionFailed(format!(
+            "an upload transaction cannot carry a {} byte bitmap slot within the {} byte limit",
+            UPLOAD_SLOT_SIZE, config.max_transaction_size
+        )));
+    }
+    Ok(whole_slots(low))
+}
+
+/// Whether an `UploadChunk` of `chunk_size` bytes from `payer` still fits
+/// with the fee strategy's `SetComputeUnitPrice` instruction, which is
+/// dropped from uploads it does not fit
+pub(crate) fn upload_carries_fee(
+    config: &VerifierConfig,
+    payer: &Pubkey,
+    proof_account: &Pubkey,
+    table: Option<&AddressLookupTableAccount>,
+    chunk_size: usize,
+) -> bool {
+    config.fees.sets_compute_unit_price()
+        && fit_upload(config, payer, table, |chunk| {
+            instructions::upload_chunk(&config.program_id, proof_account, 0, chunk)
+        })
+        .is_ok_and(|fit| fit >= chunk_size.min(PROOF_SIZE))
+}
+
+/// Cut a proof chunk size to whole upload bitmap slots
+///
+/// The program only marks a slot uploaded once one write covers all of
+/// it, so every chunk but the last must end on a slot boundary. Sizes of
+/// at least the whole proof are kept, and sizes below a slot grow to one.
+pub(crate) fn whole_slots(size: usize) -> usize {
+    if size >= PROOF_SIZE {
+        size
+    } else {
+        (size - size % UPLOAD_SLOT_SIZE).max(UPLOAD_SLOT_SIZE)
+    }
+}
+
+/// Largest public input chunk one `UploadData` transaction from `payer` can
+/// carry, counted like [`fit_chunk_size`]
+///
+/// Public inputs have no upload bitmap, so the chunk is not cut to slots.
+pub(crate) fn fit_public_input_chunk_size(
+    config: &VerifierConfig,
+    payer: &Pubkey,
+    proof_account: &Pubkey,
+    table: Option<&AddressLookupTableAccount>,
+) -> Result<usize> {
+    fit_upload(config, payer, table, |bytes| {
+        instructions::upload_data(
+            &config.program_id,
+            proof_account,
+            BufferRegion::PublicInputs,
+            0,
+            bytes,
+        )
+    })
+}
+
+/// Most bytes, up to [`PROOF_SIZE`], the instruction `upload` builds can
+/// carry in one transaction
+fn fit_upload(
+    config: &VerifierConfig,
+    payer: &Pubkey,
+    table: Option<&AddressLookupTableAccount>,
+    upload: impl Fn(&[u8]) -> Instruction,
+) -> Result<usize> {
+    let priced = config.fees.sets_compute_unit_price();
+    let size = |len: usize| -> Result<usize> {
+        let mut ixs: Vec<Instruction> = priced
+            .then(|| fees::set_compute_unit_price(u64::MAX))
+            .into_iter()
+            .collect();
+        ixs.push(upload(&vec![0; len]));
+        let message = match table {
+            Some(table) => {
+                v0::Message::try_compile(payer, &ixs, std::slice::from_ref(table), Hash::default())
+                    .map(VersionedMessage::V0)
+                    .map_err(|e| VerifierError::TransactionFailed(e.to_string()))?
+            }
+            None => VersionedMessage::Legacy(Message::new(&ixs, Some(payer))),
+        };
+        Ok(transaction_size(&message))
+    };
+
+    let (mut low, mut high) = (0, PROOF_SIZE);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if size(mid)? <= config.max_transaction_size {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    if low == 0 {
+        return Err(VerifierError::TransactionFailed(format!(
+            "an upload transaction exceeds the {} byte limit",
+            config.max_transaction_size
+        )));
+    }
+    Ok(low)
+}
+
+/// Serialized size of a signed transaction with `message`
+fn transaction_size(message: &VersionedMessage) -> usize {
+    // Signature count (compact-u16, below 128 here) and 64-byte signatures
+    1 + 64 * message.header().num_required_signatures as usize + message.serialize().len()
+}
+
+/// Group `setup`, the proof upload and `phase1` into transactions of at
+/// most `max_size` bytes as measured by `size`
+///
+/// `setup` opens the first transaction. The proof follows in order, split
+/// into `UploadChunk` instructions of at most `max_chunk` bytes, each as
+/// large as still fits the current transaction. Pieces end on an upload
+/// bitmap slot boundary where they can, so no slot is marked uploaded
+/// before all of its bytes are written. `phase1` joins the last
+/// transaction when it fits and gets its own otherwise.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pack_stream(
+    setup: Vec<Instruction>,
+    upload: impl Fn(usize, &[u8]) -> Instruction,
+    proof: &[u8],
+    max_chunk: usize,
+    phase1: Instruction,
+    max_size: usize,
+    size: impl Fn(&[Instruction]) -> Result<usize>,
+) -> Result<Vec<Vec<Instruction>>> {
+    let too_large = |size| {
+        VerifierError::TransactionFailed(format!(
+            "a {} byte transaction exceeds the {} byte limit",
+            size, max_size
+        ))
+    };
+    let fits = |ixs: &[Instruction]| -> Result<bool> { Ok(size(ixs)? <= max_size) };
+
+    if !fits(&setup)? {
+        return Err(too_large(size(&setup)?));
+    }
+    let mut transactions = Vec::new();
+    let mut current = setup;
+    let mut offset = 0;
+    while offset < proof.len() {
+        // Largest chunk that still fits; the size grows with the chunk
+        let mut candidate = |len: usize| -> Result<bool> {
+            current.push(upload(offset, &proof[offset..offset + len]));
+            let fits = fits(&current);
+            current.pop();
+            fits
+        };
+        let (mut low, mut high) = (0, max_chunk.min(proof.len() - offset));
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if candidate(mid)? {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        let end = offset + low;
+        if end < proof.len() && low >= end % UPLOAD_SLOT_SIZE {
+            low -= end % UPLOAD_SLOT_SIZE;
+        }
+
+        if low == 0 {
+            if current.is_empty() {
+                return Err(too_large(size(&[upload(
+                    offset,
+                    &proof[offset..offset + 1],
+                )])?));
+            }
+            transactions.push(std::mem::take(&mut current));
+            continue;
+        }
+        current.push(upload(offset, &proof[offset..offset + low]));
+        offset += low;
+    }
+
+    current.push(phase1);
+    if !fits(&current)? {
+        let phase1 = current.pop().expect("phase 1");
+        transactions.push(std::mem::replace(&mut current, vec![phase1]));
+    }
+    transactions.push(current);
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    fn legacy_size(payer: &Pubkey, ixs: &[Instruction]) -> Result<usize> {
+        Ok(transaction_size(&VersionedMessage::Legacy(Message::new(
+            ixs,
+            Some(payer),
+        ))))
+    }
+
+    #[test]
+    fn test_pack_stream() {
+        let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (proof_account, state_account, vk_account) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let proof: Vec<u8> = (0..PROOF_SIZE).map(|i| i as u8).collect();
+        let setup = vec![Instruction::new_with_bytes(
+            program_id,
+            &[IX_INIT_BUFFER, 1, 0],
+            vec![AccountMeta::new(proof_account, true)],
+        )];
+        let phase1 =
+            instructions::phase1_full(&program_id, &state_account, &proof_account, &vk_account);
+        let upload = |offset: usize, chunk: &[u8]| {
+            instructions::upload_chunk(&program_id, &proof_account, offset as u16, chunk)
+        };
+        let pack = |max_chunk, max_size| {
+            pack_stream(
+                setup.clone(),
+                upload,
+                &proof,
+                max_chunk,
+                phase1.clone(),
+                max_size,
+                |ixs| legacy_size(&payer, ixs),
+            )
+            .unwrap()
+        };
+
+        for (max_chunk, max_size) in [
+            (DEFAULT_CHUNK_SIZE, 1232),
+            (DEFAULT_CHUNK_SIZE, 4096),
+            (4096, 65_536),
+        ] {
+            let transactions = pack(max_chunk, max_size);
+            // Every transaction fits, setup comes first and Phase 1 last
+            for ixs in &transactions {
+                assert!(legacy_size(&payer, ixs).unwrap() <= max_size);
+            }
+            assert_eq!(transactions[0][0], setup[0]);
+            assert_eq!(transactions.last().unwrap().last(), Some(&phase1));
+
+            // The chunks cover the proof in order
+            let mut streamed = Vec::new();
+            for ix in transactions.iter().flatten() {
+                if ix.data[0] == IX_UPLOAD_CHUNK {
+                    let offset = u16::from_le_bytes([ix.data[1], ix.data[2]]) as usize;
+                    assert!(ix.data.len() - 3 <= max_chunk);
+                    assert_eq!(offset, streamed.len());
+                    assert_eq!(offset % UPLOAD_SLOT_SIZE, 0);
+                    streamed.extend_from_slice(&ix.data[3..]);
+                }
+            }
+            assert_eq!(streamed, proof);
+        }
+
+        // A large enough transaction holds everything
+        assert_eq!(pack(4096, 65_536).len(), 1);
+        // Larger transactions carry several chunks each
+        assert!(pack(DEFAULT_CHUNK_SIZE, 4096).len() < pack(DEFAULT_CHUNK_SIZE, 1232).len());
+
+        let error = pack_stream(
+            setup.clone(),
+            upload,
+            &proof,
+            1020,
+            phase1.clone(),
+            100,
+            |ixs| legacy_size(&payer, ixs),
+        );
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_fit_chunk_size() {
+        let (payer, proof_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let config = VerifierConfig::new(Pubkey::new_unique());
+        let fit = |config: &VerifierConfig| fit_chunk_size(config, &payer, &proof_account, None);
+        let upload_size = |config: &VerifierConfig, len: usize, priced: bool| {
+            let mut ixs: Vec<Instruction> = priced
+                .then(|| fees::set_compute_unit_price(1))
+                .into_iter()
+                .collect();
+            ixs.push(instructions::upload_chunk(
+                &config.program_id,
+                &proof_account,
+                0,
+                &vec![0; len],
+            ));
+            legacy_size(&payer, &ixs).unwrap()
+        };
+
+        // A default transaction holds one whole bitmap slot
+        assert_eq!(fit(&config).unwrap(), UPLOAD_SLOT_SIZE);
+
+        // A priority fee instruction leaves no room for a whole slot, so
+        // priced uploads still send one and drop the fee
+        let priced = config
+            .clone()
+            .with_fees(FeeConfig::default().with_priority_fee(1_000));
+        assert_eq!(fit(&priced).unwrap(), UPLOAD_SLOT_SIZE);
+        assert!(upload_size(&priced, UPLOAD_SLOT_SIZE, true) > DEFAULT_MAX_TRANSACTION_SIZE);
+        assert!(upload_size(&priced, UPLOAD_SLOT_SIZE, false) <= DEFAULT_MAX_TRANSACTION_SIZE);
+        assert!(!upload_carries_fee(
+            &priced,
+            &payer,
+            &proof_account,
+            None,
+            UPLOAD_SLOT_SIZE
+        ));
+        let priced_large = priced.clone().with_max_transaction_size(4096);
+        assert!(upload_carries_fee(
+            &priced_large,
+            &payer,
+            &proof_account,
+            None,
+            3 * UPLOAD_SLOT_SIZE
+        ));
+
+        // A fixed chunk size is cut to whole slots too
+        assert_eq!(whole_slots(1500), UPLOAD_SLOT_SIZE);
+        assert_eq!(whole_slots(100), UPLOAD_SLOT_SIZE);
+        assert_eq!(whole_slots(PROOF_SIZE + 1), PROOF_SIZE + 1);
+
+        // Larger transactions carry whole slots
+        let large = config.clone().with_max_transaction_size(4096);
+        assert_eq!(fit(&large).unwrap(), 3 * UPLOAD_SLOT_SIZE);
+        let huge = config.clone().with_max_transaction_size(65_536);
+        assert_eq!(fit(&huge).unwrap(), PROOF_SIZE);
+
+        assert!(fit(&config.clone().with_max_transaction_size(100)).is_err());
+
+        // Public inputs are not cut to slots; 32 of them fit one transaction
+        let pi_size = fit_public_input_chunk_size(&config, &payer, &proof_account, None).unwrap();
+        assert!((32 * 32..UPLOAD_SLOT_SIZE + 32).contains(&pi_size));
+        let pi_upload = instructions::upload_data(
+            &config.program_id,
+            &proof_account,
+            BufferRegion::PublicInputs,
+            0,
+            &vec![0; pi_size],
+        );
+        assert_eq!(pi_upload.data[..4], [IX_UPLOAD_DATA, 0, 0, 0]);
+        assert!(legacy_size(&payer, &[pi_upload]).unwrap() <= DEFAULT_MAX_TRANSACTION_SIZE);
+    }
+}
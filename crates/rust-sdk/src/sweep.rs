@@ -0,0 +1,314 @@
+//! Finding and closing every verifier account a keypair paid for
+//!
+//! Receipts record their rent payer, so they are found with one
+//! `getProgramAccounts` call filtered on that field. Proof buffers and state
+//! accounts record no owner; they are found in the payer's transaction
+//! history, from the `CreateAccount` instructions of verification setups.
+
+use crate::{client::SolanaNoirVerifier, error::Result, instructions, rpc::RpcOperation, types::*};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    config::{UiAccountEncoding, UiTransactionEncoding},
+    filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use solana_system_interface::program as system_program;
+
+/// Close instructions packed into one transaction
+const CLOSES_PER_TRANSACTION: usize = 8;
+
+/// Largest page `getSignaturesForAddress` returns
+const SIGNATURES_PER_PAGE: usize = 1000;
+
+/// Most accounts `getMultipleAccounts` takes
+const ACCOUNTS_PER_FETCH: usize = 100;
+
+// Program `Phase::Complete` and `Phase::Failed`
+const PHASE_COMPLETE: u8 = 7;
+const PHASE_FAILED: u8 = 255;
+
+impl SolanaNoirVerifier {
+    /// Find the proof buffers, state accounts and receipts `owner` paid for
+    ///
+    /// Buffers and state accounts are looked up in the last `history_limit`
+    /// transactions of `owner`; older ones are missed.
+    pub fn find_owned_accounts(
+        &self,
+        owner: &Pubkey,
+        history_limit: usize,
+    ) -> Result<OwnedAccounts> {
+        let program_id = self.config.program_id;
+        let mut owned = OwnedAccounts::default();
+
+        let pairs = self.created_verification_accounts(owner, history_limit)?;
+        let keys: Vec<Pubkey> = pairs
+            .iter()
+            .flat_map(|&(state, proof)| [state, proof])
+            .collect();
+        let mut accounts = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(ACCOUNTS_PER_FETCH) {
+            accounts.extend(
+                self.rpc_call(RpcOperation::Fetch, |rpc| rpc.get_multiple_accounts(chunk))?,
+            );
+        }
+
+        for (&(state_account, proof_account), fetched) in pairs.iter().zip(accounts.chunks(2)) {
+            let [state, proof] = [&fetched[0], &fetched[1]].map(|account| {
+                account
+                    .as_ref()
+                    .filter(|a| a.owner == program_id && a.lamports > 0)
+            });
+            if state.is_none() && proof.is_none() {
+                continue;
+            }
+            match state.and_then(|s| s.data.first()) {
+                Some(&phase) if phase != PHASE_COMPLETE && phase != PHASE_FAILED => {
+                    owned.in_progress.push(state_account);
+                }
+                _ => owned.verifications.push(OwnedVerification {
+                    state_account,
+                    proof_account,
+                    lamports: state.map_or(0, |a| a.lamports) + proof.map_or(0, |a| a.lamports),
+                }),
+            }
+        }
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(RECEIPT_SIZE as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    RECEIPT_RENT_PAYER_OFFSET,
+                    owner.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        owned.receipts = self
+            .rpc_call(RpcOperation::Fetch, |rpc| {
+                rpc.get_program_ui_accounts_with_config(&program_id, config.clone())
+            })?
+            .into_iter()
+            .map(|(receipt, account)| OwnedReceipt {
+                receipt,
+                lamports: account.lamports,
+                rent_beneficiary: account
+                    .data
+                    .decode()
+                    .and_then(|data| ReceiptInfo::from_account_data(receipt, &data))
+                    .map_or(*owner, |r| r.rent_beneficiary),
+            })
+            .collect();
+        Ok(owned)
+    }
+
+    /// Close accounts from [`find_owned_accounts`](Self::find_owned_accounts)
+    /// in batched transactions
+    ///
+    /// Receipts are only closed with `include_receipts`, since a closed
+    /// receipt no longer proves the verification to integrators. State
+    /// accounts in progress are left alone. Stops at the first failed
+    /// transaction; accounts closed before it stay closed.
+    pub fn sweep(
+        &self,
+        payer: &dyn Signer,
+        owned: &OwnedAccounts,
+        include_receipts: bool,
+    ) -> Result<SweepResult> {
+        let program_id = self.config.program_id;
+        let mut closes: Vec<(Instruction, usize, u64)> = owned
+            .verifications
+            .iter()
+            .map(|v| {
+                let ix = instructions::close_accounts(
+                    &program_id,
+                    &v.state_account,
+                    &v.proof_account,
+                    &payer.pubkey(),
+                );
+                (ix, 2, v.lamports)
+            })
+            .collect();
+        if include_receipts {
+            closes.extend(owned.receipts.iter().map(|r| {
+                let ix = instructions::close_receipt_to(
+                    &program_id,
+                    &r.receipt,
+                    &payer.pubkey(),
+                    &r.rent_beneficiary,
+                );
+                (ix, 1, r.lamports)
+            }));
+        }
+
+        let mut result = SweepResult::default();
+        for batch in closes.chunks(CLOSES_PER_TRANSACTION) {
+            let ixs = batch.iter().map(|(ix, _, _)| ix.clone()).collect();
+            result
+                .signatures
+                .push(self.send_and_confirm(payer, &[], ixs, false)?);
+            result.closed_accounts += batch.iter().map(|(_, n, _)| n).sum::<usize>();
+            result.recovered_lamports += batch.iter().map(|(_, _, l)| l).sum::<u64>();
+        }
+        Ok(result)
+    }
+
+    /// (state, proof buffer) pairs created by `owner`'s verification setups
+    fn created_verification_accounts(
+        &self,
+        owner: &Pubkey,
+        history_limit: usize,
+    ) -> Result<Vec<(Pubkey, Pubkey)>> {
+        let mut signatures = Vec::new();
+        let (mut before, mut scanned) = (None, 0);
+        while scanned < history_limit {
+            let limit = (history_limit - scanned).min(SIGNATURES_PER_PAGE);
+            let page = self.rpc_call(RpcOperation::Fetch, |rpc| {
+                rpc.get_signatures_for_address_with_config(
+                    owner,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        limit: Some(limit),
+                        ..Default::default()
+                    },
+                )
+            })?;
+            let Some(last) = page.last() else { break };
+            before = last.signature.parse().ok();
+            scanned += page.len();
+            let full_page = page.len() == limit;
+            signatures.extend(
+                page.iter()
+                    .filter(|status| status.err.is_none())
+                    .filter_map(|status| status.signature.parse::<Signature>().ok()),
+            );
+            if !full_page || before.is_none() {
+                break;
+            }
+        }
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: None,
+            max_supported_transaction_version: Some(0),
+        };
+        let mut pairs = Vec::new();
+        for signature in signatures {
+            let Some(tx) = self
+                .rpc_call(RpcOperation::Fetch, |rpc| {
+                    rpc.get_transaction_with_config(&signature, config)
+                })?
+                .transaction
+                .transaction
+                .decode()
+            else {
+                continue;
+            };
+            if let Some(pair) = setup_accounts(
+                tx.message.static_account_keys(),
+                tx.message.instructions(),
+                &self.config.program_id,
+            ) {
+                pairs.push(pair);
+            }
+        }
+        Ok(pairs)
+    }
+}
+
+/// The (state, proof buffer) a verification setup transaction creates
+///
+/// A setup creates two accounts owned by the program, the state being the
+/// one of [`STATE_SIZE`] bytes. VK uploads create no account of that size,
+/// so they never match.
+pub(crate) fn setup_accounts(
+    keys: &[Pubkey],
+    instructions: &[solana_sdk::message::compiled_instruction::CompiledInstruction],
+    program_id: &Pubkey,
+) -> Option<(Pubkey, Pubkey)> {
+    let (mut state, mut proof) = (None, None);
+    for ix in instructions {
+        if keys.get(ix.program_id_index as usize) != Some(&system_program::ID) {
+            continue;
+        }
+        // SystemInstruction::CreateAccount: tag(4) lamports(8) space(8) owner(32)
+        let data = &ix.data;
+        if data.len() != 52 || data[..4] != [0, 0, 0, 0] || &data[20..52] != program_id.as_ref() {
+            continue;
+        }
+        let space = u64::from_le_bytes(data[12..20].try_into().unwrap());
+        let Some(&new_account) = ix.accounts.get(1).and_then(|&i| keys.get(i as usize)) else {
+            continue;
+        };
+        if space == STATE_SIZE as u64 {
+            state = Some(new_account);
+        } else {
+            proof = Some(new_account);
+        }
+    }
+    state.zip(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::message::Message;
+    use solana_system_interface::instruction as system_instruction;
+
+    fn setup_message(program_id: &Pubkey, state_space: u64) -> Message {
+        let (payer, proof, state) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        Message::new(
+            &[
+                system_instruction::create_account(&payer, &proof, 1, 16_000, program_id),
+                system_instruction::create_account(&payer, &state, 1, state_space, program_id),
+                instructions::init_buffer(program_id, &proof, 1),
+            ],
+            Some(&payer),
+        )
+    }
+
+    #[test]
+    fn test_setup_accounts_finds_state_and_buffer() {
+        let program_id = Pubkey::new_unique();
+        let message = setup_message(&program_id, STATE_SIZE as u64);
+        let (state, proof) =
+            setup_accounts(&message.account_keys, &message.instructions, &program_id).unwrap();
+        assert_eq!(
+            message.account_keys[message.instructions[1].accounts[1] as usize],
+            state
+        );
+        assert_eq!(
+            message.account_keys[message.instructions[0].accounts[1] as usize],
+            proof
+        );
+    }
+
+    #[test]
+    fn test_setup_accounts_ignores_other_transactions() {
+        let program_id = Pubkey::new_unique();
+        // No account of the state's size, e.g. a VK upload
+        let message = setup_message(&program_id, 4000);
+        assert_eq!(
+            setup_accounts(&message.account_keys, &message.instructions, &program_id),
+            None
+        );
+        // Accounts created for another program
+        let message = setup_message(&Pubkey::new_unique(), STATE_SIZE as u64);
+        assert_eq!(
+            setup_accounts(&message.account_keys, &message.instructions, &program_id),
+            None
+        );
+    }
+}
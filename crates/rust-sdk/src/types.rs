@@ -1,6 +1,11 @@
 //! Types and constants for the Solana Noir Verifier SDK
 
+use crate::metrics::Metrics;
+use crate::progress::ProgressObserver;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Configuration for the Solana Noir Verifier client
 #[derive(Clone)]
@@ -9,8 +14,49 @@ pub struct VerifierConfig {
     pub program_id: Pubkey,
     /// Compute unit limit per transaction (default: 1,400,000)
     pub compute_unit_limit: u32,
-    /// Chunk size for proof uploads (default: 1020 bytes)
-    pub chunk_size: usize,
+    /// Bytes per upload transaction (default: for proofs, as many as fit in
+    /// `max_transaction_size`, see
+    /// [`SolanaNoirVerifier::proof_chunk_size`](crate::SolanaNoirVerifier::proof_chunk_size);
+    /// 1020 for VKs and shards)
+    pub chunk_size: Option<usize>,
+    /// Largest serialized transaction the cluster accepts (default: 1232 bytes)
+    pub max_transaction_size: usize,
+    /// Retries per transaction in `verify_phased` (default: 3)
+    pub max_retries: u32,
+    /// Proof chunk transactions in flight at once (default: 8)
+    pub upload_concurrency: usize,
+    /// Proofs `verify_many` verifies at once (default: 4)
+    pub batch_concurrency: usize,
+    /// Compute budget and priority fee settings for every transaction
+    pub fees: FeeConfig,
+    /// Address lookup table to compile v0 transactions against
+    pub lookup_table: Option<Pubkey>,
+    /// Websocket endpoint for confirmation and receipt notifications
+    /// (polls the RPC when unset)
+    pub websocket_url: Option<String>,
+    /// Fallback RPC endpoints, tried in order when the primary client is
+    /// rate limited, times out or is unreachable
+    pub rpc_endpoints: Vec<String>,
+    /// Retry and backoff for RPC requests, per kind of request
+    pub retry: RetryConfig,
+    /// Sign phase transactions in `verify_phased` against a durable nonce
+    /// so they cannot expire (default: false)
+    pub durable_nonce: bool,
+    /// Accounts `verify_phased` closes once the receipt exists
+    /// (default: proof buffer and state)
+    pub cleanup: CleanupPolicy,
+    /// File to checkpoint in-flight verifications to, removed once they
+    /// finish (see [`VerificationSession::load`](crate::VerificationSession::load))
+    pub session_file: Option<PathBuf>,
+    /// Receives phase, transaction and upload events as they happen
+    pub progress: Option<Arc<dyn ProgressObserver>>,
+    /// Receives transaction, retry, CU and latency measurements
+    pub metrics: Option<Arc<dyn Metrics>>,
+    /// VK registry program that `name@version` keys resolve through
+    pub registry_program: Option<Pubkey>,
+    /// Submit phase transactions as Jito bundles
+    #[cfg(feature = "jito")]
+    pub jito: Option<JitoConfig>,
 }
 
 impl VerifierConfig {
@@ -19,7 +65,24 @@ impl VerifierConfig {
         Self {
             program_id,
             compute_unit_limit: DEFAULT_COMPUTE_UNIT_LIMIT,
-            chunk_size: DEFAULT_CHUNK_SIZE,
+            chunk_size: None,
+            max_transaction_size: DEFAULT_MAX_TRANSACTION_SIZE,
+            max_retries: DEFAULT_MAX_RETRIES,
+            upload_concurrency: DEFAULT_UPLOAD_CONCURRENCY,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            fees: FeeConfig::default(),
+            lookup_table: None,
+            websocket_url: None,
+            rpc_endpoints: Vec::new(),
+            retry: RetryConfig::default(),
+            durable_nonce: false,
+            cleanup: CleanupPolicy::default(),
+            session_file: None,
+            progress: None,
+            metrics: None,
+            registry_program: None,
+            #[cfg(feature = "jito")]
+            jito: None,
         }
     }
 
@@ -29,9 +92,330 @@ impl VerifierConfig {
         self
     }
 
-    /// Set custom chunk size
+    /// Upload in chunks of `size` bytes instead of fitting proof chunks to
+    /// the transaction size
     pub fn with_chunk_size(mut self, size: usize) -> Self {
-        self.chunk_size = size;
+        self.chunk_size = Some(size);
+        self
+    }
+
+    /// Set the largest transaction the cluster accepts
+    ///
+    /// Raise it on clusters with larger transactions so proof uploads and
+    /// [`SolanaNoirVerifier::verify_streamed`](crate::SolanaNoirVerifier::verify_streamed)
+    /// pack more of the proof into each one.
+    pub fn with_max_transaction_size(mut self, size: usize) -> Self {
+        self.max_transaction_size = size;
+        self
+    }
+
+    /// Set how many proof chunks are uploaded concurrently (1 = sequential)
+    pub fn with_upload_concurrency(mut self, concurrency: usize) -> Self {
+        self.upload_concurrency = concurrency;
+        self
+    }
+
+    /// Set how many proofs `verify_many` runs at once
+    ///
+    /// Each one uploads up to `upload_concurrency` chunks at a time.
+    pub fn with_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.batch_concurrency = concurrency;
+        self
+    }
+
+    /// Set compute budget and priority fee handling
+    pub fn with_fees(mut self, fees: FeeConfig) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Send v0 transactions that resolve accounts through `lookup_table`
+    ///
+    /// Create or top up the table with
+    /// [`SolanaNoirVerifier::ensure_alt`](crate::SolanaNoirVerifier::ensure_alt).
+    pub fn with_lookup_table(mut self, lookup_table: Pubkey) -> Self {
+        self.lookup_table = Some(lookup_table);
+        self
+    }
+
+    /// Wait for confirmations and receipts over a websocket subscription,
+    /// e.g. `ws://localhost:8900`
+    pub fn with_websocket_url(mut self, url: impl Into<String>) -> Self {
+        self.websocket_url = Some(url.into());
+        self
+    }
+
+    /// Fail over to these RPC endpoints when the primary one is unhealthy
+    ///
+    /// Clients for them are created with the primary client's commitment.
+    pub fn with_rpc_endpoints<S: Into<String>>(
+        mut self,
+        endpoints: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.rpc_endpoints = endpoints.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set retry and backoff for RPC requests
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Run the phases of `verify_phased` against a durable nonce account
+    ///
+    /// The account is created before the first phase and closed after the
+    /// last, costing two extra transactions.
+    pub fn with_durable_nonce(mut self) -> Self {
+        self.durable_nonce = true;
+        self
+    }
+
+    /// Set which accounts `verify_phased` closes after creating the receipt
+    pub fn with_cleanup(mut self, cleanup: CleanupPolicy) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+
+    /// Checkpoint verifications to `path` so they survive a restart
+    ///
+    /// The file is written once the proof accounts exist and updated as
+    /// chunks and the durable nonce change. After a crash, pass it to
+    /// [`VerificationSession::load`](crate::VerificationSession::load) and
+    /// resume.
+    pub fn with_session_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.session_file = Some(path.into());
+        self
+    }
+
+    /// Report progress to `observer`
+    pub fn with_progress_observer(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.progress = Some(observer);
+        self
+    }
+
+    /// Export counters and timings through `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Resolve and register `name@version` keys on chain through
+    /// `registry_program`
+    pub fn with_registry_program(mut self, registry_program: Pubkey) -> Self {
+        self.registry_program = Some(registry_program);
+        self
+    }
+
+    /// Send phase transactions as Jito bundles through `jito.block_engine_url`
+    #[cfg(feature = "jito")]
+    pub fn with_jito(mut self, jito: JitoConfig) -> Self {
+        self.jito = Some(jito);
+        self
+    }
+
+    /// Set how often a failed transaction is retried
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+}
+
+/// How phase transactions size their compute unit limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CuLimitStrategy {
+    /// Request [`VerifierConfig::compute_unit_limit`] for every phase
+    #[default]
+    Fixed,
+    /// Request the cost-model estimate for each phase plus headroom
+    /// (see `plonk_solana_core::cost`), so priority fees are paid on the
+    /// CUs a phase actually needs
+    Estimated,
+}
+
+/// Accounts closed once a verification's receipt exists
+///
+/// Closing happens in one final transaction and returns the rent to the
+/// payer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CleanupPolicy {
+    /// Leave every account open, e.g. to inspect the state account
+    Keep,
+    /// Close the proof buffer and state accounts
+    #[default]
+    CloseBuffers,
+    /// Also close the receipt, for callers that only need the result off
+    /// chain; CPI checks no longer see the proof as verified
+    CloseAll,
+}
+
+/// How much priority fee to attach, in micro-lamports per compute unit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PriorityFeeStrategy {
+    /// No `SetComputeUnitPrice` instruction
+    #[default]
+    None,
+    /// Always pay this price
+    Fixed(u64),
+    /// Pick a percentile of `getRecentPrioritizationFees` for the accounts
+    /// the transaction writes, capped at `max_micro_lamports`
+    Dynamic {
+        percentile: u8,
+        max_micro_lamports: u64,
+    },
+}
+
+/// Compute budget settings applied to every SDK transaction
+///
+/// Budget instructions are prepended automatically: phase transactions get
+/// a `SetComputeUnitLimit` from `cu_limit_strategy` and, if set, a
+/// `RequestHeapFrame` of `heap_frame_bytes`; every transaction gets a
+/// `SetComputeUnitPrice` unless the fee strategy is `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct FeeConfig {
+    pub cu_limit_strategy: CuLimitStrategy,
+    pub priority_fee_strategy: PriorityFeeStrategy,
+    /// Heap to request for phase transactions, for a verifier built with
+    /// the `large-heap` feature (default: the runtime's 32 KB)
+    pub heap_frame_bytes: Option<u32>,
+}
+
+impl FeeConfig {
+    /// Use per-phase CU estimates instead of the fixed limit
+    pub fn with_estimated_cu_limits(mut self) -> Self {
+        self.cu_limit_strategy = CuLimitStrategy::Estimated;
+        self
+    }
+
+    /// Whether transactions get a `SetComputeUnitPrice` instruction
+    pub(crate) fn sets_compute_unit_price(&self) -> bool {
+        match self.priority_fee_strategy {
+            PriorityFeeStrategy::None => false,
+            PriorityFeeStrategy::Fixed(price) => price > 0,
+            PriorityFeeStrategy::Dynamic { .. } => true,
+        }
+    }
+
+    /// Pay a fixed priority fee
+    pub fn with_priority_fee(mut self, micro_lamports: u64) -> Self {
+        self.priority_fee_strategy = PriorityFeeStrategy::Fixed(micro_lamports);
+        self
+    }
+
+    /// Request a larger heap for phase transactions
+    ///
+    /// `bytes` must be a multiple of 1024 between 32 KB and 256 KB.
+    pub fn with_heap_frame(mut self, bytes: u32) -> Self {
+        self.heap_frame_bytes = Some(bytes);
+        self
+    }
+
+    /// Derive the priority fee from recent fees on the written accounts
+    pub fn with_dynamic_priority_fee(mut self, percentile: u8, max_micro_lamports: u64) -> Self {
+        self.priority_fee_strategy = PriorityFeeStrategy::Dynamic {
+            percentile,
+            max_micro_lamports,
+        };
+        self
+    }
+}
+
+/// Retries for one kind of RPC request
+///
+/// Only endpoint failures are retried: HTTP 429 and 5xx responses,
+/// timeouts and connection errors. Each retry fails over to the next
+/// healthy endpoint in [`VerifierConfig::rpc_endpoints`] and waits
+/// `initial_backoff`, doubling up to `max_backoff`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub const fn new(max_retries: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    /// Delay before retry number `attempt` (starting at 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_backoff)
+    }
+}
+
+/// [`RetryPolicy`] per kind of RPC request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// `sendTransaction`; resending the same signed transaction is safe
+    pub send: RetryPolicy,
+    /// Signature status polling
+    pub confirm: RetryPolicy,
+    /// Account and blockhash reads
+    pub fetch: RetryPolicy,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            send: RetryPolicy::new(3, Duration::from_millis(500), Duration::from_secs(4)),
+            confirm: RetryPolicy::new(5, Duration::from_millis(200), Duration::from_secs(2)),
+            fetch: RetryPolicy::new(3, Duration::from_millis(250), Duration::from_secs(2)),
+        }
+    }
+}
+
+/// Jito block engine settings for bundled phase submission
+///
+/// Consecutive phase transactions are grouped into bundles of up to
+/// [`JITO_MAX_BUNDLE_SIZE`]; the block engine lands a bundle in one slot,
+/// in order, or not at all. The last transaction of each bundle pays
+/// `tip_lamports` to `tip_account`.
+#[cfg(feature = "jito")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JitoConfig {
+    /// Block engine bundle endpoint
+    pub block_engine_url: String,
+    /// Tip paid per bundle
+    pub tip_lamports: u64,
+    /// One of the block engine's tip accounts
+    pub tip_account: Pubkey,
+}
+
+#[cfg(feature = "jito")]
+impl Default for JitoConfig {
+    fn default() -> Self {
+        Self {
+            block_engine_url: JITO_MAINNET_BLOCK_ENGINE.to_string(),
+            tip_lamports: JITO_DEFAULT_TIP_LAMPORTS,
+            tip_account: Pubkey::from_str_const("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+        }
+    }
+}
+
+#[cfg(feature = "jito")]
+impl JitoConfig {
+    /// Use a different block engine, e.g. a regional or testnet endpoint
+    pub fn with_block_engine_url(mut self, url: impl Into<String>) -> Self {
+        self.block_engine_url = url.into();
+        self
+    }
+
+    /// Set the tip paid per bundle
+    pub fn with_tip(mut self, lamports: u64) -> Self {
+        self.tip_lamports = lamports;
+        self
+    }
+
+    /// Set the tip account
+    pub fn with_tip_account(mut self, tip_account: Pubkey) -> Self {
+        self.tip_account = tip_account;
         self
     }
 }
@@ -70,6 +454,193 @@ pub struct VerificationResult {
     pub accounts_closed: bool,
 }
 
+/// Result of [`SolanaNoirVerifier::verify_phased`](crate::SolanaNoirVerifier::verify_phased)
+#[derive(Debug, Clone)]
+pub struct VerifiedReceipt {
+    /// The receipt created for the verified proof
+    pub receipt: ReceiptInfo,
+    /// The state account used for verification
+    pub state_account: Pubkey,
+    /// The proof buffer account
+    pub proof_account: Pubkey,
+    /// Total compute units consumed across verification phases
+    pub total_cus: u64,
+    /// All transaction signatures, in order
+    pub signatures: Vec<Signature>,
+    /// Lamports recovered from the accounts the [`CleanupPolicy`] closed
+    pub recovered_lamports: Option<u64>,
+}
+
+/// Outcome of one transaction in a [`SimulationReport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedStep {
+    /// `"setup"`, or a phase named as in progress events
+    pub name: String,
+    /// Whether the cluster simulated the transaction; phases are replayed
+    /// off-chain
+    pub on_chain: bool,
+    /// CUs the simulation consumed, or the cost model's estimate for phases
+    pub compute_units: u64,
+    /// Program logs of an on-chain simulation
+    pub logs: Vec<String>,
+    /// Why the transaction would fail
+    pub error: Option<String>,
+}
+
+/// Result of [`SolanaNoirVerifier::simulate`](crate::SolanaNoirVerifier::simulate)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Setup, then every phase transaction, in order
+    pub steps: Vec<SimulatedStep>,
+}
+
+impl SimulationReport {
+    /// The first transaction that would fail, if any
+    pub fn first_failure(&self) -> Option<&SimulatedStep> {
+        self.steps.iter().find(|step| step.error.is_some())
+    }
+
+    /// Whether every transaction would succeed
+    pub fn passed(&self) -> bool {
+        self.first_failure().is_none()
+    }
+}
+
+/// Verifier accounts one keypair paid for, from
+/// [`SolanaNoirVerifier::find_owned_accounts`](crate::SolanaNoirVerifier::find_owned_accounts)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnedAccounts {
+    /// Finished verifications, and buffers or states whose partner is
+    /// already closed
+    pub verifications: Vec<OwnedVerification>,
+    /// State accounts mid-verification, which the program refuses to close
+    pub in_progress: Vec<Pubkey>,
+    /// Receipts whose rent payer is the keypair
+    pub receipts: Vec<OwnedReceipt>,
+}
+
+/// State and proof buffer of one verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnedVerification {
+    pub state_account: Pubkey,
+    pub proof_account: Pubkey,
+    /// Rent held by both accounts
+    pub lamports: u64,
+}
+
+/// A receipt and the rent it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnedReceipt {
+    pub receipt: Pubkey,
+    pub lamports: u64,
+    /// Account closing the receipt refunds the rent to
+    pub rent_beneficiary: Pubkey,
+}
+
+/// Result of [`SolanaNoirVerifier::sweep`](crate::SolanaNoirVerifier::sweep)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SweepResult {
+    /// Buffers, state accounts and receipts closed
+    pub closed_accounts: usize,
+    /// Rent refunded to the payer, or to the beneficiaries of its receipts
+    pub recovered_lamports: u64,
+    /// One per batched close transaction
+    pub signatures: Vec<Signature>,
+}
+
+/// A verification left for a cranker, from
+/// [`SolanaNoirVerifier::submit_verification`](crate::SolanaNoirVerifier::submit_verification)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmittedVerification {
+    pub state_account: Pubkey,
+    pub proof_account: Pubkey,
+    /// Setup, upload and Phase 1 transactions
+    pub signatures: Vec<Signature>,
+}
+
+/// A verification past Phase 1 without a receipt yet, from
+/// [`SolanaNoirVerifier::find_pending_verifications`](crate::SolanaNoirVerifier::find_pending_verifications)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingVerification {
+    pub state_account: Pubkey,
+    /// VK bound to the state in Phase 1
+    pub vk_account: Pubkey,
+    /// Program phase: 2-6 mid-verification, 7 once verified
+    pub phase: u8,
+}
+
+/// Everything a cranker needs to drive someone else's verification, from
+/// [`SolanaNoirVerifier::find_crank_job`](crate::SolanaNoirVerifier::find_crank_job)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrankJob {
+    pub state_account: Pubkey,
+    pub proof_account: Pubkey,
+    pub vk_account: Pubkey,
+    /// Fee payer of the setup transaction
+    pub owner: Pubkey,
+    /// Lamports the verification's transactions transferred to the cranker
+    pub tip_lamports: u64,
+}
+
+/// What [`SolanaNoirVerifier::advance_verification`](crate::SolanaNoirVerifier::advance_verification) did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrankOutcome {
+    /// Sent the next phase transaction
+    Advanced {
+        /// Phase label, as passed to [`Metrics::phase_compute_units`](crate::Metrics::phase_compute_units)
+        phase: &'static str,
+        signature: Signature,
+        compute_units: u64,
+    },
+    /// Created the receipt of a verified proof
+    ReceiptCreated {
+        receipt: Pubkey,
+        signature: Signature,
+    },
+    /// Nothing left to send: the receipt exists, the proof was rejected,
+    /// or the owner closed the accounts
+    Finished { verified: bool },
+}
+
+/// Expected cost of one verification, from [`SolanaNoirVerifier::estimate`](crate::SolanaNoirVerifier::estimate)
+///
+/// All amounts are in lamports except `total_compute_units`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostReport {
+    /// Circuit size the phase count and CUs were estimated for
+    pub log_n: u8,
+    /// Transactions sent: setup, chunk uploads, phases, receipt and close
+    pub num_transactions: usize,
+    /// Program compute units across all verification phases
+    pub total_compute_units: u64,
+    /// Base fees for every signature
+    pub signature_fees: u64,
+    /// Priority fees at the configured or current price
+    pub priority_fees: u64,
+    /// Rent-exempt deposit for the proof buffer
+    pub buffer_rent: u64,
+    /// Rent-exempt deposit for the state account
+    pub state_rent: u64,
+    /// Rent-exempt deposit for the receipt, which stays open
+    pub receipt_rent: u64,
+}
+
+impl CostReport {
+    /// Lamports the payer needs up front
+    pub fn total_lamports(&self) -> u64 {
+        self.signature_fees
+            + self.priority_fees
+            + self.buffer_rent
+            + self.state_rent
+            + self.receipt_rent
+    }
+
+    /// Lamports spent once the buffer and state rent is reclaimed
+    pub fn net_lamports(&self) -> u64 {
+        self.total_lamports() - self.buffer_rent - self.state_rent
+    }
+}
+
 /// Options for proof verification
 #[derive(Clone)]
 pub struct VerifyOptions {
@@ -77,6 +648,8 @@ pub struct VerifyOptions {
     pub skip_preflight: bool,
     /// Automatically close accounts after verification to reclaim rent (default: true)
     pub auto_close: bool,
+    /// Skip verifying the proof off-chain before submitting it (default: false)
+    pub skip_local_check: bool,
 }
 
 impl Default for VerifyOptions {
@@ -84,6 +657,7 @@ impl Default for VerifyOptions {
         Self {
             skip_preflight: false,
             auto_close: true, // Default is to auto-close and reclaim rent
+            skip_local_check: false,
         }
     }
 }
@@ -105,6 +679,12 @@ impl VerifyOptions {
         self.skip_preflight = true;
         self
     }
+
+    /// Submit without verifying the proof off-chain first
+    pub fn with_skip_local_check(mut self) -> Self {
+        self.skip_local_check = true;
+        self
+    }
 }
 
 /// Verification phase status (from on-chain state)
@@ -121,11 +701,113 @@ pub enum VerificationPhase {
 }
 
 /// Parsed verification state from on-chain account
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct VerificationState {
     pub phase: VerificationPhase,
     pub log_n: u8,
     pub verified: bool,
+    /// Phase byte as stored, which tells the in-progress phases apart
+    pub raw_phase: u8,
+    /// Phase 1 steps done (0-6)
+    pub challenge_sub_phase: u8,
+    /// Phase 2 progress marker (rounds, then relations)
+    pub sumcheck_sub_phase: u8,
+    /// Sumcheck rounds verified so far
+    pub sumcheck_rounds_completed: u8,
+    /// Phase 3 MSM steps done (0-4)
+    pub shplemini_sub_phase: u8,
+}
+
+/// Progress of one verification stage, as [`VerificationState::progress`]
+/// reports it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageProgress {
+    pub name: &'static str,
+    pub done: u8,
+    pub total: u8,
+}
+
+impl StageProgress {
+    /// Whether the stage is done; a stage whose size is not known yet
+    /// (`total == 0`, before Phase 1 stores `log_n`) never is
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.done >= self.total
+    }
+}
+
+impl VerificationState {
+    /// Decode a state account; the sub-phase fields read as 0 when the
+    /// account is shorter than [`STATE_SIZE`]
+    pub(crate) fn from_account_data(data: &[u8]) -> Option<Self> {
+        let header = data.get(..4)?;
+        let byte = |offset: usize| data.get(offset).copied().unwrap_or(0);
+        let phase = match header[0] {
+            0 => VerificationPhase::NotStarted, // Uninitialized
+            1 => VerificationPhase::NotStarted, // ChallengesInProgress
+            2 => VerificationPhase::ChallengesGenerated,
+            3 => VerificationPhase::NotStarted, // SumcheckInProgress
+            4 => VerificationPhase::SumcheckComplete,
+            5 => VerificationPhase::NotStarted, // MsmInProgress
+            6 => VerificationPhase::MsmComplete,
+            7 => VerificationPhase::Verified, // Complete
+            255 => VerificationPhase::Failed,
+            _ => VerificationPhase::NotStarted,
+        };
+        Some(Self {
+            phase,
+            log_n: header[3],
+            verified: data.len() >= STATE_SIZE && data[STATE_VERIFIED_OFFSET] == 1,
+            raw_phase: header[0],
+            challenge_sub_phase: header[1],
+            sumcheck_sub_phase: header[2],
+            sumcheck_rounds_completed: byte(STATE_ROUNDS_COMPLETED_OFFSET),
+            shplemini_sub_phase: byte(STATE_SHPLEMINI_SUB_PHASE_OFFSET),
+        })
+    }
+
+    /// Whether the state will not change any more (verified or failed)
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.phase,
+            VerificationPhase::Verified | VerificationPhase::Failed
+        )
+    }
+
+    /// Per-stage progress, in verification order
+    ///
+    /// A stage counts as complete once the phase byte has moved past it,
+    /// since the combined instructions skip some sub-phase markers. A failed
+    /// state keeps the markers of the step that failed.
+    pub fn progress(&self) -> Vec<StageProgress> {
+        let past = |raw: u8| self.raw_phase != 255 && self.raw_phase >= raw;
+        let stage = |name, done: u8, total: u8, complete_at: u8| StageProgress {
+            name,
+            done: if past(complete_at) {
+                total
+            } else {
+                done.min(total)
+            },
+            total,
+        };
+        // RelationsPart1Done (5) is the midpoint, RelationsDone (4) the end
+        let relations = match self.sumcheck_sub_phase {
+            4 => 2,
+            5 => 1,
+            _ => 0,
+        };
+        vec![
+            stage("Phase 1 challenges", self.challenge_sub_phase, 6, 2),
+            stage(
+                "Phase 2 sumcheck rounds",
+                self.sumcheck_rounds_completed,
+                self.log_n,
+                4,
+            ),
+            stage("Phase 2 relations", relations, 2, 4),
+            stage("Phase 3 MSM", self.shplemini_sub_phase, 4, 6),
+            stage("Phase 4 pairing", 0, 1, 7),
+        ]
+    }
 }
 
 /// Receipt information
@@ -137,6 +819,113 @@ pub struct ReceiptInfo {
     pub verified_slot: u64,
     /// Unix timestamp when the proof was verified
     pub verified_timestamp: i64,
+    /// Account that paid for the receipt and may close it
+    pub rent_payer: Pubkey,
+    /// Application-defined tag the creator attached (zero if none)
+    pub tag: [u8; 32],
+    /// Verifications counted under [`DuplicateReceiptPolicy::Count`],
+    /// starting at 1 (0 for receipts created before counting existed)
+    pub verification_count: u32,
+    /// Keccak256 of the verified proof bytes, checked by
+    /// [`SolanaNoirVerifier::reverify_by_digest`](crate::SolanaNoirVerifier::reverify_by_digest)
+    pub proof_digest: [u8; 32],
+    /// Canonical hash of the verified VK, see [`vk_hash`](crate::vk_hash)
+    pub vk_hash: [u8; 32],
+    /// Account the rent is refunded to when the receipt is closed
+    pub rent_beneficiary: Pubkey,
+}
+
+impl ReceiptInfo {
+    /// Decode receipt account data; `None` if it is too short
+    pub(crate) fn from_account_data(receipt_pda: Pubkey, data: &[u8]) -> Option<Self> {
+        let data = data.get(..RECEIPT_SIZE)?;
+        Some(Self {
+            receipt_pda,
+            verified_slot: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            verified_timestamp: i64::from_le_bytes(data[8..16].try_into().unwrap()),
+            rent_payer: Pubkey::new_from_array(
+                data[RECEIPT_RENT_PAYER_OFFSET..RECEIPT_TAG_OFFSET]
+                    .try_into()
+                    .unwrap(),
+            ),
+            tag: data[RECEIPT_TAG_OFFSET..RECEIPT_PROOF_DIGEST_OFFSET]
+                .try_into()
+                .unwrap(),
+            verification_count: u32::from_le_bytes(
+                data[RECEIPT_VERIFICATION_COUNT_OFFSET..RECEIPT_RENT_PAYER_OFFSET]
+                    .try_into()
+                    .unwrap(),
+            ),
+            proof_digest: data[RECEIPT_PROOF_DIGEST_OFFSET..RECEIPT_VK_HASH_OFFSET]
+                .try_into()
+                .unwrap(),
+            vk_hash: data[RECEIPT_VK_HASH_OFFSET..RECEIPT_RENT_BENEFICIARY_OFFSET]
+                .try_into()
+                .unwrap(),
+            rent_beneficiary: Pubkey::new_from_array(
+                data[RECEIPT_RENT_BENEFICIARY_OFFSET..RECEIPT_SIZE]
+                    .try_into()
+                    .unwrap(),
+            ),
+        })
+    }
+}
+
+/// What `CreateReceipt` does when the receipt already exists
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateReceiptPolicy {
+    /// Fail with [`ProgramErrorCode::ReceiptAlreadyExists`](crate::ProgramErrorCode::ReceiptAlreadyExists)
+    #[default]
+    Error = 0,
+    /// Move the receipt's slot and timestamp to the new verification
+    Refresh = 1,
+    /// Increment the receipt's verification count
+    Count = 2,
+}
+
+/// Optional `CreateReceipt` arguments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReceiptOptions {
+    /// Stored in the receipt for integrators to read over CPI
+    pub tag: [u8; 32],
+    /// Behavior when the receipt already exists
+    pub on_duplicate: DuplicateReceiptPolicy,
+    /// Account the rent is refunded to on close, the payer if `None`
+    pub rent_beneficiary: Option<Pubkey>,
+}
+
+impl ReceiptOptions {
+    /// Attach an application-defined tag (a nullifier, an order id, a
+    /// commitment) to the receipt
+    pub fn with_tag(mut self, tag: [u8; 32]) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Refresh or count an existing receipt instead of failing
+    ///
+    /// The state account is marked as recorded either way, so a
+    /// verification creates, refreshes or counts the receipt at most once.
+    pub fn with_on_duplicate(mut self, policy: DuplicateReceiptPolicy) -> Self {
+        self.on_duplicate = policy;
+        self
+    }
+
+    /// Refund the rent to `beneficiary` instead of the payer when the
+    /// receipt is closed, e.g. when a dApp sponsors receipts for its users
+    pub fn with_rent_beneficiary(mut self, beneficiary: Pubkey) -> Self {
+        self.rent_beneficiary = Some(beneficiary);
+        self
+    }
+}
+
+/// A receipt seen by [`SolanaNoirVerifier::watch_receipts`](crate::SolanaNoirVerifier::watch_receipts)
+#[derive(Debug, Clone)]
+pub struct WatchedReceipt {
+    pub receipt: ReceiptInfo,
+    /// Keccak256 of the verified public inputs
+    pub public_inputs_hash: [u8; 32],
 }
 
 // =============================================================================
@@ -149,27 +938,150 @@ pub const PROOF_SIZE: usize = 16224;
 /// VK size for bb 0.87
 pub const VK_SIZE: usize = 1760;
 
+/// Proof buffer region an `UploadData` instruction writes to
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferRegion {
+    /// Public inputs, right after the header
+    PublicInputs = 0,
+    /// Proof bytes, after the public inputs
+    Proof = 1,
+}
+
 /// Header size in proof buffer: status(1) + proof_len(2) + pi_count(2) + chunk_bitmap(4)
 pub const BUFFER_HEADER_SIZE: usize = 9;
 
+/// Most shards a shard manifest can reference
+pub const MAX_SHARDS: usize = 8;
+
+/// Shard manifest account size: tag(1) + status(1) + proof_len(2) + pi_count(2) + shard_count(1) + shard pubkeys
+pub const MANIFEST_ACCOUNT_SIZE: usize = 7 + 32 * MAX_SHARDS;
+
+/// Header size in a shard: status(1) + manifest(32) + index(1) + start(4) + len(4) + chunk_bitmap(4)
+pub const SHARD_HEADER_SIZE: usize = 46;
+
+/// Most bytes one shard holds (its chunk bitmap covers 32 chunks)
+pub const MAX_SHARD_LEN: usize = 32 * DEFAULT_CHUNK_SIZE;
+
+/// Most scalars in the final Phase 3c MSM
+pub const MSM_MAX_TERMS: usize = plonk_solana_core::MSM_MAX_TERMS;
+
+/// MSM scratch account size: tag(1) + scalar_count(1) + state(32) + scalars
+pub const SCRATCH_ACCOUNT_SIZE: usize = 34 + 32 * MSM_MAX_TERMS;
+
 /// Header size in VK buffer: status(1) + vk_len(2)
 pub const VK_HEADER_SIZE: usize = 3;
 
+/// Size of the optional x·G2 SRS point stored after the VK
+pub const SRS_G2_SIZE: usize = 128;
+
 /// Verification state account size
-/// Includes: header + challenges + sumcheck state + vk_account field
-pub const STATE_SIZE: usize = 6408;
+/// Includes: header + challenges + sumcheck state + vk_account, proof_buffer,
+/// challenge digest, VK hash, MSM scalar hash and relation coefficients
+/// commitment fields
+pub const STATE_SIZE: usize = 6600;
+
+/// Offset of `sumcheck_rounds_completed` in the state account
+pub const STATE_ROUNDS_COMPLETED_OFFSET: usize = 3208;
+
+/// Offset of `shplemini_sub_phase` in the state account
+pub const STATE_SHPLEMINI_SUB_PHASE_OFFSET: usize = 6216;
+
+/// Offset of the `verified` flag in the state account
+pub const STATE_VERIFIED_OFFSET: usize = 6376;
+
+/// Offset of `proof_buffer`, the proof buffer Phase 1 locked, in the state account
+pub const STATE_PROOF_BUFFER_OFFSET: usize = 6440;
 
-/// Default chunk size for uploads
+/// Chunk size for VK and shard uploads, and the size of a proof buffer's
+/// upload bitmap slot
 pub const DEFAULT_CHUNK_SIZE: usize = 1020;
 
+/// Default transaction size limit (the packet size of mainnet validators)
+pub const DEFAULT_MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Default number of retries per phase transaction
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Default number of proof chunk transactions in flight
+pub const DEFAULT_UPLOAD_CONCURRENCY: usize = 8;
+
+/// Default number of proofs `verify_many` runs at once
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Mainnet block engine bundle endpoint
+#[cfg(feature = "jito")]
+pub const JITO_MAINNET_BLOCK_ENGINE: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
+
+/// Default tip per bundle (the block engine minimum is 1000 lamports)
+#[cfg(feature = "jito")]
+pub const JITO_DEFAULT_TIP_LAMPORTS: u64 = 10_000;
+
+/// Most transactions the block engine accepts in one bundle
+#[cfg(feature = "jito")]
+pub const JITO_MAX_BUNDLE_SIZE: usize = 5;
+
 /// Default compute unit limit per transaction
 pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
 
-/// Receipt size (slot + timestamp)
-pub const RECEIPT_SIZE: usize = 16;
+/// Receipt size (slot + timestamp + vk account + PI hash + bump, padded,
+/// + verification count + rent payer + tag + proof digest + VK hash
+/// + rent beneficiary)
+pub const RECEIPT_SIZE: usize = 248;
+
+/// Offset of the VK account in a receipt account
+pub const RECEIPT_VK_ACCOUNT_OFFSET: usize = 16;
+
+/// Offset of the public inputs hash in a receipt account
+pub const RECEIPT_PI_HASH_OFFSET: usize = 48;
+
+/// Offset of the PDA bump in a receipt account
+pub const RECEIPT_BUMP_OFFSET: usize = 80;
+
+/// Offset of the verification count (u32 LE) in a receipt account
+pub const RECEIPT_VERIFICATION_COUNT_OFFSET: usize = 84;
+
+/// Offset of the rent payer in a receipt account
+pub const RECEIPT_RENT_PAYER_OFFSET: usize = 88;
+
+/// Offset of the application-defined tag in a receipt account
+pub const RECEIPT_TAG_OFFSET: usize = 120;
+
+/// Offset of the proof digest (Keccak256 of the proof bytes) in a receipt
+/// account
+pub const RECEIPT_PROOF_DIGEST_OFFSET: usize = 152;
+
+/// Offset of the canonical VK hash in a receipt account
+pub const RECEIPT_VK_HASH_OFFSET: usize = 184;
+
+/// Offset of the rent beneficiary in a receipt account
+pub const RECEIPT_RENT_BENEFICIARY_OFFSET: usize = 216;
 
 /// Receipt PDA seed
-pub const RECEIPT_SEED: &[u8] = b"receipt";
+pub use solana_noir_receipt_seeds::RECEIPT_SEED;
+
+// =============================================================================
+// VK registry program
+// =============================================================================
+
+/// Name record PDA seed (`["name", name]`)
+pub const REGISTRY_NAME_SEED: &[u8] = b"name";
+
+/// Registry entry PDA seed (`["entry", name, version]`)
+pub const REGISTRY_ENTRY_SEED: &[u8] = b"entry";
+
+/// Longest name or version the registry program accepts
+pub const REGISTRY_MAX_LABEL_LEN: usize = 32;
+
+/// Name record size (authority + name + name_len + bump, padded)
+pub const REGISTRY_NAME_RECORD_SIZE: usize = 72;
+
+/// Registry entry size (VK account, VK hash, registrant, slots, labels)
+pub const REGISTRY_ENTRY_SIZE: usize = 192;
+
+pub const IX_REGISTRY_REGISTER: u8 = 0;
+pub const IX_REGISTRY_DEPRECATE: u8 = 1;
+pub const IX_REGISTRY_TRANSFER_AUTHORITY: u8 = 2;
 
 // =============================================================================
 // Instruction codes
@@ -180,14 +1092,78 @@ pub const IX_UPLOAD_CHUNK: u8 = 1;
 pub const IX_SET_PUBLIC_INPUTS: u8 = 3;
 pub const IX_INIT_VK_BUFFER: u8 = 4;
 pub const IX_UPLOAD_VK_CHUNK: u8 = 5;
+pub const IX_COMPILE_VK: u8 = 6;
+pub const IX_RESET_BUFFER: u8 = 7;
+pub const IX_INIT_MANIFEST: u8 = 8;
+pub const IX_UPLOAD_SHARD_CHUNK: u8 = 9;
+pub const IX_UPLOAD_DATA: u8 = 14;
+pub const IX_FINAL_CHECK: u8 = 13;
 pub const IX_PHASE1_FULL: u8 = 30;
 pub const IX_PHASE2_ROUNDS: u8 = 40;
+pub const IX_PHASE2C_RELATION_COEFFICIENTS: u8 = 41;
 pub const IX_PHASE2D_RELATIONS: u8 = 43;
+pub const IX_PHASE2D_RELATIONS_PART1: u8 = 44;
+pub const IX_PHASE2D_RELATIONS_PART2: u8 = 45;
 pub const IX_PHASE3A_WEIGHTS: u8 = 50;
 pub const IX_PHASE3B1_FOLDING: u8 = 51;
 pub const IX_PHASE3B2_GEMINI: u8 = 52;
+pub const IX_PHASE3C_MSM: u8 = 53;
 pub const IX_PHASE3C_AND_PAIRING: u8 = 54;
 pub const IX_PHASE2D_AND_3A: u8 = 55;
 pub const IX_PHASE3B_COMBINED: u8 = 56;
 pub const IX_CREATE_RECEIPT: u8 = 60;
+pub const IX_CLOSE_RECEIPT: u8 = 61;
+pub const IX_REVERIFY_BY_DIGEST: u8 = 62;
 pub const IX_CLOSE_ACCOUNTS: u8 = 70;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_data(raw_phase: u8, sub_phases: [u8; 2], rounds: u8, shplemini: u8) -> Vec<u8> {
+        let mut data = vec![0u8; STATE_SIZE];
+        data[0] = raw_phase;
+        data[1] = sub_phases[0];
+        data[2] = sub_phases[1];
+        data[3] = 17;
+        data[STATE_ROUNDS_COMPLETED_OFFSET] = rounds;
+        data[STATE_SHPLEMINI_SUB_PHASE_OFFSET] = shplemini;
+        data
+    }
+
+    fn done(state: &VerificationState) -> Vec<u8> {
+        state.progress().iter().map(|stage| stage.done).collect()
+    }
+
+    #[test]
+    fn test_state_progress() {
+        // Mid-sumcheck: Phase 1 is over even though its marker stopped at 4
+        let state = VerificationState::from_account_data(&state_data(3, [4, 1], 9, 0)).unwrap();
+        assert_eq!(state.phase, VerificationPhase::NotStarted);
+        assert_eq!(done(&state), [6, 9, 0, 0, 0]);
+        assert_eq!(state.progress()[1].total, 17);
+        assert!(!state.is_finished());
+
+        let state = VerificationState::from_account_data(&state_data(5, [6, 4], 17, 2)).unwrap();
+        assert_eq!(done(&state), [6, 17, 2, 2, 0]);
+
+        let mut data = state_data(7, [6, 4], 17, 4);
+        data[STATE_VERIFIED_OFFSET] = 1;
+        let state = VerificationState::from_account_data(&data).unwrap();
+        assert!(state.verified && state.is_finished());
+        assert!(state.progress().iter().all(StageProgress::is_complete));
+
+        // A failure keeps the markers of the step that failed
+        let state = VerificationState::from_account_data(&state_data(255, [6, 5], 17, 0)).unwrap();
+        assert_eq!(state.phase, VerificationPhase::Failed);
+        assert_eq!(done(&state), [6, 17, 1, 0, 0]);
+        assert!(state.is_finished());
+
+        let fresh = VerificationState::from_account_data(&[0u8; STATE_SIZE]).unwrap();
+        assert!(!fresh.progress().iter().any(StageProgress::is_complete));
+
+        assert!(VerificationState::from_account_data(&[1, 2, 3]).is_none());
+        let short = VerificationState::from_account_data(&[1, 2, 0, 17]).unwrap();
+        assert_eq!(done(&short), [2, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,264 @@
+//! HTTP routes
+//!
+//! | Route | |
+//! |-------|---|
+//! | `POST /verify` | Queue a verification, returns `202 {"job_id": ..}` |
+//! | `GET /jobs/<id>` | Job status, and the receipt once verified |
+//! | `GET /health` | Liveness |
+//!
+//! Errors are `{"error": ".."}` with a 4xx or 5xx status.
+
+use crate::jobs::{JobStatus, JobStore, Task};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_noir_verifier_sdk::{SolanaNoirVerifier, VerifierError, VkRegistry, PROOF_SIZE};
+use solana_sdk::pubkey::Pubkey;
+use std::io::Read;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+/// Largest accepted request body; a hex proof is about 32 KiB
+const MAX_BODY_SIZE: usize = 256 * 1024;
+
+/// Body of `POST /verify`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyRequest {
+    /// `name@version` in the configured registry
+    circuit: String,
+    /// Hex-encoded proof
+    proof: String,
+    /// Hex-encoded public inputs, 32 bytes each
+    public_inputs: String,
+}
+
+/// Where circuit names are looked up
+pub enum Circuits {
+    /// A registry file, as written by `noir-solana registry publish`
+    File(VkRegistry),
+    /// The registry program set in the verifier config
+    OnChain,
+}
+
+pub struct Api {
+    pub verifier: Arc<SolanaNoirVerifier>,
+    pub circuits: Circuits,
+    pub store: Arc<JobStore>,
+    pub tasks: Sender<Task>,
+    /// Required as `Authorization: Bearer <key>` when set
+    pub api_key: Option<String>,
+}
+
+impl Api {
+    /// Answer one request
+    pub fn handle(&self, mut request: tiny_http::Request) {
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| h.value.to_string());
+        let mut body = Vec::new();
+        let read = request
+            .as_reader()
+            .take(MAX_BODY_SIZE as u64 + 1)
+            .read_to_end(&mut body);
+
+        let (status, response) = match read {
+            Err(e) => (400, error(format!("failed to read body: {}", e))),
+            Ok(_) if body.len() > MAX_BODY_SIZE => (413, error("body too large")),
+            Ok(_) => self.route(
+                request.method().as_str(),
+                request.url(),
+                authorization.as_deref(),
+                &body,
+            ),
+        };
+
+        let content_type =
+            tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = tiny_http::Response::from_string(response.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        if let Err(e) = request.respond(response) {
+            log::debug!("Failed to answer request: {}", e);
+        }
+    }
+
+    /// Status code and JSON body for a request
+    fn route(
+        &self,
+        method: &str,
+        url: &str,
+        authorization: Option<&str>,
+        body: &[u8],
+    ) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or_default();
+        if path == "/health" {
+            return (200, json!({ "ok": true }));
+        }
+        if let Some(key) = &self.api_key {
+            if authorization.and_then(|h| h.strip_prefix("Bearer ")) != Some(key.as_str()) {
+                return (401, error("missing or wrong API key"));
+            }
+        }
+
+        match (method, path) {
+            ("POST", "/verify") => self.verify(body),
+            ("GET", path) if path.starts_with("/jobs/") => {
+                let job = path["/jobs/".len()..]
+                    .parse()
+                    .ok()
+                    .and_then(|id| self.store.get(id));
+                match job {
+                    Some(job) => (200, job.to_json()),
+                    None => (404, error("no such job")),
+                }
+            }
+            (_, "/verify") => (405, error("use POST")),
+            _ => (404, error("not found")),
+        }
+    }
+
+    fn verify(&self, body: &[u8]) -> (u16, Value) {
+        let request: VerifyRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return (400, error(format!("invalid request: {}", e))),
+        };
+        let (proof, public_inputs) = match parse_inputs(&request) {
+            Ok(inputs) => inputs,
+            Err(e) => return (400, error(e)),
+        };
+        let vk_account = match self.resolve(&request.circuit) {
+            Ok(vk_account) => vk_account,
+            Err(e @ VerifierError::Registry(_)) => return (404, error(e.to_string())),
+            Err(e) => return (502, error(e.to_string())),
+        };
+
+        let Some(id) = self
+            .store
+            .submit(&request.circuit, vk_account, &public_inputs)
+        else {
+            return (503, error("too many queued jobs, retry later"));
+        };
+        let task = Task {
+            id,
+            vk_account,
+            proof,
+            public_inputs,
+        };
+        if self.tasks.send(task).is_err() {
+            self.store
+                .set_status(id, JobStatus::Failed("no workers running".into()));
+            return (503, error("no workers running"));
+        }
+        log::info!("Job {}: queued for {}", id, request.circuit);
+        (202, json!({ "job_id": id.to_string() }))
+    }
+
+    fn resolve(&self, circuit: &str) -> Result<Pubkey, VerifierError> {
+        match &self.circuits {
+            Circuits::File(registry) => self.verifier.resolve_vk(registry, circuit),
+            Circuits::OnChain => self.verifier.resolve_vk_on_chain(circuit),
+        }
+    }
+}
+
+/// Decoded proof and public inputs of a request
+fn parse_inputs(request: &VerifyRequest) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let decode = |field: &str, text: &str| {
+        hex::decode(text.trim_start_matches("0x")).map_err(|e| format!("{}: {}", field, e))
+    };
+    let proof = decode("proof", &request.proof)?;
+    let public_inputs = decode("public_inputs", &request.public_inputs)?;
+    if proof.len() != PROOF_SIZE {
+        return Err(format!(
+            "proof: expected {} bytes (a ZK proof), got {}",
+            PROOF_SIZE,
+            proof.len()
+        ));
+    }
+    if public_inputs.len() % 32 != 0 {
+        return Err(format!(
+            "public_inputs: {} bytes is not a multiple of 32",
+            public_inputs.len()
+        ));
+    }
+    Ok((proof, public_inputs))
+}
+
+fn error(message: impl Into<String>) -> Value {
+    json!({ "error": message.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_client::RpcClient;
+    use solana_noir_verifier_sdk::VerifierConfig;
+    use std::sync::mpsc;
+
+    fn api(api_key: Option<&str>) -> Api {
+        // Only routes that make no RPC call are exercised
+        let rpc = Arc::new(RpcClient::new("http://127.0.0.1:1".to_string()));
+        Api {
+            verifier: Arc::new(SolanaNoirVerifier::new(
+                rpc,
+                VerifierConfig::new(Pubkey::new_unique()),
+            )),
+            circuits: Circuits::OnChain,
+            store: Arc::new(JobStore::new(4, 4)),
+            tasks: mpsc::channel().0,
+            api_key: api_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_routes() {
+        let api = api(None);
+        let id = api
+            .store
+            .submit("square@1", Pubkey::new_unique(), &[0; 32])
+            .unwrap();
+
+        let (status, body) = api.route("GET", &format!("/jobs/{}", id), None, b"");
+        assert_eq!((status, body["status"].as_str()), (200, Some("queued")));
+        assert_eq!(api.route("GET", "/jobs/999", None, b"").0, 404);
+        assert_eq!(api.route("GET", "/jobs/abc", None, b"").0, 404);
+        assert_eq!(api.route("GET", "/verify", None, b"").0, 405);
+        assert_eq!(api.route("GET", "/health?x=1", None, b"").0, 200);
+        assert_eq!(api.route("POST", "/verify", None, b"{").0, 400);
+    }
+
+    #[test]
+    fn test_api_key() {
+        let api = api(Some("secret"));
+        assert_eq!(api.route("GET", "/jobs/1", None, b"").0, 401);
+        assert_eq!(
+            api.route("GET", "/jobs/1", Some("Bearer wrong"), b"").0,
+            401
+        );
+        assert_eq!(
+            api.route("GET", "/jobs/1", Some("Bearer secret"), b"").0,
+            404
+        );
+        // Health checks stay open for load balancers
+        assert_eq!(api.route("GET", "/health", None, b"").0, 200);
+    }
+
+    #[test]
+    fn test_parse_inputs() {
+        let request = |proof: String, public_inputs: &str| VerifyRequest {
+            circuit: "square@1".into(),
+            proof,
+            public_inputs: public_inputs.into(),
+        };
+        let proof = "00".repeat(PROOF_SIZE);
+
+        let (decoded, pis) =
+            parse_inputs(&request(format!("0x{}", proof), &"01".repeat(64))).unwrap();
+        assert_eq!((decoded.len(), pis.len()), (PROOF_SIZE, 64));
+        assert!(parse_inputs(&request(proof.clone(), &"01".repeat(33))).is_err());
+        assert!(parse_inputs(&request(proof.clone(), "zz")).is_err());
+        assert!(parse_inputs(&request("00".repeat(100), "")).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Verification jobs: in-memory store and worker threads
+
+use serde_json::{json, Value};
+use solana_noir_verifier_sdk::{ReceiptInfo, SolanaNoirVerifier, VerifiedReceipt};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub type JobId = u64;
+
+/// Where a job is
+#[derive(Debug, Clone)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    /// Verified by this job
+    Verified(Box<VerifiedReceipt>),
+    /// A receipt for the same VK and public inputs already existed
+    AlreadyVerified(ReceiptInfo),
+    Failed(String),
+}
+
+impl JobStatus {
+    fn is_finished(&self) -> bool {
+        !matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+/// One `POST /verify`
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: JobId,
+    pub circuit: String,
+    pub vk_account: Pubkey,
+    pub public_inputs: Vec<u8>,
+    /// Unix timestamp of submission
+    pub created_at: u64,
+    pub status: JobStatus,
+}
+
+impl Job {
+    /// Response body of `GET /jobs/<id>`
+    pub fn to_json(&self) -> Value {
+        let mut body = json!({
+            "id": self.id.to_string(),
+            "circuit": self.circuit,
+            "vk_account": self.vk_account.to_string(),
+            "public_inputs": hex::encode(&self.public_inputs),
+            "created_at": self.created_at,
+        });
+        let fields = match &self.status {
+            JobStatus::Queued => json!({ "status": "queued" }),
+            JobStatus::Running => json!({ "status": "running" }),
+            JobStatus::Verified(result) => json!({
+                "status": "verified",
+                "receipt": result.receipt.receipt_pda.to_string(),
+                "verified_slot": result.receipt.verified_slot,
+                "state_account": result.state_account.to_string(),
+                "proof_account": result.proof_account.to_string(),
+                "total_cus": result.total_cus,
+                "signatures": result.signatures.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            }),
+            JobStatus::AlreadyVerified(receipt) => json!({
+                "status": "verified",
+                "receipt": receipt.receipt_pda.to_string(),
+                "verified_slot": receipt.verified_slot,
+                "existing_receipt": true,
+            }),
+            JobStatus::Failed(error) => json!({ "status": "failed", "error": error }),
+        };
+        body.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        body
+    }
+}
+
+/// Work handed to a worker thread
+pub struct Task {
+    pub id: JobId,
+    pub vk_account: Pubkey,
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+}
+
+/// Jobs by id
+///
+/// Finished jobs beyond `history` are dropped, oldest first; ids are never
+/// reused.
+pub struct JobStore {
+    jobs: Mutex<BTreeMap<JobId, Job>>,
+    next_id: AtomicU64,
+    max_queued: usize,
+    history: usize,
+}
+
+impl JobStore {
+    pub fn new(max_queued: usize, history: usize) -> Self {
+        Self {
+            jobs: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+            max_queued,
+            history,
+        }
+    }
+
+    /// Record a queued job, or `None` when `max_queued` jobs are waiting
+    pub fn submit(&self, circuit: &str, vk_account: Pubkey, public_inputs: &[u8]) -> Option<JobId> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let queued = jobs
+            .values()
+            .filter(|job| matches!(job.status, JobStatus::Queued))
+            .count();
+        if queued >= self.max_queued {
+            return None;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        jobs.insert(
+            id,
+            Job {
+                id,
+                circuit: circuit.to_string(),
+                vk_account,
+                public_inputs: public_inputs.to_vec(),
+                created_at,
+                status: JobStatus::Queued,
+            },
+        );
+        Some(id)
+    }
+
+    pub fn get(&self, id: JobId) -> Option<Job> {
+        self.jobs.lock().unwrap().get(&id).cloned()
+    }
+
+    pub fn set_status(&self, id: JobId, status: JobStatus) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.get_mut(&id) {
+            job.status = status;
+        }
+
+        let finished: Vec<JobId> = jobs
+            .values()
+            .filter(|job| job.status.is_finished())
+            .map(|job| job.id)
+            .collect();
+        for id in finished
+            .iter()
+            .take(finished.len().saturating_sub(self.history))
+        {
+            jobs.remove(id);
+        }
+    }
+}
+
+/// Run tasks from `tasks` until the queue closes
+pub fn run_worker(
+    verifier: Arc<SolanaNoirVerifier>,
+    payer: Arc<Keypair>,
+    store: Arc<JobStore>,
+    tasks: Arc<Mutex<Receiver<Task>>>,
+) {
+    loop {
+        // Hold the lock only while waiting, so workers take turns
+        let Ok(task) = tasks.lock().unwrap().recv() else {
+            return;
+        };
+        store.set_status(task.id, JobStatus::Running);
+        log::info!("Job {}: verifying against {}", task.id, task.vk_account);
+
+        let status = match verifier.get_receipt(&task.vk_account, &task.public_inputs) {
+            Ok(Some(receipt)) => JobStatus::AlreadyVerified(receipt),
+            _ => match verifier.verify_phased(
+                payer.as_ref(),
+                None,
+                &task.proof,
+                &task.public_inputs,
+                &task.vk_account,
+            ) {
+                Ok(result) => JobStatus::Verified(Box::new(result)),
+                Err(e) => JobStatus::Failed(e.to_string()),
+            },
+        };
+        match &status {
+            JobStatus::Failed(error) => log::warn!("Job {}: {}", task.id, error),
+            _ => log::info!("Job {}: verified", task.id),
+        }
+        store.set_status(task.id, status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_limit_and_history() {
+        let store = JobStore::new(2, 1);
+        let vk = Pubkey::new_unique();
+        let first = store.submit("square@1", vk, &[1; 32]).unwrap();
+        let second = store.submit("square@1", vk, &[2; 32]).unwrap();
+        assert_eq!(store.submit("square@1", vk, &[3; 32]), None);
+
+        // Running jobs no longer count against the queue
+        store.set_status(first, JobStatus::Running);
+        let third = store.submit("square@1", vk, &[3; 32]).unwrap();
+        assert!(first < second && second < third);
+
+        // Only the newest finished job is kept
+        store.set_status(first, JobStatus::Failed("rejected".into()));
+        store.set_status(second, JobStatus::Failed("rejected".into()));
+        assert!(store.get(first).is_none());
+        assert!(matches!(
+            store.get(second).unwrap().status,
+            JobStatus::Failed(_)
+        ));
+        assert!(matches!(
+            store.get(third).unwrap().status,
+            JobStatus::Queued
+        ));
+    }
+
+    #[test]
+    fn test_job_json() {
+        let store = JobStore::new(1, 1);
+        let vk = Pubkey::new_unique();
+        let id = store.submit("square@1", vk, &[0xab; 32]).unwrap();
+        let body = store.get(id).unwrap().to_json();
+        assert_eq!(body["id"], id.to_string());
+        assert_eq!(body["status"], "queued");
+        assert_eq!(body["vk_account"], vk.to_string());
+        assert_eq!(body["public_inputs"], "ab".repeat(32));
+
+        store.set_status(id, JobStatus::Failed("Verification failed".into()));
+        let body = store.get(id).unwrap().to_json();
+        assert_eq!(body["status"], "failed");
+        assert_eq!(body["error"], "Verification failed");
+    }
+}
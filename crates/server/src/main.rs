@@ -0,0 +1,141 @@
+//! noir-verify-server: verify Noir proofs on Solana over HTTP
+//!
+//! Backends without Rust post a proof, its public inputs and a circuit
+//! name; the server looks the circuit up in a VK registry, runs the phased
+//! verification with its own keypair and reports the receipt address.
+//!
+//! ```bash
+//! noir-verify-server --program-id <ID> --keypair payer.json --registry vk-registry.txt
+//! curl -X POST localhost:8080/verify \
+//!     -d '{"circuit": "square@1.0.0", "proof": "<hex>", "public_inputs": "<hex>"}'
+//! curl localhost:8080/jobs/1
+//! ```
+
+mod api;
+mod jobs;
+
+use api::{Api, Circuits};
+use clap::Parser;
+use jobs::JobStore;
+use solana_client::rpc_client::RpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_noir_verifier_sdk::{FeeConfig, SolanaNoirVerifier, VerifierConfig, VkRegistry};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signer},
+};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+#[derive(Parser)]
+#[command(name = "noir-verify-server")]
+#[command(about = "Verify Noir proofs on Solana over HTTP")]
+#[command(version)]
+#[command(group = clap::ArgGroup::new("circuits").required(true))]
+struct Args {
+    /// RPC endpoint
+    #[arg(long, env = "SOLANA_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    rpc_url: String,
+
+    /// Verifier program ID
+    #[arg(long, env = "VERIFIER_PROGRAM_ID")]
+    program_id: Pubkey,
+
+    /// Keypair paying for every verification
+    #[arg(long, env = "KEYPAIR_PATH")]
+    keypair: PathBuf,
+
+    /// Registry file mapping circuit names to VK accounts
+    #[arg(long, group = "circuits")]
+    registry: Option<PathBuf>,
+
+    /// Look circuit names up in this registry program instead
+    #[arg(long, env = "NOIR_SOLANA_REGISTRY_PROGRAM", group = "circuits")]
+    registry_program: Option<Pubkey>,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Bearer token required on every route but /health
+    #[arg(long, env = "NOIR_VERIFY_API_KEY")]
+    api_key: Option<String>,
+
+    /// Verifications run at once
+    #[arg(long, default_value_t = 2)]
+    workers: usize,
+
+    /// Jobs waiting for a worker before new ones are refused
+    #[arg(long, default_value_t = 100)]
+    max_queued: usize,
+
+    /// Finished jobs kept for status queries
+    #[arg(long, default_value_t = 10_000)]
+    history: usize,
+
+    /// Priority fee in micro-lamports per CU
+    #[arg(long)]
+    priority_fee: Option<u64>,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let payer = read_keypair_file(&args.keypair)
+        .map_err(|e| anyhow::anyhow!("Failed to read keypair from {:?}: {}", args.keypair, e))?;
+    let rpc = Arc::new(RpcClient::new_with_commitment(
+        args.rpc_url.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let mut fees = FeeConfig::default().with_estimated_cu_limits();
+    if let Some(price) = args.priority_fee {
+        fees = fees.with_priority_fee(price);
+    }
+    let mut config = VerifierConfig::new(args.program_id).with_fees(fees);
+    let circuits = match (&args.registry, args.registry_program) {
+        (Some(path), _) => Circuits::File(VkRegistry::open(path)?),
+        (None, Some(registry_program)) => {
+            config = config.with_registry_program(registry_program);
+            Circuits::OnChain
+        }
+        (None, None) => unreachable!("clap requires one of them"),
+    };
+    let verifier = Arc::new(SolanaNoirVerifier::new(rpc, config));
+
+    let store = Arc::new(JobStore::new(args.max_queued, args.history));
+    let (tasks, queue) = mpsc::channel();
+    let queue = Arc::new(Mutex::new(queue));
+    let payer = Arc::new(payer);
+    for _ in 0..args.workers.max(1) {
+        let (verifier, payer, store, queue) = (
+            verifier.clone(),
+            payer.clone(),
+            store.clone(),
+            queue.clone(),
+        );
+        thread::spawn(move || jobs::run_worker(verifier, payer, store, queue));
+    }
+
+    let server = tiny_http::Server::http(args.listen)
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", args.listen, e))?;
+    log::info!(
+        "Listening on http://{} with payer {}",
+        args.listen,
+        payer.pubkey()
+    );
+    let api = Api {
+        verifier,
+        circuits,
+        store,
+        tasks,
+        api_key: args.api_key,
+    };
+    for request in server.incoming_requests() {
+        api.handle(request);
+    }
+    Ok(())
+}
@@ -28,13 +28,47 @@
 
 #![no_std]
 
-use solana_program::{account_info::AccountInfo, keccak, pubkey::Pubkey};
+extern crate alloc;
 
-/// Size of the receipt account data (16 bytes)
-pub const RECEIPT_SIZE: usize = 16;
+pub mod registry;
+pub mod require;
 
-// Internal: PDA seed prefix
-const RECEIPT_SEED: &[u8] = b"receipt";
+#[cfg(feature = "macros")]
+pub use solana_noir_verifier_macros::requires_proof;
+
+/// Paths used by `#[requires_proof]` expansions
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    pub use solana_program::{account_info::AccountInfo, program_error::ProgramError};
+}
+
+use alloc::vec::Vec;
+use core::cell::Ref;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Size of the receipt account data (248 bytes)
+pub const RECEIPT_SIZE: usize = 248;
+
+pub use solana_noir_receipt_seeds::{
+    derive_receipt_pda, pi_hash, proof_digest, VerificationResult,
+};
+
+/// Verifier instruction byte for `CreateReceipt`
+pub const IX_CREATE_RECEIPT: u8 = 60;
+
+/// Verifier instruction byte for `ReverifyByDigest`
+pub const IX_REVERIFY_BY_DIGEST: u8 = 62;
+
+// Internal: system program (11111111111111111111111111111111)
+pub(crate) const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0u8; 32]);
 
 /// Check if a proof was verified
 ///
@@ -43,6 +77,9 @@ const RECEIPT_SEED: &[u8] = b"receipt";
 /// 2. The receipt is owned by the verifier program
 /// 3. The receipt has valid data
 ///
+/// Use [`Receipt::try_from_account_info`] instead to find out why a
+/// receipt was rejected or to read the verification slot.
+///
 /// # Arguments
 /// * `receipt` - The receipt account (user provides this)
 /// * `vk_account` - Your circuit's VK account pubkey
@@ -57,52 +94,527 @@ pub fn is_verified(
     public_inputs: &[u8],
     verifier_program: &Pubkey,
 ) -> bool {
-    // Hash public inputs
-    let pi_hash = keccak::hash(public_inputs).to_bytes();
+    let pi_hash = pi_hash(public_inputs);
+    is_verified_with_hash(receipt, vk_account, &pi_hash, verifier_program)
+}
+
+/// Check if a proof was verified, given the public input hash
+///
+/// Same checks as [`is_verified`], for callers that hashed the public
+/// inputs themselves, e.g. with [`PiHasher`] when the inputs are spread
+/// across several accounts or instruction fields.
+pub fn is_verified_with_hash(
+    receipt: &AccountInfo,
+    vk_account: &Pubkey,
+    pi_hash: &[u8; 32],
+    verifier_program: &Pubkey,
+) -> bool {
+    Receipt::try_from_account_info(receipt, vk_account, verifier_program)
+        .is_ok_and(|r| r.pi_hash() == pi_hash)
+}
+
+/// Check if a proof was verified against a circuit, by VK hash
+///
+/// Like [`is_verified`], but pins the circuit rather than the account that
+/// holds its VK: any VK account holding the VK with this hash (see
+/// [`Receipt::vk_hash`]) is accepted. Use it when the same circuit may be
+/// deployed to several VK accounts, e.g. after a redeploy. The hash covers
+/// the VK account's SRS point, so a VK uploaded with another x·G2 point
+/// does not match.
+pub fn is_verified_by_vk_hash(
+    receipt: &AccountInfo,
+    vk_hash: &[u8; 32],
+    public_inputs: &[u8],
+    verifier_program: &Pubkey,
+) -> bool {
+    let pi_hash = pi_hash(public_inputs);
+    Receipt::try_from_account_info_by_vk_hash(receipt, vk_hash, verifier_program)
+        .is_ok_and(|r| r.pi_hash() == &pi_hash)
+}
 
-    // Derive expected PDA
-    let (expected_pda, _) = Pubkey::find_program_address(
-        &[RECEIPT_SEED, vk_account.as_ref(), &pi_hash],
+/// Accounts for the verifier's `CreateReceipt` instruction
+pub struct CreateReceiptAccounts<'a, 'info> {
+    /// Verification state account of a completed verification (writable,
+    /// marked as recorded)
+    pub state_account: &'a AccountInfo<'info>,
+    /// Proof buffer holding the verified public inputs
+    pub proof_account: &'a AccountInfo<'info>,
+    /// VK account the proof was verified against
+    pub vk_account: &'a AccountInfo<'info>,
+    /// Receipt PDA to create (writable)
+    pub receipt: &'a AccountInfo<'info>,
+    /// Pays the receipt rent (writable, signer); may be a sponsor rather
+    /// than the prover
+    pub payer: &'a AccountInfo<'info>,
+    /// System program
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// What `CreateReceipt` does when the receipt already exists
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateReceiptPolicy {
+    /// Fail with the verifier's `ReceiptAlreadyExists` (108)
+    #[default]
+    Error = 0,
+    /// Move [`Receipt::verified_slot`] and [`Receipt::verified_timestamp`]
+    /// to the new verification
+    Refresh = 1,
+    /// Increment [`Receipt::verification_count`]
+    Count = 2,
+}
+
+/// Optional `CreateReceipt` arguments
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReceiptOptions {
+    /// Stored in the receipt, readable through [`Receipt::tag`]
+    pub tag: [u8; 32],
+    /// Behavior when the receipt already exists
+    pub on_duplicate: DuplicateReceiptPolicy,
+    /// Account the rent is refunded to on close, the payer if `None`
+    pub rent_beneficiary: Option<Pubkey>,
+}
+
+impl ReceiptOptions {
+    /// Attach an application-defined tag (a nullifier, an order id, a
+    /// commitment) so the receipt binds the proof to that context
+    pub fn with_tag(mut self, tag: [u8; 32]) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Refresh or count an existing receipt instead of failing
+    ///
+    /// The verifier marks the state account as recorded, so each
+    /// verification creates, refreshes or counts a receipt at most once.
+    pub fn with_on_duplicate(mut self, policy: DuplicateReceiptPolicy) -> Self {
+        self.on_duplicate = policy;
+        self
+    }
+
+    /// Refund the rent to `beneficiary` when the payer closes the receipt
+    ///
+    /// Lets a sponsor pay for receipts on behalf of its users while the
+    /// rent flows back to, e.g., a treasury. Only the payer can still close
+    /// the receipt.
+    pub fn with_rent_beneficiary(mut self, beneficiary: Pubkey) -> Self {
+        self.rent_beneficiary = Some(beneficiary);
+        self
+    }
+
+    /// Instruction data, starting with the instruction byte
+    fn instruction_data(&self) -> Vec<u8> {
+        let mut data = alloc::vec![IX_CREATE_RECEIPT];
+        if *self != Self::default() {
+            data.extend_from_slice(&self.tag);
+        }
+        if self.on_duplicate != DuplicateReceiptPolicy::Error || self.rent_beneficiary.is_some() {
+            data.push(self.on_duplicate as u8);
+        }
+        if let Some(beneficiary) = self.rent_beneficiary {
+            data.extend_from_slice(beneficiary.as_ref());
+        }
+        data
+    }
+}
+
+/// Build a `CreateReceipt` instruction
+pub fn create_receipt_instruction(
+    verifier_program: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    vk_account: &Pubkey,
+    receipt: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    create_receipt_instruction_with_options(
         verifier_program,
+        state_account,
+        proof_account,
+        vk_account,
+        receipt,
+        payer,
+        &ReceiptOptions::default(),
+    )
+}
+
+/// Build a `CreateReceipt` instruction with [`ReceiptOptions`]
+pub fn create_receipt_instruction_with_options(
+    verifier_program: &Pubkey,
+    state_account: &Pubkey,
+    proof_account: &Pubkey,
+    vk_account: &Pubkey,
+    receipt: &Pubkey,
+    payer: &Pubkey,
+    options: &ReceiptOptions,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *verifier_program,
+        &options.instruction_data(),
+        alloc::vec![
+            AccountMeta::new(*state_account, false),
+            AccountMeta::new_readonly(*proof_account, false),
+            AccountMeta::new_readonly(*vk_account, false),
+            AccountMeta::new(*receipt, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    )
+}
+
+/// Create a verification receipt from an integrator program
+///
+/// Invokes the verifier's `CreateReceipt` once the verification in
+/// `state_account` has completed, so a program that drives verification
+/// itself can mint the receipt in the same transaction. The verifier signs
+/// for the receipt PDA; `signer_seeds` are only needed when `payer` is a
+/// PDA of the calling program (pass `&[]` if the payer signed the
+/// transaction).
+pub fn create_receipt_cpi<'info>(
+    verifier_program: &AccountInfo<'info>,
+    accounts: CreateReceiptAccounts<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    create_receipt_cpi_with_options(
+        verifier_program,
+        accounts,
+        &ReceiptOptions::default(),
+        signer_seeds,
+    )
+}
+
+/// [`create_receipt_cpi`] with [`ReceiptOptions`], e.g. to tag the receipt
+/// or to refresh or count an existing one
+pub fn create_receipt_cpi_with_options<'info>(
+    verifier_program: &AccountInfo<'info>,
+    accounts: CreateReceiptAccounts<'_, 'info>,
+    options: &ReceiptOptions,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let ix = create_receipt_instruction_with_options(
+        verifier_program.key,
+        accounts.state_account.key,
+        accounts.proof_account.key,
+        accounts.vk_account.key,
+        accounts.receipt.key,
+        accounts.payer.key,
+        options,
     );
 
-    // Validate receipt
-    receipt.key == &expected_pda
-        && receipt.owner == verifier_program
-        && receipt.data_len() >= RECEIPT_SIZE
+    invoke_signed(
+        &ix,
+        &[
+            accounts.state_account.clone(),
+            accounts.proof_account.clone(),
+            accounts.vk_account.clone(),
+            accounts.receipt.clone(),
+            accounts.payer.clone(),
+            accounts.system_program.clone(),
+            verifier_program.clone(),
+        ],
+        signer_seeds,
+    )
 }
 
-/// Read the verification slot from a receipt
+/// Build a `ReverifyByDigest` instruction
+pub fn reverify_by_digest_instruction(
+    verifier_program: &Pubkey,
+    receipt: &Pubkey,
+    proof_digest: &[u8; 32],
+) -> Instruction {
+    let mut data = alloc::vec![IX_REVERIFY_BY_DIGEST];
+    data.extend_from_slice(proof_digest);
+    Instruction::new_with_bytes(
+        *verifier_program,
+        &data,
+        alloc::vec![AccountMeta::new_readonly(*receipt, false)],
+    )
+}
+
+/// Confirm that a receipt was created from the proof with `proof_digest`
 ///
-/// Call this after `is_verified` returns true to get when the proof was verified.
+/// The verifier fails with `ProofDigestMismatch` (304) unless the digest
+/// matches [`Receipt::proof_digest`]. Reading the receipt with
+/// [`Receipt::try_from_account_info`] and comparing the digest directly is
+/// cheaper; this is for callers that want the verifier itself to attest
+/// the match, e.g. in an instruction other programs can introspect.
+pub fn reverify_by_digest_cpi<'info>(
+    verifier_program: &AccountInfo<'info>,
+    receipt: &AccountInfo<'info>,
+    proof_digest: &[u8; 32],
+) -> ProgramResult {
+    let ix = reverify_by_digest_instruction(verifier_program.key, receipt.key, proof_digest);
+    invoke(&ix, &[receipt.clone(), verifier_program.clone()])
+}
+
+/// The [`VerificationResult`] the verifier returned from the last
+/// instruction this program invoked
 ///
-/// # Returns
-/// The slot number when the proof was verified, or None if invalid
-pub fn get_verified_slot(receipt: &AccountInfo) -> Option<u64> {
-    let data = receipt.try_borrow_data().ok()?;
-    if data.len() < 8 {
+/// Read it right after [`create_receipt_cpi`] or [`reverify_by_digest_cpi`];
+/// `None` if the last invoked program was not `verifier_program` or
+/// returned something else.
+pub fn verification_result(verifier_program: &Pubkey) -> Option<VerificationResult> {
+    let (program, data) = get_return_data()?;
+    if program != *verifier_program {
         return None;
     }
-    Some(u64::from_le_bytes(data[0..8].try_into().ok()?))
+    VerificationResult::from_bytes(&data)
 }
 
-/// Read the verification timestamp from a receipt
+/// Incremental public input hasher
 ///
-/// Call this after `is_verified` returns true to get when the proof was verified.
+/// Produces the same hash as the verifier program (and
+/// `plonk_solana_core::receipt::PiHasher`) without first copying the public
+/// inputs into one buffer. Slices are borrowed and hashed together with a
+/// single hash call on [`PiHasher::finalize`].
 ///
-/// # Returns
-/// The Unix timestamp when the proof was verified, or None if invalid
-pub fn get_verified_timestamp(receipt: &AccountInfo) -> Option<i64> {
-    let data = receipt.try_borrow_data().ok()?;
-    if data.len() < 16 {
-        return None;
+/// ```ignore
+/// let mut hasher = PiHasher::new();
+/// hasher.update(&root).update(&nullifier);
+/// if !is_verified_with_hash(receipt, &MY_VK, &hasher.finalize(), &VERIFIER) {
+///     return Err(ProgramError::Custom(1));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PiHasher<'a> {
+    parts: Vec<&'a [u8]>,
+}
+
+impl<'a> PiHasher<'a> {
+    /// Create an empty hasher
+    pub fn new() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    /// Append raw public input bytes
+    pub fn update(&mut self, data: &'a [u8]) -> &mut Self {
+        self.parts.push(data);
+        self
+    }
+
+    /// Keccak256 of everything appended so far
+    pub fn finalize(&self) -> [u8; 32] {
+        solana_noir_receipt_seeds::pi_hashv(&self.parts)
+    }
+}
+
+/// Why a receipt account was rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiptError {
+    /// Account is not owned by the verifier program
+    WrongOwner,
+    /// Account address is not the receipt PDA for the given VK account
+    WrongPda,
+    /// Account data is smaller than [`RECEIPT_SIZE`]
+    TooSmall,
+    /// Account data is already mutably borrowed
+    BorrowFailed,
+    /// Receipt was created for a VK with a different hash
+    VkHashMismatch,
+}
+
+impl core::fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::WrongOwner => "receipt not owned by the verifier program",
+            Self::WrongPda => "receipt address does not match its seeds",
+            Self::TooSmall => "receipt account data too small",
+            Self::BorrowFailed => "receipt account data already borrowed",
+            Self::VkHashMismatch => "receipt VK hash does not match",
+        })
+    }
+}
+
+impl From<ReceiptError> for ProgramError {
+    fn from(e: ReceiptError) -> Self {
+        match e {
+            ReceiptError::WrongOwner => ProgramError::IllegalOwner,
+            ReceiptError::WrongPda => ProgramError::InvalidSeeds,
+            ReceiptError::TooSmall => ProgramError::AccountDataTooSmall,
+            ReceiptError::BorrowFailed => ProgramError::AccountBorrowFailed,
+            ReceiptError::VkHashMismatch => ProgramError::InvalidAccountData,
+        }
+    }
+}
+
+/// Verification receipt, read in place from the receipt account
+///
+/// Mirrors the verifier program's `VerificationReceipt` layout. Fields are
+/// stored as byte arrays so the struct has alignment 1 and can be cast
+/// directly from account data.
+#[repr(C)]
+pub struct Receipt {
+    verified_slot: [u8; 8],
+    verified_timestamp: [u8; 8],
+    vk_account: [u8; 32],
+    pi_hash: [u8; 32],
+    bump: u8,
+    _reserved: [u8; 3],
+    verification_count: [u8; 4],
+    rent_payer: [u8; 32],
+    tag: [u8; 32],
+    proof_digest: [u8; 32],
+    vk_hash: [u8; 32],
+    rent_beneficiary: [u8; 32],
+}
+
+const _: () = assert!(core::mem::size_of::<Receipt>() == RECEIPT_SIZE);
+
+impl Receipt {
+    /// Borrow and validate a receipt account
+    ///
+    /// Checks, in order, that the account is owned by `verifier_program`,
+    /// is large enough, and sits at the receipt PDA for `vk_account` and the
+    /// public input hash it records. The address is re-derived from the
+    /// stored bump, so this costs one `create_program_address` rather than a
+    /// bump search.
+    ///
+    /// A valid receipt only proves that *some* public inputs were verified
+    /// against `vk_account`; compare [`Receipt::pi_hash`] with your own
+    /// inputs (or use [`is_verified`]) before trusting it.
+    pub fn try_from_account_info<'a>(
+        account: &'a AccountInfo,
+        vk_account: &Pubkey,
+        verifier_program: &Pubkey,
+    ) -> Result<Ref<'a, Receipt>, ReceiptError> {
+        let receipt = Self::borrow(account, verifier_program)?;
+        if receipt.vk_account != vk_account.to_bytes() {
+            return Err(ReceiptError::WrongPda);
+        }
+        receipt.check_address(account.key, verifier_program)?;
+        Ok(receipt)
+    }
+
+    /// Borrow and validate a receipt account for the VK with `vk_hash`
+    ///
+    /// Same owner and size checks as [`Receipt::try_from_account_info`], but
+    /// the address is re-derived from the VK account the receipt itself
+    /// records, and the receipt is then accepted if its [`Receipt::vk_hash`]
+    /// matches. Receipts created before the VK hash was recorded hold zeros
+    /// there and are rejected.
+    pub fn try_from_account_info_by_vk_hash<'a>(
+        account: &'a AccountInfo,
+        vk_hash: &[u8; 32],
+        verifier_program: &Pubkey,
+    ) -> Result<Ref<'a, Receipt>, ReceiptError> {
+        let receipt = Self::borrow(account, verifier_program)?;
+        receipt.check_address(account.key, verifier_program)?;
+        if &receipt.vk_hash != vk_hash {
+            return Err(ReceiptError::VkHashMismatch);
+        }
+        Ok(receipt)
+    }
+
+    /// Owner and size checks, then the in-place cast
+    fn borrow<'a>(
+        account: &'a AccountInfo,
+        verifier_program: &Pubkey,
+    ) -> Result<Ref<'a, Receipt>, ReceiptError> {
+        if account.owner != verifier_program {
+            return Err(ReceiptError::WrongOwner);
+        }
+
+        let data = account
+            .try_borrow_data()
+            .map_err(|_| ReceiptError::BorrowFailed)?;
+        if data.len() < RECEIPT_SIZE {
+            return Err(ReceiptError::TooSmall);
+        }
+        // SAFETY: length checked above and `Receipt` has alignment 1
+        Ok(Ref::map(data, |d| unsafe {
+            &*(d.as_ptr() as *const Receipt)
+        }))
+    }
+
+    /// Check that `key` is the receipt PDA for the stored seeds and bump
+    fn check_address(&self, key: &Pubkey, verifier_program: &Pubkey) -> Result<(), ReceiptError> {
+        let expected = solana_noir_receipt_seeds::create_receipt_address(
+            &self.vk_account(),
+            &self.pi_hash,
+            self.bump,
+            verifier_program,
+        )
+        .map_err(|_| ReceiptError::WrongPda)?;
+        if key != &expected {
+            return Err(ReceiptError::WrongPda);
+        }
+        Ok(())
+    }
+
+    /// Slot when the proof was verified
+    pub fn verified_slot(&self) -> u64 {
+        u64::from_le_bytes(self.verified_slot)
+    }
+
+    /// Unix timestamp when the proof was verified
+    pub fn verified_timestamp(&self) -> i64 {
+        i64::from_le_bytes(self.verified_timestamp)
+    }
+
+    /// VK account the proof was verified against
+    pub fn vk_account(&self) -> Pubkey {
+        Pubkey::new_from_array(self.vk_account)
+    }
+
+    /// Keccak256 of the verified public inputs
+    pub fn pi_hash(&self) -> &[u8; 32] {
+        &self.pi_hash
+    }
+
+    /// Receipt PDA bump
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// Verifications recorded under [`DuplicateReceiptPolicy::Count`],
+    /// starting at 1; 0 for receipts created before counting existed
+    pub fn verification_count(&self) -> u32 {
+        u32::from_le_bytes(self.verification_count)
+    }
+
+    /// Account that paid for the receipt and may close it
+    pub fn rent_payer(&self) -> Pubkey {
+        Pubkey::new_from_array(self.rent_payer)
+    }
+
+    /// Application-defined tag the receipt's creator attached, all zeros
+    /// if none
+    ///
+    /// Anyone who completed the verification can create the receipt, so a
+    /// tag only carries meaning if your program also checks who created it
+    /// (e.g. [`Receipt::rent_payer`]) or the tag is derived from the public
+    /// inputs themselves.
+    pub fn tag(&self) -> &[u8; 32] {
+        &self.tag
+    }
+
+    /// Keccak256 of the verified proof bytes (see [`proof_digest`]), for
+    /// linking proof bytes held off chain to this receipt
+    pub fn proof_digest(&self) -> &[u8; 32] {
+        &self.proof_digest
+    }
+
+    /// Canonical hash of the VK the proof was verified against
+    ///
+    /// The same for every account holding that VK with the same SRS point,
+    /// and whether or not the account was compiled; the SDK's `vk_hash`
+    /// (`vk_hash_with_srs` for a custom SRS) computes it from VK bytes.
+    /// All zeros for receipts created before it was recorded.
+    pub fn vk_hash(&self) -> &[u8; 32] {
+        &self.vk_hash
+    }
+
+    /// Account the rent is refunded to when [`Receipt::rent_payer`] closes
+    /// the receipt
+    pub fn rent_beneficiary(&self) -> Pubkey {
+        Pubkey::new_from_array(self.rent_beneficiary)
     }
-    Some(i64::from_le_bytes(data[8..16].try_into().ok()?))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_program::keccak;
 
     #[test]
     fn test_pda_derivation_is_deterministic() {
@@ -110,13 +622,252 @@ mod tests {
         let public_inputs = [1u8, 2, 3, 4];
         let program = Pubkey::new_unique();
 
-        let pi_hash = keccak::hash(&public_inputs).to_bytes();
-        let (pda1, bump1) =
-            Pubkey::find_program_address(&[RECEIPT_SEED, vk.as_ref(), &pi_hash], &program);
+        let pi_hash = pi_hash(&public_inputs);
+        assert_eq!(pi_hash, keccak::hash(&public_inputs).to_bytes());
+        let (pda1, bump1) = derive_receipt_pda(&vk, &pi_hash, &program);
         let (pda2, bump2) =
-            Pubkey::find_program_address(&[RECEIPT_SEED, vk.as_ref(), &pi_hash], &program);
+            Pubkey::find_program_address(&[b"receipt".as_slice(), vk.as_ref(), &pi_hash], &program);
 
         assert_eq!(pda1, pda2);
         assert_eq!(bump1, bump2);
     }
+
+    #[test]
+    fn test_create_receipt_instruction() {
+        let keys: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let ix =
+            create_receipt_instruction(&keys[0], &keys[1], &keys[2], &keys[3], &keys[4], &keys[5]);
+
+        assert_eq!(ix.program_id, keys[0]);
+        assert_eq!(ix.data, [IX_CREATE_RECEIPT]);
+        assert_eq!(ix.accounts.len(), 6);
+        assert!(ix.accounts[0].is_writable && !ix.accounts[0].is_signer);
+        assert!(ix.accounts[3].is_writable && !ix.accounts[3].is_signer);
+        assert!(ix.accounts[4].is_writable && ix.accounts[4].is_signer);
+        assert_eq!(ix.accounts[5].pubkey, SYSTEM_PROGRAM_ID);
+
+        let tagged = create_receipt_instruction_with_options(
+            &keys[0],
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            &ReceiptOptions::default().with_tag([9; 32]),
+        );
+        assert_eq!(tagged.data[0], IX_CREATE_RECEIPT);
+        assert_eq!(tagged.data[1..], [9; 32]);
+        assert_eq!(tagged.accounts, ix.accounts);
+
+        let counted = create_receipt_instruction_with_options(
+            &keys[0],
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            &ReceiptOptions::default().with_on_duplicate(DuplicateReceiptPolicy::Count),
+        );
+        assert_eq!(counted.data.len(), 34);
+        assert_eq!(counted.data[1..33], [0; 32]);
+        assert_eq!(counted.data[33], DuplicateReceiptPolicy::Count as u8);
+        assert_eq!(counted.accounts, ix.accounts);
+
+        let sponsored = create_receipt_instruction_with_options(
+            &keys[0],
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            &ReceiptOptions::default().with_rent_beneficiary(keys[0]),
+        );
+        assert_eq!(sponsored.data.len(), 66);
+        assert_eq!(sponsored.data[33], DuplicateReceiptPolicy::Error as u8);
+        assert_eq!(sponsored.data[34..], keys[0].to_bytes());
+        assert_eq!(sponsored.accounts, ix.accounts);
+    }
+
+    #[test]
+    fn test_reverify_by_digest_instruction() {
+        let (program, receipt) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let digest = proof_digest(&[1, 2, 3]);
+        let ix = reverify_by_digest_instruction(&program, &receipt, &digest);
+
+        assert_eq!(ix.program_id, program);
+        assert_eq!(ix.data[0], IX_REVERIFY_BY_DIGEST);
+        assert_eq!(ix.data[1..], digest);
+        assert_eq!(ix.accounts, [AccountMeta::new_readonly(receipt, false)]);
+    }
+
+    fn receipt_data(vk: &Pubkey, pi_hash: &[u8; 32], bump: u8) -> [u8; RECEIPT_SIZE] {
+        let mut data = [0u8; RECEIPT_SIZE];
+        data[0..8].copy_from_slice(&42u64.to_le_bytes());
+        data[8..16].copy_from_slice(&(-7i64).to_le_bytes());
+        data[16..48].copy_from_slice(vk.as_ref());
+        data[48..80].copy_from_slice(pi_hash);
+        data[80] = bump;
+        data[84..88].copy_from_slice(&3u32.to_le_bytes());
+        data[120..152].copy_from_slice(&[5; 32]);
+        data[152..184].copy_from_slice(&proof_digest(&[6; 64]));
+        data[184..216].copy_from_slice(&[8; 32]);
+        data[216..].copy_from_slice(&[9; 32]);
+        data
+    }
+
+    #[test]
+    fn test_receipt_from_account_info() {
+        let vk = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let public_inputs = [7u8; 64];
+        let pi_hash = pi_hash(&public_inputs);
+        let (pda, bump) = derive_receipt_pda(&vk, &pi_hash, &program);
+
+        let mut lamports = 1;
+        let mut data = receipt_data(&vk, &pi_hash, bump);
+        let account = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program,
+            false,
+        );
+
+        {
+            let receipt = Receipt::try_from_account_info(&account, &vk, &program).unwrap();
+            assert_eq!(receipt.verified_slot(), 42);
+            assert_eq!(receipt.verified_timestamp(), -7);
+            assert_eq!(receipt.vk_account(), vk);
+            assert_eq!(receipt.pi_hash(), &pi_hash);
+            assert_eq!(receipt.bump(), bump);
+            assert_eq!(receipt.verification_count(), 3);
+            assert_eq!(receipt.tag(), &[5; 32]);
+            assert_eq!(receipt.proof_digest(), &proof_digest(&[6; 64]));
+            assert_eq!(receipt.vk_hash(), &[8; 32]);
+            assert_eq!(receipt.rent_beneficiary(), Pubkey::new_from_array([9; 32]));
+        }
+        assert!(is_verified(&account, &vk, &public_inputs, &program));
+        assert!(!is_verified(&account, &vk, &[8u8; 64], &program));
+
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            Receipt::try_from_account_info(&account, &other, &program).err(),
+            Some(ReceiptError::WrongPda)
+        );
+        assert_eq!(
+            Receipt::try_from_account_info(&account, &vk, &other).err(),
+            Some(ReceiptError::WrongOwner)
+        );
+    }
+
+    #[test]
+    fn test_receipt_rejects_bad_accounts() {
+        let vk = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let pi_hash = [3u8; 32];
+        let (pda, bump) = derive_receipt_pda(&vk, &pi_hash, &program);
+
+        // Legacy 16-byte receipt
+        let mut lamports = 1;
+        let mut short = [0u8; 16];
+        let account = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut short,
+            &program,
+            false,
+        );
+        assert_eq!(
+            Receipt::try_from_account_info(&account, &vk, &program).err(),
+            Some(ReceiptError::TooSmall)
+        );
+
+        // Right seeds, wrong address
+        let key = Pubkey::new_unique();
+        let mut lamports = 1;
+        let mut data = receipt_data(&vk, &pi_hash, bump);
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program,
+            false,
+        );
+        assert_eq!(
+            Receipt::try_from_account_info(&account, &vk, &program).err(),
+            Some(ReceiptError::WrongPda)
+        );
+    }
+
+    #[test]
+    fn test_receipt_by_vk_hash() {
+        let vk = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let public_inputs = [7u8; 64];
+        let pi_hash = pi_hash(&public_inputs);
+        let (pda, bump) = derive_receipt_pda(&vk, &pi_hash, &program);
+
+        let mut lamports = 1;
+        let mut data = receipt_data(&vk, &pi_hash, bump);
+        let account = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program,
+            false,
+        );
+
+        assert!(is_verified_by_vk_hash(
+            &account,
+            &[8; 32],
+            &public_inputs,
+            &program
+        ));
+        assert!(!is_verified_by_vk_hash(
+            &account, &[8; 32], &[8u8; 64], &program
+        ));
+        assert_eq!(
+            Receipt::try_from_account_info_by_vk_hash(&account, &[9; 32], &program).err(),
+            Some(ReceiptError::VkHashMismatch)
+        );
+
+        // The stored VK account still has to match the address
+        let mut lamports = 1;
+        let mut data = receipt_data(&Pubkey::new_unique(), &pi_hash, bump);
+        let account = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program,
+            false,
+        );
+        assert_eq!(
+            Receipt::try_from_account_info_by_vk_hash(&account, &[8; 32], &program).err(),
+            Some(ReceiptError::WrongPda)
+        );
+    }
+
+    #[test]
+    fn test_pi_hasher_matches_one_shot() {
+        let public_inputs: Vec<u8> = (0..96u8).collect();
+
+        let mut hasher = PiHasher::new();
+        hasher
+            .update(&public_inputs[..32])
+            .update(&public_inputs[32..40])
+            .update(&public_inputs[40..]);
+
+        assert_eq!(hasher.finalize(), keccak::hash(&public_inputs).to_bytes());
+        assert_eq!(PiHasher::new().finalize(), keccak::hash(&[]).to_bytes());
+    }
 }
@@ -0,0 +1,280 @@
+//! Resolve circuits through the on-chain VK registry
+//!
+//! The registry program maps `name@version` to a VK account. Integrators
+//! that pin a circuit by name instead of by VK account pass the registry
+//! entry alongside the receipt:
+//!
+//! ```ignore
+//! use solana_noir_verifier_cpi::{is_verified, registry::resolve_vk};
+//!
+//! let vk_account = resolve_vk(entry, b"membership", b"2", &REGISTRY)?;
+//! if !is_verified(receipt, &vk_account, public_inputs, &VERIFIER) {
+//!     return Err(ProgramError::Custom(1));
+//! }
+//! ```
+//!
+//! Upgrading the circuit then means registering a new version and
+//! deprecating the old one; a deprecated entry no longer resolves.
+
+use core::cell::Ref;
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+/// Size of a registry entry account (192 bytes)
+pub const ENTRY_SIZE: usize = 192;
+
+/// Longest name or version the registry accepts
+pub const MAX_LABEL_LEN: usize = 32;
+
+// Internal: PDA seed prefix of entries
+const ENTRY_SEED: &[u8] = b"entry";
+
+/// Address of the registry entry for `name@version`
+pub fn find_entry_address(name: &[u8], version: &[u8], registry_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ENTRY_SEED, name, version], registry_program)
+}
+
+/// VK account registered as `name@version`
+///
+/// Fails if `entry` is not that registry entry or the entry was deprecated.
+pub fn resolve_vk(
+    entry: &AccountInfo,
+    name: &[u8],
+    version: &[u8],
+    registry_program: &Pubkey,
+) -> Result<Pubkey, RegistryError> {
+    let entry = RegistryEntry::try_from_account_info(entry, name, version, registry_program)?;
+    if entry.is_deprecated() {
+        return Err(RegistryError::Deprecated);
+    }
+    Ok(entry.vk_account())
+}
+
+/// Why a registry entry was rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// Account is not owned by the registry program
+    WrongOwner,
+    /// Account address is not the entry PDA for the given name and version
+    WrongPda,
+    /// Account data is not [`ENTRY_SIZE`] bytes
+    InvalidSize,
+    /// Account data is already mutably borrowed
+    BorrowFailed,
+    /// Entry was deprecated by the name's authority
+    Deprecated,
+}
+
+impl core::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::WrongOwner => "registry entry not owned by the registry program",
+            Self::WrongPda => "registry entry address does not match name and version",
+            Self::InvalidSize => "registry entry account has the wrong size",
+            Self::BorrowFailed => "registry entry account data already borrowed",
+            Self::Deprecated => "registry entry is deprecated",
+        })
+    }
+}
+
+impl From<RegistryError> for ProgramError {
+    fn from(e: RegistryError) -> Self {
+        match e {
+            RegistryError::WrongOwner => ProgramError::IllegalOwner,
+            RegistryError::WrongPda => ProgramError::InvalidSeeds,
+            RegistryError::InvalidSize => ProgramError::InvalidAccountData,
+            RegistryError::BorrowFailed => ProgramError::AccountBorrowFailed,
+            RegistryError::Deprecated => ProgramError::InvalidArgument,
+        }
+    }
+}
+
+/// Registry entry, read in place from the entry account
+///
+/// Mirrors the registry program's `RegistryEntry` layout.
+#[repr(C)]
+pub struct RegistryEntry {
+    vk_account: [u8; 32],
+    vk_hash: [u8; 32],
+    registered_by: [u8; 32],
+    registered_slot: [u8; 8],
+    registered_timestamp: [u8; 8],
+    deprecated_slot: [u8; 8],
+    name: [u8; MAX_LABEL_LEN],
+    version: [u8; MAX_LABEL_LEN],
+    name_len: u8,
+    version_len: u8,
+    bump: u8,
+    _reserved: [u8; 5],
+}
+
+const _: () = assert!(core::mem::size_of::<RegistryEntry>() == ENTRY_SIZE);
+
+impl RegistryEntry {
+    /// Borrow and validate the entry for `name@version`
+    ///
+    /// Checks the owner and size, then re-derives the PDA from the stored
+    /// bump. Deprecated entries are returned as-is; check
+    /// [`RegistryEntry::is_deprecated`] or use [`resolve_vk`].
+    pub fn try_from_account_info<'a>(
+        account: &'a AccountInfo,
+        name: &[u8],
+        version: &[u8],
+        registry_program: &Pubkey,
+    ) -> Result<Ref<'a, RegistryEntry>, RegistryError> {
+        if account.owner != registry_program {
+            return Err(RegistryError::WrongOwner);
+        }
+
+        let data = account
+            .try_borrow_data()
+            .map_err(|_| RegistryError::BorrowFailed)?;
+        if data.len() != ENTRY_SIZE {
+            return Err(RegistryError::InvalidSize);
+        }
+        // SAFETY: length checked above and `RegistryEntry` has alignment 1
+        let entry = Ref::map(data, |d| unsafe { &*(d.as_ptr() as *const RegistryEntry) });
+
+        let expected = Pubkey::create_program_address(
+            &[ENTRY_SEED, name, version, &[entry.bump]],
+            registry_program,
+        )
+        .map_err(|_| RegistryError::WrongPda)?;
+        if account.key != &expected {
+            return Err(RegistryError::WrongPda);
+        }
+
+        Ok(entry)
+    }
+
+    /// VK account registered for this version
+    pub fn vk_account(&self) -> Pubkey {
+        Pubkey::new_from_array(self.vk_account)
+    }
+
+    /// Keccak256 of the VK bytes, as given at registration
+    pub fn vk_hash(&self) -> &[u8; 32] {
+        &self.vk_hash
+    }
+
+    /// Authority that registered the entry
+    pub fn registered_by(&self) -> Pubkey {
+        Pubkey::new_from_array(self.registered_by)
+    }
+
+    /// Slot of registration
+    pub fn registered_slot(&self) -> u64 {
+        u64::from_le_bytes(self.registered_slot)
+    }
+
+    /// Unix timestamp of registration
+    pub fn registered_timestamp(&self) -> i64 {
+        i64::from_le_bytes(self.registered_timestamp)
+    }
+
+    /// Slot the entry was deprecated at, if it was
+    pub fn deprecated_slot(&self) -> Option<u64> {
+        match u64::from_le_bytes(self.deprecated_slot) {
+            0 => None,
+            slot => Some(slot),
+        }
+    }
+
+    /// Whether the entry was deprecated
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated_slot().is_some()
+    }
+
+    /// Registered name
+    pub fn name(&self) -> &[u8] {
+        &self.name[..(self.name_len as usize).min(MAX_LABEL_LEN)]
+    }
+
+    /// Registered version
+    pub fn version(&self) -> &[u8] {
+        &self.version[..(self.version_len as usize).min(MAX_LABEL_LEN)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_data(vk: &Pubkey, bump: u8, deprecated_slot: u64) -> [u8; ENTRY_SIZE] {
+        let mut data = [0u8; ENTRY_SIZE];
+        data[0..32].copy_from_slice(vk.as_ref());
+        data[32..64].copy_from_slice(&[9u8; 32]);
+        data[96..104].copy_from_slice(&5u64.to_le_bytes());
+        data[112..120].copy_from_slice(&deprecated_slot.to_le_bytes());
+        data[120..124].copy_from_slice(b"zkid");
+        data[152] = b'2';
+        data[184] = 4;
+        data[185] = 1;
+        data[186] = bump;
+        data
+    }
+
+    #[test]
+    fn test_resolve_vk() {
+        let vk = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let (pda, bump) = find_entry_address(b"zkid", b"2", &program);
+
+        let mut lamports = 1;
+        let mut data = entry_data(&vk, bump, 0);
+        let account = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program,
+            false,
+        );
+
+        {
+            let entry =
+                RegistryEntry::try_from_account_info(&account, b"zkid", b"2", &program).unwrap();
+            assert_eq!(entry.vk_account(), vk);
+            assert_eq!(entry.vk_hash(), &[9u8; 32]);
+            assert_eq!(entry.registered_slot(), 5);
+            assert_eq!(entry.name(), b"zkid");
+            assert_eq!(entry.version(), b"2");
+            assert_eq!(entry.deprecated_slot(), None);
+        }
+        assert_eq!(resolve_vk(&account, b"zkid", b"2", &program), Ok(vk));
+        assert_eq!(
+            resolve_vk(&account, b"zkid", b"3", &program),
+            Err(RegistryError::WrongPda)
+        );
+        assert_eq!(
+            resolve_vk(&account, b"zkid", b"2", &Pubkey::new_unique()),
+            Err(RegistryError::WrongOwner)
+        );
+    }
+
+    #[test]
+    fn test_resolve_vk_rejects_deprecated() {
+        let vk = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let (pda, bump) = find_entry_address(b"zkid", b"2", &program);
+
+        let mut lamports = 1;
+        let mut data = entry_data(&vk, bump, 77);
+        let account = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program,
+            false,
+        );
+        assert_eq!(
+            resolve_vk(&account, b"zkid", b"2", &program),
+            Err(RegistryError::Deprecated)
+        );
+        let entry =
+            RegistryEntry::try_from_account_info(&account, b"zkid", b"2", &program).unwrap();
+        assert_eq!(entry.deprecated_slot(), Some(77));
+    }
+}
@@ -0,0 +1,583 @@
+//! One-call receipt checks for instruction handlers
+//!
+//! [`require_verified_proof`] bundles the checks a receipt-gated
+//! instruction needs and returns what the receipt records:
+//!
+//! ```ignore
+//! use solana_noir_verifier_cpi::require::{require_verified_proof, ProofCheck};
+//!
+//! let proof = require_verified_proof(
+//!     ProofCheck::new(receipt, &VERIFIER).max_age_slots(150),
+//!     &MY_VK,
+//!     public_inputs,
+//! )?;
+//! msg!("verified at slot {}", proof.verified_slot);
+//! ```
+//!
+//! # Consumption
+//!
+//! A receipt stays valid until its payer closes it, so by default the same
+//! proof can gate any number of calls. [`ProofCheck::consume`] makes it
+//! single-use for the calling program: a marker account at
+//! `[b"consumed", receipt]` under the caller's program ID is created on
+//! first use, and its existence rejects every later call. The marker holds
+//! no data and costs the rent of an empty account.
+
+use crate::{Receipt, ReceiptError, SYSTEM_PROGRAM_ID};
+use alloc::format;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    keccak, msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+/// Seed prefix of consumption markers, under the calling program
+pub const CONSUMED_SEED: &[u8] = b"consumed";
+
+// Internal: VK account header (status + length), as in the verifier program
+const VK_HEADER_SIZE: usize = 3;
+
+/// Accounts and options of one receipt check
+pub struct ProofCheck<'a, 'info> {
+    receipt: &'a AccountInfo<'info>,
+    verifier_program: &'a Pubkey,
+    max_age_slots: Option<u64>,
+    vk_account: Option<&'a AccountInfo<'info>>,
+    consume: Option<ConsumeAccounts<'a, 'info>>,
+}
+
+/// Accounts [`ProofCheck::consume`] needs to create the marker
+pub struct ConsumeAccounts<'a, 'info> {
+    /// The calling program, which owns the marker
+    pub program_id: &'a Pubkey,
+    /// Marker PDA at `[CONSUMED_SEED, receipt]` (writable)
+    pub marker: &'a AccountInfo<'info>,
+    /// Pays the marker rent (writable, signer)
+    pub payer: &'a AccountInfo<'info>,
+    /// System program
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ProofCheck<'a, 'info> {
+    /// Check `receipt` against receipts of `verifier_program`
+    pub fn new(receipt: &'a AccountInfo<'info>, verifier_program: &'a Pubkey) -> Self {
+        Self {
+            receipt,
+            verifier_program,
+            max_age_slots: None,
+            vk_account: None,
+            consume: None,
+        }
+    }
+
+    /// Reject receipts created more than `slots` slots ago
+    pub fn max_age_slots(mut self, slots: u64) -> Self {
+        self.max_age_slots = Some(slots);
+        self
+    }
+
+    /// Read the VK account too, to return [`VerifiedProof::vk_hash`]
+    pub fn with_vk_account(mut self, vk_account: &'a AccountInfo<'info>) -> Self {
+        self.vk_account = Some(vk_account);
+        self
+    }
+
+    /// Accept the receipt once per calling program; see the module docs
+    pub fn consume(mut self, accounts: ConsumeAccounts<'a, 'info>) -> Self {
+        self.consume = Some(accounts);
+        self
+    }
+}
+
+/// What a valid receipt records
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifiedProof {
+    /// Slot when the proof was verified
+    pub verified_slot: u64,
+    /// Unix timestamp when the proof was verified
+    pub verified_timestamp: i64,
+    /// VK account the proof was verified against
+    pub vk_account: Pubkey,
+    /// Keccak256 of the verified public inputs
+    pub pi_hash: [u8; 32],
+    /// Keccak256 of the VK bytes, with [`ProofCheck::with_vk_account`]
+    pub vk_hash: Option<[u8; 32]>,
+    /// Tag the receipt's creator attached; see [`Receipt::tag`]
+    pub tag: [u8; 32],
+    /// Verifications counted on the receipt; see
+    /// [`Receipt::verification_count`]
+    pub verification_count: u32,
+    /// Keccak256 of the verified proof bytes; see [`Receipt::proof_digest`]
+    pub proof_digest: [u8; 32],
+}
+
+/// Why a proof was not accepted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// The receipt account itself is invalid
+    Receipt(ReceiptError),
+    /// The receipt is for other public inputs
+    PublicInputsMismatch,
+    /// The receipt is older than [`ProofCheck::max_age_slots`]
+    Expired,
+    /// The VK account passed is not the one the receipt names
+    VkAccountMismatch,
+    /// The receipt was already consumed by this program
+    AlreadyConsumed,
+    /// The marker account is not at `[CONSUMED_SEED, receipt]`
+    WrongMarker,
+    /// Reading a sysvar or creating the marker failed
+    Program(ProgramError),
+}
+
+impl core::fmt::Display for ProofError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Receipt(e) => e.fmt(f),
+            Self::PublicInputsMismatch => f.write_str("receipt is for other public inputs"),
+            Self::Expired => f.write_str("receipt is too old"),
+            Self::VkAccountMismatch => f.write_str("VK account does not match the receipt"),
+            Self::AlreadyConsumed => f.write_str("receipt already consumed"),
+            Self::WrongMarker => f.write_str("consumption marker address does not match"),
+            Self::Program(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<ReceiptError> for ProofError {
+    fn from(e: ReceiptError) -> Self {
+        Self::Receipt(e)
+    }
+}
+
+impl From<ProgramError> for ProofError {
+    fn from(e: ProgramError) -> Self {
+        Self::Program(e)
+    }
+}
+
+impl From<ProofError> for ProgramError {
+    fn from(e: ProofError) -> Self {
+        match e {
+            ProofError::Receipt(e) => e.into(),
+            ProofError::PublicInputsMismatch => ProgramError::InvalidArgument,
+            ProofError::Expired => ProgramError::InvalidArgument,
+            ProofError::VkAccountMismatch => ProgramError::InvalidAccountData,
+            ProofError::AlreadyConsumed => ProgramError::AccountAlreadyInitialized,
+            ProofError::WrongMarker => ProgramError::InvalidSeeds,
+            ProofError::Program(e) => e,
+        }
+    }
+}
+
+/// Require a verified proof of `public_inputs` against `vk_account`
+///
+/// Checks the receipt as [`Receipt::try_from_account_info`] does, then its
+/// public inputs, then the options set on `check`. The reason for a
+/// rejection is logged before the error is returned.
+pub fn require_verified_proof(
+    check: ProofCheck,
+    vk_account: &Pubkey,
+    public_inputs: &[u8],
+) -> Result<VerifiedProof, ProofError> {
+    let pi_hash = crate::pi_hash(public_inputs);
+    require_verified_proof_with_hash(check, vk_account, &pi_hash)
+}
+
+/// [`require_verified_proof`] for callers that hashed the public inputs,
+/// e.g. with [`PiHasher`](crate::PiHasher)
+pub fn require_verified_proof_with_hash(
+    check: ProofCheck,
+    vk_account: &Pubkey,
+    pi_hash: &[u8; 32],
+) -> Result<VerifiedProof, ProofError> {
+    let result = check_proof(&check, vk_account, pi_hash);
+    if let Err(e) = &result {
+        msg!("Proof not accepted: {}", e);
+    }
+    result
+}
+
+fn check_proof(
+    check: &ProofCheck,
+    vk_account: &Pubkey,
+    pi_hash: &[u8; 32],
+) -> Result<VerifiedProof, ProofError> {
+    let mut proof = {
+        let receipt =
+            Receipt::try_from_account_info(check.receipt, vk_account, check.verifier_program)?;
+        if receipt.pi_hash() != pi_hash {
+            return Err(ProofError::PublicInputsMismatch);
+        }
+        VerifiedProof {
+            verified_slot: receipt.verified_slot(),
+            verified_timestamp: receipt.verified_timestamp(),
+            vk_account: *vk_account,
+            pi_hash: *pi_hash,
+            vk_hash: None,
+            tag: *receipt.tag(),
+            verification_count: receipt.verification_count(),
+            proof_digest: *receipt.proof_digest(),
+        }
+    };
+
+    if let Some(max_age) = check.max_age_slots {
+        if !within_age(proof.verified_slot, Clock::get()?.slot, max_age) {
+            return Err(ProofError::Expired);
+        }
+    }
+    if let Some(vk) = check.vk_account {
+        proof.vk_hash = Some(vk_hash(vk, vk_account, check.verifier_program)?);
+    }
+    if let Some(consume) = &check.consume {
+        consume_receipt(consume, check.receipt.key)?;
+    }
+    Ok(proof)
+}
+
+/// Whether a receipt from `verified_slot` is at most `max_age` slots old
+fn within_age(verified_slot: u64, current_slot: u64, max_age: u64) -> bool {
+    current_slot.saturating_sub(verified_slot) <= max_age
+}
+
+/// Keccak256 of the VK bytes stored in `account`
+fn vk_hash(
+    account: &AccountInfo,
+    vk_account: &Pubkey,
+    verifier_program: &Pubkey,
+) -> Result<[u8; 32], ProofError> {
+    if account.key != vk_account || account.owner != verifier_program {
+        return Err(ProofError::VkAccountMismatch);
+    }
+    let data = account
+        .try_borrow_data()
+        .map_err(|_| ProofError::Program(ProgramError::AccountBorrowFailed))?;
+    let len = match data.get(1..VK_HEADER_SIZE) {
+        Some(len) => u16::from_le_bytes([len[0], len[1]]) as usize,
+        None => return Err(ProofError::VkAccountMismatch),
+    };
+    let vk = data
+        .get(VK_HEADER_SIZE..VK_HEADER_SIZE + len)
+        .ok_or(ProofError::VkAccountMismatch)?;
+    Ok(keccak::hash(vk).to_bytes())
+}
+
+/// Create the marker for `receipt`, failing if it already exists
+fn consume_receipt(accounts: &ConsumeAccounts, receipt: &Pubkey) -> Result<(), ProofError> {
+    let (marker, bump) =
+        Pubkey::find_program_address(&[CONSUMED_SEED, receipt.as_ref()], accounts.program_id);
+    if accounts.marker.key != &marker {
+        return Err(ProofError::WrongMarker);
+    }
+    if accounts.marker.owner == accounts.program_id {
+        return Err(ProofError::AlreadyConsumed);
+    }
+
+    let seeds: &[&[u8]] = &[CONSUMED_SEED, receipt.as_ref(), &[bump]];
+    let rent = Rent::get()?.minimum_balance(0);
+    let infos = [
+        accounts.payer.clone(),
+        accounts.marker.clone(),
+        accounts.system_program.clone(),
+    ];
+    // Anyone can send lamports to the marker address beforehand, which
+    // would make CreateAccount fail; top up and assign instead
+    let balance = accounts.marker.lamports();
+    if balance == 0 {
+        invoke_signed(
+            &system_instruction(
+                accounts.payer.key,
+                accounts.marker.key,
+                &create_account_data(rent, accounts.program_id),
+                true,
+            ),
+            &infos,
+            &[seeds],
+        )?;
+    } else {
+        if balance < rent {
+            invoke_signed(
+                &system_instruction(
+                    accounts.payer.key,
+                    accounts.marker.key,
+                    &transfer_data(rent - balance),
+                    true,
+                ),
+                &infos,
+                &[],
+            )?;
+        }
+        invoke_signed(
+            &Instruction::new_with_bytes(
+                SYSTEM_PROGRAM_ID,
+                &assign_data(accounts.program_id),
+                alloc::vec![AccountMeta::new(*accounts.marker.key, true)],
+            ),
+            &infos,
+            &[seeds],
+        )?;
+    }
+    Ok(())
+}
+
+/// A system instruction from `from` to `to`; `to` signs when `to_signs`
+fn system_instruction(from: &Pubkey, to: &Pubkey, data: &[u8], to_signs: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        SYSTEM_PROGRAM_ID,
+        data,
+        alloc::vec![
+            AccountMeta::new(*from, true),
+            AccountMeta::new(*to, to_signs)
+        ],
+    )
+}
+
+// System instructions are bincode: a u32 tag, then the fields
+
+fn create_account_data(lamports: u64, owner: &Pubkey) -> [u8; 52] {
+    let mut data = [0u8; 52];
+    // Tag 0 (CreateAccount), lamports, space 0, owner
+    data[4..12].copy_from_slice(&lamports.to_le_bytes());
+    data[20..52].copy_from_slice(owner.as_ref());
+    data
+}
+
+fn assign_data(owner: &Pubkey) -> [u8; 36] {
+    let mut data = [0u8; 36];
+    data[0..4].copy_from_slice(&1u32.to_le_bytes());
+    data[4..36].copy_from_slice(owner.as_ref());
+    data
+}
+
+fn transfer_data(lamports: u64) -> [u8; 12] {
+    let mut data = [0u8; 12];
+    data[0..4].copy_from_slice(&2u32.to_le_bytes());
+    data[4..12].copy_from_slice(&lamports.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        program: Pubkey,
+        vk: Pubkey,
+        pda: Pubkey,
+        pi_hash: [u8; 32],
+        lamports: u64,
+        data: [u8; crate::RECEIPT_SIZE],
+    }
+
+    fn fixture(public_inputs: &[u8]) -> Fixture {
+        let (program, vk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pi_hash = crate::pi_hash(public_inputs);
+        let (pda, bump) = crate::derive_receipt_pda(&vk, &pi_hash, &program);
+        let mut data = [0u8; crate::RECEIPT_SIZE];
+        data[0..8].copy_from_slice(&42u64.to_le_bytes());
+        data[8..16].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        data[16..48].copy_from_slice(vk.as_ref());
+        data[48..80].copy_from_slice(&pi_hash);
+        data[80] = bump;
+        data[84..88].copy_from_slice(&1u32.to_le_bytes());
+        Fixture {
+            program,
+            vk,
+            pda,
+            pi_hash,
+            lamports: 1,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_require_verified_proof() {
+        let public_inputs = [5u8; 64];
+        let mut f = fixture(&public_inputs);
+        let receipt = AccountInfo::new(
+            &f.pda,
+            false,
+            false,
+            &mut f.lamports,
+            &mut f.data,
+            &f.program,
+            false,
+        );
+
+        let proof =
+            require_verified_proof(ProofCheck::new(&receipt, &f.program), &f.vk, &public_inputs)
+                .unwrap();
+        assert_eq!(
+            proof,
+            VerifiedProof {
+                verified_slot: 42,
+                verified_timestamp: 1_700_000_000,
+                vk_account: f.vk,
+                pi_hash: f.pi_hash,
+                vk_hash: None,
+                tag: [0; 32],
+                verification_count: 1,
+                proof_digest: [0; 32],
+            }
+        );
+
+        assert_eq!(
+            require_verified_proof(ProofCheck::new(&receipt, &f.program), &f.vk, &[6u8; 64]),
+            Err(ProofError::PublicInputsMismatch)
+        );
+        assert_eq!(
+            require_verified_proof(
+                ProofCheck::new(&receipt, &f.program),
+                &Pubkey::new_unique(),
+                &public_inputs
+            ),
+            Err(ProofError::Receipt(ReceiptError::WrongPda))
+        );
+        assert_eq!(
+            ProgramError::from(ProofError::Receipt(ReceiptError::WrongOwner)),
+            ProgramError::IllegalOwner
+        );
+    }
+
+    #[test]
+    fn test_vk_hash() {
+        let public_inputs = [5u8; 32];
+        let mut f = fixture(&public_inputs);
+        let receipt = AccountInfo::new(
+            &f.pda,
+            false,
+            false,
+            &mut f.lamports,
+            &mut f.data,
+            &f.program,
+            false,
+        );
+        let vk_bytes = [9u8; 100];
+        let mut vk_data = [0u8; VK_HEADER_SIZE + 120];
+        vk_data[1..3].copy_from_slice(&100u16.to_le_bytes());
+        vk_data[VK_HEADER_SIZE..VK_HEADER_SIZE + 100].copy_from_slice(&vk_bytes);
+        let mut vk_lamports = 1;
+        let vk = AccountInfo::new(
+            &f.vk,
+            false,
+            false,
+            &mut vk_lamports,
+            &mut vk_data,
+            &f.program,
+            false,
+        );
+
+        let proof = require_verified_proof(
+            ProofCheck::new(&receipt, &f.program).with_vk_account(&vk),
+            &f.vk,
+            &public_inputs,
+        )
+        .unwrap();
+        assert_eq!(proof.vk_hash, Some(keccak::hash(&vk_bytes).to_bytes()));
+
+        // Only the VK account the receipt names is hashed
+        let other_vk = Pubkey::new_unique();
+        assert_eq!(
+            vk_hash(&vk, &other_vk, &f.program),
+            Err(ProofError::VkAccountMismatch)
+        );
+    }
+
+    #[test]
+    fn test_consumed_receipt() {
+        let public_inputs = [5u8; 32];
+        let mut f = fixture(&public_inputs);
+        let receipt = AccountInfo::new(
+            &f.pda,
+            false,
+            false,
+            &mut f.lamports,
+            &mut f.data,
+            &f.program,
+            false,
+        );
+        let caller = Pubkey::new_unique();
+        let (marker_key, _) =
+            Pubkey::find_program_address(&[CONSUMED_SEED, f.pda.as_ref()], &caller);
+        let (payer_key, system) = (Pubkey::new_unique(), SYSTEM_PROGRAM_ID);
+        let (mut marker_lamports, mut payer_lamports, mut system_lamports) = (1, 1, 1);
+        let (mut marker_data, mut payer_data, mut system_data) = ([], [], []);
+        // A marker owned by the caller means the receipt was used before
+        let marker = AccountInfo::new(
+            &marker_key,
+            false,
+            true,
+            &mut marker_lamports,
+            &mut marker_data,
+            &caller,
+            false,
+        );
+        let payer = AccountInfo::new(
+            &payer_key,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system,
+            false,
+        );
+        let system_program = AccountInfo::new(
+            &system,
+            false,
+            false,
+            &mut system_lamports,
+            &mut system_data,
+            &system,
+            true,
+        );
+        let consume = |marker| ConsumeAccounts {
+            program_id: &caller,
+            marker,
+            payer: &payer,
+            system_program: &system_program,
+        };
+
+        assert_eq!(
+            require_verified_proof(
+                ProofCheck::new(&receipt, &f.program).consume(consume(&marker)),
+                &f.vk,
+                &public_inputs,
+            ),
+            Err(ProofError::AlreadyConsumed)
+        );
+        assert_eq!(
+            require_verified_proof(
+                ProofCheck::new(&receipt, &f.program).consume(consume(&payer)),
+                &f.vk,
+                &public_inputs,
+            ),
+            Err(ProofError::WrongMarker)
+        );
+    }
+
+    #[test]
+    fn test_within_age() {
+        assert!(within_age(100, 150, 50));
+        assert!(!within_age(100, 151, 50));
+        // A receipt from a later slot than the clock reads is not expired
+        assert!(within_age(200, 150, 0));
+    }
+
+    #[test]
+    fn test_system_instruction_data() {
+        let owner = Pubkey::new_unique();
+        let create = create_account_data(890_880, &owner);
+        assert_eq!(&create[0..4], &0u32.to_le_bytes());
+        assert_eq!(&create[4..12], &890_880u64.to_le_bytes());
+        assert_eq!(&create[12..20], &0u64.to_le_bytes());
+        assert_eq!(&create[20..], owner.as_ref());
+        assert_eq!(&assign_data(&owner)[4..], owner.as_ref());
+        assert_eq!(&transfer_data(5)[0..4], &2u32.to_le_bytes());
+    }
+}
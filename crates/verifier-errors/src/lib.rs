@@ -0,0 +1,293 @@
+//! Error codes of the Noir UltraHonk verifier
+//!
+//! The program returns every handler failure as `ProgramError::Custom(code)`.
+//! This crate is the one table of those codes and their messages: the
+//! program logs them, the SDK decodes them from failed transactions, and the
+//! CLI prints them, all through [`VerifierError`], so a failure reads the
+//! same everywhere. Codes are grouped by the hundred and are part of the
+//! program's public interface: never renumber or reuse one.
+//!
+//! | Range | Group |
+//! |-------|-------|
+//! | 100s  | Account checks |
+//! | 200s  | Proof / VK buffer state |
+//! | 300s  | Input parsing |
+//! | 400s  | Phase ordering |
+//! | 500s  | Verification failures |
+//! | 1000 + N | Sumcheck round N failed |
+//!
+//! ```
+//! use solana_noir_verifier_errors::VerifierError;
+//!
+//! let err = VerifierError::from_code(504).unwrap();
+//! assert_eq!(err, VerifierError::PairingFailed);
+//! assert_eq!(err.to_string(), "pairing check failed (code 504)");
+//! ```
+
+#![no_std]
+
+use core::fmt;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+/// Base code for per-round sumcheck failures (`1000 + round`)
+pub const SUMCHECK_ROUND_FAILED_BASE: u32 = 1000;
+
+/// Defines [`ErrorCode`] and [`VerifierError`] from one list of
+/// `Variant = code => "message"` entries
+macro_rules! verifier_errors {
+    ($(
+        $(#[doc = $doc:literal])*
+        $name:ident = $code:literal => $message:literal,
+    )*) => {
+        /// The fixed error codes, as a plain `repr(u32)` enum
+        ///
+        /// [`VerifierError`] adds the per-round sumcheck failures, which
+        /// carry the round in their code.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
+        #[repr(u32)]
+        pub enum ErrorCode {
+            $($(#[doc = $doc])* $name = $code,)*
+        }
+
+        impl ErrorCode {
+            /// What went wrong, without the code
+            pub fn message(self) -> &'static str {
+                match self {
+                    $(Self::$name => $message,)*
+                }
+            }
+        }
+
+        /// Errors returned by the verifier program
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum VerifierError {
+            $($(#[doc = $doc])* $name,)*
+            /// Round sum check failed in the given sumcheck round
+            SumcheckRoundFailed(u8),
+        }
+
+        impl From<ErrorCode> for VerifierError {
+            fn from(code: ErrorCode) -> Self {
+                match code {
+                    $(ErrorCode::$name => Self::$name,)*
+                }
+            }
+        }
+
+        impl VerifierError {
+            /// The fixed code, or the failed round
+            fn split(self) -> Result<ErrorCode, u8> {
+                match self {
+                    $(Self::$name => Ok(ErrorCode::$name),)*
+                    Self::SumcheckRoundFailed(round) => Err(round),
+                }
+            }
+        }
+    };
+}
+
+verifier_errors! {
+    // === Accounts ===
+    /// An account that must be written was passed read-only
+    AccountNotWritable = 100 => "account must be writable",
+    /// Verification state account has the wrong size or layout
+    InvalidStateAccount = 101 => "invalid verification state account",
+    /// The VK account differs from the one the verification was started with
+    VkAccountMismatch = 102 => "VK account does not match the verification state",
+    /// Receipt account has the wrong size or layout
+    InvalidReceiptAccount = 103 => "invalid receipt account",
+    /// Signer is not the account that paid for the receipt
+    NotReceiptPayer = 104 => "signer did not pay for the receipt",
+    /// The proof buffer differs from the one the verification was started with
+    ProofBufferMismatch = 105 => "proof buffer does not match the verification state",
+    /// State challenges no longer match the digest Phase 1 recorded
+    ChallengeDigestMismatch = 106 => "verification state challenges were modified after Phase 1",
+    /// MSM scratch account is not the one Phase 3b2 wrote for this state
+    MsmScratchMismatch = 107 => "MSM scratch account does not match the verification state",
+    /// Receipt already exists and the duplicate policy is `Error`
+    ReceiptAlreadyExists = 108 => "receipt already exists",
+    /// Verification state was already recorded by a receipt
+    StateAlreadyRecorded = 109 => "verification state already recorded by a receipt",
+    /// Account passed to receive a closed receipt's rent is not its beneficiary
+    NotReceiptBeneficiary = 110 => "account is not the receipt's rent beneficiary",
+
+    // === Buffers ===
+    /// Proof buffer has not received all of its data
+    ProofBufferNotReady = 200 => "proof buffer not ready",
+    /// Proof buffer is marked ready but chunks are missing
+    ProofBufferIncomplete = 201 => "proof buffer is missing chunks",
+    /// VK buffer is not in the status the instruction requires
+    VkBufferNotReady = 202 => "VK buffer not ready",
+    /// VK buffer holds fewer bytes than a full VK
+    VkBufferIncomplete = 203 => "VK buffer incomplete",
+    /// VK was already uploaded in full or compiled and can no longer be written
+    VkAlreadyCompiled = 204 => "VK already compiled",
+    /// Proof buffer is locked by a verification and can no longer be written
+    ProofBufferLocked = 205 => "proof buffer locked by a verification",
+    /// Shard is missing from, or not bound to, its manifest's position
+    InvalidShard = 206 => "shard missing or not bound to its manifest",
+
+    // === Inputs ===
+    /// VK bytes failed to parse or validate
+    InvalidVk = 300 => "invalid verification key",
+    /// Proof bytes failed to parse or contain invalid points
+    InvalidProof = 301 => "invalid proof",
+    /// Public inputs do not match what the VK expects
+    PublicInputMismatch = 302 => "public inputs do not match the VK",
+    /// Custom SRS point in the VK account is not a valid G2 point
+    InvalidSrsG2 = 303 => "invalid SRS G2 point in VK account",
+    /// Supplied proof digest differs from the one the receipt recorded
+    ProofDigestMismatch = 304 => "proof digest does not match the receipt",
+    /// Circuit's `log_n` exceeds `MAX_LOG_N` or one of its phases cannot fit
+    /// a transaction
+    CircuitTooLarge = 305 => "circuit too large for on-chain verification",
+    /// Relation coefficients differ from the ones Phase 2c committed to
+    RelationCoefficientsMismatch = 306 => "relation coefficients do not match the verification state",
+
+    // === Phases ===
+    /// Instruction is not valid in the current (sub-)phase
+    InvalidPhase = 400 => "instruction not valid in the current phase",
+    /// Sumcheck round batch does not continue from the last completed round
+    RoundOutOfOrder = 401 => "sumcheck rounds submitted out of order",
+    /// Relations were checked before every sumcheck round was verified
+    SumcheckIncomplete = 402 => "not all sumcheck rounds verified",
+    /// Verification has not completed successfully
+    VerificationNotComplete = 403 => "verification not complete",
+    /// Instruction batches more work than fits one transaction for this circuit
+    PhaseBatchTooLarge = 404 => "phase batch too large for one transaction",
+
+    // === Verification ===
+    /// Fiat-Shamir challenge generation failed
+    ChallengeGenerationFailed = 500 => "challenge generation failed",
+    /// Sumcheck failed outside of a specific round
+    SumcheckFailed = 501 => "sumcheck failed",
+    /// Final sumcheck relation check failed
+    RelationsFailed = 502 => "sumcheck relations check failed",
+    /// Shplemini batching / MSM failed
+    MsmFailed = 503 => "MSM computation failed",
+    /// Final pairing check did not hold
+    PairingFailed = 504 => "pairing check failed",
+}
+
+impl VerifierError {
+    /// Stable numeric code, as returned in `ProgramError::Custom`
+    pub fn code(self) -> u32 {
+        match self.split() {
+            Ok(code) => code.into(),
+            Err(round) => SUMCHECK_ROUND_FAILED_BASE + round as u32,
+        }
+    }
+
+    /// Inverse of [`VerifierError::code`]
+    pub fn from_code(code: u32) -> Option<Self> {
+        if let Ok(code) = ErrorCode::try_from(code) {
+            return Some(code.into());
+        }
+        let round = code.checked_sub(SUMCHECK_ROUND_FAILED_BASE)?;
+        u8::try_from(round).ok().map(Self::SumcheckRoundFailed)
+    }
+
+    /// Whether the proof itself was rejected
+    ///
+    /// These failures are final for this proof. Every other code points at
+    /// account, buffer or phase-ordering problems that resubmitting can fix.
+    pub fn is_proof_rejection(self) -> bool {
+        matches!(
+            self,
+            Self::InvalidProof
+                | Self::PublicInputMismatch
+                | Self::ChallengeGenerationFailed
+                | Self::SumcheckFailed
+                | Self::RelationsFailed
+                | Self::MsmFailed
+                | Self::PairingFailed
+                | Self::SumcheckRoundFailed(_)
+        )
+    }
+
+    /// Decode a `ProgramError::Custom` returned by the verifier
+    #[cfg(feature = "program")]
+    pub fn from_program_error(error: &solana_program_error::ProgramError) -> Option<Self> {
+        match error {
+            solana_program_error::ProgramError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+
+    /// An `anyhow` error for a raw code, rendered like [`VerifierError`]
+    /// when the code is known
+    #[cfg(feature = "anyhow")]
+    pub fn anyhow_from_code(code: u32) -> anyhow::Error {
+        match Self::from_code(code) {
+            Some(error) => anyhow::Error::new(error),
+            None => anyhow::anyhow!("unknown verifier error (code {code})"),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message(), u32::from(*self))
+    }
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.split() {
+            Ok(code) => code.fmt(f),
+            Err(round) => write!(f, "sumcheck round {round} failed (code {})", self.code()),
+        }
+    }
+}
+
+impl core::error::Error for VerifierError {}
+
+#[cfg(feature = "program")]
+impl From<VerifierError> for solana_program_error::ProgramError {
+    fn from(e: VerifierError) -> Self {
+        solana_program_error::ProgramError::Custom(e.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_codes_roundtrip() {
+        for code in (0..2000).chain([1255, 1256, u32::MAX]) {
+            if let Some(e) = VerifierError::from_code(code) {
+                assert_eq!(e.code(), code);
+            }
+        }
+        assert_eq!(
+            VerifierError::from_code(1004),
+            Some(VerifierError::SumcheckRoundFailed(4))
+        );
+        assert_eq!(VerifierError::from_code(1256), None);
+        assert_eq!(VerifierError::from_code(505), None);
+        assert_eq!(
+            VerifierError::from(ErrorCode::try_from(205).unwrap()),
+            VerifierError::ProofBufferLocked
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            VerifierError::ReceiptAlreadyExists.to_string(),
+            "receipt already exists (code 108)"
+        );
+        assert_eq!(
+            VerifierError::SumcheckRoundFailed(3).to_string(),
+            "sumcheck round 3 failed (code 1003)"
+        );
+        assert_eq!(
+            ErrorCode::InvalidVk.to_string(),
+            VerifierError::InvalidVk.to_string()
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Attribute macro for receipt-gated instruction handlers
+//!
+//! `#[requires_proof]` runs
+//! [`require_verified_proof`](https://docs.rs/solana-noir-verifier-cpi) before
+//! the handler body, so a handler cannot skip a receipt check by accident.
+//! Use it through `solana-noir-verifier-cpi` with the `macros` feature:
+//!
+//! ```ignore
+//! use solana_noir_verifier_cpi::requires_proof;
+//!
+//! const VERIFIER_PROGRAM: Pubkey = pubkey!("...");
+//! const MY_CIRCUIT_VK: Pubkey = pubkey!("...");
+//!
+//! fn public_inputs<'a>(_accounts: &[AccountInfo], data: &'a [u8]) -> Result<&'a [u8], ProgramError> {
+//!     Ok(data)
+//! }
+//!
+//! #[requires_proof(vk = "MY_CIRCUIT_VK", inputs = "public_inputs", receipt = 0, max_age_slots = 150)]
+//! fn vote(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+//!     // Only reached with a valid receipt for these public inputs
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Arguments
+//!
+//! | Argument | |
+//! |----------|---|
+//! | `vk` | Path of the circuit's VK account `Pubkey` |
+//! | `inputs` | Path of a function extracting the public inputs |
+//! | `verifier` | Path of the verifier program ID, `VERIFIER_PROGRAM` by default |
+//! | `receipt` | Index of the receipt in the handler's `&[AccountInfo]` argument, `0` by default; or an expression of type `&AccountInfo`, e.g. `"ctx.accounts.receipt.as_ref()"` for Anchor |
+//! | `max_age_slots` | Reject receipts older than this many slots |
+//! | `bind` | Name to bind the `VerifiedProof` to in the handler body |
+//!
+//! The `inputs` function gets the handler's arguments in order, reference
+//! arguments as they are and others by reference, and returns
+//! `Result<impl AsRef<[u8]>, E>` where the handler's error type converts from
+//! `E`. Receipt errors are converted through `ProgramError`, which native
+//! and Anchor handlers both accept with `?`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse::Parser, punctuated::Punctuated, spanned::Spanned, Expr, ExprLit, FnArg, Ident, ItemFn,
+    Lit, Meta, Pat, Path, Token, Type,
+};
+
+/// Check a receipt before running the handler; see the crate docs
+#[proc_macro_attribute]
+pub fn requires_proof(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand(attr.into(), item.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Where the receipt account comes from
+enum ReceiptSource {
+    /// Index into the handler's `&[AccountInfo]` argument
+    Index(usize),
+    /// Expression evaluating to `&AccountInfo`
+    Expr(Expr),
+}
+
+struct Options {
+    vk: Path,
+    inputs: Path,
+    verifier: Path,
+    receipt: ReceiptSource,
+    max_age_slots: Option<u64>,
+    bind: Option<Ident>,
+}
+
+fn parse_options(attr: TokenStream2) -> syn::Result<Options> {
+    let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(attr)?;
+    let (mut vk, mut inputs, mut verifier) = (None, None, None);
+    let mut receipt = ReceiptSource::Index(0);
+    let (mut max_age_slots, mut bind) = (None, None);
+
+    for meta in metas {
+        let Meta::NameValue(pair) = &meta else {
+            return Err(syn::Error::new(meta.span(), "expected `name = value`"));
+        };
+        let name = pair
+            .path
+            .get_ident()
+            .map(Ident::to_string)
+            .unwrap_or_default();
+        let Expr::Lit(ExprLit { lit, .. }) = &pair.value else {
+            return Err(syn::Error::new(pair.value.span(), "expected a literal"));
+        };
+        match (name.as_str(), lit) {
+            ("vk", Lit::Str(s)) => vk = Some(s.parse()?),
+            ("inputs", Lit::Str(s)) => inputs = Some(s.parse()?),
+            ("verifier", Lit::Str(s)) => verifier = Some(s.parse()?),
+            ("receipt", Lit::Int(i)) => receipt = ReceiptSource::Index(i.base10_parse()?),
+            ("receipt", Lit::Str(s)) => receipt = ReceiptSource::Expr(s.parse()?),
+            ("max_age_slots", Lit::Int(i)) => max_age_slots = Some(i.base10_parse()?),
+            ("bind", Lit::Str(s)) => bind = Some(s.parse()?),
+            ("vk" | "inputs" | "verifier" | "bind", _) => {
+                return Err(syn::Error::new(lit.span(), "expected a string"))
+            }
+            ("max_age_slots", _) => return Err(syn::Error::new(lit.span(), "expected an integer")),
+            ("receipt", _) => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "expected an account index or an expression string",
+                ))
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    pair.path.span(),
+                    format!("unknown argument `{}`", name),
+                ))
+            }
+        }
+    }
+
+    let missing = |name| syn::Error::new(Span::call_site(), format!("missing `{}`", name));
+    Ok(Options {
+        vk: vk.ok_or_else(|| missing("vk"))?,
+        inputs: inputs.ok_or_else(|| missing("inputs"))?,
+        verifier: verifier.unwrap_or_else(|| syn::parse_quote!(VERIFIER_PROGRAM)),
+        receipt,
+        max_age_slots,
+        bind,
+    })
+}
+
+fn expand(attr: TokenStream2, item: TokenStream2) -> syn::Result<TokenStream2> {
+    let options = parse_options(attr)?;
+    let mut handler: ItemFn = syn::parse2(item)?;
+
+    // Arguments as the `inputs` function receives them
+    let mut args = Vec::new();
+    let mut accounts = None;
+    for arg in &handler.sig.inputs {
+        let FnArg::Typed(arg) = arg else {
+            return Err(syn::Error::new(
+                arg.span(),
+                "#[requires_proof] does not support methods",
+            ));
+        };
+        let Pat::Ident(name) = &*arg.pat else {
+            return Err(syn::Error::new(
+                arg.pat.span(),
+                "#[requires_proof] needs named arguments",
+            ));
+        };
+        let name = &name.ident;
+        match &*arg.ty {
+            Type::Reference(reference) => {
+                if accounts.is_none() && is_account_slice(&reference.elem) {
+                    accounts = Some(name.clone());
+                }
+                args.push(quote!(#name));
+            }
+            _ => args.push(quote!(&#name)),
+        }
+    }
+
+    let receipt = match &options.receipt {
+        ReceiptSource::Expr(expr) => quote!(#expr),
+        ReceiptSource::Index(index) => {
+            let Some(accounts) = accounts else {
+                return Err(syn::Error::new(
+                    handler.sig.span(),
+                    "no `&[AccountInfo]` argument to take the receipt from; \
+                     pass `receipt = \"<expression>\"`",
+                ));
+            };
+            quote! {
+                #accounts
+                    .get(#index)
+                    .ok_or(::solana_noir_verifier_cpi::__private::ProgramError::NotEnoughAccountKeys)?
+            }
+        }
+    };
+    let max_age = options
+        .max_age_slots
+        .map(|slots| quote!(.max_age_slots(#slots)));
+    let binding = match &options.bind {
+        Some(name) => quote!(#name),
+        None => quote!(_),
+    };
+    let (vk, inputs, verifier) = (&options.vk, &options.inputs, &options.verifier);
+
+    let check: syn::Stmt = syn::parse_quote! {
+        let #binding = {
+            let __public_inputs = #inputs(#(#args),*)?;
+            let __receipt: &::solana_noir_verifier_cpi::__private::AccountInfo = #receipt;
+            ::solana_noir_verifier_cpi::require::require_verified_proof(
+                ::solana_noir_verifier_cpi::require::ProofCheck::new(__receipt, &#verifier)#max_age,
+                &#vk,
+                ::core::convert::AsRef::<[u8]>::as_ref(&__public_inputs),
+            )
+            .map_err(::solana_noir_verifier_cpi::__private::ProgramError::from)?
+        };
+    };
+    handler.block.stmts.insert(0, check);
+    Ok(quote!(#handler))
+}
+
+/// Whether `ty` is `[AccountInfo]` or `[AccountInfo<'_>]`
+fn is_account_slice(ty: &Type) -> bool {
+    let Type::Slice(slice) = ty else {
+        return false;
+    };
+    let Type::Path(path) = &*slice.elem else {
+        return false;
+    };
+    path.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "AccountInfo")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(attr: TokenStream2, item: TokenStream2) -> String {
+        expand(attr, item).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_native_handler() {
+        let code = expanded(
+            quote!(
+                vk = "MY_VK",
+                inputs = "pi",
+                receipt = 1,
+                max_age_slots = 150
+            ),
+            quote! {
+                fn handler(accounts: &[AccountInfo], data: &[u8], amount: u64) -> ProgramResult {
+                    Ok(())
+                }
+            },
+        );
+        // Reference arguments are passed as they are, others borrowed
+        assert!(code.contains("pi (accounts , data , & amount) ?"));
+        assert!(code.contains("accounts . get (1usize)"));
+        assert!(code.contains(
+            "ProofCheck :: new (__receipt , & VERIFIER_PROGRAM) . max_age_slots (150u64)"
+        ));
+        assert!(code.contains("let _ ="));
+        // The check comes before the body
+        assert!(code.find("require_verified_proof") < code.find("Ok (())"));
+    }
+
+    #[test]
+    fn test_receipt_expression_and_binding() {
+        let code = expanded(
+            quote!(
+                vk = "consts::VK",
+                inputs = "pi",
+                verifier = "crate::VERIFIER",
+                receipt = "ctx.accounts.receipt.as_ref()",
+                bind = "proof"
+            ),
+            quote! {
+                pub fn handler(ctx: Context<Vote>) -> Result<()> {
+                    msg!("{}", proof.verified_slot);
+                    Ok(())
+                }
+            },
+        );
+        assert!(code.contains("pi (& ctx) ?"));
+        assert!(code.contains("= ctx . accounts . receipt . as_ref () ;"));
+        assert!(code.contains("& crate :: VERIFIER"));
+        assert!(code.contains("& consts :: VK"));
+        assert!(code.contains("let proof ="));
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let handler = quote!(
+            fn handler(accounts: &[AccountInfo]) -> ProgramResult {
+                Ok(())
+            }
+        );
+        let error = |attr| expand(attr, handler.clone()).unwrap_err().to_string();
+        assert_eq!(error(quote!(inputs = "pi")), "missing `vk`");
+        assert_eq!(
+            error(quote!(vk = "VK", inputs = "pi", receipts = 1)),
+            "unknown argument `receipts`"
+        );
+        assert_eq!(
+            error(quote!(vk = "VK", inputs = "pi", max_age_slots = "1")),
+            "expected an integer"
+        );
+
+        // Index mode needs an accounts slice to index into
+        let no_slice = expand(
+            quote!(vk = "VK", inputs = "pi"),
+            quote!(
+                fn handler(data: &[u8]) -> ProgramResult {
+                    Ok(())
+                }
+            ),
+        );
+        assert!(no_slice.is_err());
+    }
+}
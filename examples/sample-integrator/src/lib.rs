@@ -14,7 +14,7 @@
 //! 3. User calls your program, passing the receipt account
 //! 4. Your program validates the receipt and executes business logic
 
-use solana_noir_verifier_cpi::{get_verified_slot, is_verified};
+use solana_noir_verifier_cpi::requires_proof;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     declare_id, entrypoint,
@@ -65,14 +65,36 @@ pub fn process_instruction(
 // INSTRUCTION: Protected Action (requires verified proof)
 // ============================================================================
 
+/// Public inputs of the protected action: the instruction data after the
+/// discriminator
+fn protected_action_inputs<'a>(
+    _accounts: &[AccountInfo],
+    public_inputs: &'a [u8],
+) -> Result<&'a [u8], ProgramError> {
+    Ok(public_inputs)
+}
+
 /// Process an action that requires a verified ZK proof
 ///
 /// Accounts:
 /// 0. `[]` Receipt account (PDA from verifier, user provides)
 /// 1. `[signer]` User
+///
+/// `#[requires_proof]` checks the receipt's owner, PDA and public inputs
+/// before the body runs. Add `max_age_slots = ..` to reject old receipts; to
+/// accept each proof only once, call `require_verified_proof` with
+/// `ProofCheck::consume(..)` instead.
+#[requires_proof(
+    vk = "MY_CIRCUIT_VK",
+    inputs = "protected_action_inputs",
+    receipt = 0,
+    bind = "proof"
+)]
 fn process_protected_action(accounts: &[AccountInfo], public_inputs: &[u8]) -> ProgramResult {
+    msg!("✅ Proof verified at slot {}", proof.verified_slot);
+
     let account_iter = &mut accounts.iter();
-    let receipt = next_account_info(account_iter)?;
+    let _receipt = next_account_info(account_iter)?;
     let user = next_account_info(account_iter)?;
 
     // User must sign
@@ -81,24 +103,7 @@ fn process_protected_action(accounts: &[AccountInfo], public_inputs: &[u8]) -> P
     }
 
     // =========================================================================
-    // STEP 1: Validate the proof receipt
-    // =========================================================================
-
-    msg!("Checking proof receipt...");
-
-    if !is_verified(receipt, &MY_CIRCUIT_VK, public_inputs, &VERIFIER_PROGRAM) {
-        msg!("❌ Proof not verified!");
-        msg!("   User must verify proof and create receipt first");
-        return Err(ProgramError::Custom(1)); // NotVerified
-    }
-
-    // Optional: Check when it was verified
-    if let Some(slot) = get_verified_slot(receipt) {
-        msg!("✅ Proof verified at slot {}", slot);
-    }
-
-    // =========================================================================
-    // STEP 2: Execute business logic (proof is valid!)
+    // Execute business logic (proof is valid!)
     // =========================================================================
 
     msg!("Executing protected action...");
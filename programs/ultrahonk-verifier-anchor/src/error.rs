@@ -0,0 +1,134 @@
+//! Verifier error codes
+//!
+//! Instructions are forwarded to the native verifier by CPI, and a failing
+//! CPI aborts the transaction with the verifier's own `Custom(code)`. This
+//! enum mirrors the code table in `solana-noir-verifier-errors` with
+//! `offset = 0`, so the IDL decodes those codes and the handlers' own checks
+//! report the same ones.
+//!
+//! Codes below 6000 overlap Anchor's framework errors (100-103 and 1000+),
+//! which only this program raises; the failing program in the transaction
+//! log tells them apart. `SumcheckRoundFailed` is the base of the per-round
+//! range: round N fails with `1000 + N`.
+
+use anchor_lang::prelude::*;
+
+#[error_code(offset = 0)]
+pub enum VerifierError {
+    // === Accounts ===
+    #[msg("An account that must be written was passed read-only")]
+    AccountNotWritable = 100,
+    #[msg("Verification state account has the wrong size or layout")]
+    InvalidStateAccount,
+    #[msg("The VK account differs from the one the verification was started with")]
+    VkAccountMismatch,
+    #[msg("Receipt account has the wrong address, size or layout")]
+    InvalidReceiptAccount,
+    #[msg("Signer is not the account that paid for the receipt")]
+    NotReceiptPayer,
+    #[msg("The proof buffer differs from the one the verification was started with")]
+    ProofBufferMismatch,
+    #[msg("State challenges no longer match the digest Phase 1 recorded")]
+    ChallengeDigestMismatch,
+    #[msg("The MSM scratch account does not match the verification state")]
+    MsmScratchMismatch,
+    #[msg("Receipt already exists and the duplicate policy is Error")]
+    ReceiptAlreadyExists,
+    #[msg("Verification state was already recorded by a receipt")]
+    StateAlreadyRecorded,
+    #[msg("Account passed to receive a closed receipt's rent is not its beneficiary")]
+    NotReceiptBeneficiary,
+
+    // === Buffers ===
+    #[msg("Proof buffer has not received all of its data")]
+    ProofBufferNotReady = 200,
+    #[msg("Proof buffer is marked ready but chunks are missing")]
+    ProofBufferIncomplete,
+    #[msg("VK buffer is not in the status the instruction requires")]
+    VkBufferNotReady,
+    #[msg("VK buffer holds fewer bytes than a full VK")]
+    VkBufferIncomplete,
+    #[msg("VK was already uploaded in full or compiled and can no longer be written")]
+    VkAlreadyCompiled,
+    #[msg("Proof buffer is locked by a verification and can no longer be written")]
+    ProofBufferLocked,
+    #[msg("Shard is missing from, or not bound to, its manifest's position")]
+    InvalidShard,
+
+    // === Inputs ===
+    #[msg("VK bytes failed to parse or validate")]
+    InvalidVk = 300,
+    #[msg("Proof bytes failed to parse or contain invalid points")]
+    InvalidProof,
+    #[msg("Public inputs do not match what the VK expects")]
+    PublicInputMismatch,
+    #[msg("Custom SRS point in the VK account is not a valid G2 point")]
+    InvalidSrsG2,
+    #[msg("Supplied proof digest differs from the one the receipt recorded")]
+    ProofDigestMismatch,
+    #[msg("Circuit's log_n exceeds MAX_LOG_N or one of its phases cannot fit a transaction")]
+    CircuitTooLarge,
+    #[msg("Relation coefficients differ from the ones Phase 2c committed to")]
+    RelationCoefficientsMismatch,
+
+    // === Phases ===
+    #[msg("Instruction is not valid in the current (sub-)phase")]
+    InvalidPhase = 400,
+    #[msg("Sumcheck round batch does not continue from the last completed round")]
+    RoundOutOfOrder,
+    #[msg("Relations were checked before every sumcheck round was verified")]
+    SumcheckIncomplete,
+    #[msg("Verification has not completed successfully")]
+    VerificationNotComplete,
+    #[msg("Instruction batches more work than fits one transaction for this circuit")]
+    PhaseBatchTooLarge,
+
+    // === Verification ===
+    #[msg("Fiat-Shamir challenge generation failed")]
+    ChallengeGenerationFailed = 500,
+    #[msg("Sumcheck failed outside of a specific round")]
+    SumcheckFailed,
+    #[msg("Final sumcheck relation check failed")]
+    RelationsFailed,
+    #[msg("Shplemini batching / MSM failed")]
+    MsmFailed,
+    #[msg("Final pairing check did not hold")]
+    PairingFailed,
+    #[msg("Round sum check failed in sumcheck round (code - 1000)")]
+    SumcheckRoundFailed = 1000,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_match_native_verifier() {
+        let codes = [
+            (VerifierError::AccountNotWritable, 100),
+            (VerifierError::ProofBufferMismatch, 105),
+            (VerifierError::ChallengeDigestMismatch, 106),
+            (VerifierError::MsmScratchMismatch, 107),
+            (VerifierError::ReceiptAlreadyExists, 108),
+            (VerifierError::StateAlreadyRecorded, 109),
+            (VerifierError::NotReceiptBeneficiary, 110),
+            (VerifierError::ProofBufferNotReady, 200),
+            (VerifierError::ProofBufferLocked, 205),
+            (VerifierError::InvalidShard, 206),
+            (VerifierError::InvalidVk, 300),
+            (VerifierError::InvalidSrsG2, 303),
+            (VerifierError::ProofDigestMismatch, 304),
+            (VerifierError::CircuitTooLarge, 305),
+            (VerifierError::RelationCoefficientsMismatch, 306),
+            (VerifierError::InvalidPhase, 400),
+            (VerifierError::VerificationNotComplete, 403),
+            (VerifierError::PhaseBatchTooLarge, 404),
+            (VerifierError::ChallengeGenerationFailed, 500),
+            (VerifierError::PairingFailed, 504),
+            (VerifierError::SumcheckRoundFailed, 1000),
+        ];
+        for (error, code) in codes {
+            assert_eq!(u32::from(error), code, "{:?}", error);
+        }
+    }
+}
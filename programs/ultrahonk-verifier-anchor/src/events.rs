@@ -0,0 +1,45 @@
+//! Events emitted after successful verifier instructions
+//!
+//! Indexers can follow verifications through these instead of decoding the
+//! native program's instruction data and logs.
+
+use anchor_lang::prelude::*;
+
+/// Phase 1 locked a proof buffer and bound it to a verification state
+#[event]
+pub struct VerificationStarted {
+    pub state: Pubkey,
+    pub proof_buffer: Pubkey,
+    pub vk_account: Pubkey,
+}
+
+/// A receipt was created for a verified proof, or refreshed or counted
+/// again under its duplicate policy
+#[event]
+pub struct ProofVerified {
+    pub receipt: Pubkey,
+    pub vk_account: Pubkey,
+    /// Keccak256 of the public inputs, the last receipt seed
+    pub public_inputs_hash: [u8; 32],
+    pub payer: Pubkey,
+    pub slot: u64,
+    /// Application-defined tag stored in the receipt (zero if none)
+    pub tag: [u8; 32],
+    /// Verifications the receipt has counted so far
+    pub verification_count: u32,
+}
+
+/// A receipt was closed and its rent refunded to its beneficiary
+#[event]
+pub struct ReceiptClosed {
+    pub receipt: Pubkey,
+    pub rent_payer: Pubkey,
+    pub rent_beneficiary: Pubkey,
+}
+
+/// A locked proof buffer was unlocked and its state zeroed
+#[event]
+pub struct ProofBufferReset {
+    pub proof_buffer: Pubkey,
+    pub state: Pubkey,
+}
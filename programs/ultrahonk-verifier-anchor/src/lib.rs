@@ -0,0 +1,721 @@
+//! Anchor interface to the UltraHonk verifier
+//!
+//! Exposes the `ultrahonk-verifier` instruction set as an Anchor program, so
+//! Anchor-first teams get an IDL, typed clients and constraint-checked
+//! accounts. Each instruction checks its accounts, forwards to the native
+//! verifier by CPI and emits an event where one is useful. Verification
+//! itself stays in the native program (and in `plonk-solana-core`, which
+//! this program also uses for receipt seeds), so proof buffers, states, VK
+//! accounts and receipts are owned by the verifier whichever program was
+//! called, and receipts from either path are interchangeable.
+//!
+//! Forwarding costs a few thousand compute units per instruction on top of
+//! the verifier's own. Only the instructions the SDK's phase layouts use are
+//! exposed: the deprecated single-transaction `Verify`, the original Phase
+//! 1-3 instructions and the Phase 1 sub-phases are native-only.
+//!
+//! ## Instructions
+//!
+//! | Instruction | Native tag |
+//! |-------------|------------|
+//! | `init_buffer`, `upload_chunk`, `set_public_inputs`, `reset_buffer` | 0, 1, 3, 7 |
+//! | `init_vk_buffer`, `upload_vk_chunk`, `compile_vk` | 4, 5, 6 |
+//! | `final_check` | 13 |
+//! | `phase1_full` | 30 |
+//! | `phase2_rounds`, `phase2d_relations`, `phase2d_relations_part1/2` | 40, 43-45 |
+//! | `phase3a_weights`, `phase3b1_folding`, `phase3b2_gemini`, `phase3c_and_pairing` | 50-52, 54 |
+//! | `phase2d_and_3a`, `phase3b_combined` | 55, 56 |
+//! | `create_receipt`, `create_receipt_with_options`, `close_receipt`, `reverify_by_digest` | 60, 60, 61, 62 |
+//! | `close_accounts` | 70 |
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use plonk_solana_core::receipt::{public_inputs_hash, RECEIPT_SEED};
+
+pub mod error;
+pub mod events;
+
+pub use error::VerifierError;
+pub use events::*;
+
+declare_id!("5i4aQeTQ8fxBbgqafbP9ES8dD62dmpLABBKHLKVMWVp2");
+
+/// The native UltraHonk verifier program
+pub const VERIFIER_PROGRAM_ID: Pubkey = pubkey!("GrBZJ7YpCKijTHwkuWfRF1Jti3xngdEV1geAcgk8aoNk");
+
+/// Proof buffer header: status (1) + proof_len (2) + pi_count (2) + chunk_bitmap (4)
+pub const BUFFER_HEADER_SIZE: usize = 9;
+
+/// Receipt account of the native verifier
+///
+/// The Borsh encoding of this struct is the receipt account's 248-byte
+/// layout, so clients can decode receipt accounts with it directly.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReceipt {
+    /// Slot when verification completed
+    pub verified_slot: u64,
+    /// Unix timestamp when verification completed
+    pub verified_timestamp: i64,
+    /// VK account the proof was verified against
+    pub vk_account: Pubkey,
+    /// Keccak256 of the public inputs
+    pub pi_hash: [u8; 32],
+    /// Receipt PDA bump
+    pub bump: u8,
+    /// Padding to 4-byte alignment
+    pub reserved: [u8; 3],
+    /// Verifications counted under `DuplicateReceiptPolicy::Count`,
+    /// starting at 1
+    pub verification_count: u32,
+    /// Account that paid the receipt's rent; only it may close the receipt
+    pub rent_payer: Pubkey,
+    /// Application-defined tag the creator attached (zero if none)
+    pub tag: [u8; 32],
+    /// Keccak256 of the verified proof bytes
+    pub proof_digest: [u8; 32],
+    /// Canonical hash of the VK and its SRS point, the same for every
+    /// account holding them
+    pub vk_hash: [u8; 32],
+    /// Account the rent is refunded to when the receipt is closed
+    pub rent_beneficiary: Pubkey,
+}
+
+/// What `create_receipt_with_options` does when the receipt already exists
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateReceiptPolicy {
+    /// Fail with `ReceiptAlreadyExists`
+    #[default]
+    Error,
+    /// Move the receipt's slot and timestamp to the new verification
+    Refresh,
+    /// Increment the receipt's verification count
+    Count,
+}
+
+/// Optional arguments of `create_receipt_with_options`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReceiptOptions {
+    /// Application-defined tag to store in the receipt (a nullifier, an
+    /// order id, a commitment)
+    pub tag: [u8; 32],
+    /// Behavior when the receipt already exists
+    pub on_duplicate: DuplicateReceiptPolicy,
+    /// Account to refund the rent to on close, the payer if `None`
+    pub rent_beneficiary: Option<Pubkey>,
+}
+
+/// The native verifier, for `Program<'info, UltrahonkVerifier>` accounts
+#[derive(Clone)]
+pub struct UltrahonkVerifier;
+
+impl Id for UltrahonkVerifier {
+    fn id() -> Pubkey {
+        VERIFIER_PROGRAM_ID
+    }
+}
+
+/// Native instruction tags (`ultrahonk_verifier::Instruction`)
+mod tag {
+    pub const INIT_BUFFER: u8 = 0;
+    pub const UPLOAD_CHUNK: u8 = 1;
+    pub const SET_PUBLIC_INPUTS: u8 = 3;
+    pub const INIT_VK_BUFFER: u8 = 4;
+    pub const UPLOAD_VK_CHUNK: u8 = 5;
+    pub const COMPILE_VK: u8 = 6;
+    pub const RESET_BUFFER: u8 = 7;
+    pub const FINAL_CHECK: u8 = 13;
+    pub const PHASE1_FULL: u8 = 30;
+    pub const PHASE2_ROUNDS: u8 = 40;
+    pub const PHASE2D_RELATIONS: u8 = 43;
+    pub const PHASE2D_RELATIONS_PART1: u8 = 44;
+    pub const PHASE2D_RELATIONS_PART2: u8 = 45;
+    pub const PHASE3A_WEIGHTS: u8 = 50;
+    pub const PHASE3B1_FOLDING: u8 = 51;
+    pub const PHASE3B2_GEMINI: u8 = 52;
+    pub const PHASE3C_AND_PAIRING: u8 = 54;
+    pub const PHASE2D_AND_3A: u8 = 55;
+    pub const PHASE3B_COMBINED: u8 = 56;
+    pub const CREATE_RECEIPT: u8 = 60;
+    pub const CLOSE_RECEIPT: u8 = 61;
+    pub const REVERIFY_BY_DIGEST: u8 = 62;
+    pub const CLOSE_ACCOUNTS: u8 = 70;
+}
+
+#[program]
+pub mod ultrahonk_verifier_anchor {
+    use super::*;
+
+    // === Proof buffer ===
+
+    /// Initialize a proof buffer for `num_public_inputs` public inputs
+    pub fn init_buffer(ctx: Context<InitBuffer>, num_public_inputs: u16) -> Result<()> {
+        let mut accounts = vec![ctx.accounts.proof_buffer.to_account_info()];
+        accounts.extend(ctx.accounts.authority.as_ref().map(|a| a.to_account_info()));
+        forward(
+            &ctx.accounts.verifier_program,
+            with_tag(tag::INIT_BUFFER, &num_public_inputs.to_le_bytes()),
+            accounts,
+        )
+    }
+
+    /// Write `chunk` at `offset` into the proof
+    pub fn upload_chunk(ctx: Context<ProofBuffer>, offset: u16, chunk: Vec<u8>) -> Result<()> {
+        let mut data = with_tag(tag::UPLOAD_CHUNK, &offset.to_le_bytes());
+        data.extend_from_slice(&chunk);
+        forward(
+            &ctx.accounts.verifier_program,
+            data,
+            vec![ctx.accounts.proof_buffer.to_account_info()],
+        )
+    }
+
+    /// Write the public inputs (32 bytes each)
+    pub fn set_public_inputs(ctx: Context<ProofBuffer>, public_inputs: Vec<u8>) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            with_tag(tag::SET_PUBLIC_INPUTS, &public_inputs),
+            vec![ctx.accounts.proof_buffer.to_account_info()],
+        )
+    }
+
+    /// Unlock a proof buffer and zero the verification state bound to it
+    pub fn reset_buffer(ctx: Context<ResetBuffer>) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            vec![tag::RESET_BUFFER],
+            vec![
+                ctx.accounts.proof_buffer.to_account_info(),
+                ctx.accounts.state.to_account_info(),
+            ],
+        )?;
+        emit!(ProofBufferReset {
+            proof_buffer: ctx.accounts.proof_buffer.key(),
+            state: ctx.accounts.state.key(),
+        });
+        Ok(())
+    }
+
+    // === VK account ===
+
+    /// Initialize a VK buffer
+    pub fn init_vk_buffer(ctx: Context<VkBuffer>) -> Result<()> {
+        forward_vk(ctx, vec![tag::INIT_VK_BUFFER])
+    }
+
+    /// Write `chunk` at `offset` into the VK (or the SRS point past it)
+    pub fn upload_vk_chunk(ctx: Context<VkBuffer>, offset: u16, chunk: Vec<u8>) -> Result<()> {
+        let mut data = with_tag(tag::UPLOAD_VK_CHUNK, &offset.to_le_bytes());
+        data.extend_from_slice(&chunk);
+        forward_vk(ctx, data)
+    }
+
+    /// Validate the uploaded VK and rewrite it in the compiled layout
+    pub fn compile_vk(ctx: Context<VkBuffer>) -> Result<()> {
+        forward_vk(ctx, vec![tag::COMPILE_VK])
+    }
+
+    // === Phases ===
+
+    /// Phase 1: lock the proof buffer and generate every challenge
+    pub fn phase1_full(ctx: Context<Phase1>) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            vec![tag::PHASE1_FULL],
+            vec![
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.proof_buffer.to_account_info(),
+                ctx.accounts.vk_account.to_account_info(),
+            ],
+        )?;
+        emit!(VerificationStarted {
+            state: ctx.accounts.state.key(),
+            proof_buffer: ctx.accounts.proof_buffer.key(),
+            vk_account: ctx.accounts.vk_account.key(),
+        });
+        Ok(())
+    }
+
+    /// Phase 2: verify sumcheck rounds `start_round..end_round`
+    pub fn phase2_rounds(ctx: Context<Phase>, start_round: u8, end_round: u8) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE2_ROUNDS, start_round, end_round])
+    }
+
+    /// Phase 2d: relations and the final sumcheck check
+    pub fn phase2d_relations(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE2D_RELATIONS])
+    }
+
+    /// Phase 2d1: arithmetic, permutation, range and elliptic relations
+    pub fn phase2d_relations_part1(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE2D_RELATIONS_PART1])
+    }
+
+    /// Phase 2d2: lookup, memory and Poseidon relations and the final check
+    pub fn phase2d_relations_part2(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE2D_RELATIONS_PART2])
+    }
+
+    /// Phase 3a: Shplemini weights
+    pub fn phase3a_weights(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE3A_WEIGHTS])
+    }
+
+    /// Phase 3b1: folding rounds
+    pub fn phase3b1_folding(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE3B1_FOLDING])
+    }
+
+    /// Phase 3b2: Gemini and Libra scalars
+    pub fn phase3b2_gemini(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE3B2_GEMINI])
+    }
+
+    /// Phases 2d and 3a in one transaction
+    pub fn phase2d_and_3a(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE2D_AND_3A])
+    }
+
+    /// Phases 3b1 and 3b2 in one transaction
+    pub fn phase3b_combined(ctx: Context<Phase>) -> Result<()> {
+        forward_phase(ctx, vec![tag::PHASE3B_COMBINED])
+    }
+
+    /// Phase 3c and 4: MSM and the pairing check
+    pub fn phase3c_and_pairing(ctx: Context<FinalPhase>) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            vec![tag::PHASE3C_AND_PAIRING],
+            vec![
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.proof_buffer.to_account_info(),
+                ctx.accounts.vk_account.to_account_info(),
+            ],
+        )
+    }
+
+    /// Phase 4: the pairing check after a separate MSM
+    pub fn final_check(ctx: Context<FinalCheck>) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            vec![tag::FINAL_CHECK],
+            vec![
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.vk_account.to_account_info(),
+            ],
+        )
+    }
+
+    // === Receipts and cleanup ===
+
+    /// Create the receipt PDA for a completed verification
+    ///
+    /// `public_inputs_hash` must be the Keccak256 of the public inputs in the
+    /// proof buffer; it is taken as an argument so the receipt address can be
+    /// derived (and declared in the IDL) from the instruction alone. Returns
+    /// the new receipt.
+    pub fn create_receipt(
+        ctx: Context<CreateReceipt>,
+        public_inputs_hash: [u8; 32],
+    ) -> Result<VerificationReceipt> {
+        create_receipt_with_options(ctx, public_inputs_hash, ReceiptOptions::default())
+    }
+
+    /// [`create_receipt`] with a tag stored in the receipt for integrators
+    /// to read over CPI, or a policy to refresh or count an existing receipt
+    pub fn create_receipt_with_options(
+        ctx: Context<CreateReceipt>,
+        public_inputs_hash: [u8; 32],
+        options: ReceiptOptions,
+    ) -> Result<VerificationReceipt> {
+        require!(
+            buffer_public_inputs_hash(&ctx.accounts.proof_buffer)? == public_inputs_hash,
+            VerifierError::PublicInputMismatch
+        );
+
+        forward(
+            &ctx.accounts.verifier_program,
+            create_receipt_data(&options),
+            vec![
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.proof_buffer.to_account_info(),
+                ctx.accounts.vk_account.to_account_info(),
+                ctx.accounts.receipt.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        let receipt =
+            VerificationReceipt::deserialize(&mut &ctx.accounts.receipt.try_borrow_data()?[..])?;
+        emit!(ProofVerified {
+            receipt: ctx.accounts.receipt.key(),
+            vk_account: ctx.accounts.vk_account.key(),
+            public_inputs_hash,
+            payer: ctx.accounts.payer.key(),
+            slot: receipt.verified_slot,
+            tag: receipt.tag,
+            verification_count: receipt.verification_count,
+        });
+        Ok(receipt)
+    }
+
+    /// Close a receipt and refund its rent to the beneficiary it recorded
+    pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+        let mut accounts = vec![
+            ctx.accounts.receipt.to_account_info(),
+            ctx.accounts.rent_payer.to_account_info(),
+        ];
+        accounts.extend(
+            ctx.accounts
+                .rent_beneficiary
+                .as_ref()
+                .map(|a| a.to_account_info()),
+        );
+        forward(
+            &ctx.accounts.verifier_program,
+            vec![tag::CLOSE_RECEIPT],
+            accounts,
+        )?;
+        emit!(ReceiptClosed {
+            receipt: ctx.accounts.receipt.key(),
+            rent_payer: ctx.accounts.rent_payer.key(),
+            rent_beneficiary: ctx
+                .accounts
+                .rent_beneficiary
+                .as_ref()
+                .map_or(ctx.accounts.rent_payer.key(), |a| a.key()),
+        });
+        Ok(())
+    }
+
+    /// Fail unless `proof_digest` is the Keccak256 of the proof the receipt
+    /// was created from
+    pub fn reverify_by_digest(
+        ctx: Context<ReverifyByDigest>,
+        proof_digest: [u8; 32],
+    ) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            with_tag(tag::REVERIFY_BY_DIGEST, &proof_digest),
+            vec![ctx.accounts.receipt.to_account_info()],
+        )
+    }
+
+    /// Close a finished verification's state and proof buffer
+    pub fn close_accounts(ctx: Context<CloseAccounts>) -> Result<()> {
+        forward(
+            &ctx.accounts.verifier_program,
+            vec![tag::CLOSE_ACCOUNTS],
+            vec![
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.proof_buffer.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+            ],
+        )
+    }
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitBuffer<'info> {
+    /// CHECK: verifier-owned proof buffer; the verifier checks its size
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    /// Authority of a sponsored upload, when it differs from the payer
+    pub authority: Option<Signer<'info>>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct ProofBuffer<'info> {
+    /// CHECK: verifier-owned proof buffer; the verifier checks its status
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct ResetBuffer<'info> {
+    /// CHECK: verifier-owned proof buffer, bound to `state`
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned verification state
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct VkBuffer<'info> {
+    /// CHECK: verifier-owned VK account; the verifier checks its status
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub vk_account: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct Phase1<'info> {
+    /// CHECK: verifier-owned verification state
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned proof buffer, locked by this phase
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned VK account, recorded in the state
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub vk_account: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct Phase<'info> {
+    /// CHECK: verifier-owned verification state
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned proof buffer; the verifier checks it is bound to `state`
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct FinalPhase<'info> {
+    /// CHECK: verifier-owned verification state
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned proof buffer; the verifier checks it is bound to `state`
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned VK account; the verifier checks it against the state
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub vk_account: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct FinalCheck<'info> {
+    /// CHECK: verifier-owned verification state
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned VK account; the verifier checks it against the state
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub vk_account: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+#[instruction(public_inputs_hash: [u8; 32])]
+pub struct CreateReceipt<'info> {
+    /// CHECK: verifier-owned verification state; the verifier checks it
+    /// completed and marks it as recorded
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned proof buffer holding the public inputs
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned VK account, the first receipt seed
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub vk_account: UncheckedAccount<'info>,
+    /// CHECK: receipt PDA of the verifier, created (or updated) by it
+    #[account(
+        mut,
+        seeds = [RECEIPT_SEED, vk_account.key().as_ref(), public_inputs_hash.as_ref()],
+        seeds::program = VERIFIER_PROGRAM_ID,
+        bump
+    )]
+    pub receipt: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReceipt<'info> {
+    /// CHECK: verifier-owned receipt; the verifier checks the rent payer
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub receipt: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    /// CHECK: receives the rent; the verifier checks it is the receipt's beneficiary
+    #[account(mut)]
+    pub rent_beneficiary: Option<UncheckedAccount<'info>>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct ReverifyByDigest<'info> {
+    /// CHECK: verifier-owned receipt; the verifier checks its address and digest
+    #[account(owner = VERIFIER_PROGRAM_ID)]
+    pub receipt: UncheckedAccount<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAccounts<'info> {
+    /// CHECK: verifier-owned verification state; the verifier checks it finished
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: verifier-owned proof buffer
+    #[account(mut, owner = VERIFIER_PROGRAM_ID)]
+    pub proof_buffer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub verifier_program: Program<'info, UltrahonkVerifier>,
+}
+
+// ============================================================================
+// Forwarding
+// ============================================================================
+
+/// `tag` followed by `args`
+fn with_tag(tag: u8, args: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + args.len());
+    data.push(tag);
+    data.extend_from_slice(args);
+    data
+}
+
+/// Native `CreateReceipt` data: the tag alone, followed by the options
+/// unless they are all defaults
+fn create_receipt_data(options: &ReceiptOptions) -> Vec<u8> {
+    if *options == ReceiptOptions::default() {
+        return vec![tag::CREATE_RECEIPT];
+    }
+    let mut data = with_tag(tag::CREATE_RECEIPT, &options.tag);
+    if options.on_duplicate != DuplicateReceiptPolicy::Error || options.rent_beneficiary.is_some() {
+        data.push(options.on_duplicate as u8);
+    }
+    if let Some(beneficiary) = options.rent_beneficiary {
+        data.extend_from_slice(beneficiary.as_ref());
+    }
+    data
+}
+
+/// Invoke the verifier with `data`, passing `accounts` in order with the
+/// signer and writable flags they came with
+fn forward<'info>(
+    verifier_program: &Program<'info, UltrahonkVerifier>,
+    data: Vec<u8>,
+    mut accounts: Vec<AccountInfo<'info>>,
+) -> Result<()> {
+    let instruction = Instruction {
+        program_id: VERIFIER_PROGRAM_ID,
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    accounts.push(verifier_program.to_account_info());
+    invoke(&instruction, &accounts).map_err(Into::into)
+}
+
+fn forward_vk(ctx: Context<VkBuffer>, data: Vec<u8>) -> Result<()> {
+    forward(
+        &ctx.accounts.verifier_program,
+        data,
+        vec![ctx.accounts.vk_account.to_account_info()],
+    )
+}
+
+fn forward_phase(ctx: Context<Phase>, data: Vec<u8>) -> Result<()> {
+    forward(
+        &ctx.accounts.verifier_program,
+        data,
+        vec![
+            ctx.accounts.state.to_account_info(),
+            ctx.accounts.proof_buffer.to_account_info(),
+        ],
+    )
+}
+
+/// Keccak256 of the public inputs stored in a proof buffer
+fn buffer_public_inputs_hash(proof_buffer: &AccountInfo) -> Result<[u8; 32]> {
+    let data = proof_buffer.try_borrow_data()?;
+    public_inputs(&data)
+        .map(public_inputs_hash)
+        .ok_or_else(|| VerifierError::ProofBufferNotReady.into())
+}
+
+/// Public inputs of a proof buffer, after its header
+fn public_inputs(buffer: &[u8]) -> Option<&[u8]> {
+    let count = u16::from_le_bytes(buffer.get(3..5)?.try_into().ok()?) as usize;
+    buffer.get(BUFFER_HEADER_SIZE..BUFFER_HEADER_SIZE + count * 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_inputs_follow_header() {
+        let mut buffer = vec![0u8; BUFFER_HEADER_SIZE + 64 + 16];
+        buffer[3..5].copy_from_slice(&2u16.to_le_bytes());
+        buffer[BUFFER_HEADER_SIZE] = 0xaa;
+        buffer[BUFFER_HEADER_SIZE + 63] = 0xbb;
+
+        let inputs = public_inputs(&buffer).unwrap();
+        assert_eq!(inputs.len(), 64);
+        assert_eq!((inputs[0], inputs[63]), (0xaa, 0xbb));
+
+        // A header claiming more inputs than the account holds
+        buffer[3..5].copy_from_slice(&3u16.to_le_bytes());
+        assert!(public_inputs(&buffer).is_none());
+        assert!(public_inputs(&buffer[..4]).is_none());
+    }
+
+    #[test]
+    fn test_create_receipt_data() {
+        assert_eq!(
+            create_receipt_data(&ReceiptOptions::default()),
+            [tag::CREATE_RECEIPT]
+        );
+
+        let beneficiary = Pubkey::new_unique();
+        let data = create_receipt_data(&ReceiptOptions {
+            tag: [7; 32],
+            rent_beneficiary: Some(beneficiary),
+            ..ReceiptOptions::default()
+        });
+        assert_eq!(data.len(), 66);
+        assert_eq!(data[1..33], [7; 32]);
+        assert_eq!(data[33], DuplicateReceiptPolicy::Error as u8);
+        assert_eq!(data[34..], beneficiary.to_bytes());
+    }
+
+    #[test]
+    fn test_receipt_matches_account_layout() {
+        let mut data = [0u8; 248];
+        data[..8].copy_from_slice(&42u64.to_le_bytes());
+        data[16] = 0xaa; // first byte of vk_account
+        data[80] = 254; // bump
+        data[84] = 3; // verification_count
+        data[119] = 0xbb; // last byte of rent_payer
+        data[151] = 0xcc; // last byte of tag
+        data[183] = 0xdd; // last byte of proof_digest
+        data[215] = 0xee; // last byte of vk_hash
+        data[247] = 0xff; // last byte of rent_beneficiary
+
+        let receipt = VerificationReceipt::try_from_slice(&data).unwrap();
+        assert_eq!(receipt.verified_slot, 42);
+        assert_eq!(receipt.vk_account.to_bytes()[0], 0xaa);
+        assert_eq!(receipt.bump, 254);
+        assert_eq!(receipt.verification_count, 3);
+        assert_eq!(receipt.rent_payer.to_bytes()[31], 0xbb);
+        assert_eq!(receipt.tag[31], 0xcc);
+        assert_eq!(receipt.proof_digest[31], 0xdd);
+        assert_eq!(receipt.vk_hash[31], 0xee);
+        assert_eq!(receipt.rent_beneficiary.to_bytes()[31], 0xff);
+        assert_eq!(receipt.try_to_vec().unwrap(), data);
+    }
+}
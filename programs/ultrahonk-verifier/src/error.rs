@@ -0,0 +1,24 @@
+//! Program error codes
+//!
+//! Every handler failure is surfaced as `ProgramError::Custom(code)` so the
+//! cause can be read from a transaction log or simulation result without
+//! digging through `msg!` output. The codes and their messages are defined
+//! once in `solana-noir-verifier-errors`, which the SDK and CLI decode them
+//! with; see that crate for the table.
+
+use plonk_solana_core::VerifyError;
+
+pub use solana_noir_verifier_errors::{ErrorCode, VerifierError, SUMCHECK_ROUND_FAILED_BASE};
+
+/// Classify an error from challenge generation
+///
+/// Transcript construction is where the proof, VK and public inputs are
+/// first read together, so input problems surface here too.
+pub fn challenge_error(e: &VerifyError) -> VerifierError {
+    match e {
+        VerifyError::PublicInput(_) => VerifierError::PublicInputMismatch,
+        VerifyError::Key(_) => VerifierError::InvalidVk,
+        VerifyError::Proof(_) | VerifyError::InvalidPoint { .. } => VerifierError::InvalidProof,
+        _ => VerifierError::ChallengeGenerationFailed,
+    }
+}
@@ -0,0 +1,213 @@
+//! Program heap allocator (`custom-heap` feature)
+//!
+//! The runtime's default allocator bumps downward through a 32 KB heap and
+//! never frees, so every temporary buffer and every `Vec` regrowth stays
+//! allocated until the instruction ends. This allocator keeps the same
+//! layout but gives back the most recent allocation when it is freed or
+//! resized, which covers the short-lived scratch buffers of the phase
+//! functions and growing a `Vec` that was allocated last. It also records the
+//! high-water mark, which the `heap-audit` feature logs after every
+//! instruction.
+//!
+//! With `large-heap` the allocator uses 256 KB. Transactions must then
+//! request the larger heap frame with the compute budget program (see
+//! `FeeConfig::with_heap_frame` in the Rust SDK), or allocations beyond
+//! 32 KB fault.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::size_of;
+use core::ptr::null_mut;
+
+/// Heap size the allocator hands out
+pub const HEAP_LENGTH: usize = if cfg!(feature = "large-heap") {
+    256 * 1024
+} else {
+    solana_program::entrypoint::HEAP_LENGTH
+};
+
+#[cfg(all(feature = "custom-heap", target_os = "solana"))]
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator {
+    start: solana_program::entrypoint::HEAP_START_ADDRESS as usize,
+    len: HEAP_LENGTH,
+};
+
+/// Allocator state, stored in the first bytes of the heap because SBF
+/// programs have no writable statics
+#[repr(C)]
+struct Header {
+    /// Lowest allocated address; 0 until the first allocation
+    top: usize,
+    /// Most bytes allocated at once
+    high_water: usize,
+}
+
+/// Downward bump allocator that reclaims its most recent allocation
+pub struct BumpAllocator {
+    start: usize,
+    len: usize,
+}
+
+impl BumpAllocator {
+    /// Allocator over `arena`, for tests on the host
+    ///
+    /// # Safety
+    /// `arena` must outlive the allocator and every allocation from it, and
+    /// must not be accessed otherwise while they are alive.
+    pub unsafe fn new(arena: &mut [u8]) -> Self {
+        assert!(arena.len() > size_of::<Header>());
+        assert_eq!(arena.as_ptr() as usize % core::mem::align_of::<Header>(), 0);
+        let allocator = Self {
+            start: arena.as_mut_ptr() as usize,
+            len: arena.len(),
+        };
+        *allocator.header() = Header {
+            top: 0,
+            high_water: 0,
+        };
+        allocator
+    }
+
+    /// Bytes allocated now and at most, header included
+    pub fn usage(&self) -> (usize, usize) {
+        // SAFETY: the header lies at the start of the heap the allocator owns
+        let header = unsafe { &*self.header() };
+        (self.used(header.top), header.high_water)
+    }
+
+    fn header(&self) -> *mut Header {
+        self.start as *mut Header
+    }
+
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    fn used(&self, top: usize) -> usize {
+        match top {
+            0 => size_of::<Header>(),
+            top => self.end() - top + size_of::<Header>(),
+        }
+    }
+
+    /// Move the top to `top`, recording the high-water mark
+    fn set_top(&self, header: &mut Header, top: usize) {
+        header.top = top;
+        header.high_water = header.high_water.max(self.used(top));
+    }
+}
+
+unsafe impl GlobalAlloc for BumpAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let header = &mut *self.header();
+        let top = match header.top {
+            0 => self.end(),
+            top => top,
+        };
+        let pos = top.saturating_sub(layout.size()) & !(layout.align() - 1);
+        if pos < self.start + size_of::<Header>() {
+            return null_mut();
+        }
+        self.set_top(header, pos);
+        pos as *mut u8
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let header = &mut *self.header();
+        if ptr as usize == header.top {
+            // Alignment padding above the allocation stays lost
+            header.top = ptr as usize + layout.size();
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let header = &mut *self.header();
+        if ptr as usize != header.top {
+            // Not the newest allocation: copy it, as the default allocator does
+            let new = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+            if !new.is_null() {
+                core::ptr::copy_nonoverlapping(ptr, new, layout.size().min(new_size));
+            }
+            return new;
+        }
+
+        // Newest allocation: keep its end and move its start, in place
+        let end = ptr as usize + layout.size();
+        let pos = end.saturating_sub(new_size) & !(layout.align() - 1);
+        if pos < self.start + size_of::<Header>() {
+            return null_mut();
+        }
+        core::ptr::copy(ptr, pos as *mut u8, layout.size().min(new_size));
+        self.set_top(header, pos);
+        pos as *mut u8
+    }
+}
+
+/// Log the heap in use and its high-water mark for this instruction
+#[cfg(all(feature = "heap-audit", target_os = "solana"))]
+pub fn log_usage() {
+    let (used, high_water) = ALLOCATOR.usage();
+    solana_program::msg!(
+        "Heap: {} bytes high-water, {} in use, of {}",
+        high_water,
+        used,
+        HEAP_LENGTH
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(align(16))]
+    struct Arena([u8; 1024]);
+
+    #[test]
+    fn test_reclaims_newest_allocation() {
+        let mut arena = Arena([0; 1024]);
+        let heap = unsafe { BumpAllocator::new(&mut arena.0) };
+        let header = size_of::<Header>();
+        let layout = Layout::from_size_align(64, 8).unwrap();
+
+        unsafe {
+            let a = heap.alloc(layout);
+            let b = heap.alloc(layout);
+            assert_eq!(heap.usage(), (header + 128, header + 128));
+
+            // Freeing the newest allocation gives it back; older ones stay
+            heap.dealloc(b, layout);
+            assert_eq!(heap.usage(), (header + 64, header + 128));
+            heap.dealloc(a, layout);
+            assert_eq!(heap.usage(), (header, header + 128));
+        }
+    }
+
+    #[test]
+    fn test_realloc_in_place_keeps_contents() {
+        let mut arena = Arena([0; 1024]);
+        let heap = unsafe { BumpAllocator::new(&mut arena.0) };
+        let header = size_of::<Header>();
+        let layout = Layout::from_size_align(16, 8).unwrap();
+
+        unsafe {
+            let ptr = heap.alloc(layout);
+            core::ptr::copy_nonoverlapping([7u8; 16].as_ptr(), ptr, 16);
+
+            // Growing the newest allocation uses no extra space
+            let grown = heap.realloc(ptr, layout, 48);
+            assert_eq!(core::slice::from_raw_parts(grown, 16), &[7u8; 16]);
+            assert_eq!(heap.usage().0, header + 48);
+
+            // Growing an older one copies it
+            let _newer = heap.alloc(layout);
+            let moved = heap.realloc(grown, Layout::from_size_align(48, 8).unwrap(), 64);
+            assert_eq!(core::slice::from_raw_parts(moved, 16), &[7u8; 16]);
+            assert_eq!(heap.usage().0, header + 48 + 16 + 64);
+
+            // Requests past the arena fail instead of overlapping the header
+            assert!(heap
+                .alloc(Layout::from_size_align(1024, 8).unwrap())
+                .is_null());
+        }
+    }
+}
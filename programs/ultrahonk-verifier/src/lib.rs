@@ -21,38 +21,55 @@
 //! 13. ComputeMSM - Phase 3: Shplemini P0/P1 computation
 //! 14. FinalPairingCheck - Phase 4: Final pairing verification
 
+pub mod error;
+pub mod heap;
 pub mod phased;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
+use error::challenge_error;
+pub use error::VerifierError;
 
 use plonk_solana_core::{
     // Split delta computation
-    compute_delta_part1,
+    compute_delta_part1_streamed,
     compute_delta_part2,
     // Incremental challenge generation
-    generate_challenges_phase1a,
+    generate_challenges_phase1a_streamed,
     generate_challenges_phase1b,
     generate_challenges_phase1c,
     generate_challenges_phase1d,
+    relations::RelationCoefficients,
     // Incremental shplemini (MSM) verification
     shplemini_phase3a,
     shplemini_phase3b1,
+    shplemini_msm_scalars,
     shplemini_phase3b2,
-    shplemini_phase3c,
+    shplemini_phase3c_cached,
     // Incremental sumcheck verification
     sumcheck_rounds_init,
     verify_step1_challenges,
     verify_step2_sumcheck,
     verify_step3_pairing_points,
-    verify_step4_pairing_check,
+    verify_step4_pairing_check_with_srs,
     verify_sumcheck_relations,
+    verify_sumcheck_relations_part1,
+    verify_sumcheck_relations_part2,
     verify_sumcheck_rounds_partial,
+    cu_estimate,
     Challenges,
     DeltaPartialResult,
     Fr,
     FrLimbs, // For efficient state storage
+    G2,
+    PhasePlan,
+    RelationBatching,
     ShpleminiPhase3aResult,
     ShpleminiPhase3b1Result,
     ShpleminiPhase3bResult,
     SumcheckRoundsState,
+    MAX_LOG_N,
+    MSM_MAX_TERMS,
 };
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -81,15 +98,53 @@ pub const PROOF_SIZE: usize = 16224;
 /// VK size for bb 0.87
 pub const VK_SIZE: usize = 1760;
 
-/// Maximum chunk size for uploads (to fit in tx)
+/// Bytes per slot of the upload bitmap; uploads may be any length up to the
+/// end of the buffer, but only mark the slots they write in full (the last
+/// slot ends with the proof)
 pub const MAX_CHUNK_SIZE: usize = 1020;
 
 /// Header size in proof buffer: status (1) + proof_len (2) + pi_count (2) + chunk_bitmap (4)
 pub const BUFFER_HEADER_SIZE: usize = 9;
 
+/// First byte of a shard manifest; no `BufferStatus` uses it
+pub const MANIFEST_TAG: u8 = 0x4d;
+
+/// Most shards a manifest can reference
+pub const MAX_SHARDS: usize = 8;
+
+/// Header size in a shard manifest: tag (1) + status (1) + proof_len (2) + pi_count (2) + shard_count (1)
+pub const MANIFEST_HEADER_SIZE: usize = 7;
+
+/// Manifest account size (header + one pubkey per shard)
+pub const MANIFEST_ACCOUNT_SIZE: usize = MANIFEST_HEADER_SIZE + 32 * MAX_SHARDS;
+
+/// Header size in a shard: status (1) + manifest (32) + index (1) + start (4) + len (4) + chunk_bitmap (4)
+pub const SHARD_HEADER_SIZE: usize = 46;
+
+/// Most bytes one shard holds (the chunk bitmap covers 32 chunks)
+pub const MAX_SHARD_LEN: usize = 32 * MAX_CHUNK_SIZE;
+
+/// First byte of an MSM scratch account; no `BufferStatus` uses it
+pub const SCRATCH_TAG: u8 = 0x53;
+
+/// Header size in an MSM scratch account: tag (1) + scalar_count (1) + state (32)
+pub const SCRATCH_HEADER_SIZE: usize = 34;
+
+/// MSM scratch account size (header + room for the largest MSM)
+pub const SCRATCH_ACCOUNT_SIZE: usize = SCRATCH_HEADER_SIZE + 32 * MSM_MAX_TERMS;
+
 /// Header size in VK buffer: status (1) + vk_len (2)
 pub const VK_HEADER_SIZE: usize = 3;
 
+/// Compiled VK size (16-byte header + 27 G1), fits in the raw VK slot
+pub const COMPILED_VK_SIZE: usize = plonk_solana_core::COMPILED_VK_SIZE;
+
+/// Offset of the optional x·G2 SRS point in a VK account (right after the raw VK)
+pub const VK_SRS_G2_OFFSET: usize = VK_HEADER_SIZE + VK_SIZE;
+
+/// VK account size when a custom SRS point is supplied
+pub const VK_ACCOUNT_SIZE_WITH_SRS: usize = VK_SRS_G2_OFFSET + 128;
+
 /// VK buffer status values
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq)]
@@ -97,6 +152,8 @@ pub enum VkBufferStatus {
     Empty = 0,
     Uploading = 1,
     Ready = 2,
+    /// Raw VK was validated and rewritten in place in the compiled layout
+    Compiled = 3,
 }
 
 // ============================================================================
@@ -126,7 +183,8 @@ pub enum Instruction {
     /// Accounts: [proof_buffer (writable), payer (signer)]
     InitBuffer = 0,
 
-    /// Upload chunk of proof data
+    /// Upload chunk of proof data; a chunk may be any length up to the end
+    /// of the buffer, and several may be sent in one transaction
     /// Accounts: [proof_buffer (writable), authority (signer)]
     /// Data: [instruction(1), offset(2), chunk_data(...)]
     UploadChunk = 1,
@@ -134,6 +192,7 @@ pub enum Instruction {
     /// Verify the proof from buffer (FAILS: >1.4M CUs)
     /// Accounts: [proof_buffer (readonly)]
     /// Data: [instruction(1)]
+    /// Returns: VerificationResult(73)
     Verify = 2,
 
     /// Set public inputs
@@ -143,18 +202,47 @@ pub enum Instruction {
 
     // === VK Account Management ===
     /// Initialize VK buffer account
+    /// Fails once the VK is `Ready` or `Compiled`
     /// Accounts: [vk_buffer (writable)]
     /// Data: [instruction(1)]
     InitVkBuffer = 4,
 
     /// Upload chunk of VK data
+    /// Offsets past the VK write the optional x·G2 SRS point
+    /// (see `VK_SRS_G2_OFFSET`); the VK is `Ready`, and no longer
+    /// writable, once the account's VK and SRS slot are written
     /// Accounts: [vk_buffer (writable)]
     /// Data: [instruction(1), offset(2), chunk_data(...)]
     UploadVkChunk = 5,
 
+    /// Validate an uploaded VK once and rewrite it in the compiled layout,
+    /// so later phases read it zero-copy instead of re-parsing
+    /// Accounts: [vk_buffer (writable)]
+    /// Data: [instruction(1)]
+    CompileVk = 6,
+
+    /// Unlock a proof buffer locked by Phase 1 and zero the verification
+    /// state bound to it, so the buffer can be re-uploaded. Fails while the
+    /// state holds a successful verification no receipt has recorded yet
+    /// Accounts: [proof_buffer (writable), state (writable)]
+    /// Data: [instruction(1)]
+    ResetBuffer = 7,
+
+    /// Turn an account into a shard manifest and bind its shards, which
+    /// split `[public_inputs][proof]` into consecutive `shard_len` slices
+    /// Accounts: [manifest (writable), shards... (writable)]
+    /// Data: [instruction(1), num_public_inputs(2), shard_len(4)]
+    InitManifest = 8,
+
+    /// Upload a chunk of a shard; only the shard is written, so the shards
+    /// of one manifest can be uploaded in parallel
+    /// Accounts: [shard (writable), manifest (readonly)]
+    /// Data: [instruction(1), offset in shard(4), chunk_data(...)]
+    UploadShardChunk = 9,
+
     // === Multi-TX phased verification (original - exceeds CU) ===
     /// Phase 1: Initialize state + generate challenges (FAILS: >1.4M CUs)
-    /// Accounts: [state (writable), proof_data (readonly)]
+    /// Accounts: [state (writable), proof_data (writable, locked here)]
     PhasedGenerateChallenges = 10,
 
     /// Phase 2: Verify sumcheck
@@ -166,12 +254,19 @@ pub enum Instruction {
     PhasedComputeMSM = 12,
 
     /// Phase 4: Final pairing check
-    /// Accounts: [state (writable)]
+    /// Accounts: [state (writable), vk_account (optional, for a custom SRS point)]
     PhasedFinalCheck = 13,
 
+    /// Upload public inputs or proof bytes; lets a client send both through
+    /// one stream of chunks, in any order
+    /// Accounts: [proof_buffer (writable)]
+    /// Data: [instruction(1), region(1), offset(2), bytes(...)]
+    /// (region: 0 = public inputs, 1 = proof)
+    UploadData = 14,
+
     // === Sub-phased challenge generation (splits Phase 1) ===
     /// Phase 1a: eta, beta/gamma challenges
-    /// Accounts: [state (writable), proof_data (readonly)]
+    /// Accounts: [state (writable), proof_data (writable, locked here)]
     Phase1aEtaBetaGamma = 20,
 
     /// Phase 1b: alpha + gate challenges
@@ -196,7 +291,7 @@ pub enum Instruction {
 
     // === Unified Phase 1 (after Montgomery optimization) ===
     /// Phase 1 Full: All challenge generation in one TX (~300K CUs)
-    /// Accounts: [state (writable), proof_data (readonly)]
+    /// Accounts: [state (writable), proof_data (writable, locked here), vk_account (readonly)]
     Phase1Full = 30,
 
     // === Sub-phased sumcheck verification (splits Phase 2) ===
@@ -209,6 +304,14 @@ pub enum Instruction {
     /// Accounts: [state (writable), proof_data (readonly)]
     Phase2dRelations = 43,
 
+    /// Phase 2d1: First half of the relations (arithmetic, permutation, range, elliptic)
+    /// Accounts: [state (writable), proof_data (readonly)]
+    Phase2dRelationsPart1 = 44,
+
+    /// Phase 2d2: Second half of the relations (lookup, memory, poseidon) + final check
+    /// Accounts: [state (writable), proof_data (readonly)]
+    Phase2dRelationsPart2 = 45,
+
     // === Sub-phased MSM computation (splits Phase 3) ===
     /// Phase 3a: Weights + scalar accumulation (~870K CUs)
     /// Accounts: [state (writable), proof_data (readonly)]
@@ -218,23 +321,47 @@ pub enum Instruction {
     /// Accounts: [state (writable), proof_data (readonly)]
     Phase3b1Folding = 51,
 
-    /// Phase 3b2: Gemini + libra (~500K CUs)
-    /// Accounts: [state (writable), proof_data (readonly)]
+    /// Phase 3b2: Gemini + libra (~500K CUs); with `use_scratch` = 1 it also
+    /// writes the Phase 3c MSM scalars to an MSM scratch account
+    /// Accounts: [state (writable), proof_data (readonly),
+    ///            vk_account (readonly, scratch only), scratch (writable, scratch only)]
+    /// Data: [instruction(1), use_scratch(1, optional)]
     Phase3b2Gemini = 52,
 
-    /// Phase 3c: MSM computation (~500K CUs)
-    /// Accounts: [state (writable), proof_data (readonly)]
+    /// Phase 3c: MSM computation (~500K CUs); with `use_scratch` = 1 it reads
+    /// the scalars Phase 3b2 cached instead of recomputing them
+    /// Accounts: [state (writable), proof_data (readonly), vk_account (readonly),
+    ///            scratch (readonly, scratch only)]
+    /// Data: [instruction(1), use_scratch(1, optional)]
     Phase3cMsm = 53,
 
-    /// Phase 3c + 4: Combined MSM + Pairing (~790K CUs, saves 1 TX)
-    /// Accounts: [state (writable), proof_data (readonly)]
+    /// Phase 3c + 4: Combined MSM + Pairing (~790K CUs, saves 1 TX); takes
+    /// an MSM scratch like `Phase3cMsm`
+    /// Accounts: [state (writable), proof_data (readonly), vk_account (readonly),
+    ///            scratch (readonly, scratch only)]
+    /// Data: [instruction(1), use_scratch(1, optional)]
     Phase3cAndPairing = 54,
 
     // === Verification Receipt ===
     /// Create verification receipt PDA after successful verification
-    /// Accounts: [state (readonly), proof_buffer (readonly), vk_account (readonly),
-    ///            receipt_pda (writable), payer (signer), system_program]
+    /// Accounts: [state (readonly, writable to mark it recorded), proof_buffer (readonly),
+    ///            vk_account (readonly), receipt_pda (writable), payer (signer),
+    ///            system_program]
+    /// Data: [instruction(1), tag(32, optional), duplicate_policy(1, optional),
+    ///        rent_beneficiary(32, optional)]
+    /// Returns: VerificationResult(73)
     CreateReceipt = 60,
+
+    /// Close a receipt and refund its rent to the beneficiary it recorded
+    /// Accounts: [receipt_pda (writable), rent_payer (signer, writable),
+    ///            rent_beneficiary (writable, if not the rent payer)]
+    CloseReceipt = 61,
+
+    /// Check a proof digest against the one a receipt recorded
+    /// Accounts: [receipt_pda (readonly), vk_account (readonly, optional)]
+    /// Data: [instruction(1), proof_digest(32)]
+    /// Returns: VerificationResult(73)
+    ReverifyByDigest = 62,
 }
 
 // ============================================================================
@@ -242,12 +369,55 @@ pub enum Instruction {
 // ============================================================================
 
 /// Proof buffer account layout:
-/// [0]:       status (0=empty, 1=uploading, 2=ready)
+/// [0]:       status (0=empty, 1=uploading, 2=ready, 3=locked)
 /// [1..3]:    proof_length (u16 LE)
 /// [3..5]:    public_inputs_count (u16 LE)
 /// [5..9]:    chunk_bitmap (u32 LE) - tracks which chunks have been uploaded (supports up to 32 chunks)
 /// [9..9+PI]: public inputs (32 bytes each)
 /// [9+PI..]:  proof data
+///
+/// Phase 1 moves a ready buffer to `Locked` and records it in the
+/// verification state. A locked buffer rejects `InitBuffer`, `UploadChunk`
+/// and `SetPublicInputs`, and every later phase and `CreateReceipt` only
+/// accept the buffer their state is bound to. `ResetBuffer` unlocks it.
+///
+/// Instead of a proof buffer, every instruction that takes one also accepts
+/// a shard manifest, with the manifest's shards appended as extra accounts:
+///
+/// Manifest account layout:
+/// [0]:       `MANIFEST_TAG`
+/// [1]:       status (0=empty, 3=locked)
+/// [2..4]:    proof_length (u16 LE)
+/// [4..6]:    public_inputs_count (u16 LE)
+/// [6]:       shard_count
+/// [7..]:     shard pubkeys, in order
+///
+/// Shard account layout:
+/// [0]:       status (0=empty, 1=uploading, 2=ready)
+/// [1..33]:   manifest pubkey
+/// [33]:      index in the manifest
+/// [34..38]:  start offset in `[public_inputs][proof]` (u32 LE)
+/// [38..42]:  length (u32 LE)
+/// [42..46]:  chunk_bitmap (u32 LE), relative to the shard
+/// [46..]:    data
+///
+/// Phase 1 locks the manifest once every shard is ready. A shard stays
+/// bound to its manifest for life, and `UploadShardChunk` checks the
+/// manifest's lock, so a locked manifest's bytes cannot change. Handlers
+/// read a sharded proof as a copy in the proof buffer layout, which costs
+/// about 16 KB of heap on top of the phase itself: programs verifying
+/// sharded proofs should be built with `large-heap`.
+///
+/// MSM scratch account layout:
+/// [0]:       `SCRATCH_TAG`
+/// [1]:       scalar_count
+/// [2..34]:   state pubkey
+/// [34..]:    Phase 3c MSM scalars (32 bytes each, big-endian)
+///
+/// Phase 3b2 can write the scalars of the final MSM to a fresh,
+/// program-owned account of `SCRATCH_ACCOUNT_SIZE`, and records their hash
+/// in the state. Phase 3c given the same scratch then only runs the MSM,
+/// which keeps retries cheap; `CloseAccounts` closes it with the state.
 
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq)]
@@ -255,6 +425,27 @@ pub enum BufferStatus {
     Empty = 0,
     Uploading = 1,
     Ready = 2,
+    Locked = 3,
+}
+
+/// Part of a proof buffer an `UploadData` instruction writes to
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BufferRegion {
+    /// The public inputs, `num_public_inputs * 32` bytes after the header
+    PublicInputs = 0,
+    /// The proof, after the public inputs
+    Proof = 1,
+}
+
+impl BufferRegion {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::PublicInputs),
+            1 => Some(Self::Proof),
+            _ => None,
+        }
+    }
 }
 
 // ============================================================================
@@ -263,10 +454,11 @@ pub enum BufferStatus {
 
 /// Validate that all proof chunks have been uploaded
 fn validate_proof_chunks_complete(proof_data: &[u8]) -> ProgramResult {
-    // Check buffer status
-    if proof_data[0] != BufferStatus::Ready as u8 {
+    // Check buffer status (a locked buffer was complete when it was locked)
+    let status = proof_data[0];
+    if status != BufferStatus::Ready as u8 && status != BufferStatus::Locked as u8 {
         msg!("ERROR: Proof buffer not ready. Upload all chunks before verification.");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::ProofBufferNotReady.into());
     }
 
     // Validate chunk bitmap
@@ -299,13 +491,429 @@ fn validate_proof_chunks_complete(proof_data: &[u8]) -> ProgramResult {
             "Expected: {:#034b}",
             expected_bitmap
         );
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::ProofBufferIncomplete.into());
     }
 
     msg!("✓ All {} proof chunks validated", num_chunks);
     Ok(())
 }
 
+/// Lock a complete proof buffer and bind it to `state`
+///
+/// A state stays bound to one buffer until `ResetBuffer` zeroes it, and a
+/// locked buffer can only be locked again by its own state, so a failed
+/// Phase 1 can be retried but never pointed at different proof bytes.
+fn lock_proof_buffer(
+    proof_account: &AccountInfo,
+    state: &mut phased::VerificationState,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if !proof_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    let key = proof_account.key.to_bytes();
+    if state.proof_buffer != [0u8; 32] && state.proof_buffer != key {
+        msg!("ERROR: State is bound to another proof buffer. Reset it first.");
+        return Err(VerifierError::ProofBufferMismatch.into());
+    }
+
+    let mut proof_data = proof_account.try_borrow_mut_data()?;
+    let status_index = if proof_data.first() == Some(&MANIFEST_TAG) {
+        for shard in manifest_shards(proof_account.key, &proof_data, accounts)? {
+            if shard.try_borrow_data()?[0] != BufferStatus::Ready as u8 {
+                msg!("ERROR: Shard {} has not received all of its data", shard.key);
+                return Err(VerifierError::ProofBufferIncomplete.into());
+            }
+        }
+        1
+    } else {
+        validate_proof_chunks_complete(&proof_data)?;
+        0
+    };
+    if proof_data[status_index] == BufferStatus::Locked as u8 && state.proof_buffer != key {
+        msg!("ERROR: Proof buffer is locked by another verification");
+        return Err(VerifierError::ProofBufferLocked.into());
+    }
+
+    proof_data[status_index] = BufferStatus::Locked as u8;
+    state.proof_buffer = key;
+    Ok(())
+}
+
+/// Proof buffer contents as the phases read them
+///
+/// A sharded proof is copied out of its shards into the proof buffer
+/// layout, so handlers index both kinds the same way.
+enum ProofBytes<'a> {
+    Buffer(core::cell::Ref<'a, &'a mut [u8]>),
+    Sharded(Vec<u8>),
+}
+
+impl core::ops::Deref for ProofBytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Buffer(data) => data,
+            Self::Sharded(data) => data,
+        }
+    }
+}
+
+/// Read a proof buffer, or assemble a manifest's shards from `accounts`
+fn borrow_proof<'a, 'info>(
+    proof_account: &'a AccountInfo<'info>,
+    accounts: &[AccountInfo],
+) -> Result<ProofBytes<'a>, ProgramError> {
+    let proof_data = proof_account.try_borrow_data()?;
+    if proof_data.first() != Some(&MANIFEST_TAG) {
+        return Ok(ProofBytes::Buffer(proof_data));
+    }
+
+    let shards = manifest_shards(proof_account.key, &proof_data, accounts)?;
+    let total = sharded_len(&proof_data);
+    let mut bytes = Vec::with_capacity(BUFFER_HEADER_SIZE + total);
+    bytes.push(BufferStatus::Locked as u8);
+    bytes.extend_from_slice(&proof_data[2..6]); // proof_len, pi_count
+    bytes.extend_from_slice(&u32::MAX.to_le_bytes()); // every chunk present
+    for shard in shards {
+        let shard_data = shard.try_borrow_data()?;
+        let len = u32::from_le_bytes(shard_data[38..42].try_into().unwrap()) as usize;
+        bytes.extend_from_slice(&shard_data[SHARD_HEADER_SIZE..SHARD_HEADER_SIZE + len]);
+    }
+    Ok(ProofBytes::Sharded(bytes))
+}
+
+/// Fail if the challenges in `state` changed since Phase 1 recorded them
+fn check_challenge_digest(state: &phased::VerificationState) -> ProgramResult {
+    if !state.challenges_intact() {
+        msg!("ERROR: State challenges do not match the Phase 1 digest");
+        return Err(VerifierError::ChallengeDigestMismatch.into());
+    }
+    Ok(())
+}
+
+/// Relation coefficients passed in a relations instruction's `data`
+///
+/// `None` for empty data, which batches with the stored alphas. Otherwise
+/// the data must be the raw coefficients whose commitment Phase 2c
+/// recorded, or this fails with `RelationCoefficientsMismatch`.
+fn read_relation_coefficients(
+    state: &phased::VerificationState,
+    data: &[u8],
+) -> Result<Option<RelationCoefficients>, ProgramError> {
+    if data.is_empty() {
+        return Ok(None);
+    }
+    let coefficients = plonk_solana_core::relations::relation_coefficients_from_bytes(data)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if state.relation_coefficients_hash == [0u8; 32]
+        || plonk_solana_core::transcript::keccak256(data) != state.relation_coefficients_hash
+    {
+        msg!("ERROR: Relation coefficients do not match the Phase 2c commitment");
+        return Err(VerifierError::RelationCoefficientsMismatch.into());
+    }
+    Ok(Some(coefficients))
+}
+
+/// Batch with `coefficients` if the instruction passed them, otherwise
+/// with the state's alphas
+fn relation_batching<'a>(
+    state: &'a phased::VerificationState,
+    coefficients: Option<&'a RelationCoefficients>,
+) -> RelationBatching<'a> {
+    coefficients.map_or(
+        RelationBatching::Alphas(&state.alphas),
+        RelationBatching::Coefficients,
+    )
+}
+
+/// Fail unless the phased verifier can run a circuit of this shape
+///
+/// Checked when Phase 1 starts, so an oversized circuit is refused before
+/// any state is written rather than partway through the phases.
+fn check_circuit_supported(log_n: usize, is_zk: bool, num_public_inputs: usize) -> ProgramResult {
+    let plan = cu_estimate(
+        u8::try_from(log_n).unwrap_or(u8::MAX),
+        is_zk,
+        num_public_inputs,
+    );
+    if plan.log_n > MAX_LOG_N {
+        msg!("ERROR: log_n {} exceeds the maximum of {}", log_n, MAX_LOG_N);
+        return Err(VerifierError::CircuitTooLarge.into());
+    }
+    if let Some(phase) = plan.oversized_phase() {
+        msg!("ERROR: {} does not fit a transaction at log_n {}", phase, log_n);
+        return Err(VerifierError::CircuitTooLarge.into());
+    }
+    Ok(())
+}
+
+/// Phase plan for the circuit Phase 1 recorded in `state`
+fn state_phase_plan(state: &phased::VerificationState) -> PhasePlan {
+    cu_estimate(
+        state.log_n,
+        state.is_zk != 0,
+        state.num_public_inputs as usize,
+    )
+}
+
+/// Keccak256 of a VK account's data, as Phase 1 records it in the state
+fn vk_hash(vk_account: &AccountInfo) -> Result<[u8; 32], ProgramError> {
+    Ok(plonk_solana_core::transcript::keccak256(&vk_account.try_borrow_data()?))
+}
+
+/// Report a successful verification to the caller through return data
+fn set_verification_result(slot: u64, vk_hash: [u8; 32], pi_hash: [u8; 32]) {
+    let result = solana_noir_receipt_seeds::VerificationResult {
+        verified: true,
+        slot,
+        vk_hash,
+        pi_hash,
+    };
+    solana_program::program::set_return_data(&result.to_bytes());
+}
+
+/// Fail unless `vk_account` is the VK account Phase 1 bound to `state`,
+/// with the contents it had then
+///
+/// A VK account rewritten or compiled since Phase 1 counts as a different
+/// VK; the verification has to start over from Phase 1.
+fn check_bound_vk(state: &phased::VerificationState, vk_account: &AccountInfo) -> ProgramResult {
+    if state.vk_account == [0u8; 32] {
+        msg!("ERROR: No VK account is bound to this state");
+        return Err(VerifierError::VkAccountMismatch.into());
+    }
+    if state.vk_account != vk_account.key.to_bytes() {
+        msg!(
+            "VK account mismatch! Phase 1 used {}, but this instruction received {}",
+            Pubkey::from(state.vk_account),
+            vk_account.key
+        );
+        return Err(VerifierError::VkAccountMismatch.into());
+    }
+    if state.vk_hash != vk_hash(vk_account)? {
+        msg!("ERROR: VK account {} changed since Phase 1", vk_account.key);
+        return Err(VerifierError::VkAccountMismatch.into());
+    }
+    Ok(())
+}
+
+/// Read the proof buffer (or manifest) that Phase 1 bound to `state`
+fn borrow_bound_proof<'a, 'info>(
+    state: &phased::VerificationState,
+    proof_account: &'a AccountInfo<'info>,
+    accounts: &[AccountInfo],
+) -> Result<ProofBytes<'a>, ProgramError> {
+    if state.proof_buffer != proof_account.key.to_bytes() {
+        msg!("ERROR: Proof buffer {} is not bound to this state", proof_account.key);
+        return Err(VerifierError::ProofBufferMismatch.into());
+    }
+    borrow_proof(proof_account, accounts)
+}
+
+/// Phase 3b result as Phases 3a-3b2 left it in `state`
+fn phase3b_result_from_state(state: &phased::VerificationState) -> ShpleminiPhase3bResult {
+    // Load FrLimbs directly from raw bytes (no Montgomery conversion!)
+    ShpleminiPhase3bResult {
+        const_acc: FrLimbs::from_raw_bytes(&state.shplemini_const_acc),
+        gemini_scalars: state
+            .shplemini_gemini_scalars
+            .iter()
+            .map(FrLimbs::from_raw_bytes)
+            .collect(),
+        libra_scalars: state.shplemini_libra_scalars.iter().map(FrLimbs::from_raw_bytes).collect(),
+        r_pows: state.shplemini_r_pows.iter().map(FrLimbs::from_raw_bytes).collect(),
+        unshifted: FrLimbs::from_raw_bytes(&state.shplemini_unshifted),
+        shifted: FrLimbs::from_raw_bytes(&state.shplemini_shifted),
+    }
+}
+
+/// Whether a phase instruction's optional flag byte asks for an MSM scratch
+fn uses_scratch(data: &[u8]) -> Result<bool, ProgramError> {
+    match data.first() {
+        None | Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        Some(_) => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Write the Phase 3c MSM scalars to `scratch` and record their hash in
+/// `state`
+///
+/// `scratch` must be a program-owned account of `SCRATCH_ACCOUNT_SIZE` that
+/// is either still zeroed or already holds scalars for this state.
+fn write_msm_scratch(
+    program_id: &Pubkey,
+    state_key: &Pubkey,
+    state: &mut phased::VerificationState,
+    scratch: &AccountInfo,
+    scalars: &[Fr],
+) -> ProgramResult {
+    if scratch.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !scratch.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+    if scalars.len() > MSM_MAX_TERMS {
+        return Err(VerifierError::MsmFailed.into());
+    }
+
+    let mut data = scratch.try_borrow_mut_data()?;
+    if data.len() != SCRATCH_ACCOUNT_SIZE {
+        msg!("ERROR: MSM scratch must be {} bytes", SCRATCH_ACCOUNT_SIZE);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let fresh = data.iter().all(|&b| b == 0);
+    let ours = data[0] == SCRATCH_TAG && data[2..SCRATCH_HEADER_SIZE] == state_key.to_bytes();
+    if !fresh && !ours {
+        msg!("ERROR: MSM scratch {} is in use by another account", scratch.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    data[0] = SCRATCH_TAG;
+    data[1] = scalars.len() as u8;
+    data[2..SCRATCH_HEADER_SIZE].copy_from_slice(state_key.as_ref());
+    let body = &mut data[SCRATCH_HEADER_SIZE..];
+    body.fill(0);
+    let scalar_bytes = &mut body[..scalars.len() * 32];
+    for (dst, scalar) in scalar_bytes.chunks_exact_mut(32).zip(scalars) {
+        dst.copy_from_slice(scalar);
+    }
+    state.msm_scalars_hash = plonk_solana_core::transcript::keccak256(scalar_bytes);
+    Ok(())
+}
+
+/// Read the MSM scalars Phase 3b2 wrote to `scratch` for `state`
+///
+/// Fails with `MsmScratchMismatch` unless the scratch is bound to the state
+/// and its scalars hash to the value Phase 3b2 recorded.
+fn read_msm_scratch(
+    program_id: &Pubkey,
+    state_key: &Pubkey,
+    state: &phased::VerificationState,
+    scratch: &AccountInfo,
+) -> Result<Vec<Fr>, ProgramError> {
+    let data = scratch.try_borrow_data()?;
+    let count = data.get(1).copied().unwrap_or(0) as usize;
+    let bound = scratch.owner == program_id
+        && data.len() == SCRATCH_ACCOUNT_SIZE
+        && data[0] == SCRATCH_TAG
+        && data[2..SCRATCH_HEADER_SIZE] == state_key.to_bytes()
+        && count <= MSM_MAX_TERMS;
+    if !bound || state.msm_scalars_hash == [0u8; 32] {
+        msg!("ERROR: MSM scratch {} is not bound to this state", scratch.key);
+        return Err(VerifierError::MsmScratchMismatch.into());
+    }
+
+    let scalar_bytes = &data[SCRATCH_HEADER_SIZE..SCRATCH_HEADER_SIZE + count * 32];
+    if plonk_solana_core::transcript::keccak256(scalar_bytes) != state.msm_scalars_hash {
+        msg!("ERROR: MSM scratch {} changed since Phase 3b2", scratch.key);
+        return Err(VerifierError::MsmScratchMismatch.into());
+    }
+    Ok(scalar_bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect())
+}
+
+/// Phase 3c MSM scalars, from the scratch in `accounts[3]` when
+/// `use_scratch` is set, otherwise recomputed from `state`
+fn phase3c_scalars(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    state: &phased::VerificationState,
+    vk: &plonk_solana_core::key::VerificationKey,
+    use_scratch: bool,
+) -> Result<Vec<Fr>, ProgramError> {
+    if use_scratch {
+        let scratch = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        return read_msm_scratch(program_id, accounts[0].key, state, scratch);
+    }
+    let challenges = reconstruct_challenges(state);
+    Ok(shplemini_msm_scalars(
+        vk,
+        &challenges,
+        &phase3b_result_from_state(state),
+        state.is_zk != 0,
+    ))
+}
+
+/// Bytes of `[public_inputs][proof]` a manifest splits across its shards
+fn sharded_len(manifest_data: &[u8]) -> usize {
+    let proof_len = u16::from_le_bytes([manifest_data[2], manifest_data[3]]) as usize;
+    let num_pi = u16::from_le_bytes([manifest_data[4], manifest_data[5]]) as usize;
+    num_pi * 32 + proof_len
+}
+
+/// Find a manifest's shards in `accounts`, in order
+///
+/// Each shard must be bound to the manifest at its position and the shards
+/// must cover `[public_inputs][proof]` without gaps.
+fn manifest_shards<'a, 'info>(
+    manifest_key: &Pubkey,
+    manifest_data: &[u8],
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<Vec<&'a AccountInfo<'info>>, ProgramError> {
+    let shard_count = manifest_data[6] as usize;
+    if shard_count == 0
+        || shard_count > MAX_SHARDS
+        || manifest_data.len() < MANIFEST_HEADER_SIZE + shard_count * 32
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut shards = Vec::with_capacity(shard_count);
+    let mut next_start = 0usize;
+    for index in 0..shard_count {
+        let key_start = MANIFEST_HEADER_SIZE + index * 32;
+        let key = &manifest_data[key_start..key_start + 32];
+        let shard = accounts
+            .iter()
+            .find(|account| account.key.as_ref() == key)
+            .ok_or_else(|| {
+                msg!("ERROR: Shard {} of the manifest is missing", index);
+                ProgramError::NotEnoughAccountKeys
+            })?;
+
+        let shard_data = shard.try_borrow_data()?;
+        if shard_data.len() < SHARD_HEADER_SIZE
+            || shard_data[1..33] != manifest_key.to_bytes()
+            || shard_data[33] as usize != index
+            || u32::from_le_bytes(shard_data[34..38].try_into().unwrap()) as usize != next_start
+        {
+            msg!("ERROR: Shard {} is not bound to this manifest", shard.key);
+            return Err(VerifierError::InvalidShard.into());
+        }
+        next_start += u32::from_le_bytes(shard_data[38..42].try_into().unwrap()) as usize;
+        shards.push(shard);
+    }
+
+    if next_start != sharded_len(manifest_data) {
+        msg!("ERROR: Shards do not cover the proof");
+        return Err(VerifierError::InvalidShard.into());
+    }
+    Ok(shards)
+}
+
+/// Reject writes to a buffer that a verification has locked
+///
+/// Shard manifests are rejected too: their bytes are written through
+/// `UploadShardChunk`.
+fn check_buffer_unlocked(buffer_data: &[u8]) -> ProgramResult {
+    if buffer_data[0] == BufferStatus::Locked as u8 {
+        msg!("ERROR: Proof buffer is locked by a verification. Use ResetBuffer first.");
+        return Err(VerifierError::ProofBufferLocked.into());
+    }
+    if buffer_data[0] == MANIFEST_TAG {
+        msg!("ERROR: Account is a shard manifest. Upload to its shards instead.");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -315,6 +923,24 @@ pub fn process_instruction(
         return Err(ProgramError::InvalidInstructionData);
     }
 
+    let result = dispatch(program_id, accounts, instruction_data);
+    if let Some(error) = result
+        .as_ref()
+        .err()
+        .and_then(VerifierError::from_program_error)
+    {
+        msg!("Error: {}", error);
+    }
+    #[cfg(all(feature = "heap-audit", target_os = "solana"))]
+    heap::log_usage();
+    result
+}
+
+fn dispatch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
     match instruction_data[0] {
         // Single-TX verification
         0 => process_init_buffer(program_id, accounts, &instruction_data[1..]),
@@ -325,6 +951,13 @@ pub fn process_instruction(
         // VK account management
         4 => process_init_vk_buffer(program_id, accounts),
         5 => process_upload_vk_chunk(program_id, accounts, &instruction_data[1..]),
+        6 => process_compile_vk(program_id, accounts),
+
+        // Proof buffer management
+        7 => process_reset_buffer(program_id, accounts),
+        8 => process_init_manifest(program_id, accounts, &instruction_data[1..]),
+        9 => process_upload_shard_chunk(program_id, accounts, &instruction_data[1..]),
+        14 => process_upload_data(program_id, accounts, &instruction_data[1..]),
 
         // Multi-TX phased verification (original - may exceed CU)
         10 => process_phased_generate_challenges(program_id, accounts),
@@ -345,21 +978,26 @@ pub fn process_instruction(
 
         // Sub-phased sumcheck verification
         40 => process_phase2_rounds(program_id, accounts, instruction_data),
-        43 => process_phase2d_relations(program_id, accounts),
+        41 => process_phase2c_relation_coefficients(program_id, accounts),
+        43 => process_phase2d_relations(program_id, accounts, &instruction_data[1..]),
+        44 => process_phase2d_relations_part1(program_id, accounts, &instruction_data[1..]),
+        45 => process_phase2d_relations_part2(program_id, accounts, &instruction_data[1..]),
 
         // Sub-phased MSM computation
         50 => process_phase3a_weights(program_id, accounts),
         51 => process_phase3b1_folding(program_id, accounts),
-        52 => process_phase3b2_gemini(program_id, accounts),
-        53 => process_phase3c_msm(program_id, accounts),
-        54 => process_phase3c_and_pairing(program_id, accounts),
+        52 => process_phase3b2_gemini(program_id, accounts, &instruction_data[1..]),
+        53 => process_phase3c_msm(program_id, accounts, &instruction_data[1..]),
+        54 => process_phase3c_and_pairing(program_id, accounts, &instruction_data[1..]),
 
         // Combined phases (fewer TXs)
-        55 => process_phase2d_and_3a(program_id, accounts), // Relations + Weights (~1.1M CUs)
+        55 => process_phase2d_and_3a(program_id, accounts, &instruction_data[1..]), // Relations + Weights (~1.1M CUs)
         56 => process_phase3b_combined(program_id, accounts), // Folding + Gemini (~800K CUs)
 
         // Verification receipt
-        60 => process_create_receipt(program_id, accounts),
+        60 => process_create_receipt(program_id, accounts, &instruction_data[1..]),
+        61 => process_close_receipt(program_id, accounts),
+        62 => process_reverify_by_digest(program_id, accounts, &instruction_data[1..]),
 
         // Account management
         70 => process_close_accounts(program_id, accounts),
@@ -381,7 +1019,7 @@ fn process_init_buffer(
     let buffer_account = next_account_info(account_iter)?;
 
     if !buffer_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     // Parse number of public inputs
@@ -403,6 +1041,7 @@ fn process_init_buffer(
         );
         return Err(ProgramError::AccountDataTooSmall);
     }
+    check_buffer_unlocked(&buffer_data)?;
 
     // Set header
     buffer_data[0] = BufferStatus::Empty as u8;
@@ -421,32 +1060,79 @@ fn process_upload_chunk(
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    let account_iter = &mut accounts.iter();
-    let buffer_account = next_account_info(account_iter)?;
-
-    if !buffer_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+    if data.len() < 2 {
+        return Err(ProgramError::InvalidInstructionData);
     }
+    let offset = u16::from_le_bytes([data[0], data[1]]) as usize;
+    write_buffer(accounts, BufferRegion::Proof, offset, &data[2..])
+}
 
-    if data.len() < 2 {
+/// Upload public input or proof bytes
+/// Data format: [region (1), offset (u16 LE), bytes...]
+fn process_upload_data(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.len() < 3 {
         return Err(ProgramError::InvalidInstructionData);
     }
+    let region = BufferRegion::from_u8(data[0]).ok_or(ProgramError::InvalidInstructionData)?;
+    let offset = u16::from_le_bytes([data[1], data[2]]) as usize;
+    write_buffer(accounts, region, offset, &data[3..])
+}
 
-    let offset = u16::from_le_bytes([data[0], data[1]]) as usize;
-    let chunk = &data[2..];
+/// Write `chunk` at `offset` into a region of an unlocked proof buffer
+///
+/// The write path of `UploadChunk`, `SetPublicInputs` and `UploadData`.
+/// Public inputs must stay within the count `InitBuffer` set. Proof writes
+/// mark every bitmap slot they fully cover, and the buffer turns `Ready`
+/// once all of the proof's slots are marked.
+fn write_buffer(
+    accounts: &[AccountInfo],
+    region: BufferRegion,
+    offset: usize,
+    chunk: &[u8],
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let buffer_account = next_account_info(account_iter)?;
+
+    if !buffer_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
 
     msg!(
-        "UltraHonk: UploadChunk offset={} len={}",
+        "UltraHonk: Upload {:?} offset={} len={}",
+        region,
         offset,
         chunk.len()
     );
 
     let mut buffer_data = buffer_account.try_borrow_mut_data()?;
+    check_buffer_unlocked(&buffer_data)?;
 
     // Read header
     let num_pi = u16::from_le_bytes([buffer_data[3], buffer_data[4]]) as usize;
     let data_start = BUFFER_HEADER_SIZE + (num_pi * 32);
 
+    if region == BufferRegion::PublicInputs {
+        if offset + chunk.len() > num_pi * 32 {
+            msg!(
+                "Public inputs exceed {} bytes: {} at offset {}",
+                num_pi * 32,
+                chunk.len(),
+                offset
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let write_start = BUFFER_HEADER_SIZE + offset;
+        buffer_data
+            .get_mut(write_start..write_start + chunk.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(chunk);
+        return Ok(());
+    }
+
     // Write chunk
     let write_start = data_start + offset;
     let write_end = write_start + chunk.len();
@@ -470,19 +1156,17 @@ fn process_upload_chunk(
         buffer_data[1..3].copy_from_slice(&new_len.to_le_bytes());
     }
 
-    // Mark this chunk as uploaded in the bitmap
+    // Mark every chunk the write covers in full as uploaded in the bitmap,
+    // so a single instruction may carry several chunks on clusters that
+    // allow larger transactions
     let chunk_num = offset / MAX_CHUNK_SIZE;
-    if chunk_num >= 32 {
-        msg!("Chunk number exceeds bitmap size: {}", chunk_num);
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let mut bitmap = u32::from_le_bytes([
+    let bitmap = u32::from_le_bytes([
         buffer_data[5],
         buffer_data[6],
         buffer_data[7],
         buffer_data[8],
     ]);
-    bitmap |= 1u32 << chunk_num;
+    let bitmap = mark_written_slots(bitmap, offset, chunk.len(), PROOF_SIZE);
     buffer_data[5..9].copy_from_slice(&bitmap.to_le_bytes());
 
     // Check if all chunks are uploaded
@@ -508,7 +1192,30 @@ fn process_upload_chunk(
     Ok(())
 }
 
+/// Mark the upload bitmap slots a write of `len` bytes at `offset` covers
+///
+/// Slots are `MAX_CHUNK_SIZE` bytes of a region `region_len` bytes long,
+/// the last one ending with the region. A slot the write only partly
+/// covers stays unmarked, so the region cannot turn `Ready` while some of
+/// its bytes were never written.
+fn mark_written_slots(bitmap: u32, offset: usize, len: usize, region_len: usize) -> u32 {
+    let end = (offset + len).min(region_len);
+    let mut bitmap = bitmap;
+    let mut slot = offset.div_ceil(MAX_CHUNK_SIZE);
+    while slot < 32 && slot * MAX_CHUNK_SIZE < region_len {
+        if ((slot + 1) * MAX_CHUNK_SIZE).min(region_len) > end {
+            break;
+        }
+        bitmap |= 1u32 << slot;
+        slot += 1;
+    }
+    bitmap
+}
+
 /// Verify the proof from buffer
+///
+/// Returns a `VerificationResult` with the current slot and the hash of
+/// the embedded VK.
 fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     msg!("UltraHonk: Verify");
     sol_log_compute_units();
@@ -521,7 +1228,7 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
     // Check status
     if buffer_data[0] != BufferStatus::Ready as u8 {
         msg!("Buffer not ready for verification");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::ProofBufferNotReady.into());
     }
 
     // Read header
@@ -556,7 +1263,7 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         Ok(v) => v,
         Err(e) => {
             msg!("VK parse error: {:?}", e);
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VerifierError::InvalidVk.into());
         }
     };
     msg!("CU after VK parse:");
@@ -570,7 +1277,7 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         Ok(p) => p,
         Err(e) => {
             msg!("Proof parse error: {:?}", e);
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VerifierError::InvalidProof.into());
         }
     };
     msg!("CU after proof parse:");
@@ -582,7 +1289,7 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         Ok(c) => c,
         Err(e) => {
             msg!("Step 1 failed: {:?}", e);
-            return Err(ProgramError::InvalidAccountData);
+            return Err(challenge_error(&e).into());
         }
     };
     msg!("CU after step 1:");
@@ -594,12 +1301,12 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         Ok(ok) => ok,
         Err(e) => {
             msg!("Step 2 failed: {:?}", e);
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VerifierError::SumcheckFailed.into());
         }
     };
     if !sumcheck_ok {
         msg!("Sumcheck verification failed");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::SumcheckFailed.into());
     }
     msg!("CU after step 2:");
     sol_log_compute_units();
@@ -610,7 +1317,7 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         Ok(pts) => pts,
         Err(e) => {
             msg!("Step 3 failed: {:?}", e);
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VerifierError::MsmFailed.into());
         }
     };
     msg!("CU after step 3:");
@@ -622,7 +1329,7 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         Ok(ok) => ok,
         Err(e) => {
             msg!("Step 4 failed: {:?}", e);
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VerifierError::PairingFailed.into());
         }
     };
     msg!("CU after step 4:");
@@ -630,10 +1337,15 @@ fn process_verify(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
 
     if pairing_ok {
         msg!("✅ UltraHonk proof verified successfully!");
+        set_verification_result(
+            solana_program::clock::Clock::get()?.slot,
+            vk.hash_with_srs(&plonk_solana_core::default_srs_g2()),
+            solana_noir_receipt_seeds::pi_hash(&buffer_data[pi_start..pi_end]),
+        );
         Ok(())
     } else {
         msg!("❌ Verification failed: pairing check returned false");
-        Err(ProgramError::InvalidAccountData)
+        Err(VerifierError::PairingFailed.into())
     }
 }
 
@@ -646,17 +1358,14 @@ fn process_set_public_inputs(
 ) -> ProgramResult {
     msg!("UltraHonk: SetPublicInputs");
 
-    let account_iter = &mut accounts.iter();
-    let buffer_account = next_account_info(account_iter)?;
-
-    if !buffer_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    let mut buffer_data = buffer_account.try_borrow_mut_data()?;
+    let buffer_account = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
 
     // Read expected PI count from header
-    let num_pi = u16::from_le_bytes([buffer_data[3], buffer_data[4]]) as usize;
+    let num_pi = {
+        let buffer_data = buffer_account.try_borrow_data()?;
+        check_buffer_unlocked(&buffer_data)?;
+        u16::from_le_bytes([buffer_data[3], buffer_data[4]]) as usize
+    };
     let expected_size = num_pi * 32;
 
     if data.len() != expected_size {
@@ -668,20 +1377,252 @@ fn process_set_public_inputs(
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    // Write PI after header
-    let pi_start = BUFFER_HEADER_SIZE;
-    buffer_data[pi_start..pi_start + expected_size].copy_from_slice(data);
+    write_buffer(accounts, BufferRegion::PublicInputs, 0, data)?;
 
     msg!("Set {} public inputs ({} bytes)", num_pi, expected_size);
     Ok(())
 }
 
+/// Unlock a proof buffer and zero the verification state bound to it
+///
+/// Accounts:
+/// 0. proof_account (writable) - Proof buffer or shard manifest to unlock
+/// 1. state_account (writable) - Verification state the buffer is bound to
+/// 2. shards... (writable) - The manifest's shards, if it is one
+///
+/// The buffer goes back to `Empty` with its public input count kept, ready
+/// for a new upload; a manifest keeps its shards, which go back to `Empty`.
+/// Zeroing the state discards any progress, so a new verification starts
+/// again from Phase 1. Anyone may reset, so a successful verification is
+/// kept until `CreateReceipt` records it; a failed one can be reset at once.
+fn process_reset_buffer(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("UltraHonk: ResetBuffer");
+
+    let account_iter = &mut accounts.iter();
+    let buffer_account = next_account_info(account_iter)?;
+    let state_account = next_account_info(account_iter)?;
+
+    if !buffer_account.is_writable || !state_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    let mut state_data = state_account.try_borrow_mut_data()?;
+    let state = phased::VerificationState::from_bytes(&state_data)
+        .ok_or(VerifierError::InvalidStateAccount)?;
+    if state.proof_buffer != buffer_account.key.to_bytes() {
+        msg!("ERROR: Proof buffer is not bound to this state");
+        return Err(VerifierError::ProofBufferMismatch.into());
+    }
+    if state.get_phase() == phased::Phase::Complete
+        && state.verified == 1
+        && state.receipt_recorded == 0
+    {
+        msg!("ERROR: Verification succeeded but has no receipt yet. Use CreateReceipt first.");
+        return Err(VerifierError::InvalidPhase.into());
+    }
+    state_data.fill(0);
+
+    let mut buffer_data = buffer_account.try_borrow_mut_data()?;
+    if buffer_data[0] == MANIFEST_TAG {
+        for shard in manifest_shards(buffer_account.key, &buffer_data, accounts)? {
+            if !shard.is_writable {
+                return Err(VerifierError::AccountNotWritable.into());
+            }
+            let mut shard_data = shard.try_borrow_mut_data()?;
+            shard_data[0] = BufferStatus::Empty as u8;
+            shard_data[42..46].copy_from_slice(&0u32.to_le_bytes()); // chunk_bitmap = 0
+        }
+        buffer_data[1] = BufferStatus::Empty as u8;
+        msg!("Shard manifest unlocked and verification state reset");
+        return Ok(());
+    }
+    buffer_data[0] = BufferStatus::Empty as u8;
+    buffer_data[1..3].copy_from_slice(&0u16.to_le_bytes()); // proof_len = 0
+    buffer_data[5..9].copy_from_slice(&0u32.to_le_bytes()); // chunk_bitmap = 0
+
+    msg!("Proof buffer unlocked and verification state reset");
+    Ok(())
+}
+
+/// Initialize a shard manifest and bind its shards
+/// Data format: [num_public_inputs (u16 LE), shard_len (u32 LE)]
+///
+/// Shard `i` holds bytes `i * shard_len..` of `[public_inputs][proof]`; the
+/// last one may be shorter. A shard can only be bound to one manifest, but
+/// re-initializing its own manifest resets it.
+fn process_init_manifest(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    msg!("UltraHonk: InitManifest");
+
+    let (manifest_account, shards) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if !manifest_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    if data.len() < 6 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let num_pi = u16::from_le_bytes([data[0], data[1]]);
+    let shard_len = u32::from_le_bytes([data[2], data[3], data[4], data[5]]) as usize;
+    if shard_len == 0 || shard_len > MAX_SHARD_LEN {
+        msg!("Shard length must be 1..={} bytes", MAX_SHARD_LEN);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let total = num_pi as usize * 32 + PROOF_SIZE;
+    let shard_count = total.div_ceil(shard_len);
+    if shard_count > MAX_SHARDS || shards.len() != shard_count {
+        msg!(
+            "Expected {} shards (at most {}), got {}",
+            shard_count,
+            MAX_SHARDS,
+            shards.len()
+        );
+        return Err(VerifierError::InvalidShard.into());
+    }
+
+    let mut manifest_data = manifest_account.try_borrow_mut_data()?;
+    if manifest_data.len() < MANIFEST_HEADER_SIZE + shard_count * 32 {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    if manifest_data[0] == MANIFEST_TAG {
+        if manifest_data[1] == BufferStatus::Locked as u8 {
+            msg!("ERROR: Manifest is locked by a verification. Use ResetBuffer first.");
+            return Err(VerifierError::ProofBufferLocked.into());
+        }
+    } else {
+        check_buffer_unlocked(&manifest_data)?;
+    }
+
+    for (index, shard) in shards.iter().enumerate() {
+        let start = index * shard_len;
+        let len = shard_len.min(total - start);
+        if !shard.is_writable {
+            return Err(VerifierError::AccountNotWritable.into());
+        }
+
+        let mut shard_data = shard.try_borrow_mut_data()?;
+        if shard_data.len() < SHARD_HEADER_SIZE + len {
+            msg!("Shard {} too small: {} < {}", index, shard_data.len(), SHARD_HEADER_SIZE + len);
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let bound = &shard_data[1..33];
+        if bound != [0u8; 32] && bound != manifest_account.key.as_ref() {
+            msg!("ERROR: Shard {} is bound to another manifest", shard.key);
+            return Err(VerifierError::InvalidShard.into());
+        }
+
+        shard_data[0] = BufferStatus::Empty as u8;
+        shard_data[1..33].copy_from_slice(manifest_account.key.as_ref());
+        shard_data[33] = index as u8;
+        shard_data[34..38].copy_from_slice(&(start as u32).to_le_bytes());
+        shard_data[38..42].copy_from_slice(&(len as u32).to_le_bytes());
+        shard_data[42..46].copy_from_slice(&0u32.to_le_bytes()); // chunk_bitmap = 0
+
+        let key_start = MANIFEST_HEADER_SIZE + index * 32;
+        manifest_data[key_start..key_start + 32].copy_from_slice(shard.key.as_ref());
+    }
+
+    manifest_data[0] = MANIFEST_TAG;
+    manifest_data[1] = BufferStatus::Empty as u8;
+    manifest_data[2..4].copy_from_slice(&(PROOF_SIZE as u16).to_le_bytes());
+    manifest_data[4..6].copy_from_slice(&num_pi.to_le_bytes());
+    manifest_data[6] = shard_count as u8;
+
+    msg!(
+        "Manifest initialized: {} public inputs across {} shards",
+        num_pi,
+        shard_count
+    );
+    Ok(())
+}
+
+/// Upload a chunk of a shard
+/// Data format: [offset in shard (u32 LE), chunk_data...]
+fn process_upload_shard_chunk(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let shard_account = next_account_info(account_iter)?;
+    let manifest_account = next_account_info(account_iter)?;
+
+    if !shard_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    if data.len() <= 4 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let offset = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let chunk = &data[4..];
+
+    msg!(
+        "UltraHonk: UploadShardChunk offset={} len={}",
+        offset,
+        chunk.len()
+    );
+
+    let manifest_data = manifest_account.try_borrow_data()?;
+    if manifest_data.first() != Some(&MANIFEST_TAG) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if manifest_data[1] == BufferStatus::Locked as u8 {
+        msg!("ERROR: Manifest is locked by a verification. Use ResetBuffer first.");
+        return Err(VerifierError::ProofBufferLocked.into());
+    }
+
+    let mut shard_data = shard_account.try_borrow_mut_data()?;
+    if shard_data.len() < SHARD_HEADER_SIZE || shard_data[1..33] != manifest_account.key.to_bytes() {
+        msg!("ERROR: Shard {} is not bound to this manifest", shard_account.key);
+        return Err(VerifierError::InvalidShard.into());
+    }
+
+    let len = u32::from_le_bytes(shard_data[38..42].try_into().unwrap()) as usize;
+    if offset + chunk.len() > len {
+        msg!("Chunk exceeds shard: {} > {}", offset + chunk.len(), len);
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    let write_start = SHARD_HEADER_SIZE + offset;
+    shard_data[write_start..write_start + chunk.len()].copy_from_slice(chunk);
+
+    // Same chunk accounting as `UploadChunk`, relative to the shard
+    let bitmap = u32::from_le_bytes(shard_data[42..46].try_into().unwrap());
+    let bitmap = mark_written_slots(bitmap, offset, chunk.len(), len);
+    shard_data[42..46].copy_from_slice(&bitmap.to_le_bytes());
+
+    let num_chunks = len.div_ceil(MAX_CHUNK_SIZE);
+    let expected_bitmap = if num_chunks >= 32 {
+        u32::MAX
+    } else {
+        (1u32 << num_chunks) - 1
+    };
+    if bitmap == expected_bitmap {
+        shard_data[0] = BufferStatus::Ready as u8;
+        msg!("Shard upload complete: all {} chunks received", num_chunks);
+    } else {
+        shard_data[0] = BufferStatus::Uploading as u8;
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // VK Account Management
 // ============================================================================
 
 /// Initialize a VK buffer account
 /// The account must already be created with sufficient space
+///
+/// VK accounts have no upload authority, so a `Ready` or `Compiled` VK,
+/// which receipts may already vouch for, cannot be reset and re-uploaded.
 fn process_init_vk_buffer(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     msg!("UltraHonk: InitVkBuffer");
 
@@ -689,7 +1630,7 @@ fn process_init_vk_buffer(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
     let vk_account = next_account_info(account_iter)?;
 
     if !vk_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut vk_data = vk_account.try_borrow_mut_data()?;
@@ -700,6 +1641,10 @@ fn process_init_vk_buffer(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
         msg!("VK buffer too small: {} < {}", vk_data.len(), required_size);
         return Err(ProgramError::AccountDataTooSmall);
     }
+    if vk_data[0] == VkBufferStatus::Ready as u8 || vk_data[0] == VkBufferStatus::Compiled as u8 {
+        msg!("VK already uploaded, cannot re-initialize");
+        return Err(VerifierError::VkAlreadyCompiled.into());
+    }
 
     // Set header: status = Empty, vk_len = 0
     vk_data[0] = VkBufferStatus::Empty as u8;
@@ -720,7 +1665,7 @@ fn process_upload_vk_chunk(
     let vk_account = next_account_info(account_iter)?;
 
     if !vk_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     if data.len() < 2 {
@@ -738,6 +1683,18 @@ fn process_upload_vk_chunk(
 
     let mut vk_data = vk_account.try_borrow_mut_data()?;
 
+    // A compiled VK no longer holds raw bytes; chunks would corrupt it.
+    // A ready one is complete, and rewriting it would change the key
+    // under any receipt issued against it
+    if vk_data[0] == VkBufferStatus::Compiled as u8 {
+        msg!("VK already compiled, cannot upload more chunks");
+        return Err(VerifierError::VkAlreadyCompiled.into());
+    }
+    if vk_data[0] == VkBufferStatus::Ready as u8 {
+        msg!("VK already uploaded, cannot upload more chunks");
+        return Err(VerifierError::VkAlreadyCompiled.into());
+    }
+
     // Write chunk after header
     let write_start = VK_HEADER_SIZE + offset;
     let write_end = write_start + chunk.len();
@@ -757,9 +1714,15 @@ fn process_upload_vk_chunk(
         vk_data[1..3].copy_from_slice(&new_len.to_le_bytes());
     }
 
-    // Mark ready if full VK uploaded
+    // Mark ready once the VK, and the SRS slot if the account has one, is
+    // uploaded
+    let full_len = if vk_data.len() >= VK_ACCOUNT_SIZE_WITH_SRS {
+        VK_ACCOUNT_SIZE_WITH_SRS - VK_HEADER_SIZE
+    } else {
+        VK_SIZE
+    };
     let vk_len = u16::from_le_bytes([vk_data[1], vk_data[2]]) as usize;
-    if vk_len >= VK_SIZE {
+    if vk_len >= full_len {
         vk_data[0] = VkBufferStatus::Ready as u8;
         msg!("VK upload complete: {} bytes", vk_len);
     }
@@ -767,6 +1730,62 @@ fn process_upload_vk_chunk(
     Ok(())
 }
 
+/// Compile an uploaded VK in place
+///
+/// Runs the full `VerificationKey::from_bytes` validation once and replaces the
+/// raw bb bytes with the compiled layout. `vk_len` is updated to the compiled
+/// size and the status becomes `Compiled`.
+fn process_compile_vk(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("UltraHonk: CompileVk");
+
+    let account_iter = &mut accounts.iter();
+    let vk_account = next_account_info(account_iter)?;
+
+    if !vk_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+    if vk_account.owner != program_id {
+        msg!("VK account not owned by this program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut vk_data = vk_account.try_borrow_mut_data()?;
+
+    if vk_data[0] != VkBufferStatus::Ready as u8 {
+        msg!("VK buffer not ready for compilation, status={}", vk_data[0]);
+        return Err(VerifierError::VkBufferNotReady.into());
+    }
+
+    let vk = plonk_solana_core::key::VerificationKey::from_bytes(
+        &vk_data[VK_HEADER_SIZE..VK_HEADER_SIZE + VK_SIZE],
+    )
+    .map_err(|e| {
+        msg!("VK parse error: {:?}", e);
+        VerifierError::InvalidVk
+    })?;
+
+    // Compilation is the one-time point where the key is committed to, so
+    // reject malformed keys here instead of on every verification
+    vk.validate().map_err(|e| {
+        msg!("VK validation failed: {:?}", e);
+        VerifierError::InvalidVk
+    })?;
+    msg!("VK hash: {:?}", &vk.hash());
+
+    // The SRS point sits after the raw VK and is left alone by compilation
+    read_srs_g2(&vk_data)?;
+
+    vk.write_compiled(&mut vk_data[VK_HEADER_SIZE..VK_HEADER_SIZE + VK_SIZE])
+        .map_err(|_| ProgramError::AccountDataTooSmall)?;
+
+    let compiled_len = vk.compiled_size() as u16;
+    vk_data[1..3].copy_from_slice(&compiled_len.to_le_bytes());
+    vk_data[0] = VkBufferStatus::Compiled as u8;
+
+    msg!("VK compiled: {} bytes", compiled_len);
+    Ok(())
+}
+
 /// Parse VK from a VK account (REQUIRED - no embedded fallback for security)
 ///
 /// VK account is mandatory to ensure:
@@ -776,7 +1795,7 @@ fn process_upload_vk_chunk(
 ///
 /// Validates:
 /// - VK account is owned by this program
-/// - VK buffer status is Ready
+/// - VK buffer status is Ready (raw bb bytes) or Compiled (see CompileVk)
 /// - VK data is complete and parseable
 fn parse_vk(
     vk_account: &AccountInfo,
@@ -794,17 +1813,30 @@ fn parse_vk(
 
     let vk_data = vk_account.try_borrow_data()?;
 
+    // Compiled VKs were validated by CompileVk; just copy them out
+    if vk_data[0] == VkBufferStatus::Compiled as u8 {
+        msg!("Using compiled VK from account: {}", vk_account.key);
+        let compiled = plonk_solana_core::CompiledVk::from_bytes(
+            &vk_data[VK_HEADER_SIZE..VK_HEADER_SIZE + COMPILED_VK_SIZE],
+        )
+        .map_err(|e| {
+            msg!("Compiled VK error: {:?}", e);
+            VerifierError::InvalidVk
+        })?;
+        return Ok(compiled.to_key());
+    }
+
     // Check status
     if vk_data[0] != VkBufferStatus::Ready as u8 {
         msg!("VK buffer not ready, status={}", vk_data[0]);
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::VkBufferNotReady.into());
     }
 
     // Check length
     let vk_len = u16::from_le_bytes([vk_data[1], vk_data[2]]) as usize;
     if vk_len < VK_SIZE {
         msg!("VK incomplete: {} < {}", vk_len, VK_SIZE);
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::VkBufferIncomplete.into());
     }
 
     // Parse VK from account data
@@ -812,10 +1844,33 @@ fn parse_vk(
     msg!("Using VK from account: {}", vk_account.key);
     plonk_solana_core::key::VerificationKey::from_bytes(vk_bytes).map_err(|e| {
         msg!("VK parse error: {:?}", e);
-        ProgramError::InvalidAccountData
+        VerifierError::InvalidVk.into()
     })
 }
 
+/// Read the x·G2 SRS point stored with a VK
+///
+/// Accounts sized for the VK alone, or whose SRS slot was never written,
+/// use the default Ignition point. A supplied point must be a valid
+/// non-identity point on the twist.
+fn read_srs_g2(vk_data: &[u8]) -> Result<G2, VerifierError> {
+    let Some(slot) = vk_data.get(VK_SRS_G2_OFFSET..VK_ACCOUNT_SIZE_WITH_SRS) else {
+        return Ok(plonk_solana_core::default_srs_g2());
+    };
+    if slot.iter().all(|&b| b == 0) {
+        return Ok(plonk_solana_core::default_srs_g2());
+    }
+
+    let mut srs_g2 = [0u8; 128];
+    srs_g2.copy_from_slice(slot);
+    plonk_solana_core::validate_srs_g2(&srs_g2).map_err(|e| {
+        msg!("SRS G2 error: {:?}", e);
+        VerifierError::InvalidSrsG2
+    })?;
+    msg!("Using custom SRS G2 point");
+    Ok(srs_g2)
+}
+
 // ============================================================================
 // Phased Verification Instructions
 // ============================================================================
@@ -835,25 +1890,25 @@ fn process_phased_generate_challenges(
 
     // Verify state account is writable
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're in the right phase (Uninitialized or can restart)
     let current_phase = state.get_phase();
     if current_phase != phased::Phase::Uninitialized && current_phase != phased::Phase::Failed {
         msg!("Invalid phase: {:?}", current_phase);
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
-    // Read proof data from proof account
-    let proof_data = proof_account.try_borrow_data()?;
+    // Validate all chunks are uploaded and lock the buffer to this state
+    lock_proof_buffer(proof_account, state, accounts)?;
 
-    // Validate all chunks are uploaded
-    validate_proof_chunks_complete(&proof_data)?;
+    // Read proof data from proof account
+    let proof_data = borrow_proof(proof_account, accounts)?;
 
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
     let num_pi = u16::from_le_bytes([proof_data[3], proof_data[4]]) as usize;
@@ -881,13 +1936,14 @@ fn process_phased_generate_challenges(
 
     // Parse VK
     let vk = plonk_solana_core::key::VerificationKey::from_bytes(VK_BYTES)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidVk)?;
 
     // Parse proof
     let log_n = vk.log2_circuit_size as usize;
     let is_zk = true;
+    check_circuit_supported(log_n, is_zk, num_pi)?;
     let proof = plonk_solana_core::proof::Proof::from_bytes(proof_bytes, log_n, is_zk)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Generating challenges...");
     sol_log_compute_units();
@@ -895,7 +1951,7 @@ fn process_phased_generate_challenges(
     // Generate challenges - THIS IS THE EXPENSIVE PART
     let challenges = verify_step1_challenges(&vk, &proof, &public_inputs).map_err(|e| {
         msg!("Challenge generation failed: {:?}", e);
-        ProgramError::InvalidAccountData
+        challenge_error(&e)
     })?;
 
     msg!("Saving challenges to state...");
@@ -942,6 +1998,8 @@ fn process_phased_generate_challenges(
     state.shplonk_nu = challenges.shplonk_nu;
     state.shplonk_z = challenges.shplonk_z;
 
+    state.seal_challenges();
+
     // Update phase
     state.set_phase(phased::Phase::ChallengesGenerated);
 
@@ -960,21 +2018,23 @@ fn process_phased_verify_sumcheck(_program_id: &Pubkey, accounts: &[AccountInfo]
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're in the right phase
     if state.get_phase() != phased::Phase::ChallengesGenerated {
         msg!("Invalid phase: expected ChallengesGenerated");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -982,13 +2042,13 @@ fn process_phased_verify_sumcheck(_program_id: &Pubkey, accounts: &[AccountInfo]
 
     // Parse VK and proof
     let vk = plonk_solana_core::key::VerificationKey::from_bytes(VK_BYTES)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidVk)?;
     let proof = plonk_solana_core::proof::Proof::from_bytes(
         proof_bytes,
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct challenges from state
     let challenges = reconstruct_challenges(state);
@@ -1000,13 +2060,13 @@ fn process_phased_verify_sumcheck(_program_id: &Pubkey, accounts: &[AccountInfo]
     let sumcheck_ok = verify_step2_sumcheck(&vk, &proof, &challenges).map_err(|e| {
         msg!("Sumcheck failed: {:?}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::SumcheckFailed
     })?;
 
     if !sumcheck_ok {
         msg!("Sumcheck verification returned false");
         state.set_phase(phased::Phase::Failed);
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::SumcheckFailed.into());
     }
 
     state.sumcheck_passed = 1;
@@ -1027,21 +2087,23 @@ fn process_phased_compute_msm(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're in the right phase
     if state.get_phase() != phased::Phase::SumcheckVerified {
         msg!("Invalid phase: expected SumcheckVerified");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1049,13 +2111,13 @@ fn process_phased_compute_msm(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
 
     // Parse VK and proof
     let vk = plonk_solana_core::key::VerificationKey::from_bytes(VK_BYTES)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidVk)?;
     let proof = plonk_solana_core::proof::Proof::from_bytes(
         proof_bytes,
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct challenges from state
     let challenges = reconstruct_challenges(state);
@@ -1067,7 +2129,7 @@ fn process_phased_compute_msm(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
     let (p0, p1) = verify_step3_pairing_points(&vk, &proof, &challenges).map_err(|e| {
         msg!("MSM failed: {:?}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     // Save P0/P1 to state
@@ -1081,27 +2143,42 @@ fn process_phased_compute_msm(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
 }
 
 /// Phase 4: Final pairing check
-fn process_phased_final_check(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn process_phased_final_check(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     msg!("Phased: Final Pairing Check");
     sol_log_compute_units();
 
     let account_iter = &mut accounts.iter();
     let state_account = next_account_info(account_iter)?;
+    let vk_account = account_iter.next(); // optional
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're in the right phase
     if state.get_phase() != phased::Phase::MsmComputed {
         msg!("Invalid phase: expected MsmComputed");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
+    // Without the VK account only the default SRS point can be used
+    let srs_g2 = match vk_account {
+        Some(vk_account) => {
+            check_bound_vk(state, vk_account)?;
+            if vk_account.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            read_srs_g2(&vk_account.try_borrow_data()?)?
+        }
+        None => plonk_solana_core::default_srs_g2(),
+    };
+
     msg!("Running pairing check...");
     sol_log_compute_units();
 
@@ -1130,11 +2207,12 @@ fn process_phased_final_check(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
     );
 
     // Final pairing check
-    let pairing_ok = verify_step4_pairing_check(&state.p0, &state.p1).map_err(|e| {
-        msg!("Pairing check failed: {:?}", e);
-        state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
-    })?;
+    let pairing_ok = verify_step4_pairing_check_with_srs(&state.p0, &state.p1, &srs_g2)
+        .map_err(|e| {
+            msg!("Pairing check failed: {:?}", e);
+            state.set_phase(phased::Phase::Failed);
+            VerifierError::PairingFailed
+        })?;
 
     if pairing_ok {
         state.verified = 1;
@@ -1144,7 +2222,7 @@ fn process_phased_final_check(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
         state.verified = 0;
         state.set_phase(phased::Phase::Failed);
         msg!("❌ Pairing check failed");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::PairingFailed.into());
     }
 
     sol_log_compute_units();
@@ -1173,53 +2251,54 @@ fn process_phase1_full(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     let vk_account = next_account_info(account_iter)?; // REQUIRED
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
-    // Read proof buffer header
-    let proof_data = proof_account.try_borrow_data()?;
+    // Validate all chunks are uploaded and lock the buffer to this state
+    {
+        let mut state_data = state_account.try_borrow_mut_data()?;
+        let state = phased::VerificationState::from_bytes_mut(&mut state_data)
+            .ok_or(VerifierError::InvalidStateAccount)?;
+        lock_proof_buffer(proof_account, state, accounts)?;
+    }
 
-    // Validate all chunks are uploaded
-    validate_proof_chunks_complete(&proof_data)?;
+    // Read proof buffer header
+    let proof_data = borrow_proof(proof_account, accounts)?;
 
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
     let num_pi = u16::from_le_bytes([proof_data[3], proof_data[4]]) as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_bytes = &proof_data[pi_end..pi_end + proof_len];
 
-    // Parse public inputs
-    let mut public_inputs: Vec<Fr> = Vec::with_capacity(num_pi);
-    for i in 0..num_pi {
-        let start = BUFFER_HEADER_SIZE + (i * 32);
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&proof_data[start..start + 32]);
-        public_inputs.push(arr);
-    }
+    // Public inputs are read in place from the buffer, not collected
+    let public_inputs = &proof_data[BUFFER_HEADER_SIZE..pi_end];
 
     // Parse VK from account (validates ownership)
     let vk = parse_vk(vk_account, program_id)?;
     let log_n = vk.log2_circuit_size as usize;
     let is_zk = true;
+    check_circuit_supported(log_n, is_zk, num_pi)?;
 
     // Parse proof
     let proof = plonk_solana_core::proof::Proof::from_bytes(proof_bytes, log_n, is_zk)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Phase 1a: eta/beta/gamma");
     sol_log_compute_units();
 
     // === PHASE 1A: eta, beta, gamma ===
-    let result_1a = generate_challenges_phase1a(&vk, &proof, &public_inputs)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let result_1a = generate_challenges_phase1a_streamed(&vk, &proof, public_inputs)
+        .map_err(|e| challenge_error(&e))?;
 
     // Write 1a results to state IMMEDIATELY
     {
         let mut state_data = state_account.try_borrow_mut_data()?;
         let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         
         // SECURITY: Store VK account to prevent using different VK in later phases
         state.vk_account = vk_account.key.to_bytes();
+        state.vk_hash = vk_hash(vk_account)?;
         
         state.log_n = log_n as u8;
         state.is_zk = 1;
@@ -1239,14 +2318,14 @@ fn process_phase1_full(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     let transcript_1a = result_1a.transcript_state;
     drop(result_1a); // Free heap
 
-    let result_1b = generate_challenges_phase1b(&proof, &transcript_1a)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let result_1b =
+        generate_challenges_phase1b(&proof, &transcript_1a).map_err(|e| challenge_error(&e))?;
 
     // Write 1b results
     {
         let mut state_data = state_account.try_borrow_mut_data()?;
         let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         for (i, alpha) in result_1b.alphas.iter().enumerate() {
             state.alphas[i] = *alpha;
         }
@@ -1264,14 +2343,14 @@ fn process_phase1_full(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     let transcript_1b = result_1b.transcript_state;
     drop(result_1b);
 
-    let result_1c = generate_challenges_phase1c(&proof, &transcript_1b)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let result_1c =
+        generate_challenges_phase1c(&proof, &transcript_1b).map_err(|e| challenge_error(&e))?;
 
     // Write 1c results
     {
         let mut state_data = state_account.try_borrow_mut_data()?;
         let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         for (i, sc) in result_1c.sumcheck_challenges.iter().enumerate() {
             if i < 14 {
                 state.sumcheck_challenges[i] = *sc;
@@ -1288,13 +2367,13 @@ fn process_phase1_full(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     drop(result_1c);
 
     let result_1d = generate_challenges_phase1d(&proof, &transcript_1c, is_zk)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|e| challenge_error(&e))?;
 
     // Write 1d results
     {
         let mut state_data = state_account.try_borrow_mut_data()?;
         let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         // result_1d.sumcheck_challenges contains challenges 14-27 as indices 0-13
         for (i, sc) in result_1d.sumcheck_challenges.iter().enumerate() {
             state.sumcheck_challenges[14 + i] = *sc;
@@ -1314,18 +2393,18 @@ fn process_phase1_full(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     let (beta, gamma) = {
         let state_data = state_account.try_borrow_data()?;
         let state = phased::VerificationState::from_bytes(&state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         (state.beta, state.gamma)
     };
 
     // Compute delta part 1
-    let partial = compute_delta_part1(&public_inputs, &proof, &beta, &gamma, vk.circuit_size());
+    let partial = compute_delta_part1_streamed(public_inputs, &proof, &beta, &gamma, vk.circuit_size());
 
     // Write partial results
     {
         let mut state_data = state_account.try_borrow_mut_data()?;
         let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         state.delta_numerator = partial.numerator;
         state.delta_denominator = partial.denominator;
         state.delta_numerator_acc = partial.numerator_acc;
@@ -1339,8 +2418,9 @@ fn process_phase1_full(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     {
         let mut state_data = state_account.try_borrow_mut_data()?;
         let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-            .ok_or(ProgramError::InvalidAccountData)?;
+            .ok_or(VerifierError::InvalidStateAccount)?;
         state.public_input_delta = delta;
+        state.seal_challenges();
         state.set_phase(phased::Phase::ChallengesGenerated);
         state.set_challenge_sub_phase(phased::ChallengeSubPhase::DeltaComputed);
     }
@@ -1360,57 +2440,52 @@ fn process_phase1a_eta_beta_gamma(_program_id: &Pubkey, accounts: &[AccountInfo]
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're at the start
     let sub_phase = state.get_challenge_sub_phase();
     if sub_phase != phased::ChallengeSubPhase::NotStarted {
         msg!("Invalid sub-phase: expected NotStarted");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
-    // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    // Validate all chunks are uploaded and lock the buffer to this state
+    lock_proof_buffer(proof_account, state, accounts)?;
 
-    // Validate all chunks are uploaded
-    validate_proof_chunks_complete(&proof_data)?;
+    // Read proof data
+    let proof_data = borrow_proof(proof_account, accounts)?;
 
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
     let num_pi = u16::from_le_bytes([proof_data[3], proof_data[4]]) as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_bytes = &proof_data[pi_end..pi_end + proof_len];
 
-    // Parse public inputs
-    let mut public_inputs: Vec<Fr> = Vec::with_capacity(num_pi);
-    for i in 0..num_pi {
-        let start = BUFFER_HEADER_SIZE + (i * 32);
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&proof_data[start..start + 32]);
-        public_inputs.push(arr);
-    }
+    // Public inputs are read in place from the buffer, not collected
+    let public_inputs = &proof_data[BUFFER_HEADER_SIZE..pi_end];
 
     msg!("Parsing VK/Proof...");
     sol_log_compute_units();
 
     // Parse VK and proof
     let vk = plonk_solana_core::key::VerificationKey::from_bytes(VK_BYTES)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidVk)?;
     let log_n = vk.log2_circuit_size as usize;
     let is_zk = true;
+    check_circuit_supported(log_n, is_zk, num_pi)?;
     let proof = plonk_solana_core::proof::Proof::from_bytes(proof_bytes, log_n, is_zk)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Generating eta/beta/gamma...");
     sol_log_compute_units();
 
     // Generate phase 1a challenges
-    let result = generate_challenges_phase1a(&vk, &proof, &public_inputs)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let result = generate_challenges_phase1a_streamed(&vk, &proof, public_inputs)
+        .map_err(|e| challenge_error(&e))?;
 
     // Save to state
     state.log_n = log_n as u8;
@@ -1441,21 +2516,21 @@ fn process_phase1b_alphas_gates(_program_id: &Pubkey, accounts: &[AccountInfo])
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check sub-phase
     if state.get_challenge_sub_phase() != phased::ChallengeSubPhase::EtaBetaGammaDone {
         msg!("Invalid sub-phase: expected EtaBetaGammaDone");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1466,13 +2541,13 @@ fn process_phase1b_alphas_gates(_program_id: &Pubkey, accounts: &[AccountInfo])
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Generating alphas/gates...");
     sol_log_compute_units();
 
     let result = generate_challenges_phase1b(&proof, &state.transcript_state)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|e| challenge_error(&e))?;
 
     // Save alphas
     for (i, alpha) in result.alphas.iter().enumerate() {
@@ -1520,20 +2595,20 @@ fn process_phase1c_sumcheck_half(_program_id: &Pubkey, accounts: &[AccountInfo])
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     if state.get_challenge_sub_phase() != phased::ChallengeSubPhase::AlphasGatesDone {
         msg!("Invalid sub-phase: expected AlphasGatesDone");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1544,13 +2619,13 @@ fn process_phase1c_sumcheck_half(_program_id: &Pubkey, accounts: &[AccountInfo])
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Generating sumcheck 0-13...");
     sol_log_compute_units();
 
     let result = generate_challenges_phase1c(&proof, &state.transcript_state)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|e| challenge_error(&e))?;
 
     // Save sumcheck challenges (first 14)
     for (i, sc) in result.sumcheck_challenges.iter().enumerate() {
@@ -1590,20 +2665,20 @@ fn process_phase1d_sumcheck_rest(_program_id: &Pubkey, accounts: &[AccountInfo])
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     if state.get_challenge_sub_phase() != phased::ChallengeSubPhase::SumcheckHalfDone {
         msg!("Invalid sub-phase: expected SumcheckHalfDone");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1614,7 +2689,7 @@ fn process_phase1d_sumcheck_rest(_program_id: &Pubkey, accounts: &[AccountInfo])
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Generating sumcheck 14-27 + final...");
     sol_log_compute_units();
@@ -1633,7 +2708,7 @@ fn process_phase1d_sumcheck_rest(_program_id: &Pubkey, accounts: &[AccountInfo])
     );
 
     let result = generate_challenges_phase1d(&proof, &state.transcript_state, state.is_zk != 0)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|e| challenge_error(&e))?;
 
     // Save remaining sumcheck challenges (14-27)
     for (i, sc) in result.sumcheck_challenges.iter().enumerate() {
@@ -1661,48 +2736,42 @@ fn process_phase1e1_delta_part1(_program_id: &Pubkey, accounts: &[AccountInfo])
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     if state.get_challenge_sub_phase() != phased::ChallengeSubPhase::AllChallengesDone {
         msg!("Invalid sub-phase: expected AllChallengesDone");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
     // Read proof and public inputs
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
     let proof_bytes = &proof_data[pi_end..pi_end + proof_len];
 
-    // Parse public inputs
-    let mut public_inputs: Vec<Fr> = Vec::with_capacity(num_pi);
-    for i in 0..num_pi {
-        let start = BUFFER_HEADER_SIZE + (i * 32);
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&proof_data[start..start + 32]);
-        public_inputs.push(arr);
-    }
+    // Public inputs are read in place from the buffer, not collected
+    let public_inputs = &proof_data[BUFFER_HEADER_SIZE..pi_end];
 
     let vk = plonk_solana_core::key::VerificationKey::from_bytes(VK_BYTES)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+        .map_err(|_| VerifierError::InvalidVk)?;
     let proof = plonk_solana_core::proof::Proof::from_bytes(
         proof_bytes,
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     msg!("Computing delta part1...");
     sol_log_compute_units();
 
-    let partial = compute_delta_part1(
-        &public_inputs,
+    let partial = compute_delta_part1_streamed(
+        public_inputs,
         &proof,
         &state.beta,
         &state.gamma,
@@ -1731,20 +2800,20 @@ fn process_phase1e2_delta_part2(_program_id: &Pubkey, accounts: &[AccountInfo])
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     if state.get_challenge_sub_phase() != phased::ChallengeSubPhase::DeltaPart1Done {
         msg!("Invalid sub-phase: expected DeltaPart1Done");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1755,7 +2824,7 @@ fn process_phase1e2_delta_part2(_program_id: &Pubkey, accounts: &[AccountInfo])
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct partial result
     let partial = DeltaPartialResult {
@@ -1773,6 +2842,7 @@ fn process_phase1e2_delta_part2(_program_id: &Pubkey, accounts: &[AccountInfo])
 
     state.public_input_delta = delta;
     state.set_challenge_sub_phase(phased::ChallengeSubPhase::DeltaComputed);
+    state.seal_challenges();
     state.set_phase(phased::Phase::ChallengesGenerated);
 
     msg!("Phase 1e2 complete - all challenges generated!");
@@ -1807,20 +2877,22 @@ fn process_phase2_rounds(
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase - must be ChallengesGenerated or SumcheckInProgress
     let phase = state.get_phase();
     if phase != phased::Phase::ChallengesGenerated && phase != phased::Phase::SumcheckInProgress {
         msg!("Invalid phase: expected ChallengesGenerated or SumcheckInProgress");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // Check rounds continuity
     let rounds_completed = state.sumcheck_rounds_completed as usize;
     if start_round != rounds_completed {
@@ -1829,11 +2901,24 @@ fn process_phase2_rounds(
             start_round,
             rounds_completed
         );
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::RoundOutOfOrder.into());
+    }
+
+    // A batch must fit one transaction; rounds past log_n are free
+    let plan = state_phase_plan(state);
+    let batch = end_round.min(state.log_n as usize).saturating_sub(start_round);
+    if !plan.fits_rounds(batch) {
+        msg!(
+            "Round batch {}-{} exceeds {} rounds per transaction",
+            start_round,
+            end_round,
+            plan.rounds_per_tx()
+        );
+        return Err(VerifierError::PhaseBatchTooLarge.into());
     }
 
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1844,7 +2929,7 @@ fn process_phase2_rounds(
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Get or initialize sumcheck state
     let prev_state = if start_round == 0 {
@@ -1874,7 +2959,7 @@ fn process_phase2_rounds(
         verify_sumcheck_rounds_partial(&proof, &challenges, &prev_state, start_round, end_round)
             .map_err(|e| {
                 msg!("Rounds {}-{} failed: {}", start_round, end_round, e);
-                ProgramError::InvalidAccountData
+                VerifierError::SumcheckRoundFailed(e.round as u8)
             })?;
 
     // Save intermediate state
@@ -1898,9 +2983,68 @@ fn process_phase2_rounds(
     Ok(())
 }
 
-/// Phase 2d: Verify relations and final check
-fn process_phase2d_relations(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
-    msg!("Phase 2d: relations");
+/// Phase 2c: Commit to the relation batching coefficients (optional)
+///
+/// Derives one coefficient per subrelation from the stored alphas and pow
+/// factor and records their commitment. The relations phases can then take
+/// the coefficients, computed off-chain, in their instruction data instead
+/// of converting the alphas and pow factor themselves.
+fn process_phase2c_relation_coefficients(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Phase 2c: relation coefficients");
+    sol_log_compute_units();
+
+    let account_iter = &mut accounts.iter();
+    let state_account = next_account_info(account_iter)?;
+
+    if !state_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    let mut state_data = state_account.try_borrow_mut_data()?;
+    let state = phased::VerificationState::from_bytes_mut(&mut state_data)
+        .ok_or(VerifierError::InvalidStateAccount)?;
+
+    if state.get_phase() != phased::Phase::SumcheckInProgress {
+        msg!("Invalid phase: expected SumcheckInProgress");
+        return Err(VerifierError::InvalidPhase.into());
+    }
+
+    check_challenge_digest(state)?;
+
+    // The pow factor is final once every round is verified
+    if (state.sumcheck_rounds_completed as usize) < state.log_n as usize {
+        msg!(
+            "Not all rounds completed: {} < {}",
+            state.sumcheck_rounds_completed,
+            state.log_n
+        );
+        return Err(VerifierError::SumcheckIncomplete.into());
+    }
+
+    let coefficients = plonk_solana_core::relations::relation_coefficients(
+        &state.alphas,
+        &state.sumcheck_pow_partial,
+    );
+    state.relation_coefficients_hash =
+        plonk_solana_core::relations::relation_coefficients_commitment(&coefficients);
+
+    msg!("Phase 2c complete");
+    sol_log_compute_units();
+    Ok(())
+}
+
+/// Phase 2d: Verify relations and final check
+///
+/// `data` optionally carries the relation coefficients Phase 2c committed to.
+fn process_phase2d_relations(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    msg!("Phase 2d: relations");
     sol_log_compute_units();
 
     let account_iter = &mut accounts.iter();
@@ -1908,18 +3052,21 @@ fn process_phase2d_relations(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're in SumcheckInProgress with all rounds done
     if state.get_phase() != phased::Phase::SumcheckInProgress {
         msg!("Invalid phase: expected SumcheckInProgress");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
+
+    check_challenge_digest(state)?;
+
     // Verify all rounds are completed (rounds_completed >= log_n)
     let log_n = state.log_n as usize;
     if (state.sumcheck_rounds_completed as usize) < log_n {
@@ -1928,11 +3075,13 @@ fn process_phase2d_relations(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
             state.sumcheck_rounds_completed,
             log_n
         );
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::SumcheckIncomplete.into());
     }
 
+    let coefficients = read_relation_coefficients(state, data)?;
+
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -1943,7 +3092,7 @@ fn process_phase2d_relations(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct sumcheck state
     let sumcheck_state = SumcheckRoundsState {
@@ -1976,14 +3125,16 @@ fn process_phase2d_relations(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
     verify_sumcheck_relations(
         &proof,
         &relation_params,
-        &state.alphas,
+        relation_batching(state, coefficients.as_ref()),
         &sumcheck_u_challenges,
         &sumcheck_state,
         libra_challenge.as_ref(),
     )
     .map_err(|e| {
         msg!("Relations failed: {}", e);
-        ProgramError::InvalidAccountData
+        #[cfg(feature = "diagnostics")]
+        log_relations_diagnostics(&proof, state);
+        VerifierError::RelationsFailed
     })?;
 
     state.sumcheck_passed = 1;
@@ -1995,6 +3146,204 @@ fn process_phase2d_relations(_program_id: &Pubkey, accounts: &[AccountInfo]) ->
     Ok(())
 }
 
+/// Phase 2d1: First half of the relations
+///
+/// Stores the batched partial sum in `relations_partial` for Phase 2d2.
+/// `data` optionally carries the relation coefficients Phase 2c committed to.
+fn process_phase2d_relations_part1(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    msg!("Phase 2d1: relations part 1");
+    sol_log_compute_units();
+
+    let account_iter = &mut accounts.iter();
+    let state_account = next_account_info(account_iter)?;
+    let proof_account = next_account_info(account_iter)?;
+
+    if !state_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    let mut state_data = state_account.try_borrow_mut_data()?;
+    let state = phased::VerificationState::from_bytes_mut(&mut state_data)
+        .ok_or(VerifierError::InvalidStateAccount)?;
+
+    if state.get_phase() != phased::Phase::SumcheckInProgress
+        || state.get_sumcheck_sub_phase() == phased::SumcheckSubPhase::RelationsPart1Done
+    {
+        msg!("Invalid phase: expected SumcheckInProgress before relations");
+        return Err(VerifierError::InvalidPhase.into());
+    }
+
+    check_challenge_digest(state)?;
+
+    if (state.sumcheck_rounds_completed as usize) < state.log_n as usize {
+        msg!(
+            "Not all rounds completed: {} < {}",
+            state.sumcheck_rounds_completed,
+            state.log_n
+        );
+        return Err(VerifierError::SumcheckIncomplete.into());
+    }
+
+    let coefficients = read_relation_coefficients(state, data)?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
+    let proof = read_phased_proof(state, &proof_data)?;
+
+    let partial = verify_sumcheck_relations_part1(
+        &proof,
+        &relation_params_from_state(state),
+        relation_batching(state, coefficients.as_ref()),
+        &sumcheck_state_from_state(state),
+    )
+    .map_err(|e| {
+        msg!("Relations part 1 failed: {}", e);
+        VerifierError::RelationsFailed
+    })?;
+
+    state.relations_partial = partial;
+    state.set_sumcheck_sub_phase(phased::SumcheckSubPhase::RelationsPart1Done);
+
+    msg!("Phase 2d1 complete");
+    sol_log_compute_units();
+    Ok(())
+}
+
+/// Phase 2d2: Second half of the relations + final sumcheck check
+///
+/// `data` optionally carries the relation coefficients Phase 2c committed to.
+fn process_phase2d_relations_part2(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    msg!("Phase 2d2: relations part 2");
+    sol_log_compute_units();
+
+    let account_iter = &mut accounts.iter();
+    let state_account = next_account_info(account_iter)?;
+    let proof_account = next_account_info(account_iter)?;
+
+    if !state_account.is_writable {
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+
+    let mut state_data = state_account.try_borrow_mut_data()?;
+    let state = phased::VerificationState::from_bytes_mut(&mut state_data)
+        .ok_or(VerifierError::InvalidStateAccount)?;
+
+    if state.get_phase() != phased::Phase::SumcheckInProgress
+        || state.get_sumcheck_sub_phase() != phased::SumcheckSubPhase::RelationsPart1Done
+    {
+        msg!("Invalid phase: expected SumcheckInProgress(RelationsPart1Done)");
+        return Err(VerifierError::InvalidPhase.into());
+    }
+
+    check_challenge_digest(state)?;
+
+    let coefficients = read_relation_coefficients(state, data)?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
+    let proof = read_phased_proof(state, &proof_data)?;
+
+    let libra_challenge = if state.libra_challenge == [0u8; 32] {
+        None
+    } else {
+        Some(state.libra_challenge)
+    };
+
+    verify_sumcheck_relations_part2(
+        &proof,
+        &relation_params_from_state(state),
+        relation_batching(state, coefficients.as_ref()),
+        &state.sumcheck_challenges,
+        &sumcheck_state_from_state(state),
+        libra_challenge.as_ref(),
+        &state.relations_partial,
+    )
+    .map_err(|e| {
+        msg!("Relations failed: {}", e);
+        #[cfg(feature = "diagnostics")]
+        log_relations_diagnostics(&proof, state);
+        VerifierError::RelationsFailed
+    })?;
+
+    state.sumcheck_passed = 1;
+    state.set_sumcheck_sub_phase(phased::SumcheckSubPhase::RelationsDone);
+    state.set_phase(phased::Phase::SumcheckVerified);
+
+    msg!("Phase 2d2 complete - sumcheck verified!");
+    sol_log_compute_units();
+    Ok(())
+}
+
+/// Parse the proof from a proof buffer using the shape recorded in the state
+fn read_phased_proof<'a>(
+    state: &phased::VerificationState,
+    proof_data: &'a [u8],
+) -> Result<plonk_solana_core::proof::Proof<'a>, ProgramError> {
+    let num_pi = state.num_public_inputs as usize;
+    let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
+    let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
+    let proof_bytes = &proof_data[pi_end..pi_end + proof_len];
+
+    plonk_solana_core::proof::Proof::from_bytes(
+        proof_bytes,
+        state.log_n as usize,
+        state.is_zk != 0,
+    )
+    .map_err(|_| VerifierError::InvalidProof.into())
+}
+
+fn relation_params_from_state(
+    state: &phased::VerificationState,
+) -> plonk_solana_core::RelationParameters {
+    plonk_solana_core::RelationParameters {
+        eta: state.eta,
+        eta_two: state.eta_two,
+        eta_three: state.eta_three,
+        beta: state.beta,
+        gamma: state.gamma,
+        public_input_delta: state.public_input_delta,
+    }
+}
+
+fn sumcheck_state_from_state(state: &phased::VerificationState) -> SumcheckRoundsState {
+    SumcheckRoundsState {
+        target: state.sumcheck_target,
+        pow_partial: state.sumcheck_pow_partial,
+        rounds_completed: state.sumcheck_rounds_completed as usize,
+    }
+}
+
+/// Log which relation group a failed relations check diverged in, with
+/// the expected and actual grand sums and every group's share
+#[cfg(feature = "diagnostics")]
+fn log_relations_diagnostics(
+    proof: &plonk_solana_core::proof::Proof,
+    state: &phased::VerificationState,
+) {
+    let libra_challenge = (state.libra_challenge != [0u8; 32]).then_some(state.libra_challenge);
+    match plonk_solana_core::diagnose_sumcheck_relations(
+        proof,
+        &relation_params_from_state(state),
+        &state.alphas,
+        &state.sumcheck_challenges,
+        &sumcheck_state_from_state(state),
+        libra_challenge.as_ref(),
+    ) {
+        Ok(diagnostics) => {
+            msg!("{}", diagnostics);
+            for (group, share) in diagnostics.groups {
+                let share = plonk_solana_core::debug::fr_to_hex(&share);
+                msg!("  {}: {}", group, share);
+            }
+        }
+        Err(e) => msg!("Relations diagnostics failed: {}", e),
+    }
+}
+
 // ============================================================================
 // Sub-Phased MSM Computation (splits Phase 3)
 // ============================================================================
@@ -2009,12 +3358,12 @@ fn process_phase3a_weights(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase - we can start from SumcheckVerified or MsmInProgress with Phase3a not done
     let phase = state.get_phase();
@@ -2023,11 +3372,13 @@ fn process_phase3a_weights(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
             && state.get_shplemini_sub_phase() == phased::ShpleminiSubPhase::NotStarted)
     {
         msg!("Invalid phase: expected SumcheckVerified or MsmInProgress(NotStarted)");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2039,7 +3390,7 @@ fn process_phase3a_weights(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct challenges from state
     let challenges = reconstruct_challenges(state);
@@ -2051,7 +3402,7 @@ fn process_phase3a_weights(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
     let result = shplemini_phase3a(&proof, &challenges, state.log_n as usize).map_err(|e| {
         msg!("Phase 3a failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     // Save intermediate state as raw FrLimbs bytes (no Montgomery conversion!)
@@ -2084,23 +3435,25 @@ fn process_phase3b1_folding(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase
     if state.get_phase() != phased::Phase::MsmInProgress
         || state.get_shplemini_sub_phase() != phased::ShpleminiSubPhase::Phase3aDone
     {
         msg!("Invalid phase: expected MsmInProgress(Phase3aDone)");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2112,7 +3465,7 @@ fn process_phase3b1_folding(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct challenges and Phase 3a result from state
     // Load FrLimbs directly from raw bytes (no Montgomery conversion!)
@@ -2138,7 +3491,7 @@ fn process_phase3b1_folding(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         .map_err(|e| {
             msg!("Phase 3b1 failed: {}", e);
             state.set_phase(phased::Phase::Failed);
-            ProgramError::InvalidAccountData
+            VerifierError::MsmFailed
         })?;
 
     // Save fold_pos and const_acc as raw FrLimbs bytes
@@ -2157,32 +3510,39 @@ fn process_phase3b1_folding(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
 }
 
 /// Phase 3b2: Gemini + libra (~500K CUs)
-fn process_phase3b2_gemini(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn process_phase3b2_gemini(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
     msg!("Phase 3b2: gemini + libra");
     sol_log_compute_units();
+    let use_scratch = uses_scratch(data)?;
 
     let account_iter = &mut accounts.iter();
     let state_account = next_account_info(account_iter)?;
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase
     if state.get_phase() != phased::Phase::MsmInProgress
         || state.get_shplemini_sub_phase() != phased::ShpleminiSubPhase::Phase3b1Done
     {
         msg!("Invalid phase: expected MsmInProgress(Phase3b1Done)");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2194,7 +3554,7 @@ fn process_phase3b2_gemini(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // Reconstruct from state - load FrLimbs directly from raw bytes
     let challenges = reconstruct_challenges(state);
@@ -2233,7 +3593,7 @@ fn process_phase3b2_gemini(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
     .map_err(|e| {
         msg!("Phase 3b2 failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     // Save intermediate state as raw FrLimbs bytes
@@ -2249,6 +3609,19 @@ fn process_phase3b2_gemini(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
         }
     }
 
+    // Optionally compute the Phase 3c scalars now, so Phase 3c only runs the MSM
+    state.msm_scalars_hash = [0u8; 32];
+    if use_scratch {
+        let vk_account = next_account_info(account_iter)?;
+        let scratch = next_account_info(account_iter)?;
+        check_bound_vk(state, vk_account)?;
+        let vk = parse_vk(vk_account, program_id)?;
+        let result = phase3b_result_from_state(state);
+        let scalars = shplemini_msm_scalars(&vk, &challenges, &result, state.is_zk != 0);
+        write_msm_scratch(program_id, state_account.key, state, scratch, &scalars)?;
+        msg!("Cached {} MSM scalars in {}", scalars.len(), scratch.key);
+    }
+
     state.set_shplemini_sub_phase(phased::ShpleminiSubPhase::Phase3b2Done);
 
     msg!("Phase 3b2 complete!");
@@ -2262,7 +3635,13 @@ fn process_phase3b2_gemini(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pr
 
 /// Combined Phase 2d + 3a: Relations + Weights (~1.1M CUs)
 /// Saves 1 TX by running both in sequence
-fn process_phase2d_and_3a(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///
+/// `data` optionally carries the relation coefficients Phase 2c committed to.
+fn process_phase2d_and_3a(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
     msg!("Combined Phase 2d+3a: relations + weights");
     sol_log_compute_units();
 
@@ -2271,18 +3650,21 @@ fn process_phase2d_and_3a(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check we're in SumcheckInProgress with all rounds done
     if state.get_phase() != phased::Phase::SumcheckInProgress {
         msg!("Invalid phase: expected SumcheckInProgress");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
+
+    check_challenge_digest(state)?;
+
     let log_n = state.log_n as usize;
     if (state.sumcheck_rounds_completed as usize) < log_n {
         msg!(
@@ -2290,11 +3672,18 @@ fn process_phase2d_and_3a(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
             state.sumcheck_rounds_completed,
             log_n
         );
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::SumcheckIncomplete.into());
+    }
+
+    if !PhasePlan::fits_tx(state_phase_plan(state).relations_and_weights()) {
+        msg!("Relations + weights do not fit one transaction at log_n {}", log_n);
+        return Err(VerifierError::PhaseBatchTooLarge.into());
     }
 
+    let coefficients = read_relation_coefficients(state, data)?;
+
     // Read proof
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2305,7 +3694,7 @@ fn process_phase2d_and_3a(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     // === PHASE 2d: RELATIONS ===
     let sumcheck_state = SumcheckRoundsState {
@@ -2336,14 +3725,16 @@ fn process_phase2d_and_3a(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
     verify_sumcheck_relations(
         &proof,
         &relation_params,
-        &state.alphas,
+        relation_batching(state, coefficients.as_ref()),
         &sumcheck_u_challenges,
         &sumcheck_state,
         libra_challenge.as_ref(),
     )
     .map_err(|e| {
         msg!("Relations failed: {}", e);
-        ProgramError::InvalidAccountData
+        #[cfg(feature = "diagnostics")]
+        log_relations_diagnostics(&proof, state);
+        VerifierError::RelationsFailed
     })?;
 
     state.sumcheck_passed = 1;
@@ -2357,7 +3748,7 @@ fn process_phase2d_and_3a(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
     let result = shplemini_phase3a(&proof, &challenges, state.log_n as usize).map_err(|e| {
         msg!("Phase 3a failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     // Save intermediate state
@@ -2392,23 +3783,31 @@ fn process_phase3b_combined(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let proof_account = next_account_info(account_iter)?;
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase
     if state.get_phase() != phased::Phase::MsmInProgress
         || state.get_shplemini_sub_phase() != phased::ShpleminiSubPhase::Phase3aDone
     {
         msg!("Invalid phase: expected MsmInProgress(Phase3aDone)");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
+    }
+
+    check_challenge_digest(state)?;
+
+    let plan = state_phase_plan(state);
+    if !PhasePlan::fits_tx(plan.folding + plan.gemini) {
+        msg!("Folding + Gemini do not fit one transaction at log_n {}", state.log_n);
+        return Err(VerifierError::PhaseBatchTooLarge.into());
     }
 
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2419,7 +3818,7 @@ fn process_phase3b_combined(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
     let challenges = reconstruct_challenges(state);
     let phase3a_result = ShpleminiPhase3aResult {
@@ -2444,7 +3843,7 @@ fn process_phase3b_combined(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
             |e| {
                 msg!("Phase 3b1 failed: {}", e);
                 state.set_phase(phased::Phase::Failed);
-                ProgramError::InvalidAccountData
+                VerifierError::MsmFailed
             },
         )?;
 
@@ -2462,7 +3861,7 @@ fn process_phase3b_combined(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     .map_err(|e| {
         msg!("Phase 3b2 failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     // Save intermediate state
@@ -2491,9 +3890,15 @@ fn process_phase3b_combined(_program_id: &Pubkey, accounts: &[AccountInfo]) -> P
 ///   [0] state (writable) - verification state account
 ///   [1] proof_data (readonly) - proof buffer account  
 ///   [2] vk_account (REQUIRED, readonly) - VK account for the circuit
-fn process_phase3c_msm(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///   [3] scratch (readonly, only with `use_scratch`) - MSM scratch from Phase 3b2
+fn process_phase3c_msm(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
     msg!("Phase 3c: MSM");
     sol_log_compute_units();
+    let use_scratch = uses_scratch(data)?;
 
     let account_iter = &mut accounts.iter();
     let state_account = next_account_info(account_iter)?;
@@ -2501,34 +3906,29 @@ fn process_phase3c_msm(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
     let vk_account = next_account_info(account_iter)?; // REQUIRED
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase
     if state.get_phase() != phased::Phase::MsmInProgress
         || state.get_shplemini_sub_phase() != phased::ShpleminiSubPhase::Phase3b2Done
     {
         msg!("Invalid phase: expected MsmInProgress(Phase3b2Done)");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // SECURITY: Validate VK account matches the one used in Phase 1
     // This prevents attacks where different VKs are used across phases
-    if state.vk_account != vk_account.key.to_bytes() {
-        msg!(
-            "VK account mismatch! Phase 1 used {}, but this phase received {}",
-            Pubkey::from(state.vk_account),
-            vk_account.key
-        );
-        return Err(ProgramError::InvalidArgument);
-    }
+    check_bound_vk(state, vk_account)?;
 
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2541,76 +3941,18 @@ fn process_phase3c_msm(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
-
-    // Reconstruct challenges and Phase 3b result from state
-    let challenges = reconstruct_challenges(state);
+    .map_err(|_| VerifierError::InvalidProof)?;
 
-    // Debug: print key challenge values
-    msg!(
-        "rho[0..8]: {:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        challenges.rho[0],
-        challenges.rho[1],
-        challenges.rho[2],
-        challenges.rho[3],
-        challenges.rho[4],
-        challenges.rho[5],
-        challenges.rho[6],
-        challenges.rho[7]
-    );
-    msg!(
-        "const_acc[0..8]: {:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        state.shplemini_const_acc[0],
-        state.shplemini_const_acc[1],
-        state.shplemini_const_acc[2],
-        state.shplemini_const_acc[3],
-        state.shplemini_const_acc[4],
-        state.shplemini_const_acc[5],
-        state.shplemini_const_acc[6],
-        state.shplemini_const_acc[7]
-    );
-    msg!(
-        "unshifted[0..8]: {:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        state.shplemini_unshifted[0],
-        state.shplemini_unshifted[1],
-        state.shplemini_unshifted[2],
-        state.shplemini_unshifted[3],
-        state.shplemini_unshifted[4],
-        state.shplemini_unshifted[5],
-        state.shplemini_unshifted[6],
-        state.shplemini_unshifted[7]
-    );
-
-    // Load FrLimbs directly from raw bytes (no Montgomery conversion!)
-    let phase3b_result = ShpleminiPhase3bResult {
-        const_acc: FrLimbs::from_raw_bytes(&state.shplemini_const_acc),
-        gemini_scalars: state
-            .shplemini_gemini_scalars
-            .iter()
-            .map(|b| FrLimbs::from_raw_bytes(b))
-            .collect(),
-        libra_scalars: state
-            .shplemini_libra_scalars
-            .iter()
-            .map(|b| FrLimbs::from_raw_bytes(b))
-            .collect(),
-        r_pows: state
-            .shplemini_r_pows
-            .iter()
-            .map(|b| FrLimbs::from_raw_bytes(b))
-            .collect(),
-        unshifted: FrLimbs::from_raw_bytes(&state.shplemini_unshifted),
-        shifted: FrLimbs::from_raw_bytes(&state.shplemini_shifted),
-    };
+    let scalars = phase3c_scalars(program_id, accounts, state, &vk, use_scratch)?;
 
     msg!("Computing shplemini phase 3c (MSM)...");
     sol_log_compute_units();
 
     // Compute Phase 3c (final MSM)
-    let (p0, p1) = shplemini_phase3c(&proof, &vk, &challenges, &phase3b_result).map_err(|e| {
+    let (p0, p1) = shplemini_phase3c_cached(&proof, &vk, &scalars).map_err(|e| {
         msg!("Phase 3c failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     // Debug: print first 8 bytes of computed P0 and P1
@@ -2654,9 +3996,15 @@ fn process_phase3c_msm(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
 ///   [0] state (writable) - verification state account
 ///   [1] proof_data (readonly) - proof buffer account  
 ///   [2] vk_account (REQUIRED, readonly) - VK account for the circuit
-fn process_phase3c_and_pairing(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///   [3] scratch (readonly, only with `use_scratch`) - MSM scratch from Phase 3b2
+fn process_phase3c_and_pairing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
     msg!("Phase 3c+4: MSM + Pairing (combined)");
     sol_log_compute_units();
+    let use_scratch = uses_scratch(data)?;
 
     let account_iter = &mut accounts.iter();
     let state_account = next_account_info(account_iter)?;
@@ -2664,34 +4012,29 @@ fn process_phase3c_and_pairing(program_id: &Pubkey, accounts: &[AccountInfo]) ->
     let vk_account = next_account_info(account_iter)?; // REQUIRED
 
     if !state_account.is_writable {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::AccountNotWritable.into());
     }
 
     let mut state_data = state_account.try_borrow_mut_data()?;
     let state = phased::VerificationState::from_bytes_mut(&mut state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        .ok_or(VerifierError::InvalidStateAccount)?;
 
     // Check phase - must be after Phase 3b2
     if state.get_phase() != phased::Phase::MsmInProgress
         || state.get_shplemini_sub_phase() != phased::ShpleminiSubPhase::Phase3b2Done
     {
         msg!("Invalid phase: expected MsmInProgress(Phase3b2Done)");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::InvalidPhase.into());
     }
 
+    check_challenge_digest(state)?;
+
     // SECURITY: Validate VK account matches the one used in Phase 1
     // This prevents attacks where different VKs are used across phases
-    if state.vk_account != vk_account.key.to_bytes() {
-        msg!(
-            "VK account mismatch! Phase 1 used {}, but this phase received {}",
-            Pubkey::from(state.vk_account),
-            vk_account.key
-        );
-        return Err(ProgramError::InvalidArgument);
-    }
+    check_bound_vk(state, vk_account)?;
 
     // Read proof data
-    let proof_data = proof_account.try_borrow_data()?;
+    let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
     let num_pi = state.num_public_inputs as usize;
     let pi_end = BUFFER_HEADER_SIZE + (num_pi * 32);
     let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
@@ -2699,55 +4042,34 @@ fn process_phase3c_and_pairing(program_id: &Pubkey, accounts: &[AccountInfo]) ->
 
     // Parse VK from account (validates ownership) and proof
     let vk = parse_vk(vk_account, program_id)?;
+    let srs_g2 = read_srs_g2(&vk_account.try_borrow_data()?)?;
     let proof = plonk_solana_core::proof::Proof::from_bytes(
         proof_bytes,
         state.log_n as usize,
         state.is_zk != 0,
     )
-    .map_err(|_| ProgramError::InvalidAccountData)?;
+    .map_err(|_| VerifierError::InvalidProof)?;
 
-    // Reconstruct challenges and Phase 3b result
-    // Load FrLimbs directly from raw bytes (no Montgomery conversion!)
-    let challenges = reconstruct_challenges(state);
-    let phase3b_result = ShpleminiPhase3bResult {
-        const_acc: FrLimbs::from_raw_bytes(&state.shplemini_const_acc),
-        gemini_scalars: state
-            .shplemini_gemini_scalars
-            .iter()
-            .map(|b| FrLimbs::from_raw_bytes(b))
-            .collect(),
-        libra_scalars: state
-            .shplemini_libra_scalars
-            .iter()
-            .map(|b| FrLimbs::from_raw_bytes(b))
-            .collect(),
-        r_pows: state
-            .shplemini_r_pows
-            .iter()
-            .map(|b| FrLimbs::from_raw_bytes(b))
-            .collect(),
-        unshifted: FrLimbs::from_raw_bytes(&state.shplemini_unshifted),
-        shifted: FrLimbs::from_raw_bytes(&state.shplemini_shifted),
-    };
+    let scalars = phase3c_scalars(program_id, accounts, state, &vk, use_scratch)?;
 
     msg!("Computing MSM...");
     sol_log_compute_units();
 
     // Phase 3c: Compute P0/P1
-    let (p0, p1) = shplemini_phase3c(&proof, &vk, &challenges, &phase3b_result).map_err(|e| {
+    let (p0, p1) = shplemini_phase3c_cached(&proof, &vk, &scalars).map_err(|e| {
         msg!("MSM failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::MsmFailed
     })?;
 
     msg!("Running pairing check...");
     sol_log_compute_units();
 
     // Phase 4: Pairing check immediately
-    let pairing_ok = verify_step4_pairing_check(&p0, &p1).map_err(|e| {
+    let pairing_ok = verify_step4_pairing_check_with_srs(&p0, &p1, &srs_g2).map_err(|e| {
         msg!("Pairing failed: {}", e);
         state.set_phase(phased::Phase::Failed);
-        ProgramError::InvalidAccountData
+        VerifierError::PairingFailed
     })?;
 
     // Save final state
@@ -2763,7 +4085,7 @@ fn process_phase3c_and_pairing(program_id: &Pubkey, accounts: &[AccountInfo]) ->
         state.verified = 0;
         state.set_phase(phased::Phase::Failed);
         msg!("❌ Pairing check failed");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VerifierError::PairingFailed.into());
     }
 
     sol_log_compute_units();
@@ -2774,18 +4096,83 @@ fn process_phase3c_and_pairing(program_id: &Pubkey, accounts: &[AccountInfo]) ->
 // Verification Receipt Instructions
 // ============================================================================
 
+/// What `CreateReceipt` does when the receipt for the (VK, public inputs)
+/// pair already exists
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateReceiptPolicy {
+    /// Fail with `ReceiptAlreadyExists` (the default)
+    Error = 0,
+    /// Move the receipt's slot and timestamp to the new verification
+    Refresh = 1,
+    /// Increment the receipt's `verification_count`
+    Count = 2,
+}
+
+impl DuplicateReceiptPolicy {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Error),
+            1 => Some(Self::Refresh),
+            2 => Some(Self::Count),
+            _ => None,
+        }
+    }
+}
+
 /// Create a verification receipt PDA after successful verification
 ///
 /// Accounts:
-/// 0. state_account (readonly) - Must be in Complete phase with verified=1
+/// 0. state_account (writable) - Owned by the program, in Complete phase
+///    with verified=1; marked as recorded
 /// 1. proof_account (readonly) - For extracting public inputs hash
-/// 2. vk_account (readonly) - For PDA derivation
+/// 2. vk_account (readonly) - The VK Phase 1 bound to the state; part of
+///    the PDA seeds
 /// 3. receipt_pda (writable) - PDA to create
-/// 4. payer (signer) - Pays for account creation
+/// 4. payer (signer) - Pays for account creation; any account may sponsor
+///    the receipt for the prover
 /// 5. system_program - For CPI
-fn process_create_receipt(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///
+/// Data format: empty, [tag (32 bytes)], [tag (32 bytes), policy (1 byte)]
+/// or [tag (32 bytes), policy (1 byte), rent_beneficiary (32 bytes)]
+/// - `tag` is an application-defined value (nullifier, order id, ...)
+///   stored in the receipt for CPI readers
+/// - `policy` is a [`DuplicateReceiptPolicy`], `Error` if omitted
+/// - `rent_beneficiary` receives the rent when the payer closes the
+///   receipt, the payer itself if omitted
+///
+/// The state is marked as recorded, and a state already recorded is
+/// rejected, so replaying one verification cannot create, refresh or count
+/// a receipt twice. An updated receipt keeps its tag, rent payer and
+/// beneficiary; `Refresh` also records the new proof's digest and VK hash.
+///
+/// The receipt stores the VK's canonical hash next to the VK account, so
+/// readers can pin the circuit rather than the account it was uploaded to.
+/// The hash covers the account's SRS point (`VerificationKey::hash_with_srs`):
+/// a VK uploaded with a custom x·G2 accepts other proofs, so it must not
+/// share the hash of the same VK checked against the default SRS.
+///
+/// Returns a `VerificationResult` with the receipt's `verified_slot` and VK
+/// hash.
+fn process_create_receipt(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
     msg!("CreateReceipt");
 
+    if !matches!(data.len(), 0 | 32 | 33 | 65) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let tag: [u8; 32] = data.get(..32).map_or([0u8; 32], |t| t.try_into().unwrap());
+    let policy = match data.get(32) {
+        Some(&policy) => {
+            DuplicateReceiptPolicy::from_u8(policy).ok_or(ProgramError::InvalidInstructionData)?
+        }
+        None => DuplicateReceiptPolicy::Error,
+    };
+    let beneficiary: Option<[u8; 32]> = data.get(33..).and_then(|b| b.try_into().ok());
+
     let account_iter = &mut accounts.iter();
     let state_account = next_account_info(account_iter)?;
     let proof_account = next_account_info(account_iter)?;
@@ -2794,82 +4181,288 @@ fn process_create_receipt(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
     let payer = next_account_info(account_iter)?;
     let system_program = next_account_info(account_iter)?;
 
-    // Verify state account shows successful verification
-    let state_data = state_account.try_borrow_data()?;
-    let state = phased::VerificationState::from_bytes(&state_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
-
-    if state.get_phase() != phased::Phase::Complete || state.verified != 1 {
-        msg!("Verification not complete or failed");
-        return Err(ProgramError::InvalidAccountData);
+    // Anyone can create an account that reads as a completed state; only
+    // the program can have written one it owns
+    if state_account.owner != program_id {
+        msg!("State account not owned by verifier program");
+        return Err(VerifierError::InvalidStateAccount.into());
     }
 
-    // Compute public inputs hash from proof buffer
-    let proof_data = proof_account.try_borrow_data()?;
-    let num_pi = u16::from_le_bytes([proof_data[3], proof_data[4]]) as usize;
-    let pi_start = BUFFER_HEADER_SIZE;
-    let pi_end = pi_start + (num_pi * 32);
-    let public_inputs = &proof_data[pi_start..pi_end];
+    let (pi_hash, proof_digest, vk_hash, already_recorded) = {
+        // Verify state account shows successful verification
+        let state_data = state_account.try_borrow_data()?;
+        let state = phased::VerificationState::from_bytes(&state_data)
+            .ok_or(VerifierError::InvalidStateAccount)?;
+
+        if state.get_phase() != phased::Phase::Complete || state.verified != 1 {
+            msg!("Verification not complete or failed");
+            return Err(VerifierError::VerificationNotComplete.into());
+        }
 
-    // Hash public inputs using Keccak256
-    let pi_hash = solana_program::keccak::hash(public_inputs).to_bytes();
+        // The receipt vouches for the VK the proof was actually verified against
+        check_bound_vk(state, vk_account)?;
+        let srs_g2 = read_srs_g2(&vk_account.try_borrow_data()?)?;
+        let vk_hash = parse_vk(vk_account, program_id)?.hash_with_srs(&srs_g2);
+
+        // Compute public inputs hash and proof digest from proof buffer
+        let proof_data = borrow_bound_proof(state, proof_account, accounts)?;
+        let proof_len = u16::from_le_bytes([proof_data[1], proof_data[2]]) as usize;
+        let num_pi = u16::from_le_bytes([proof_data[3], proof_data[4]]) as usize;
+        let pi_start = BUFFER_HEADER_SIZE;
+        let pi_end = pi_start + (num_pi * 32);
+        let public_inputs = &proof_data[pi_start..pi_end];
+        let proof = &proof_data[pi_end..pi_end + proof_len];
+
+        (
+            solana_noir_receipt_seeds::pi_hash(public_inputs),
+            solana_noir_receipt_seeds::proof_digest(proof),
+            vk_hash,
+            state.receipt_recorded != 0,
+        )
+    };
 
     // Derive PDA and verify
-    let seeds: &[&[u8]] = &[phased::RECEIPT_SEED, vk_account.key.as_ref(), &pi_hash];
-    let (expected_pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    let (expected_pda, bump) =
+        solana_noir_receipt_seeds::derive_receipt_pda(vk_account.key, &pi_hash, program_id);
 
     if expected_pda != *receipt_pda.key {
         msg!("Invalid receipt PDA");
         return Err(ProgramError::InvalidSeeds);
     }
 
-    // Create the PDA account
-    let rent = solana_program::rent::Rent::default();
-    let space = phased::VerificationReceipt::SIZE;
-    let lamports = rent.minimum_balance(space);
-
-    let signer_seeds: &[&[u8]] = &[
-        phased::RECEIPT_SEED,
-        vk_account.key.as_ref(),
-        &pi_hash,
-        &[bump],
-    ];
-
-    // Build CreateAccount instruction manually (system program instruction 0)
-    // Layout: [instruction_type(4 bytes LE), lamports(8 bytes LE), space(8 bytes LE), owner(32 bytes)]
-    let mut create_account_data = Vec::with_capacity(4 + 8 + 8 + 32);
-    create_account_data.extend_from_slice(&0u32.to_le_bytes()); // SystemInstruction::CreateAccount = 0
-    create_account_data.extend_from_slice(&lamports.to_le_bytes());
-    create_account_data.extend_from_slice(&(space as u64).to_le_bytes());
-    create_account_data.extend_from_slice(program_id.as_ref());
-
-    let create_account_ix = solana_program::instruction::Instruction {
-        // System program ID (11111111111111111111111111111111)
-        program_id: Pubkey::new_from_array([0u8; 32]),
-        accounts: vec![
-            solana_program::instruction::AccountMeta::new(*payer.key, true),
-            solana_program::instruction::AccountMeta::new(*receipt_pda.key, true),
-        ],
-        data: create_account_data,
-    };
+    // The state is marked below, so one verification backs one receipt
+    // update at most
+    if !state_account.is_writable {
+        msg!("State must be writable to record the verification");
+        return Err(VerifierError::AccountNotWritable.into());
+    }
+    let receipt_exists = receipt_pda.owner == program_id;
+    if receipt_exists && policy == DuplicateReceiptPolicy::Error {
+        msg!("Receipt already exists");
+        return Err(VerifierError::ReceiptAlreadyExists.into());
+    }
+    if already_recorded {
+        msg!("Verification already recorded by a receipt");
+        return Err(VerifierError::StateAlreadyRecorded.into());
+    }
 
-    solana_program::program::invoke_signed(
-        &create_account_ix,
-        &[payer.clone(), receipt_pda.clone(), system_program.clone()],
-        &[signer_seeds],
-    )?;
+    let clock = solana_program::clock::Clock::get()?;
+    let verified_slot;
+
+    if receipt_exists {
+        // The receipt exists: apply the duplicate policy
+        let mut receipt_data = receipt_pda.try_borrow_mut_data()?;
+        let receipt = phased::VerificationReceipt::from_bytes_mut(&mut receipt_data)
+            .ok_or(VerifierError::InvalidReceiptAccount)?;
+        if policy == DuplicateReceiptPolicy::Refresh {
+            receipt.verified_slot = clock.slot;
+            receipt.verified_timestamp = clock.unix_timestamp;
+            receipt.proof_digest = proof_digest;
+            receipt.vk_hash = vk_hash;
+            msg!("✅ Receipt refreshed at slot {}", clock.slot);
+        } else {
+            receipt.verification_count = receipt.verification_count.saturating_add(1);
+            msg!("✅ Receipt counted {} verifications", receipt.verification_count);
+        }
+        verified_slot = receipt.verified_slot;
+    } else {
+        // Create the PDA account
+        let rent = solana_program::rent::Rent::default();
+        let space = phased::VerificationReceipt::SIZE;
+        let lamports = rent.minimum_balance(space);
+
+        let [prefix, vk_seed, pi_seed] =
+            solana_noir_receipt_seeds::receipt_seeds(vk_account.key, &pi_hash);
+        let signer_seeds: &[&[u8]] = &[prefix, vk_seed, pi_seed, &[bump]];
+
+        // Build CreateAccount instruction manually (system program instruction 0)
+        // Layout: [instruction_type(4 bytes LE), lamports(8 bytes LE), space(8 bytes LE), owner(32 bytes)]
+        let mut create_account_data = Vec::with_capacity(4 + 8 + 8 + 32);
+        create_account_data.extend_from_slice(&0u32.to_le_bytes()); // SystemInstruction::CreateAccount = 0
+        create_account_data.extend_from_slice(&lamports.to_le_bytes());
+        create_account_data.extend_from_slice(&(space as u64).to_le_bytes());
+        create_account_data.extend_from_slice(program_id.as_ref());
+
+        let create_account_ix = solana_program::instruction::Instruction {
+            // System program ID (11111111111111111111111111111111)
+            program_id: Pubkey::new_from_array([0u8; 32]),
+            accounts: vec![
+                solana_program::instruction::AccountMeta::new(*payer.key, true),
+                solana_program::instruction::AccountMeta::new(*receipt_pda.key, true),
+            ],
+            data: create_account_data,
+        };
 
-    // Initialize the receipt with timing data
-    let mut receipt_data = receipt_pda.try_borrow_mut_data()?;
-    let receipt = phased::VerificationReceipt::from_bytes_mut(&mut receipt_data)
-        .ok_or(ProgramError::InvalidAccountData)?;
+        solana_program::program::invoke_signed(
+            &create_account_ix,
+            &[payer.clone(), receipt_pda.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
 
-    let clock = solana_program::clock::Clock::get()?;
-    receipt.verified_slot = clock.slot;
-    receipt.verified_timestamp = clock.unix_timestamp;
+        // Initialize the receipt with timing data
+        let mut receipt_data = receipt_pda.try_borrow_mut_data()?;
+        let receipt = phased::VerificationReceipt::from_bytes_mut(&mut receipt_data)
+            .ok_or(VerifierError::InvalidReceiptAccount)?;
+
+        receipt.verified_slot = clock.slot;
+        receipt.verified_timestamp = clock.unix_timestamp;
+        receipt.vk_account = vk_account.key.to_bytes();
+        receipt.pi_hash = pi_hash;
+        receipt.bump = bump;
+        receipt.verification_count = 1;
+        receipt.rent_payer = payer.key.to_bytes();
+        receipt.rent_beneficiary = beneficiary.unwrap_or(receipt.rent_payer);
+        receipt.tag = tag;
+        receipt.proof_digest = proof_digest;
+        receipt.vk_hash = vk_hash;
+
+        msg!("✅ Receipt created at slot {}", clock.slot);
+        verified_slot = clock.slot;
+    }
+
+    let mut state_data = state_account.try_borrow_mut_data()?;
+    let state = phased::VerificationState::from_bytes_mut(&mut state_data)
+        .ok_or(VerifierError::InvalidStateAccount)?;
+    state.receipt_recorded = 1;
 
-    msg!("✅ Receipt created at slot {}", clock.slot);
+    set_verification_result(verified_slot, vk_hash, pi_hash);
+    Ok(())
+}
+
+/// Check a proof digest against the one a receipt recorded
+///
+/// Accounts:
+/// 0. receipt_pda (readonly) - Receipt of a completed verification
+/// 1. vk_account (readonly, optional) - Checked to be the receipt's VK
+///    account
+///
+/// Data format: [proof_digest (32 bytes)] - Keccak256 of the proof bytes
+///
+/// Lets a holder of the proof bytes confirm, for a few thousand CUs, that
+/// they are the proof the receipt was created from without re-running the
+/// verification. The receipt's address is re-derived from its stored
+/// seeds, so no other program-owned account can pass as a receipt.
+fn process_reverify_by_digest(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    msg!("ReverifyByDigest");
+
+    let proof_digest: [u8; 32] = data
+        .try_into()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let receipt_pda = next_account_info(account_iter)?;
+
+    if receipt_pda.owner != program_id {
+        msg!("Receipt not owned by verifier program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let receipt_data = receipt_pda.try_borrow_data()?;
+    let receipt = phased::VerificationReceipt::from_bytes(&receipt_data)
+        .ok_or(VerifierError::InvalidReceiptAccount)?;
+    let expected = solana_noir_receipt_seeds::create_receipt_address(
+        &Pubkey::new_from_array(receipt.vk_account),
+        &receipt.pi_hash,
+        receipt.bump,
+        program_id,
+    )
+    .map_err(|_| VerifierError::InvalidReceiptAccount)?;
+    if expected != *receipt_pda.key {
+        msg!("Account is not a receipt PDA");
+        return Err(VerifierError::InvalidReceiptAccount.into());
+    }
+
+    if receipt.proof_digest != proof_digest {
+        msg!("Proof digest does not match the receipt");
+        return Err(VerifierError::ProofDigestMismatch.into());
+    }
+
+    if let Ok(vk_account) = next_account_info(account_iter) {
+        if vk_account.key.to_bytes() != receipt.vk_account {
+            msg!("VK account {} is not the receipt's", vk_account.key);
+            return Err(VerifierError::VkAccountMismatch.into());
+        }
+    }
+
+    msg!("✅ Proof digest matches receipt");
+    set_verification_result(receipt.verified_slot, receipt.vk_hash, receipt.pi_hash);
+    Ok(())
+}
+
+/// Close a verification receipt, refunding its rent
+///
+/// Accounts:
+/// 0. receipt_pda (writable) - Receipt to close
+/// 1. rent_payer (signer, writable) - The payer recorded in the receipt
+/// 2. rent_beneficiary (writable) - The beneficiary recorded in the
+///    receipt; receives the lamports. Omitted when it is the rent payer
+///
+/// Only the payer can close a receipt, so a sponsor decides when, while
+/// the refund goes wherever the receipt was told to send it. Like
+/// `ReverifyByDigest`, the receipt's address is re-derived from its stored
+/// seeds, so no other program-owned account can be drained as a receipt.
+/// Once closed, the proof counts as unverified for CPI checks until a new
+/// receipt is created.
+fn process_close_receipt(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("CloseReceipt");
+
+    let account_iter = &mut accounts.iter();
+    let receipt_pda = next_account_info(account_iter)?;
+    let rent_payer = next_account_info(account_iter)?;
+
+    if receipt_pda.owner != program_id {
+        msg!("Receipt not owned by verifier program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !rent_payer.is_signer {
+        msg!("Rent payer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let beneficiary = {
+        let receipt_data = receipt_pda.try_borrow_data()?;
+        let receipt = phased::VerificationReceipt::from_bytes(&receipt_data)
+            .ok_or(VerifierError::InvalidReceiptAccount)?;
+        let expected = solana_noir_receipt_seeds::create_receipt_address(
+            &Pubkey::new_from_array(receipt.vk_account),
+            &receipt.pi_hash,
+            receipt.bump,
+            program_id,
+        )
+        .map_err(|_| VerifierError::InvalidReceiptAccount)?;
+        if expected != *receipt_pda.key {
+            msg!("Account is not a receipt PDA");
+            return Err(VerifierError::InvalidReceiptAccount.into());
+        }
+        if receipt.rent_payer != rent_payer.key.to_bytes() {
+            msg!("Signer did not pay for this receipt");
+            return Err(VerifierError::NotReceiptPayer.into());
+        }
+        if receipt.rent_beneficiary == receipt.rent_payer {
+            rent_payer
+        } else {
+            let beneficiary = next_account_info(account_iter)?;
+            if receipt.rent_beneficiary != beneficiary.key.to_bytes() {
+                msg!("{} is not the receipt's rent beneficiary", beneficiary.key);
+                return Err(VerifierError::NotReceiptBeneficiary.into());
+            }
+            beneficiary
+        }
+    };
+
+    let lamports = receipt_pda.lamports();
+    **receipt_pda.try_borrow_mut_lamports()? = 0;
+    **beneficiary.try_borrow_mut_lamports()? = beneficiary
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    receipt_pda.try_borrow_mut_data()?.fill(0);
 
+    msg!("Receipt closed, {} lamports refunded", lamports);
     Ok(())
 }
 
@@ -2925,12 +4518,17 @@ fn reconstruct_challenges(state: &phased::VerificationState) -> Challenges {
 ///
 /// Accounts:
 /// 0. state_account (writable) - State account to close (must be Complete or Failed)
-/// 1. proof_account (writable) - Proof buffer account to close
+/// 1. proof_account (writable) - Proof buffer account to close (if locked,
+///    it must be bound to `state_account`)
 /// 2. payer (signer, writable) - Receives the lamports
+/// 3. shards... (writable) - The manifest's shards, if `proof_account` is a
+///    shard manifest; they are closed too
+/// 4. scratch (writable, optional) - An MSM scratch bound to `state_account`
+///    is closed too; it may come before or among the shards
 ///
 /// Only closes if verification is complete or failed.
 /// VK and Receipt accounts are NOT closed (they should persist).
-fn process_close_accounts(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn process_close_accounts(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     msg!("Closing verification accounts");
 
     let account_iter = &mut accounts.iter();
@@ -2954,9 +4552,37 @@ fn process_close_accounts(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
                 "Can only close after verification complete or failed (phase={})",
                 phase
             );
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VerifierError::VerificationNotComplete.into());
         }
     }
+
+    // A locked buffer may only be closed together with its own state
+    let proof_data = proof_account.try_borrow_data()?;
+    let (status, shards) = if proof_data.first() == Some(&MANIFEST_TAG) {
+        (proof_data.get(1), manifest_shards(proof_account.key, &proof_data, accounts)?)
+    } else {
+        (proof_data.first(), Vec::new())
+    };
+    if status == Some(&(BufferStatus::Locked as u8)) {
+        let bound = phased::VerificationState::from_bytes(&state_data)
+            .is_some_and(|state| state.proof_buffer == proof_account.key.to_bytes());
+        if !bound {
+            msg!("Proof buffer is locked by another verification");
+            return Err(VerifierError::ProofBufferMismatch.into());
+        }
+    }
+    let scratches: Vec<&AccountInfo> = accounts[3..]
+        .iter()
+        .filter(|account| {
+            account.owner == program_id
+                && account.try_borrow_data().is_ok_and(|data| {
+                    data.len() == SCRATCH_ACCOUNT_SIZE
+                        && data[0] == SCRATCH_TAG
+                        && data[2..SCRATCH_HEADER_SIZE] == state_account.key.to_bytes()
+                })
+        })
+        .collect();
+    drop(proof_data);
     drop(state_data);
 
     // Transfer lamports from state account to payer
@@ -2984,9 +4610,22 @@ fn process_close_accounts(_program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
     let mut proof_data = proof_account.try_borrow_mut_data()?;
     proof_data.fill(0);
 
+    // A manifest's shards, and the state's MSM scratch, are closed with them
+    let mut shard_lamports = 0u64;
+    for shard in shards.into_iter().chain(scratches) {
+        let lamports = shard.lamports();
+        **shard.try_borrow_mut_lamports()? = 0;
+        **payer.try_borrow_mut_lamports()? = payer
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        shard.try_borrow_mut_data()?.fill(0);
+        shard_lamports += lamports;
+    }
+
     msg!(
         "Accounts closed, {} lamports recovered",
-        state_lamports + proof_lamports
+        state_lamports + proof_lamports + shard_lamports
     );
     Ok(())
 }
@@ -3010,10 +4649,784 @@ mod tests {
         assert_eq!(VK_BYTES.len(), VK_SIZE, "VK should be {} bytes", VK_SIZE);
     }
 
+    #[test]
+    fn test_compiled_vk_fits_raw_slot() {
+        // CompileVk rewrites the VK in place, so the compiled form must fit
+        assert!(COMPILED_VK_SIZE <= VK_SIZE);
+        let vk = plonk_solana_core::key::VerificationKey::from_bytes(VK_BYTES).unwrap();
+        assert_eq!(vk.compiled_size(), COMPILED_VK_SIZE);
+    }
+
+    #[test]
+    fn test_read_srs_g2() {
+        let default = plonk_solana_core::default_srs_g2();
+
+        // VK-only account and zeroed slot fall back to the default
+        let mut data = vec![0u8; VK_HEADER_SIZE + VK_SIZE];
+        assert_eq!(read_srs_g2(&data).unwrap(), default);
+        data.resize(VK_ACCOUNT_SIZE_WITH_SRS, 0);
+        assert_eq!(read_srs_g2(&data).unwrap(), default);
+
+        // An explicit point is validated and returned as-is
+        data[VK_SRS_G2_OFFSET..].copy_from_slice(&default);
+        assert_eq!(read_srs_g2(&data).unwrap(), default);
+
+        data[VK_ACCOUNT_SIZE_WITH_SRS - 1] ^= 1;
+        assert_eq!(read_srs_g2(&data), Err(VerifierError::InvalidSrsG2));
+    }
+
+    #[test]
+    fn test_vk_upload_frozen() {
+        let program_id = Pubkey::new_unique();
+        let vk_key = Pubkey::new_unique();
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+        let mut vk_data = vec![0u8; VK_ACCOUNT_SIZE_WITH_SRS];
+        let mut lamports = 1u64;
+        let vk = AccountInfo::new(
+            &vk_key,
+            false,
+            true,
+            &mut lamports,
+            &mut vk_data,
+            &program_id,
+            false,
+        );
+        let accounts = [vk.clone()];
+        let upload = |offset: usize, bytes: &[u8]| {
+            let mut data = (offset as u16).to_le_bytes().to_vec();
+            data.extend_from_slice(bytes);
+            process_upload_vk_chunk(&program_id, &accounts, &data)
+        };
+        let status = || vk.try_borrow_data().unwrap()[0];
+
+        // With an SRS slot, the VK alone does not make the account ready
+        process_init_vk_buffer(&program_id, &accounts).unwrap();
+        upload(0, VK_BYTES).unwrap();
+        assert_eq!(status(), VkBufferStatus::Uploading as u8);
+        upload(VK_SIZE, &plonk_solana_core::default_srs_g2()).unwrap();
+        assert_eq!(status(), VkBufferStatus::Ready as u8);
+
+        // A ready or compiled VK can be neither reset nor rewritten
+        for frozen in [VkBufferStatus::Ready, VkBufferStatus::Compiled] {
+            vk.try_borrow_mut_data().unwrap()[0] = frozen as u8;
+            let before = vk.try_borrow_data().unwrap().to_vec();
+            assert_eq!(
+                process_init_vk_buffer(&program_id, &accounts),
+                custom(VerifierError::VkAlreadyCompiled)
+            );
+            assert_eq!(
+                upload(0, &[0xff; 32]),
+                custom(VerifierError::VkAlreadyCompiled)
+            );
+            assert_eq!(vk.try_borrow_data().unwrap()[..], before[..]);
+        }
+    }
+
+    #[test]
+    fn test_error_codes_roundtrip() {
+        let errors = [
+            VerifierError::AccountNotWritable,
+            VerifierError::InvalidStateAccount,
+            VerifierError::VkAccountMismatch,
+            VerifierError::InvalidReceiptAccount,
+            VerifierError::NotReceiptPayer,
+            VerifierError::ChallengeDigestMismatch,
+            VerifierError::MsmScratchMismatch,
+            VerifierError::ReceiptAlreadyExists,
+            VerifierError::StateAlreadyRecorded,
+            VerifierError::NotReceiptBeneficiary,
+            VerifierError::ProofBufferNotReady,
+            VerifierError::ProofBufferIncomplete,
+            VerifierError::VkBufferNotReady,
+            VerifierError::VkBufferIncomplete,
+            VerifierError::VkAlreadyCompiled,
+            VerifierError::InvalidShard,
+            VerifierError::InvalidVk,
+            VerifierError::InvalidProof,
+            VerifierError::PublicInputMismatch,
+            VerifierError::InvalidSrsG2,
+            VerifierError::ProofDigestMismatch,
+            VerifierError::CircuitTooLarge,
+            VerifierError::RelationCoefficientsMismatch,
+            VerifierError::InvalidPhase,
+            VerifierError::RoundOutOfOrder,
+            VerifierError::SumcheckIncomplete,
+            VerifierError::VerificationNotComplete,
+            VerifierError::PhaseBatchTooLarge,
+            VerifierError::ChallengeGenerationFailed,
+            VerifierError::SumcheckFailed,
+            VerifierError::RelationsFailed,
+            VerifierError::MsmFailed,
+            VerifierError::PairingFailed,
+            VerifierError::SumcheckRoundFailed(0),
+            VerifierError::SumcheckRoundFailed(27),
+        ];
+        let mut codes: Vec<u32> = errors.iter().map(|e| e.code()).collect();
+        for (e, code) in errors.iter().zip(&codes) {
+            assert_eq!(VerifierError::from_code(*code), Some(*e));
+            assert_eq!(ProgramError::from(*e), ProgramError::Custom(*code));
+        }
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len(), "error codes must be unique");
+        assert_eq!(VerifierError::SumcheckRoundFailed(5).code(), 1005);
+        assert_eq!(VerifierError::from_code(0), None);
+    }
+
     #[test]
     fn test_buffer_layout() {
         // For 1 public input: header(5) + pi(32) + proof(16224) = 16261
         let expected = BUFFER_HEADER_SIZE + 32 + PROOF_SIZE;
         assert_eq!(expected, 16261);
     }
+
+    #[test]
+    fn test_proof_buffer_lock() {
+        let program_id = Pubkey::new_unique();
+        let (buffer_key, state_key, other_key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let num_chunks = (PROOF_SIZE + MAX_CHUNK_SIZE - 1) / MAX_CHUNK_SIZE;
+
+        let mut buffer_data = vec![0u8; BUFFER_HEADER_SIZE + 32 + PROOF_SIZE];
+        buffer_data[0] = BufferStatus::Ready as u8;
+        buffer_data[1..3].copy_from_slice(&(PROOF_SIZE as u16).to_le_bytes());
+        buffer_data[3..5].copy_from_slice(&1u16.to_le_bytes());
+        buffer_data[5..9].copy_from_slice(&((1u32 << num_chunks) - 1).to_le_bytes());
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        let mut other_data = vec![0u8; phased::VerificationState::SIZE];
+        let (mut l1, mut l2, mut l3) = (1u64, 1u64, 1u64);
+        let buffer = AccountInfo::new(
+            &buffer_key,
+            false,
+            true,
+            &mut l1,
+            &mut buffer_data,
+            &program_id,
+            false,
+        );
+        let state = AccountInfo::new(
+            &state_key,
+            false,
+            true,
+            &mut l2,
+            &mut state_data,
+            &program_id,
+            false,
+        );
+        let other = AccountInfo::new(
+            &other_key,
+            false,
+            true,
+            &mut l3,
+            &mut other_data,
+            &program_id,
+            false,
+        );
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+
+        // Phase 1 locks the buffer to its state
+        {
+            let mut data = state.try_borrow_mut_data().unwrap();
+            let state = phased::VerificationState::from_bytes_mut(&mut data).unwrap();
+            lock_proof_buffer(&buffer, state, &[]).unwrap();
+            assert_eq!(state.proof_buffer, buffer_key.to_bytes());
+        }
+        assert_eq!(
+            buffer.try_borrow_data().unwrap()[0],
+            BufferStatus::Locked as u8
+        );
+
+        // No more writes, and no other verification can use it
+        let accounts = [buffer.clone()];
+        assert_eq!(
+            process_upload_chunk(&program_id, &accounts, &[0, 0, 1]),
+            custom(VerifierError::ProofBufferLocked)
+        );
+        assert_eq!(
+            process_set_public_inputs(&program_id, &accounts, &[0; 32]),
+            custom(VerifierError::ProofBufferLocked)
+        );
+        assert_eq!(
+            process_init_buffer(&program_id, &accounts, &[1, 0]),
+            custom(VerifierError::ProofBufferLocked)
+        );
+        {
+            let mut data = other.try_borrow_mut_data().unwrap();
+            let other_state = phased::VerificationState::from_bytes_mut(&mut data).unwrap();
+            assert_eq!(
+                lock_proof_buffer(&buffer, other_state, &[]),
+                custom(VerifierError::ProofBufferLocked)
+            );
+            assert!(borrow_bound_proof(other_state, &buffer, &[]).is_err());
+        }
+        assert_eq!(
+            process_reset_buffer(&program_id, &[buffer.clone(), other.clone()]),
+            custom(VerifierError::ProofBufferMismatch)
+        );
+
+        // A successful verification stays until a receipt records it
+        let set_state = |verified: u8, recorded: u8| {
+            let mut data = state.try_borrow_mut_data().unwrap();
+            let state = phased::VerificationState::from_bytes_mut(&mut data).unwrap();
+            state.phase = phased::Phase::Complete as u8;
+            state.verified = verified;
+            state.receipt_recorded = recorded;
+        };
+        set_state(1, 0);
+        let before = state.try_borrow_data().unwrap().to_vec();
+        assert_eq!(
+            process_reset_buffer(&program_id, &[buffer.clone(), state.clone()]),
+            custom(VerifierError::InvalidPhase)
+        );
+        assert_eq!(state.try_borrow_data().unwrap()[..], before[..]);
+        assert_eq!(
+            buffer.try_borrow_data().unwrap()[0],
+            BufferStatus::Locked as u8
+        );
+        set_state(1, 1);
+
+        // Reset unlocks the buffer and unbinds the state
+        process_reset_buffer(&program_id, &[buffer.clone(), state.clone()]).unwrap();
+        assert_eq!(
+            buffer.try_borrow_data().unwrap()[0],
+            BufferStatus::Empty as u8
+        );
+        assert!(state.try_borrow_data().unwrap().iter().all(|&b| b == 0));
+        process_upload_chunk(&program_id, &accounts, &[0, 0, 1]).unwrap();
+    }
+
+    #[test]
+    fn test_upload_chunk_lengths() {
+        let program_id = Pubkey::new_unique();
+        let buffer_key = Pubkey::new_unique();
+        let mut buffer_data = vec![0u8; BUFFER_HEADER_SIZE + 32 + PROOF_SIZE];
+        buffer_data[3..5].copy_from_slice(&1u16.to_le_bytes());
+        let mut lamports = 1u64;
+        let buffer = AccountInfo::new(
+            &buffer_key,
+            false,
+            true,
+            &mut lamports,
+            &mut buffer_data,
+            &program_id,
+            false,
+        );
+        let accounts = [buffer.clone()];
+        let upload = |offset: usize, len: usize| {
+            let mut data = (offset as u16).to_le_bytes().to_vec();
+            data.extend(vec![7u8; len]);
+            process_upload_chunk(&program_id, &accounts, &data)
+        };
+        let bitmap = || {
+            u32::from_le_bytes(buffer.try_borrow_data().unwrap()[5..9].try_into().unwrap())
+        };
+        let status = || buffer.try_borrow_data().unwrap()[0];
+
+        // A byte in every slot marks none of them
+        let num_chunks = PROOF_SIZE.div_ceil(MAX_CHUNK_SIZE);
+        for slot in 0..num_chunks {
+            upload(slot * MAX_CHUNK_SIZE + 1, 1).unwrap();
+        }
+        assert_eq!(bitmap(), 0);
+        assert_eq!(status(), BufferStatus::Uploading as u8);
+
+        // A chunk longer than a slot, ending mid-slot, marks the slots it
+        // writes in full
+        upload(0, 2 * MAX_CHUNK_SIZE + 100).unwrap();
+        assert_eq!(bitmap(), 0b11);
+        assert_eq!(status(), BufferStatus::Uploading as u8);
+
+        // With every later slot written, the third is missing its first
+        // byte and stays unmarked
+        for slot in 3..num_chunks {
+            let len = MAX_CHUNK_SIZE.min(PROOF_SIZE - slot * MAX_CHUNK_SIZE);
+            upload(slot * MAX_CHUNK_SIZE, len).unwrap();
+        }
+        upload(2 * MAX_CHUNK_SIZE + 1, MAX_CHUNK_SIZE - 1).unwrap();
+        assert_eq!(bitmap().count_ones() as usize, num_chunks - 1);
+        assert_eq!(status(), BufferStatus::Uploading as u8);
+
+        // Nothing may be written past the end of the buffer
+        let rest = PROOF_SIZE - 2 * MAX_CHUNK_SIZE;
+        assert_eq!(
+            upload(2 * MAX_CHUNK_SIZE, rest + 1),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+
+        // The whole rest of the proof fits one upload
+        upload(2 * MAX_CHUNK_SIZE, rest).unwrap();
+        let data = buffer.try_borrow_data().unwrap();
+        assert_eq!(data[0], BufferStatus::Ready as u8);
+        assert_eq!(u16::from_le_bytes([data[1], data[2]]) as usize, PROOF_SIZE);
+    }
+
+    #[test]
+    fn test_upload_data() {
+        let program_id = Pubkey::new_unique();
+        let buffer_key = Pubkey::new_unique();
+        let mut buffer_data = vec![0u8; BUFFER_HEADER_SIZE + 2 * 32 + PROOF_SIZE];
+        buffer_data[3..5].copy_from_slice(&2u16.to_le_bytes());
+        let mut lamports = 1u64;
+        let buffer = AccountInfo::new(
+            &buffer_key,
+            false,
+            true,
+            &mut lamports,
+            &mut buffer_data,
+            &program_id,
+            false,
+        );
+        let accounts = [buffer.clone()];
+        let upload = |region: u8, offset: usize, bytes: &[u8]| {
+            let mut data = vec![region];
+            data.extend_from_slice(&(offset as u16).to_le_bytes());
+            data.extend_from_slice(bytes);
+            process_upload_data(&program_id, &accounts, &data)
+        };
+        let proof: Vec<u8> = (0..PROOF_SIZE).map(|i| i as u8).collect();
+        let (first, second) = proof.split_at(8 * MAX_CHUNK_SIZE);
+
+        // Public input and proof chunks interleave in any order
+        upload(BufferRegion::Proof as u8, 8 * MAX_CHUNK_SIZE, second).unwrap();
+        upload(BufferRegion::PublicInputs as u8, 32, &[2; 32]).unwrap();
+        upload(BufferRegion::Proof as u8, 0, first).unwrap();
+        upload(BufferRegion::PublicInputs as u8, 0, &[1; 32]).unwrap();
+        {
+            let data = buffer.try_borrow_data().unwrap();
+            assert_eq!(data[0], BufferStatus::Ready as u8);
+            assert_eq!(&data[BUFFER_HEADER_SIZE..BUFFER_HEADER_SIZE + 32], &[1; 32]);
+            assert_eq!(&data[BUFFER_HEADER_SIZE + 32..BUFFER_HEADER_SIZE + 64], &[2; 32]);
+            assert_eq!(&data[BUFFER_HEADER_SIZE + 64..], &proof[..]);
+        }
+
+        // Public inputs stay within the count InitBuffer set
+        assert_eq!(
+            upload(BufferRegion::PublicInputs as u8, 33, &[0; 32]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(upload(2, 0, &[0; 32]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(
+            process_upload_data(&program_id, &accounts, &[1, 0]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+
+        // SetPublicInputs takes the same path and still needs every input
+        assert_eq!(
+            process_set_public_inputs(&program_id, &accounts, &[0; 32]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        process_set_public_inputs(&program_id, &accounts, &[3; 64]).unwrap();
+        let data = buffer.try_borrow_data().unwrap();
+        assert_eq!(&data[BUFFER_HEADER_SIZE..BUFFER_HEADER_SIZE + 64], &[3; 64]);
+    }
+
+    #[test]
+    fn test_challenge_digest() {
+        let program_id = Pubkey::new_unique();
+        let state_key = Pubkey::new_unique();
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        {
+            let state = phased::VerificationState::from_bytes_mut(&mut state_data).unwrap();
+            state.beta = [3; 32];
+            state.alphas[7] = [5; 32];
+            state.sumcheck_challenges[27] = [9; 32];
+            state.seal_challenges();
+            assert!(check_challenge_digest(state).is_ok());
+
+            // Non-challenge fields are not covered
+            state.sumcheck_target = [1; 32];
+            assert!(state.challenges_intact());
+
+            state.sumcheck_challenges[27][31] ^= 1;
+            assert_eq!(
+                check_challenge_digest(state),
+                custom(VerifierError::ChallengeDigestMismatch)
+            );
+            state.set_phase(phased::Phase::MsmComputed);
+        }
+
+        // Later phases stop before using the overwritten challenges
+        let mut lamports = 1u64;
+        let state = AccountInfo::new(
+            &state_key,
+            false,
+            true,
+            &mut lamports,
+            &mut state_data,
+            &program_id,
+            false,
+        );
+        assert_eq!(
+            process_phased_final_check(&program_id, &[state]),
+            custom(VerifierError::ChallengeDigestMismatch)
+        );
+    }
+
+    #[test]
+    fn test_circuit_size_limits() {
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+        let max = MAX_LOG_N as usize;
+        assert!(check_circuit_supported(max, true, 32).is_ok());
+        assert_eq!(
+            check_circuit_supported(max + 1, true, 1),
+            custom(VerifierError::CircuitTooLarge)
+        );
+        assert_eq!(
+            check_circuit_supported(300, true, 1),
+            custom(VerifierError::CircuitTooLarge)
+        );
+
+        // Round batches are capped at the plan's rounds per transaction
+        let program_id = Pubkey::new_unique();
+        let (state_key, proof_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        let rounds_per_tx = {
+            let state = phased::VerificationState::from_bytes_mut(&mut state_data).unwrap();
+            state.log_n = MAX_LOG_N;
+            state.is_zk = 1;
+            state.set_phase(phased::Phase::ChallengesGenerated);
+            state.seal_challenges();
+            state_phase_plan(state).rounds_per_tx()
+        };
+        let mut proof_data = vec![0u8; BUFFER_HEADER_SIZE];
+        let (mut l1, mut l2) = (1u64, 1u64);
+        let accounts = [
+            AccountInfo::new(
+                &state_key,
+                false,
+                true,
+                &mut l1,
+                &mut state_data,
+                &program_id,
+                false,
+            ),
+            AccountInfo::new(
+                &proof_key,
+                false,
+                false,
+                &mut l2,
+                &mut proof_data,
+                &program_id,
+                false,
+            ),
+        ];
+        assert_eq!(
+            process_phase2_rounds(&program_id, &accounts, &[40, 0, rounds_per_tx + 1]),
+            custom(VerifierError::PhaseBatchTooLarge)
+        );
+        // A full batch passes the check and fails later on the unbound buffer
+        assert_eq!(
+            process_phase2_rounds(&program_id, &accounts, &[40, 0, rounds_per_tx]),
+            custom(VerifierError::ProofBufferMismatch)
+        );
+        // Rounds past log_n cost nothing
+        let end = MAX_LOG_N.saturating_add(rounds_per_tx);
+        let start = MAX_LOG_N - 1;
+        {
+            let mut data = accounts[0].try_borrow_mut_data().unwrap();
+            let state = phased::VerificationState::from_bytes_mut(&mut data).unwrap();
+            state.sumcheck_rounds_completed = start;
+        }
+        assert_eq!(
+            process_phase2_rounds(&program_id, &accounts, &[40, start, end]),
+            custom(VerifierError::ProofBufferMismatch)
+        );
+    }
+
+    #[test]
+    fn test_bound_vk() {
+        let program_id = Pubkey::new_unique();
+        let (vk_key, other_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+        let mut vk_data = vec![VkBufferStatus::Ready as u8; VK_HEADER_SIZE + VK_SIZE];
+        let mut other_data = vk_data.clone();
+        let (mut l1, mut l2) = (1u64, 1u64);
+        let vk = AccountInfo::new(
+            &vk_key,
+            false,
+            false,
+            &mut l1,
+            &mut vk_data,
+            &program_id,
+            false,
+        );
+        let other = AccountInfo::new(
+            &other_key,
+            false,
+            false,
+            &mut l2,
+            &mut other_data,
+            &program_id,
+            false,
+        );
+
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        let state = phased::VerificationState::from_bytes_mut(&mut state_data).unwrap();
+        assert_eq!(
+            check_bound_vk(state, &vk),
+            custom(VerifierError::VkAccountMismatch)
+        );
+
+        state.vk_account = vk_key.to_bytes();
+        state.vk_hash = vk_hash(&vk).unwrap();
+        check_bound_vk(state, &vk).unwrap();
+
+        // Same contents under another key, then the same key rewritten
+        assert_eq!(
+            check_bound_vk(state, &other),
+            custom(VerifierError::VkAccountMismatch)
+        );
+        vk.try_borrow_mut_data().unwrap()[VK_HEADER_SIZE] ^= 1;
+        assert_eq!(
+            check_bound_vk(state, &vk),
+            custom(VerifierError::VkAccountMismatch)
+        );
+    }
+
+    #[test]
+    fn test_msm_scratch() {
+        let program_id = Pubkey::new_unique();
+        let (state_key, other_key, scratch_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+        let scalars: Vec<Fr> = (0..MSM_MAX_TERMS as u8).map(|i| [i; 32]).collect();
+        let mut scratch_data = vec![0u8; SCRATCH_ACCOUNT_SIZE];
+        let mut lamports = 1u64;
+        let scratch = AccountInfo::new(
+            &scratch_key,
+            false,
+            true,
+            &mut lamports,
+            &mut scratch_data,
+            &program_id,
+            false,
+        );
+
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        let state = phased::VerificationState::from_bytes_mut(&mut state_data).unwrap();
+        assert_eq!(
+            read_msm_scratch(&program_id, &state_key, state, &scratch),
+            custom(VerifierError::MsmScratchMismatch)
+        );
+
+        write_msm_scratch(&program_id, &state_key, state, &scratch, &scalars).unwrap();
+        assert_eq!(
+            read_msm_scratch(&program_id, &state_key, state, &scratch),
+            Ok(scalars.clone())
+        );
+
+        // A retry may rewrite its own scratch, with fewer scalars too
+        write_msm_scratch(&program_id, &state_key, state, &scratch, &scalars[..3]).unwrap();
+        assert_eq!(
+            read_msm_scratch(&program_id, &state_key, state, &scratch),
+            Ok(scalars[..3].to_vec())
+        );
+
+        // Another state can neither read nor take over the scratch
+        assert_eq!(
+            read_msm_scratch(&program_id, &other_key, state, &scratch),
+            custom(VerifierError::MsmScratchMismatch)
+        );
+        assert_eq!(
+            write_msm_scratch(&program_id, &other_key, state, &scratch, &scalars),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        scratch.try_borrow_mut_data().unwrap()[SCRATCH_HEADER_SIZE + 40] ^= 1;
+        assert_eq!(
+            read_msm_scratch(&program_id, &state_key, state, &scratch),
+            custom(VerifierError::MsmScratchMismatch)
+        );
+    }
+
+    #[test]
+    fn test_relation_coefficients() {
+        let program_id = Pubkey::new_unique();
+        let state_key = Pubkey::new_unique();
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        let (alphas, pow_partial) = {
+            let state = phased::VerificationState::from_bytes_mut(&mut state_data).unwrap();
+            for (i, alpha) in state.alphas.iter_mut().enumerate() {
+                alpha[31] = i as u8 + 2;
+            }
+            state.sumcheck_pow_partial[31] = 7;
+            state.log_n = 3;
+            state.sumcheck_rounds_completed = 2;
+            state.set_phase(phased::Phase::SumcheckInProgress);
+            state.seal_challenges();
+            (state.alphas, state.sumcheck_pow_partial)
+        };
+        let coefficients = plonk_solana_core::relations::relation_coefficients_to_bytes(
+            &plonk_solana_core::relations::relation_coefficients(&alphas, &pow_partial),
+        );
+
+        let mut lamports = 1u64;
+        let state = AccountInfo::new(
+            &state_key,
+            false,
+            true,
+            &mut lamports,
+            &mut state_data,
+            &program_id,
+            false,
+        );
+        let phase2c =
+            || process_phase2c_relation_coefficients(&program_id, std::slice::from_ref(&state));
+        let read = |data: &[u8]| {
+            let state_data = state.try_borrow_data().unwrap();
+            let state = phased::VerificationState::from_bytes(&state_data).unwrap();
+            read_relation_coefficients(state, data).map(|c| c.is_some())
+        };
+
+        // Nothing is committed until every round is verified
+        assert_eq!(phase2c(), custom(VerifierError::SumcheckIncomplete));
+        assert_eq!(read(&[]), Ok(false));
+        assert_eq!(
+            read(&coefficients),
+            Err(VerifierError::RelationCoefficientsMismatch.into())
+        );
+
+        phased::VerificationState::from_bytes_mut(&mut state.try_borrow_mut_data().unwrap())
+            .unwrap()
+            .sumcheck_rounds_completed = 3;
+        phase2c().unwrap();
+        assert_eq!(read(&coefficients), Ok(true));
+        assert_eq!(read(&[]), Ok(false));
+        assert_eq!(
+            read(&coefficients[..32]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+
+        let mut tampered = coefficients;
+        tampered[5 * 32] ^= 1;
+        assert_eq!(
+            read(&tampered),
+            Err(VerifierError::RelationCoefficientsMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_sharded_proof() {
+        let program_id = Pubkey::new_unique();
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let shard_len = 10 * MAX_CHUNK_SIZE;
+        let logical: Vec<u8> = (0..32 + PROOF_SIZE).map(|i| (i % 251) as u8).collect();
+
+        let mut manifest_data = vec![0u8; MANIFEST_ACCOUNT_SIZE];
+        let mut shard_data = [
+            vec![0u8; SHARD_HEADER_SIZE + shard_len],
+            vec![0u8; SHARD_HEADER_SIZE + shard_len],
+        ];
+        let mut state_data = vec![0u8; phased::VerificationState::SIZE];
+        let mut lamports = [1u64; 4];
+        let [l0, l1, l2, l3] = &mut lamports;
+        let [s0, s1] = &mut shard_data;
+        let manifest = AccountInfo::new(
+            &keys[0],
+            false,
+            true,
+            l0,
+            &mut manifest_data,
+            &program_id,
+            false,
+        );
+        let shard0 = AccountInfo::new(&keys[1], false, true, l1, s0, &program_id, false);
+        let shard1 = AccountInfo::new(&keys[2], false, true, l2, s1, &program_id, false);
+        let state = AccountInfo::new(
+            &keys[3],
+            false,
+            true,
+            l3,
+            &mut state_data,
+            &program_id,
+            false,
+        );
+        let custom = |e: VerifierError| Err(ProgramError::from(e));
+
+        let mut init = 1u16.to_le_bytes().to_vec();
+        init.extend_from_slice(&(shard_len as u32).to_le_bytes());
+        assert_eq!(
+            process_init_manifest(&program_id, &[manifest.clone(), shard0.clone()], &init),
+            custom(VerifierError::InvalidShard)
+        );
+        let manifest_and_shards = [manifest.clone(), shard0.clone(), shard1.clone()];
+        process_init_manifest(&program_id, &manifest_and_shards, &init).unwrap();
+
+        // Shards upload independently, in any order
+        let upload = |index: usize, offset: usize, bytes: &[u8]| {
+            let shard = [&shard0, &shard1][index].clone();
+            let mut data = (offset as u32).to_le_bytes().to_vec();
+            data.extend_from_slice(bytes);
+            process_upload_shard_chunk(&program_id, &[shard, manifest.clone()], &data)
+        };
+        let (first, second) = logical.split_at(shard_len);
+        for (offset, chunk) in second.chunks(MAX_CHUNK_SIZE).enumerate().rev() {
+            upload(1, offset * MAX_CHUNK_SIZE, chunk).unwrap();
+        }
+        upload(0, 0, &first[..MAX_CHUNK_SIZE]).unwrap();
+        {
+            let mut data = state.try_borrow_mut_data().unwrap();
+            let state = phased::VerificationState::from_bytes_mut(&mut data).unwrap();
+            assert_eq!(
+                lock_proof_buffer(&manifest, state, &manifest_and_shards),
+                custom(VerifierError::ProofBufferIncomplete)
+            );
+        }
+        upload(0, 0, first).unwrap();
+
+        // Phase 1 locks the manifest, and the phases read the shards as one buffer
+        let mut data = state.try_borrow_mut_data().unwrap();
+        let bound = phased::VerificationState::from_bytes_mut(&mut data).unwrap();
+        let missing_shard = [manifest.clone(), shard0.clone()];
+        assert!(lock_proof_buffer(&manifest, bound, &missing_shard).is_err());
+        lock_proof_buffer(&manifest, bound, &manifest_and_shards).unwrap();
+        let proof = borrow_bound_proof(bound, &manifest, &manifest_and_shards).unwrap();
+        assert_eq!(proof[0], BufferStatus::Locked as u8);
+        assert_eq!(proof[1..3], (PROOF_SIZE as u16).to_le_bytes());
+        assert_eq!(proof[3..5], 1u16.to_le_bytes());
+        assert_eq!(proof[BUFFER_HEADER_SIZE..], logical[..]);
+        drop((proof, data));
+
+        assert_eq!(upload(0, 0, &[1]), custom(VerifierError::ProofBufferLocked));
+        assert_eq!(
+            process_init_manifest(&program_id, &manifest_and_shards, &init),
+            custom(VerifierError::ProofBufferLocked)
+        );
+        assert_eq!(
+            process_upload_chunk(&program_id, core::slice::from_ref(&manifest), &[0, 0, 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // A bound shard cannot join another manifest
+        let other_key = Pubkey::new_unique();
+        let (mut other_data, mut other_lamports) = (vec![0u8; MANIFEST_ACCOUNT_SIZE], 1u64);
+        let other = AccountInfo::new(
+            &other_key,
+            false,
+            true,
+            &mut other_lamports,
+            &mut other_data,
+            &program_id,
+            false,
+        );
+        assert_eq!(
+            process_init_manifest(&program_id, &[other, shard0.clone(), shard1.clone()], &init),
+            custom(VerifierError::InvalidShard)
+        );
+
+        // Reset unlocks the manifest and empties its shards
+        process_reset_buffer(
+            &program_id,
+            &[manifest.clone(), state.clone(), shard0.clone(), shard1.clone()],
+        )
+        .unwrap();
+        assert_eq!(manifest.try_borrow_data().unwrap()[1], BufferStatus::Empty as u8);
+        assert_eq!(shard0.try_borrow_data().unwrap()[0], BufferStatus::Empty as u8);
+        upload(0, 0, &[1]).unwrap();
+    }
 }
@@ -14,6 +14,8 @@
 //! - **2b**: Rounds 10-19
 //! - **2c**: Rounds 20-27
 //! - **2d**: Relations + final check
+//! - **2d1/2d2**: the same relations split in two (arithmetic/permutation/range/elliptic,
+//!   then lookup/memory/poseidon + final check) for circuits where 2d runs close to the limit
 //!
 //! ## Main Phases
 //! 3. **ComputeMSM**: Shplemini MSM to get P0/P1
@@ -131,6 +133,8 @@ pub enum SumcheckSubPhase {
     AllRoundsDone = 3,
     /// Relations accumulated, verification complete
     RelationsDone = 4,
+    /// First half of the relations accumulated (`relations_partial` is set)
+    RelationsPart1Done = 5,
 }
 
 impl From<u8> for SumcheckSubPhase {
@@ -141,6 +145,7 @@ impl From<u8> for SumcheckSubPhase {
             2 => SumcheckSubPhase::Rounds10to19Done,
             3 => SumcheckSubPhase::AllRoundsDone,
             4 => SumcheckSubPhase::RelationsDone,
+            5 => SumcheckSubPhase::RelationsPart1Done,
             _ => SumcheckSubPhase::NotStarted,
         }
     }
@@ -148,8 +153,13 @@ impl From<u8> for SumcheckSubPhase {
 
 /// State account layout for phased verification
 ///
-/// Total size: ~6.4 KB
+/// Total size: ~6.5 KB
+///
+/// The layout has no implicit padding and every multi-byte integer is
+/// little-endian, so with the `borsh` feature the raw account data can be
+/// decoded directly with `VerificationState::try_from_slice`.
 #[repr(C)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
 pub struct VerificationState {
     /// Current phase (1 byte)
     pub phase: u8,
@@ -169,10 +179,15 @@ pub struct VerificationState {
     /// Number of public inputs (1 byte) - max 255
     pub num_public_inputs: u8,
 
-    /// Reserved (2 bytes)
-    pub _reserved: u16,
+    /// Set once CreateReceipt recorded this verification in a receipt
+    /// (1 byte)
+    pub receipt_recorded: u8,
+
+    /// Reserved (1 byte)
+    pub _reserved: u8,
 
-    /// VK account pubkey - stored in Phase 1, validated in Phase 3c
+    /// VK account pubkey - stored in Phase 1, validated by every later
+    /// instruction that takes the VK account and by CreateReceipt
     /// This prevents using different VKs across phases (security critical!)
     pub vk_account: [u8; 32],
 
@@ -259,12 +274,42 @@ pub struct VerificationState {
     // === Final result (Phase 4 output) ===
     pub verified: u8,
     pub _final_padding: [u8; 31],
+
+    // === Split relations (Phase 2d1 output) ===
+    /// Batched sum of the first relation half, consumed by Phase 2d2
+    pub relations_partial: [u8; 32],
+
+    // === Proof buffer (locked in Phase 1) ===
+    /// Proof buffer the challenges were generated from; later phases and
+    /// the receipt read only this buffer
+    pub proof_buffer: [u8; 32],
+
+    // === Challenge digest (Phase 1 output) ===
+    /// Keccak256 of every challenge, recorded when Phase 1 completes and
+    /// checked again before Phases 2-4 read the challenges back
+    pub challenge_digest: [u8; 32],
+
+    // === VK binding (Phase 1) ===
+    /// Keccak256 of the VK account data Phase 1 read, so a VK account
+    /// rewritten mid-verification is rejected like a different one
+    pub vk_hash: [u8; 32],
+
+    // === MSM scratch (Phase 3b2, optional) ===
+    /// Keccak256 of the MSM scalars Phase 3b2 wrote to a scratch account,
+    /// or zero when it wrote none; Phase 3c only trusts a matching scratch
+    pub msm_scalars_hash: [u8; 32],
+
+    // === Relation coefficients (Phase 2c, optional) ===
+    /// Commitment to the relation batching coefficients Phase 2c derived
+    /// from the alphas and pow factor, or zero when it has not run; the
+    /// relations phases only take coefficients that match it
+    pub relation_coefficients_hash: [u8; 32],
 }
 
 impl VerificationState {
     /// Size of the state account in bytes
     pub const SIZE: usize = 8 +           // header (phase, challenge_sub_phase, sumcheck_sub_phase, log_n, is_zk, num_pi, reserved)
-        32 +          // vk_account (stored in Phase 1, validated in Phase 3c)
+        32 +          // vk_account (stored in Phase 1, validated in later phases)
         32 +          // transcript_state
         192 +         // relation_params (eta, eta_two, eta_three, beta, gamma, public_input_delta)
         800 +         // alphas (25 × 32)
@@ -290,8 +335,14 @@ impl VerificationState {
         32 +          // shplemini_sub_phase + padding
         // Final outputs:
         128 +         // P0 + P1
-        32; // verified + padding
-            // Total: 6408 bytes
+        32 +          // verified + padding
+        32 +          // relations_partial (Phase 2d1)
+        32 +          // proof_buffer (Phase 1)
+        32 +          // challenge_digest (Phase 1)
+        32 +          // vk_hash (Phase 1)
+        32 +          // msm_scalars_hash (Phase 3b2)
+        32; // relation_coefficients_hash (Phase 2c)
+            // Total: 6600 bytes
 
     /// Initialize state from account data
     pub fn from_bytes(data: &[u8]) -> Option<&Self> {
@@ -350,10 +401,44 @@ impl VerificationState {
     pub fn set_shplemini_sub_phase(&mut self, sub_phase: ShpleminiSubPhase) {
         self.shplemini_sub_phase = sub_phase as u8;
     }
+
+    /// Keccak256 over every challenge field, in layout order
+    pub fn compute_challenge_digest(&self) -> [u8; 32] {
+        plonk_solana_core::transcript::keccak256v(&[
+            &self.eta,
+            &self.eta_two,
+            &self.eta_three,
+            &self.beta,
+            &self.gamma,
+            &self.public_input_delta,
+            self.alphas.as_flattened(),
+            self.gate_challenges.as_flattened(),
+            self.sumcheck_challenges.as_flattened(),
+            &self.libra_challenge,
+            &self.rho,
+            &self.gemini_r,
+            &self.shplonk_nu,
+            &self.shplonk_z,
+        ])
+    }
+
+    /// Record the challenge digest; called once Phase 1 has written every
+    /// challenge
+    pub fn seal_challenges(&mut self) {
+        self.challenge_digest = self.compute_challenge_digest();
+    }
+
+    /// Whether the challenges still hash to the digest Phase 1 recorded
+    ///
+    /// A mismatch means the account was overwritten between transactions;
+    /// continuing would only surface later as a failed pairing.
+    pub fn challenges_intact(&self) -> bool {
+        self.compute_challenge_digest() == self.challenge_digest
+    }
 }
 
 // Verify the size at compile time
-const _: () = assert!(VerificationState::SIZE == 6408);
+const _: () = assert!(VerificationState::SIZE == 6600);
 
 /// Account indices for phased verification instructions
 pub mod accounts {
@@ -370,30 +455,58 @@ pub mod accounts {
 // ============================================================================
 
 /// PDA seed for verification receipts
-pub const RECEIPT_SEED: &[u8] = b"receipt";
+pub use solana_noir_receipt_seeds::RECEIPT_SEED;
 
 /// Verification Receipt - persistent record that a proof was verified
 ///
 /// PDA derivation: `["receipt", vk_account, keccak(public_inputs)]`
 ///
-/// The VK account and PI hash are encoded in the PDA address itself,
-/// so we only store timing information in the account data.
+/// The seeds are repeated in the account data together with the bump, so a
+/// reader can re-check the address with a single `create_program_address`
+/// instead of searching for the bump.
 ///
 /// To check if a proof was verified:
 /// 1. Compute the expected PDA from (vk_account, pi_hash)
 /// 2. Check if the account exists at that address
 /// 3. Read the timing data if needed
+///
+/// To accept any VK account holding a given circuit, re-derive the PDA
+/// from the stored `vk_account` instead and compare `vk_hash`.
 #[repr(C)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
 pub struct VerificationReceipt {
     /// Slot when verification completed
     pub verified_slot: u64,
     /// Unix timestamp when verification completed
     pub verified_timestamp: i64,
+    /// VK account the proof was verified against
+    pub vk_account: [u8; 32],
+    /// Keccak256 of the public inputs
+    pub pi_hash: [u8; 32],
+    /// Receipt PDA bump
+    pub bump: u8,
+    /// Padding to 4-byte alignment
+    pub _reserved: [u8; 3],
+    /// Verifications recorded under the `Count` duplicate policy, starting
+    /// at 1 (0 for receipts created before counting existed)
+    pub verification_count: u32,
+    /// Account that paid the receipt's rent; only it may close the receipt
+    pub rent_payer: [u8; 32],
+    /// Application-defined tag the creator attached (zero if none)
+    pub tag: [u8; 32],
+    /// Keccak256 of the verified proof bytes, checked by `ReverifyByDigest`
+    pub proof_digest: [u8; 32],
+    /// Canonical hash of the VK and its SRS point
+    /// (`VerificationKey::hash_with_srs`), the same for every account the
+    /// circuit's VK is uploaded to with that SRS
+    pub vk_hash: [u8; 32],
+    /// Account the rent is refunded to when the receipt is closed
+    pub rent_beneficiary: [u8; 32],
 }
 
 impl VerificationReceipt {
-    /// Size of the receipt account in bytes (16 bytes)
-    pub const SIZE: usize = 8 + 8; // slot + timestamp
+    /// Size of the receipt account in bytes (248 bytes)
+    pub const SIZE: usize = 8 + 8 + 32 + 32 + 1 + 3 + 4 + 32 + 32 + 32 + 32 + 32;
 
     /// Initialize from account data
     pub fn from_bytes(data: &[u8]) -> Option<&Self> {
@@ -413,4 +526,44 @@ impl VerificationReceipt {
 }
 
 // Verify the size at compile time
-const _: () = assert!(VerificationReceipt::SIZE == 16);
+const _: () = assert!(VerificationReceipt::SIZE == 248);
+const _: () = assert!(core::mem::size_of::<VerificationReceipt>() == VerificationReceipt::SIZE);
+
+#[cfg(all(test, feature = "borsh"))]
+mod tests {
+    use super::*;
+    use borsh::BorshDeserialize;
+
+    #[test]
+    fn test_borsh_matches_account_layout() {
+        let mut data = vec![0u8; VerificationState::SIZE];
+        data[0] = Phase::SumcheckInProgress as u8;
+        data[3] = 6;
+        data[6] = 1; // receipt_recorded
+        data[7] = 0x12;
+        data[8 + 31] = 0xaa; // last byte of vk_account
+        data[VerificationState::SIZE - 224] = 1; // verified
+        data[VerificationState::SIZE - 161] = 0xbb; // last byte of relations_partial
+        data[VerificationState::SIZE - 129] = 0xcc; // last byte of proof_buffer
+        data[VerificationState::SIZE - 97] = 0xdd; // last byte of challenge_digest
+        data[VerificationState::SIZE - 65] = 0xee; // last byte of vk_hash
+        data[VerificationState::SIZE - 33] = 0xff; // last byte of msm_scalars_hash
+        data[VerificationState::SIZE - 1] = 0x11; // last byte of relation_coefficients_hash
+
+        let state = VerificationState::from_bytes(&data).unwrap();
+        let decoded = VerificationState::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.get_phase(), state.get_phase());
+        assert_eq!(decoded.log_n, 6);
+        assert_eq!(decoded.receipt_recorded, 1);
+        assert_eq!(decoded._reserved, 0x12);
+        assert_eq!(decoded.vk_account, state.vk_account);
+        assert_eq!(decoded.verified, 1);
+        assert_eq!(decoded.relations_partial[31], 0xbb);
+        assert_eq!(decoded.proof_buffer[31], 0xcc);
+        assert_eq!(decoded.challenge_digest[31], 0xdd);
+        assert_eq!(decoded.vk_hash[31], 0xee);
+        assert_eq!(decoded.msm_scalars_hash[31], 0xff);
+        assert_eq!(decoded.relation_coefficients_hash[31], 0x11);
+        assert_eq!(borsh::to_vec(&decoded).unwrap(), data);
+    }
+}
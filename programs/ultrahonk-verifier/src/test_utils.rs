@@ -0,0 +1,191 @@
+//! In-process test harness for programs that consume receipts
+//!
+//! Enabled by the `test-utils` feature. Integrators testing receipt-gated
+//! instructions can start a `ProgramTest` with the verifier loaded natively
+//! and seed it with VK and receipt accounts, without a validator or a full
+//! verification run:
+//!
+//! ```ignore
+//! let mut program_test = ultrahonk_verifier::test_utils::program_test();
+//! program_test.add_program("my_app", my_app::id(), processor!(my_app::process));
+//! let vk_account = add_vk_account(&mut program_test, &fixture.vk);
+//! let receipt = mint_receipt_for(&mut program_test, &vk_account, &fixture.public_inputs);
+//! let (banks_client, payer, blockhash) = program_test.start().await;
+//! ```
+
+// solana-program-test 3.x flags its API as unstable through deprecation
+#![allow(deprecated)]
+
+use crate::phased::{Phase, VerificationReceipt, VerificationState};
+use crate::{
+    BufferStatus, VkBufferStatus, BUFFER_HEADER_SIZE, VK_ACCOUNT_SIZE_WITH_SRS, VK_HEADER_SIZE,
+    VK_SIZE, VK_SRS_G2_OFFSET,
+};
+use plonk_solana_core::G2;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{account::Account, rent::Rent};
+use std::io;
+use std::path::PathBuf;
+
+/// Proof, public inputs and VK of a circuit in `test-circuits`
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+    pub vk: Vec<u8>,
+}
+
+/// A `ProgramTest` with the verifier loaded under [`crate::id`]
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new(
+        "ultrahonk_verifier",
+        crate::id(),
+        processor!(crate::process_instruction),
+    )
+}
+
+/// Add a VK account holding `vk`, as if uploaded, and return its address
+///
+/// The VK is stored uncompiled; phases accept both layouts.
+pub fn add_vk_account(program_test: &mut ProgramTest, vk: &[u8]) -> Pubkey {
+    let vk_account = Pubkey::new_unique();
+    program_test.add_account(vk_account, program_account(vk_account_data(vk, None)));
+    vk_account
+}
+
+/// Add a VK account holding `vk` and the custom x·G2 SRS point `srs_g2`,
+/// and return its address
+pub fn add_vk_account_with_srs(program_test: &mut ProgramTest, vk: &[u8], srs_g2: &G2) -> Pubkey {
+    let vk_account = Pubkey::new_unique();
+    let data = vk_account_data(vk, Some(srs_g2));
+    program_test.add_account(vk_account, program_account(data));
+    vk_account
+}
+
+/// Add a locked proof buffer and the state a successful verification of it
+/// against `vk_account` leaves behind, and return `(state, proof buffer)`
+///
+/// `vk` must be the bytes [`add_vk_account`] stored at `vk_account`; the
+/// state records their hash as Phase 1 would. No phase runs, so `proof`
+/// can be any bytes: this is for exercising `CreateReceipt` and the checks
+/// around it, not the verifier.
+pub fn add_verified_state(
+    program_test: &mut ProgramTest,
+    vk_account: &Pubkey,
+    vk: &[u8],
+    public_inputs: &[u8],
+    proof: &[u8],
+) -> (Pubkey, Pubkey) {
+    let vk_data = vk_account_data(vk, None);
+    add_state(program_test, vk_account, &vk_data, public_inputs, proof)
+}
+
+/// [`add_verified_state`] for a VK account added with
+/// [`add_vk_account_with_srs`]
+pub fn add_verified_state_with_srs(
+    program_test: &mut ProgramTest,
+    vk_account: &Pubkey,
+    vk: &[u8],
+    srs_g2: &G2,
+    public_inputs: &[u8],
+    proof: &[u8],
+) -> (Pubkey, Pubkey) {
+    let vk_data = vk_account_data(vk, Some(srs_g2));
+    add_state(program_test, vk_account, &vk_data, public_inputs, proof)
+}
+
+fn add_state(
+    program_test: &mut ProgramTest,
+    vk_account: &Pubkey,
+    vk_data: &[u8],
+    public_inputs: &[u8],
+    proof: &[u8],
+) -> (Pubkey, Pubkey) {
+    let mut buffer = vec![0u8; BUFFER_HEADER_SIZE];
+    buffer[0] = BufferStatus::Locked as u8;
+    buffer[1..3].copy_from_slice(&(proof.len() as u16).to_le_bytes());
+    buffer[3..5].copy_from_slice(&((public_inputs.len() / 32) as u16).to_le_bytes());
+    buffer.extend_from_slice(public_inputs);
+    buffer.extend_from_slice(proof);
+    let proof_account = Pubkey::new_unique();
+    program_test.add_account(proof_account, program_account(buffer));
+
+    let mut data = vec![0u8; VerificationState::SIZE];
+    let state = VerificationState::from_bytes_mut(&mut data).unwrap();
+    state.set_phase(Phase::Complete);
+    state.verified = 1;
+    state.num_public_inputs = (public_inputs.len() / 32) as u8;
+    state.vk_account = vk_account.to_bytes();
+    state.vk_hash = plonk_solana_core::transcript::keccak256(vk_data);
+    state.proof_buffer = proof_account.to_bytes();
+    let state_account = Pubkey::new_unique();
+    program_test.add_account(state_account, program_account(data));
+
+    (state_account, proof_account)
+}
+
+/// Add the receipt a successful verification of `public_inputs` against
+/// `vk_account` would have created, and return its PDA
+///
+/// The receipt records slot and timestamp 0 and the default pubkey as its
+/// rent payer and beneficiary, so tests cannot close it.
+pub fn mint_receipt_for(
+    program_test: &mut ProgramTest,
+    vk_account: &Pubkey,
+    public_inputs: &[u8],
+) -> Pubkey {
+    let pi_hash = solana_noir_receipt_seeds::pi_hash(public_inputs);
+    let (receipt_pda, bump) =
+        solana_noir_receipt_seeds::derive_receipt_pda(vk_account, &pi_hash, &crate::id());
+
+    // Written field by field: a Vec<u8> need not be aligned for the struct
+    let mut data = vec![0u8; VerificationReceipt::SIZE];
+    data[16..48].copy_from_slice(vk_account.as_ref());
+    data[48..80].copy_from_slice(&pi_hash);
+    data[80] = bump;
+    data[84..88].copy_from_slice(&1u32.to_le_bytes()); // verification_count
+
+    program_test.add_account(receipt_pda, program_account(data));
+    receipt_pda
+}
+
+/// Read the keccak artifacts of `test-circuits/<circuit>`
+///
+/// Run `test-circuits/build_all.sh` first; the artifacts are not checked in.
+pub fn fixture(circuit: &str) -> io::Result<Fixture> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test-circuits")
+        .join(circuit)
+        .join("target/keccak");
+    Ok(Fixture {
+        proof: std::fs::read(dir.join("proof"))?,
+        public_inputs: std::fs::read(dir.join("public_inputs"))?,
+        vk: std::fs::read(dir.join("vk"))?,
+    })
+}
+
+/// VK account data holding `vk` uncompiled, followed by `srs_g2` if given
+fn vk_account_data(vk: &[u8], srs_g2: Option<&G2>) -> Vec<u8> {
+    assert_eq!(vk.len(), VK_SIZE, "VK must be {} bytes", VK_SIZE);
+    let mut data = vec![0u8; VK_HEADER_SIZE + VK_SIZE];
+    data[0] = VkBufferStatus::Ready as u8;
+    data[1..3].copy_from_slice(&(VK_SIZE as u16).to_le_bytes());
+    data[VK_HEADER_SIZE..].copy_from_slice(vk);
+    if let Some(srs_g2) = srs_g2 {
+        data.resize(VK_ACCOUNT_SIZE_WITH_SRS, 0);
+        data[VK_SRS_G2_OFFSET..].copy_from_slice(srs_g2);
+    }
+    data
+}
+
+/// A rent-exempt account owned by the verifier
+fn program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: crate::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
@@ -0,0 +1,30 @@
+//! The `test-utils` harness seeds accounts the program accepts
+#![cfg(feature = "test-utils")]
+#![allow(deprecated)]
+
+use solana_sdk::pubkey::Pubkey;
+use ultrahonk_verifier::phased::VerificationReceipt;
+use ultrahonk_verifier::test_utils::{mint_receipt_for, program_test};
+
+#[tokio::test]
+async fn test_mint_receipt_for() {
+    let mut program_test = program_test();
+    let vk_account = Pubkey::new_unique();
+    let public_inputs = [9u8; 64];
+    let receipt_pda = mint_receipt_for(&mut program_test, &vk_account, &public_inputs);
+
+    let (banks_client, _payer, _blockhash) = program_test.start().await;
+    let account = banks_client
+        .get_account(receipt_pda)
+        .await
+        .unwrap()
+        .expect("receipt exists");
+    assert_eq!(account.owner, ultrahonk_verifier::id());
+
+    let receipt = VerificationReceipt::from_bytes(&account.data).unwrap();
+    assert_eq!(receipt.vk_account, vk_account.to_bytes());
+    assert_eq!(
+        receipt.pi_hash,
+        plonk_solana_core::receipt::public_inputs_hash(&public_inputs)
+    );
+}
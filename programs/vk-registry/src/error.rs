@@ -0,0 +1,80 @@
+//! Program error codes
+//!
+//! Failures are surfaced as `ProgramError::Custom(code)`, grouped by the
+//! hundred as in the verifier program. Codes are part of the program's
+//! public interface: never renumber or reuse one.
+//!
+//! | Range | Group |
+//! |-------|-------|
+//! | 100s  | Account checks |
+//! | 300s  | Input parsing |
+//! | 400s  | Entry lifecycle |
+
+use solana_program::program_error::ProgramError;
+
+/// Errors returned by the registry program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    // === Accounts ===
+    /// Signer is not the authority of the name
+    NotAuthority,
+    /// Name record has the wrong owner, size or address
+    InvalidNameRecord,
+    /// Entry account has the wrong owner, size or address
+    InvalidEntry,
+    /// name@version is already registered
+    EntryExists,
+    /// Entry belongs to a different name than the name record
+    EntryNameMismatch,
+
+    // === Inputs ===
+    /// Name is empty, too long or has characters outside `[A-Za-z0-9._+-]`
+    InvalidName,
+    /// Version is empty, too long or has characters outside `[A-Za-z0-9._+-]`
+    InvalidVersion,
+
+    // === Lifecycle ===
+    /// Entry was already deprecated
+    AlreadyDeprecated,
+}
+
+impl RegistryError {
+    /// Stable numeric code
+    pub fn code(self) -> u32 {
+        match self {
+            Self::NotAuthority => 100,
+            Self::InvalidNameRecord => 101,
+            Self::InvalidEntry => 102,
+            Self::EntryExists => 103,
+            Self::EntryNameMismatch => 104,
+
+            Self::InvalidName => 300,
+            Self::InvalidVersion => 301,
+
+            Self::AlreadyDeprecated => 400,
+        }
+    }
+
+    /// Inverse of [`RegistryError::code`]
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            100 => Self::NotAuthority,
+            101 => Self::InvalidNameRecord,
+            102 => Self::InvalidEntry,
+            103 => Self::EntryExists,
+            104 => Self::EntryNameMismatch,
+
+            300 => Self::InvalidName,
+            301 => Self::InvalidVersion,
+
+            400 => Self::AlreadyDeprecated,
+            _ => return None,
+        })
+    }
+}
+
+impl From<RegistryError> for ProgramError {
+    fn from(e: RegistryError) -> Self {
+        ProgramError::Custom(e.code())
+    }
+}
@@ -0,0 +1,389 @@
+//! VK Registry for Solana
+//!
+//! Maps `name@version` to an UltraHonk verifier VK account and the hash of
+//! its VK bytes, so clients and integrator programs can refer to circuits
+//! symbolically and upgrade them with a visible history. The registry only
+//! stores references: VKs are still uploaded to the verifier program.
+//!
+//! ## Instructions
+//!
+//! 0. Register - Record a new version (creates the name on first use)
+//! 1. Deprecate - Mark a version as no longer to be used
+//! 2. TransferAuthority - Hand a name to a new authority
+//!
+//! Account layouts are described in [`state`].
+
+pub mod error;
+pub mod state;
+
+pub use error::RegistryError;
+
+use core::cell::RefMut;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction as SolanaInstruction},
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use state::{is_valid_label, pad_label, NameRecord, RegistryEntry, ENTRY_SEED, NAME_SEED};
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// ============================================================================
+// Instructions
+// ============================================================================
+
+#[repr(u8)]
+pub enum Instruction {
+    /// Register `name@version`
+    ///
+    /// The first registration of a name creates its name record with the
+    /// signer as authority; later versions must be signed by that
+    /// authority, who also pays the rent.
+    /// Accounts: [name_record (writable), entry (writable),
+    ///            authority (signer, writable), system_program]
+    /// Data: [instruction(1), name_len(1), name, version_len(1), version,
+    ///        vk_account(32), vk_hash(32)]
+    Register = 0,
+
+    /// Deprecate a registered version
+    /// Accounts: [name_record, entry (writable), authority (signer)]
+    /// Data: [instruction(1)]
+    Deprecate = 1,
+
+    /// Transfer a name to a new authority
+    /// Accounts: [name_record (writable), authority (signer)]
+    /// Data: [instruction(1), new_authority(32)]
+    TransferAuthority = 2,
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (&tag, data) = instruction_data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    match tag {
+        0 => process_register(program_id, accounts, data),
+        1 => process_deprecate(program_id, accounts),
+        2 => process_transfer_authority(program_id, accounts, data),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+fn process_register(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Register");
+
+    let account_iter = &mut accounts.iter();
+    let name_record = next_account_info(account_iter)?;
+    let entry = next_account_info(account_iter)?;
+    let authority = next_account_info(account_iter)?;
+    let system_program = next_account_info(account_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut reader = Reader(data);
+    let name = reader.label().ok_or(RegistryError::InvalidName)?;
+    let version = reader.label().ok_or(RegistryError::InvalidVersion)?;
+    let vk_account: [u8; 32] = reader.array().ok_or(ProgramError::InvalidInstructionData)?;
+    let vk_hash: [u8; 32] = reader.array().ok_or(ProgramError::InvalidInstructionData)?;
+    if !is_valid_label(name) {
+        return Err(RegistryError::InvalidName.into());
+    }
+    if !is_valid_label(version) {
+        return Err(RegistryError::InvalidVersion.into());
+    }
+
+    // Name record: check the authority, or claim the name
+    let (name_pda, name_bump) = Pubkey::find_program_address(&[NAME_SEED, name], program_id);
+    if name_pda != *name_record.key {
+        msg!("Invalid name record PDA");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if name_record.owner == program_id {
+        let record_data = name_record.try_borrow_data()?;
+        let record =
+            NameRecord::from_bytes(&record_data).ok_or(RegistryError::InvalidNameRecord)?;
+        if record.authority != authority.key.to_bytes() {
+            msg!("Signer is not the authority of this name");
+            return Err(RegistryError::NotAuthority.into());
+        }
+    } else {
+        create_pda(
+            program_id,
+            authority,
+            name_record,
+            system_program,
+            NameRecord::SIZE,
+            &[NAME_SEED, name, &[name_bump]],
+        )?;
+        let mut record_data = name_record.try_borrow_mut_data()?;
+        let record =
+            NameRecord::from_bytes_mut(&mut record_data).ok_or(RegistryError::InvalidNameRecord)?;
+        record.authority = authority.key.to_bytes();
+        record.name = pad_label(name);
+        record.name_len = name.len() as u8;
+        record.bump = name_bump;
+        msg!("Name claimed by {}", authority.key);
+    }
+
+    // Entry: must not exist yet
+    let (entry_pda, entry_bump) =
+        Pubkey::find_program_address(&[ENTRY_SEED, name, version], program_id);
+    if entry_pda != *entry.key {
+        msg!("Invalid entry PDA");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if entry.owner == program_id {
+        return Err(RegistryError::EntryExists.into());
+    }
+    create_pda(
+        program_id,
+        authority,
+        entry,
+        system_program,
+        RegistryEntry::SIZE,
+        &[ENTRY_SEED, name, version, &[entry_bump]],
+    )?;
+
+    let clock = Clock::get()?;
+    let mut entry_data = entry.try_borrow_mut_data()?;
+    let registered =
+        RegistryEntry::from_bytes_mut(&mut entry_data).ok_or(RegistryError::InvalidEntry)?;
+    registered.vk_account = vk_account;
+    registered.vk_hash = vk_hash;
+    registered.registered_by = authority.key.to_bytes();
+    registered.registered_slot = clock.slot.to_le_bytes();
+    registered.registered_timestamp = clock.unix_timestamp.to_le_bytes();
+    registered.name = pad_label(name);
+    registered.name_len = name.len() as u8;
+    registered.version = pad_label(version);
+    registered.version_len = version.len() as u8;
+    registered.bump = entry_bump;
+
+    msg!("Registered at slot {}", clock.slot);
+    Ok(())
+}
+
+fn process_deprecate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Deprecate");
+
+    let account_iter = &mut accounts.iter();
+    let name_record = next_account_info(account_iter)?;
+    let entry = next_account_info(account_iter)?;
+    let authority = next_account_info(account_iter)?;
+
+    let record_data = load_name_record(program_id, name_record, authority)?;
+    let record = NameRecord::from_bytes(&record_data).ok_or(RegistryError::InvalidNameRecord)?;
+
+    if entry.owner != program_id {
+        return Err(RegistryError::InvalidEntry.into());
+    }
+    let mut entry_data = entry.try_borrow_mut_data()?;
+    let deprecated =
+        RegistryEntry::from_bytes_mut(&mut entry_data).ok_or(RegistryError::InvalidEntry)?;
+    if deprecated.name() != record.name() {
+        return Err(RegistryError::EntryNameMismatch.into());
+    }
+    if deprecated.is_deprecated() {
+        return Err(RegistryError::AlreadyDeprecated.into());
+    }
+
+    // Slot 0 means active, so a deprecation at genesis is recorded as slot 1
+    let slot = Clock::get()?.slot.max(1);
+    deprecated.deprecated_slot = slot.to_le_bytes();
+
+    msg!("Deprecated at slot {}", slot);
+    Ok(())
+}
+
+fn process_transfer_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    msg!("TransferAuthority");
+
+    let account_iter = &mut accounts.iter();
+    let name_record = next_account_info(account_iter)?;
+    let authority = next_account_info(account_iter)?;
+
+    let new_authority: [u8; 32] = Reader(data)
+        .array()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let mut record_data = load_name_record(program_id, name_record, authority)?;
+    let record =
+        NameRecord::from_bytes_mut(&mut record_data).ok_or(RegistryError::InvalidNameRecord)?;
+    record.authority = new_authority;
+
+    msg!(
+        "Authority transferred to {}",
+        Pubkey::new_from_array(new_authority)
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Borrow a name record after checking its owner, address and that
+/// `authority` signed as its authority
+fn load_name_record<'a, 'info>(
+    program_id: &Pubkey,
+    name_record: &'a AccountInfo<'info>,
+    authority: &AccountInfo,
+) -> Result<RefMut<'a, &'info mut [u8]>, ProgramError> {
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if name_record.owner != program_id {
+        return Err(RegistryError::InvalidNameRecord.into());
+    }
+    let data = name_record.try_borrow_mut_data()?;
+    let record = NameRecord::from_bytes(&data).ok_or(RegistryError::InvalidNameRecord)?;
+    let expected =
+        Pubkey::create_program_address(&[NAME_SEED, record.name(), &[record.bump]], program_id)
+            .map_err(|_| RegistryError::InvalidNameRecord)?;
+    if expected != *name_record.key {
+        return Err(RegistryError::InvalidNameRecord.into());
+    }
+    if record.authority != authority.key.to_bytes() {
+        msg!("Signer is not the authority of this name");
+        return Err(RegistryError::NotAuthority.into());
+    }
+    Ok(data)
+}
+
+/// Create a rent-exempt PDA owned by this program, paid by `payer`
+fn create_pda<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports = Rent::get()?.minimum_balance(space);
+
+    // SystemInstruction::CreateAccount:
+    // [instruction_type(4 LE), lamports(8 LE), space(8 LE), owner(32)]
+    let mut data = Vec::with_capacity(4 + 8 + 8 + 32);
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&lamports.to_le_bytes());
+    data.extend_from_slice(&(space as u64).to_le_bytes());
+    data.extend_from_slice(program_id.as_ref());
+
+    let create_account_ix = SolanaInstruction {
+        // System program ID (11111111111111111111111111111111)
+        program_id: Pubkey::new_from_array([0u8; 32]),
+        accounts: vec![
+            AccountMeta::new(*payer.key, true),
+            AccountMeta::new(*account.key, true),
+        ],
+        data,
+    };
+    invoke_signed(
+        &create_account_ix,
+        &[payer.clone(), account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Cursor over instruction data
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Length-prefixed name or version
+    fn label(&mut self) -> Option<&'a [u8]> {
+        let (&len, rest) = self.0.split_first()?;
+        let len = len as usize;
+        if rest.len() < len {
+            return None;
+        }
+        let (label, rest) = rest.split_at(len);
+        self.0 = rest;
+        Some(label)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.0.len() < N {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        bytes.try_into().ok()
+    }
+}
+
+// ============================================================================
+// Program ID
+// ============================================================================
+
+solana_program::declare_id!("6evZeF2PvTJMnCkfyYwoK1vmzNieTGcX5PfNqGz7r7Nc");
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_labels() {
+        assert!(is_valid_label(b"square"));
+        assert!(is_valid_label(b"1.2.0-rc.1+build"));
+        assert!(is_valid_label(&[b'a'; 32]));
+        assert!(!is_valid_label(b""));
+        assert!(!is_valid_label(&[b'a'; 33]));
+        assert!(!is_valid_label(b"sq uare"));
+        assert!(!is_valid_label(b"square@1"));
+    }
+
+    #[test]
+    fn test_reader() {
+        let data = [3, b'a', b'b', b'c', 1, b'1', 7, 7];
+        let mut reader = Reader(&data);
+        assert_eq!(reader.label(), Some(&b"abc"[..]));
+        assert_eq!(reader.label(), Some(&b"1"[..]));
+        assert_eq!(reader.array::<2>(), Some([7, 7]));
+        assert_eq!(reader.array::<1>(), None);
+        assert_eq!(Reader(&[5, b'a']).label(), None);
+    }
+
+    #[test]
+    fn test_error_codes_roundtrip() {
+        let errors = [
+            RegistryError::NotAuthority,
+            RegistryError::InvalidNameRecord,
+            RegistryError::InvalidEntry,
+            RegistryError::EntryExists,
+            RegistryError::EntryNameMismatch,
+            RegistryError::InvalidName,
+            RegistryError::InvalidVersion,
+            RegistryError::AlreadyDeprecated,
+        ];
+        for error in errors {
+            assert_eq!(RegistryError::from_code(error.code()), Some(error));
+        }
+        assert_eq!(RegistryError::from_code(0), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Registry account layouts
+//!
+//! Two kinds of PDA, both owned by the registry program:
+//!
+//! - [`NameRecord`] at `["name", name]`: the authority allowed to register
+//!   versions of `name`. Created by the first `Register` for that name.
+//! - [`RegistryEntry`] at `["entry", name, version]`: the VK account and
+//!   VK hash of one circuit version. Entries are never rewritten; a changed
+//!   circuit gets a new version and the old one can be deprecated.
+//!
+//! Multi-byte integers are stored as little-endian byte arrays so the
+//! structs have alignment 1 and can be cast directly from account data.
+
+/// Seed prefix of name records
+pub const NAME_SEED: &[u8] = b"name";
+
+/// Seed prefix of registry entries
+pub const ENTRY_SEED: &[u8] = b"entry";
+
+/// Longest name or version; each is used whole as a PDA seed
+pub const MAX_LABEL_LEN: usize = 32;
+
+/// Whether `label` can be a name or version
+///
+/// Same character set as `name@version` keys in the SDK's file registry.
+pub fn is_valid_label(label: &[u8]) -> bool {
+    !label.is_empty()
+        && label.len() <= MAX_LABEL_LEN
+        && label
+            .iter()
+            .all(|&c| c.is_ascii_alphanumeric() || b"-_.+".contains(&c))
+}
+
+/// Owner of a name
+#[repr(C)]
+pub struct NameRecord {
+    /// May register and deprecate versions, and transfer the name
+    pub authority: [u8; 32],
+    /// Name, zero-padded to `MAX_LABEL_LEN`
+    pub name: [u8; MAX_LABEL_LEN],
+    pub name_len: u8,
+    /// Name record PDA bump
+    pub bump: u8,
+    pub _reserved: [u8; 6],
+}
+
+impl NameRecord {
+    /// Size of a name record account in bytes (72 bytes)
+    pub const SIZE: usize = 32 + MAX_LABEL_LEN + 1 + 1 + 6;
+
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len() != Self::SIZE {
+            return None;
+        }
+        // SAFETY: length checked above and the struct has alignment 1
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    pub fn from_bytes_mut(data: &mut [u8]) -> Option<&mut Self> {
+        if data.len() != Self::SIZE {
+            return None;
+        }
+        // SAFETY: length checked above and the struct has alignment 1
+        Some(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name[..(self.name_len as usize).min(MAX_LABEL_LEN)]
+    }
+}
+
+/// One registered circuit version
+#[repr(C)]
+pub struct RegistryEntry {
+    /// Verifier VK account of this version
+    pub vk_account: [u8; 32],
+    /// Keccak256 of the VK bytes, as given by the authority
+    pub vk_hash: [u8; 32],
+    /// Authority that registered the entry
+    pub registered_by: [u8; 32],
+    /// Slot of registration (u64 LE)
+    pub registered_slot: [u8; 8],
+    /// Unix timestamp of registration (i64 LE)
+    pub registered_timestamp: [u8; 8],
+    /// Slot the entry was deprecated at, 0 while active (u64 LE)
+    pub deprecated_slot: [u8; 8],
+    /// Name, zero-padded to `MAX_LABEL_LEN`
+    pub name: [u8; MAX_LABEL_LEN],
+    /// Version, zero-padded to `MAX_LABEL_LEN`
+    pub version: [u8; MAX_LABEL_LEN],
+    pub name_len: u8,
+    pub version_len: u8,
+    /// Entry PDA bump
+    pub bump: u8,
+    pub _reserved: [u8; 5],
+}
+
+impl RegistryEntry {
+    /// Size of an entry account in bytes (192 bytes)
+    pub const SIZE: usize = 32 * 3 + 8 * 3 + MAX_LABEL_LEN * 2 + 3 + 5;
+
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len() != Self::SIZE {
+            return None;
+        }
+        // SAFETY: length checked above and the struct has alignment 1
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    pub fn from_bytes_mut(data: &mut [u8]) -> Option<&mut Self> {
+        if data.len() != Self::SIZE {
+            return None;
+        }
+        // SAFETY: length checked above and the struct has alignment 1
+        Some(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name[..(self.name_len as usize).min(MAX_LABEL_LEN)]
+    }
+
+    pub fn version(&self) -> &[u8] {
+        &self.version[..(self.version_len as usize).min(MAX_LABEL_LEN)]
+    }
+
+    pub fn deprecated_slot(&self) -> u64 {
+        u64::from_le_bytes(self.deprecated_slot)
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated_slot() != 0
+    }
+}
+
+// Verify the sizes at compile time
+const _: () = assert!(NameRecord::SIZE == 72);
+const _: () = assert!(core::mem::size_of::<NameRecord>() == NameRecord::SIZE);
+const _: () = assert!(RegistryEntry::SIZE == 192);
+const _: () = assert!(core::mem::size_of::<RegistryEntry>() == RegistryEntry::SIZE);
+
+/// Copy `label` into a zero-padded seed-sized field
+pub fn pad_label(label: &[u8]) -> [u8; MAX_LABEL_LEN] {
+    let mut padded = [0u8; MAX_LABEL_LEN];
+    padded[..label.len()].copy_from_slice(label);
+    padded
+}
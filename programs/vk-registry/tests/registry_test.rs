@@ -0,0 +1,268 @@
+//! Integration tests for the VK registry program
+//!
+//! Uses solana-program-test to run the registry in-process
+
+#![allow(deprecated)]
+
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use vk_registry::{
+    state::{NameRecord, RegistryEntry, ENTRY_SEED, NAME_SEED},
+    RegistryError,
+};
+
+// System program ID (11111111111111111111111111111111)
+const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0u8; 32]);
+
+fn program_test() -> ProgramTest {
+    ProgramTest::new(
+        "vk_registry",
+        vk_registry::id(),
+        processor!(vk_registry::process_instruction),
+    )
+}
+
+fn name_record(name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[NAME_SEED, name.as_bytes()], &vk_registry::id()).0
+}
+
+fn entry(name: &str, version: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ENTRY_SEED, name.as_bytes(), version.as_bytes()],
+        &vk_registry::id(),
+    )
+    .0
+}
+
+fn register_ix(
+    authority: &Pubkey,
+    name: &str,
+    version: &str,
+    vk_account: &Pubkey,
+    vk_hash: [u8; 32],
+) -> Instruction {
+    let mut data = vec![0u8, name.len() as u8];
+    data.extend_from_slice(name.as_bytes());
+    data.push(version.len() as u8);
+    data.extend_from_slice(version.as_bytes());
+    data.extend_from_slice(vk_account.as_ref());
+    data.extend_from_slice(&vk_hash);
+    Instruction::new_with_bytes(
+        vk_registry::id(),
+        &data,
+        vec![
+            AccountMeta::new(name_record(name), false),
+            AccountMeta::new(entry(name, version), false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    )
+}
+
+fn deprecate_ix(authority: &Pubkey, name: &str, version: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        vk_registry::id(),
+        &[1],
+        vec![
+            AccountMeta::new_readonly(name_record(name), false),
+            AccountMeta::new(entry(name, version), false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+fn transfer_ix(authority: &Pubkey, name: &str, new_authority: &Pubkey) -> Instruction {
+    let mut data = vec![2u8];
+    data.extend_from_slice(new_authority.as_ref());
+    Instruction::new_with_bytes(
+        vk_registry::id(),
+        &data,
+        vec![
+            AccountMeta::new(name_record(name), false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+async fn send(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    signers: &[&Keypair],
+    ix: Instruction,
+) -> Result<(), TransactionError> {
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &all_signers, blockhash);
+    banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+fn custom(error: RegistryError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error.code()))
+}
+
+#[tokio::test]
+async fn test_register_deprecate_transfer() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let vk_v1 = Pubkey::new_unique();
+    let vk_v2 = Pubkey::new_unique();
+
+    // First registration claims the name
+    send(
+        &mut banks_client,
+        &payer,
+        &[],
+        register_ix(&payer.pubkey(), "square", "1.0.0", &vk_v1, [1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let account = banks_client
+        .get_account(entry("square", "1.0.0"))
+        .await
+        .unwrap()
+        .unwrap();
+    let registered = RegistryEntry::from_bytes(&account.data).unwrap();
+    assert_eq!(registered.vk_account, vk_v1.to_bytes());
+    assert_eq!(registered.vk_hash, [1u8; 32]);
+    assert_eq!(registered.registered_by, payer.pubkey().to_bytes());
+    assert_eq!(registered.name(), b"square");
+    assert_eq!(registered.version(), b"1.0.0");
+    assert!(!registered.is_deprecated());
+
+    let account = banks_client
+        .get_account(name_record("square"))
+        .await
+        .unwrap()
+        .unwrap();
+    let record = NameRecord::from_bytes(&account.data).unwrap();
+    assert_eq!(record.authority, payer.pubkey().to_bytes());
+
+    // A version can only be registered once
+    let err = send(
+        &mut banks_client,
+        &payer,
+        &[],
+        register_ix(&payer.pubkey(), "square", "1.0.0", &vk_v2, [2u8; 32]),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, custom(RegistryError::EntryExists));
+
+    send(
+        &mut banks_client,
+        &payer,
+        &[],
+        register_ix(&payer.pubkey(), "square", "2.0.0", &vk_v2, [2u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    // Only the authority may add versions
+    let intruder = Keypair::new();
+    let err = send(
+        &mut banks_client,
+        &payer,
+        &[&intruder],
+        register_ix(&intruder.pubkey(), "square", "3.0.0", &vk_v2, [3u8; 32]),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, custom(RegistryError::NotAuthority));
+
+    // Deprecate v1, once
+    send(
+        &mut banks_client,
+        &payer,
+        &[],
+        deprecate_ix(&payer.pubkey(), "square", "1.0.0"),
+    )
+    .await
+    .unwrap();
+    let account = banks_client
+        .get_account(entry("square", "1.0.0"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(RegistryEntry::from_bytes(&account.data)
+        .unwrap()
+        .is_deprecated());
+    let err = send(
+        &mut banks_client,
+        &payer,
+        &[],
+        deprecate_ix(&payer.pubkey(), "square", "1.0.0"),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, custom(RegistryError::AlreadyDeprecated));
+
+    // Hand the name over; the old authority loses control
+    let new_authority = Keypair::new();
+    send(
+        &mut banks_client,
+        &payer,
+        &[],
+        transfer_ix(&payer.pubkey(), "square", &new_authority.pubkey()),
+    )
+    .await
+    .unwrap();
+    let err = send(
+        &mut banks_client,
+        &payer,
+        &[],
+        deprecate_ix(&payer.pubkey(), "square", "2.0.0"),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, custom(RegistryError::NotAuthority));
+    send(
+        &mut banks_client,
+        &payer,
+        &[&new_authority],
+        deprecate_ix(&new_authority.pubkey(), "square", "2.0.0"),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_labels_and_foreign_entries() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let vk = Pubkey::new_unique();
+
+    let err = send(
+        &mut banks_client,
+        &payer,
+        &[],
+        register_ix(&payer.pubkey(), "bad name", "1", &vk, [0u8; 32]),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, custom(RegistryError::InvalidName));
+
+    // An entry of another name cannot be deprecated through this record
+    for name in ["alpha", "beta"] {
+        send(
+            &mut banks_client,
+            &payer,
+            &[],
+            register_ix(&payer.pubkey(), name, "1", &vk, [0u8; 32]),
+        )
+        .await
+        .unwrap();
+    }
+    let mut ix = deprecate_ix(&payer.pubkey(), "alpha", "1");
+    ix.accounts[1].pubkey = entry("beta", "1");
+    let err = send(&mut banks_client, &payer, &[], ix).await.unwrap_err();
+    assert_eq!(err, custom(RegistryError::EntryNameMismatch));
+}
@@ -20,10 +20,17 @@ import {
   BUFFER_HEADER_SIZE,
   VK_HEADER_SIZE,
   STATE_SIZE,
+  STATE_VERIFIED_OFFSET,
   DEFAULT_CHUNK_SIZE,
   DEFAULT_COMPUTE_UNIT_LIMIT,
   RECEIPT_SEED,
   RECEIPT_SIZE,
+  RECEIPT_VERIFICATION_COUNT_OFFSET,
+  RECEIPT_RENT_PAYER_OFFSET,
+  RECEIPT_TAG_OFFSET,
+  RECEIPT_PROOF_DIGEST_OFFSET,
+  RECEIPT_VK_HASH_OFFSET,
+  RECEIPT_RENT_BENEFICIARY_OFFSET,
 } from './types.js';
 import {
   createInitVKBufferInstruction,
@@ -104,29 +111,15 @@ export class SolanaNoirVerifier {
     const setupSig = await this.sendAndConfirm(setupTx, [payer, vkAccount]);
     signatures.push(setupSig);
 
-    // Upload VK chunks in parallel
+    // Upload VK chunks in order: the program stops accepting chunks once
+    // the last byte is written, so a chunk that lands late would be rejected
     const chunks = this.splitIntoChunks(vk);
-    const blockhash = await this.connection.getLatestBlockhash();
-
-    const uploadTxs = chunks.map(({ offset, data }) => {
+    for (const { offset, data } of chunks) {
       const tx = new Transaction().add(
         createUploadVKChunkInstruction(this.programId, vkAccount.publicKey, offset, data)
       );
-      tx.feePayer = payer.publicKey;
-      tx.recentBlockhash = blockhash.blockhash;
-      return tx;
-    });
-
-    // Send all, then confirm all
-    const uploadSigs = await Promise.all(
-      uploadTxs.map(tx => this.connection.sendTransaction(tx, [payer], { skipPreflight: true }))
-    );
-    await Promise.all(
-      uploadSigs.map(sig =>
-        this.connection.confirmTransaction({ signature: sig, ...blockhash }, 'confirmed')
-      )
-    );
-    signatures.push(...uploadSigs);
+      signatures.push(await this.sendAndConfirm(tx, [payer], true));
+    }
 
     return {
       vkAccount: vkAccount.publicKey,
@@ -224,7 +217,10 @@ export class SolanaNoirVerifier {
 
     // Upload proof chunks in parallel
     options?.onProgress?.('upload', 0, 1);
-    const chunks = this.splitIntoChunks(proof);
+    // The program only marks bitmap slots a chunk writes in full, so
+    // proof chunks are cut to whole 1020-byte slots
+    const slots = Math.max(1, Math.floor(this.chunkSize / DEFAULT_CHUNK_SIZE));
+    const chunks = this.splitIntoChunks(proof, slots * DEFAULT_CHUNK_SIZE);
     const blockhash = await this.connection.getLatestBlockhash();
 
     const uploadTxs = chunks.map(({ offset, data }) => {
@@ -361,9 +357,8 @@ export class SolanaNoirVerifier {
     const phase = data[0] as VerificationPhase;
     const logN = data[3];
 
-    // The verified flag is at offset (data.length - 32) - at the end before final padding
-    // This matches the on-chain VerificationState struct layout
-    const verified = data[data.length - 32] === 1;
+    // Offset of the verified flag in the on-chain VerificationState struct
+    const verified = data[STATE_VERIFIED_OFFSET] === 1;
 
     return { phase, logN, verified };
   }
@@ -435,7 +430,17 @@ export class SolanaNoirVerifier {
   async getReceipt(
     vkAccount: PublicKey,
     publicInputs: Buffer[]
-  ): Promise<{ receiptPda: PublicKey; verifiedSlot: bigint; verifiedTimestamp: bigint } | null> {
+  ): Promise<{
+    receiptPda: PublicKey;
+    verifiedSlot: bigint;
+    verifiedTimestamp: bigint;
+    verificationCount: number;
+    tag: Buffer;
+    proofDigest: Buffer;
+    vkHash: Buffer;
+    rentPayer: PublicKey;
+    rentBeneficiary: PublicKey;
+  } | null> {
     const [receiptPda] = this.deriveReceiptPda(vkAccount, publicInputs);
 
     const accountInfo = await this.connection.getAccountInfo(receiptPda);
@@ -452,8 +457,38 @@ export class SolanaNoirVerifier {
     const verifiedSlot = accountInfo.data.readBigUInt64LE(0);
     // Read verified_timestamp (offset 8, 8 bytes LE signed)
     const verifiedTimestamp = accountInfo.data.readBigInt64LE(8);
+    // Verifications counted under DuplicateReceiptPolicy.Count, starting at 1
+    const verificationCount = accountInfo.data.readUInt32LE(RECEIPT_VERIFICATION_COUNT_OFFSET);
+    // Application-defined tag, all zeros if the creator attached none
+    const tag = Buffer.from(accountInfo.data.subarray(RECEIPT_TAG_OFFSET, RECEIPT_PROOF_DIGEST_OFFSET));
+    // Keccak256 of the verified proof bytes
+    const proofDigest = Buffer.from(
+      accountInfo.data.subarray(RECEIPT_PROOF_DIGEST_OFFSET, RECEIPT_VK_HASH_OFFSET)
+    );
+    // Canonical hash of the VK, the same for every account holding it
+    const vkHash = Buffer.from(
+      accountInfo.data.subarray(RECEIPT_VK_HASH_OFFSET, RECEIPT_RENT_BENEFICIARY_OFFSET)
+    );
+    // Account that paid the rent and may close the receipt, and the one the
+    // rent is refunded to
+    const rentPayer = new PublicKey(
+      accountInfo.data.subarray(RECEIPT_RENT_PAYER_OFFSET, RECEIPT_TAG_OFFSET)
+    );
+    const rentBeneficiary = new PublicKey(
+      accountInfo.data.subarray(RECEIPT_RENT_BENEFICIARY_OFFSET, RECEIPT_SIZE)
+    );
 
-    return { receiptPda, verifiedSlot, verifiedTimestamp };
+    return {
+      receiptPda,
+      verifiedSlot,
+      verifiedTimestamp,
+      verificationCount,
+      tag,
+      proofDigest,
+      vkHash,
+      rentPayer,
+      rentBeneficiary,
+    };
   }
 
   /**
@@ -540,11 +575,14 @@ export class SolanaNoirVerifier {
     throw new Error('Transaction confirmation timeout');
   }
 
-  private splitIntoChunks(data: Buffer): Array<{ offset: number; data: Buffer }> {
+  private splitIntoChunks(
+    data: Buffer,
+    size: number = this.chunkSize
+  ): Array<{ offset: number; data: Buffer }> {
     const chunks: Array<{ offset: number; data: Buffer }> = [];
     let offset = 0;
     while (offset < data.length) {
-      const chunkSize = Math.min(this.chunkSize, data.length - offset);
+      const chunkSize = Math.min(size, data.length - offset);
       chunks.push({
         offset,
         data: data.subarray(offset, offset + chunkSize),
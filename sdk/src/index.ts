@@ -49,25 +49,39 @@ export type {
 export {
   // Enums
   VerificationPhase,
+  DuplicateReceiptPolicy,
   // Constants
   PROOF_SIZE,
   VK_SIZE,
   BUFFER_HEADER_SIZE,
   VK_HEADER_SIZE,
   STATE_SIZE,
+  STATE_VERIFIED_OFFSET,
   DEFAULT_CHUNK_SIZE,
   DEFAULT_COMPUTE_UNIT_LIMIT,
   RECEIPT_SEED,
   RECEIPT_SIZE,
+  RECEIPT_VERIFICATION_COUNT_OFFSET,
+  RECEIPT_RENT_PAYER_OFFSET,
+  RECEIPT_TAG_OFFSET,
+  RECEIPT_PROOF_DIGEST_OFFSET,
+  RECEIPT_VK_HASH_OFFSET,
+  RECEIPT_RENT_BENEFICIARY_OFFSET,
   // Instruction codes (for advanced use)
   IX_INIT_BUFFER,
   IX_UPLOAD_CHUNK,
   IX_SET_PUBLIC_INPUTS,
+  IX_UPLOAD_DATA,
+  REGION_PUBLIC_INPUTS,
+  REGION_PROOF,
   IX_INIT_VK_BUFFER,
   IX_UPLOAD_VK_CHUNK,
+  IX_RESET_BUFFER,
   IX_PHASE1_FULL,
   IX_PHASE2_ROUNDS,
   IX_PHASE2D_RELATIONS,
+  IX_PHASE2D_RELATIONS_PART1,
+  IX_PHASE2D_RELATIONS_PART2,
   IX_PHASE3A_WEIGHTS,
   IX_PHASE3B1_FOLDING,
   IX_PHASE3B2_GEMINI,
@@ -75,6 +89,8 @@ export {
   IX_PHASE2D_AND_3A,
   IX_PHASE3B_COMBINED,
   IX_CREATE_RECEIPT,
+  IX_CLOSE_RECEIPT,
+  IX_REVERIFY_BY_DIGEST,
   IX_CLOSE_ACCOUNTS,
 } from './types.js';
 
@@ -85,9 +101,13 @@ export {
   createInitBufferInstruction,
   createUploadChunkInstruction,
   createSetPublicInputsInstruction,
+  createUploadDataInstruction,
+  createResetBufferInstruction,
   createPhase1Instruction,
   createPhase2RoundsInstruction,
   createPhase2RelationsInstruction,
+  createPhase2RelationsPart1Instruction,
+  createPhase2RelationsPart2Instruction,
   createPhase3aInstruction,
   createPhase3b1Instruction,
   createPhase3b2Instruction,
@@ -97,6 +117,8 @@ export {
   createAccountInstruction,
   // Receipt instructions
   createReceiptInstruction,
+  createCloseReceiptInstruction,
+  createReverifyByDigestInstruction,
   // Account management
   createCloseAccountsInstruction,
 } from './instructions.js';
@@ -7,11 +7,15 @@ import {
   IX_INIT_BUFFER,
   IX_UPLOAD_CHUNK,
   IX_SET_PUBLIC_INPUTS,
+  IX_UPLOAD_DATA,
   IX_INIT_VK_BUFFER,
   IX_UPLOAD_VK_CHUNK,
+  IX_RESET_BUFFER,
   IX_PHASE1_FULL,
   IX_PHASE2_ROUNDS,
   IX_PHASE2D_RELATIONS,
+  IX_PHASE2D_RELATIONS_PART1,
+  IX_PHASE2D_RELATIONS_PART2,
   IX_PHASE3A_WEIGHTS,
   IX_PHASE3B1_FOLDING,
   IX_PHASE3B2_GEMINI,
@@ -19,7 +23,10 @@ import {
   IX_PHASE2D_AND_3A,
   IX_PHASE3B_COMBINED,
   IX_CREATE_RECEIPT,
+  IX_CLOSE_RECEIPT,
+  IX_REVERIFY_BY_DIGEST,
   IX_CLOSE_ACCOUNTS,
+  DuplicateReceiptPolicy,
 } from './types.js';
 
 /**
@@ -116,6 +123,50 @@ export function createSetPublicInputsInstruction(
   });
 }
 
+/**
+ * Create instruction to write public input or proof bytes at `offset` into
+ * `region` (`REGION_PUBLIC_INPUTS` or `REGION_PROOF`)
+ */
+export function createUploadDataInstruction(
+  programId: PublicKey,
+  proofAccount: PublicKey,
+  region: number,
+  offset: number,
+  bytes: Buffer
+): TransactionInstruction {
+  const data = Buffer.alloc(4 + bytes.length);
+  data[0] = IX_UPLOAD_DATA;
+  data[1] = region;
+  data.writeUInt16LE(offset, 2);
+  bytes.copy(data, 4);
+
+  return new TransactionInstruction({
+    keys: [{ pubkey: proofAccount, isSigner: false, isWritable: true }],
+    programId,
+    data,
+  });
+}
+
+/**
+ * Create instruction to unlock a proof buffer locked by Phase 1
+ *
+ * Also zeroes the verification state the buffer is bound to.
+ */
+export function createResetBufferInstruction(
+  programId: PublicKey,
+  proofAccount: PublicKey,
+  stateAccount: PublicKey
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: proofAccount, isSigner: false, isWritable: true },
+      { pubkey: stateAccount, isSigner: false, isWritable: true },
+    ],
+    programId,
+    data: Buffer.from([IX_RESET_BUFFER]),
+  });
+}
+
 /**
  * Create Phase 1 instruction (challenge generation)
  */
@@ -128,7 +179,8 @@ export function createPhase1Instruction(
   return new TransactionInstruction({
     keys: [
       { pubkey: stateAccount, isSigner: false, isWritable: true },
-      { pubkey: proofAccount, isSigner: false, isWritable: false },
+      // Writable: Phase 1 locks the proof buffer to the state
+      { pubkey: proofAccount, isSigner: false, isWritable: true },
       { pubkey: vkAccount, isSigner: false, isWritable: false },
     ],
     programId,
@@ -174,6 +226,42 @@ export function createPhase2RelationsInstruction(
   });
 }
 
+/**
+ * Create Phase 2d1 instruction (first half of the relations)
+ */
+export function createPhase2RelationsPart1Instruction(
+  programId: PublicKey,
+  stateAccount: PublicKey,
+  proofAccount: PublicKey
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: stateAccount, isSigner: false, isWritable: true },
+      { pubkey: proofAccount, isSigner: false, isWritable: false },
+    ],
+    programId,
+    data: Buffer.from([IX_PHASE2D_RELATIONS_PART1]),
+  });
+}
+
+/**
+ * Create Phase 2d2 instruction (second half of the relations + final check)
+ */
+export function createPhase2RelationsPart2Instruction(
+  programId: PublicKey,
+  stateAccount: PublicKey,
+  proofAccount: PublicKey
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: stateAccount, isSigner: false, isWritable: true },
+      { pubkey: proofAccount, isSigner: false, isWritable: false },
+    ],
+    programId,
+    data: Buffer.from([IX_PHASE2D_RELATIONS_PART2]),
+  });
+}
+
 /**
  * Create Phase 3a weights instruction
  */
@@ -309,12 +397,21 @@ export function createAccountInstruction(
  * Create verification receipt PDA instruction
  * 
  * Accounts:
- * 0. state_account (readonly) - Must be in Complete phase
+ * 0. state_account (writable) - Must be in Complete phase; marked as
+ *    recorded
  * 1. proof_account (readonly) - For extracting public inputs hash
  * 2. vk_account (readonly) - For PDA derivation
  * 3. receipt_pda (writable) - PDA to create
- * 4. payer (signer) - Pays for account creation
+ * 4. payer (signer) - Pays for account creation; may sponsor the user
  * 5. system_program - For CPI
+ *
+ * `tag` is an optional application-defined 32-byte value stored in the
+ * receipt for integrators to read over CPI. `onDuplicate` picks what
+ * happens if the receipt already exists. The program marks the state as
+ * recorded, so a verification creates, refreshes or counts the receipt at
+ * most once.
+ * `rentBeneficiary` receives the rent when the payer closes the receipt,
+ * the payer itself if omitted.
  */
 export function createReceiptInstruction(
   programId: PublicKey,
@@ -322,11 +419,28 @@ export function createReceiptInstruction(
   proofAccount: PublicKey,
   vkAccount: PublicKey,
   receiptPda: PublicKey,
-  payer: PublicKey
+  payer: PublicKey,
+  tag?: Uint8Array,
+  onDuplicate: DuplicateReceiptPolicy = DuplicateReceiptPolicy.Error,
+  rentBeneficiary?: PublicKey
 ): TransactionInstruction {
+  if (tag && tag.length !== 32) {
+    throw new Error(`Receipt tag must be 32 bytes, got ${tag.length}`);
+  }
+  const hasPolicy = onDuplicate !== DuplicateReceiptPolicy.Error;
+  const parts = [Buffer.from([IX_CREATE_RECEIPT])];
+  if (tag || hasPolicy || rentBeneficiary) {
+    parts.push(tag ? Buffer.from(tag) : Buffer.alloc(32));
+  }
+  if (hasPolicy || rentBeneficiary) {
+    parts.push(Buffer.from([onDuplicate]));
+  }
+  if (rentBeneficiary) {
+    parts.push(rentBeneficiary.toBuffer());
+  }
   return new TransactionInstruction({
     keys: [
-      { pubkey: stateAccount, isSigner: false, isWritable: false },
+      { pubkey: stateAccount, isSigner: false, isWritable: true },
       { pubkey: proofAccount, isSigner: false, isWritable: false },
       { pubkey: vkAccount, isSigner: false, isWritable: false },
       { pubkey: receiptPda, isSigner: false, isWritable: true },
@@ -334,7 +448,58 @@ export function createReceiptInstruction(
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
     programId,
-    data: Buffer.from([IX_CREATE_RECEIPT]),
+    data: Buffer.concat(parts),
+  });
+}
+
+/**
+ * Create close receipt instruction, refunding the receipt's rent
+ *
+ * Accounts:
+ * 0. receipt_pda (writable) - Receipt to close
+ * 1. rent_payer (signer, writable) - The payer recorded in the receipt
+ * 2. rent_beneficiary (writable) - The beneficiary recorded in the
+ *    receipt, passed only when it is not the payer
+ */
+export function createCloseReceiptInstruction(
+  programId: PublicKey,
+  receiptPda: PublicKey,
+  rentPayer: PublicKey,
+  rentBeneficiary: PublicKey = rentPayer
+): TransactionInstruction {
+  const keys = [
+    { pubkey: receiptPda, isSigner: false, isWritable: true },
+    { pubkey: rentPayer, isSigner: true, isWritable: true },
+  ];
+  if (!rentBeneficiary.equals(rentPayer)) {
+    keys.push({ pubkey: rentBeneficiary, isSigner: false, isWritable: true });
+  }
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data: Buffer.from([IX_CLOSE_RECEIPT]),
+  });
+}
+
+/**
+ * Create an instruction that fails unless `proofDigest` (the Keccak256 of
+ * the proof bytes) is the digest the receipt recorded
+ *
+ * Accounts:
+ * 0. receipt_pda (readonly) - Receipt of a completed verification
+ */
+export function createReverifyByDigestInstruction(
+  programId: PublicKey,
+  receiptPda: PublicKey,
+  proofDigest: Uint8Array
+): TransactionInstruction {
+  if (proofDigest.length !== 32) {
+    throw new Error(`Proof digest must be 32 bytes, got ${proofDigest.length}`);
+  }
+  return new TransactionInstruction({
+    keys: [{ pubkey: receiptPda, isSigner: false, isWritable: false }],
+    programId,
+    data: Buffer.concat([Buffer.from([IX_REVERIFY_BY_DIGEST]), Buffer.from(proofDigest)]),
   });
 }
 
@@ -8,7 +8,7 @@ export interface VerifierConfig {
   programId: PublicKey;
   /** Optional: Compute unit limit per transaction (default: 1,400,000) */
   computeUnitLimit?: number;
-  /** Optional: Chunk size for proof uploads (default: 1020 bytes) */
+  /** Optional: Chunk size for uploads (default: 1020 bytes); proof chunks are cut to whole 1020-byte slots */
   chunkSize?: number;
 }
 
@@ -92,6 +92,18 @@ export enum VerificationPhase {
   Failed = 255,
 }
 
+/**
+ * What CreateReceipt does when the receipt already exists
+ */
+export enum DuplicateReceiptPolicy {
+  /** Fail with ReceiptAlreadyExists (108) */
+  Error = 0,
+  /** Move the receipt's slot and timestamp to the new verification */
+  Refresh = 1,
+  /** Increment the receipt's verification count */
+  Count = 2,
+}
+
 /**
  * Parsed verification state from on-chain account
  */
@@ -106,7 +118,8 @@ export const PROOF_SIZE = 16224;
 export const VK_SIZE = 1760;
 export const BUFFER_HEADER_SIZE = 9; // status(1) + proof_len(2) + pi_count(2) + chunk_bitmap(4)
 export const VK_HEADER_SIZE = 3;
-export const STATE_SIZE = 6408;
+export const STATE_SIZE = 6600;
+export const STATE_VERIFIED_OFFSET = 6376;
 export const DEFAULT_CHUNK_SIZE = 1020;
 export const DEFAULT_COMPUTE_UNIT_LIMIT = 1_400_000;
 
@@ -114,11 +127,19 @@ export const DEFAULT_COMPUTE_UNIT_LIMIT = 1_400_000;
 export const IX_INIT_BUFFER = 0;
 export const IX_UPLOAD_CHUNK = 1;
 export const IX_SET_PUBLIC_INPUTS = 3;
+export const IX_UPLOAD_DATA = 14;
+
+// UploadData regions
+export const REGION_PUBLIC_INPUTS = 0;
+export const REGION_PROOF = 1;
 export const IX_INIT_VK_BUFFER = 4;
 export const IX_UPLOAD_VK_CHUNK = 5;
+export const IX_RESET_BUFFER = 7;
 export const IX_PHASE1_FULL = 30;
 export const IX_PHASE2_ROUNDS = 40;
 export const IX_PHASE2D_RELATIONS = 43;
+export const IX_PHASE2D_RELATIONS_PART1 = 44; // Split relations: first half
+export const IX_PHASE2D_RELATIONS_PART2 = 45; // Split relations: second half + final check
 export const IX_PHASE3A_WEIGHTS = 50;
 export const IX_PHASE3B1_FOLDING = 51;
 export const IX_PHASE3B2_GEMINI = 52;
@@ -126,9 +147,17 @@ export const IX_PHASE3C_AND_PAIRING = 54;
 export const IX_PHASE2D_AND_3A = 55; // Combined: Relations + Weights
 export const IX_PHASE3B_COMBINED = 56; // Combined: Folding + Gemini
 export const IX_CREATE_RECEIPT = 60;
+export const IX_CLOSE_RECEIPT = 61;
+export const IX_REVERIFY_BY_DIGEST = 62;
 export const IX_CLOSE_ACCOUNTS = 70;
 
 // Receipt PDA constants
 export const RECEIPT_SEED = 'receipt';
-export const RECEIPT_SIZE = 16; // slot (8) + timestamp (8)
+export const RECEIPT_SIZE = 248; // slot (8) + timestamp (8) + vk account (32) + PI hash (32) + bump (1) + padding (3) + verification count (4) + rent payer (32) + tag (32) + proof digest (32) + VK hash (32) + rent beneficiary (32)
+export const RECEIPT_VERIFICATION_COUNT_OFFSET = 84;
+export const RECEIPT_RENT_PAYER_OFFSET = 88;
+export const RECEIPT_TAG_OFFSET = 120;
+export const RECEIPT_PROOF_DIGEST_OFFSET = 152;
+export const RECEIPT_VK_HASH_OFFSET = 184;
+export const RECEIPT_RENT_BENEFICIARY_OFFSET = 216;
 
@@ -0,0 +1,89 @@
+//! Receipt-gated program for the negative-path suite
+//!
+//! The tests in `tests/` try every way an auditor would look for to get a
+//! receipt without a verified proof, against both the verifier's
+//! `CreateReceipt` and an integrator that checks receipts with
+//! `solana-noir-verifier-cpi`. This crate is that integrator: a program
+//! loaded natively next to the verifier that accepts a call only when the
+//! receipt it is given vouches for its public inputs.
+//!
+//! Run with the test-circuits artifacts in place (the verifier's build
+//! script embeds a VK):
+//!
+//! ```text
+//! cd tests/security && cargo test
+//! ```
+
+use solana_noir_verifier_cpi::{
+    is_verified, is_verified_by_vk_hash,
+    require::{require_verified_proof, ProofCheck},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Gate with [`is_verified`]
+pub const IX_IS_VERIFIED: u8 = 0;
+
+/// Gate with [`require_verified_proof`]
+pub const IX_REQUIRE_VERIFIED: u8 = 1;
+
+/// Gate with [`is_verified_by_vk_hash`], pinning the VK hash given in the
+/// instruction data
+pub const IX_IS_VERIFIED_BY_VK_HASH: u8 = 2;
+
+/// Error returned when [`is_verified`] or [`is_verified_by_vk_hash`]
+/// rejects the receipt
+pub const NOT_VERIFIED: u32 = 1;
+
+/// Accept the call only with a receipt for the public inputs
+///
+/// Accounts:
+/// 0. receipt (readonly) - Receipt to check
+/// 1. vk_account (readonly) - VK the proof must have been verified against
+///
+/// Data format: [gate (1 byte), public inputs...], or
+/// [gate (1 byte), vk_hash (32 bytes), public inputs...] for
+/// [`IX_IS_VERIFIED_BY_VK_HASH`]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (&gate, public_inputs) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let receipt = next_account_info(account_iter)?;
+    let vk_account = next_account_info(account_iter)?;
+    let verifier = ultrahonk_verifier::id();
+
+    match gate {
+        IX_IS_VERIFIED => {
+            if !is_verified(receipt, vk_account.key, public_inputs, &verifier) {
+                return Err(ProgramError::Custom(NOT_VERIFIED));
+            }
+        }
+        IX_REQUIRE_VERIFIED => {
+            require_verified_proof(
+                ProofCheck::new(receipt, &verifier),
+                vk_account.key,
+                public_inputs,
+            )?;
+        }
+        IX_IS_VERIFIED_BY_VK_HASH => {
+            let (vk_hash, public_inputs) = public_inputs
+                .split_first_chunk::<32>()
+                .ok_or(ProgramError::InvalidInstructionData)?;
+            if !is_verified_by_vk_hash(receipt, vk_hash, public_inputs, &verifier) {
+                return Err(ProgramError::Custom(NOT_VERIFIED));
+            }
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
@@ -0,0 +1,1026 @@
+//! Attempts to obtain or fake a receipt without a verified proof
+//!
+//! Most tests play one bypass against the verifier's `CreateReceipt` and
+//! against an integrator checking receipts with `solana-noir-verifier-cpi`
+//! (the gate program in `src/lib.rs`), and assert both reject it. Completed
+//! verifications are seeded with `test_utils::add_verified_state`, so no
+//! proof is verified here. The rest attack the accounts receipts rest on:
+//! rewriting a VK account receipts are keyed by, draining writable
+//! program-owned accounts through `CloseReceipt`, and wiping a verification
+//! with `ResetBuffer` before its receipt exists.
+
+#![allow(deprecated)]
+
+use plonk_solana_core::key::VerificationKey;
+use plonk_solana_core::types::FQ_MODULUS;
+use plonk_solana_core::G2;
+use solana_noir_verifier_cpi::{
+    create_receipt_instruction, create_receipt_instruction_with_options, derive_receipt_pda,
+    pi_hash, proof_digest, reverify_by_digest_instruction, DuplicateReceiptPolicy, ReceiptOptions,
+    VerificationResult,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use ultrahonk_verifier::phased::{VerificationReceipt, VerificationState};
+use ultrahonk_verifier::test_utils::{
+    add_verified_state, add_verified_state_with_srs, add_vk_account, add_vk_account_with_srs,
+    mint_receipt_for, program_test,
+};
+use ultrahonk_verifier::{
+    VerifierError, VkBufferStatus, BUFFER_HEADER_SIZE, PROOF_SIZE, VK_HEADER_SIZE, VK_SIZE,
+};
+use verifier_security_tests::{
+    IX_IS_VERIFIED, IX_IS_VERIFIED_BY_VK_HASH, IX_REQUIRE_VERIFIED, NOT_VERIFIED,
+};
+
+const PUBLIC_INPUTS: [u8; 32] = [1u8; 32];
+const OTHER_INPUTS: [u8; 32] = [2u8; 32];
+const PROOF: [u8; 64] = [5u8; 64];
+
+/// Program ID of the gate in `src/lib.rs`
+fn gate_id() -> Pubkey {
+    Pubkey::new_from_array([0x6a; 32])
+}
+
+/// Owner of accounts the attacker controls
+fn attacker_program() -> Pubkey {
+    Pubkey::new_from_array([0xa7; 32])
+}
+
+/// VK with a valid header (`CreateReceipt` parses it to record its hash)
+/// and arbitrary commitments
+fn vk() -> Vec<u8> {
+    let mut vk = vec![7u8; VK_SIZE];
+    vk[..32].fill(0);
+    vk[7] = 64; // circuit size
+    vk[15] = 6; // log2 circuit size
+    vk[23] = 1; // public inputs
+    vk
+}
+
+/// Verifier and gate, with a VK account for [`vk`]
+fn setup() -> (ProgramTest, Pubkey) {
+    let mut program_test = program_test();
+    program_test.add_program(
+        "gate",
+        gate_id(),
+        processor!(verifier_security_tests::process_instruction),
+    );
+    let vk_account = add_vk_account(&mut program_test, &vk());
+    (program_test, vk_account)
+}
+
+fn receipt_pda(vk_account: &Pubkey, public_inputs: &[u8]) -> Pubkey {
+    derive_receipt_pda(
+        vk_account,
+        &pi_hash(public_inputs),
+        &ultrahonk_verifier::id(),
+    )
+    .0
+}
+
+/// Receipt data as `CreateReceipt` would write it for `public_inputs`
+fn receipt_data(vk_account: &Pubkey, public_inputs: &[u8]) -> Vec<u8> {
+    let pi_hash = pi_hash(public_inputs);
+    let (_, bump) = derive_receipt_pda(vk_account, &pi_hash, &ultrahonk_verifier::id());
+    let mut data = vec![0u8; VerificationReceipt::SIZE];
+    data[16..48].copy_from_slice(vk_account.as_ref());
+    data[48..80].copy_from_slice(&pi_hash);
+    data[80] = bump;
+    data[84..88].copy_from_slice(&1u32.to_le_bytes());
+    data
+}
+
+fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn gate_ix(gate: u8, receipt: &Pubkey, vk_account: &Pubkey, public_inputs: &[u8]) -> Instruction {
+    let mut data = vec![gate];
+    data.extend_from_slice(public_inputs);
+    Instruction::new_with_bytes(
+        gate_id(),
+        &data,
+        vec![
+            AccountMeta::new_readonly(*receipt, false),
+            AccountMeta::new_readonly(*vk_account, false),
+        ],
+    )
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    signer: &Keypair,
+    ix: Instruction,
+) -> Result<(), TransactionError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+/// Send `ix` and decode the [`VerificationResult`] the verifier returned
+async fn send_for_result(
+    context: &mut ProgramTestContext,
+    signer: &Keypair,
+    ix: Instruction,
+) -> VerificationResult {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+    let outcome = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    outcome.result.unwrap();
+    let return_data = outcome.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, ultrahonk_verifier::id());
+    VerificationResult::from_bytes(&return_data.data).unwrap()
+}
+
+/// Assert both gates reject `receipt` for `public_inputs`, the
+/// `require_verified_proof` one with `expected`
+async fn assert_gates_reject(
+    context: &mut ProgramTestContext,
+    receipt: &Pubkey,
+    vk_account: &Pubkey,
+    public_inputs: &[u8],
+    expected: InstructionError,
+) {
+    let payer = context.payer.insecure_clone();
+    let ix = gate_ix(IX_IS_VERIFIED, receipt, vk_account, public_inputs);
+    assert_eq!(
+        send(context, &payer, ix).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(NOT_VERIFIED))
+    );
+    let ix = gate_ix(IX_REQUIRE_VERIFIED, receipt, vk_account, public_inputs);
+    assert_eq!(
+        send(context, &payer, ix).await.unwrap_err(),
+        TransactionError::InstructionError(0, expected)
+    );
+}
+
+async fn assert_gates_accept(
+    context: &mut ProgramTestContext,
+    receipt: &Pubkey,
+    vk_account: &Pubkey,
+    public_inputs: &[u8],
+) {
+    let payer = context.payer.insecure_clone();
+    for gate in [IX_IS_VERIFIED, IX_REQUIRE_VERIFIED] {
+        let ix = gate_ix(gate, receipt, vk_account, public_inputs);
+        send(context, &payer, ix).await.unwrap();
+    }
+}
+
+fn close_receipt_ix(
+    receipt: &Pubkey,
+    rent_payer: &Pubkey,
+    beneficiary: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*receipt, false),
+        AccountMeta::new(*rent_payer, true),
+    ];
+    accounts.extend(beneficiary.map(|b| AccountMeta::new(*b, false)));
+    Instruction::new_with_bytes(
+        ultrahonk_verifier::id(),
+        &[ultrahonk_verifier::Instruction::CloseReceipt as u8],
+        accounts,
+    )
+}
+
+fn custom(error: VerifierError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error.code()))
+}
+
+/// A funded keypair that is not the test payer
+fn add_attacker(program_test: &mut ProgramTest) -> Keypair {
+    let attacker = Keypair::new();
+    program_test.add_account(
+        attacker.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+    attacker
+}
+
+#[tokio::test]
+async fn test_genuine_receipt_passes_gates() {
+    // Control: the seeded accounts are accepted when nothing is tampered with
+    let (mut program_test, vk_account) = setup();
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let minted = mint_receipt_for(&mut program_test, &vk_account, &OTHER_INPUTS);
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    let receipt = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+    let ix = create_receipt_instruction(
+        &ultrahonk_verifier::id(),
+        &state,
+        &proof_buffer,
+        &vk_account,
+        &receipt,
+        &payer.pubkey(),
+    );
+    let created = send_for_result(&mut context, &payer, ix).await;
+
+    assert_gates_accept(&mut context, &receipt, &vk_account, &PUBLIC_INPUTS).await;
+    assert_gates_accept(&mut context, &minted, &vk_account, &OTHER_INPUTS).await;
+
+    // Both receipt instructions report the verification to CPI callers
+    let account = |key| {
+        let banks = context.banks_client.clone();
+        async move { banks.get_account(key).await.unwrap().unwrap().data }
+    };
+    let receipt_data = account(receipt).await;
+    let stored = VerificationReceipt::from_bytes(&receipt_data).unwrap();
+    let vk_hash = VerificationKey::from_bytes(&vk()).unwrap().hash();
+    assert_eq!(stored.vk_hash, vk_hash);
+    let expected = VerificationResult {
+        verified: true,
+        slot: stored.verified_slot,
+        vk_hash,
+        pi_hash: pi_hash(&PUBLIC_INPUTS),
+    };
+    assert_eq!(created, expected);
+
+    let mut ix =
+        reverify_by_digest_instruction(&ultrahonk_verifier::id(), &receipt, &proof_digest(&PROOF));
+    let reverified = send_for_result(&mut context, &payer, ix.clone()).await;
+    assert_eq!(reverified, expected);
+    let mut wrong_vk = ix.clone();
+    ix.accounts
+        .push(AccountMeta::new_readonly(vk_account, false));
+    assert_eq!(send_for_result(&mut context, &payer, ix).await, expected);
+    wrong_vk
+        .accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    assert_eq!(
+        send(&mut context, &payer, wrong_vk).await.unwrap_err(),
+        custom(VerifierError::VkAccountMismatch)
+    );
+}
+
+#[tokio::test]
+async fn test_fake_receipt_owned_by_attacker() {
+    let (mut program_test, vk_account) = setup();
+    let data = receipt_data(&vk_account, &PUBLIC_INPUTS);
+
+    // At the receipt's own address, as if the PDA could be squatted, and
+    // at an address of the attacker's choosing
+    let at_pda = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+    let elsewhere = Pubkey::new_unique();
+    program_test.add_account(at_pda, account(attacker_program(), data.clone()));
+    program_test.add_account(elsewhere, account(attacker_program(), data));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    for receipt in [at_pda, elsewhere] {
+        assert_gates_reject(
+            &mut context,
+            &receipt,
+            &vk_account,
+            &PUBLIC_INPUTS,
+            InstructionError::IllegalOwner,
+        )
+        .await;
+
+        let ix = reverify_by_digest_instruction(
+            &ultrahonk_verifier::id(),
+            &receipt,
+            &proof_digest(&PROOF),
+        );
+        assert_eq!(
+            send(&mut context, &payer, ix).await.unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_receipt_at_wrong_pda() {
+    let (mut program_test, vk_account) = setup();
+    let other_vk = add_vk_account(&mut program_test, &vec![8u8; VK_SIZE]);
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+
+    // Genuine receipts, for other inputs and for another VK
+    let other_inputs = mint_receipt_for(&mut program_test, &vk_account, &OTHER_INPUTS);
+    let other_vk_receipt = mint_receipt_for(&mut program_test, &other_vk, &PUBLIC_INPUTS);
+
+    // Our receipt's bytes in a verifier-owned account off the PDA
+    let copied = Pubkey::new_unique();
+    program_test.add_account(
+        copied,
+        account(
+            ultrahonk_verifier::id(),
+            receipt_data(&vk_account, &PUBLIC_INPUTS),
+        ),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    assert_gates_reject(
+        &mut context,
+        &other_inputs,
+        &vk_account,
+        &PUBLIC_INPUTS,
+        InstructionError::InvalidArgument,
+    )
+    .await;
+    for receipt in [other_vk_receipt, copied, vk_account] {
+        assert_gates_reject(
+            &mut context,
+            &receipt,
+            &vk_account,
+            &PUBLIC_INPUTS,
+            InstructionError::InvalidSeeds,
+        )
+        .await;
+    }
+    let ix = reverify_by_digest_instruction(&ultrahonk_verifier::id(), &copied, &[0u8; 32]);
+    assert_eq!(
+        send(&mut context, &payer, ix).await.unwrap_err(),
+        custom(VerifierError::InvalidReceiptAccount)
+    );
+
+    // CreateReceipt only writes the PDA of the verified inputs
+    for receipt in [
+        Pubkey::new_unique(),
+        receipt_pda(&vk_account, &OTHER_INPUTS),
+        receipt_pda(&other_vk, &PUBLIC_INPUTS),
+    ] {
+        let ix = create_receipt_instruction(
+            &ultrahonk_verifier::id(),
+            &state,
+            &proof_buffer,
+            &vk_account,
+            &receipt,
+            &payer.pubkey(),
+        );
+        assert_eq!(
+            send(&mut context, &payer, ix).await.unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_state_from_different_proof() {
+    let (mut program_test, vk_account) = setup();
+    let other_vk = add_vk_account(&mut program_test, &vec![8u8; VK_SIZE]);
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let (_, other_buffer) =
+        add_verified_state(&mut program_test, &vk_account, &vk(), &OTHER_INPUTS, &PROOF);
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let create = |state: &Pubkey, buffer: &Pubkey, vk: &Pubkey, receipt: &Pubkey| {
+        create_receipt_instruction(
+            &ultrahonk_verifier::id(),
+            state,
+            buffer,
+            vk,
+            receipt,
+            &payer.pubkey(),
+        )
+    };
+
+    // The state vouches only for the buffer it verified
+    let wanted = receipt_pda(&vk_account, &OTHER_INPUTS);
+    let ix = create(&state, &other_buffer, &vk_account, &wanted);
+    assert_eq!(
+        send(&mut context, &payer, ix).await.unwrap_err(),
+        custom(VerifierError::ProofBufferMismatch)
+    );
+
+    // ... and only for the VK it verified against
+    let ix = create(
+        &state,
+        &proof_buffer,
+        &other_vk,
+        &receipt_pda(&other_vk, &PUBLIC_INPUTS),
+    );
+    assert_eq!(
+        send(&mut context, &payer, ix).await.unwrap_err(),
+        custom(VerifierError::VkAccountMismatch)
+    );
+
+    // A copy of a completed state in an account the attacker owns, bound
+    // to the buffer holding the inputs they want a receipt for
+    let mut forged = context
+        .banks_client
+        .get_account(state)
+        .await
+        .unwrap()
+        .unwrap();
+    VerificationState::from_bytes_mut(&mut forged.data)
+        .unwrap()
+        .proof_buffer = other_buffer.to_bytes();
+    forged.owner = attacker_program();
+    let forged_state = Pubkey::new_unique();
+    context.set_account(&forged_state, &AccountSharedData::from(forged.clone()));
+    let ix = create(&forged_state, &other_buffer, &vk_account, &wanted);
+    assert_eq!(
+        send(&mut context, &payer, ix).await.unwrap_err(),
+        custom(VerifierError::InvalidStateAccount)
+    );
+
+    // A verifier-owned state whose verification did not succeed
+    forged.owner = ultrahonk_verifier::id();
+    VerificationState::from_bytes_mut(&mut forged.data)
+        .unwrap()
+        .verified = 0;
+    context.set_account(&forged_state, &AccountSharedData::from(forged));
+    let ix = create(&forged_state, &other_buffer, &vk_account, &wanted);
+    assert_eq!(
+        send(&mut context, &payer, ix).await.unwrap_err(),
+        custom(VerifierError::VerificationNotComplete)
+    );
+
+    assert_gates_reject(
+        &mut context,
+        &wanted,
+        &vk_account,
+        &OTHER_INPUTS,
+        InstructionError::IllegalOwner,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_replayed_create_receipt() {
+    let (mut program_test, vk_account) = setup();
+    let attacker = add_attacker(&mut program_test);
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let receipt = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+    let create = |payer: &Keypair, policy: DuplicateReceiptPolicy| {
+        create_receipt_instruction_with_options(
+            &ultrahonk_verifier::id(),
+            &state,
+            &proof_buffer,
+            &vk_account,
+            &receipt,
+            &payer.pubkey(),
+            &ReceiptOptions::default().with_on_duplicate(policy),
+        )
+    };
+
+    send(
+        &mut context,
+        &payer,
+        create(&payer, DuplicateReceiptPolicy::Count),
+    )
+    .await
+    .unwrap();
+
+    // Replays by anyone, with any policy, leave the receipt as it was
+    assert_eq!(
+        send(
+            &mut context,
+            &attacker,
+            create(&attacker, DuplicateReceiptPolicy::Error)
+        )
+        .await
+        .unwrap_err(),
+        custom(VerifierError::ReceiptAlreadyExists)
+    );
+    for policy in [
+        DuplicateReceiptPolicy::Count,
+        DuplicateReceiptPolicy::Refresh,
+    ] {
+        assert_eq!(
+            send(&mut context, &attacker, create(&attacker, policy))
+                .await
+                .unwrap_err(),
+            custom(VerifierError::StateAlreadyRecorded)
+        );
+
+        // A read-only state cannot be checked for an earlier recording
+        let mut ix = create(&attacker, policy);
+        ix.accounts[0].is_writable = false;
+        assert_eq!(
+            send(&mut context, &attacker, ix).await.unwrap_err(),
+            custom(VerifierError::AccountNotWritable)
+        );
+    }
+
+    let account = context
+        .banks_client
+        .get_account(receipt)
+        .await
+        .unwrap()
+        .unwrap();
+    let recorded = VerificationReceipt::from_bytes(&account.data).unwrap();
+    assert_eq!(recorded.verification_count, 1);
+    assert_eq!(recorded.rent_payer, payer.pubkey().to_bytes());
+    assert_gates_accept(&mut context, &receipt, &vk_account, &PUBLIC_INPUTS).await;
+}
+
+#[tokio::test]
+async fn test_count_after_create_receipt() {
+    // Creating the receipt records the verification, whatever the policy,
+    // so the same state cannot count it a second time
+    let (mut program_test, vk_account) = setup();
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let receipt = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+    let create = |policy: DuplicateReceiptPolicy| {
+        create_receipt_instruction_with_options(
+            &ultrahonk_verifier::id(),
+            &state,
+            &proof_buffer,
+            &vk_account,
+            &receipt,
+            &payer.pubkey(),
+            &ReceiptOptions::default().with_on_duplicate(policy),
+        )
+    };
+
+    // The state cannot be left unmarked by passing it read-only
+    let mut ix = create(DuplicateReceiptPolicy::Error);
+    ix.accounts[0].is_writable = false;
+    assert_eq!(
+        send(&mut context, &payer, ix).await.unwrap_err(),
+        custom(VerifierError::AccountNotWritable)
+    );
+
+    send(&mut context, &payer, create(DuplicateReceiptPolicy::Error))
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        VerificationState::from_bytes(&account.data)
+            .unwrap()
+            .receipt_recorded,
+        1
+    );
+
+    assert_eq!(
+        send(&mut context, &payer, create(DuplicateReceiptPolicy::Count))
+            .await
+            .unwrap_err(),
+        custom(VerifierError::StateAlreadyRecorded)
+    );
+    let account = context
+        .banks_client
+        .get_account(receipt)
+        .await
+        .unwrap()
+        .unwrap();
+    let recorded = VerificationReceipt::from_bytes(&account.data).unwrap();
+    assert_eq!(recorded.verification_count, 1);
+}
+
+#[tokio::test]
+async fn test_reset_before_receipt() {
+    let (mut program_test, vk_account) = setup();
+    let attacker = add_attacker(&mut program_test);
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let receipt = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+    let reset = Instruction {
+        program_id: ultrahonk_verifier::id(),
+        accounts: vec![
+            AccountMeta::new(proof_buffer, false),
+            AccountMeta::new(state, false),
+        ],
+        data: vec![7],
+    };
+    let before = context
+        .banks_client
+        .get_account(state)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Nobody can wipe a successful verification before its receipt exists
+    assert_eq!(
+        send(&mut context, &attacker, reset.clone())
+            .await
+            .unwrap_err(),
+        custom(VerifierError::InvalidPhase)
+    );
+    let after = context
+        .banks_client
+        .get_account(state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after.data, before.data);
+
+    send(
+        &mut context,
+        &payer,
+        create_receipt_instruction(
+            &ultrahonk_verifier::id(),
+            &state,
+            &proof_buffer,
+            &vk_account,
+            &receipt,
+            &payer.pubkey(),
+        ),
+    )
+    .await
+    .unwrap();
+    assert_gates_accept(&mut context, &receipt, &vk_account, &PUBLIC_INPUTS).await;
+
+    // Once recorded, the buffer can be reset and the receipt stays
+    send(&mut context, &attacker, reset).await.unwrap();
+    assert_gates_accept(&mut context, &receipt, &vk_account, &PUBLIC_INPUTS).await;
+}
+
+#[tokio::test]
+async fn test_sponsored_receipt_close() {
+    // A sponsor pays for the receipt and sends the rent to a treasury; no
+    // one can redirect it or close the receipt in the sponsor's place
+    let (mut program_test, vk_account) = setup();
+    let attacker = add_attacker(&mut program_test);
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let mut context = program_test.start_with_context().await;
+    let sponsor = context.payer.insecure_clone();
+    let treasury = Pubkey::new_unique();
+    let receipt = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+
+    let ix = create_receipt_instruction_with_options(
+        &ultrahonk_verifier::id(),
+        &state,
+        &proof_buffer,
+        &vk_account,
+        &receipt,
+        &sponsor.pubkey(),
+        &ReceiptOptions::default().with_rent_beneficiary(treasury),
+    );
+    send(&mut context, &sponsor, ix).await.unwrap();
+    let account = context
+        .banks_client
+        .get_account(receipt)
+        .await
+        .unwrap()
+        .unwrap();
+    let recorded = VerificationReceipt::from_bytes(&account.data).unwrap();
+    assert_eq!(recorded.rent_payer, sponsor.pubkey().to_bytes());
+    assert_eq!(recorded.rent_beneficiary, treasury.to_bytes());
+
+    let close = |rent_payer: &Pubkey, beneficiary: Option<&Pubkey>| {
+        close_receipt_ix(&receipt, rent_payer, beneficiary)
+    };
+    assert_eq!(
+        send(
+            &mut context,
+            &attacker,
+            close(&attacker.pubkey(), Some(&attacker.pubkey()))
+        )
+        .await
+        .unwrap_err(),
+        custom(VerifierError::NotReceiptPayer)
+    );
+    assert_eq!(
+        send(
+            &mut context,
+            &sponsor,
+            close(&sponsor.pubkey(), Some(&attacker.pubkey()))
+        )
+        .await
+        .unwrap_err(),
+        custom(VerifierError::NotReceiptBeneficiary)
+    );
+    assert_eq!(
+        send(&mut context, &sponsor, close(&sponsor.pubkey(), None))
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    send(
+        &mut context,
+        &sponsor,
+        close(&sponsor.pubkey(), Some(&treasury)),
+    )
+    .await
+    .unwrap();
+    let refunded = context.banks_client.get_balance(treasury).await.unwrap();
+    assert_eq!(refunded, account.lamports);
+    assert!(context
+        .banks_client
+        .get_account(receipt)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_close_proof_buffer_as_receipt() {
+    // Anyone can write an unlocked proof buffer; one written to read as a
+    // receipt the attacker paid for must not be drained through
+    // CloseReceipt
+    let (mut program_test, _) = setup();
+    let attacker = add_attacker(&mut program_test);
+    let proof_buffer = Pubkey::new_unique();
+    let mut data = vec![0u8; BUFFER_HEADER_SIZE + 32 + PROOF_SIZE];
+    data[3..5].copy_from_slice(&1u16.to_le_bytes());
+    program_test.add_account(proof_buffer, account(ultrahonk_verifier::id(), data));
+    let mut context = program_test.start_with_context().await;
+
+    // The proof starts past the header and one public input; put the
+    // attacker at the receipt's rent_payer and rent_beneficiary offsets
+    let proof_start = BUFFER_HEADER_SIZE + 32;
+    let mut chunk = vec![0u8; VerificationReceipt::SIZE - proof_start];
+    for field in [88, 216] {
+        let at = field - proof_start;
+        chunk[at..at + 32].copy_from_slice(attacker.pubkey().as_ref());
+    }
+    let mut upload = vec![ultrahonk_verifier::Instruction::UploadChunk as u8, 0, 0];
+    upload.extend_from_slice(&chunk);
+    let ix = Instruction::new_with_bytes(
+        ultrahonk_verifier::id(),
+        &upload,
+        vec![AccountMeta::new(proof_buffer, false)],
+    );
+    send(&mut context, &attacker, ix).await.unwrap();
+    let before = context
+        .banks_client
+        .get_account(proof_buffer)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let ix = close_receipt_ix(&proof_buffer, &attacker.pubkey(), None);
+    assert_eq!(
+        send(&mut context, &attacker, ix).await.unwrap_err(),
+        custom(VerifierError::InvalidReceiptAccount)
+    );
+    let after = context
+        .banks_client
+        .get_account(proof_buffer)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after, before);
+}
+
+/// `UploadVkChunk` writing `bytes` at `offset` of `vk_account`
+fn upload_vk_chunk_ix(vk_account: &Pubkey, offset: u16, bytes: &[u8]) -> Instruction {
+    let mut data = vec![ultrahonk_verifier::Instruction::UploadVkChunk as u8];
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(bytes);
+    Instruction::new_with_bytes(
+        ultrahonk_verifier::id(),
+        &data,
+        vec![AccountMeta::new(*vk_account, false)],
+    )
+}
+
+#[tokio::test]
+async fn test_overwrite_vk_account() {
+    // VK accounts have no upload authority. Were a ready or compiled VK
+    // rewritable, an attacker could swap in their own key and create
+    // receipts at PDA(vk_account, pi) for any public inputs
+    let (mut program_test, ready_vk) = setup();
+    let attacker = add_attacker(&mut program_test);
+    let key = VerificationKey::from_bytes(&vk()).unwrap();
+    let mut compiled = vec![0u8; VK_HEADER_SIZE + VK_SIZE];
+    compiled[0] = VkBufferStatus::Compiled as u8;
+    compiled[1..3].copy_from_slice(&(key.compiled_size() as u16).to_le_bytes());
+    key.write_compiled(&mut compiled[VK_HEADER_SIZE..]).unwrap();
+    let compiled_vk = Pubkey::new_unique();
+    program_test.add_account(compiled_vk, account(ultrahonk_verifier::id(), compiled));
+    let mut context = program_test.start_with_context().await;
+
+    for vk_account in [ready_vk, compiled_vk] {
+        let before = context
+            .banks_client
+            .get_account(vk_account)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let init = Instruction::new_with_bytes(
+            ultrahonk_verifier::id(),
+            &[ultrahonk_verifier::Instruction::InitVkBuffer as u8],
+            vec![AccountMeta::new(vk_account, false)],
+        );
+        assert_eq!(
+            send(&mut context, &attacker, init).await.unwrap_err(),
+            custom(VerifierError::VkAlreadyCompiled)
+        );
+        let ix = upload_vk_chunk_ix(&vk_account, 0, &[0xa7; 512]);
+        assert_eq!(
+            send(&mut context, &attacker, ix).await.unwrap_err(),
+            custom(VerifierError::VkAlreadyCompiled)
+        );
+
+        let after = context
+            .banks_client
+            .get_account(vk_account)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after, before);
+    }
+}
+
+#[tokio::test]
+async fn test_close_vk_account_as_receipt() {
+    // A VK account still being uploaded takes anyone's chunks; one written
+    // to read as a receipt the attacker paid for must not be drained
+    let (mut program_test, _) = setup();
+    let attacker = add_attacker(&mut program_test);
+    let vk_account = Pubkey::new_unique();
+    program_test.add_account(
+        vk_account,
+        account(
+            ultrahonk_verifier::id(),
+            vec![0u8; VK_HEADER_SIZE + VK_SIZE],
+        ),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    // The VK starts past its header; put the attacker at the receipt's
+    // rent_payer and rent_beneficiary offsets
+    let mut chunk = vec![0u8; VerificationReceipt::SIZE - VK_HEADER_SIZE];
+    for field in [88, 216] {
+        let at = field - VK_HEADER_SIZE;
+        chunk[at..at + 32].copy_from_slice(attacker.pubkey().as_ref());
+    }
+    let ix = upload_vk_chunk_ix(&vk_account, 0, &chunk);
+    send(&mut context, &attacker, ix).await.unwrap();
+    let before = context
+        .banks_client
+        .get_account(vk_account)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let ix = close_receipt_ix(&vk_account, &attacker.pubkey(), None);
+    assert_eq!(
+        send(&mut context, &attacker, ix).await.unwrap_err(),
+        custom(VerifierError::InvalidReceiptAccount)
+    );
+    let after = context
+        .banks_client
+        .get_account(vk_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after, before);
+}
+
+/// The default x·G2 SRS point negated: a valid point that accepts proofs
+/// the real setup does not
+fn attacker_srs_g2() -> G2 {
+    let mut srs_g2 = plonk_solana_core::default_srs_g2();
+    for y in srs_g2[64..].chunks_exact_mut(32) {
+        let mut borrow = 0u16;
+        for i in (0..32).rev() {
+            let diff = FQ_MODULUS[i] as u16 + 0x100 - y[i] as u16 - borrow;
+            y[i] = diff as u8;
+            borrow = 1 - (diff >> 8);
+        }
+    }
+    srs_g2
+}
+
+#[tokio::test]
+async fn test_custom_srs_vk_hash() {
+    // The real VK uploaded with the attacker's SRS point verifies forged
+    // proofs; its receipts must not pass a check pinned to the VK hash
+    let (mut program_test, vk_account) = setup();
+    let srs_g2 = attacker_srs_g2();
+    let srs_vk_account = add_vk_account_with_srs(&mut program_test, &vk(), &srs_g2);
+    let (state, proof_buffer) = add_verified_state(
+        &mut program_test,
+        &vk_account,
+        &vk(),
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let (srs_state, srs_proof_buffer) = add_verified_state_with_srs(
+        &mut program_test,
+        &srs_vk_account,
+        &vk(),
+        &srs_g2,
+        &PUBLIC_INPUTS,
+        &PROOF,
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    let receipt = receipt_pda(&vk_account, &PUBLIC_INPUTS);
+    let ix = create_receipt_instruction(
+        &ultrahonk_verifier::id(),
+        &state,
+        &proof_buffer,
+        &vk_account,
+        &receipt,
+        &payer.pubkey(),
+    );
+    let honest = send_for_result(&mut context, &payer, ix).await;
+    let srs_receipt = receipt_pda(&srs_vk_account, &PUBLIC_INPUTS);
+    let ix = create_receipt_instruction(
+        &ultrahonk_verifier::id(),
+        &srs_state,
+        &srs_proof_buffer,
+        &srs_vk_account,
+        &srs_receipt,
+        &payer.pubkey(),
+    );
+    let forged = send_for_result(&mut context, &payer, ix).await;
+
+    let vk = VerificationKey::from_bytes(&vk()).unwrap();
+    assert_eq!(honest.vk_hash, vk.hash());
+    assert_eq!(forged.vk_hash, vk.hash_with_srs(&srs_g2));
+    assert_ne!(forged.vk_hash, honest.vk_hash);
+
+    let by_vk_hash = |receipt: &Pubkey, vk_hash: &[u8; 32]| {
+        gate_ix(
+            IX_IS_VERIFIED_BY_VK_HASH,
+            receipt,
+            &vk_account,
+            &[vk_hash.as_slice(), &PUBLIC_INPUTS].concat(),
+        )
+    };
+    send(&mut context, &payer, by_vk_hash(&receipt, &honest.vk_hash))
+        .await
+        .unwrap();
+    assert_eq!(
+        send(
+            &mut context,
+            &payer,
+            by_vk_hash(&srs_receipt, &honest.vk_hash)
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(NOT_VERIFIED))
+    );
+}
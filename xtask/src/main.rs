@@ -0,0 +1,301 @@
+//! Repository automation, run as `cargo xtask <task>`
+//!
+//! Tasks:
+//!   differential  Check plonk-core against `bb verify` on the test-circuits
+//!                 corpus and on randomly mutated proofs
+//!
+//! The differential harness needs Barretenberg's `bb` (the version pinned in
+//! the README) and circuits built with `test-circuits/build_all.sh`. It is a
+//! separate binary so the verifier crates never depend on an external tool.
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use plonk_solana_core::key::VerificationKey;
+use plonk_solana_core::proof::CONST_PROOF_SIZE_LOG_N;
+use plonk_solana_core::test_vectors::{commitment_slots, mutate, Mutation};
+use plonk_solana_core::{verify_auto, Fr, ProofVariant};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Parser, Debug)]
+#[command(name = "xtask")]
+#[command(about = "Repository automation")]
+struct Cli {
+    #[command(subcommand)]
+    task: Task,
+}
+
+#[derive(Subcommand, Debug)]
+enum Task {
+    /// Assert plonk-core and `bb verify` agree on every built test circuit
+    Differential(DifferentialArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct DifferentialArgs {
+    /// Only check these circuits (directory names under --circuits-dir)
+    #[arg(long = "circuit")]
+    circuits: Vec<String>,
+
+    /// Directory holding the circuits, each with `target/keccak/{vk,proof,public_inputs}`
+    #[arg(long, default_value_os_t = workspace_root().join("test-circuits"))]
+    circuits_dir: PathBuf,
+
+    /// Random mutations to check per circuit
+    #[arg(long, default_value_t = 16)]
+    mutations: usize,
+
+    /// Seed for the mutations; printed on every run so failures can be replayed
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// `bb` binary; by default `$BB`, then `bb` on the `PATH`, then `~/.bb/bb`
+    #[arg(long, env = "BB")]
+    bb: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().task {
+        Task::Differential(args) => differential(args),
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace root")
+        .to_path_buf()
+}
+
+/// Artifacts of one built circuit
+struct Circuit {
+    name: String,
+    dir: PathBuf,
+    vk: Vec<u8>,
+    proof: Vec<u8>,
+    public_inputs: Vec<Fr>,
+}
+
+impl Circuit {
+    fn load(name: String, dir: PathBuf) -> Result<Self> {
+        let read = |file: &str| {
+            fs::read(dir.join(file)).with_context(|| format!("reading {}/{}", name, file))
+        };
+        let vk = read("vk")?;
+        let proof = read("proof")?;
+        let pi_bytes = read("public_inputs")?;
+        if pi_bytes.len() % 32 != 0 {
+            bail!("{}: public_inputs is not a multiple of 32 bytes", name);
+        }
+        let public_inputs = pi_bytes
+            .chunks_exact(32)
+            .map(|c| c.try_into().expect("32-byte chunk"))
+            .collect();
+        Ok(Self {
+            name,
+            dir,
+            vk,
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+fn differential(args: DifferentialArgs) -> Result<()> {
+    let bb = args.bb.clone().unwrap_or_else(default_bb);
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    let mut rng = SplitMix64(seed);
+
+    let circuits = discover(&args)?;
+    if circuits.is_empty() {
+        bail!(
+            "no built circuits under {} (run test-circuits/build_all.sh)",
+            args.circuits_dir.display()
+        );
+    }
+
+    let out_dir = workspace_root().join("target/differential");
+    fs::create_dir_all(&out_dir).context("creating target/differential")?;
+
+    println!("bb:   {}", bb.display());
+    println!("seed: {}", seed);
+    println!();
+
+    let mut cases = 0;
+    let mut failures = Vec::new();
+    for circuit in &circuits {
+        let vk = VerificationKey::from_bytes(&circuit.vk)
+            .map_err(|e| anyhow::anyhow!("{}: invalid VK: {:?}", circuit.name, e))?;
+        let log_n = vk.log2_circuit_size as usize;
+        let num_commitments = commitment_slots(&circuit.proof)
+            .map_err(|e| anyhow::anyhow!("{}: {}", circuit.name, e))?
+            .len();
+
+        let mut candidates = vec![(None, circuit.proof.clone())];
+        for _ in 0..args.mutations {
+            let mutation = match rng.below(3) {
+                0 => Mutation::FlipByte(rng.below(circuit.proof.len())),
+                1 => Mutation::CorruptCommitment(rng.below(num_commitments)),
+                // Later rounds are zero padding, so zeroing them is a no-op
+                _ => Mutation::ZeroSumcheckUnivariate(rng.below(log_n.min(CONST_PROOF_SIZE_LOG_N))),
+            };
+            let proof = mutate(&circuit.proof, mutation)
+                .map_err(|e| anyhow::anyhow!("{}: {:?}: {}", circuit.name, mutation, e))?;
+            candidates.push((Some(mutation), proof));
+        }
+
+        let mut agreed = 0;
+        for (i, (mutation, proof)) in candidates.iter().enumerate() {
+            cases += 1;
+            let path = out_dir.join(format!("{}-{}.proof", circuit.name, i));
+            fs::write(&path, proof).with_context(|| format!("writing {}", path.display()))?;
+
+            let ours = verify_auto(&circuit.vk, proof, &circuit.public_inputs).is_ok();
+            let theirs = bb_verify(&bb, circuit, &path)?;
+            let label = match mutation {
+                Some(m) => format!("{:?}", m),
+                None => "unmodified".to_string(),
+            };
+
+            if ours == theirs {
+                agreed += 1;
+                fs::remove_file(&path).ok();
+            } else {
+                // Keep the proof so the case can be replayed by hand
+                failures.push(format!(
+                    "{} {}: plonk-core {}, bb {} (proof kept at {})",
+                    circuit.name,
+                    label,
+                    verdict(ours),
+                    verdict(theirs),
+                    path.display()
+                ));
+            }
+            if mutation.is_none() && !ours && !theirs {
+                failures.push(format!(
+                    "{} unmodified: both reject the built proof; rebuild the circuit",
+                    circuit.name
+                ));
+            }
+        }
+        println!(
+            "  {:<25} {}/{} agree",
+            circuit.name,
+            agreed,
+            candidates.len()
+        );
+    }
+
+    println!();
+    if failures.is_empty() {
+        println!("✅ {} cases, plonk-core and bb agree", cases);
+        return Ok(());
+    }
+    for d in &failures {
+        println!("  ❌ {}", d);
+    }
+    bail!(
+        "{} failure(s) in {} cases (replay with --seed {})",
+        failures.len(),
+        cases,
+        seed
+    )
+}
+
+/// Built circuits under `--circuits-dir`, sorted by name
+fn discover(args: &DifferentialArgs) -> Result<Vec<Circuit>> {
+    let mut names: Vec<String> = if args.circuits.is_empty() {
+        fs::read_dir(&args.circuits_dir)
+            .with_context(|| format!("reading {}", args.circuits_dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("Nargo.toml").is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect()
+    } else {
+        args.circuits.clone()
+    };
+    names.sort();
+
+    let mut circuits = Vec::new();
+    for name in names {
+        let dir = args.circuits_dir.join(&name).join("target/keccak");
+        if !dir.join("proof").is_file() {
+            if args.circuits.is_empty() {
+                println!("  {:<25} skipped (not built)", name);
+                continue;
+            }
+            bail!("{} is not built ({} has no proof)", name, dir.display());
+        }
+        circuits.push(Circuit::load(name, dir)?);
+    }
+    Ok(circuits)
+}
+
+/// Run `bb verify` on the circuit's VK and public inputs with `proof`
+///
+/// bb reports an invalid proof through its exit status, the same way it
+/// reports most other failures, so only a failure to start it is an error.
+fn bb_verify(bb: &Path, circuit: &Circuit, proof: &Path) -> Result<bool> {
+    let mut command = Command::new(bb);
+    command
+        .arg("verify")
+        .arg("-p")
+        .arg(proof)
+        .arg("-k")
+        .arg(circuit.dir.join("vk"))
+        .arg("-i")
+        .arg(circuit.dir.join("public_inputs"))
+        .args(["--oracle_hash", "keccak"]);
+    if ProofVariant::detect(&circuit.proof).is_ok_and(|v| v.is_zk()) {
+        command.arg("--zk");
+    }
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run {}", bb.display()))?;
+    Ok(output.status.success())
+}
+
+fn verdict(accepted: bool) -> &'static str {
+    if accepted {
+        "accepts"
+    } else {
+        "rejects"
+    }
+}
+
+/// `bb` on the `PATH`, else the installer's `~/.bb/bb`
+fn default_bb() -> PathBuf {
+    let on_path = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join("bb").is_file()))
+        .unwrap_or(false);
+    match std::env::var_os("HOME") {
+        Some(home) if !on_path && Path::new(&home).join(".bb/bb").is_file() => {
+            Path::new(&home).join(".bb/bb")
+        }
+        _ => PathBuf::from("bb"),
+    }
+}
+
+/// Small deterministic generator so a seed replays the same mutations
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}